@@ -12,8 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::Mutex;
 
+use itertools::Itertools as _;
 use jj_cli::cli_util::CliRunner;
 use jj_cli::commit_templater::CommitTemplateBuildFnTable;
 use jj_cli::commit_templater::CommitTemplateLanguageExtension;
@@ -25,9 +28,12 @@ use jj_lib::commit::Commit;
 use jj_lib::extensions_map::ExtensionsMap;
 use jj_lib::object_id::ObjectId as _;
 use jj_lib::repo::Repo;
+use jj_lib::revset;
+use jj_lib::revset::BoxedRevsetFunction;
 use jj_lib::revset::FunctionCallNode;
 use jj_lib::revset::LoweringContext;
 use jj_lib::revset::PartialSymbolResolver;
+use jj_lib::revset::RevsetCommitsExtension;
 use jj_lib::revset::RevsetDiagnostics;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetFilterExtension;
@@ -189,10 +195,64 @@ fn even_digits(
     )))
 }
 
+/// Commits listed in a file, e.g. the commits which have been reviewed
+/// according to a code review tool. The list is read once per file.
+#[derive(Debug)]
+struct CommitsListedInFile {
+    path: String,
+    cache: Arc<Mutex<HashMap<String, Vec<CommitId>>>>,
+}
+
+impl RevsetCommitsExtension for CommitsListedInFile {
+    fn resolve(&self, repo: &dyn Repo) -> Result<Vec<CommitId>, RevsetResolutionError> {
+        let mut cache = self.cache.lock().unwrap();
+        if !cache.contains_key(&self.path) {
+            let text = std::fs::read_to_string(&self.path)
+                .map_err(|err| RevsetResolutionError::Other(err.into()))?;
+            let ids: Vec<_> = text
+                .split_whitespace()
+                .map(|hex| {
+                    CommitId::try_from_hex(hex).ok_or_else(|| {
+                        RevsetResolutionError::Other(format!("Invalid commit ID: {hex}").into())
+                    })
+                })
+                .try_collect()?;
+            cache.insert(self.path.clone(), ids);
+        }
+        // The list may mention commits which haven't been fetched yet
+        let mut commit_ids = vec![];
+        for id in &cache[&self.path] {
+            if repo
+                .index()
+                .has_id(id)
+                .map_err(|err| RevsetResolutionError::Other(err.into()))?
+            {
+                commit_ids.push(id.clone());
+            }
+        }
+        Ok(commit_ids)
+    }
+}
+
+fn listed_in() -> BoxedRevsetFunction {
+    let cache = Arc::new(Mutex::new(HashMap::new()));
+    Box::new(move |_diagnostics, function, _context| {
+        let [path_arg] = function.expect_exact_arguments()?;
+        let path = revset::expect_literal("string", path_arg)?;
+        Ok(RevsetExpression::extension_commits(Arc::new(
+            CommitsListedInFile {
+                path,
+                cache: cache.clone(),
+            },
+        )))
+    })
+}
+
 fn main() -> std::process::ExitCode {
     CliRunner::init()
         .add_symbol_resolver_extension(Box::new(TheDigitest))
         .add_revset_function_extension("even_digits", even_digits)
+        .add_boxed_revset_function_extension("listed_in", listed_in())
         .add_commit_template_extension(Box::new(HexCounter))
         .run()
         .into()
@@ -36,12 +36,14 @@ use jj_lib::settings::UserSettings;
 use jj_lib::signing::Signer;
 use jj_lib::store::Store;
 use jj_lib::working_copy::CheckoutError;
+use jj_lib::working_copy::CheckoutOptions;
 use jj_lib::working_copy::CheckoutStats;
 use jj_lib::working_copy::LockedWorkingCopy;
 use jj_lib::working_copy::ResetError;
 use jj_lib::working_copy::SnapshotError;
 use jj_lib::working_copy::SnapshotOptions;
 use jj_lib::working_copy::SnapshotStats;
+use jj_lib::working_copy::SparsePatterns;
 use jj_lib::working_copy::WorkingCopy;
 use jj_lib::working_copy::WorkingCopyFactory;
 use jj_lib::working_copy::WorkingCopyStateError;
@@ -170,10 +172,14 @@ impl WorkingCopy for ConflictsWorkingCopy {
         self.inner.tree()
     }
 
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError> {
+    fn sparse_patterns(&self) -> Result<&SparsePatterns, WorkingCopyStateError> {
         self.inner.sparse_patterns()
     }
 
+    fn force_tracked_paths(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError> {
+        self.inner.force_tracked_paths()
+    }
+
     fn start_mutation(&self) -> Result<Box<dyn LockedWorkingCopy>, WorkingCopyStateError> {
         let inner = self.inner.start_mutation()?;
         Ok(Box::new(LockedConflictsWorkingCopy {
@@ -251,14 +257,18 @@ impl LockedWorkingCopy for LockedConflictsWorkingCopy {
         self.inner.snapshot(&options).await
     }
 
-    async fn check_out(&mut self, commit: &Commit) -> Result<CheckoutStats, CheckoutError> {
+    async fn check_out(
+        &mut self,
+        commit: &Commit,
+        options: &CheckoutOptions,
+    ) -> Result<CheckoutStats, CheckoutError> {
         let conflicts = commit
             .tree()
             .conflicts()
             .map(|(path, _value)| format!("{}\n", path.as_internal_file_string()))
             .join("");
         std::fs::write(self.wc_path.join(".conflicts"), conflicts).unwrap();
-        self.inner.check_out(commit).await
+        self.inner.check_out(commit, options).await
     }
 
     fn rename_workspace(&mut self, new_name: WorkspaceNameBuf) {
@@ -273,17 +283,28 @@ impl LockedWorkingCopy for LockedConflictsWorkingCopy {
         self.inner.recover(commit).await
     }
 
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError> {
+    fn sparse_patterns(&self) -> Result<&SparsePatterns, WorkingCopyStateError> {
         self.inner.sparse_patterns()
     }
 
     async fn set_sparse_patterns(
         &mut self,
-        new_sparse_patterns: Vec<RepoPathBuf>,
+        new_sparse_patterns: SparsePatterns,
     ) -> Result<CheckoutStats, CheckoutError> {
         self.inner.set_sparse_patterns(new_sparse_patterns).await
     }
 
+    fn force_tracked_paths(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError> {
+        self.inner.force_tracked_paths()
+    }
+
+    fn set_force_tracked_paths(
+        &mut self,
+        paths: Vec<RepoPathBuf>,
+    ) -> Result<(), WorkingCopyStateError> {
+        self.inner.set_force_tracked_paths(paths)
+    }
+
     async fn finish(
         self: Box<Self>,
         operation_id: OperationId,
@@ -63,7 +63,9 @@ use jj_lib::config::ConfigNamePathBuf;
 use jj_lib::config::ConfigSource;
 use jj_lib::config::StackedConfig;
 use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::default_index::DefaultReadonlyIndex;
 use jj_lib::fileset;
+use jj_lib::fileset::BoxedFilesetFunction;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
 use jj_lib::gitignore::GitIgnoreError;
@@ -83,6 +85,7 @@ use jj_lib::op_store::RefTarget;
 use jj_lib::op_walk;
 use jj_lib::op_walk::OpsetEvaluationError;
 use jj_lib::operation::Operation;
+use jj_lib::plugin_backend::PluginBackendError;
 use jj_lib::ref_name::RefName;
 use jj_lib::ref_name::RefNameBuf;
 use jj_lib::ref_name::RemoteName;
@@ -103,6 +106,7 @@ use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::repo_path::RepoPathUiConverter;
 use jj_lib::repo_path::UiPathParseError;
 use jj_lib::revset;
+use jj_lib::revset::BoxedRevsetFunction;
 use jj_lib::revset::ResolvedRevsetExpression;
 use jj_lib::revset::RevsetAliasesMap;
 use jj_lib::revset::RevsetDiagnostics;
@@ -118,12 +122,15 @@ use jj_lib::revset::UserRevsetExpression;
 use jj_lib::rewrite::restore_tree;
 use jj_lib::settings::HumanByteSize;
 use jj_lib::settings::UserSettings;
+use jj_lib::simple_backend::SimpleBackendEncryptionError;
 use jj_lib::store::Store;
 use jj_lib::str_util::StringExpression;
 use jj_lib::str_util::StringMatcher;
 use jj_lib::str_util::StringPattern;
 use jj_lib::transaction::Transaction;
 use jj_lib::working_copy;
+use jj_lib::working_copy::CheckoutError;
+use jj_lib::working_copy::CheckoutOptions;
 use jj_lib::working_copy::CheckoutStats;
 use jj_lib::working_copy::LockedWorkingCopy;
 use jj_lib::working_copy::SnapshotOptions;
@@ -178,6 +185,8 @@ use crate::merge_tools::MergeEditor;
 use crate::merge_tools::MergeToolConfigError;
 use crate::operation_templater::OperationTemplateLanguage;
 use crate::operation_templater::OperationTemplateLanguageExtension;
+use crate::profile::ProfileHandle;
+use crate::profile::ProfileLayer;
 use crate::revset_util;
 use crate::revset_util::RevsetExpressionEvaluator;
 use crate::revset_util::parse_union_name_patterns;
@@ -189,6 +198,7 @@ use crate::templater::TemplateRenderer;
 use crate::templater::WrapTemplateProperty;
 use crate::text_util;
 use crate::ui::ColorChoice;
+use crate::ui::ProgressChoice;
 use crate::ui::Ui;
 
 const SHORT_CHANGE_ID_TEMPLATE_TEXT: &str = "format_short_change_id_with_change_offset(self)";
@@ -214,6 +224,7 @@ pub struct TracingSubscription {
         tracing_subscriber::Registry,
     >,
     _chrome_tracing_flush_guard: ChromeTracingFlushGuard,
+    profile: ProfileHandle,
 }
 
 impl TracingSubscription {
@@ -256,6 +267,22 @@ impl TracingSubscription {
             Err(_) => (None, ChromeTracingFlushGuard { _inner: None }),
         };
 
+        let profile_layer = ProfileLayer::new();
+        let profile = profile_layer.handle();
+        let profile_filter = {
+            let profile = profile.clone();
+            tracing_subscriber::filter::DynFilterFn::new(move |_, _| profile.is_enabled())
+                // Events aren't recorded, and spans are only created while
+                // profiling.
+                .with_callsite_filter(|metadata| {
+                    if metadata.is_span() {
+                        tracing::subscriber::Interest::sometimes()
+                    } else {
+                        tracing::subscriber::Interest::never()
+                    }
+                })
+        };
+
         tracing_subscriber::registry()
             .with(
                 tracing_subscriber::fmt::Layer::default()
@@ -263,13 +290,30 @@ impl TracingSubscription {
                     .with_filter(filter),
             )
             .with(chrome_tracing_layer)
+            .with(profile_layer.with_filter(profile_filter))
             .init();
         Self {
             reload_log_filter,
             _chrome_tracing_flush_guard: chrome_tracing_flush_guard,
+            profile,
         }
     }
 
+    /// Starts recording the time spent in each span for `--profile`.
+    pub fn start_profiling(&self) {
+        self.profile.start();
+    }
+
+    /// Writes the profile recorded since `start_profiling()` to `path`, and
+    /// prints the spans that took the most time.
+    pub fn finish_profiling(&self, ui: &Ui, path: &Path) -> Result<(), CommandError> {
+        self.profile
+            .finish(path, &mut ui.status())
+            .map_err(|err| user_error_with_message("Failed to write profile", err))?;
+        writeln!(ui.status(), "Wrote profile to {}", path.display())?;
+        Ok(())
+    }
+
     pub fn enable_debug_logging(&self) -> Result<(), CommandError> {
         self.reload_log_filter
             .modify(|filter| {
@@ -350,6 +394,11 @@ impl CommandHelper {
         &self.data.raw_config
     }
 
+    /// Rules to migrate deprecated config variables.
+    pub fn config_migrations(&self) -> &[ConfigMigrationRule] {
+        &self.data.config_migrations
+    }
+
     /// Settings for the current command and workspace.
     ///
     /// This may be different from the settings for new workspace created by
@@ -372,6 +421,8 @@ impl CommandHelper {
         config_env.reload_repo_config(ui, &mut raw_config)?;
         config_env.reset_workspace_path(workspace_root);
         config_env.reload_workspace_config(ui, &mut raw_config)?;
+        // Drop the repo-managed config of the cwd workspace if any.
+        config_env.reload_repo_managed_config(ui, &mut raw_config)?;
         let mut config = config_env.resolve_config(&raw_config)?;
         // No migration messages here, which would usually be emitted before.
         jj_lib::config::migrate(&mut config, &self.data.config_migrations)?;
@@ -599,6 +650,7 @@ impl CommandHelper {
                     WorkingCopyFreshness::WorkingCopyStale
                     | WorkingCopyFreshness::SiblingOperation => {
                         let stats = update_stale_working_copy(
+                            ui,
                             locked_ws,
                             repo.op_id().clone(),
                             &stale_wc_commit,
@@ -1061,6 +1113,43 @@ impl SnapshotWorkingCopyError {
     }
 }
 
+/// Rebases the descendants of the commits rewritten in `mut_repo`, or
+/// defers that according to the configured policy. Deferred rebases are
+/// done on checkout. Returns the number of rebased commits.
+fn rebase_descendants_with_policy(
+    ui: &Ui,
+    mut_repo: &mut MutableRepo,
+    policy: DescendantRebasePolicy,
+    is_checkout: bool,
+) -> Result<usize, CommandError> {
+    if policy == DescendantRebasePolicy::Eager
+        || (policy == DescendantRebasePolicy::Lazy && is_checkout)
+    {
+        return Ok(mut_repo.rebase_deferred_descendants()?);
+    }
+    let (num_rebased, deferred_ids) = mut_repo.defer_rebase_descendants()?;
+    if deferred_ids.is_empty() {
+        return Ok(num_rebased);
+    }
+    if policy == DescendantRebasePolicy::Never {
+        for old_id in &deferred_ids {
+            mut_repo.remove_deferred_rewrite(old_id);
+        }
+        writeln!(
+            ui.status(),
+            "Did not rebase the descendants of {} rewritten commits",
+            deferred_ids.len()
+        )?;
+    } else {
+        writeln!(
+            ui.status(),
+            "The descendants of {} rewritten commits will be rebased on the next checkout or push",
+            deferred_ids.len()
+        )?;
+    }
+    Ok(num_rebased)
+}
+
 fn snapshot_command_error<E>(err: E) -> SnapshotWorkingCopyError
 where
     E: Into<CommandError>,
@@ -1228,6 +1317,8 @@ impl WorkspaceCommandHelper {
         }
 
         let mut tx = tx.into_inner();
+        // Git has already moved the bookmarks.
+        tx.allow_protected_bookmarks();
         let old_git_head = self.repo().view().git_head().clone();
         let new_git_head = tx.repo().view().git_head().clone();
         if let Some(new_git_head_id) = new_git_head.as_normal() {
@@ -1287,6 +1378,9 @@ impl WorkspaceCommandHelper {
         }
 
         let mut tx = tx.into_inner();
+        // Git has already moved the bookmarks, so refusing to import them
+        // would only leave the repos out of sync.
+        tx.allow_protected_bookmarks();
         // Rebase here to show slightly different status message.
         let num_rebased = tx.repo_mut().rebase_descendants()?;
         if num_rebased > 0 {
@@ -1426,16 +1520,54 @@ to the current parents may contain changes from multiple commits.
         }
     }
 
+    /// Parses the given strings as file patterns. Predicates on file
+    /// properties, such as `size()`, are evaluated against the `trees`.
+    pub fn parse_file_patterns_in_trees(
+        &self,
+        ui: &Ui,
+        values: &[String],
+        trees: &[&MergedTree],
+    ) -> Result<FilesetExpression, CommandError> {
+        if values.is_empty() {
+            return Ok(FilesetExpression::all());
+        }
+        let expression = self.parse_union_filesets_with_predicates(ui, values)?;
+        Ok(expression.resolve_predicates(trees).block_on()?)
+    }
+
     /// Parses the given fileset expressions and concatenates them all.
     pub fn parse_union_filesets(
         &self,
         ui: &Ui,
         file_args: &[String], // TODO: introduce FileArg newtype?
+    ) -> Result<FilesetExpression, CommandError> {
+        let expression = self.parse_union_filesets_with_predicates(ui, file_args)?;
+        if expression.has_predicates() {
+            return Err(user_error_with_hint(
+                "Predicates on file properties aren't supported by this command",
+                "Use functions such as `size()` with `jj diff`, `jj file list`, or `jj split` \
+                 instead.",
+            ));
+        }
+        Ok(expression)
+    }
+
+    fn parse_union_filesets_with_predicates(
+        &self,
+        ui: &Ui,
+        file_args: &[String],
     ) -> Result<FilesetExpression, CommandError> {
         let mut diagnostics = FilesetDiagnostics::new();
         let expressions: Vec<_> = file_args
             .iter()
-            .map(|arg| fileset::parse_maybe_bare(&mut diagnostics, arg, self.path_converter()))
+            .map(|arg| {
+                fileset::parse_maybe_bare(
+                    &mut diagnostics,
+                    arg,
+                    self.path_converter(),
+                    self.env.command.revset_extensions().fileset(),
+                )
+            })
             .try_collect()?;
         print_parse_diagnostics(ui, "In fileset expression", &diagnostics)?;
         Ok(FilesetExpression::union_all(expressions))
@@ -1451,6 +1583,7 @@ to the current parents may contain changes from multiple commits.
                 cwd: "".into(),
                 base: "".into(),
             },
+            self.env.command.revset_extensions().fileset(),
         )?;
         print_parse_diagnostics(ui, "In `snapshot.auto-track`", &diagnostics)?;
         Ok(expression.to_matcher())
@@ -1911,6 +2044,9 @@ to the current parents may contain changes from multiple commits.
         let options = self
             .snapshot_options_with_start_tracking_matcher(&auto_tracking_matcher)
             .map_err(snapshot_command_error)?;
+        let rebase_policy = self
+            .descendant_rebase_policy()
+            .map_err(snapshot_command_error)?;
 
         // Compare working-copy tree and operation with repo's, and reload as needed.
         let mut locked_ws = self
@@ -1929,7 +2065,7 @@ to the current parents may contain changes from multiple commits.
         self.user_repo = ReadonlyUserRepo::new(repo);
         let (new_tree, stats) = {
             let mut options = options;
-            let progress = crate::progress::snapshot_progress(ui);
+            let progress = crate::progress::progress_reporter(ui);
             options.progress = progress.as_ref().map(|x| x as _);
             locked_ws
                 .locked_wc()
@@ -1941,6 +2077,9 @@ to the current parents may contain changes from multiple commits.
             let mut tx =
                 start_repo_transaction(&self.user_repo.repo, self.env.command.string_args());
             tx.set_is_snapshot(true);
+            if self.env.command.global_args().allow_protected {
+                tx.allow_protected_bookmarks();
+            }
             let mut_repo = tx.repo_mut();
             let commit = mut_repo
                 .rewrite_commit(&wc_commit)
@@ -1952,8 +2091,7 @@ to the current parents may contain changes from multiple commits.
                 .map_err(snapshot_command_error)?;
 
             // Rebase descendants
-            let num_rebased = mut_repo
-                .rebase_descendants()
+            let num_rebased = rebase_descendants_with_policy(ui, mut_repo, rebase_policy, false)
                 .map_err(snapshot_command_error)?;
             if num_rebased > 0 {
                 writeln!(
@@ -1990,12 +2128,24 @@ to the current parents may contain changes from multiple commits.
     ) -> Result<(), CommandError> {
         assert!(self.may_update_working_copy);
         let stats = update_working_copy(
+            ui,
             &self.user_repo.repo,
             &mut self.workspace,
             maybe_old_commit,
             new_commit,
         )?;
-        self.print_updated_working_copy_stats(ui, maybe_old_commit, new_commit, &stats)
+        self.print_updated_working_copy_stats(ui, maybe_old_commit, new_commit, &stats)?;
+        #[cfg(feature = "git")]
+        if self.settings().get_bool("git.update-submodules")? {
+            crate::git_util::update_cloned_submodules(
+                ui,
+                self.settings(),
+                self.workspace_root(),
+                self.path_converter(),
+                new_commit,
+            )?;
+        }
+        Ok(())
     }
 
     fn print_updated_working_copy_stats(
@@ -2035,8 +2185,33 @@ to the current parents may contain changes from multiple commits.
         Ok(())
     }
 
+    fn descendant_rebase_policy(&self) -> Result<DescendantRebasePolicy, CommandError> {
+        if let Some(policy) = self.env.command.global_args().rebase_descendants {
+            Ok(policy)
+        } else {
+            Ok(self.settings().get("rewrite.rebase-descendants")?)
+        }
+    }
+
+    /// Rebases the descendants whose rebase was deferred by the
+    /// `rewrite.rebase-descendants = "lazy"` policy.
+    pub fn rebase_deferred_descendants(&mut self, ui: &Ui) -> Result<(), CommandError> {
+        if self.repo().view().deferred_rewrites().is_empty() {
+            return Ok(());
+        }
+        let mut tx = self.start_transaction();
+        let num_rebased = tx.repo_mut().rebase_deferred_descendants()?;
+        if num_rebased > 0 {
+            writeln!(ui.status(), "Rebased {num_rebased} descendant commits")?;
+        }
+        tx.finish(ui, "rebase deferred descendants")
+    }
+
     pub fn start_transaction(&mut self) -> WorkspaceCommandTransaction<'_> {
-        let tx = start_repo_transaction(self.repo(), self.env.command.string_args());
+        let mut tx = start_repo_transaction(self.repo(), self.env.command.string_args());
+        if self.env.command.global_args().allow_protected {
+            tx.allow_protected_bookmarks();
+        }
         let id_prefix_context = mem::take(&mut self.user_repo.id_prefix_context);
         WorkspaceCommandTransaction {
             helper: self,
@@ -2052,7 +2227,22 @@ to the current parents may contain changes from multiple commits.
         description: impl Into<String>,
         _git_import_export_lock: &GitImportExportLock,
     ) -> Result<(), CommandError> {
-        let num_rebased = tx.repo_mut().rebase_descendants()?;
+        let is_checkout = {
+            let old_wc_commit_id = tx
+                .base_repo()
+                .view()
+                .get_wc_commit_id(self.workspace_name());
+            let new_wc_commit_id = tx.repo().view().get_wc_commit_id(self.workspace_name());
+            match (old_wc_commit_id, new_wc_commit_id) {
+                (Some(old_id), Some(new_id)) if old_id != new_id => {
+                    let store = tx.repo().store();
+                    store.get_commit(old_id)?.change_id() != store.get_commit(new_id)?.change_id()
+                }
+                _ => false,
+            }
+        };
+        let policy = self.descendant_rebase_policy()?;
+        let num_rebased = rebase_descendants_with_policy(ui, tx.repo_mut(), policy, is_checkout)?;
         if num_rebased > 0 {
             writeln!(ui.status(), "Rebased {num_rebased} descendant commits")?;
         }
@@ -2138,6 +2328,7 @@ to the current parents may contain changes from multiple commits.
             crate::git_util::print_git_export_stats(ui, &stats)?;
         }
 
+        let auto_resolved_paths = tx.repo().auto_resolved_paths().clone();
         self.user_repo = ReadonlyUserRepo::new(tx.commit(description)?);
 
         // Update working copy before reporting repo changes, so that
@@ -2152,7 +2343,9 @@ to the current parents may contain changes from multiple commits.
             }
         }
 
+        self.report_auto_resolved_conflicts(ui, &auto_resolved_paths)?;
         self.report_repo_changes(ui, &old_repo)?;
+        self.maybe_compact_index_in_background(ui)?;
 
         let settings = self.settings();
         let missing_user_name = settings.user_name().is_empty();
@@ -2186,6 +2379,79 @@ to the current parents may contain changes from multiple commits.
         Ok(())
     }
 
+    /// Spawns `jj debug compact-index` if incremental updates left the commit
+    /// index fragmented.
+    ///
+    /// The child process isn't waited for. Compaction writes new segment files
+    /// and then atomically links them to the operation, so concurrent commands
+    /// keep using the old segments in the meantime.
+    fn maybe_compact_index_in_background(&self, ui: &Ui) -> Result<(), CommandError> {
+        if !self.settings().get_bool("gc.auto-compact-index")? {
+            return Ok(());
+        }
+        let index = self.repo().readonly_index();
+        let Some(index) = index.downcast_ref::<DefaultReadonlyIndex>() else {
+            return Ok(());
+        };
+        if !index.needs_compaction() {
+            return Ok(());
+        }
+        let spawned = env::current_exe().and_then(|exe| {
+            std::process::Command::new(exe)
+                .arg("--repository")
+                .arg(self.workspace_root())
+                .args(["--ignore-working-copy", "--at-operation"])
+                .arg(self.repo().op_id().hex())
+                .args(["debug", "compact-index"])
+                .stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .spawn()
+        });
+        if let Err(err) = spawned {
+            writeln!(
+                ui.warning_default(),
+                "Failed to start background index compaction: {err}"
+            )?;
+            writeln!(
+                ui.hint_default(),
+                "Run `jj util gc` to compact the index manually."
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Inform the user about conflicts that were resolved using recorded
+    /// resolutions.
+    fn report_auto_resolved_conflicts(
+        &self,
+        ui: &Ui,
+        auto_resolved_paths: &BTreeMap<CommitId, Vec<RepoPathBuf>>,
+    ) -> Result<(), CommandError> {
+        if auto_resolved_paths.is_empty() {
+            return Ok(());
+        }
+        let Some(mut fmt) = ui.status_formatter() else {
+            return Ok(());
+        };
+        writeln!(
+            fmt,
+            "Resolved conflicts using recorded resolutions in {} commits:",
+            auto_resolved_paths.len()
+        )?;
+        let template = self.commit_summary_template();
+        for (commit_id, paths) in auto_resolved_paths {
+            let commit = self.repo().store().get_commit(commit_id)?;
+            write!(fmt, "  ")?;
+            template.format(&commit, fmt.as_mut())?;
+            writeln!(fmt)?;
+            for path in paths {
+                writeln!(fmt, "    {}", self.format_file_path(path))?;
+            }
+        }
+        Ok(())
+    }
+
     /// Inform the user about important changes to the repo since the previous
     /// operation (when `old_repo` was loaded).
     fn report_repo_changes(
@@ -2457,6 +2723,20 @@ impl WorkspaceCommandTransaction<'_> {
         self.tx.repo_mut()
     }
 
+    /// Whether the descendants of rewritten commits should be rebased right
+    /// away, according to the `rewrite.rebase-descendants` policy.
+    pub fn rebases_descendants_eagerly(&self) -> Result<bool, CommandError> {
+        Ok(self.helper.descendant_rebase_policy()? == DescendantRebasePolicy::Eager)
+    }
+
+    /// Rebases the descendants of the rewritten commits, or defers that
+    /// according to the `rewrite.rebase-descendants` policy. Returns the
+    /// number of rebased commits.
+    pub fn rebase_descendants(&mut self, ui: &Ui) -> Result<usize, CommandError> {
+        let policy = self.helper.descendant_rebase_policy()?;
+        rebase_descendants_with_policy(ui, self.tx.repo_mut(), policy, false)
+    }
+
     pub fn check_out(&mut self, commit: &Commit) -> Result<Commit, CheckOutCommitError> {
         let name = self.helper.workspace_name().to_owned();
         self.id_prefix_context.take(); // invalidate
@@ -2511,6 +2791,20 @@ impl WorkspaceCommandTransaction<'_> {
         self.helper.env.parse_template(ui, &language, template_text)
     }
 
+    /// Lets the transaction move protected bookmarks backwards or sideways,
+    /// or delete them. Used when the bookmarks follow a remote or Git, which
+    /// the user doesn't control from here.
+    pub fn allow_protected_bookmarks(&mut self) {
+        self.tx.allow_protected_bookmarks();
+    }
+
+    /// Fails if the transaction moves protected bookmarks backwards or
+    /// sideways, or deletes them. Committing the transaction would fail too,
+    /// but checking first avoids reporting changes that won't be made.
+    pub fn check_protected_bookmarks(&self) -> Result<(), CommandError> {
+        Ok(self.tx.check_protected_bookmarks()?)
+    }
+
     pub fn finish(self, ui: &Ui, description: impl Into<String>) -> Result<(), CommandError> {
         if !self.tx.repo().has_changes() {
             writeln!(ui.status(), "Nothing changed.")?;
@@ -2588,6 +2882,11 @@ jj git init",
                 err,
             )
         }
+        WorkspaceLoadError::StoreLoadError(StoreLoadError::Backend(err))
+            if err.0.is::<SimpleBackendEncryptionError>() || err.0.is::<PluginBackendError>() =>
+        {
+            user_error(err)
+        }
         WorkspaceLoadError::StoreLoadError(
             err @ (StoreLoadError::ReadError { .. } | StoreLoadError::Backend(_)),
         ) => internal_error_with_message("The repository appears broken or inaccessible", err),
@@ -2692,6 +2991,7 @@ See https://docs.jj-vcs.dev/latest/working-copy/#stale-working-copy \
 }
 
 fn update_stale_working_copy(
+    ui: &Ui,
     mut locked_ws: LockedWorkspace,
     op_id: OperationId,
     stale_commit: &Commit,
@@ -2704,12 +3004,16 @@ fn update_stale_working_copy(
     {
         return Err(user_error("Concurrent working copy operation. Try again."));
     }
+    let progress = crate::progress::progress_reporter(ui);
+    let options = CheckoutOptions {
+        progress: progress.as_ref().map(|x| x as _),
+    };
     let stats = locked_ws
         .locked_wc()
-        .check_out(new_commit)
+        .check_out(new_commit, &options)
         .block_on()
         .map_err(|err| {
-            internal_error_with_message(
+            checkout_error_with_message(
                 format!("Failed to check out commit {}", new_commit.id().hex()),
                 err,
             )
@@ -2844,6 +3148,10 @@ fn build_untracked_reason_message(reason: &UntrackedReason) -> Option<String> {
                  ({max_size} bytes)",
             ))
         }
+        UntrackedReason::CaseCollision { other_path } => Some(format!(
+            "differs only in case from {}",
+            other_path.as_internal_file_string()
+        )),
         // Paths with UntrackedReason::FileNotAutoTracked shouldn't be warned about
         // every time we make a snapshot. These paths will be printed by
         // "jj status" instead.
@@ -2886,7 +3194,7 @@ pub fn print_snapshot_stats(
         .values()
         .filter_map(|reason| match reason {
             UntrackedReason::FileTooLarge { size, .. } => Some(size),
-            UntrackedReason::FileNotAutoTracked => None,
+            UntrackedReason::FileNotAutoTracked | UntrackedReason::CaseCollision { .. } => None,
         });
     if let Some(size) = large_files_sizes.max() {
         writedoc!(
@@ -2933,6 +3241,52 @@ Discard the conflicting changes with `jj restore --from {}`.",
             short_commit_hash(new_commit.id())
         )?;
     }
+    let (renamed_collisions, skipped_collisions): (Vec<_>, Vec<_>) = stats
+        .case_collisions
+        .iter()
+        .partition(|collision| collision.checked_out_as.is_some());
+    if !skipped_collisions.is_empty() {
+        writeln!(
+            ui.warning_default(),
+            "Skipped checking out paths that differ only in case from other paths:"
+        )?;
+        for collision in &skipped_collisions {
+            writeln!(
+                ui.warning_no_heading(),
+                "  {} (collides with {})",
+                collision.path.as_internal_file_string(),
+                collision.other_path.as_internal_file_string()
+            )?;
+        }
+        writeln!(
+            ui.hint_default(),
+            "The skipped paths are left unchanged in the working-copy commit. Rename them to \
+             check them out."
+        )?;
+    }
+    if !renamed_collisions.is_empty() {
+        writeln!(
+            ui.warning_default(),
+            "Checked out paths that differ only in case from other paths under other names:"
+        )?;
+        for collision in &renamed_collisions {
+            writeln!(
+                ui.warning_no_heading(),
+                "  {} as {} (collides with {})",
+                collision.path.as_internal_file_string(),
+                collision
+                    .checked_out_as
+                    .as_ref()
+                    .unwrap()
+                    .as_internal_file_string(),
+                collision.other_path.as_internal_file_string()
+            )?;
+        }
+        writeln!(
+            ui.hint_default(),
+            "Changes to the renamed files are recorded at their original paths."
+        )?;
+    }
     Ok(())
 }
 
@@ -2965,6 +3319,7 @@ pub fn print_unmatched_explicit_paths<'a>(
 }
 
 pub fn update_working_copy(
+    ui: &Ui,
     repo: &Arc<ReadonlyRepo>,
     workspace: &mut Workspace,
     old_commit: Option<&Commit>,
@@ -2973,10 +3328,19 @@ pub fn update_working_copy(
     let old_tree = old_commit.map(|commit| commit.tree());
     // TODO: CheckoutError::ConcurrentCheckout should probably just result in a
     // warning for most commands (but be an error for the checkout command)
+    let progress = crate::progress::progress_reporter(ui);
+    let options = CheckoutOptions {
+        progress: progress.as_ref().map(|x| x as _),
+    };
     let stats = workspace
-        .check_out(repo.op_id().clone(), old_tree.as_ref(), new_commit)
+        .check_out_with_options(
+            repo.op_id().clone(),
+            old_tree.as_ref(),
+            new_commit,
+            &options,
+        )
         .map_err(|err| {
-            internal_error_with_message(
+            checkout_error_with_message(
                 format!("Failed to check out commit {}", new_commit.id().hex()),
                 err,
             )
@@ -2984,6 +3348,32 @@ pub fn update_working_copy(
     Ok(stats)
 }
 
+/// Converts a checkout error into a user error if it's caused by the contents
+/// of the commit, or into an internal error otherwise.
+pub fn checkout_error_with_message(message: impl Into<String>, err: CheckoutError) -> CommandError {
+    match &err {
+        CheckoutError::CaseCollision(collisions) => {
+            let paths = collisions
+                .iter()
+                .map(|collision| {
+                    format!(
+                        "  {} (collides with {})",
+                        collision.path.as_internal_file_string(),
+                        collision.other_path.as_internal_file_string()
+                    )
+                })
+                .join("\n");
+            user_error_with_message(message, err)
+                .hinted(format!("Colliding paths:\n{paths}"))
+                .hinted(
+                    "Rename the paths, or set `working-copy.case-collisions` to `\"skip\"` or \
+                     `\"suffix\"` to check them out anyway.",
+                )
+        }
+        _ => internal_error_with_message(message, err),
+    }
+}
+
 /// Returns the special remote name that should be ignored by default.
 #[cfg_attr(not(feature = "git"), expect(unused_variables))]
 pub fn default_ignored_remote_name(store: &Store) -> Option<&'static RemoteName> {
@@ -3319,6 +3709,18 @@ pub struct Args {
     pub global_args: GlobalArgs,
 }
 
+/// When to rebase the descendants of rewritten commits.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum DescendantRebasePolicy {
+    /// Rebase the descendants right away
+    Eager,
+    /// Rebase the descendants on the next checkout or push
+    Lazy,
+    /// Leave the descendants on the old commits
+    Never,
+}
+
 #[derive(clap::Args, Clone, Debug)]
 #[command(next_help_heading = "Global Options")]
 pub struct GlobalArgs {
@@ -3355,6 +3757,21 @@ pub struct GlobalArgs {
     #[arg(long, global = true)]
     pub ignore_immutable: bool,
 
+    /// Allow moving protected bookmarks backwards or deleting them
+    ///
+    /// By default, Jujutsu prevents moving the bookmarks matching the
+    /// `bookmarks.protect` setting to commits which aren't descendants of
+    /// their current targets, and deleting them, both locally and on push.
+    /// This option disables that check.
+    #[arg(long, global = true)]
+    pub allow_protected: bool,
+
+    /// When to rebase the descendants of rewritten commits
+    ///
+    /// Overrides the `rewrite.rebase-descendants` setting.
+    #[arg(long, value_name = "POLICY", global = true)]
+    pub rebase_descendants: Option<DescendantRebasePolicy>,
+
     /// Operation to load the repo at
     ///
     /// Operation to load the repo at. By default, Jujutsu loads the repo at the
@@ -3385,6 +3802,16 @@ pub struct GlobalArgs {
     #[arg(long, global = true)]
     pub debug: bool,
 
+    /// Write a profile of the command to the given file
+    ///
+    /// The time spent in the slowest operations is also printed. The file is
+    /// in the Chrome trace event format if its name ends with `.json`, which
+    /// can be opened in Perfetto or `chrome://tracing`. Otherwise, it's in the
+    /// folded stack format, which can be rendered as a flamegraph by tools
+    /// like `inferno-flamegraph` or `flamegraph.pl`.
+    #[arg(long, value_name = "FILE", global = true, value_hint = clap::ValueHint::FilePath)]
+    pub profile: Option<PathBuf>,
+
     #[command(flatten)]
     pub early_args: EarlyArgs,
 }
@@ -3412,6 +3839,10 @@ pub struct EarlyArgs {
     // Option<bool>.
     pub no_pager: Option<bool>,
 
+    /// How to display the progress of long-running operations
+    #[arg(long, value_name = "FORMAT", global = true)]
+    pub progress: Option<ProgressChoice>,
+
     /// Additional configuration options (can be repeated)
     ///
     /// The name should be specified as TOML dotted keys. The value should be
@@ -3652,6 +4083,20 @@ fn parse_early_args(
     if args.no_pager.unwrap_or_default() {
         layer.set_value("ui.paginate", "never").unwrap();
     }
+    match args.progress {
+        Some(ProgressChoice::Auto) => {
+            layer.set_value("ui.progress-indicator", true).unwrap();
+            layer.set_value("ui.progress-format", "text").unwrap();
+        }
+        Some(ProgressChoice::Json) => {
+            layer.set_value("ui.progress-indicator", true).unwrap();
+            layer.set_value("ui.progress-format", "json").unwrap();
+        }
+        Some(ProgressChoice::Never) => {
+            layer.set_value("ui.progress-indicator", false).unwrap();
+        }
+        None => {}
+    }
     if !layer.is_empty() {
         config_layers.push(layer);
     }
@@ -3910,6 +4355,39 @@ impl<'a> CliRunner<'a> {
         self
     }
 
+    /// Registers a revset function which may capture state, e.g. a connection
+    /// to the data source it looks up commits in.
+    ///
+    /// Panics if a function of the same name is already registered. Functions
+    /// defined in the `revset-functions` config table are registered later,
+    /// and conflicts with them are reported as config errors.
+    pub fn add_boxed_revset_function_extension(
+        mut self,
+        name: impl Into<String>,
+        func: BoxedRevsetFunction,
+    ) -> Self {
+        if let Err(err) = self.revset_extensions.add_boxed_function(name, func) {
+            panic!("{err}");
+        }
+        self
+    }
+
+    /// Registers a fileset function which may capture state. The function is
+    /// available in file arguments, and in filesets embedded in revsets and
+    /// templates.
+    ///
+    /// Panics if a function of the same name is already registered.
+    pub fn add_fileset_function_extension(
+        mut self,
+        name: impl Into<String>,
+        func: BoxedFilesetFunction,
+    ) -> Self {
+        if let Err(err) = self.revset_extensions.add_fileset_function(name, func) {
+            panic!("{err}");
+        }
+        self
+    }
+
     pub fn add_commit_template_extension(
         mut self,
         commit_template_extension: Box<dyn CommitTemplateLanguageExtension>,
@@ -4010,6 +4488,7 @@ impl<'a> CliRunner<'a> {
             config_env.reload_repo_config(ui, &mut raw_config)?;
             config_env.reset_workspace_path(loader.workspace_root());
             config_env.reload_workspace_config(ui, &mut raw_config)?;
+            load_repo_managed_config(ui, &config_env, &mut raw_config)?;
         }
         let mut config = config_env.resolve_config(&raw_config)?;
         migrate_config(&mut config)?;
@@ -4038,6 +4517,10 @@ impl<'a> CliRunner<'a> {
             // TODO: set up debug logging as early as possible
             self.tracing_subscription.enable_debug_logging()?;
         }
+        let profile_path = args.global_args.profile.as_ref().map(|path| cwd.join(path));
+        if profile_path.is_some() {
+            self.tracing_subscription.start_profiling();
+        }
         for process_global_args_fn in self.process_global_args_fns {
             process_global_args_fn(ui, &matches)?;
         }
@@ -4056,6 +4539,7 @@ impl<'a> CliRunner<'a> {
             config_env.reload_repo_config(ui, &mut raw_config)?;
             config_env.reset_workspace_path(loader.workspace_root());
             config_env.reload_workspace_config(ui, &mut raw_config)?;
+            load_repo_managed_config(ui, &config_env, &mut raw_config)?;
             Ok(loader)
         } else {
             maybe_cwd_workspace_loader
@@ -4072,6 +4556,7 @@ impl<'a> CliRunner<'a> {
                 ConfigSource::Default => "default-provided",
                 ConfigSource::EnvBase | ConfigSource::EnvOverrides => "environment-provided",
                 ConfigSource::User => "user-level",
+                ConfigSource::RepoManaged => "repo-managed",
                 ConfigSource::Repo => "repo-level",
                 ConfigSource::Workspace => "workspace-level",
                 ConfigSource::CommandArg => "CLI-provided",
@@ -4087,6 +4572,8 @@ impl<'a> CliRunner<'a> {
         }
 
         let settings = UserSettings::from_config(config)?;
+        let mut revset_extensions = self.revset_extensions;
+        revset_util::add_config_revset_functions(&mut revset_extensions, &settings)?;
         let command_helper_data = CommandHelperData {
             app: self.app,
             cwd,
@@ -4097,7 +4584,7 @@ impl<'a> CliRunner<'a> {
             config_migrations: self.config_migrations,
             raw_config,
             settings,
-            revset_extensions: self.revset_extensions.into(),
+            revset_extensions: revset_extensions.into(),
             commit_template_extensions: self.commit_template_extensions,
             operation_template_extensions: self.operation_template_extensions,
             maybe_workspace_loader,
@@ -4116,7 +4603,11 @@ impl<'a> CliRunner<'a> {
                 })
             },
         );
-        (dispatch_fn)(ui, &command_helper)
+        let result = (dispatch_fn)(ui, &command_helper);
+        if let Some(path) = &profile_path {
+            self.tracing_subscription.finish_profiling(ui, path)?;
+        }
+        result
     }
 
     #[must_use]
@@ -4193,6 +4684,42 @@ fn format_template_aliases_hint(template_aliases: &TemplateAliasesMap) -> String
     hint
 }
 
+/// Loads the config file checked in to the workspace if it's trusted. If it
+/// isn't, asks the user whether to trust it, or prints a warning if the user
+/// can't be prompted.
+fn load_repo_managed_config(
+    ui: &Ui,
+    config_env: &ConfigEnv,
+    raw_config: &mut RawConfig,
+) -> Result<(), CommandError> {
+    let Some(path) = config_env.reload_repo_managed_config(ui, raw_config)? else {
+        return Ok(());
+    };
+    if Ui::can_prompt() {
+        writeln!(
+            ui.warning_default(),
+            "This repository has a config file at {}, which hasn't been trusted yet. Config can \
+             run arbitrary commands, e.g. through aliases.",
+            path.display()
+        )?;
+        if ui.prompt_yes_no("Trust the file and load it?", Some(false))? {
+            config_env.trust_repo_managed_config(ui)?;
+            config_env.reload_repo_managed_config(ui, raw_config)?;
+            return Ok(());
+        }
+    }
+    writeln!(
+        ui.warning_default(),
+        "Ignoring untrusted repo config file {}",
+        path.display()
+    )?;
+    writeln!(
+        ui.hint_default(),
+        "Review the file, then run `jj config trust` to load it."
+    )?;
+    Ok(())
+}
+
 // If -R or --config* is specified, check if the expanded arguments differ.
 fn warn_if_args_mismatch(
     ui: &Ui,
@@ -41,6 +41,7 @@ use jj_lib::op_heads_store::OpHeadsStoreError;
 use jj_lib::op_store::OpStoreError;
 use jj_lib::op_walk::OpsetEvaluationError;
 use jj_lib::op_walk::OpsetResolutionError;
+use jj_lib::protected_bookmarks::ProtectedBookmarkError;
 use jj_lib::repo::CheckOutCommitError;
 use jj_lib::repo::EditCommitError;
 use jj_lib::repo::RepoLoaderError;
@@ -352,6 +353,9 @@ impl From<WorkspaceInitError> for CommandError {
             WorkspaceInitError::CheckOutCommit(err) => {
                 internal_error_with_message("Failed to check out the initial commit", err)
             }
+            WorkspaceInitError::CheckOut(err) => {
+                internal_error_with_message("Failed to check out the initial commit", err)
+            }
             WorkspaceInitError::Path(err) => {
                 internal_error_with_message("Failed to access the repository", err)
             }
@@ -408,6 +412,19 @@ impl From<OpStoreError> for CommandError {
     }
 }
 
+impl From<ProtectedBookmarkError> for CommandError {
+    fn from(err: ProtectedBookmarkError) -> Self {
+        match err {
+            ProtectedBookmarkError::Deleted { .. }
+            | ProtectedBookmarkError::NotFastForward { .. } => {
+                user_error_with_hint(err, "Use --allow-protected to allow it.")
+            }
+            ProtectedBookmarkError::Config(err) => err.into(),
+            ProtectedBookmarkError::Index(err) => err.into(),
+        }
+    }
+}
+
 impl From<RepoLoaderError> for CommandError {
     fn from(err: RepoLoaderError) -> Self {
         internal_error_with_message("Failed to load the repo", err)
@@ -422,7 +439,10 @@ impl From<ResetError> for CommandError {
 
 impl From<TransactionCommitError> for CommandError {
     fn from(err: TransactionCommitError) -> Self {
-        internal_error(err)
+        match err {
+            TransactionCommitError::ProtectedBookmark(err) => err.into(),
+            _ => internal_error(err),
+        }
     }
 }
 
@@ -529,6 +549,7 @@ impl From<TrailerParseError> for CommandError {
 
 #[cfg(feature = "git")]
 mod git {
+    use jj_lib::git::GitCreateTagError;
     use jj_lib::git::GitDefaultRefspecError;
     use jj_lib::git::GitExportError;
     use jj_lib::git::GitFetchError;
@@ -538,6 +559,11 @@ mod git {
     use jj_lib::git::GitRemoteManagementError;
     use jj_lib::git::GitResetHeadError;
     use jj_lib::git::UnexpectedGitBackendError;
+    use jj_lib::git_credential::GitCredentialError;
+    use jj_lib::git_notes::GitNotesError;
+    use jj_lib::git_oauth::OAuthError;
+    use jj_lib::git_submodule::GitSubmoduleError;
+    use jj_lib::op_sync::OpSyncError;
 
     use super::*;
 
@@ -579,6 +605,21 @@ jj currently does not support partial clones. To use jj with this repository, tr
                     "Run `jj git remote rename` to give a different name.",
                 ),
                 GitFetchError::Subprocess(_) => user_error(err),
+                GitFetchError::ReadRefs(_) | GitFetchError::ReadShallowCommits(_) => {
+                    internal_error(err)
+                }
+            }
+        }
+    }
+
+    impl From<GitCreateTagError> for CommandError {
+        fn from(err: GitCreateTagError) -> Self {
+            match err {
+                GitCreateTagError::AlreadyExists(_) => {
+                    user_error_with_hint(err, "Run `jj git import` to import the existing tag.")
+                }
+                GitCreateTagError::Git(..) => internal_error(err),
+                GitCreateTagError::UnexpectedBackend(err) => err.into(),
             }
         }
     }
@@ -635,6 +676,76 @@ jj currently does not support partial clones. To use jj with this repository, tr
             user_error(err)
         }
     }
+
+    impl From<GitCredentialError> for CommandError {
+        fn from(err: GitCredentialError) -> Self {
+            match err {
+                GitCredentialError::Parse(_) | GitCredentialError::InvalidUrl { .. } => {
+                    user_error(err)
+                }
+                GitCredentialError::Spawn(_) | GitCredentialError::Git { .. } => {
+                    user_error_with_message("Failed to access the credential helpers", err)
+                }
+            }
+        }
+    }
+
+    impl From<OAuthError> for CommandError {
+        fn from(err: OAuthError) -> Self {
+            match err {
+                OAuthError::Spawn { .. } => user_error_with_message("OAuth login failed", err)
+                    .hinted("Install curl, or set `git.curl-executable` to its path."),
+                OAuthError::Http { .. }
+                | OAuthError::InvalidResponse { .. }
+                | OAuthError::AccessDenied
+                | OAuthError::Expired
+                | OAuthError::Server { .. } => user_error_with_message("OAuth login failed", err),
+            }
+        }
+    }
+
+    impl From<OpSyncError> for CommandError {
+        fn from(err: OpSyncError) -> Self {
+            match err {
+                OpSyncError::UnexpectedBackend(err) => err.into(),
+                OpSyncError::Subprocess(_) => {
+                    user_error_with_message("Failed to transfer operations", err)
+                }
+                OpSyncError::NoSuchRemote(_) | OpSyncError::InvalidOperationCommit { .. } => {
+                    user_error(err)
+                }
+                OpSyncError::OpStore(_)
+                | OpSyncError::OpHeadsStore(_)
+                | OpSyncError::IdMismatch(_)
+                | OpSyncError::Backend(_)
+                | OpSyncError::Git(_) => internal_error(err),
+            }
+        }
+    }
+
+    impl From<GitNotesError> for CommandError {
+        fn from(err: GitNotesError) -> Self {
+            match err {
+                GitNotesError::UnexpectedBackend(err) => err.into(),
+                GitNotesError::NoSuchRemote(_) | GitNotesError::Subprocess(_) => user_error(err),
+                GitNotesError::Read { .. } | GitNotesError::Write { .. } => internal_error(err),
+            }
+        }
+    }
+
+    impl From<GitSubmoduleError> for CommandError {
+        fn from(err: GitSubmoduleError) -> Self {
+            match err {
+                GitSubmoduleError::Backend(err) => err.into(),
+                GitSubmoduleError::InvalidGitmodules(_)
+                | GitSubmoduleError::MissingUrl { .. }
+                | GitSubmoduleError::UnresolvableUrl { .. }
+                | GitSubmoduleError::InvalidPath(_)
+                | GitSubmoduleError::Subprocess(_) => user_error(err),
+                GitSubmoduleError::Repository { .. } => internal_error(err),
+            }
+        }
+    }
 }
 
 impl From<RevsetEvaluationError> for CommandError {
@@ -755,6 +866,7 @@ impl From<FixError> for CommandError {
                 "An error occurred while attempting to fix file content",
                 err,
             ),
+            FixError::PathConflict { .. } => user_error(err),
         }
     }
 }
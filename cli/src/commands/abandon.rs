@@ -125,6 +125,7 @@ pub(crate) fn cmd_abandon(
             Ok(())
         },
     )?;
+    tx.check_protected_bookmarks()?;
 
     let deleted_bookmarks = diff_named_ref_targets(
         tx.base_repo().view().local_bookmarks(),
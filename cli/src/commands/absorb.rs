@@ -12,21 +12,38 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
+use std::mem;
+use std::ops::Range;
+use std::sync::Arc;
+
+use bstr::ByteSlice as _;
 use clap_complete::ArgValueCompleter;
+use jj_lib::absorb::AbsorbFile;
+use jj_lib::absorb::AbsorbHunk;
+use jj_lib::absorb::AbsorbHunks;
 use jj_lib::absorb::AbsorbSource;
 use jj_lib::absorb::absorb_hunks;
-use jj_lib::absorb::split_hunks_to_trees;
+use jj_lib::absorb::map_hunks_to_destinations;
+use jj_lib::commit::Commit;
 use jj_lib::matchers::EverythingMatcher;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
+use jj_lib::revset::ResolvedRevsetExpression;
+use jj_lib::revset::RevsetExpression;
 use pollster::FutureExt as _;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::print_unmatched_explicit_paths;
 use crate::cli_util::print_updated_commits;
+use crate::cli_util::short_commit_hash;
 use crate::command_error::CommandError;
 use crate::complete;
 use crate::diff_util::DiffFormat;
+use crate::formatter::FormatterExt as _;
 use crate::ui::Ui;
 
 /// Move changes from a revision into the stack of mutable revisions
@@ -40,6 +57,10 @@ use crate::ui::Ui;
 /// destination revisions, and if the source revision has no description.
 ///
 /// The modification made by `jj absorb` can be reviewed by `jj op show -p`.
+///
+/// With `--interactive`, each hunk is shown along with the revision it would
+/// be absorbed into, and you can accept it, skip it, or pick another
+/// destination revision before anything is rewritten.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct AbsorbArgs {
     /// Source revision to absorb from
@@ -64,6 +85,19 @@ pub(crate) struct AbsorbArgs {
     #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
     #[arg(add = ArgValueCompleter::new(complete::modified_from_files))]
     paths: Vec<String>,
+
+    /// Choose the destination of each hunk interactively
+    #[arg(long, short)]
+    interactive: bool,
+
+    /// Only print which revision each hunk would be absorbed into
+    ///
+    /// One line is printed per hunk, with tab-separated fields: the file
+    /// path, the hunk's line range in the source's parent and in the source
+    /// (as in a unified diff hunk header), and the full change ID and commit
+    /// ID of the destination revision.
+    #[arg(long, conflicts_with = "interactive")]
+    dry_run: bool,
 }
 
 #[instrument(skip_all)]
@@ -84,7 +118,7 @@ pub(crate) fn cmd_absorb(
 
     let repo = workspace_command.repo().as_ref();
     let source = AbsorbSource::from_commit(repo, source_commit.clone())?;
-    let selected_trees = split_hunks_to_trees(repo, &source, &destinations, &matcher).block_on()?;
+    let mut hunks = map_hunks_to_destinations(repo, &source, &destinations, &matcher).block_on()?;
 
     print_unmatched_explicit_paths(
         ui,
@@ -94,11 +128,26 @@ pub(crate) fn cmd_absorb(
     )?;
 
     let path_converter = workspace_command.path_converter();
-    for (path, reason) in selected_trees.skipped_paths {
-        let ui_path = path_converter.format_file_path(&path);
+    for (path, reason) in &hunks.skipped_paths {
+        let ui_path = path_converter.format_file_path(path);
         writeln!(ui.warning_default(), "Skipping {ui_path}: {reason}")?;
     }
 
+    if args.dry_run {
+        print_hunk_destinations(ui, &workspace_command, &hunks)?;
+        return Ok(());
+    }
+    if args.interactive {
+        choose_hunk_destinations(
+            ui,
+            &workspace_command,
+            &source_commit,
+            &destinations,
+            &mut hunks,
+        )?;
+    }
+    let selected_trees = hunks.to_selected_trees(repo, &source).block_on()?;
+
     workspace_command.check_rewritable(selected_trees.target_commits.keys())?;
 
     let mut tx = workspace_command.start_transaction();
@@ -150,3 +199,129 @@ pub(crate) fn cmd_absorb(
     }
     Ok(())
 }
+
+/// Returns the 1-based line number and the number of lines of the byte range
+/// in the text, as in a unified diff hunk header.
+fn to_line_range(text: &[u8], range: &Range<usize>) -> (usize, usize) {
+    let start = text[..range.start].lines_with_terminator().count();
+    let len = text[range.clone()].lines_with_terminator().count();
+    // An empty range is reported as the preceding line.
+    if len == 0 {
+        (start, 0)
+    } else {
+        (start + 1, len)
+    }
+}
+
+fn format_hunk_header(file: &AbsorbFile, hunk: &AbsorbHunk) -> String {
+    let (left_start, left_len) = to_line_range(&file.left_text, &hunk.left_range);
+    let (right_start, right_len) = to_line_range(&file.right_text, &hunk.right_range);
+    format!("-{left_start},{left_len} +{right_start},{right_len}")
+}
+
+fn print_hunk_destinations(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    hunks: &AbsorbHunks,
+) -> Result<(), CommandError> {
+    let repo = workspace_command.repo();
+    let mut formatter = ui.stdout_formatter();
+    for file in &hunks.files {
+        let ui_path = workspace_command.format_file_path(&file.path);
+        for hunk in &file.hunks {
+            let commit = repo.store().get_commit(&hunk.destination)?;
+            writeln!(
+                formatter,
+                "{ui_path}\t{}\t{}\t{}",
+                format_hunk_header(file, hunk),
+                commit.change_id().reverse_hex(),
+                commit.id().hex(),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Asks the user to accept, skip, or retarget each hunk.
+fn choose_hunk_destinations(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    source_commit: &Commit,
+    destinations: &Arc<ResolvedRevsetExpression>,
+    hunks: &mut AbsorbHunks,
+) -> Result<(), CommandError> {
+    let repo = workspace_command.repo();
+    let template = workspace_command.commit_summary_template();
+    // Only ancestors of the source revision can be destinations.
+    let valid_destinations = destinations
+        .intersection(&RevsetExpression::commits(source_commit.parent_ids().to_vec()).ancestors());
+    for file in &mut hunks.files {
+        let ui_path = workspace_command.format_file_path(&file.path);
+        let mut kept_hunks = vec![];
+        for mut hunk in mem::take(&mut file.hunks) {
+            {
+                let mut formatter = ui.stderr_formatter();
+                writeln!(
+                    formatter.labeled("hunk_header"),
+                    "{ui_path} @@ {} @@",
+                    format_hunk_header(file, &hunk)
+                )?;
+                for line in file.left_text[hunk.left_range.clone()].lines_with_terminator() {
+                    write!(formatter.labeled("removed"), "-")?;
+                    formatter.labeled("removed").write_all(line)?;
+                }
+                for line in file.right_text[hunk.right_range.clone()].lines_with_terminator() {
+                    write!(formatter.labeled("added"), "+")?;
+                    formatter.labeled("added").write_all(line)?;
+                }
+                write!(formatter, "Destination: ")?;
+                template.format(
+                    &repo.store().get_commit(&hunk.destination)?,
+                    formatter.as_mut(),
+                )?;
+                writeln!(formatter)?;
+            }
+            let choice = ui.prompt_choice(
+                "Absorb this hunk? [y]es, [n]o (skip), [r]etarget",
+                &["y", "n", "r"],
+                Some(0),
+            )?;
+            match choice {
+                0 => kept_hunks.push(hunk),
+                1 => {}
+                _ => {
+                    let destination = loop {
+                        let revision = ui.prompt("Enter the revision to absorb the hunk into")?;
+                        let commit = match workspace_command
+                            .resolve_single_rev(ui, &RevisionArg::from(revision))
+                        {
+                            Ok(commit) => commit,
+                            Err(err) => {
+                                writeln!(ui.warning_default(), "{}; try again", err.error)?;
+                                continue;
+                            }
+                        };
+                        let is_valid = valid_destinations
+                            .intersection(&RevsetExpression::commit(commit.id().clone()))
+                            .evaluate(repo.as_ref())?
+                            .iter()
+                            .next()
+                            .is_some();
+                        if is_valid {
+                            break commit;
+                        }
+                        writeln!(
+                            ui.warning_default(),
+                            "Revision {} is not one of the destination revisions; try again",
+                            short_commit_hash(commit.id())
+                        )?;
+                    };
+                    hunk.destination = destination.id().clone();
+                    kept_hunks.push(hunk);
+                }
+            }
+        }
+        file.hunks = kept_hunks;
+    }
+    Ok(())
+}
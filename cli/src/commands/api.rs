@@ -0,0 +1,671 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(unix)]
+use std::fs;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+#[cfg(unix)]
+use std::path::Path;
+#[cfg(any(unix, feature = "builtin-fsmonitor"))]
+use std::path::PathBuf;
+#[cfg(feature = "builtin-fsmonitor")]
+use std::sync::mpsc;
+#[cfg(feature = "builtin-fsmonitor")]
+use std::time::Duration;
+#[cfg(feature = "builtin-fsmonitor")]
+use std::time::Instant;
+
+use bstr::BStr;
+use futures::StreamExt as _;
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::conflicts::ConflictMaterializeOptions;
+use jj_lib::conflicts::MaterializedTreeDiffEntry;
+use jj_lib::conflicts::materialized_diff_stream;
+use jj_lib::copies::CopyRecords;
+use jj_lib::diff_presentation::unified::DiffLineType;
+use jj_lib::diff_presentation::unified::UnifiedDiffHunk;
+use jj_lib::diff_presentation::unified::git_diff_part;
+use jj_lib::diff_presentation::unified::unified_diff_hunks;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::merge::Diff;
+use jj_lib::merged_tree::TreeDiffEntry;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo;
+use pollster::FutureExt as _;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use serde_json::json;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::CommandError;
+use crate::diff_util::DiffRenderError;
+use crate::diff_util::UnifiedDiffOptions;
+use crate::diff_util::hunk_line_number;
+use crate::ui::Ui;
+
+/// Version of the request and response formats. It's incremented when
+/// existing methods change incompatibly.
+const API_VERSION: u32 = 1;
+
+// Error codes defined by JSON-RPC 2.0
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+// Application-defined error code for errors reported by jj
+const COMMAND_ERROR: i64 = 1;
+
+/// How long to wait for more filesystem events before snapshotting the
+/// working copy.
+#[cfg(feature = "builtin-fsmonitor")]
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Serve repository queries to editors and other tools
+///
+/// Requests and responses are [JSON-RPC 2.0] messages, one per line. By
+/// default, requests are read from stdin and responses are written to
+/// stdout. With `--socket`, the server listens on a Unix domain socket
+/// instead and serves clients one at a time. A stale socket file left by a
+/// previous server is replaced.
+///
+/// The following methods are supported:
+///
+/// * `api.version`: Returns the version of the interface as `{"version": N}`.
+///
+/// * `operation.head`: Returns the id of the current operation as `{"id": ID}`.
+///
+/// * `operation.wait`: Takes a `since` operation id and an optional
+///   `timeout_ms` (default: 30000), and waits until the current operation
+///   differs from `since` or the timeout expires. Returns the current operation
+///   as `{"id": ID}`. The server watches the working copy and the operation log
+///   for changes, so clients can call this in a loop to get notified of changes
+///   to the repository or the working copy. Only available if jj was built with
+///   the `builtin-fsmonitor` feature.
+///
+/// * `log`: Takes optional `revisions` (default: `revsets.log`) and `template`
+///   (default: `templates.log`) parameters, and returns the matching commits as
+///   `{"commits": [{"commit_id", "change_id", "output"}]}`, where `output` is
+///   the rendered template.
+///
+/// * `diff`: Takes an optional `revision` parameter (default: `@`), and returns
+///   the changes in that revision as `{"commit_id", "files": [{"path",
+///   "status", "binary", "hunks": [{"old_start", "old_lines", "new_start",
+///   "new_lines", "lines"}]}]}`. Each of the `lines` starts with ` `, `-`, or
+///   `+` like in a unified diff. Binary files have no hunks.
+///
+/// * `status`: Returns the working-copy commit and the files changed in it as
+///   `{"commit_id", "change_id", "files": [{"path", "status"}], "conflicts":
+///   [PATH]}`.
+///
+/// * `describe`: Takes a required `message` and an optional `revision`
+///   parameter (default: `@`), and updates the description of the revision.
+///   Returns the rewritten commit and the new operation as `{"commit_id",
+///   "operation_id"}`. Descendants are rebased as with `jj describe`.
+///
+/// The repository is loaded once and only reloaded when another process adds
+/// an operation. The server watches the working copy, and only snapshots it
+/// when files changed. If jj was built without the `builtin-fsmonitor`
+/// feature, the working copy is snapshotted for every request instead.
+///
+/// [JSON-RPC 2.0]: https://www.jsonrpc.org/specification
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct ApiArgs {
+    /// Listen on the Unix domain socket at the given path
+    #[cfg(unix)]
+    #[arg(long, value_name = "PATH", value_hint = clap::ValueHint::FilePath)]
+    socket: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    /// Request id, which is absent in notifications.
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct LogParams {
+    revisions: Option<String>,
+    template: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct DiffParams {
+    revision: Option<String>,
+}
+
+#[cfg(feature = "builtin-fsmonitor")]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WaitParams {
+    since: String,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DescribeParams {
+    #[serde(default)]
+    revision: Option<String>,
+    message: String,
+}
+
+enum ApiError {
+    MethodNotFound(String),
+    InvalidParams(serde_json::Error),
+    Command(CommandError),
+}
+
+impl From<CommandError> for ApiError {
+    fn from(err: CommandError) -> Self {
+        Self::Command(err)
+    }
+}
+
+impl ApiError {
+    fn to_json(&self) -> Value {
+        let (code, message) = match self {
+            Self::MethodNotFound(method) => {
+                (METHOD_NOT_FOUND, format!("Method not found: {method}"))
+            }
+            Self::InvalidParams(err) => (INVALID_PARAMS, format!("Invalid params: {err}")),
+            Self::Command(err) => (COMMAND_ERROR, err.error.to_string()),
+        };
+        json!({ "code": code, "message": message })
+    }
+}
+
+/// Workspace state kept between requests. The repo is only reloaded when
+/// another process added an operation, so the loaded index and other caches
+/// are reused.
+struct Session<'a> {
+    command: &'a CommandHelper,
+    workspace_command: WorkspaceCommandHelper,
+    #[cfg(feature = "builtin-fsmonitor")]
+    watcher: ChangeWatcher,
+}
+
+impl<'a> Session<'a> {
+    fn new(ui: &Ui, command: &'a CommandHelper) -> Result<Self, CommandError> {
+        // Start watching before the working copy is snapshotted so no changes
+        // will be missed.
+        #[cfg(feature = "builtin-fsmonitor")]
+        let watcher = ChangeWatcher::new(command)?;
+        let workspace_command = command.workspace_helper(ui)?;
+        Ok(Self {
+            command,
+            workspace_command,
+            #[cfg(feature = "builtin-fsmonitor")]
+            watcher,
+        })
+    }
+
+    /// Returns the workspace after bringing it up to date with the repo and
+    /// the working copy.
+    fn workspace_command(&mut self, ui: &Ui) -> Result<&mut WorkspaceCommandHelper, CommandError> {
+        #[cfg(feature = "builtin-fsmonitor")]
+        let working_copy_changed = self.watcher.take_working_copy_changed()?;
+        #[cfg(not(feature = "builtin-fsmonitor"))]
+        let working_copy_changed = true;
+        let repo = self.workspace_command.repo();
+        let op_heads = repo.loader().op_heads_store().get_op_heads().block_on()?;
+        if op_heads.as_slice() != std::slice::from_ref(repo.op_id()) {
+            // The working copy may have been updated too.
+            self.workspace_command = self.command.workspace_helper(ui)?;
+        } else if working_copy_changed {
+            self.workspace_command.maybe_snapshot(ui)?;
+        }
+        Ok(&mut self.workspace_command)
+    }
+}
+
+/// Watches the working copy and the operation log for changes.
+#[cfg(feature = "builtin-fsmonitor")]
+struct ChangeWatcher {
+    _watcher: notify::RecommendedWatcher,
+    rx: mpsc::Receiver<notify::Result<notify::Event>>,
+    workspace_root: PathBuf,
+    op_heads_dir: PathBuf,
+    working_copy_changed: bool,
+}
+
+#[cfg(feature = "builtin-fsmonitor")]
+impl ChangeWatcher {
+    fn new(command: &CommandHelper) -> Result<Self, CommandError> {
+        use notify::RecursiveMode;
+        use notify::Watcher as _;
+
+        let loader = command.workspace_loader()?;
+        let workspace_root = dunce::canonicalize(loader.workspace_root())?;
+        let op_heads_dir = dunce::canonicalize(loader.repo_path().join("op_heads"))?;
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+        watcher
+            .watch(&workspace_root, RecursiveMode::Recursive)
+            .map_err(watch_error)?;
+        watcher
+            .watch(&op_heads_dir, RecursiveMode::Recursive)
+            .map_err(watch_error)?;
+        Ok(Self {
+            _watcher: watcher,
+            rx,
+            workspace_root,
+            op_heads_dir,
+            working_copy_changed: false,
+        })
+    }
+
+    /// Returns true if files in the working copy may have changed since the
+    /// last call.
+    fn take_working_copy_changed(&mut self) -> Result<bool, CommandError> {
+        while let Ok(event) = self.rx.try_recv() {
+            self.handle_event(event)?;
+        }
+        Ok(std::mem::take(&mut self.working_copy_changed))
+    }
+
+    /// Waits until the working copy or the operation log may have changed.
+    /// Returns false if the `deadline` passed first.
+    fn wait(&mut self, deadline: Instant) -> Result<bool, CommandError> {
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let Ok(event) = self.rx.recv_timeout(timeout) else {
+                return Ok(false);
+            };
+            if self.handle_event(event)? {
+                break;
+            }
+        }
+        // Wait for related events (such as writes to the same file) to
+        // arrive so they're handled by a single snapshot.
+        while let Ok(event) = self.rx.recv_timeout(DEBOUNCE_INTERVAL) {
+            self.handle_event(event)?;
+        }
+        Ok(true)
+    }
+
+    /// Records the changes reported by `event`, and returns true if they're
+    /// relevant.
+    fn handle_event(&mut self, event: notify::Result<notify::Event>) -> Result<bool, CommandError> {
+        use notify::EventKind;
+
+        let event = event.map_err(watch_error)?;
+        if event.need_rescan() {
+            self.working_copy_changed = true;
+            return Ok(true);
+        }
+        if matches!(event.kind, EventKind::Access(_)) {
+            return Ok(false);
+        }
+        let mut relevant = false;
+        for path in &event.paths {
+            if path.starts_with(&self.op_heads_dir) {
+                relevant = true;
+            } else if let Ok(path) = path.strip_prefix(&self.workspace_root)
+                // Like the builtin fsmonitor, ignore the internal directories,
+                // which are also written by jj itself.
+                && !path.starts_with(".jj")
+                && !path.starts_with(".git")
+            {
+                self.working_copy_changed = true;
+                relevant = true;
+            }
+        }
+        Ok(relevant)
+    }
+}
+
+#[cfg(feature = "builtin-fsmonitor")]
+fn watch_error(err: notify::Error) -> CommandError {
+    crate::command_error::user_error_with_message("Failed to watch for changes", err)
+}
+
+pub(crate) fn cmd_api(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &ApiArgs,
+) -> Result<(), CommandError> {
+    // Fails early if there's no workspace.
+    let mut session = Session::new(ui, command)?;
+
+    #[cfg(unix)]
+    if let Some(path) = &args.socket {
+        use std::os::unix::net::UnixListener;
+
+        use crate::command_error::user_error_with_message;
+
+        remove_stale_socket(path)?;
+        let listener = UnixListener::bind(path).map_err(|err| {
+            user_error_with_message(format!("Failed to listen on {}", path.display()), err)
+        })?;
+        for stream in listener.incoming() {
+            // A misbehaving client shouldn't bring down the server.
+            let result = stream
+                .map_err(CommandError::from)
+                .and_then(|stream| serve(ui, &mut session, io::BufReader::new(&stream), &stream));
+            if let Err(err) = result {
+                writeln!(
+                    ui.warning_default(),
+                    "Failed to serve client: {}",
+                    err.error
+                )?;
+            }
+        }
+        return Ok(());
+    }
+    #[cfg(not(unix))]
+    let _ = args;
+
+    serve(ui, &mut session, io::stdin().lock(), ui.stdout())
+}
+
+/// Removes the socket file at `path` if no server is listening on it.
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path) -> Result<(), CommandError> {
+    use std::os::unix::fs::FileTypeExt as _;
+    use std::os::unix::net::UnixStream;
+
+    let Ok(metadata) = path.symlink_metadata() else {
+        return Ok(());
+    };
+    if metadata.file_type().is_socket()
+        && UnixStream::connect(path)
+            .is_err_and(|err| err.kind() == io::ErrorKind::ConnectionRefused)
+    {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Serves requests read from `input` until it's closed.
+fn serve(
+    ui: &Ui,
+    session: &mut Session,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<(), CommandError> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(Request {
+                id: None,
+                method,
+                params,
+            }) => {
+                // Notifications don't get a response, even on error.
+                handle_request(ui, session, &method, params).ok();
+                continue;
+            }
+            Ok(Request {
+                id: Some(id),
+                method,
+                params,
+            }) => match handle_request(ui, session, &method, params) {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                Err(err) => json!({ "jsonrpc": "2.0", "id": id, "error": err.to_json() }),
+            },
+            Err(err) => json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": PARSE_ERROR, "message": format!("Parse error: {err}") },
+            }),
+        };
+        serde_json::to_writer(&mut output, &response).map_err(io::Error::from)?;
+        writeln!(output)?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+fn handle_request(
+    ui: &Ui,
+    session: &mut Session,
+    method: &str,
+    params: Value,
+) -> Result<Value, ApiError> {
+    match method {
+        "api.version" => Ok(json!({ "version": API_VERSION })),
+        "operation.head" => Ok(operation_head(ui, session)?),
+        #[cfg(feature = "builtin-fsmonitor")]
+        "operation.wait" => Ok(operation_wait(ui, session, parse_params(params)?)?),
+        "log" => Ok(log(ui, session, parse_params(params)?)?),
+        "diff" => Ok(diff(ui, session, parse_params(params)?)?),
+        "status" => Ok(status(ui, session)?),
+        "describe" => Ok(describe(ui, session, parse_params(params)?)?),
+        _ => Err(ApiError::MethodNotFound(method.to_owned())),
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, ApiError> {
+    // Omitted params are the same as an empty object.
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(ApiError::InvalidParams)
+}
+
+fn operation_head(ui: &Ui, session: &mut Session) -> Result<Value, CommandError> {
+    let workspace_command = session.workspace_command(ui)?;
+    let op_id = workspace_command.repo().op_id();
+    Ok(json!({ "id": op_id.hex() }))
+}
+
+#[cfg(feature = "builtin-fsmonitor")]
+fn operation_wait(
+    ui: &Ui,
+    session: &mut Session,
+    params: WaitParams,
+) -> Result<Value, CommandError> {
+    let deadline = Instant::now() + Duration::from_millis(params.timeout_ms.unwrap_or(30_000));
+    loop {
+        // Snapshots the working copy if needed, so changed files are reported
+        // as a new operation.
+        let op_id = session.workspace_command(ui)?.repo().op_id().hex();
+        if op_id != params.since || !session.watcher.wait(deadline)? {
+            return Ok(json!({ "id": op_id }));
+        }
+    }
+}
+
+fn log(ui: &Ui, session: &mut Session, params: LogParams) -> Result<Value, CommandError> {
+    let workspace_command = session.workspace_command(ui)?;
+    let settings = workspace_command.settings();
+    let revisions = match params.revisions {
+        Some(revisions) => revisions,
+        None => settings.get_string("revsets.log")?,
+    };
+    let template_text = match params.template {
+        Some(template) => template,
+        None => settings.get_string("templates.log")?,
+    };
+    let template = workspace_command.parse_commit_template(ui, &template_text)?;
+    let commits: Vec<_> = workspace_command
+        .parse_revset(ui, &RevisionArg::from(revisions))?
+        .evaluate_to_commits()?
+        .map(|commit| -> Result<_, CommandError> {
+            let commit = commit?;
+            let output = template.format_plain_text(&commit);
+            Ok(json!({
+                "commit_id": commit.id().hex(),
+                "change_id": commit.change_id().reverse_hex(),
+                "output": String::from_utf8_lossy(&output),
+            }))
+        })
+        .try_collect()?;
+    Ok(json!({ "commits": commits }))
+}
+
+fn diff(ui: &Ui, session: &mut Session, params: DiffParams) -> Result<Value, CommandError> {
+    let workspace_command = session.workspace_command(ui)?;
+    let revision = RevisionArg::from(params.revision.unwrap_or_else(|| "@".to_owned()));
+    let commit = workspace_command.resolve_single_rev(ui, &revision)?;
+    let repo = workspace_command.repo();
+    let options = UnifiedDiffOptions::from_settings(workspace_command.settings())?;
+    let materialize_options = ConflictMaterializeOptions {
+        marker_style: workspace_command.env().conflict_marker_style(),
+        marker_len: None,
+        merge: repo.store().merge_options().clone(),
+    };
+    let from_tree = commit.parent_tree(repo.as_ref())?;
+    let to_tree = commit.tree();
+    let copy_records = CopyRecords::default();
+    let tree_diff = from_tree.diff_stream_with_copies(&to_tree, &EverythingMatcher, &copy_records);
+    let conflict_labels = Diff::new(from_tree.labels(), to_tree.labels());
+    let entries: Vec<_> = materialized_diff_stream(repo.store(), tree_diff, conflict_labels)
+        .collect()
+        .block_on();
+    let files: Vec<_> = entries
+        .into_iter()
+        .map(
+            |MaterializedTreeDiffEntry { path, values }| -> Result<_, CommandError> {
+                let values = values?;
+                let (left_path, right_path) = (path.source(), path.target());
+                let left_part = git_diff_part(left_path, values.before, &materialize_options)
+                    .map_err(DiffRenderError::from)?;
+                let right_part = git_diff_part(right_path, values.after, &materialize_options)
+                    .map_err(DiffRenderError::from)?;
+                let status = match (left_part.mode, right_part.mode) {
+                    (None, _) => "added",
+                    (_, None) => "removed",
+                    _ => "modified",
+                };
+                let binary = left_part.content.is_binary || right_part.content.is_binary;
+                let hunks = if binary {
+                    vec![]
+                } else {
+                    let contents = Diff::new(
+                        BStr::new(&left_part.content.contents),
+                        BStr::new(&right_part.content.contents),
+                    );
+                    unified_diff_hunks(
+                        contents,
+                        options.context,
+                        options.line_diff.compare_mode,
+                        options.line_diff.algorithm,
+                    )
+                    .iter()
+                    .map(hunk_to_json)
+                    .collect()
+                };
+                Ok(json!({
+                    "path": right_path.as_internal_file_string(),
+                    "status": status,
+                    "binary": binary,
+                    "hunks": hunks,
+                }))
+            },
+        )
+        .try_collect()?;
+    Ok(json!({ "commit_id": commit.id().hex(), "files": files }))
+}
+
+fn hunk_to_json(hunk: &UnifiedDiffHunk) -> Value {
+    let lines = hunk
+        .lines
+        .iter()
+        .map(|(line_type, tokens)| {
+            let sigil = match line_type {
+                DiffLineType::Context => " ",
+                DiffLineType::Removed => "-",
+                DiffLineType::Added => "+",
+            };
+            let content: Vec<u8> = tokens
+                .iter()
+                .flat_map(|(_, content)| *content)
+                .copied()
+                .collect();
+            format!("{sigil}{}", String::from_utf8_lossy(&content))
+        })
+        .collect_vec();
+    json!({
+        "old_start": hunk_line_number(hunk.left_line_range.clone()),
+        "old_lines": hunk.left_line_range.len(),
+        "new_start": hunk_line_number(hunk.right_line_range.clone()),
+        "new_lines": hunk.right_line_range.len(),
+        "lines": lines,
+    })
+}
+
+fn status(ui: &Ui, session: &mut Session) -> Result<Value, CommandError> {
+    let workspace_command = session.workspace_command(ui)?;
+    let commit = workspace_command.resolve_single_rev(ui, &RevisionArg::AT)?;
+    let files = changed_files(workspace_command.repo().as_ref(), &commit)?;
+    let conflicts = commit
+        .tree()
+        .conflicts()
+        .map(|(path, _value)| path.as_internal_file_string().to_owned())
+        .collect_vec();
+    Ok(json!({
+        "commit_id": commit.id().hex(),
+        "change_id": commit.change_id().reverse_hex(),
+        "files": files,
+        "conflicts": conflicts,
+    }))
+}
+
+fn describe(ui: &Ui, session: &mut Session, params: DescribeParams) -> Result<Value, CommandError> {
+    let workspace_command = session.workspace_command(ui)?;
+    let revision = RevisionArg::from(params.revision.unwrap_or_else(|| "@".to_owned()));
+    let commit = workspace_command.resolve_single_rev(ui, &revision)?;
+    workspace_command.check_rewritable([commit.id()])?;
+    let mut tx = workspace_command.start_transaction();
+    let new_commit = tx
+        .repo_mut()
+        .rewrite_commit(&commit)
+        .set_description(params.message)
+        .write()?;
+    tx.finish(ui, format!("describe commit {}", commit.id().hex()))?;
+    Ok(json!({
+        "commit_id": new_commit.id().hex(),
+        "operation_id": workspace_command.repo().op_id().hex(),
+    }))
+}
+
+/// Lists the files changed in `commit` compared to its parents.
+fn changed_files(repo: &dyn Repo, commit: &Commit) -> Result<Vec<Value>, CommandError> {
+    let from_tree = commit.parent_tree(repo)?;
+    let to_tree = commit.tree();
+    let entries: Vec<_> = from_tree
+        .diff_stream(&to_tree, &EverythingMatcher)
+        .collect()
+        .block_on();
+    entries
+        .into_iter()
+        .map(
+            |TreeDiffEntry { path, values }| -> Result<_, CommandError> {
+                let values = values?;
+                let status = if values.before.is_absent() {
+                    "added"
+                } else if values.after.is_absent() {
+                    "removed"
+                } else {
+                    "modified"
+                };
+                Ok(json!({ "path": path.as_internal_file_string(), "status": status }))
+            },
+        )
+        .try_collect()
+}
@@ -0,0 +1,212 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCompleter;
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo;
+use jj_lib::revset::RevsetIteratorExt as _;
+use jj_lib::rewrite::CommitWithSelection;
+use jj_lib::rewrite::squash_commits;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::print_updated_commits;
+use crate::cli_util::short_commit_hash;
+use crate::command_error::CommandError;
+use crate::command_error::internal_error;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Squash `fixup!` and `squash!` revisions into the revisions they refer to
+///
+/// A revision whose description starts with `fixup! <target>` or `squash!
+/// <target>` is squashed into the closest ancestor in the given revisions that
+/// matches `<target>`. The target can be a change ID prefix, a commit ID
+/// prefix, or the beginning of the first line of the target's description, as
+/// with `git rebase --autosquash`.
+///
+/// With `fixup!`, the target keeps its description. With `squash!`, the rest of
+/// the squashed revision's description is appended to the target's.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct AutosquashArgs {
+    /// The revisions to look for fixup revisions and their targets in
+    #[arg(long, short, default_value = "::@ & mutable()", value_name = "REVSETS")]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_mutable))]
+    revisions: Vec<RevisionArg>,
+}
+
+/// How a fixup revision is squashed into its target.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum FixupKind {
+    /// Keep only the target's description.
+    Fixup,
+    /// Append the fixup revision's description to the target's.
+    Squash,
+}
+
+/// Parses the first line of a description like `fixup! fixup! <target>` into
+/// the kind of fixup and the target.
+fn parse_fixup_subject(description: &str) -> Option<(FixupKind, &str)> {
+    let mut subject = description.lines().next()?;
+    let mut kind = None;
+    loop {
+        if let Some(rest) = subject.strip_prefix("fixup! ") {
+            kind.get_or_insert(FixupKind::Fixup);
+            subject = rest.trim_start();
+        } else if let Some(rest) = subject.strip_prefix("squash! ") {
+            kind.get_or_insert(FixupKind::Squash);
+            subject = rest.trim_start();
+        } else {
+            break;
+        }
+    }
+    let target = subject.trim_end();
+    (!target.is_empty()).then_some((kind?, target))
+}
+
+fn matches_fixup_target(commit: &Commit, target: &str) -> bool {
+    if parse_fixup_subject(commit.description()).is_some() {
+        return false;
+    }
+    let subject = commit.description().lines().next().unwrap_or_default();
+    commit.change_id().reverse_hex().starts_with(target)
+        || commit.id().hex().starts_with(target)
+        || subject.starts_with(target)
+}
+
+/// Returns the current version of the given commit after the commits in the
+/// transaction have been rewritten.
+fn current_commit(repo: &dyn Repo, commit: &Commit) -> Result<Commit, CommandError> {
+    let targets = repo.resolve_change_id(commit.change_id())?;
+    let commit_id = targets
+        .iter()
+        .flat_map(|targets| targets.visible_with_offsets())
+        .map(|(_, id)| id)
+        .exactly_one()
+        .map_err(|_| {
+            internal_error(format!(
+                "Cannot find the rewritten version of commit {}",
+                short_commit_hash(commit.id())
+            ))
+        })?;
+    Ok(repo.store().get_commit(commit_id)?)
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_autosquash(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &AutosquashArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let expression = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .resolve()?;
+    workspace_command.check_rewritable_expr(&expression)?;
+    let repo = workspace_command.repo().clone();
+    // In reverse topological order, so the closest ancestor comes first.
+    let candidates: Vec<Commit> = expression
+        .evaluate(repo.as_ref())?
+        .iter()
+        .commits(repo.store())
+        .try_collect()?;
+
+    let mut fixups = vec![];
+    for (i, commit) in candidates.iter().enumerate() {
+        let Some((kind, target)) = parse_fixup_subject(commit.description()) else {
+            continue;
+        };
+        let mut destination = None;
+        for candidate in &candidates[i + 1..] {
+            if matches_fixup_target(candidate, target)
+                && repo.index().is_ancestor(candidate.id(), commit.id())?
+            {
+                destination = Some(candidate);
+                break;
+            }
+        }
+        let Some(destination) = destination else {
+            writeln!(
+                ui.warning_default(),
+                "Could not find the revision that {} refers to: {target}",
+                short_commit_hash(commit.id()),
+            )?;
+            continue;
+        };
+        fixups.push((kind, commit.clone(), destination.clone()));
+    }
+    if fixups.is_empty() {
+        writeln!(ui.status(), "No fixup revisions to squash.")?;
+        return Ok(());
+    }
+    // Squash the oldest fixups first, like `git rebase --autosquash`.
+    fixups.reverse();
+
+    let mut tx = workspace_command.start_transaction();
+    let mut rewritten_destinations: Vec<Commit> = vec![];
+    for (kind, source, destination) in &fixups {
+        let source = current_commit(tx.repo(), source)?;
+        let destination = current_commit(tx.repo(), destination)?;
+        let source_selection = CommitWithSelection {
+            selected_tree: source.tree(),
+            parent_tree: source.parent_tree(tx.repo())?,
+            commit: source.clone(),
+        };
+        let Some(squashed) =
+            squash_commits(tx.repo_mut(), &[source_selection], &destination, false)?
+        else {
+            continue;
+        };
+        let mut commit_builder = squashed.commit_builder.detach();
+        if *kind == FixupKind::Squash {
+            let extra_description = source
+                .description()
+                .split_once('\n')
+                .map_or("", |(_, rest)| rest.trim_start_matches('\n'));
+            if destination.description().is_empty() {
+                commit_builder.set_description(extra_description);
+            } else if !extra_description.is_empty() {
+                let description = format!("{}\n{extra_description}", destination.description());
+                commit_builder.set_description(description);
+            }
+        }
+        let new_commit = commit_builder.write(tx.repo_mut())?;
+        tx.repo_mut().rebase_descendants()?;
+        rewritten_destinations.retain(|commit| commit.change_id() != new_commit.change_id());
+        rewritten_destinations.push(new_commit);
+    }
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(
+            formatter,
+            "Squashed {} fixup revisions into {} revisions:",
+            fixups.len(),
+            rewritten_destinations.len()
+        )?;
+        let rewritten_destinations: Vec<Commit> = rewritten_destinations
+            .iter()
+            .map(|commit| current_commit(tx.repo(), commit))
+            .try_collect()?;
+        print_updated_commits(
+            formatter.as_mut(),
+            &tx.commit_summary_template(),
+            rewritten_destinations.iter().rev(),
+        )?;
+    }
+    tx.finish(ui, format!("autosquash {} fixup revisions", fixups.len()))?;
+    Ok(())
+}
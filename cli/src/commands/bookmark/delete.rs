@@ -71,6 +71,7 @@ pub fn cmd_bookmark_delete(
         tx.repo_mut()
             .set_local_bookmark_target(name, RefTarget::absent());
     }
+    tx.check_protected_bookmarks()?;
     writeln!(
         ui.status(),
         "Deleted {} bookmarks.",
@@ -94,6 +94,7 @@ pub fn cmd_bookmark_forget(
             tx.repo_mut().untrack_remote_bookmark(symbol);
         }
     }
+    tx.check_protected_bookmarks()?;
     writeln!(
         ui.status(),
         "Forgot {} local bookmarks.",
@@ -0,0 +1,123 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::slice;
+
+use clap_complete::ArgValueCandidates;
+use jj_lib::op_walk;
+use jj_lib::op_walk::BookmarkChangeIndex;
+use jj_lib::operation::Operation;
+use jj_lib::ref_name::RefNameBuf;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::LogContentFormat;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::CommandError;
+use crate::commands::operation::write_ref_target_summary;
+use crate::complete;
+use crate::revset_util;
+use crate::templater::TemplateRenderer;
+use crate::ui::Ui;
+
+/// Show the operations that moved a bookmark
+///
+/// Lists every operation that changed the target of the local bookmark, most
+/// recent first, along with the new and old targets. This is similar to the
+/// per-ref reflog in Git, but computed from the operation log.
+#[derive(clap::Args, Clone, Debug)]
+pub struct BookmarkLogArgs {
+    /// The bookmark to show the history of
+    #[arg(value_parser = revset_util::parse_bookmark_name)]
+    #[arg(add = ArgValueCandidates::new(complete::local_bookmarks))]
+    name: RefNameBuf,
+
+    /// Limit number of operations to show
+    #[arg(long, short = 'n')]
+    limit: Option<usize>,
+
+    /// Render each operation using the given template
+    ///
+    /// You can specify arbitrary template expressions using the
+    /// [built-in keywords]. See [`jj help -k templates`] for more
+    /// information.
+    ///
+    /// [built-in keywords]:
+    ///     https://docs.jj-vcs.dev/latest/templates/#operation-keywords
+    ///
+    /// [`jj help -k templates`]:
+    ///     https://docs.jj-vcs.dev/latest/templates/
+    #[arg(long, short = 'T')]
+    #[arg(add = ArgValueCandidates::new(complete::template_aliases))]
+    template: Option<String>,
+}
+
+pub fn cmd_bookmark_log(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BookmarkLogArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let settings = workspace_command.settings();
+    let with_content_format = LogContentFormat::new(ui, settings)?;
+
+    let template: TemplateRenderer<Operation> = {
+        let text = match &args.template {
+            Some(value) => value.to_owned(),
+            None => settings.get_string("templates.op_log")?,
+        };
+        workspace_command
+            .parse_operation_template(ui, &text)?
+            .labeled(["bookmark_log", "operation"])
+    };
+    let commit_summary_template = workspace_command
+        .commit_summary_template()
+        .labeled(["bookmark_log", "commit"]);
+
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    let formatter = formatter.as_mut();
+    let index = bookmark_change_index(&workspace_command);
+    let changes =
+        op_walk::walk_bookmark_changes(&index, slice::from_ref(repo.operation()), &args.name)
+            .take(args.limit.unwrap_or(usize::MAX));
+    for change in changes {
+        let change = change?;
+        with_content_format.write(formatter, |formatter| {
+            template.format(&change.operation, formatter)
+        })?;
+        write_ref_target_summary(
+            formatter,
+            repo.as_ref(),
+            &commit_summary_template,
+            &change.new_target,
+            true,
+            None,
+        )?;
+        write_ref_target_summary(
+            formatter,
+            repo.as_ref(),
+            &commit_summary_template,
+            &change.old_target,
+            false,
+            None,
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the index of bookmark changes stored in the repo directory.
+pub fn bookmark_change_index(workspace_command: &WorkspaceCommandHelper) -> BookmarkChangeIndex {
+    BookmarkChangeIndex::new(workspace_command.repo_path().join("bookmark_change_index"))
+}
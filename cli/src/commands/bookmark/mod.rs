@@ -16,8 +16,10 @@ mod create;
 mod delete;
 mod forget;
 mod list;
+mod log;
 mod r#move;
 mod rename;
+mod resolve;
 mod set;
 mod track;
 mod untrack;
@@ -45,10 +47,15 @@ use self::forget::BookmarkForgetArgs;
 use self::forget::cmd_bookmark_forget;
 use self::list::BookmarkListArgs;
 use self::list::cmd_bookmark_list;
+use self::log::BookmarkLogArgs;
+pub use self::log::bookmark_change_index;
+use self::log::cmd_bookmark_log;
 use self::r#move::BookmarkMoveArgs;
 use self::r#move::cmd_bookmark_move;
 use self::rename::BookmarkRenameArgs;
 use self::rename::cmd_bookmark_rename;
+use self::resolve::BookmarkResolveArgs;
+use self::resolve::cmd_bookmark_resolve;
 use self::set::BookmarkSetArgs;
 use self::set::cmd_bookmark_set;
 use self::track::BookmarkTrackArgs;
@@ -79,10 +86,12 @@ pub enum BookmarkCommand {
     Forget(BookmarkForgetArgs),
     #[command(visible_alias("l"))]
     List(BookmarkListArgs),
+    Log(BookmarkLogArgs),
     #[command(visible_alias("m"))]
     Move(BookmarkMoveArgs),
     #[command(visible_alias("r"))]
     Rename(BookmarkRenameArgs),
+    Resolve(BookmarkResolveArgs),
     #[command(visible_alias("s"))]
     Set(BookmarkSetArgs),
     #[command(visible_alias("t"))]
@@ -100,8 +109,10 @@ pub fn cmd_bookmark(
         BookmarkCommand::Delete(args) => cmd_bookmark_delete(ui, command, args),
         BookmarkCommand::Forget(args) => cmd_bookmark_forget(ui, command, args),
         BookmarkCommand::List(args) => cmd_bookmark_list(ui, command, args),
+        BookmarkCommand::Log(args) => cmd_bookmark_log(ui, command, args),
         BookmarkCommand::Move(args) => cmd_bookmark_move(ui, command, args),
         BookmarkCommand::Rename(args) => cmd_bookmark_rename(ui, command, args),
+        BookmarkCommand::Resolve(args) => cmd_bookmark_resolve(ui, command, args),
         BookmarkCommand::Set(args) => cmd_bookmark_set(ui, command, args),
         BookmarkCommand::Track(args) => cmd_bookmark_track(ui, command, args),
         BookmarkCommand::Untrack(args) => cmd_bookmark_untrack(ui, command, args),
@@ -126,7 +137,7 @@ fn find_trackable_remote_bookmarks<'a>(
         }
         matching_bookmarks.extend(matches);
     }
-    matching_bookmarks.sort_unstable_by(|(sym1, _), (sym2, _)| sym1.cmp(sym2));
+    matching_bookmarks.sort_unstable_by_key(|(sym, _)| *sym);
     matching_bookmarks.dedup_by(|(sym1, _), (sym2, _)| sym1 == sym2);
     if !unmatched_symbols.is_empty() {
         writeln!(
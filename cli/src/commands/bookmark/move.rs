@@ -141,6 +141,7 @@ pub fn cmd_bookmark_move(
         tx.repo_mut()
             .set_local_bookmark_target(name, RefTarget::normal(target_commit.id().clone()));
     }
+    tx.check_protected_bookmarks()?;
 
     if let Some(mut formatter) = ui.status_formatter() {
         write!(formatter, "Moved {} bookmarks to ", matched_bookmarks.len())?;
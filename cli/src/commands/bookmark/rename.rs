@@ -74,6 +74,7 @@ pub fn cmd_bookmark_rename(
         .set_local_bookmark_target(new_bookmark, ref_target);
     tx.repo_mut()
         .set_local_bookmark_target(old_bookmark, RefTarget::absent());
+    tx.check_protected_bookmarks()?;
 
     let remote_matcher = match default_ignored_remote_name(tx.repo().store()) {
         Some(remote) => StringExpression::exact(remote).negated().to_matcher(),
@@ -0,0 +1,174 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::op_store::RefTarget;
+use jj_lib::ref_name::RefNameBuf;
+use jj_lib::repo::Repo as _;
+use jj_lib::rewrite::merge_commit_trees;
+use jj_lib::str_util::StringMatcher;
+use pollster::FutureExt as _;
+
+use super::warn_unmatched_local_bookmarks;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::revset_util::parse_union_name_patterns;
+use crate::ui::Ui;
+
+/// Resolve conflicted bookmarks interactively
+///
+/// For each conflicted local bookmark, the revisions it points to are listed,
+/// and you can choose which one the bookmark should point to. Alternatively,
+/// you can create a merge commit of all of them and point the bookmark there,
+/// or delete the bookmark if it was deleted on one side of the conflict.
+///
+/// Tracked remote bookmarks are left as is. The next `jj git push` will update
+/// them to the resolved target.
+#[derive(clap::Args, Clone, Debug)]
+pub struct BookmarkResolveArgs {
+    /// The bookmarks to resolve (default: all conflicted bookmarks)
+    ///
+    /// By default, the specified pattern matches bookmark names with glob
+    /// syntax. You can also use other [string pattern syntax].
+    ///
+    /// [string pattern syntax]:
+    ///     https://docs.jj-vcs.dev/latest/revsets/#string-patterns
+    #[arg(add = ArgValueCandidates::new(complete::local_bookmarks))]
+    names: Option<Vec<String>>,
+}
+
+/// How to resolve a conflicted bookmark.
+enum Resolution {
+    Target(CommitId),
+    Merge,
+    Delete,
+    Skip,
+}
+
+pub fn cmd_bookmark_resolve(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BookmarkResolveArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo().clone();
+    let name_matcher = if let Some(names) = &args.names {
+        let name_expr = parse_union_name_patterns(ui, names)?;
+        warn_unmatched_local_bookmarks(ui, repo.view(), &name_expr)?;
+        name_expr.to_matcher()
+    } else {
+        StringMatcher::all()
+    };
+    let conflicted_bookmarks: Vec<(RefNameBuf, RefTarget)> = repo
+        .view()
+        .local_bookmarks_matching(&name_matcher)
+        .filter(|(_, target)| target.has_conflict())
+        .map(|(name, target)| (name.to_owned(), target.clone()))
+        .collect();
+    if conflicted_bookmarks.is_empty() {
+        writeln!(ui.status(), "No conflicted bookmarks to resolve.")?;
+        return Ok(());
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let mut resolved_names = vec![];
+    for (name, target) in &conflicted_bookmarks {
+        let target_ids = target.added_ids().unique().cloned().collect_vec();
+        let deleted_on_one_side = target.as_merge().adds().any(|id| id.is_none());
+        let resolution = {
+            let template = tx.commit_summary_template();
+            let mut formatter = ui.stderr_formatter();
+            writeln!(
+                formatter,
+                "Bookmark {name} is conflicted:",
+                name = name.as_symbol()
+            )?;
+            let mut choices = vec![];
+            for (i, id) in target_ids.iter().enumerate() {
+                let commit = tx.repo().store().get_commit(id)?;
+                write!(formatter, "{}: ", i + 1)?;
+                template.format(&commit, formatter.as_mut())?;
+                writeln!(formatter)?;
+                choices.push((format!("{}", i + 1), Resolution::Target(id.clone())));
+            }
+            writeln!(formatter, "m: create a merge commit of all targets")?;
+            choices.push(("m".to_owned(), Resolution::Merge));
+            if deleted_on_one_side {
+                writeln!(formatter, "d: delete the bookmark")?;
+                choices.push(("d".to_owned(), Resolution::Delete));
+            }
+            writeln!(formatter, "s: skip this bookmark")?;
+            choices.push(("s".to_owned(), Resolution::Skip));
+            drop(formatter);
+
+            let keys = choices.iter().map(|(key, _)| key).collect_vec();
+            let index = ui.prompt_choice(
+                &format!("choose how to resolve {name}", name = name.as_symbol()),
+                &keys,
+                None,
+            )?;
+            choices.swap_remove(index).1
+        };
+        let new_target = match resolution {
+            Resolution::Target(id) => RefTarget::normal(id),
+            Resolution::Merge => {
+                let parents: Vec<_> = target_ids
+                    .iter()
+                    .map(|id| tx.repo().store().get_commit(id))
+                    .try_collect()?;
+                let merged_tree = merge_commit_trees(tx.repo(), &parents).block_on()?;
+                let merge_commit = tx
+                    .repo_mut()
+                    .new_commit(target_ids.clone(), merged_tree)
+                    .write()?;
+                if let Some(mut formatter) = ui.status_formatter() {
+                    write!(formatter, "Created merge commit ")?;
+                    tx.write_commit_summary(formatter.as_mut(), &merge_commit)?;
+                    writeln!(formatter)?;
+                }
+                RefTarget::normal(merge_commit.id().clone())
+            }
+            Resolution::Delete => RefTarget::absent(),
+            Resolution::Skip => continue,
+        };
+        tx.repo_mut().set_local_bookmark_target(name, new_target);
+        resolved_names.push(name);
+    }
+    if resolved_names.is_empty() {
+        writeln!(ui.status(), "No bookmarks were resolved.")?;
+        return Ok(());
+    }
+    tx.check_protected_bookmarks()?;
+
+    writeln!(
+        ui.status(),
+        "Resolved {} bookmark{}.",
+        resolved_names.len(),
+        if resolved_names.len() == 1 { "" } else { "s" }
+    )?;
+    tx.finish(
+        ui,
+        format!(
+            "resolve bookmark {}",
+            resolved_names
+                .iter()
+                .map(|name| name.as_symbol())
+                .join(", ")
+        ),
+    )?;
+    Ok(())
+}
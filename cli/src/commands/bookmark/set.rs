@@ -116,6 +116,7 @@ pub fn cmd_bookmark_set(
             }
         }
     }
+    tx.check_protected_bookmarks()?;
 
     if let Some(mut formatter) = ui.status_formatter() {
         let new_bookmark_count = new_bookmarks.len();
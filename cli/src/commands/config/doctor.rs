@@ -0,0 +1,293 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use itertools::Itertools as _;
+use jj_lib::config::ConfigNamePathBuf;
+use jj_lib::config::ConfigSource;
+use jj_lib::config::StackedConfig;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::command_error::user_error;
+use crate::config::CONFIG_SCHEMA;
+use crate::config::to_serializable_value;
+use crate::ui::Ui;
+
+/// Check the config for problems
+///
+/// All loaded config files and `--config` arguments are validated against the
+/// config schema. Unknown keys, values of invalid type, and deprecated settings
+/// are reported along with the file they came from.
+///
+/// Since the schema doesn't cover variables defined by custom builds of jj,
+/// some of the unknown keys may be false positives.
+#[derive(clap::Args, Clone, Debug)]
+pub struct ConfigDoctorArgs {}
+
+#[instrument(skip_all)]
+pub fn cmd_config_doctor(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &ConfigDoctorArgs,
+) -> Result<(), CommandError> {
+    let schema: serde_json::Value =
+        serde_json::from_str(CONFIG_SCHEMA).expect("config schema should be valid JSON");
+    let checker = SchemaChecker { root: &schema };
+    let mut problem_count = 0;
+    let mut formatter = ui.stdout_formatter();
+    for layer in command.raw_config().as_ref().layers() {
+        // These layers are generated by jj, not written by the user.
+        if matches!(
+            layer.source,
+            ConfigSource::Default | ConfigSource::EnvBase | ConfigSource::EnvOverrides
+        ) {
+            continue;
+        }
+        let mut problems = vec![];
+        // Check the migrated layer so that deprecated variables aren't
+        // reported as unknown.
+        let mut config = StackedConfig::empty();
+        config.add_layer(layer.clone());
+        match jj_lib::config::migrate(&mut config, command.config_migrations()) {
+            Ok(descriptions) => problems.extend(
+                descriptions
+                    .into_iter()
+                    .map(|(_, desc)| format!("Deprecated setting: {desc}")),
+            ),
+            Err(err) => problems.push(err.to_string()),
+        }
+        let data = table_to_value(config.layers()[0].data.as_table());
+        checker.check(&schema, &ConfigNamePathBuf::root(), &data, &mut problems);
+        if problems.is_empty() {
+            continue;
+        }
+        match &layer.path {
+            Some(path) => writeln!(formatter, "{} config {}:", layer.source, path.display())?,
+            None => writeln!(formatter, "{} config:", layer.source)?,
+        }
+        for problem in &problems {
+            writeln!(formatter, "  {}", problem.replace('\n', "\n    "))?;
+        }
+        problem_count += problems.len();
+    }
+    drop(formatter);
+    if problem_count > 0 {
+        return Err(user_error(format!(
+            "Found {problem_count} problems in config"
+        )));
+    }
+    writeln!(ui.status(), "No problems found in config")?;
+    Ok(())
+}
+
+fn item_to_value(item: &toml_edit::Item) -> Option<toml::Value> {
+    match item {
+        toml_edit::Item::None => None,
+        toml_edit::Item::Value(value) => Some(to_serializable_value(value.clone())),
+        toml_edit::Item::Table(table) => Some(table_to_value(table)),
+        toml_edit::Item::ArrayOfTables(tables) => Some(toml::Value::Array(
+            tables.iter().map(table_to_value).collect(),
+        )),
+    }
+}
+
+fn table_to_value(table: &toml_edit::Table) -> toml::Value {
+    let table = table
+        .iter()
+        .filter_map(|(key, item)| Some((key.to_owned(), item_to_value(item)?)))
+        .collect();
+    toml::Value::Table(table)
+}
+
+fn value_type_name(value: &toml::Value) -> &'static str {
+    match value {
+        toml::Value::String(_) => "string",
+        toml::Value::Integer(_) => "integer",
+        toml::Value::Float(_) => "number",
+        toml::Value::Boolean(_) => "boolean",
+        toml::Value::Datetime(_) => "datetime",
+        toml::Value::Array(_) => "array",
+        toml::Value::Table(_) => "table",
+    }
+}
+
+fn type_matches(type_name: &str, value: &toml::Value) -> bool {
+    matches!(
+        (type_name, value),
+        ("string", toml::Value::String(_))
+            | ("integer", toml::Value::Integer(_))
+            | ("number", toml::Value::Integer(_) | toml::Value::Float(_))
+            | ("boolean", toml::Value::Boolean(_))
+            | ("array", toml::Value::Array(_))
+            | ("object", toml::Value::Table(_))
+    )
+}
+
+fn enum_variant_matches(variant: &serde_json::Value, value: &toml::Value) -> bool {
+    match (variant, value) {
+        (serde_json::Value::String(v), toml::Value::String(s)) => v == s,
+        (serde_json::Value::Bool(v), toml::Value::Boolean(b)) => v == b,
+        (serde_json::Value::Number(v), toml::Value::Integer(n)) => v.as_i64() == Some(*n),
+        _ => false,
+    }
+}
+
+/// Validates config values against the subset of JSON Schema used by the
+/// config schema.
+///
+/// Unlike JSON Schema, a table with known `properties` but without
+/// `additionalProperties` is considered closed, so unknown keys are reported.
+struct SchemaChecker<'a> {
+    root: &'a serde_json::Value,
+}
+
+impl<'a> SchemaChecker<'a> {
+    fn resolve(&self, mut schema: &'a serde_json::Value) -> &'a serde_json::Value {
+        while let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+            let Some(target) = reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.root.pointer(pointer))
+            else {
+                break;
+            };
+            schema = target;
+        }
+        schema
+    }
+
+    fn branches(&self, schema: &'a serde_json::Value) -> Option<&'a Vec<serde_json::Value>> {
+        schema
+            .get("oneOf")
+            .or_else(|| schema.get("anyOf"))
+            .and_then(|branches| branches.as_array())
+    }
+
+    /// Returns the type names accepted by the `schema`.
+    fn expected_types(&self, schema: &'a serde_json::Value) -> Vec<&'a str> {
+        let schema = self.resolve(schema);
+        if let Some(branches) = self.branches(schema) {
+            return branches
+                .iter()
+                .flat_map(|branch| self.expected_types(branch))
+                .unique()
+                .collect();
+        }
+        match schema.get("type") {
+            Some(serde_json::Value::String(name)) => vec![name],
+            Some(serde_json::Value::Array(names)) => {
+                names.iter().filter_map(|name| name.as_str()).collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    fn accepts_type(&self, schema: &'a serde_json::Value, value: &toml::Value) -> bool {
+        let types = self.expected_types(schema);
+        types.is_empty() || types.iter().any(|name| type_matches(name, value))
+    }
+
+    fn check(
+        &self,
+        schema: &'a serde_json::Value,
+        name: &ConfigNamePathBuf,
+        value: &toml::Value,
+        problems: &mut Vec<String>,
+    ) {
+        let schema = self.resolve(schema);
+        if let Some(branches) = self.branches(schema) {
+            let mut type_matched_problems = None;
+            for branch in branches {
+                let mut branch_problems = vec![];
+                self.check(branch, name, value, &mut branch_problems);
+                if branch_problems.is_empty() {
+                    return;
+                }
+                if type_matched_problems.is_none() && self.accepts_type(branch, value) {
+                    type_matched_problems = Some(branch_problems);
+                }
+            }
+            match type_matched_problems {
+                Some(branch_problems) => problems.extend(branch_problems),
+                None => problems.push(self.type_mismatch(schema, name, value)),
+            }
+            return;
+        }
+        if !self.accepts_type(schema, value) {
+            problems.push(self.type_mismatch(schema, name, value));
+            return;
+        }
+        if let Some(variants) = schema.get("enum").and_then(|v| v.as_array())
+            && !variants.iter().any(|v| enum_variant_matches(v, value))
+        {
+            problems.push(format!(
+                "{name}: Expected one of {}, but found {value}",
+                variants.iter().join(", ")
+            ));
+            return;
+        }
+        match value {
+            toml::Value::Table(table) => {
+                let properties = schema.get("properties").and_then(|p| p.as_object());
+                let additional = schema.get("additionalProperties");
+                for (key, value) in table {
+                    let mut name = name.clone();
+                    name.push(key.as_str());
+                    if let Some(schema) = properties.and_then(|p| p.get(key)) {
+                        self.check(schema, &name, value, problems);
+                        continue;
+                    }
+                    match additional {
+                        Some(serde_json::Value::Bool(true)) => {}
+                        Some(schema @ serde_json::Value::Object(_)) => {
+                            self.check(schema, &name, value, problems);
+                        }
+                        Some(_) => problems.push(format!("{name}: Unknown config key")),
+                        None if properties.is_some() => {
+                            problems.push(format!("{name}: Unknown config key"));
+                        }
+                        None => {}
+                    }
+                }
+            }
+            toml::Value::Array(items) => {
+                if let Some(schema) = schema.get("items") {
+                    for item in items {
+                        self.check(schema, name, item, problems);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn type_mismatch(
+        &self,
+        schema: &'a serde_json::Value,
+        name: &ConfigNamePathBuf,
+        value: &toml::Value,
+    ) -> String {
+        let expected = self
+            .expected_types(schema)
+            .iter()
+            .map(|name| if *name == "object" { "table" } else { name })
+            .join(" or ");
+        format!(
+            "{name}: Expected {expected}, but found {}",
+            value_type_name(value)
+        )
+    }
+}
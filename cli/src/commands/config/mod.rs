@@ -12,11 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod doctor;
 mod edit;
 mod get;
 mod list;
 mod path;
 mod set;
+mod trust;
 mod unset;
 
 use std::path::PathBuf;
@@ -26,6 +28,8 @@ use jj_lib::config::ConfigFile;
 use jj_lib::config::ConfigSource;
 use tracing::instrument;
 
+use self::doctor::ConfigDoctorArgs;
+use self::doctor::cmd_config_doctor;
 use self::edit::ConfigEditArgs;
 use self::edit::cmd_config_edit;
 use self::get::ConfigGetArgs;
@@ -36,6 +40,8 @@ use self::path::ConfigPathArgs;
 use self::path::cmd_config_path;
 use self::set::ConfigSetArgs;
 use self::set::cmd_config_set;
+use self::trust::ConfigTrustArgs;
+use self::trust::cmd_config_trust;
 use self::unset::ConfigUnsetArgs;
 use self::unset::cmd_config_unset;
 use crate::cli_util::CommandHelper;
@@ -153,6 +159,7 @@ impl ConfigLevelArgs {
 ///     https://docs.jj-vcs.dev/latest/config/
 #[derive(clap::Subcommand, Clone, Debug)]
 pub(crate) enum ConfigCommand {
+    Doctor(ConfigDoctorArgs),
     #[command(visible_alias("e"))]
     Edit(ConfigEditArgs),
     #[command(visible_alias("g"))]
@@ -163,6 +170,7 @@ pub(crate) enum ConfigCommand {
     Path(ConfigPathArgs),
     #[command(visible_alias("s"))]
     Set(ConfigSetArgs),
+    Trust(ConfigTrustArgs),
     #[command(visible_alias("u"))]
     Unset(ConfigUnsetArgs),
 }
@@ -174,11 +182,13 @@ pub(crate) fn cmd_config(
     subcommand: &ConfigCommand,
 ) -> Result<(), CommandError> {
     match subcommand {
+        ConfigCommand::Doctor(args) => cmd_config_doctor(ui, command, args),
         ConfigCommand::Edit(args) => cmd_config_edit(ui, command, args),
         ConfigCommand::Get(args) => cmd_config_get(ui, command, args),
         ConfigCommand::List(args) => cmd_config_list(ui, command, args),
         ConfigCommand::Path(args) => cmd_config_path(ui, command, args),
         ConfigCommand::Set(args) => cmd_config_set(ui, command, args),
+        ConfigCommand::Trust(args) => cmd_config_trust(ui, command, args),
         ConfigCommand::Unset(args) => cmd_config_unset(ui, command, args),
     }
 }
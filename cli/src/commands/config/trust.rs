@@ -0,0 +1,49 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::command_error::user_error_with_hint;
+use crate::ui::Ui;
+
+/// Trust the config file checked in to the repository
+///
+/// A repository can share settings such as revset aliases with its users by
+/// checking in a `.config/jj/config.toml` file at the workspace root. Since
+/// config can run arbitrary commands (e.g. through aliases), the file is loaded
+/// only if its current content has been trusted. If the file changes, it has to
+/// be trusted again.
+#[derive(clap::Args, Clone, Debug)]
+pub struct ConfigTrustArgs {}
+
+#[instrument(skip_all)]
+pub fn cmd_config_trust(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &ConfigTrustArgs,
+) -> Result<(), CommandError> {
+    command.workspace_loader()?;
+    let Some(path) = command.config_env().trust_repo_managed_config(ui)? else {
+        return Err(user_error_with_hint(
+            "No repo-managed config file found",
+            "The file should be located at `.config/jj/config.toml` in the workspace root.",
+        ));
+    };
+    writeln!(ui.status(), "Trusted config file {}", path.display())?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::io::Write as _;
+
+use jj_lib::default_index::DefaultIndexStore;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::command_error::internal_error;
+use crate::command_error::user_error;
+use crate::ui::Ui;
+
+/// Merge commit index segments left by incremental updates
+///
+/// This is run in the background after operations that leave the index
+/// fragmented, unless `gc.auto-compact-index` is disabled.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugCompactIndexArgs {}
+
+pub fn cmd_debug_compact_index(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &DebugCompactIndexArgs,
+) -> Result<(), CommandError> {
+    // Resolve the operation without loading the repo, so this command won't
+    // update the index before compacting it.
+    let workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let op = command.resolve_operation(ui, repo_loader)?;
+    let index_store = repo_loader.index_store();
+    if let Some(default_index_store) = index_store.downcast_ref::<DefaultIndexStore>() {
+        let default_index = default_index_store
+            .compact_index_at_operation(op.id(), repo_loader.store())
+            .map_err(internal_error)?;
+        writeln!(
+            ui.status(),
+            "Compacted index of {} commits.",
+            default_index.num_commits()
+        )?;
+    } else {
+        return Err(user_error(format!(
+            "Cannot compact indexes of type '{}'",
+            index_store.name()
+        )));
+    }
+    Ok(())
+}
@@ -39,7 +39,12 @@ pub fn cmd_debug_fileset(
     let path_converter = workspace_command.path_converter();
 
     let mut diagnostics = FilesetDiagnostics::new();
-    let expression = fileset::parse_maybe_bare(&mut diagnostics, &args.path, path_converter)?;
+    let expression = fileset::parse_maybe_bare(
+        &mut diagnostics,
+        &args.path,
+        path_converter,
+        command.revset_extensions().fileset(),
+    )?;
     print_parse_diagnostics(ui, "In fileset expression", &diagnostics)?;
     writeln!(ui.stdout(), "-- Parsed:")?;
     writeln!(ui.stdout(), "{expression:#?}")?;
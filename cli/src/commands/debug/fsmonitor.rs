@@ -0,0 +1,143 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(feature = "builtin-fsmonitor")]
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use clap::Subcommand;
+#[cfg(feature = "builtin-fsmonitor")]
+use jj_lib::fsmonitor::BuiltinFsmonitorConfig;
+#[cfg(feature = "builtin-fsmonitor")]
+use jj_lib::fsmonitor::FsmonitorSettings;
+#[cfg(feature = "builtin-fsmonitor")]
+use jj_lib::fsmonitor::builtin;
+
+#[cfg(feature = "builtin-fsmonitor")]
+use super::check_local_disk_wc;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+#[cfg(feature = "builtin-fsmonitor")]
+use crate::command_error::internal_error;
+#[cfg(not(feature = "builtin-fsmonitor"))]
+use crate::command_error::user_error;
+use crate::ui::Ui;
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum DebugFsmonitorCommand {
+    /// Check whether the builtin filesystem monitor is enabled and running
+    Status,
+    /// List the files changed since the last snapshot, as reported by the
+    /// running daemon
+    QueryChangedFiles,
+    /// Ask the filesystem monitor daemon to exit
+    Stop,
+    /// Run the filesystem monitor daemon in the foreground
+    ///
+    /// This is normally started in the background by jj itself.
+    Daemon {
+        /// Root of the working copy to watch
+        #[arg(long, value_hint = clap::ValueHint::DirPath)]
+        working_copy: PathBuf,
+        /// Directory to store the journal and the lock file in
+        #[arg(long, value_hint = clap::ValueHint::DirPath)]
+        state_dir: PathBuf,
+    },
+}
+
+#[cfg(feature = "builtin-fsmonitor")]
+pub fn cmd_debug_fsmonitor(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &DebugFsmonitorCommand,
+) -> Result<(), CommandError> {
+    if let DebugFsmonitorCommand::Daemon {
+        working_copy,
+        state_dir,
+    } = subcommand
+    {
+        // The daemon must not load the workspace, which would snapshot the
+        // working copy and query the daemon itself.
+        return builtin::run_daemon(working_copy, state_dir).map_err(internal_error);
+    }
+
+    let workspace_command = command.workspace_helper(ui)?;
+    let wc = check_local_disk_wc(workspace_command.working_copy())?;
+    let fsmonitor = wc.builtin_fsmonitor();
+    match subcommand {
+        DebugFsmonitorCommand::Status => {
+            match FsmonitorSettings::from_settings(workspace_command.settings())? {
+                FsmonitorSettings::Builtin(config) => {
+                    writeln!(
+                        ui.stdout(),
+                        "The builtin filesystem monitor is enabled via `fsmonitor.backend`."
+                    )?;
+                    writeln!(
+                        ui.stdout(),
+                        "The daemon is {}. Use `fsmonitor.builtin.auto-start` to control it.",
+                        if config.auto_start {
+                            "started automatically"
+                        } else {
+                            "not started automatically"
+                        }
+                    )?;
+                }
+                _ => {
+                    writeln!(
+                        ui.stdout(),
+                        r#"The builtin filesystem monitor is disabled. Set `fsmonitor.backend="builtin"` to enable."#
+                    )?;
+                }
+            }
+            writeln!(
+                ui.stdout(),
+                "The daemon is currently {}.",
+                if fsmonitor.is_daemon_running().map_err(internal_error)? {
+                    "running"
+                } else {
+                    "not running"
+                }
+            )?;
+        }
+        DebugFsmonitorCommand::QueryChangedFiles => {
+            let config = BuiltinFsmonitorConfig {
+                auto_start: false,
+                daemon_command: None,
+            };
+            let (_clock, changed_files) = wc.query_builtin_fsmonitor(&config)?;
+            writeln!(ui.stdout(), "Changed files: {changed_files:?}")?;
+        }
+        DebugFsmonitorCommand::Stop => {
+            if fsmonitor.stop_daemon().map_err(internal_error)? {
+                writeln!(ui.status(), "Asked the filesystem monitor daemon to exit")?;
+            } else {
+                writeln!(ui.status(), "The filesystem monitor daemon is not running")?;
+            }
+        }
+        DebugFsmonitorCommand::Daemon { .. } => unreachable!(),
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "builtin-fsmonitor"))]
+pub fn cmd_debug_fsmonitor(
+    _ui: &mut Ui,
+    _command: &CommandHelper,
+    _subcommand: &DebugFsmonitorCommand,
+) -> Result<(), CommandError> {
+    Err(user_error(
+        "Cannot use the builtin filesystem monitor because jj was not compiled with the \
+         `builtin-fsmonitor` feature",
+    ))
+}
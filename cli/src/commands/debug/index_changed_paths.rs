@@ -20,6 +20,7 @@ use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
 use crate::command_error::internal_error;
 use crate::command_error::user_error;
+use crate::progress::progress_reporter;
 use crate::ui::Ui;
 
 /// Build changed-path index
@@ -45,8 +46,14 @@ pub fn cmd_debug_index_changed_paths(
             index_store.name()
         )));
     };
+    let progress = progress_reporter(ui);
     let index = default_index_store
-        .build_changed_path_index_at_operation(repo.op_id(), repo.store(), args.limit)
+        .build_changed_path_index_at_operation(
+            repo.op_id(),
+            repo.store(),
+            args.limit,
+            progress.as_ref().map(|x| x as _),
+        )
         .block_on()
         .map_err(internal_error)?;
     let stats = index.stats();
@@ -37,6 +37,21 @@ pub(crate) struct DebugInitSimpleArgs {
     /// The destination directory
     #[arg(default_value = ".", value_hint = clap::ValueHint::DirPath)]
     destination: String,
+
+    /// Encrypt the objects stored in the repo
+    ///
+    /// The encryption key is read from the output of the command configured
+    /// in `simple-backend.encryption-key-command`. The command has to be
+    /// configured whenever the repo is used.
+    #[arg(long)]
+    encrypt: bool,
+
+    /// Store the objects through the given backend plugin
+    ///
+    /// The plugin's command is read from `backend-plugins.<name>.command`,
+    /// which has to be configured whenever the repo is used.
+    #[arg(long, value_name = "NAME", conflicts_with = "encrypt")]
+    backend_plugin: Option<String>,
 }
 
 #[instrument(skip_all)]
@@ -57,10 +72,14 @@ pub(crate) fn cmd_debug_init_simple(
         .and_then(|_| dunce::canonicalize(wc_path))
         .map_err(|e| user_error_with_message("Failed to create workspace", e))?;
 
-    Workspace::init_simple(
-        &command.settings_for_new_workspace(ui, &wc_path)?.0,
-        &wc_path,
-    )?;
+    let settings = command.settings_for_new_workspace(ui, &wc_path)?.0;
+    if let Some(plugin_name) = &args.backend_plugin {
+        Workspace::init_with_backend_plugin(&settings, &wc_path, plugin_name)?;
+    } else if args.encrypt {
+        Workspace::init_simple_encrypted(&settings, &wc_path)?;
+    } else {
+        Workspace::init_simple(&settings, &wc_path)?;
+    }
 
     let relative_wc_path = file_util::relative_path(cwd, &wc_path);
     writeln!(
@@ -12,8 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod compact_index;
 mod copy_detection;
 mod fileset;
+mod fsmonitor;
 mod index;
 mod index_changed_paths;
 mod init_simple;
@@ -32,10 +34,14 @@ use clap::Subcommand;
 use jj_lib::local_working_copy::LocalWorkingCopy;
 use jj_lib::working_copy::WorkingCopy;
 
+use self::compact_index::DebugCompactIndexArgs;
+use self::compact_index::cmd_debug_compact_index;
 use self::copy_detection::CopyDetectionArgs;
 use self::copy_detection::cmd_debug_copy_detection;
 use self::fileset::DebugFilesetArgs;
 use self::fileset::cmd_debug_fileset;
+use self::fsmonitor::DebugFsmonitorCommand;
+use self::fsmonitor::cmd_debug_fsmonitor;
 use self::index::DebugIndexArgs;
 use self::index::cmd_debug_index;
 use self::index_changed_paths::DebugIndexChangedPathsArgs;
@@ -71,8 +77,11 @@ use crate::ui::Ui;
 #[derive(Subcommand, Clone, Debug)]
 #[command(hide = true)]
 pub enum DebugCommand {
+    CompactIndex(DebugCompactIndexArgs),
     CopyDetection(CopyDetectionArgs),
     Fileset(DebugFilesetArgs),
+    #[command(subcommand)]
+    Fsmonitor(DebugFsmonitorCommand),
     Index(DebugIndexArgs),
     IndexChangedPaths(DebugIndexChangedPathsArgs),
     InitSimple(DebugInitSimpleArgs),
@@ -96,8 +105,10 @@ pub fn cmd_debug(
     subcommand: &DebugCommand,
 ) -> Result<(), CommandError> {
     match subcommand {
+        DebugCommand::CompactIndex(args) => cmd_debug_compact_index(ui, command, args),
         DebugCommand::CopyDetection(args) => cmd_debug_copy_detection(ui, command, args),
         DebugCommand::Fileset(args) => cmd_debug_fileset(ui, command, args),
+        DebugCommand::Fsmonitor(args) => cmd_debug_fsmonitor(ui, command, args),
         DebugCommand::Index(args) => cmd_debug_index(ui, command, args),
         DebugCommand::IndexChangedPaths(args) => cmd_debug_index_changed_paths(ui, command, args),
         DebugCommand::InitSimple(args) => cmd_debug_init_simple(ui, command, args),
@@ -22,6 +22,7 @@ use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
 use crate::command_error::internal_error;
 use crate::command_error::user_error;
+use crate::progress::progress_reporter;
 use crate::ui::Ui;
 
 /// Rebuild commit index
@@ -41,8 +42,9 @@ pub fn cmd_debug_reindex(
     let index_store = repo_loader.index_store();
     if let Some(default_index_store) = index_store.downcast_ref::<DefaultIndexStore>() {
         default_index_store.reinit().map_err(internal_error)?;
+        let progress = progress_reporter(ui);
         let default_index = default_index_store
-            .build_index_at_operation(&op, repo_loader.store())
+            .build_index_at_operation(&op, repo_loader.store(), progress.as_ref().map(|x| x as _))
             .block_on()
             .map_err(internal_error)?;
         writeln!(
@@ -289,7 +289,7 @@ pub(crate) fn cmd_describe(
 
     // Filter out unchanged commits to avoid rebasing descendants in
     // `transform_descendants` below unnecessarily.
-    let commit_builders: HashMap<_, _> = iter::zip(&commits, commit_builders)
+    let mut commit_builders: HashMap<_, _> = iter::zip(&commits, commit_builders)
         .filter(|(old_commit, commit_builder)| {
             old_commit.description() != commit_builder.description()
                 || args.reset_author
@@ -303,6 +303,21 @@ pub(crate) fn cmd_describe(
 
     let mut num_described = 0;
     let mut num_reparented = 0;
+    if !tx.rebases_descendants_eagerly()? {
+        // Only rewrite the described commits, and let the transaction defer
+        // rebasing their descendants.
+        for commit in &commits {
+            if let Some(commit_builder) = commit_builders.remove(commit.id()) {
+                commit_builder.attach(tx.repo_mut()).write()?;
+                num_described += 1;
+            }
+        }
+        if num_described > 1 {
+            writeln!(ui.status(), "Updated {num_described} commits")?;
+        }
+        tx.finish(ui, tx_description)?;
+        return Ok(());
+    }
     // Even though `MutableRepo::rewrite_commit` and
     // `MutableRepo::rebase_descendants` can handle rewriting of a commit even
     // if it is a descendant of another commit being rewritten, using
@@ -120,12 +120,10 @@ pub(crate) fn cmd_diff(
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
     let repo = workspace_command.repo();
-    let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
-    let matcher = fileset_expression.to_matcher();
-
     let from_tree;
     let to_tree;
-    let mut copy_records = CopyRecords::default();
+    // Pairs of commits to look up copies between
+    let mut copy_sources = vec![];
     if args.from.is_some() || args.to.is_some() {
         let resolve_revision = |r: &Option<RevisionArg>| {
             workspace_command.resolve_single_rev(ui, r.as_ref().unwrap_or(&RevisionArg::AT))
@@ -135,8 +133,7 @@ pub(crate) fn cmd_diff(
         from_tree = from.tree();
         to_tree = to.tree();
 
-        let records = get_copy_records(repo.store(), from.id(), to.id(), &matcher)?;
-        copy_records.add_records(records)?;
+        copy_sources.push((from.id().clone(), to.id().clone()));
     } else {
         let revision_args = args
             .revisions
@@ -178,12 +175,20 @@ pub(crate) fn cmd_diff(
 
         for p in &parents {
             for to in &heads {
-                let records = get_copy_records(repo.store(), p.id(), to.id(), &matcher)?;
-                copy_records.add_records(records)?;
+                copy_sources.push((p.id().clone(), to.id().clone()));
             }
         }
     }
 
+    let fileset_expression =
+        workspace_command.parse_file_patterns_in_trees(ui, &args.paths, &[&from_tree, &to_tree])?;
+    let matcher = fileset_expression.to_matcher();
+    let mut copy_records = CopyRecords::default();
+    for (from_id, to_id) in &copy_sources {
+        let records = get_copy_records(repo.store(), from_id, to_id, &matcher)?;
+        copy_records.add_records(records)?;
+    }
+
     // -T disables both short/long rendering formats, but it might be okay to
     // enable long format if explicitly specified (assuming -T is for short or
     // summary output.)
@@ -153,7 +153,7 @@ don't make any changes, then the operation will be aborted.",
                 " (while preserving their content)",
             )
         } else {
-            (tx.repo_mut().rebase_descendants()?, "")
+            (tx.rebase_descendants(ui)?, "")
         };
         if let Some(mut formatter) = ui.status_formatter()
             && num_rebased > 0
@@ -41,6 +41,9 @@ use crate::ui::Ui;
 ///
 /// Lists the previous commits which a change has pointed to. The current commit
 /// of a change evolves when the change is updated, rebased, etc.
+///
+/// Use `jj restore --from-evolog` to restore a change to one of its earlier
+/// versions.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct EvologArgs {
     /// Follow changes from these revisions
@@ -63,7 +63,8 @@ pub(crate) fn cmd_file_list(
     let workspace_command = command.workspace_helper(ui)?;
     let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
     let tree = commit.tree();
-    let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
+    let fileset_expression =
+        workspace_command.parse_file_patterns_in_trees(ui, &args.paths, &[&tree])?;
     let matcher = fileset_expression.to_matcher();
     let template: TemplateRenderer<TreeEntry> = {
         let language = workspace_command.commit_template_language();
@@ -51,6 +51,14 @@ pub(crate) struct FileTrackArgs {
     /// restrictions, explicitly tracking the specified paths.
     #[arg(long)]
     include_ignored: bool,
+
+    /// Keep tracking paths even if they're ignored or too large
+    ///
+    /// Like `--include-ignored`, but the tracked files are also recorded in
+    /// the working copy, so they're tracked again if they're deleted and
+    /// re-created later. Use `jj file untrack` to remove them from the record.
+    #[arg(long)]
+    force: bool,
 }
 
 #[instrument(skip_all)]
@@ -65,13 +73,22 @@ pub(crate) fn cmd_file_track(
         .to_matcher();
 
     let mut options = workspace_command.snapshot_options_with_start_tracking_matcher(&matcher)?;
-    if args.include_ignored {
+    if args.include_ignored || args.force {
         options.force_tracking_matcher = &matcher;
     }
 
     let mut tx = workspace_command.start_transaction().into_inner();
     let (mut locked_ws, _wc_commit) = workspace_command.start_working_copy_mutation()?;
-    let (_tree, track_stats) = locked_ws.locked_wc().snapshot(&options).block_on()?;
+    let (tree, track_stats) = locked_ws.locked_wc().snapshot(&options).block_on()?;
+    if args.force {
+        let locked_wc = locked_ws.locked_wc();
+        let mut force_tracked_paths = locked_wc.force_tracked_paths()?.to_vec();
+        force_tracked_paths.extend(
+            tree.entries_matching(matcher.as_ref())
+                .map(|(path, _value)| path),
+        );
+        locked_wc.set_force_tracked_paths(force_tracked_paths)?;
+    }
     let num_rebased = tx.repo_mut().rebase_descendants()?;
     if num_rebased > 0 {
         writeln!(ui.status(), "Rebased {num_rebased} descendant commits")?;
@@ -111,7 +128,7 @@ pub fn print_track_snapshot_stats(
         .iter()
         .filter_map(|(path, reason)| match reason {
             UntrackedReason::FileTooLarge { size, .. } => Some((path, *size)),
-            UntrackedReason::FileNotAutoTracked => None,
+            UntrackedReason::FileNotAutoTracked | UntrackedReason::CaseCollision { .. } => None,
         })
         .unzip();
     if let Some(size) = sizes.iter().max() {
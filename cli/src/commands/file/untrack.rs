@@ -31,6 +31,9 @@ use crate::complete;
 use crate::ui::Ui;
 
 /// Stop tracking specified paths in the working copy
+///
+/// Paths which were tracked with `jj file track --force` are no longer tracked
+/// regardless of the ignore patterns.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct FileUntrackArgs {
     /// Paths to untrack. They must already be ignored.
@@ -71,6 +74,18 @@ pub(crate) fn cmd_file_untrack(
         .rewrite_commit(&wc_commit)
         .set_tree(new_tree)
         .write()?;
+    // Stop forcing the paths to be tracked
+    let force_tracked_paths = locked_ws.locked_wc().force_tracked_paths()?;
+    if force_tracked_paths.iter().any(|path| matcher.matches(path)) {
+        let remaining_paths = force_tracked_paths
+            .iter()
+            .filter(|path| !matcher.matches(path))
+            .cloned()
+            .collect();
+        locked_ws
+            .locked_wc()
+            .set_force_tracked_paths(remaining_paths)?;
+    }
     // Reset the working copy to the new commit
     locked_ws.locked_wc().reset(&new_commit).block_on()?;
     // Commit the working copy again so we can inform the user if paths couldn't be
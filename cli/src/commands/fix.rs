@@ -12,24 +12,35 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
 use std::io::Write as _;
+use std::iter;
 use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
 use std::process::Stdio;
 
 use clap_complete::ArgValueCompleter;
 use itertools::Itertools as _;
-use jj_lib::backend::FileId;
 use jj_lib::commit::Commit;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
+use jj_lib::fileset::FilesetExtensions;
 use jj_lib::fix::FileToFix;
+use jj_lib::fix::FixCache;
+use jj_lib::fix::FixCacheKey;
 use jj_lib::fix::FixError;
+use jj_lib::fix::FixedFile;
 use jj_lib::fix::ParallelFileFixer;
 use jj_lib::fix::fix_files;
 use jj_lib::matchers::Matcher;
 use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::repo_path::RepoPathUiConverter;
 use jj_lib::revset::RevsetIteratorExt as _;
 use jj_lib::settings::UserSettings;
@@ -40,6 +51,7 @@ use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::print_unmatched_explicit_paths;
 use crate::command_error::CommandError;
 use crate::command_error::config_error;
@@ -66,6 +78,14 @@ use crate::ui::Ui;
 /// with existing conflicts will be updated on all sides of the conflict, which
 /// can potentially increase or decrease the number of conflict markers.
 ///
+/// The output of tools with `cache = true` is cached in the repo, keyed by the
+/// tool's command line and executable, the files listed in its `cache-inputs`,
+/// and the content it was given. Running such a tool on content it has already
+/// seen, for example in other revisions of a stack or when running `jj fix`
+/// again, reuses the cached output instead of running the tool. Use
+/// `--no-cache` to run the tools anyway. Entries that haven't been used
+/// recently are removed by `jj util gc`.
+///
 /// ### Configuration
 ///
 /// See `jj help -k config` chapter `Code formatting and other file content
@@ -162,6 +182,11 @@ pub(crate) struct FixArgs {
     /// specified, all files in the repo will be fixed.
     #[arg(long)]
     include_unchanged_files: bool,
+
+    /// Run the tools even if their output for the same input is cached, and
+    /// don't cache the new output
+    #[arg(long)]
+    no_cache: bool,
 }
 
 #[instrument(skip_all)]
@@ -173,7 +198,13 @@ pub(crate) fn cmd_fix(
     let mut workspace_command = command.workspace_helper(ui)?;
     let workspace_root = workspace_command.workspace_root().to_owned();
     let path_converter = workspace_command.path_converter().to_owned();
-    let tools_config = get_tools_config(ui, workspace_command.settings())?;
+    let tools_config = get_tools_config(
+        ui,
+        workspace_command.settings(),
+        command.revset_extensions().fileset(),
+        &workspace_root,
+    )?;
+    let fix_cache = (!args.no_cache).then(|| fix_cache(&workspace_command));
     let target_expr = if args.source.is_empty() {
         let revs = workspace_command.settings().get_string("revsets.fix")?;
         workspace_command.parse_revset(ui, &RevisionArg::from(revs))?
@@ -209,6 +240,7 @@ pub(crate) fn cmd_fix(
             &workspace_root,
             &path_converter,
             &tools_config,
+            fix_cache.as_ref(),
             store,
             file_to_fix,
         )
@@ -236,7 +268,8 @@ pub(crate) fn cmd_fix(
 
 /// Invokes all matching tools (if any) to file_to_fix. If the content is
 /// successfully transformed the new content is written and the new FileId is
-/// returned. Returns None if the content is unchanged.
+/// returned, along with any rename or created files reported by tools with
+/// JSON output. Returns None if nothing changed.
 ///
 /// The matching tools are invoked in order, with the result of one tool feeding
 /// into the next tool. Returns FixError if there is an error reading or writing
@@ -244,6 +277,10 @@ pub(crate) fn cmd_fix(
 /// is simply skipped and we proceed to invoke the next tool (this is
 /// indistinguishable from succeeding with no changes).
 ///
+/// If `fix_cache` is given, the output of a tool with caching enabled that was
+/// already run on the same content is taken from the cache instead of running
+/// the tool again. Only the output of successful tool invocations is cached.
+///
 /// TODO: Better error handling so we can tell the user what went wrong with
 /// each failed input.
 async fn fix_one_file(
@@ -251,51 +288,216 @@ async fn fix_one_file(
     workspace_root: &Path,
     path_converter: &RepoPathUiConverter,
     tools_config: &ToolsConfig,
+    fix_cache: Option<&FixCache>,
     store: &Store,
     file_to_fix: &FileToFix,
-) -> Result<Option<FileId>, FixError> {
+) -> Result<Option<FixedFile>, FixError> {
     let mut matching_tools = tools_config
         .tools
         .iter()
         .filter(|tool_config| tool_config.matcher.matches(&file_to_fix.repo_path))
         .peekable();
-    if matching_tools.peek().is_some() {
-        // The first matching tool gets its input from the committed file, and any
-        // subsequent matching tool gets its input from the previous matching tool's
-        // output.
-        let mut old_content = vec![];
-        let mut read = store
-            .read_file(&file_to_fix.repo_path, &file_to_fix.file_id)
-            .await?;
-        read.read_to_end(&mut old_content).await?;
-        let new_content = matching_tools.fold(old_content.clone(), |prev_content, tool_config| {
-            match run_tool(
+    if matching_tools.peek().is_none() {
+        return Ok(None);
+    }
+    // The first matching tool gets its input from the committed file, and any
+    // subsequent matching tool gets its input from the previous matching tool's
+    // output.
+    let mut old_content = vec![];
+    let mut read = store
+        .read_file(&file_to_fix.repo_path, &file_to_fix.file_id)
+        .await?;
+    read.read_to_end(&mut old_content).await?;
+    let mut new_content = old_content.clone();
+    let mut new_path = None;
+    let mut new_files = BTreeMap::new();
+    for tool_config in matching_tools {
+        let command = tool_command_for_file(workspace_root, &tool_config.command, file_to_fix);
+        let fix_cache = fix_cache.filter(|_| tool_config.cache_salt.is_some());
+        let cache_key = fix_cache.map(|_| tool_cache_key(&command, tool_config, &new_content));
+        let cached_stdout = match (fix_cache, &cache_key) {
+            (Some(fix_cache), Some(cache_key)) => fix_cache.get(cache_key)?,
+            _ => None,
+        };
+        let stdout = if let Some(stdout) = cached_stdout {
+            tracing::debug!(?command, ?file_to_fix.repo_path, "using cached fix tool output");
+            stdout
+        } else {
+            let Ok(stdout) = run_tool(
                 ui,
                 workspace_root,
                 path_converter,
                 &tool_config.command,
+                command,
                 file_to_fix,
-                &prev_content,
-            ) {
-                Ok(next_content) => next_content,
+                &new_content,
+            ) else {
                 // TODO: Because the stderr is passed through, this isn't always failing
                 // silently, but it should do something better will the exit code, tool
                 // name, etc.
-                Err(()) => prev_content,
+                continue;
+            };
+            if let (Some(fix_cache), Some(cache_key)) = (fix_cache, &cache_key) {
+                fix_cache.insert(cache_key, &stdout)?;
+            }
+            stdout
+        };
+        match tool_config.output {
+            ToolOutputFormat::Content => new_content = stdout,
+            ToolOutputFormat::Json => {
+                let Ok(output) = parse_json_output(
+                    ui,
+                    path_converter,
+                    &tool_config.command,
+                    file_to_fix,
+                    &stdout,
+                ) else {
+                    continue;
+                };
+                if let Some(content) = output.content {
+                    new_content = content;
+                }
+                if let Some(path) = output.rename {
+                    new_path = Some(path);
+                }
+                new_files.extend(output.create);
             }
-        });
-        if new_content != old_content {
-            // TODO: send futures back over channel
-            let new_file_id = store
-                .write_file(&file_to_fix.repo_path, &mut new_content.as_slice())
-                .await?;
-            return Ok(Some(new_file_id));
         }
     }
-    Ok(None)
+    let mut fixed_file = FixedFile {
+        new_path: new_path.filter(|path| *path != file_to_fix.repo_path),
+        ..FixedFile::default()
+    };
+    if new_content != old_content {
+        // TODO: send futures back over channel
+        let new_file_id = store
+            .write_file(&file_to_fix.repo_path, &mut new_content.as_slice())
+            .await?;
+        fixed_file.file_id = Some(new_file_id);
+    }
+    for (path, content) in new_files {
+        let file_id = store.write_file(&path, &mut content.as_slice()).await?;
+        fixed_file.new_files.push((path, file_id));
+    }
+    if fixed_file == FixedFile::default() {
+        Ok(None)
+    } else {
+        Ok(Some(fixed_file))
+    }
+}
+
+/// Changes requested by a tool with JSON output.
+struct ParsedToolOutput {
+    content: Option<Vec<u8>>,
+    rename: Option<RepoPathBuf>,
+    create: BTreeMap<RepoPathBuf, Vec<u8>>,
+}
+
+/// Parses the standard output of a tool with `output = "json"`.
+///
+/// Prints a warning and returns `Err` if the output is malformed, in which case
+/// the tool is skipped like a failing tool.
+fn parse_json_output(
+    ui: &Ui,
+    path_converter: &RepoPathUiConverter,
+    tool_command: &CommandNameAndArgs,
+    file_to_fix: &FileToFix,
+    stdout: &[u8],
+) -> Result<ParsedToolOutput, ()> {
+    let warn = |message: String| {
+        writeln!(
+            ui.warning_default(),
+            "Fix tool `{}` produced invalid output for `{}`: {message}",
+            tool_command.split_name(),
+            path_converter.format_file_path(&file_to_fix.repo_path)
+        )
+        .ok();
+    };
+    let parse_path = |path: String| {
+        let path = RepoPathBuf::from_internal_string(path).map_err(|err| warn(err.to_string()))?;
+        // Reject paths like `../foo` which can't be checked out.
+        path.to_fs_path(Path::new(""))
+            .map_err(|err| warn(err.to_string()))?;
+        if path.is_root() {
+            warn("Empty path".to_owned());
+            return Err(());
+        }
+        Ok(path)
+    };
+    let output: JsonToolOutput = serde_json::from_slice(stdout).map_err(|err| {
+        warn(err.to_string());
+    })?;
+    Ok(ParsedToolOutput {
+        content: output.content.map(String::into_bytes),
+        rename: output.rename.map(parse_path).transpose()?,
+        create: output
+            .create
+            .into_iter()
+            .map(|(path, content)| Ok((parse_path(path)?, content.into_bytes())))
+            .try_collect()?,
+    })
+}
+
+/// Builds the process for running `tool_command` on the given file, with the
+/// `$path` and `$root` variables substituted.
+fn tool_command_for_file(
+    workspace_root: &Path,
+    tool_command: &CommandNameAndArgs,
+    file_to_fix: &FileToFix,
+) -> Command {
+    let mut vars: HashMap<&str, &str> = HashMap::new();
+    vars.insert("path", file_to_fix.repo_path.as_internal_file_string());
+    // TODO: workspace_root.to_str() returns None if the workspace path is not
+    // UTF-8, but we ignore that failure so `jj fix` still runs in that
+    // situation. Maybe we should do something like substituting bytes instead
+    // of strings so we can handle any Path here.
+    if let Some(root) = workspace_root.to_str() {
+        vars.insert("root", root);
+    }
+    tool_command.to_command_with_variables(&vars)
+}
+
+/// Returns the cache of tool outputs stored in the repo directory.
+pub fn fix_cache(workspace_command: &WorkspaceCommandHelper) -> FixCache {
+    FixCache::new(workspace_command.repo_path().join("fix_cache"))
+}
+
+/// Computes the [FixCacheKey] for running `command` on `content`.
+///
+/// The key covers the program, the arguments after variable substitution, the
+/// environment variables set by the tool config, how the output is
+/// interpreted, and the tool's [`ToolConfig::cache_salt`]. The file path only
+/// affects the key if it's passed to the tool.
+fn tool_cache_key(command: &Command, tool_config: &ToolConfig, content: &[u8]) -> FixCacheKey {
+    let output_format: &[u8] = match tool_config.output {
+        ToolOutputFormat::Content => b"content",
+        ToolOutputFormat::Json => b"json",
+    };
+    let envs = command
+        .get_envs()
+        .sorted()
+        .flat_map(|(name, value)| [Some(name), value])
+        .flatten()
+        .map(|s| s.as_encoded_bytes());
+    let args = command.get_args().map(|s| s.as_encoded_bytes());
+    FixCacheKey::new(
+        [
+            tool_config
+                .cache_salt
+                .as_ref()
+                .map_or("", |salt| salt.hex())
+                .as_bytes(),
+            command.get_program().as_encoded_bytes(),
+        ]
+        .into_iter()
+        .chain(args)
+        .chain([&b"\0"[..]])
+        .chain(envs)
+        .chain([&b"\0"[..], output_format, content]),
+    )
 }
 
-/// Runs the `tool_command` to fix the given file content.
+/// Runs the `command` built from `tool_command` to fix the given file content.
 ///
 /// The `old_content` is assumed to be that of the `file_to_fix`'s `FileId`, but
 /// this is not verified.
@@ -308,19 +510,10 @@ fn run_tool(
     workspace_root: &Path,
     path_converter: &RepoPathUiConverter,
     tool_command: &CommandNameAndArgs,
+    mut command: Command,
     file_to_fix: &FileToFix,
     old_content: &[u8],
 ) -> Result<Vec<u8>, ()> {
-    let mut vars: HashMap<&str, &str> = HashMap::new();
-    vars.insert("path", file_to_fix.repo_path.as_internal_file_string());
-    // TODO: workspace_root.to_str() returns None if the workspace path is not
-    // UTF-8, but we ignore that failure so `jj fix` still runs in that
-    // situation. Maybe we should do something like substituting bytes instead
-    // of strings so we can handle any Path here.
-    if let Some(root) = workspace_root.to_str() {
-        vars.insert("root", root);
-    }
-    let mut command = tool_command.to_command_with_variables(&vars);
     tracing::debug!(?command, ?file_to_fix.repo_path, "spawning fix tool");
     let mut child = match command
         .current_dir(workspace_root)
@@ -383,6 +576,11 @@ struct ToolConfig {
     matcher: Box<dyn Matcher>,
     /// Whether the tool is enabled
     enabled: bool,
+    /// How the standard output of the tool is interpreted.
+    output: ToolOutputFormat,
+    /// Identifies the tool's executable and the content of its
+    /// `cache-inputs`, or `None` if the tool's output isn't cached.
+    cache_salt: Option<FixCacheKey>,
     // TODO: Store the `name` field here and print it with the command's stderr, to clearly
     // associate any errors/warnings with the tool and its configuration entry.
 }
@@ -402,6 +600,36 @@ struct RawToolConfig {
     patterns: Vec<String>,
     #[serde(default = "default_tool_enabled")]
     enabled: bool,
+    #[serde(default)]
+    output: ToolOutputFormat,
+    #[serde(default)]
+    cache: bool,
+    #[serde(default)]
+    cache_inputs: Vec<String>,
+}
+
+/// How the standard output of a tool is interpreted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum ToolOutputFormat {
+    /// The output is the new file content.
+    #[default]
+    Content,
+    /// The output is a JSON object describing the changes to make.
+    Json,
+}
+
+/// Deserializes the standard output of a tool with `output = "json"`.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonToolOutput {
+    /// The new file content. The content is unchanged if omitted.
+    content: Option<String>,
+    /// The repo-relative path to move the file to.
+    rename: Option<String>,
+    /// Files to create, mapping repo-relative paths to their content.
+    #[serde(default)]
+    create: BTreeMap<String, String>,
 }
 
 fn default_tool_enabled() -> bool {
@@ -413,7 +641,12 @@ fn default_tool_enabled() -> bool {
 /// Fails if any of the commands or patterns are obviously unusable, but does
 /// not check for issues that might still occur later like missing executables.
 /// This is a place where we could fail earlier in some cases, though.
-fn get_tools_config(ui: &mut Ui, settings: &UserSettings) -> Result<ToolsConfig, CommandError> {
+fn get_tools_config(
+    ui: &mut Ui,
+    settings: &UserSettings,
+    fileset_extensions: &FilesetExtensions,
+    workspace_root: &Path,
+) -> Result<ToolsConfig, CommandError> {
     let mut tools: Vec<ToolConfig> = settings
         .table_keys("fix.tools")
         // Sort keys early so errors are deterministic.
@@ -432,15 +665,27 @@ fn get_tools_config(ui: &mut Ui, settings: &UserSettings) -> Result<ToolsConfig,
                                 cwd: "".into(),
                                 base: "".into(),
                             },
+                            fileset_extensions,
                         )
                     })
                     .try_collect()?,
             );
             print_parse_diagnostics(ui, &format!("In `fix.tools.{name}`"), &diagnostics)?;
+            let cache_salt = if tool.cache && tool.enabled {
+                Some(tool_cache_salt(
+                    workspace_root,
+                    &tool.command,
+                    &tool.cache_inputs,
+                )?)
+            } else {
+                None
+            };
             Ok(ToolConfig {
                 command: tool.command,
                 matcher: expression.to_matcher(),
                 enabled: tool.enabled,
+                output: tool.output,
+                cache_salt,
             })
         })
         .try_collect()?;
@@ -456,3 +701,64 @@ fn get_tools_config(ui: &mut Ui, settings: &UserSettings) -> Result<ToolsConfig,
         Ok(ToolsConfig { tools })
     }
 }
+
+/// Computes the part of the cache keys of a tool that doesn't depend on the
+/// file being fixed.
+///
+/// The tool's executable is identified by its path, size and modification
+/// time, so that upgrading the tool invalidates its cached outputs. The
+/// `cache_inputs` are paths relative to the workspace root, typically config
+/// files of the tool, whose content is read from the working copy.
+fn tool_cache_salt(
+    workspace_root: &Path,
+    command: &CommandNameAndArgs,
+    cache_inputs: &[String],
+) -> Result<FixCacheKey, CommandError> {
+    let program = find_program(workspace_root, Path::new(&*command.split_name()));
+    let program_metadata = match &program {
+        Some(path) => {
+            let metadata = fs::metadata(path)?;
+            let modified = metadata
+                .modified()?
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default();
+            format!("{}:{}", metadata.len(), modified.as_nanos())
+        }
+        None => String::new(),
+    };
+    let mut parts = vec![
+        program
+            .as_deref()
+            .map_or(&[][..], |path| path.as_os_str().as_encoded_bytes())
+            .to_vec(),
+        program_metadata.into_bytes(),
+    ];
+    for input in cache_inputs {
+        match fs::read(workspace_root.join(input)) {
+            Ok(content) => parts.extend([b"+".to_vec(), content]),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => parts.push(b"-".to_vec()),
+            Err(err) => {
+                return Err(config_error(format!(
+                    "Failed to read `cache-inputs` file {input}: {err}"
+                )));
+            }
+        }
+    }
+    Ok(FixCacheKey::new(parts.iter().map(Vec::as_slice)))
+}
+
+/// Finds the executable that will be run for `program`, which is resolved
+/// relative to the workspace root or looked up in `PATH`.
+fn find_program(workspace_root: &Path, program: &Path) -> Option<PathBuf> {
+    if program.components().count() > 1 {
+        let path = workspace_root.join(program);
+        return path.is_file().then_some(path);
+    }
+    env::split_paths(&env::var_os("PATH")?)
+        .flat_map(|dir| {
+            let path = dir.join(program);
+            let exe_path = cfg!(windows).then(|| path.with_extension("exe"));
+            iter::once(path).chain(exe_path)
+        })
+        .find(|path| path.is_file())
+}
@@ -25,6 +25,7 @@ use jj_lib::git;
 use jj_lib::git::GitRefUpdate;
 use jj_lib::git::GitSubprocessOptions;
 use jj_lib::object_id::ObjectId as _;
+use jj_lib::progress::ProgressTask;
 use jj_lib::repo::Repo as _;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::settings::UserSettings;
@@ -355,7 +356,7 @@ pub fn cmd_gerrit_upload(
         // how do we get better errors from the remote? 'git push' tells us
         // about rejected refs AND ALSO '(nothing changed)' when there are no
         // changes to push, but we don't get that here.
-        let push_stats = with_remote_git_callbacks(ui, |cb| {
+        let push_stats = with_remote_git_callbacks(ui, ProgressTask::Push, |cb| {
             git::push_updates(
                 tx.repo_mut(),
                 subprocess_options.clone(),
@@ -21,10 +21,12 @@ use std::path::Path;
 use itertools::Itertools as _;
 use jj_lib::file_util;
 use jj_lib::git;
+use jj_lib::git::FetchDepth;
 use jj_lib::git::FetchTagsOverride;
 use jj_lib::git::GitFetch;
 use jj_lib::git::GitSettings;
 use jj_lib::git::expand_fetch_refspecs;
+use jj_lib::progress::ProgressTask;
 use jj_lib::ref_name::RefName;
 use jj_lib::ref_name::RefNameBuf;
 use jj_lib::ref_name::RemoteName;
@@ -104,6 +106,13 @@ pub struct GitCloneArgs {
     #[arg(long)]
     depth: Option<NonZeroU32>,
 
+    /// Create a partial clone with the given filter spec (e.g. `blob:none`)
+    ///
+    /// Objects omitted by the filter are fetched from the remote when they're
+    /// needed. See the `--filter` option of `git clone` for the syntax.
+    #[arg(long, value_name = "FILTER_SPEC")]
+    filter: Option<String>,
+
     /// Configure when to fetch tags
     ///
     /// Unless otherwise specified, the initial clone will fetch all tags,
@@ -205,6 +214,7 @@ pub fn cmd_git_clone(
             // will be loaded here?
             &bookmark_expr,
             args.depth,
+            args.filter.as_deref(),
             args.fetch_tags,
         )?;
         Ok((workspace_command, default_branch, config_env))
@@ -323,6 +333,7 @@ fn fetch_new_remote(
     remote_name: &RemoteName,
     bookmark_expr: &StringExpression,
     depth: Option<NonZeroU32>,
+    filter: Option<&str>,
     fetch_tags: Option<FetchTagsMode>,
 ) -> Result<(Option<RefNameBuf>, bool), CommandError> {
     writeln!(
@@ -342,12 +353,13 @@ fn fetch_new_remote(
 
         let fetch_refspecs = expand_fetch_refspecs(remote_name, bookmark_expr.clone())?;
 
-        with_remote_git_callbacks(ui, |cb| {
+        with_remote_git_callbacks(ui, ProgressTask::Fetch, |cb| {
             git_fetch.fetch(
                 remote_name,
                 fetch_refspecs,
                 cb,
-                depth,
+                depth.map(FetchDepth::Depth),
+                filter,
                 match fetch_tags {
                     // If not explicitly specified on the CLI, override the remote
                     // configuration and fetch all tags by default since this is
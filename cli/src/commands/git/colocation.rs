@@ -28,6 +28,7 @@ use crate::command_error::user_error;
 use crate::command_error::user_error_with_message;
 use crate::commands::git::maybe_add_gitignore;
 use crate::git_util::is_colocated_git_workspace;
+use crate::git_util::print_git_export_stats;
 use crate::ui::Ui;
 
 /// Show the current colocation status
@@ -292,7 +293,7 @@ fn set_git_repo_bare(path: &std::path::Path, bare: bool) -> Result<(), CommandEr
     Ok(())
 }
 
-/// Set the git HEAD to the working copy commit's parent
+/// Set the git HEAD to the working copy commit's parent, and export the refs
 fn set_git_head_to_wc_parent(
     ui: &mut Ui,
     workspace_command: &mut crate::cli_util::WorkspaceCommandHelper,
@@ -300,6 +301,10 @@ fn set_git_head_to_wc_parent(
 ) -> Result<(), CommandError> {
     let mut tx = workspace_command.start_transaction();
     git::reset_head(tx.repo_mut(), wc_commit)?;
+    // Refs aren't exported automatically in non-colocated workspaces, so the
+    // Git repository might not know about all bookmarks yet.
+    let stats = git::export_refs(tx.repo_mut())?;
+    print_git_export_stats(ui, &stats)?;
     if tx.repo().has_changes() {
         tx.finish(ui, "set git head to working copy parent")?;
     }
@@ -0,0 +1,209 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::io::Write as _;
+
+use jj_lib::config::ConfigGetResultExt as _;
+use jj_lib::git::GitSubprocessOptions;
+use jj_lib::git_credential::CredentialHelpers;
+use jj_lib::git_credential::GitCredential;
+use jj_lib::git_oauth::CurlTransport;
+use jj_lib::git_oauth::OAuthProvider;
+use jj_lib::settings::UserSettings;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::ui::Ui;
+
+/// Store the credentials for an HTTPS remote
+///
+/// The user name is prompted for if not specified. The password or access
+/// token is prompted for unless `--password-stdin` or `--oauth` is given.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitCredentialLoginArgs {
+    /// URL of the remote or of the host, e.g. `https://github.com`
+    #[arg(value_hint = clap::ValueHint::Url)]
+    url: String,
+
+    /// The user name
+    #[arg(long, short)]
+    username: Option<String>,
+
+    /// Read the password or access token from stdin instead of prompting
+    #[arg(long, conflicts_with = "oauth")]
+    password_stdin: bool,
+
+    /// Sign in through the browser and store the access token issued by the
+    /// forge
+    ///
+    /// The forge is configured in `git.oauth.<host>`. The user name defaults
+    /// to `oauth2`.
+    #[arg(long)]
+    oauth: bool,
+}
+
+/// Remove the stored credentials for an HTTPS remote
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitCredentialLogoutArgs {
+    /// URL of the remote or of the host, e.g. `https://github.com`
+    #[arg(value_hint = clap::ValueHint::Url)]
+    url: String,
+}
+
+/// Manage credentials for HTTPS remotes
+///
+/// The credentials are passed to the credential helpers configured in Git and
+/// in `git.credential-helper`, which are also consulted by `jj git fetch`, `jj
+/// git push`, and `jj git clone`. Use a helper backed by the system keychain,
+/// such as `osxkeychain`, `manager`, or `libsecret`.
+///
+/// With `--oauth`, `jj` signs in to the forge with the OAuth device flow: it
+/// shows a code to enter in the browser, and stores the access token issued by
+/// the forge.
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum GitCredentialCommand {
+    Login(GitCredentialLoginArgs),
+    Logout(GitCredentialLogoutArgs),
+}
+
+pub fn cmd_git_credential(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &GitCredentialCommand,
+) -> Result<(), CommandError> {
+    let helpers = credential_helpers(ui, command)?;
+    match subcommand {
+        GitCredentialCommand::Login(args) => {
+            cmd_git_credential_login(ui, command.settings(), &helpers, args)
+        }
+        GitCredentialCommand::Logout(args) => cmd_git_credential_logout(ui, &helpers, args),
+    }
+}
+
+fn credential_helpers(ui: &Ui, command: &CommandHelper) -> Result<CredentialHelpers, CommandError> {
+    let helpers = CredentialHelpers::new(GitSubprocessOptions::from_settings(command.settings())?);
+    let names = helpers.list()?;
+    if names.is_empty() {
+        return Err(user_error_with_hint(
+            "No credential helper is configured",
+            "Configure a helper backed by the system keychain in Git, e.g. `git config --global \
+             credential.helper osxkeychain` (or `manager`, `libsecret`), or set \
+             `git.credential-helper`.",
+        ));
+    }
+    if names
+        .iter()
+        .any(|name| name == "store" || name.starts_with("store "))
+    {
+        writeln!(
+            ui.warning_default(),
+            "The `store` credential helper saves passwords unencrypted on disk"
+        )?;
+    }
+    Ok(helpers)
+}
+
+fn cmd_git_credential_login(
+    ui: &mut Ui,
+    settings: &UserSettings,
+    helpers: &CredentialHelpers,
+    args: &GitCredentialLoginArgs,
+) -> Result<(), CommandError> {
+    let mut credential = GitCredential::from_url(&args.url)?;
+    if credential.password.is_some() {
+        return Err(user_error("The URL must not contain a password"));
+    }
+    if let Some(username) = &args.username {
+        credential.username = Some(username.clone());
+    } else if args.oauth {
+        credential
+            .username
+            .get_or_insert_with(|| "oauth2".to_owned());
+    } else if credential.username.is_none() {
+        credential.username = Some(ui.prompt("Username")?);
+    }
+    let password = if args.oauth {
+        let host = credential.host.as_deref().unwrap_or_default();
+        oauth_login(ui, settings, host)?
+    } else if args.password_stdin {
+        let mut password = String::new();
+        io::stdin().read_line(&mut password)?;
+        password.trim_end_matches(['\r', '\n']).to_owned()
+    } else {
+        ui.prompt_password("Password or access token")?
+    };
+    if password.is_empty() {
+        return Err(user_error("The password must not be empty"));
+    }
+    credential.password = Some(password);
+    helpers.approve(&credential)?;
+    writeln!(ui.status(), "Stored credentials for {url}", url = args.url)?;
+    Ok(())
+}
+
+fn oauth_login(ui: &Ui, settings: &UserSettings, host: &str) -> Result<String, CommandError> {
+    if settings.table_keys(["git", "oauth", host]).next().is_none() {
+        return Err(user_error_with_hint(
+            format!("No OAuth provider is configured for {host}"),
+            format!(
+                "Set `git.oauth.\"{host}\".device-authorization-url` and `token-url` to the \
+                 endpoints of the forge."
+            ),
+        ));
+    }
+    let get = |name: &str| settings.get::<String>(["git", "oauth", host, name]);
+    let Some(client_id) = get("client-id").optional()? else {
+        return Err(user_error_with_hint(
+            format!("No OAuth client ID is configured for {host}"),
+            format!(
+                "Register an OAuth app with the device flow enabled on the forge, and set \
+                 `git.oauth.\"{host}\".client-id` to its client ID."
+            ),
+        ));
+    };
+    let provider = OAuthProvider {
+        client_id,
+        device_authorization_url: get("device-authorization-url")?,
+        token_url: get("token-url")?,
+        scopes: settings
+            .get(["git", "oauth", host, "scopes"])
+            .optional()?
+            .unwrap_or_default(),
+    };
+    let mut transport = CurlTransport::new(settings.get::<String>("git.curl-executable")?);
+    let authorization = provider.authorize_device(&mut transport)?;
+    writeln!(
+        ui.status(),
+        "Open {url} and enter the code {code}",
+        url = authorization.verification_uri,
+        code = authorization.user_code
+    )?;
+    writeln!(ui.status(), "Waiting for authorization...")?;
+    let token = provider.wait_for_token(&mut transport, &authorization, &mut std::thread::sleep)?;
+    Ok(token)
+}
+
+fn cmd_git_credential_logout(
+    ui: &mut Ui,
+    helpers: &CredentialHelpers,
+    args: &GitCredentialLogoutArgs,
+) -> Result<(), CommandError> {
+    let query = GitCredential::from_url(&args.url)?;
+    helpers.reject(&query)?;
+    writeln!(ui.status(), "Removed credentials for {url}", url = args.url)?;
+    Ok(())
+}
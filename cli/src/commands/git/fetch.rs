@@ -13,11 +13,14 @@
 // limitations under the License.
 
 use std::io;
+use std::num::NonZeroU32;
 
 use clap_complete::ArgValueCandidates;
 use itertools::Itertools as _;
 use jj_lib::config::ConfigGetResultExt as _;
+use jj_lib::default_index::DefaultIndexStore;
 use jj_lib::git;
+use jj_lib::git::FetchDepth;
 use jj_lib::git::GitFetch;
 use jj_lib::git::GitSettings;
 use jj_lib::git::IgnoredRefspec;
@@ -25,21 +28,25 @@ use jj_lib::git::IgnoredRefspecs;
 use jj_lib::git::expand_default_fetch_refspecs;
 use jj_lib::git::expand_fetch_refspecs;
 use jj_lib::git::get_git_backend;
+use jj_lib::progress::ProgressTask;
 use jj_lib::ref_name::RefName;
 use jj_lib::ref_name::RemoteName;
 use jj_lib::repo::Repo as _;
 use jj_lib::str_util::StringExpression;
+use pollster::FutureExt as _;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::WorkspaceCommandTransaction;
 use crate::command_error::CommandError;
+use crate::command_error::internal_error;
 use crate::command_error::user_error;
 use crate::commands::git::get_single_remote;
 use crate::complete;
 use crate::git_util::load_git_import_options;
 use crate::git_util::print_git_import_stats;
 use crate::git_util::with_remote_git_callbacks;
+use crate::progress::progress_reporter;
 use crate::revset_util::parse_union_name_patterns;
 use crate::ui::Ui;
 
@@ -62,7 +69,7 @@ pub struct GitFetchArgs {
     /// [logical operators]:
     ///     https://docs.jj-vcs.dev/latest/revsets/#string-patterns
     #[arg(long = "branch", short, alias = "bookmark", value_name = "BRANCH")]
-    #[arg(add = ArgValueCandidates::new(complete::bookmarks))]
+    #[arg(add = ArgValueCandidates::new(complete::remote_bookmarks))]
     branches: Option<Vec<String>>,
 
     /// Fetch only tracked bookmarks
@@ -90,6 +97,14 @@ pub struct GitFetchArgs {
     /// Fetch from all remotes
     #[arg(long, conflicts_with = "remotes")]
     all_remotes: bool,
+
+    /// Extend the history of a shallow clone by the given number of commits
+    #[arg(long, value_name = "DEPTH")]
+    deepen: Option<NonZeroU32>,
+
+    /// Fetch the complete history of a shallow clone
+    #[arg(long, conflicts_with = "deepen")]
+    unshallow: bool,
 }
 
 #[tracing::instrument(skip_all)]
@@ -132,6 +147,8 @@ pub fn cmd_git_fetch(
     }
 
     let mut tx = workspace_command.start_transaction();
+    // Bookmarks follow their remotes even if they were protected.
+    tx.allow_protected_bookmarks();
 
     let common_bookmark_expr = match &args.branches {
         Some(texts) => Some(parse_union_name_patterns(ui, texts)?),
@@ -173,13 +190,19 @@ pub fn cmd_git_fetch(
         &import_options,
     )?;
 
+    let depth = if args.unshallow {
+        Some(FetchDepth::Unshallow)
+    } else {
+        args.deepen.map(FetchDepth::Deepen)
+    };
     for (remote, expanded) in expansions {
-        with_remote_git_callbacks(ui, |callbacks| {
-            git_fetch.fetch(remote, expanded, callbacks, None, None)
+        with_remote_git_callbacks(ui, ProgressTask::Fetch, |callbacks| {
+            git_fetch.fetch(remote, expanded, callbacks, depth, None, None)
         })?;
     }
 
     let import_stats = git_fetch.import_refs()?;
+    let shallow_boundary_moved = git_fetch.has_moved_shallow_boundary();
     print_git_import_stats(ui, tx.repo(), &import_stats, true)?;
     if let Some(bookmark_expr) = &common_bookmark_expr {
         warn_if_branches_not_found(ui, &tx, bookmark_expr, &matching_remotes)?;
@@ -191,6 +214,32 @@ pub fn cmd_git_fetch(
             matching_remotes.iter().map(|n| n.as_symbol()).join(",")
         ),
     )?;
+    if shallow_boundary_moved {
+        // The parents of the previously shallow commits have changed.
+        rebuild_index(ui, &workspace_command)?;
+    }
+    Ok(())
+}
+
+fn rebuild_index(ui: &Ui, workspace_command: &WorkspaceCommandHelper) -> Result<(), CommandError> {
+    let repo_loader = workspace_command.workspace().repo_loader();
+    let index_store = repo_loader.index_store();
+    let Some(default_index_store) = index_store.downcast_ref::<DefaultIndexStore>() else {
+        return Err(user_error(format!(
+            "Cannot reindex indexes of type '{}'",
+            index_store.name()
+        )));
+    };
+    default_index_store.reinit().map_err(internal_error)?;
+    let progress = progress_reporter(ui);
+    default_index_store
+        .build_index_at_operation(
+            workspace_command.repo().operation(),
+            repo_loader.store(),
+            progress.as_ref().map(|x| x as _),
+        )
+        .block_on()
+        .map_err(internal_error)?;
     Ok(())
 }
 
@@ -14,6 +14,7 @@
 
 mod clone;
 mod colocation;
+mod credential;
 mod export;
 mod fetch;
 mod import;
@@ -38,6 +39,8 @@ use self::clone::GitCloneArgs;
 use self::clone::cmd_git_clone;
 use self::colocation::GitColocationCommand;
 use self::colocation::cmd_git_colocation;
+use self::credential::GitCredentialCommand;
+use self::credential::cmd_git_credential;
 use self::export::GitExportArgs;
 use self::export::cmd_git_export;
 use self::fetch::GitFetchArgs;
@@ -74,6 +77,8 @@ pub enum GitCommand {
     Clone(GitCloneArgs),
     #[command(subcommand)]
     Colocation(GitColocationCommand),
+    #[command(subcommand)]
+    Credential(GitCredentialCommand),
     Export(GitExportArgs),
     Fetch(GitFetchArgs),
     Import(GitImportArgs),
@@ -92,6 +97,7 @@ pub fn cmd_git(
     match subcommand {
         GitCommand::Clone(args) => cmd_git_clone(ui, command, args),
         GitCommand::Colocation(subcommand) => cmd_git_colocation(ui, command, subcommand),
+        GitCommand::Credential(subcommand) => cmd_git_credential(ui, command, subcommand),
         GitCommand::Export(args) => cmd_git_export(ui, command, args),
         GitCommand::Fetch(args) => cmd_git_fetch(ui, command, args),
         GitCommand::Import(args) => cmd_git_import(ui, command, args),
@@ -117,7 +123,9 @@ pub fn maybe_add_gitignore(workspace_command: &WorkspaceCommandHelper) -> Result
     }
 }
 
-fn get_single_remote(store: &Store) -> Result<Option<RemoteNameBuf>, UnexpectedGitBackendError> {
+pub(crate) fn get_single_remote(
+    store: &Store,
+) -> Result<Option<RemoteNameBuf>, UnexpectedGitBackendError> {
     let mut names = git::get_all_remote_names(store)?;
     Ok(match names.len() {
         1 => names.pop(),
@@ -30,10 +30,14 @@ use jj_lib::commit::CommitIteratorExt as _;
 use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::git;
 use jj_lib::git::GitBranchPushTargets;
+use jj_lib::git::GitRefKind;
 use jj_lib::git::GitSettings;
 use jj_lib::index::IndexResult;
 use jj_lib::op_store::RefTarget;
 use jj_lib::operation::Operation;
+use jj_lib::progress::ProgressTask;
+use jj_lib::protected_bookmarks::ProtectedBookmarks;
+use jj_lib::ref_name::GitRefName;
 use jj_lib::ref_name::RefName;
 use jj_lib::ref_name::RefNameBuf;
 use jj_lib::ref_name::RemoteName;
@@ -65,6 +69,7 @@ use crate::command_error::user_error_with_message;
 use crate::commands::git::get_single_remote;
 use crate::complete;
 use crate::formatter::Formatter;
+use crate::git_util::print_git_export_stats;
 use crate::git_util::print_push_stats;
 use crate::git_util::with_remote_git_callbacks;
 use crate::revset_util::parse_bookmark_name;
@@ -198,6 +203,28 @@ pub struct GitPushArgs {
     #[arg(add = ArgValueCompleter::new(complete::branch_name_equals_any_revision))]
     named: Vec<String>,
 
+    /// Push only this tag, or tags matching a pattern (can be repeated)
+    ///
+    /// Bookmarks aren't pushed if tags are specified. Tags which already exist
+    /// on the remote aren't overwritten.
+    ///
+    /// By default, the specified pattern matches tag names with glob syntax.
+    /// You can also use other [string pattern syntax].
+    ///
+    /// [string pattern syntax]:
+    ///     https://docs.jj-vcs.dev/latest/revsets/#string-patterns
+    #[arg(long, conflicts_with_all = ["specific", "what", "deleted"])]
+    #[arg(add = ArgValueCandidates::new(complete::local_tags))]
+    tag: Vec<String>,
+
+    /// Transmit this option to the server (can be repeated)
+    ///
+    /// The options are passed to the hooks of the server, e.g. `ci.skip` to
+    /// skip CI pipelines on GitLab. They are added to the options in the
+    /// `git.push-options` setting.
+    #[arg(long = "push-option", short = 'o', value_name = "OPTION")]
+    push_options: Vec<String>,
+
     /// Only display what will change on the remote
     #[arg(long)]
     dry_run: bool,
@@ -234,6 +261,7 @@ pub fn cmd_git_push(
         )?;
     }
     let mut workspace_command = command.workspace_helper(ui)?;
+    workspace_command.rebase_deferred_descendants(ui)?;
 
     let default_remote;
     let remote = if let Some(name) = &args.remote {
@@ -243,6 +271,10 @@ pub fn cmd_git_push(
         &default_remote
     };
 
+    if !args.tag.is_empty() {
+        return push_tags(ui, &mut workspace_command, remote, args);
+    }
+
     let mut tx = workspace_command.start_transaction();
     let view = tx.repo().view();
     let tx_description;
@@ -416,14 +448,26 @@ pub fn cmd_git_push(
         writeln!(ui.status(), "Nothing changed.")?;
         return Ok(());
     }
+    if !command.global_args().allow_protected {
+        let protected_bookmarks = ProtectedBookmarks::from_settings(tx.settings())?;
+        for (name, update) in &bookmark_updates {
+            protected_bookmarks.check_push_update(tx.repo(), name, update)?;
+        }
+    }
 
     let sign_behavior = if tx.settings().get_bool("git.sign-on-push")? {
         Some(SignBehavior::Own)
     } else {
         None
     };
-    let commits_to_sign =
-        validate_commits_ready_to_push(ui, &bookmark_updates, remote, &tx, args, sign_behavior)?;
+    let commits_to_sign = validate_commits_ready_to_push(
+        ui,
+        &bookmark_updates,
+        remote,
+        tx.base_workspace_helper(),
+        args,
+        sign_behavior,
+    )?;
     if !args.dry_run
         && !commits_to_sign.is_empty()
         && let Some(sign_behavior) = sign_behavior
@@ -463,8 +507,11 @@ pub fn cmd_git_push(
     let targets = GitBranchPushTargets {
         branch_updates: bookmark_updates,
     };
-    let git_settings = GitSettings::from_settings(tx.settings())?;
-    let push_stats = with_remote_git_callbacks(ui, |cb| {
+    let mut git_settings = GitSettings::from_settings(tx.settings())?;
+    git_settings
+        .push_options
+        .extend(args.push_options.iter().cloned());
+    let push_stats = with_remote_git_callbacks(ui, ProgressTask::Push, |cb| {
         git::push_branches(
             tx.repo_mut(),
             git_settings.to_subprocess_options(),
@@ -486,6 +533,120 @@ pub fn cmd_git_push(
     }
 }
 
+/// Pushes the tags matching `args.tag`.
+///
+/// Since Git has no remote tags, the pushed tags aren't recorded in the view.
+fn push_tags(
+    ui: &Ui,
+    workspace_command: &mut WorkspaceCommandHelper,
+    remote: &RemoteName,
+    args: &GitPushArgs,
+) -> Result<(), CommandError> {
+    let repo = workspace_command.repo().clone();
+    let view = repo.view();
+    let tag_expr = parse_union_name_patterns(ui, &args.tag)?;
+    let tags = view
+        .local_tags_matching(&tag_expr.to_matcher())
+        .collect_vec();
+    let mut unmatched_names = tag_expr
+        .exact_strings()
+        .map(RefName::new)
+        .filter(|&name| view.get_local_tag(name).is_absent())
+        .peekable();
+    if unmatched_names.peek().is_some() {
+        writeln!(
+            ui.warning_default(),
+            "No matching tags for names: {}",
+            unmatched_names.map(|name| name.as_symbol()).join(", ")
+        )?;
+    }
+    if tags.is_empty() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+
+    let mut tag_updates = vec![];
+    for &(name, target) in &tags {
+        let Some(commit_id) = target.as_normal() else {
+            return Err(user_error_with_hint(
+                format!("Tag {name} is conflicted", name = name.as_symbol()),
+                "Run `jj tag set --allow-move` to resolve the conflict.",
+            ));
+        };
+        // Reuse the checks for bookmarks, as if a new bookmark were pushed.
+        let update = BookmarkPushUpdate {
+            old_target: None,
+            new_target: Some(commit_id.clone()),
+        };
+        tag_updates.push((name.to_owned(), update));
+    }
+    validate_commits_ready_to_push(ui, &tag_updates, remote, workspace_command, args, None)?;
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(
+            formatter,
+            "Changes to push to {remote}:",
+            remote = remote.as_symbol()
+        )?;
+        for (name, update) in &tag_updates {
+            writeln!(
+                formatter,
+                "  Add tag {name} to {new}",
+                name = name.as_symbol(),
+                new = short_commit_hash(update.new_target.as_ref().unwrap())
+            )?;
+        }
+    }
+
+    if args.dry_run {
+        writeln!(ui.status(), "Dry-run requested, not pushing.")?;
+        return Ok(());
+    }
+
+    // Tags are pushed from the underlying Git repo, so they have to be
+    // exported first.
+    let unexported_names: HashSet<&RefName> = tags
+        .iter()
+        .filter(|&&(name, target)| {
+            let git_ref_name = format!("refs/tags/{name}", name = name.as_str());
+            view.get_git_ref(GitRefName::new(&git_ref_name)) != target
+        })
+        .map(|&(name, _)| name)
+        .collect();
+    if !unexported_names.is_empty() {
+        let mut tx = workspace_command.start_transaction();
+        let stats = git::export_some_refs(tx.repo_mut(), |kind, symbol| {
+            kind == GitRefKind::Tag && unexported_names.contains(symbol.name)
+        })?;
+        tx.finish(ui, "export tags to git")?;
+        print_git_export_stats(ui, &stats)?;
+        if !stats.failed_tags.is_empty() {
+            return Err(user_error("Failed to export some tags"));
+        }
+    }
+
+    let names = tag_updates.into_iter().map(|(name, _)| name).collect_vec();
+    let mut git_settings = GitSettings::from_settings(workspace_command.settings())?;
+    git_settings
+        .push_options
+        .extend(args.push_options.iter().cloned());
+    let push_stats = with_remote_git_callbacks(ui, ProgressTask::Push, |cb| {
+        git::push_tags(
+            workspace_command.repo().as_ref(),
+            git_settings.to_subprocess_options(),
+            remote,
+            &names,
+            cb,
+        )
+    })?;
+    print_push_stats(ui, &push_stats)?;
+    if push_stats.all_ok() {
+        Ok(())
+    } else {
+        Err(user_error("Failed to push some tags"))
+    }
+}
+
 /// Validates that the commits that will be pushed are ready (have authorship
 /// information, are not conflicted, etc.).
 ///
@@ -494,11 +655,10 @@ fn validate_commits_ready_to_push(
     ui: &Ui,
     bookmark_updates: &[(RefNameBuf, BookmarkPushUpdate)],
     remote: &RemoteName,
-    tx: &WorkspaceCommandTransaction,
+    workspace_helper: &WorkspaceCommandHelper,
     args: &GitPushArgs,
     sign_behavior: Option<SignBehavior>,
 ) -> Result<Vec<Commit>, CommandError> {
-    let workspace_helper = tx.base_workspace_helper();
     let repo = workspace_helper.repo();
 
     let new_heads = bookmark_updates
@@ -15,8 +15,11 @@
 use std::io::Write as _;
 
 use bstr::BString;
+use bstr::ByteSlice as _;
 use gix::Remote;
 use jj_lib::git;
+use jj_lib::git::GitRemoteUrls;
+use jj_lib::ref_name::RemoteName;
 use jj_lib::repo::Repo as _;
 
 use crate::cli_util::CommandHelper;
@@ -34,7 +37,8 @@ pub fn cmd_git_remote_list(
     _args: &GitRemoteListArgs,
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
-    let git_repo = git::get_git_repo(workspace_command.repo().store())?;
+    let store = workspace_command.repo().store();
+    let git_repo = git::get_git_repo(store)?;
     for remote_name in git_repo.remote_names() {
         let remote = match git_repo.try_find_remote(&*remote_name) {
             Some(Ok(remote)) => remote,
@@ -46,6 +50,26 @@ pub fn cmd_git_remote_list(
             }
             None => continue, // ignore empty [remote "<name>"] section
         };
+        let urls = match remote_name.to_str() {
+            Ok(name) => git::get_remote_urls(store, RemoteName::new(name))?,
+            Err(_) => GitRemoteUrls::default(),
+        };
+        if urls.fetch.len() > 1 || urls.push.len() > 1 {
+            // Git fetches from the first URL, and pushes to all push URLs, or
+            // to all fetch URLs if there are no push URLs.
+            let fetch_url = urls.fetch.first().map_or("<no URL>", |url| url.as_str());
+            let push_urls = if urls.push.is_empty() {
+                &urls.fetch
+            } else {
+                &urls.push
+            };
+            writeln!(
+                ui.stdout(),
+                "{remote_name} {fetch_url} (push: {push_urls})",
+                push_urls = push_urls.join(", ")
+            )?;
+            continue;
+        }
         let fetch_url = get_url(&remote, gix::remote::Direction::Fetch);
         let push_url = get_url(&remote, gix::remote::Direction::Push);
         if fetch_url == push_url {
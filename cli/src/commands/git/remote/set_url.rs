@@ -24,6 +24,11 @@ use crate::git_util::absolute_git_url;
 use crate::ui::Ui;
 
 /// Set the URL of a Git remote
+///
+/// A remote can have several URLs. Git fetches from the first fetch URL, and
+/// pushes to every push URL, or to every fetch URL if no push URL is
+/// configured. Use `--add` and `--delete` to manage the additional URLs of
+/// mirrored remotes.
 #[derive(clap::Args, Clone, Debug)]
 pub struct GitRemoteSetUrlArgs {
     /// The remote's name
@@ -49,6 +54,16 @@ pub struct GitRemoteSetUrlArgs {
     /// Local path will be resolved to absolute form.
     #[arg(long, value_hint = clap::ValueHint::Url, conflicts_with = "url")]
     fetch: Option<String>,
+
+    /// Add the URLs to the remote instead of replacing the existing ones
+    #[arg(long, conflicts_with = "delete")]
+    add: bool,
+
+    /// Remove the URLs from the remote
+    ///
+    /// The given URLs are taken verbatim; local paths are not resolved.
+    #[arg(long)]
+    delete: bool,
 }
 
 pub fn cmd_git_remote_set_url(
@@ -58,16 +73,46 @@ pub fn cmd_git_remote_set_url(
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
 
+    let store = workspace_command.repo().store();
+    let fetch_url = args.url.as_ref().or(args.fetch.as_ref());
+    let push_url = args.push.as_ref();
+
+    if args.delete {
+        let urls = [
+            (fetch_url, gix::remote::Direction::Fetch),
+            (push_url, gix::remote::Direction::Push),
+        ];
+        for (url, direction) in urls {
+            if let Some(url) = url {
+                git::remove_remote_url(store, &args.remote, url, direction)?;
+            }
+        }
+        return Ok(());
+    }
+
     let process_url = |url: Option<&String>| {
         url.map(|url| absolute_git_url(command.cwd(), url))
             .transpose()
     };
 
-    let fetch_url = process_url(args.url.as_ref().or(args.fetch.as_ref()))?;
-    let push_url = process_url(args.push.as_ref())?;
+    let fetch_url = process_url(fetch_url)?;
+    let push_url = process_url(push_url)?;
+
+    if args.add {
+        let urls = [
+            (fetch_url, gix::remote::Direction::Fetch),
+            (push_url, gix::remote::Direction::Push),
+        ];
+        for (url, direction) in urls {
+            if let Some(url) = url {
+                git::add_remote_url(store, &args.remote, &url, direction)?;
+            }
+        }
+        return Ok(());
+    }
 
     git::set_remote_urls(
-        workspace_command.repo().store(),
+        store,
         &args.remote,
         fetch_url.as_deref(),
         push_url.as_deref(),
@@ -13,6 +13,10 @@
 // limitations under the License.
 
 use std::cmp::min;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::Arc;
 
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
@@ -21,13 +25,18 @@ use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
 use jj_lib::graph::GraphEdge;
 use jj_lib::graph::GraphEdgeType;
+use jj_lib::graph::GraphNode;
 use jj_lib::graph::TopoGroupedGraphIterator;
 use jj_lib::graph::reverse_graph;
 use jj_lib::repo::Repo as _;
+use jj_lib::revset::Revset;
+use jj_lib::revset::RevsetContainingFn;
 use jj_lib::revset::RevsetEvaluationError;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetFilterPredicate;
 use jj_lib::revset::RevsetIteratorExt as _;
+use jj_lib::store::Store;
+use jj_lib::str_util::StringExpression;
 use pollster::FutureExt as _;
 use tracing::instrument;
 
@@ -39,6 +48,7 @@ use crate::command_error::CommandError;
 use crate::complete;
 use crate::diff_util::DiffFormatArgs;
 use crate::formatter::FormatterExt as _;
+use crate::graphlog::GraphLog;
 use crate::graphlog::GraphStyle;
 use crate::graphlog::get_graphlog;
 use crate::templater::TemplateRenderer;
@@ -55,7 +65,8 @@ use crate::ui::Ui;
 ///     https://docs.jj-vcs.dev/latest/revsets/
 ///
 /// Spans of revisions that are not included in the graph per `--revisions` are
-/// rendered as a synthetic node labeled "(elided revisions)".
+/// rendered as a synthetic node labeled "(elided revisions)". Revisions hidden
+/// by `--elide` are collapsed into elided edges instead.
 ///
 /// The working-copy commit is indicated by a `@` symbol in the graph.
 /// [Immutable revisions] have a `◆` symbol. Other commits have a `○` symbol.
@@ -67,6 +78,8 @@ use crate::ui::Ui;
 /// [customized]:
 ///     https://docs.jj-vcs.dev/latest/config/#node-style
 #[derive(clap::Args, Clone, Debug)]
+#[command(mut_arg("ignore_all_space", |a| a.short('w')))]
+#[command(mut_arg("ignore_space_change", |a| a.short('b')))]
 pub(crate) struct LogArgs {
     /// Which revisions to show
     ///
@@ -81,6 +94,19 @@ pub(crate) struct LogArgs {
     #[arg(add = ArgValueCompleter::new(complete::log_files))]
     paths: Vec<String>,
 
+    /// Hide revisions matching this revset from the graph
+    ///
+    /// This is meant for collapsing noisy commits such as automated merges.
+    /// Edges through hidden revisions are rendered as elided edges (dotted
+    /// connectors), without the synthetic nodes used for revisions excluded
+    /// by `--revisions`.
+    ///
+    /// If no paths nor revisions nor revisions to hide are specified, this
+    /// defaults to the `revsets.log-elide` setting.
+    #[arg(long, value_name = "REVSETS")]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
+    elide: Vec<RevisionArg>,
+
     /// Limit number of revisions to show
     ///
     /// Applied after revisions are filtered and reordered topologically, but
@@ -96,6 +122,19 @@ pub(crate) struct LogArgs {
     #[arg(long, short = 'G')]
     no_graph: bool,
 
+    /// Render the graph more compactly
+    ///
+    /// Elided revisions leading to the same revision are shown as a single
+    /// node, and the `~` marker for parents outside the revset is omitted if
+    /// the revision has other parents in the graph. If
+    /// `ui.graph.collapse-linear-runs` is enabled, runs of linear revisions
+    /// without bookmarks, tags, or working copies are also collapsed into a
+    /// single node.
+    ///
+    /// If not specified, this defaults to the `ui.graph.compact` setting.
+    #[arg(long, conflicts_with = "no_graph")]
+    compact_graph: bool,
+
     /// Render each revision using the given template
     ///
     /// Run `jj log -T` to list the built-in templates.
@@ -138,7 +177,7 @@ pub(crate) fn cmd_log(
 
     let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
     let mut explicit_paths = fileset_expression.explicit_paths().collect_vec();
-    let revset_expression = {
+    let (revset_expression, elided_expression) = {
         // only use default revset if neither revset nor path are specified
         let mut expression = if args.revisions.is_empty() && args.paths.is_empty() {
             let revset_string = settings.get_string("revsets.log")?;
@@ -155,7 +194,22 @@ pub(crate) fn cmd_log(
             let predicate = RevsetFilterPredicate::File(fileset_expression.clone());
             expression.intersect_with(&RevsetExpression::filter(predicate));
         }
-        expression
+        // only use default elided revset if the default revset is used
+        let elide_expression = if !args.elide.is_empty() {
+            workspace_command.parse_union_revsets(ui, &args.elide)?
+        } else if args.revisions.is_empty() && args.paths.is_empty() {
+            let revset_string = settings.get_string("revsets.log-elide")?;
+            workspace_command.parse_revset(ui, &RevisionArg::from(revset_string))?
+        } else {
+            workspace_command.attach_revset_evaluator(RevsetExpression::none())
+        };
+        let elided_expression = workspace_command.attach_revset_evaluator(
+            expression
+                .expression()
+                .intersection(elide_expression.expression()),
+        );
+        expression.intersect_with(&elide_expression.expression().negated());
+        (expression, elided_expression)
     };
 
     let revset = revset_expression.evaluate()?;
@@ -187,6 +241,9 @@ pub(crate) fn cmd_log(
     let store = repo.store();
     let diff_renderer = workspace_command.diff_renderer_for_log(&args.diff_format, args.patch)?;
     let graph_style = GraphStyle::from_settings(settings)?;
+    let compact_graph = args.compact_graph || settings.get_bool("ui.graph.compact")?;
+    let collapse_linear_runs =
+        compact_graph && settings.get_bool("ui.graph.collapse-linear-runs")?;
 
     let use_elided_nodes = settings.get_bool("ui.log-synthetic-elided-nodes")?;
     let with_content_format = LogContentFormat::new(ui, settings)?;
@@ -236,8 +293,80 @@ pub(crate) fn cmd_log(
                     Box::new(forward_iter)
                 }
             };
+            // Maps the first revision of each collapsed run to the length of
+            // the run and the revision following it.
+            let mut collapsed_runs = HashMap::new();
+            let mut collapsed_ids = HashSet::new();
+            let iter = if collapse_linear_runs {
+                let nodes: Vec<_> = iter.try_collect()?;
+                let keep_expression = RevsetExpression::union_all(&[
+                    RevsetExpression::working_copies(),
+                    RevsetExpression::bookmarks(StringExpression::all()),
+                    RevsetExpression::remote_bookmarks(
+                        StringExpression::all(),
+                        StringExpression::all(),
+                        None,
+                    ),
+                    RevsetExpression::tags(StringExpression::all()),
+                ]);
+                let keep_revset = workspace_command
+                    .attach_revset_evaluator(keep_expression)
+                    .evaluate()?;
+                let keep = keep_revset.containing_fn();
+                for run in find_linear_runs(&nodes, &keep)? {
+                    let (first_id, _) = &nodes[run.start];
+                    let (next_id, _) = &nodes[run.end];
+                    collapsed_runs.insert(first_id.clone(), (run.len(), next_id.clone()));
+                    collapsed_ids.extend(
+                        nodes[run.start + 1..run.end]
+                            .iter()
+                            .map(|(id, _)| id.clone()),
+                    );
+                }
+                Box::new(nodes.into_iter().map(Ok))
+            } else {
+                iter
+            };
+            let elided_edges = if use_elided_nodes {
+                elided_edge_targets(store, elided_expression.evaluate()?.as_ref())?
+            } else {
+                HashMap::new()
+            };
+            // In compact mode, elided nodes leading to the same revision are
+            // rendered once, right before that revision.
+            let mut pending_elided_targets: Vec<CommitId> = vec![];
             for node in iter {
                 let (commit_id, edges) = node?;
+                if collapsed_ids.contains(&commit_id) {
+                    continue;
+                }
+                if let Some(pos) = pending_elided_targets
+                    .iter()
+                    .position(|id| *id == commit_id)
+                {
+                    let elided_target = pending_elided_targets.remove(pos);
+                    add_synthetic_node(
+                        ui,
+                        graph.as_mut(),
+                        &with_content_format,
+                        &node_template,
+                        (elided_target.clone(), elided_target),
+                        "(elided revisions)",
+                    )?;
+                }
+                if let Some((len, next_id)) = collapsed_runs.get(&commit_id) {
+                    add_synthetic_node(
+                        ui,
+                        graph.as_mut(),
+                        &with_content_format,
+                        &node_template,
+                        (commit_id, next_id.clone()),
+                        &format!("({len} revisions)"),
+                    )?;
+                    continue;
+                }
+                let key = (commit_id, false);
+                let commit = store.get_commit(&key.0)?;
 
                 // The graph is keyed by (CommitId, is_synthetic)
                 let mut graphlog_edges = vec![];
@@ -252,11 +381,20 @@ pub(crate) fn cmd_log(
                             missing_edge_id = Some(edge.target);
                         }
                         GraphEdgeType::Direct => {
-                            graphlog_edges.push(GraphEdge::direct((edge.target, false)));
+                            let is_synthetic = collapsed_runs.contains_key(&edge.target);
+                            graphlog_edges.push(GraphEdge::direct((edge.target, is_synthetic)));
                         }
                         GraphEdgeType::Indirect => {
-                            if use_elided_nodes {
-                                elided_targets.push(edge.target.clone());
+                            // Revisions hidden by --elide are collapsed into
+                            // the edge rather than a synthetic node.
+                            if use_elided_nodes
+                                && !is_elided_edge(&elided_edges, &commit, &edge.target)
+                            {
+                                if !compact_graph {
+                                    elided_targets.push(edge.target.clone());
+                                } else if !pending_elided_targets.contains(&edge.target) {
+                                    pending_elided_targets.push(edge.target.clone());
+                                }
                                 graphlog_edges.push(GraphEdge::direct((edge.target, true)));
                             } else {
                                 graphlog_edges.push(GraphEdge::indirect((edge.target, false)));
@@ -264,12 +402,12 @@ pub(crate) fn cmd_log(
                         }
                     }
                 }
-                if let Some(missing_edge_id) = missing_edge_id {
+                if let Some(missing_edge_id) = missing_edge_id
+                    && (!compact_graph || graphlog_edges.is_empty())
+                {
                     graphlog_edges.push(GraphEdge::missing((missing_edge_id, false)));
                 }
                 let mut buffer = vec![];
-                let key = (commit_id, false);
-                let commit = store.get_commit(&key.0)?;
                 let within_graph =
                     with_content_format.sub_width(graph.width(&key, &graphlog_edges));
                 within_graph.write(ui.new_formatter(&mut buffer).as_mut(), |formatter| {
@@ -302,24 +440,26 @@ pub(crate) fn cmd_log(
                 explicit_paths.retain(|&path| tree.path_value(path).unwrap().is_absent());
 
                 for elided_target in elided_targets {
-                    let elided_key = (elided_target, true);
-                    let real_key = (elided_key.0.clone(), false);
-                    let edges = [GraphEdge::direct(real_key)];
-                    let mut buffer = vec![];
-                    let within_graph =
-                        with_content_format.sub_width(graph.width(&elided_key, &edges));
-                    within_graph.write(ui.new_formatter(&mut buffer).as_mut(), |formatter| {
-                        writeln!(formatter.labeled("elided"), "(elided revisions)")
-                    })?;
-                    let node_symbol = format_template(ui, &None, &node_template);
-                    graph.add_node(
-                        &elided_key,
-                        &edges,
-                        &node_symbol,
-                        &String::from_utf8_lossy(&buffer),
+                    add_synthetic_node(
+                        ui,
+                        graph.as_mut(),
+                        &with_content_format,
+                        &node_template,
+                        (elided_target.clone(), elided_target),
+                        "(elided revisions)",
                     )?;
                 }
             }
+            for elided_target in pending_elided_targets {
+                add_synthetic_node(
+                    ui,
+                    graph.as_mut(),
+                    &with_content_format,
+                    &node_template,
+                    (elided_target.clone(), elided_target),
+                    "(elided revisions)",
+                )?;
+            }
         } else {
             let iter: Box<dyn Iterator<Item = Result<CommitId, RevsetEvaluationError>>> = {
                 let forward_iter = revset.iter().take(args.limit.unwrap_or(usize::MAX));
@@ -385,3 +525,112 @@ pub(crate) fn cmd_log(
 
     Ok(())
 }
+
+/// Renders a synthetic node keyed by `id` with an edge to the real revision
+/// `target`.
+fn add_synthetic_node(
+    ui: &Ui,
+    graph: &mut dyn GraphLog<(CommitId, bool)>,
+    with_content_format: &LogContentFormat,
+    node_template: &TemplateRenderer<Option<Commit>>,
+    (id, target): (CommitId, CommitId),
+    text: &str,
+) -> Result<(), CommandError> {
+    let key = (id, true);
+    let edges = [GraphEdge::direct((target, false))];
+    let mut buffer = vec![];
+    let within_graph = with_content_format.sub_width(graph.width(&key, &edges));
+    within_graph.write(ui.new_formatter(&mut buffer).as_mut(), |formatter| {
+        writeln!(formatter.labeled("elided"), "{text}")
+    })?;
+    let node_symbol = format_template(ui, &None, node_template);
+    graph.add_node(
+        &key,
+        &edges,
+        &node_symbol,
+        &String::from_utf8_lossy(&buffer),
+    )?;
+    Ok(())
+}
+
+/// Finds runs of revisions that are linearly connected to both the previous
+/// and the next revision in the graph, and aren't in the `keep` set. Returns
+/// the ranges of indices of runs with at least two revisions.
+fn find_linear_runs(
+    nodes: &[GraphNode<CommitId>],
+    keep: &RevsetContainingFn,
+) -> Result<Vec<Range<usize>>, RevsetEvaluationError> {
+    let mut num_children: HashMap<&CommitId, usize> = HashMap::new();
+    for (_, edges) in nodes {
+        for edge in edges {
+            if edge.edge_type != GraphEdgeType::Missing {
+                *num_children.entry(&edge.target).or_default() += 1;
+            }
+        }
+    }
+    let is_linear = |(_, edges): &GraphNode<CommitId>, (next_id, _): &GraphNode<CommitId>| {
+        matches!(
+            edges.as_slice(),
+            [edge] if edge.edge_type == GraphEdgeType::Direct && edge.target == *next_id
+        ) && num_children.get(next_id) == Some(&1)
+    };
+    let mut runs = vec![];
+    let mut run_start = None;
+    for i in 0..nodes.len() {
+        let collapsible = i > 0
+            && i + 1 < nodes.len()
+            && is_linear(&nodes[i - 1], &nodes[i])
+            && is_linear(&nodes[i], &nodes[i + 1])
+            && !keep(&nodes[i].0)?;
+        match (collapsible, run_start) {
+            (true, None) => run_start = Some(i),
+            (false, Some(start)) => {
+                runs.push(start..i);
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    runs.retain(|run| run.len() >= 2);
+    Ok(runs)
+}
+
+/// Maps each revision hidden by `--elide` to the revisions that can be
+/// reached from it through hidden revisions only.
+fn elided_edge_targets(
+    store: &Arc<Store>,
+    elided_revset: &dyn Revset,
+) -> Result<HashMap<CommitId, HashSet<CommitId>>, CommandError> {
+    let elided_ids: Vec<_> = elided_revset.iter().try_collect()?;
+    let mut targets: HashMap<CommitId, HashSet<CommitId>> = HashMap::new();
+    // Visit parents before children so the targets of hidden parents are
+    // known.
+    for id in elided_ids.into_iter().rev() {
+        let commit = store.get_commit(&id)?;
+        let mut commit_targets = HashSet::new();
+        for parent_id in commit.parent_ids() {
+            match targets.get(parent_id) {
+                Some(parent_targets) => commit_targets.extend(parent_targets.iter().cloned()),
+                None => {
+                    commit_targets.insert(parent_id.clone());
+                }
+            }
+        }
+        targets.insert(id, commit_targets);
+    }
+    Ok(targets)
+}
+
+/// Returns true if `target` can be reached from `commit` through revisions
+/// hidden by `--elide` only.
+fn is_elided_edge(
+    elided_edges: &HashMap<CommitId, HashSet<CommitId>>,
+    commit: &Commit,
+    target: &CommitId,
+) -> bool {
+    commit.parent_ids().iter().any(|parent_id| {
+        elided_edges
+            .get(parent_id)
+            .is_some_and(|targets| targets.contains(target))
+    })
+}
@@ -14,6 +14,8 @@
 
 mod abandon;
 mod absorb;
+mod api;
+mod autosquash;
 #[cfg(feature = "bench")]
 mod bench;
 mod bisect;
@@ -39,6 +41,8 @@ mod log;
 mod metaedit;
 mod new;
 mod next;
+#[cfg(feature = "git")]
+mod note;
 mod operation;
 mod parallelize;
 mod prev;
@@ -56,6 +60,9 @@ mod sparse;
 mod split;
 mod squash;
 mod status;
+#[cfg(feature = "git")]
+mod submodule;
+mod swap;
 mod tag;
 mod undo;
 mod unsign;
@@ -93,6 +100,8 @@ const STYLES: Styles = Styles::styled()
 enum Command {
     Abandon(abandon::AbandonArgs),
     Absorb(absorb::AbsorbArgs),
+    Api(api::ApiArgs),
+    Autosquash(autosquash::AutosquashArgs),
     #[cfg(feature = "bench")]
     #[command(subcommand)]
     Bench(bench::BenchCommand),
@@ -127,6 +136,9 @@ enum Command {
     Metaedit(metaedit::MetaeditArgs),
     New(new::NewArgs),
     Next(next::NextArgs),
+    #[cfg(feature = "git")]
+    #[command(subcommand)]
+    Note(note::NoteCommand),
     #[command(subcommand)]
     #[command(visible_alias = "op")]
     Operation(operation::OperationCommand),
@@ -149,6 +161,10 @@ enum Command {
     Split(split::SplitArgs),
     Squash(squash::SquashArgs),
     Status(status::StatusArgs),
+    #[cfg(feature = "git")]
+    #[command(subcommand)]
+    Submodule(submodule::SubmoduleCommand),
+    Swap(swap::SwapArgs),
     #[command(subcommand)]
     Tag(tag::TagCommand),
     Undo(undo::UndoArgs),
@@ -170,6 +186,8 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
     match &subcommand {
         Command::Abandon(args) => abandon::cmd_abandon(ui, command_helper, args),
         Command::Absorb(args) => absorb::cmd_absorb(ui, command_helper, args),
+        Command::Api(args) => api::cmd_api(ui, command_helper, args),
+        Command::Autosquash(args) => autosquash::cmd_autosquash(ui, command_helper, args),
         #[cfg(feature = "bench")]
         Command::Bench(args) => bench::cmd_bench(ui, command_helper, args),
         Command::Bisect(args) => bisect::cmd_bisect(ui, command_helper, args),
@@ -195,6 +213,8 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
         Command::Metaedit(args) => metaedit::cmd_metaedit(ui, command_helper, args),
         Command::New(args) => new::cmd_new(ui, command_helper, args),
         Command::Next(args) => next::cmd_next(ui, command_helper, args),
+        #[cfg(feature = "git")]
+        Command::Note(args) => note::cmd_note(ui, command_helper, args),
         Command::Operation(args) => operation::cmd_operation(ui, command_helper, args),
         Command::Parallelize(args) => parallelize::cmd_parallelize(ui, command_helper, args),
         Command::Prev(args) => prev::cmd_prev(ui, command_helper, args),
@@ -214,6 +234,9 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
         Command::Split(args) => split::cmd_split(ui, command_helper, args),
         Command::Squash(args) => squash::cmd_squash(ui, command_helper, args),
         Command::Status(args) => status::cmd_status(ui, command_helper, args),
+        #[cfg(feature = "git")]
+        Command::Submodule(args) => submodule::cmd_submodule(ui, command_helper, args),
+        Command::Swap(args) => swap::cmd_swap(ui, command_helper, args),
         Command::Tag(args) => tag::cmd_tag(ui, command_helper, args),
         Command::Undo(args) => undo::cmd_undo(ui, command_helper, args),
         Command::Unsign(args) => unsign::cmd_unsign(ui, command_helper, args),
@@ -47,6 +47,9 @@ use crate::ui::Ui;
 /// |        |
 /// @        A
 /// ```
+///
+/// If there are multiple children to choose from, you are asked to pick one of
+/// them. Use `--no-interactive` to fail instead, for example in scripts.
 #[derive(clap::Args, Clone, Debug)]
 #[command(verbatim_doc_comment)]
 pub(crate) struct NextArgs {
@@ -74,6 +77,11 @@ pub(crate) struct NextArgs {
     /// Jump to the next conflicted descendant
     #[arg(long, conflicts_with = "offset")]
     conflict: bool,
+
+    /// Fail instead of prompting if there are multiple child revisions to
+    /// choose from
+    #[arg(long)]
+    no_interactive: bool,
 }
 
 impl From<&NextArgs> for MovementArgs {
@@ -83,6 +91,7 @@ impl From<&NextArgs> for MovementArgs {
             edit: val.edit,
             no_edit: val.no_edit,
             conflict: val.conflict,
+            no_interactive: val.no_interactive,
         }
     }
 }
@@ -0,0 +1,95 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCompleter;
+use jj_lib::git_notes::DEFAULT_NOTES_REF;
+use tracing::instrument;
+
+use super::load_notes;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::description_util::cleanup_description_lines;
+use crate::description_util::join_message_paragraphs;
+use crate::ui::Ui;
+
+/// Attach a note to a revision
+///
+/// An existing note of the revision is replaced. Without `--message`, an
+/// editor is opened to edit the existing note. If the resulting note is empty,
+/// the note is removed.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct NoteAddArgs {
+    /// The revision to attach the note to
+    #[arg(long, short, default_value = "@", value_name = "REVSET")]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
+    revision: RevisionArg,
+
+    /// The note to attach (don't open editor)
+    #[arg(long = "message", short, value_name = "MESSAGE")]
+    message_paragraphs: Vec<String>,
+
+    /// The notes ref to use
+    #[arg(long = "ref", value_name = "REF", default_value = DEFAULT_NOTES_REF)]
+    notes_ref: String,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_note_add(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &NoteAddArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let mut notes = load_notes(&workspace_command, &args.notes_ref)?;
+    let text = if args.message_paragraphs.is_empty() {
+        let existing = notes.get(commit.id())?.unwrap_or_default();
+        let mut text = String::from_utf8_lossy(&existing).into_owned();
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str("\nJJ: Enter the note for the revision.\n");
+        text.push_str("JJ: Lines starting with \"JJ:\" (like this one) will be removed.\n");
+        let text = workspace_command
+            .text_editor()?
+            .edit_str(text, Some(".jjnote"))
+            .map_err(|err| err.with_name("note"))?;
+        cleanup_description_lines(text.lines())
+    } else {
+        join_message_paragraphs(&args.message_paragraphs)
+    };
+
+    let (note, message, action) = if text.is_empty() {
+        (None, "Notes removed by 'jj note add'", "Removed note from")
+    } else {
+        (
+            Some(text.as_bytes()),
+            "Notes added by 'jj note add'",
+            "Added note to",
+        )
+    };
+    let changed = notes.set(commit.id(), note, &command.settings().signature(), message)?;
+    if let Some(mut formatter) = ui.status_formatter() {
+        if changed {
+            write!(formatter, "{action} ")?;
+            workspace_command.write_commit_summary(formatter.as_mut(), &commit)?;
+            writeln!(formatter)?;
+        } else {
+            writeln!(formatter, "Nothing changed.")?;
+        }
+    }
+    Ok(())
+}
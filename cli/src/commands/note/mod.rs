@@ -0,0 +1,108 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod add;
+mod fetch;
+mod push;
+mod remove;
+mod show;
+
+use clap::Subcommand;
+use jj_lib::git_notes;
+use jj_lib::git_notes::GitNotes;
+use jj_lib::ref_name::RemoteName;
+use jj_lib::ref_name::RemoteNameBuf;
+use jj_lib::repo::Repo as _;
+use tracing::instrument;
+
+use self::add::NoteAddArgs;
+use self::add::cmd_note_add;
+use self::fetch::NoteFetchArgs;
+use self::fetch::cmd_note_fetch;
+use self::push::NotePushArgs;
+use self::push::cmd_note_push;
+use self::remove::NoteRemoveArgs;
+use self::remove::cmd_note_remove;
+use self::show::NoteShowArgs;
+use self::show::cmd_note_show;
+use super::git::get_single_remote;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+const DEFAULT_REMOTE: &RemoteName = RemoteName::new("origin");
+
+/// Manage Git notes attached to commits
+///
+/// Notes are stored in the Git repository under `refs/notes/`, separately from
+/// the commits they're attached to, so adding a note doesn't rewrite the
+/// commit. Notes use the `refs/notes/commits` ref by default, like `git notes`
+/// does. Use `--ref` to work with other notes refs, such as the ones written by
+/// CI systems.
+///
+/// Changes to notes aren't recorded in the operation log, so they can't be
+/// undone with `jj undo`.
+#[derive(Subcommand, Clone, Debug)]
+pub(crate) enum NoteCommand {
+    Add(NoteAddArgs),
+    Fetch(NoteFetchArgs),
+    Push(NotePushArgs),
+    Remove(NoteRemoveArgs),
+    Show(NoteShowArgs),
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_note(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &NoteCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        NoteCommand::Add(args) => cmd_note_add(ui, command, args),
+        NoteCommand::Fetch(args) => cmd_note_fetch(ui, command, args),
+        NoteCommand::Push(args) => cmd_note_push(ui, command, args),
+        NoteCommand::Remove(args) => cmd_note_remove(ui, command, args),
+        NoteCommand::Show(args) => cmd_note_show(ui, command, args),
+    }
+}
+
+/// Loads the notes of the notes ref named `name`, which may be a short name.
+fn load_notes(
+    workspace_command: &WorkspaceCommandHelper,
+    name: &str,
+) -> Result<GitNotes, CommandError> {
+    let ref_name = git_notes::expand_notes_ref(name);
+    Ok(GitNotes::load(workspace_command.repo().store(), &ref_name)?)
+}
+
+/// Returns the remote to transfer notes from or to if none was specified:
+/// the only remote if there's a single one, and `origin` otherwise.
+fn get_default_remote(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+) -> Result<RemoteNameBuf, CommandError> {
+    if let Some(remote) = get_single_remote(workspace_command.repo().store())? {
+        if remote != DEFAULT_REMOTE {
+            writeln!(
+                ui.hint_default(),
+                "Using the only existing remote: {remote}",
+                remote = remote.as_symbol()
+            )?;
+        }
+        Ok(remote)
+    } else {
+        Ok(DEFAULT_REMOTE.to_owned())
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCandidates;
+use jj_lib::git::GitSubprocessOptions;
+use jj_lib::git_notes;
+use jj_lib::git_notes::DEFAULT_NOTES_REF;
+use jj_lib::ref_name::RemoteNameBuf;
+use jj_lib::repo::Repo as _;
+use tracing::instrument;
+
+use super::get_default_remote;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Push notes to a Git remote
+///
+/// The push is rejected if the remote notes ref has notes which haven't been
+/// fetched. Run `jj note fetch` first in that case.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct NotePushArgs {
+    /// The remote to push to
+    ///
+    /// This defaults to the only existing remote, or `origin` if there are
+    /// several.
+    #[arg(long, value_name = "REMOTE")]
+    #[arg(add = ArgValueCandidates::new(complete::git_remotes))]
+    remote: Option<RemoteNameBuf>,
+
+    /// The notes ref to push
+    #[arg(long = "ref", value_name = "REF", default_value = DEFAULT_NOTES_REF)]
+    notes_ref: String,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_note_push(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &NotePushArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let remote = match &args.remote {
+        Some(remote) => remote.clone(),
+        None => get_default_remote(ui, &workspace_command)?,
+    };
+    let ref_name = git_notes::expand_notes_ref(&args.notes_ref);
+    git_notes::push_notes(
+        workspace_command.repo().store(),
+        GitSubprocessOptions::from_settings(command.settings())?,
+        &remote,
+        &ref_name,
+    )?;
+    writeln!(
+        ui.status(),
+        "Pushed {ref_name} to {remote}",
+        remote = remote.as_symbol()
+    )?;
+    Ok(())
+}
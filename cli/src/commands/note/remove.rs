@@ -0,0 +1,64 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCompleter;
+use jj_lib::git_notes::DEFAULT_NOTES_REF;
+use tracing::instrument;
+
+use super::load_notes;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Remove the note of a revision
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct NoteRemoveArgs {
+    /// The revision whose note to remove
+    #[arg(long, short, default_value = "@", value_name = "REVSET")]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
+    revision: RevisionArg,
+
+    /// The notes ref to use
+    #[arg(long = "ref", value_name = "REF", default_value = DEFAULT_NOTES_REF)]
+    notes_ref: String,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_note_remove(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &NoteRemoveArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let mut notes = load_notes(&workspace_command, &args.notes_ref)?;
+    let changed = notes.set(
+        commit.id(),
+        None,
+        &command.settings().signature(),
+        "Notes removed by 'jj note remove'",
+    )?;
+    if let Some(mut formatter) = ui.status_formatter() {
+        if changed {
+            write!(formatter, "Removed note from ")?;
+            workspace_command.write_commit_summary(formatter.as_mut(), &commit)?;
+            writeln!(formatter)?;
+        } else {
+            writeln!(formatter, "Nothing changed.")?;
+        }
+    }
+    Ok(())
+}
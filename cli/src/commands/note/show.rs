@@ -0,0 +1,59 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCompleter;
+use jj_lib::git_notes::DEFAULT_NOTES_REF;
+use tracing::instrument;
+
+use super::load_notes;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::short_commit_hash;
+use crate::command_error::CommandError;
+use crate::command_error::user_error;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Print the note of a revision
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct NoteShowArgs {
+    /// The revision whose note to print
+    #[arg(long, short, default_value = "@", value_name = "REVSET")]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
+    revision: RevisionArg,
+
+    /// The notes ref to use
+    #[arg(long = "ref", value_name = "REF", default_value = DEFAULT_NOTES_REF)]
+    notes_ref: String,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_note_show(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &NoteShowArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let notes = load_notes(&workspace_command, &args.notes_ref)?;
+    let Some(note) = notes.get(commit.id())? else {
+        return Err(user_error(format!(
+            "Revision {} has no note in {}",
+            short_commit_hash(commit.id()),
+            notes.ref_name()
+        )));
+    };
+    ui.stdout_formatter().write_all(&note)?;
+    Ok(())
+}
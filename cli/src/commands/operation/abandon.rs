@@ -16,9 +16,11 @@ use std::io::Write as _;
 use std::iter;
 use std::slice;
 
-use clap_complete::ArgValueCandidates;
+use clap_complete::ArgValueCompleter;
 use itertools::Itertools as _;
 use jj_lib::op_walk;
+use jj_lib::operation::Operation;
+use jj_lib::workspace::Workspace;
 use pollster::FutureExt as _;
 
 use crate::cli_util::CommandHelper;
@@ -45,7 +47,7 @@ use crate::ui::Ui;
 #[derive(clap::Args, Clone, Debug)]
 pub struct OperationAbandonArgs {
     /// The operation or operation range to abandon
-    #[arg(add = ArgValueCandidates::new(complete::operations))]
+    #[arg(add = ArgValueCompleter::new(complete::operation_range))]
     operation: String,
 }
 
@@ -105,19 +107,43 @@ pub fn cmd_op_abandon(
         return Err(err);
     }
 
+    abandon_operation_range(
+        ui,
+        command,
+        &mut workspace,
+        &abandon_root_op,
+        &abandon_head_ops,
+        &current_head_ops,
+    )
+}
+
+/// Abandons the operations between `abandon_root_op` (exclusive) and
+/// `abandon_head_ops` (inclusive) by reparenting their descendants onto
+/// `abandon_root_op`. The `current_head_ops` must not be abandoned.
+pub(crate) fn abandon_operation_range(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    workspace: &mut Workspace,
+    abandon_root_op: &Operation,
+    abandon_head_ops: &[Operation],
+    current_head_ops: &[Operation],
+) -> Result<(), CommandError> {
+    let repo_loader = workspace.repo_loader();
+    let op_store = repo_loader.op_store();
+    let op_heads_store = repo_loader.op_heads_store().clone();
     // Reparent descendants, count the number of abandoned operations.
     let stats = op_walk::reparent_range(
         op_store.as_ref(),
-        &abandon_head_ops,
-        &current_head_ops,
-        &abandon_root_op,
+        abandon_head_ops,
+        current_head_ops,
+        abandon_root_op,
     )?;
     assert_eq!(
         current_head_ops.len(),
         stats.new_head_ids.len(),
         "all current_head_ops should be reparented as they aren't included in abandon_head_ops"
     );
-    let reparented_head_ops = || iter::zip(&current_head_ops, &stats.new_head_ids);
+    let reparented_head_ops = || iter::zip(current_head_ops, &stats.new_head_ids);
     if reparented_head_ops().all(|(old, new_id)| old.id() == new_id) {
         writeln!(ui.status(), "Nothing changed.")?;
         return Ok(());
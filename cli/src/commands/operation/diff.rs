@@ -407,7 +407,7 @@ fn write_modified_change_summary(
 }
 
 /// Writes a summary for the given `RefTarget`.
-fn write_ref_target_summary(
+pub fn write_ref_target_summary(
     formatter: &mut dyn Formatter,
     repo: &dyn Repo,
     commit_summary_template: &TemplateRenderer<Commit>,
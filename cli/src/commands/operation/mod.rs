@@ -12,20 +12,46 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-mod abandon;
+pub mod abandon;
 mod diff;
 mod log;
+#[cfg(feature = "git")]
+mod pull;
+#[cfg(feature = "git")]
+mod push;
 mod restore;
 pub mod revert;
 mod show;
 
+#[cfg(feature = "git")]
+use std::io::Write as _;
+
 use abandon::OperationAbandonArgs;
 use abandon::cmd_op_abandon;
 use clap::Subcommand;
 use diff::OperationDiffArgs;
 use diff::cmd_op_diff;
+pub use diff::write_ref_target_summary;
+#[cfg(feature = "git")]
+use jj_lib::git::GitSubprocessOptions;
+#[cfg(feature = "git")]
+use jj_lib::git::get_git_backend;
+#[cfg(feature = "git")]
+use jj_lib::op_sync;
+#[cfg(feature = "git")]
+use jj_lib::ref_name::RemoteNameBuf;
+#[cfg(feature = "git")]
+use jj_lib::workspace::Workspace;
 use log::OperationLogArgs;
 use log::cmd_op_log;
+#[cfg(feature = "git")]
+use pull::OperationPullArgs;
+#[cfg(feature = "git")]
+use pull::cmd_op_pull;
+#[cfg(feature = "git")]
+use push::OperationPushArgs;
+#[cfg(feature = "git")]
+use push::cmd_op_push;
 use restore::OperationRestoreArgs;
 use restore::cmd_op_restore;
 use revert::OperationRevertArgs;
@@ -35,6 +61,10 @@ use show::cmd_op_show;
 
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
+#[cfg(feature = "git")]
+use crate::command_error::cli_error;
+#[cfg(feature = "git")]
+use crate::command_error::user_error_with_hint;
 use crate::commands::renamed_cmd;
 use crate::ui::Ui;
 
@@ -49,6 +79,10 @@ pub enum OperationCommand {
     Abandon(OperationAbandonArgs),
     Diff(OperationDiffArgs),
     Log(OperationLogArgs),
+    #[cfg(feature = "git")]
+    Pull(OperationPullArgs),
+    #[cfg(feature = "git")]
+    Push(OperationPushArgs),
     Restore(OperationRestoreArgs),
     Revert(OperationRevertArgs),
     Show(OperationShowArgs),
@@ -66,6 +100,10 @@ pub fn cmd_operation(
         OperationCommand::Abandon(args) => cmd_op_abandon(ui, command, args),
         OperationCommand::Diff(args) => cmd_op_diff(ui, command, args),
         OperationCommand::Log(args) => cmd_op_log(ui, command, args),
+        #[cfg(feature = "git")]
+        OperationCommand::Pull(args) => cmd_op_pull(ui, command, args),
+        #[cfg(feature = "git")]
+        OperationCommand::Push(args) => cmd_op_push(ui, command, args),
         OperationCommand::Restore(args) => cmd_op_restore(ui, command, args),
         OperationCommand::Revert(args) => cmd_op_revert(ui, command, args),
         OperationCommand::Show(args) => cmd_op_show(ui, command, args),
@@ -76,14 +114,109 @@ pub fn cmd_operation(
     }
 }
 
+/// Repo to transfer operations from or to.
+#[cfg(feature = "git")]
+enum OtherRepo {
+    Workspace(Box<Workspace>),
+    GitRemote(RemoteNameBuf),
+}
+
+/// Resolves `name` to a Git remote of the `local` repo, or to the workspace at
+/// that path.
+#[cfg(feature = "git")]
+fn load_other_repo(
+    command: &CommandHelper,
+    local: &Workspace,
+    name: &str,
+) -> Result<OtherRepo, CommandError> {
+    let git_backend = get_git_backend(local.repo_loader().store())?;
+    if git_backend.git_repo().try_find_remote(name).is_some() {
+        return Ok(OtherRepo::GitRemote(name.into()));
+    }
+    let workspace_root = command.cwd().join(name);
+    if !workspace_root.exists() {
+        return Err(user_error_with_hint(
+            format!("No git remote or repo path named {name}"),
+            "Specify the name of a Git remote, or the path to a workspace of another repo.",
+        ));
+    }
+    let workspace = command.load_workspace_at(&workspace_root, command.settings())?;
+    Ok(OtherRepo::Workspace(Box::new(workspace)))
+}
+
+/// Copies the operations missing from the `other` repo to it if `push`, or
+/// the other way around, and reports what was copied.
+#[cfg(feature = "git")]
+fn transfer_operations(
+    ui: &Ui,
+    command: &CommandHelper,
+    local: &Workspace,
+    other: &OtherRepo,
+    push: bool,
+) -> Result<(), CommandError> {
+    // The operations are merged with the current head when the repo is
+    // loaded, so it doesn't make sense to start from another operation.
+    if command.global_args().at_operation.is_some() {
+        return Err(cli_error("--at-op is not respected"));
+    }
+    let subprocess_options = GitSubprocessOptions::from_settings(command.settings())?;
+    let local_loader = local.repo_loader();
+    let stats = match other {
+        OtherRepo::Workspace(other) if push => {
+            op_sync::transfer_operations(local_loader, other.repo_loader(), subprocess_options)?
+        }
+        OtherRepo::Workspace(other) => {
+            op_sync::transfer_operations(other.repo_loader(), local_loader, subprocess_options)?
+        }
+        OtherRepo::GitRemote(remote_name) if push => {
+            op_sync::push_operations(local_loader, subprocess_options, remote_name)?
+        }
+        OtherRepo::GitRemote(remote_name) => {
+            op_sync::pull_operations(local_loader, subprocess_options, remote_name)?
+        }
+    };
+    if stats.transferred_operations.is_empty() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+    let verb = if push { "Pushed" } else { "Pulled" };
+    writeln!(
+        ui.status(),
+        "{verb} {} operations",
+        stats.transferred_operations.len()
+    )?;
+    if !stats.diverged {
+        return Ok(());
+    }
+    match other {
+        OtherRepo::GitRemote(remote_name) if push => writeln!(
+            ui.hint_default(),
+            "The remote has operations which aren't in this repo. Run `jj op pull {}` to merge \
+             the operation logs.",
+            remote_name.as_symbol()
+        )?,
+        _ => writeln!(
+            ui.hint_default(),
+            "The operation logs have diverged. The operations will be merged the next time the \
+             repo is loaded."
+        )?,
+    }
+    Ok(())
+}
+
 // pub for `jj undo`
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
 pub(crate) enum RevertWhatToRestore {
-    /// The jj repo state and local bookmarks
+    /// The jj repo state, including the local bookmarks and the working-copy
+    /// commits
     Repo,
     /// The remote-tracking bookmarks. Do not restore these if you'd like to
     /// push after the undo
     RemoteTracking,
+    /// The local bookmarks only
+    Bookmarks,
+    /// The working-copy commits only
+    WorkingCopy,
 }
 
 // pub for `jj undo`
@@ -98,23 +231,46 @@ pub(crate) fn view_with_desired_portions_restored(
     current_view: &jj_lib::op_store::View,
     what: &[RevertWhatToRestore],
 ) -> jj_lib::op_store::View {
-    let repo_source = if what.contains(&RevertWhatToRestore::Repo) {
-        view_being_restored
-    } else {
-        current_view
-    };
-    let remote_source = if what.contains(&RevertWhatToRestore::RemoteTracking) {
-        view_being_restored
-    } else {
-        current_view
+    let restore_repo = what.contains(&RevertWhatToRestore::Repo);
+    let restore_bookmarks = restore_repo || what.contains(&RevertWhatToRestore::Bookmarks);
+    let restore_working_copy = restore_repo || what.contains(&RevertWhatToRestore::WorkingCopy);
+    let restore_remote = what.contains(&RevertWhatToRestore::RemoteTracking);
+    let source = |restore: bool| {
+        if restore {
+            view_being_restored
+        } else {
+            current_view
+        }
     };
+    let repo_source = source(restore_repo);
+    let bookmarks_source = source(restore_bookmarks);
+    let working_copy_source = source(restore_working_copy);
+    let mut head_ids = repo_source.head_ids.clone();
+    if !restore_repo {
+        // Keep the restored bookmarks and working-copy commits visible. The
+        // redundant heads are removed when the view is set.
+        if restore_bookmarks {
+            head_ids.extend(
+                bookmarks_source
+                    .local_bookmarks
+                    .values()
+                    .flat_map(|target| target.added_ids())
+                    .cloned(),
+            );
+        }
+        if restore_working_copy {
+            head_ids.extend(working_copy_source.wc_commit_ids.values().cloned());
+        }
+    }
     jj_lib::op_store::View {
-        head_ids: repo_source.head_ids.clone(),
-        local_bookmarks: repo_source.local_bookmarks.clone(),
+        head_ids,
+        local_bookmarks: bookmarks_source.local_bookmarks.clone(),
         local_tags: repo_source.local_tags.clone(),
-        remote_views: remote_source.remote_views.clone(),
+        remote_views: source(restore_remote).remote_views.clone(),
         git_refs: current_view.git_refs.clone(),
         git_head: current_view.git_head.clone(),
-        wc_commit_ids: repo_source.wc_commit_ids.clone(),
+        wc_commit_ids: working_copy_source.wc_commit_ids.clone(),
+        deferred_rewrites: repo_source.deferred_rewrites.clone(),
+        ephemeral_workspaces: working_copy_source.ephemeral_workspaces.clone(),
     }
 }
@@ -0,0 +1,49 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::load_other_repo;
+use super::transfer_operations;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Copy operations from another repo into this one
+///
+/// Operations which exist in the other repo but not in this one are copied,
+/// along with the commits they reference. If operations were also made in this
+/// repo, the operation logs have diverged, and the operations are merged the
+/// next time the repo is loaded, just like concurrent operations.
+///
+/// This lets you continue working on another machine with the same history of
+/// changes and operations, as long as both repos are backed by Git. The other
+/// repo can be a Git remote which operations were pushed to with `jj op push`,
+/// or a local path, e.g. on a mounted drive. The working-copy commits are
+/// recorded by workspace name, so each copy of the repo should use different
+/// workspace names (see `jj workspace rename`).
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationPullArgs {
+    /// Git remote, or path to a workspace of the repo to copy operations from
+    #[arg(value_name = "REMOTE_OR_PATH", value_hint = clap::ValueHint::DirPath)]
+    repo: String,
+}
+
+pub fn cmd_op_pull(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationPullArgs,
+) -> Result<(), CommandError> {
+    let local = command.load_workspace()?;
+    let other = load_other_repo(command, &local, &args.repo)?;
+    transfer_operations(ui, command, &local, &other, false)
+}
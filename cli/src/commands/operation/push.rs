@@ -0,0 +1,45 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::load_other_repo;
+use super::transfer_operations;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Copy operations from this repo into another one
+///
+/// Operations which exist in this repo but not in the other one are copied,
+/// along with the commits they reference. See `jj op pull` for details.
+///
+/// When pushing to a Git remote, each operation is stored as a Git commit, and
+/// the operation heads are stored as `refs/jj/op-heads/<operation id>` refs.
+/// Heads of the remote which aren't in this repo are kept, and are merged when
+/// they're pulled.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationPushArgs {
+    /// Git remote, or path to a workspace of the repo to copy operations to
+    #[arg(value_name = "REMOTE_OR_PATH", value_hint = clap::ValueHint::DirPath)]
+    repo: String,
+}
+
+pub fn cmd_op_push(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationPushArgs,
+) -> Result<(), CommandError> {
+    let local = command.load_workspace()?;
+    let other = load_other_repo(command, &local, &args.repo)?;
+    transfer_operations(ui, command, &local, &other, true)
+}
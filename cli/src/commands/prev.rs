@@ -46,6 +46,9 @@ use crate::ui::Ui;
 /// |        |
 /// A        A
 /// ```
+///
+/// If there are multiple parents to choose from, you are asked to pick one of
+/// them. Use `--no-interactive` to fail instead, for example in scripts.
 #[derive(clap::Args, Clone, Debug)]
 #[command(verbatim_doc_comment)]
 pub(crate) struct PrevArgs {
@@ -70,6 +73,11 @@ pub(crate) struct PrevArgs {
     /// Jump to the previous conflicted ancestor
     #[arg(long, conflicts_with = "offset")]
     conflict: bool,
+
+    /// Fail instead of prompting if there are multiple parent revisions to
+    /// choose from
+    #[arg(long)]
+    no_interactive: bool,
 }
 
 impl From<&PrevArgs> for MovementArgs {
@@ -79,6 +87,7 @@ impl From<&PrevArgs> for MovementArgs {
             edit: val.edit,
             no_edit: val.no_edit,
             conflict: val.conflict,
+            no_interactive: val.no_interactive,
         }
     }
 }
@@ -12,18 +12,26 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fmt::Write as _;
 use std::io::Write as _;
+use std::iter;
 use std::sync::Arc;
 
 use clap::ArgGroup;
 use clap_complete::ArgValueCompleter;
+use indoc::indoc;
 use itertools::Itertools as _;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
+use jj_lib::merge::Diff;
+use jj_lib::merge::Merge;
+use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId as _;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo as _;
 use jj_lib::revset::RevsetExpression;
+use jj_lib::revset::RevsetIteratorExt as _;
+use jj_lib::rewrite::CommitRewriter;
 use jj_lib::rewrite::EmptyBehavior;
 use jj_lib::rewrite::MoveCommitsLocation;
 use jj_lib::rewrite::MoveCommitsStats;
@@ -32,6 +40,7 @@ use jj_lib::rewrite::RebaseOptions;
 use jj_lib::rewrite::RewriteRefsOptions;
 use jj_lib::rewrite::compute_move_commits;
 use jj_lib::rewrite::find_duplicate_divergent_commits;
+use pollster::FutureExt as _;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -42,7 +51,9 @@ use crate::cli_util::print_updated_commits;
 use crate::cli_util::short_commit_hash;
 use crate::command_error::CommandError;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
 use crate::complete;
+use crate::description_util::try_combine_messages;
 use crate::ui::Ui;
 
 /// Move revisions to different parent(s)
@@ -262,6 +273,29 @@ use crate::ui::Ui;
 /// |/          |/
 /// J           J
 /// ```
+///
+/// ### Reordering a stack interactively
+///
+/// With `--reorder`, the revisions given by `-r` must form a linear stack. An
+/// editor is opened with one line per revision, from the bottom of the stack
+/// to the top. Move the lines to reorder the revisions, change `pick` to
+/// `drop` to abandon a revision, or change `pick` to `squash` to squash a
+/// revision into the one on the line above it. For example, with the todo
+/// list `pick K`, `pick M`, `squash L` (`jj rebase -r K::M --reorder`):
+///
+/// ```text
+/// N           N'
+/// |           |
+/// M           M'+L'
+/// |           |
+/// L     =>    K'
+/// |           |
+/// K           J
+/// |
+/// J
+/// ```
+///
+/// Descendants of the top of the stack are rebased onto the new top.
 #[derive(clap::Args, Clone, Debug)]
 #[command(verbatim_doc_comment)]
 #[command(group(ArgGroup::new("to_rebase").args(&["branch", "source", "revisions"])))]
@@ -317,6 +351,18 @@ pub(crate) struct RebaseArgs {
     /// destination with identical changes.
     #[arg(long)]
     keep_divergent: bool,
+
+    /// Open an editor to reorder, drop, or squash the revisions given by `-r`
+    ///
+    /// The revisions must form a linear stack. See the "Reordering a stack
+    /// interactively" section above.
+    #[arg(
+        long,
+        group = "RebaseDestinationArgs",
+        requires = "revisions",
+        conflicts_with_all = ["onto", "insert_after", "insert_before", "skip_emptied", "keep_divergent"],
+    )]
+    reorder: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -375,6 +421,9 @@ pub(crate) fn cmd_rebase(
         },
         simplify_ancestor_merge: false,
     };
+    if args.reorder {
+        return reorder_revisions(ui, command, &args.revisions);
+    }
     let mut workspace_command = command.workspace_helper(ui)?;
     let loc = if !args.revisions.is_empty() {
         plan_rebase_revisions(ui, &workspace_command, &args.revisions, &args.destination)?
@@ -534,6 +583,241 @@ fn plan_rebase_branch(
     })
 }
 
+/// An action on a revision in the `--reorder` todo list.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ReorderAction {
+    Pick,
+    Squash,
+    Drop,
+}
+
+/// Rewrites the linear stack given by `revisions` in the order chosen by the
+/// user in an editor.
+fn reorder_revisions(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    revisions: &[RevisionArg],
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let target_expr = workspace_command
+        .parse_union_revsets(ui, revisions)?
+        .resolve()?;
+    workspace_command.check_rewritable_expr(&target_expr)?;
+    let mut stack: Vec<Commit> = target_expr
+        .evaluate(workspace_command.repo().as_ref())?
+        .iter()
+        .commits(workspace_command.repo().store())
+        .try_collect()?;
+    stack.reverse();
+    if stack.is_empty() {
+        writeln!(ui.status(), "No revisions to reorder.")?;
+        return Ok(());
+    }
+    for (parent, child) in stack.iter().tuple_windows() {
+        if child.parent_ids() != [parent.id().clone()] {
+            return Err(user_error_with_hint(
+                "The revisions to reorder must form a linear stack",
+                format!(
+                    "Commit {} must have commit {} as its only parent",
+                    short_commit_hash(child.id()),
+                    short_commit_hash(parent.id()),
+                ),
+            ));
+        }
+    }
+
+    let todo = edit_reorder_todo(&workspace_command, &stack)?;
+    let base_parent_ids = stack[0].parent_ids().to_vec();
+    let old_top_id = stack.last().unwrap().id().clone();
+
+    let mut tx = workspace_command.start_transaction();
+    let mut new_commits: Vec<Commit> = vec![];
+    let mut groups: Vec<(Commit, Vec<Commit>)> = vec![];
+    for (action, commit) in todo {
+        match action {
+            ReorderAction::Pick => groups.push((commit, vec![])),
+            ReorderAction::Squash => groups.last_mut().unwrap().1.push(commit),
+            ReorderAction::Drop => tx.repo_mut().record_abandoned_commit(&commit),
+        }
+    }
+    for (commit, squashed) in groups {
+        let parent_ids = match new_commits.last() {
+            Some(previous) => vec![previous.id().clone()],
+            None => base_parent_ids.clone(),
+        };
+        let mut diffs = vec![];
+        for source in &squashed {
+            let source_parent_tree = source.parent_tree(tx.repo())?;
+            diffs.push(Diff::new(
+                (
+                    source_parent_tree,
+                    format!(
+                        "{} (parents of squashed revision)",
+                        source.parents_conflict_label()?
+                    ),
+                ),
+                (
+                    source.tree(),
+                    format!("{} (squashed revision)", source.conflict_label()),
+                ),
+            ));
+        }
+        let mut commit_builder = CommitRewriter::new(tx.repo_mut(), commit.clone(), parent_ids)
+            .rebase()
+            .block_on()?;
+        if !squashed.is_empty() {
+            let new_tree = MergedTree::merge(Merge::from_diffs(
+                (
+                    commit_builder.tree(),
+                    format!("{} (squash destination)", commit.conflict_label()),
+                ),
+                diffs,
+            ))
+            .block_on()?;
+            let description = try_combine_messages(&squashed, &commit).unwrap_or_else(|| {
+                iter::once(&commit)
+                    .chain(&squashed)
+                    .map(|commit| commit.description())
+                    .filter(|description| !description.is_empty())
+                    .join("\n")
+            });
+            let mut predecessors = vec![commit.id().clone()];
+            predecessors.extend(squashed.iter().map(|commit| commit.id().clone()));
+            commit_builder = commit_builder
+                .set_tree(new_tree)
+                .set_description(description)
+                .set_predecessors(predecessors);
+        }
+        let new_commit = commit_builder.write()?;
+        for source in &squashed {
+            tx.repo_mut()
+                .set_rewritten_commit(source.id().clone(), new_commit.id().clone());
+        }
+        new_commits.push(new_commit);
+    }
+
+    // Descendants of the old top of the stack are moved onto the new top.
+    let new_top_ids = match new_commits.last() {
+        Some(commit) => vec![commit.id().clone()],
+        None => base_parent_ids,
+    };
+    let mut num_rebased = 0;
+    tx.repo_mut()
+        .transform_descendants(vec![old_top_id.clone()], async |mut rewriter| {
+            let parent_ids = rewriter
+                .old_commit()
+                .parent_ids()
+                .iter()
+                .flat_map(|id| {
+                    if *id == old_top_id {
+                        new_top_ids.clone()
+                    } else {
+                        vec![id.clone()]
+                    }
+                })
+                .collect_vec();
+            rewriter.set_new_rewritten_parents(&parent_ids);
+            num_rebased += 1;
+            rewriter.rebase().await?.write()?;
+            Ok(())
+        })?;
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        if num_rebased > 0 {
+            writeln!(formatter, "Rebased {num_rebased} descendant commits")?;
+        }
+        writeln!(formatter, "Reordered {} commits into:", stack.len())?;
+        print_updated_commits(
+            formatter.as_mut(),
+            &tx.commit_summary_template(),
+            new_commits.iter().rev(),
+        )?;
+    }
+    tx.finish(ui, format!("reorder {} commits", stack.len()))?;
+    Ok(())
+}
+
+/// Opens the `--reorder` todo list in an editor and returns the commits in
+/// their new order, from the bottom of the stack to the top.
+fn edit_reorder_todo(
+    workspace_command: &WorkspaceCommandHelper,
+    stack: &[Commit],
+) -> Result<Vec<(ReorderAction, Commit)>, CommandError> {
+    let mut content = String::new();
+    for commit in stack {
+        let subject = commit.description().lines().next().unwrap_or_default();
+        writeln!(content, "pick {} {subject}", short_commit_hash(commit.id())).unwrap();
+    }
+    content.push_str(indoc! {"
+
+        JJ: Reorder the lines above to reorder the revisions. The first line is
+        JJ: the bottom of the stack.
+        JJ:
+        JJ: pick <commit> = keep the revision
+        JJ: squash <commit> = squash the revision into the one on the line above
+        JJ: drop <commit> = abandon the revision
+    "});
+    let editor = workspace_command.text_editor()?;
+    let content = editor
+        .edit_str(content, Some(".jjreorder"))
+        .map_err(|err| err.with_name("reorder todo list"))?;
+
+    let mut todo: Vec<(ReorderAction, Commit)> = vec![];
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("JJ:") {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let action = match words.next() {
+            Some("pick" | "p") => ReorderAction::Pick,
+            Some("squash" | "s") => ReorderAction::Squash,
+            Some("drop" | "d") => ReorderAction::Drop,
+            _ => {
+                return Err(user_error(format!(
+                    "Invalid action in reorder todo list: {line}"
+                )));
+            }
+        };
+        let prefix = words.next().unwrap_or_default();
+        let commit = stack
+            .iter()
+            .filter(|commit| !prefix.is_empty() && commit.id().hex().starts_with(prefix))
+            .exactly_one()
+            .map_err(|_| user_error(format!("Unknown revision in reorder todo list: {line}")))?;
+        if todo.iter().any(|(_, other)| other.id() == commit.id()) {
+            return Err(user_error(format!(
+                "Revision {} is listed more than once",
+                short_commit_hash(commit.id())
+            )));
+        }
+        if action == ReorderAction::Squash
+            && !todo
+                .iter()
+                .any(|(action, _)| *action == ReorderAction::Pick)
+        {
+            return Err(user_error(format!(
+                "Cannot squash revision {} without a picked revision on a line above it",
+                short_commit_hash(commit.id())
+            )));
+        }
+        todo.push((action, commit.clone()));
+    }
+    if let Some(commit) = stack
+        .iter()
+        .find(|commit| !todo.iter().any(|(_, other)| other.id() == commit.id()))
+    {
+        return Err(user_error_with_hint(
+            format!(
+                "Revision {} is missing from the reorder todo list",
+                short_commit_hash(commit.id())
+            ),
+            "Use `drop` to abandon a revision.",
+        ));
+    }
+    Ok(todo)
+}
+
 fn check_rebase_destinations(
     repo: &Arc<ReadonlyRepo>,
     new_parents: &[CommitId],
@@ -35,11 +35,18 @@ use crate::ui::Ui;
 /// restore` to explicitly restore an older operation by its id (available in
 /// the operation log).
 #[derive(clap::Args, Clone, Debug)]
-pub struct RedoArgs {}
+pub struct RedoArgs {
+    /// Show which operation would be restored, without redoing it
+    ///
+    /// Unlike `jj undo --what`, this doesn't take the portions of the state to
+    /// restore. A redo always restores everything that was undone.
+    #[arg(long)]
+    what: bool,
+}
 
 const REDO_OP_DESC_PREFIX: &str = "redo: restore to operation ";
 
-pub fn cmd_redo(ui: &mut Ui, command: &CommandHelper, _: &RedoArgs) -> Result<(), CommandError> {
+pub fn cmd_redo(ui: &mut Ui, command: &CommandHelper, args: &RedoArgs) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
 
     let mut op_to_redo = workspace_command.repo().operation().clone();
@@ -146,6 +153,15 @@ pub fn cmd_redo(ui: &mut Ui, command: &CommandHelper, _: &RedoArgs) -> Result<()
             .load_operation(&id_of_original_op)?;
     }
 
+    if args.what {
+        let mut formatter = ui.stdout_formatter();
+        write!(formatter, "Would restore to operation: ")?;
+        let template = workspace_command.operation_summary_template();
+        template.format(&op_to_restore, formatter.as_mut())?;
+        writeln!(formatter)?;
+        return Ok(());
+    }
+
     let mut tx = workspace_command.start_transaction();
     let new_view = view_with_desired_portions_restored(
         op_to_restore.view()?.store_view(),
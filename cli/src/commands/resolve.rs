@@ -12,14 +12,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io;
+use std::io::Write as _;
+use std::iter;
+
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::conflict_labels::ConflictLabels;
+use jj_lib::fileset::FilesetExpression;
+use jj_lib::matchers::Matcher;
 use jj_lib::object_id::ObjectId as _;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::print_conflicted_paths;
 use crate::cli_util::print_unmatched_explicit_paths;
 use crate::command_error::CommandError;
@@ -48,6 +57,10 @@ use crate::ui::Ui;
 //     simplify the present one.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct ResolveArgs {
+    /// The revision to resolve conflicts in
+    ///
+    /// With `--list`, this can be a revset, and the conflicts in all of its
+    /// revisions are listed.
     #[arg(long, short, default_value = "@", value_name = "REVSET")]
     #[arg(add = ArgValueCompleter::new(complete::revset_expression_mutable_conflicts))]
     revision: RevisionArg,
@@ -58,6 +71,15 @@ pub(crate) struct ResolveArgs {
     #[arg(long, short)]
     list: bool,
 
+    /// Output format of `--list`
+    ///
+    /// With `json`, one JSON object is printed per line for each conflicted
+    /// path, with the commit and change ids, the repo-relative path, the
+    /// number of sides, and the labels of the sides and bases (or `null` if
+    /// unlabeled). Unlike the default format, no conflicts is not an error.
+    #[arg(long, requires = "list", default_value = "text", value_name = "FORMAT")]
+    format: ListFormat,
+
     /// Specify 3-way merge tool to be used
     ///
     /// The built-in merge tools `:ours` and `:theirs` can be used to choose
@@ -82,6 +104,15 @@ pub(crate) fn cmd_resolve(
     let mut workspace_command = command.workspace_helper(ui)?;
     let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
     let matcher = fileset_expression.to_matcher();
+    if args.list {
+        return list_conflicts(
+            ui,
+            &workspace_command,
+            args,
+            &fileset_expression,
+            matcher.as_ref(),
+        );
+    }
     let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
     let tree = commit.tree();
     let conflicts = tree.conflicts_matching(&matcher).collect_vec();
@@ -95,14 +126,6 @@ pub(crate) fn cmd_resolve(
             "No conflicts found at the given path(s)"
         }));
     }
-    if args.list {
-        return print_conflicted_paths(
-            conflicts,
-            ui.stdout_formatter().as_mut(),
-            &workspace_command,
-        );
-    };
-
     let repo_paths = conflicts
         .iter()
         .map(|(path, _)| path.as_ref())
@@ -142,3 +165,90 @@ pub(crate) fn cmd_resolve(
     }
     Ok(())
 }
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, clap::ValueEnum)]
+enum ListFormat {
+    Text,
+    Json,
+}
+
+#[derive(serde::Serialize)]
+struct ConflictEntry<'a> {
+    commit_id: String,
+    change_id: String,
+    path: &'a str,
+    num_sides: usize,
+    sides: Vec<Option<&'a str>>,
+    bases: Vec<Option<&'a str>>,
+}
+
+fn list_conflicts(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    args: &ResolveArgs,
+    fileset_expression: &FilesetExpression,
+    matcher: &dyn Matcher,
+) -> Result<(), CommandError> {
+    let commits: Vec<Commit> = workspace_command
+        .parse_revset(ui, &args.revision)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    let trees = commits.iter().map(|commit| commit.tree()).collect_vec();
+    print_unmatched_explicit_paths(ui, workspace_command, fileset_expression, &trees)?;
+
+    let conflicted = iter::zip(&commits, &trees)
+        .map(|(commit, tree)| (commit, tree, tree.conflicts_matching(matcher).collect_vec()))
+        .filter(|(_, _, conflicts)| !conflicts.is_empty())
+        .collect_vec();
+    let mut formatter = ui.stdout_formatter();
+    match args.format {
+        ListFormat::Text => {
+            if conflicted.is_empty() {
+                return Err(cli_error(match (commits.len(), args.paths.is_empty()) {
+                    (1, true) => "No conflicts found at this revision",
+                    (_, true) => "No conflicts found at these revisions",
+                    (_, false) => "No conflicts found at the given path(s)",
+                }));
+            }
+            if commits.len() == 1 {
+                let (_, _, conflicts) = conflicted.into_iter().exactly_one().unwrap();
+                return print_conflicted_paths(conflicts, formatter.as_mut(), workspace_command);
+            }
+            for (commit, _, conflicts) in conflicted {
+                write!(formatter, "Conflicts in ")?;
+                workspace_command.write_commit_summary(formatter.as_mut(), commit)?;
+                writeln!(formatter, ":")?;
+                print_conflicted_paths(conflicts, formatter.as_mut(), workspace_command)?;
+            }
+        }
+        ListFormat::Json => {
+            for (commit, tree, conflicts) in conflicted {
+                for (path, conflict) in conflicts {
+                    let conflict = conflict?;
+                    // Labels are recorded for the terms of the whole tree
+                    let labels = if tree.labels().num_sides() == Some(conflict.num_sides()) {
+                        tree.labels().clone()
+                    } else {
+                        ConflictLabels::unlabeled()
+                    };
+                    let (labels, conflict) = labels.simplify_with(&conflict);
+                    let entry = ConflictEntry {
+                        commit_id: commit.id().hex(),
+                        change_id: commit.change_id().reverse_hex(),
+                        path: path.as_internal_file_string(),
+                        num_sides: conflict.num_sides(),
+                        sides: (0..conflict.num_sides())
+                            .map(|i| labels.get_add(i))
+                            .collect(),
+                        bases: (0..conflict.num_sides() - 1)
+                            .map(|i| labels.get_remove(i))
+                            .collect(),
+                    };
+                    serde_json::to_writer(formatter.as_mut(), &entry).map_err(io::Error::from)?;
+                    writeln!(formatter)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -13,18 +13,28 @@
 // limitations under the License.
 
 use std::io::Write as _;
+use std::slice;
 
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use indoc::formatdoc;
 use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::evolution::walk_predecessors;
 use jj_lib::merge::Diff;
+use jj_lib::merge::Merge;
+use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
+use pollster::FutureExt as _;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::print_unmatched_explicit_paths;
+use crate::cli_util::short_change_hash;
+use crate::cli_util::short_commit_hash;
 use crate::command_error::CommandError;
 use crate::command_error::user_error;
 use crate::complete;
@@ -44,6 +54,12 @@ use crate::ui::Ui;
 /// similar to `jj abandon`, except that it leaves an empty revision with its
 /// description and other metadata preserved.
 ///
+/// With `--from-evolog`, a change is restored to an earlier version of itself
+/// from its evolution log (see `jj evolog`). The changes made in that version
+/// are applied on top of the change's current parents, and its description is
+/// restored too unless `--keep-description` is given. Unlike `jj op restore`,
+/// this doesn't affect any other changes, except that descendants are rebased.
+///
 /// See `jj diffedit` if you'd like to restore portions of files rather than
 /// entire files.
 #[derive(clap::Args, Clone, Debug)]
@@ -75,6 +91,22 @@ pub(crate) struct RestoreArgs {
     #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
     changes_in: Option<RevisionArg>,
 
+    /// Restore a change to this earlier version of it from its evolution log
+    ///
+    /// The restored change defaults to the current version of the same change
+    /// instead of the working copy.
+    #[arg(
+        long,
+        value_name = "COMMIT",
+        conflicts_with_all = ["from", "changes_in"]
+    )]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
+    from_evolog: Option<RevisionArg>,
+
+    /// Don't restore the description with `--from-evolog`
+    #[arg(long, requires = "from_evolog")]
+    keep_description: bool,
+
     /// Prints an error. DO NOT USE.
     ///
     /// If we followed the pattern of `jj diff` and `jj diffedit`, we would use
@@ -116,7 +148,42 @@ pub(crate) fn cmd_restore(
                 ),
         );
     }
-    if args.from.is_some() || args.into.is_some() {
+    let mut restored_description = None;
+    if let Some(from_evolog) = &args.from_evolog {
+        let predecessor = workspace_command.resolve_single_rev(ui, from_evolog)?;
+        to_commit = match &args.into {
+            Some(into) => workspace_command.resolve_single_rev(ui, into)?,
+            None => current_version(&workspace_command, &predecessor)?,
+        };
+        check_in_evolog(&workspace_command, &predecessor, &to_commit)?;
+        // Apply the changes made in the earlier version on top of the current
+        // parents, so the change isn't moved back to its old parents' content.
+        let repo = workspace_command.repo().as_ref();
+        from_tree = MergedTree::merge(Merge::from_diffs(
+            (
+                to_commit.parent_tree(repo)?,
+                format!("{} (current parents)", to_commit.parents_conflict_label()?),
+            ),
+            [Diff::new(
+                (
+                    predecessor.parent_tree(repo)?,
+                    format!(
+                        "{} (parents of earlier version)",
+                        predecessor.parents_conflict_label()?
+                    ),
+                ),
+                (
+                    predecessor.tree(),
+                    format!("{} (earlier version)", predecessor.conflict_label()),
+                ),
+            )],
+        ))
+        .block_on()?;
+        if !args.keep_description {
+            restored_description = Some(predecessor.description().to_owned());
+        }
+        from_commits = vec![predecessor];
+    } else if args.from.is_some() || args.into.is_some() {
         to_commit = workspace_command
             .resolve_single_rev(ui, args.into.as_ref().unwrap_or(&RevisionArg::AT))?;
         let from_commit = workspace_command
@@ -165,14 +232,18 @@ pub(crate) fn cmd_restore(
         [&to_tree, &from_tree],
     )?;
 
-    if new_tree.tree_ids() == to_commit.tree_ids() {
+    let description_changed = restored_description
+        .as_ref()
+        .is_some_and(|description| description != to_commit.description());
+    if new_tree.tree_ids() == to_commit.tree_ids() && !description_changed {
         writeln!(ui.status(), "Nothing changed.")?;
     } else {
         let mut tx = workspace_command.start_transaction();
-        tx.repo_mut()
-            .rewrite_commit(&to_commit)
-            .set_tree(new_tree)
-            .write()?;
+        let mut commit_builder = tx.repo_mut().rewrite_commit(&to_commit).set_tree(new_tree);
+        if let Some(description) = restored_description {
+            commit_builder = commit_builder.set_description(description);
+        }
+        commit_builder.write()?;
         // rebase_descendants early; otherwise the new commit would always have
         // a conflicted change id at this point.
         let (num_rebased, extra_msg) = if args.restore_descendants {
@@ -181,7 +252,7 @@ pub(crate) fn cmd_restore(
                 " (while preserving their content)",
             )
         } else {
-            (tx.repo_mut().rebase_descendants()?, "")
+            (tx.rebase_descendants(ui)?, "")
         };
         if let Some(mut formatter) = ui.status_formatter()
             && num_rebased > 0
@@ -191,7 +262,68 @@ pub(crate) fn cmd_restore(
                 "Rebased {num_rebased} descendant commits{extra_msg}"
             )?;
         }
-        tx.finish(ui, format!("restore into commit {}", to_commit.id().hex()))?;
+        let tx_description = if args.from_evolog.is_some() {
+            format!(
+                "restore commit {} from evolog commit {}",
+                to_commit.id().hex(),
+                from_commits[0].id().hex()
+            )
+        } else {
+            format!("restore into commit {}", to_commit.id().hex())
+        };
+        tx.finish(ui, tx_description)?;
     }
     Ok(())
 }
+
+/// Returns the visible commit of the change that `commit` is a version of.
+fn current_version(
+    workspace_command: &WorkspaceCommandHelper,
+    commit: &Commit,
+) -> Result<Commit, CommandError> {
+    let repo = workspace_command.repo();
+    let targets = repo.resolve_change_id(commit.change_id())?;
+    let visible_ids = targets
+        .iter()
+        .flat_map(|targets| targets.visible_with_offsets())
+        .map(|(_, id)| id)
+        .collect_vec();
+    match visible_ids.as_slice() {
+        [id] => Ok(repo.store().get_commit(id)?),
+        [] => Err(user_error(format!(
+            "Change {} has no visible revision to restore into",
+            short_change_hash(commit.change_id())
+        ))
+        .hinted("Use `--into` to choose the revision to restore into.")),
+        _ => Err(user_error(format!(
+            "Change {} is divergent",
+            short_change_hash(commit.change_id())
+        ))
+        .hinted("Use `--into` to choose the revision to restore into.")),
+    }
+}
+
+/// Checks that `predecessor` is an earlier version of the same change as
+/// `commit`.
+fn check_in_evolog(
+    workspace_command: &WorkspaceCommandHelper,
+    predecessor: &Commit,
+    commit: &Commit,
+) -> Result<(), CommandError> {
+    if predecessor.change_id() == commit.change_id() {
+        for entry in walk_predecessors(workspace_command.repo(), slice::from_ref(commit.id())) {
+            if entry?.commit.id() == predecessor.id() {
+                return Ok(());
+            }
+        }
+    }
+    Err(user_error(format!(
+        "Commit {} is not in the evolution log of revision {}",
+        short_commit_hash(predecessor.id()),
+        short_commit_hash(commit.id())
+    ))
+    .hinted(format!(
+        "Use `jj evolog -r {}` to list the earlier versions of the change.",
+        short_change_hash(commit.change_id())
+    )))
+}
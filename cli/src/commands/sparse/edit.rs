@@ -16,9 +16,10 @@ use std::fmt::Write as _;
 use std::path::Path;
 
 use itertools::Itertools as _;
-use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::working_copy::SparsePatterns;
 use tracing::instrument;
 
+use super::SparsePattern;
 use super::update_sparse_patterns_with;
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
@@ -28,6 +29,9 @@ use crate::description_util::TextEditor;
 use crate::ui::Ui;
 
 /// Start an editor to update the patterns that are present in the working copy
+///
+/// Paths prefixed with `!` are excluded from the working copy even if they're
+/// under one of the other paths.
 #[derive(clap::Args, Clone, Debug)]
 pub struct SparseEditArgs {}
 
@@ -40,19 +44,35 @@ pub fn cmd_sparse_edit(
     let mut workspace_command = command.workspace_helper(ui)?;
     let editor = workspace_command.text_editor()?;
     update_sparse_patterns_with(ui, &mut workspace_command, |_ui, old_patterns| {
-        let mut new_patterns = edit_sparse(&editor, old_patterns)?;
-        new_patterns.sort_unstable();
-        new_patterns.dedup();
+        let mut new_patterns = SparsePatterns {
+            included: vec![],
+            excluded: vec![],
+            cone: old_patterns.cone,
+        };
+        for pattern in edit_sparse(&editor, old_patterns)? {
+            match pattern {
+                SparsePattern::Include(path) => new_patterns.included.push(path),
+                SparsePattern::Exclude(path) => new_patterns.excluded.push(path),
+            }
+        }
+        new_patterns.included.sort_unstable();
+        new_patterns.included.dedup();
+        new_patterns.excluded.sort_unstable();
+        new_patterns.excluded.dedup();
         Ok(new_patterns)
     })
 }
 
 fn edit_sparse(
     editor: &TextEditor,
-    sparse: &[RepoPathBuf],
-) -> Result<Vec<RepoPathBuf>, CommandError> {
+    sparse: &SparsePatterns,
+) -> Result<Vec<SparsePattern>, CommandError> {
     let mut content = String::new();
-    for sparse_path in sparse {
+    let paths = itertools::chain(
+        sparse.included.iter().map(|path| ("", path)),
+        sparse.excluded.iter().map(|path| ("!", path)),
+    );
+    for (prefix, sparse_path) in paths {
         // Invalid path shouldn't block editing. Edited paths will be validated.
         let workspace_relative_sparse_path = sparse_path.to_fs_path_unchecked(Path::new(""));
         let path_string = workspace_relative_sparse_path.to_str().ok_or_else(|| {
@@ -61,7 +81,7 @@ fn edit_sparse(
                 workspace_relative_sparse_path.display()
             ))
         })?;
-        writeln!(&mut content, "{path_string}").unwrap();
+        writeln!(&mut content, "{prefix}{path_string}").unwrap();
     }
 
     let content = editor
@@ -74,7 +94,7 @@ fn edit_sparse(
         .map(|line| line.trim())
         .filter(|line| !line.is_empty())
         .map(|line| {
-            RepoPathBuf::from_relative_path(line).map_err(|err| {
+            SparsePattern::parse(line).map_err(|err| {
                 user_error_with_message(format!("Failed to parse sparse pattern: {line}"), err)
             })
         })
@@ -13,10 +13,10 @@
 // limitations under the License.
 
 use std::io::Write as _;
-use std::path::Path;
 
 use tracing::instrument;
 
+use super::format_sparse_patterns;
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
@@ -25,7 +25,7 @@ use crate::ui::Ui;
 ///
 /// By default, a newly cloned or initialized repo will have have a pattern
 /// matching all files from the repo root. That pattern is rendered as `.` (a
-/// single period).
+/// single period). Excluded paths are prefixed with `!`.
 #[derive(clap::Args, Clone, Debug)]
 pub struct SparseListArgs {}
 
@@ -36,11 +36,15 @@ pub fn cmd_sparse_list(
     _args: &SparseListArgs,
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
-    for path in workspace_command.working_copy().sparse_patterns()? {
+    let patterns = workspace_command.working_copy().sparse_patterns()?;
+    for line in format_sparse_patterns(patterns) {
+        writeln!(ui.stdout(), "{line}")?;
+    }
+    if patterns.cone {
         writeln!(
-            ui.stdout(),
-            "{}",
-            path.to_fs_path_unchecked(Path::new("")).display()
+            ui.status(),
+            "Cone mode is enabled: files in the parent directories of the patterns are also \
+             present"
         )?;
     }
     Ok(())
@@ -17,8 +17,12 @@ mod list;
 mod reset;
 mod set;
 
+use std::path::Path;
+
 use clap::Subcommand;
+use jj_lib::repo_path::RelativePathParseError;
 use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::working_copy::SparsePatterns;
 use pollster::FutureExt as _;
 use tracing::instrument;
 
@@ -32,9 +36,9 @@ use self::set::SparseSetArgs;
 use self::set::cmd_sparse_set;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::WorkspaceCommandHelper;
+use crate::cli_util::checkout_error_with_message;
 use crate::cli_util::print_checkout_stats;
 use crate::command_error::CommandError;
-use crate::command_error::internal_error_with_message;
 use crate::ui::Ui;
 
 /// Manage which paths from the working-copy commit are present in the working
@@ -61,10 +65,43 @@ pub(crate) fn cmd_sparse(
     }
 }
 
+/// Sparse pattern parsed from command-line argument or edited text.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+enum SparsePattern {
+    Include(RepoPathBuf),
+    /// Pattern prefixed with `!`.
+    Exclude(RepoPathBuf),
+}
+
+impl SparsePattern {
+    fn parse(text: &str) -> Result<Self, RelativePathParseError> {
+        if let Some(path) = text.strip_prefix('!') {
+            Ok(Self::Exclude(RepoPathBuf::from_relative_path(path)?))
+        } else {
+            Ok(Self::Include(RepoPathBuf::from_relative_path(text)?))
+        }
+    }
+}
+
+/// Returns the patterns in the form they are displayed and edited, with the
+/// excluded paths prefixed with `!`.
+fn format_sparse_patterns(patterns: &SparsePatterns) -> impl Iterator<Item = String> {
+    let to_fs_path = |path: &RepoPathBuf| path.to_fs_path_unchecked(Path::new(""));
+    let included = patterns
+        .included
+        .iter()
+        .map(move |path| to_fs_path(path).display().to_string());
+    let excluded = patterns
+        .excluded
+        .iter()
+        .map(move |path| format!("!{}", to_fs_path(path).display()));
+    included.chain(excluded)
+}
+
 fn update_sparse_patterns_with(
     ui: &mut Ui,
     workspace_command: &mut WorkspaceCommandHelper,
-    f: impl FnOnce(&mut Ui, &[RepoPathBuf]) -> Result<Vec<RepoPathBuf>, CommandError>,
+    f: impl FnOnce(&mut Ui, &SparsePatterns) -> Result<SparsePatterns, CommandError>,
 ) -> Result<(), CommandError> {
     let (mut locked_ws, wc_commit) = workspace_command.start_working_copy_mutation()?;
     let new_patterns = f(ui, locked_ws.locked_wc().sparse_patterns()?)?;
@@ -72,7 +109,7 @@ fn update_sparse_patterns_with(
         .locked_wc()
         .set_sparse_patterns(new_patterns)
         .block_on()
-        .map_err(|err| internal_error_with_message("Failed to update working copy paths", err))?;
+        .map_err(|err| checkout_error_with_message("Failed to update working copy paths", err))?;
     let operation_id = locked_ws.locked_wc().old_operation_id().clone();
     locked_ws.finish(operation_id)?;
     print_checkout_stats(ui, &stats, &wc_commit)?;
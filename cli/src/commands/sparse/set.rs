@@ -15,9 +15,10 @@
 use std::collections::HashSet;
 
 use itertools::Itertools as _;
-use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::working_copy::SparsePatterns;
 use tracing::instrument;
 
+use super::SparsePattern;
 use super::update_sparse_patterns_with;
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
@@ -28,28 +29,45 @@ use crate::ui::Ui;
 /// For example, if all you need is the `README.md` and the `lib/`
 /// directory, use `jj sparse set --clear --add README.md --add lib`.
 /// If you no longer need the `lib` directory, use `jj sparse set --remove lib`.
+///
+/// Patterns prefixed with `!` exclude paths even if they're under one of the
+/// other patterns. For example, `jj sparse set --add src --add '!src/gen'`
+/// includes everything under `src` except `src/gen`.
 #[derive(clap::Args, Clone, Debug)]
 pub struct SparseSetArgs {
     /// Patterns to add to the working copy
     #[arg(
         long,
         value_hint = clap::ValueHint::AnyPath,
-        value_parser = |s: &str| RepoPathBuf::from_relative_path(s),
+        value_parser = SparsePattern::parse,
     )]
-    add: Vec<RepoPathBuf>,
+    add: Vec<SparsePattern>,
 
     /// Patterns to remove from the working copy
     #[arg(
         long,
         conflicts_with = "clear",
         value_hint = clap::ValueHint::AnyPath,
-        value_parser = |s: &str| RepoPathBuf::from_relative_path(s),
+        value_parser = SparsePattern::parse,
     )]
-    remove: Vec<RepoPathBuf>,
+    remove: Vec<SparsePattern>,
 
     /// Include no files in the working copy (combine with --add)
     #[arg(long)]
     clear: bool,
+
+    /// Also include the files directly in the parent directories of the
+    /// patterns, like Git's cone mode
+    ///
+    /// For example, with the pattern `src/lib`, the files in the root
+    /// directory and in `src` are also present in the working copy, but not
+    /// the other directories in `src`.
+    #[arg(long)]
+    cone: bool,
+
+    /// Disable cone mode
+    #[arg(long, conflicts_with = "cone")]
+    no_cone: bool,
 }
 
 #[instrument(skip_all)]
@@ -62,14 +80,44 @@ pub fn cmd_sparse_set(
     update_sparse_patterns_with(ui, &mut workspace_command, |_ui, old_patterns| {
         let mut new_patterns = HashSet::new();
         if !args.clear {
-            new_patterns.extend(old_patterns.iter().cloned());
-            for path in &args.remove {
-                new_patterns.remove(path);
+            new_patterns.extend(itertools::chain(
+                old_patterns
+                    .included
+                    .iter()
+                    .cloned()
+                    .map(SparsePattern::Include),
+                old_patterns
+                    .excluded
+                    .iter()
+                    .cloned()
+                    .map(SparsePattern::Exclude),
+            ));
+            for pattern in &args.remove {
+                new_patterns.remove(pattern);
             }
         }
-        for path in &args.add {
-            new_patterns.insert(path.to_owned());
+        for pattern in &args.add {
+            new_patterns.insert(pattern.clone());
         }
-        Ok(new_patterns.into_iter().sorted_unstable().collect())
+        let (included, excluded) =
+            new_patterns
+                .into_iter()
+                .sorted_unstable()
+                .partition_map(|pattern| match pattern {
+                    SparsePattern::Include(path) => itertools::Either::Left(path),
+                    SparsePattern::Exclude(path) => itertools::Either::Right(path),
+                });
+        let cone = if args.cone {
+            true
+        } else if args.no_cone {
+            false
+        } else {
+            old_patterns.cone
+        };
+        Ok(SparsePatterns {
+            included,
+            excluded,
+            cone,
+        })
     })
 }
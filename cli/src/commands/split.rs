@@ -13,16 +13,22 @@
 // limitations under the License.
 use std::collections::HashMap;
 use std::io::Write as _;
+use std::slice;
 
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
+use futures::StreamExt as _;
+use itertools::Itertools as _;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::matchers::FilesMatcher;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::Diff;
 use jj_lib::merge::Merge;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::rewrite::CommitWithSelection;
 use jj_lib::rewrite::EmptyBehavior;
 use jj_lib::rewrite::MoveCommitsLocation;
@@ -31,6 +37,7 @@ use jj_lib::rewrite::RebaseOptions;
 use jj_lib::rewrite::RebasedCommit;
 use jj_lib::rewrite::RewriteRefsOptions;
 use jj_lib::rewrite::move_commits;
+use jj_lib::rewrite::restore_tree;
 use pollster::FutureExt as _;
 use tracing::instrument;
 
@@ -42,6 +49,7 @@ use crate::cli_util::WorkspaceCommandTransaction;
 use crate::cli_util::compute_commit_location;
 use crate::cli_util::print_unmatched_explicit_paths;
 use crate::command_error::CommandError;
+use crate::command_error::user_error;
 use crate::command_error::user_error_with_hint;
 use crate::complete;
 use crate::description_util::add_trailers;
@@ -104,6 +112,25 @@ use crate::ui::Ui;
 /// description, the second commit will not get a description, and you will be
 /// asked for a description only for the first commit.
 ///
+/// With `--by` or `--by-file`, the revision is split into more than two
+/// commits without opening the diff editor. Each `--by` fileset gets its own
+/// commit, in the order given, and any changes not matched by a fileset go
+/// into a last commit. With `--by-file`, every changed file gets its own
+/// commit. All the new commits keep the original description, and the first
+/// one keeps the original change ID:
+///
+/// ```text
+///                   L'
+///                   |
+/// L                 K3 (remaining)
+/// |                 |
+/// K (split)   =>    K2 (--by 'docs')
+/// |                 |
+/// J                 K1 (--by 'src')
+///                   |
+///                   J
+/// ```
+///
 /// Splitting an empty commit is not supported because the same effect can be
 /// achieved with `jj new`.
 #[derive(clap::Args, Clone, Debug)]
@@ -193,6 +220,45 @@ pub(crate) struct SplitArgs {
     #[arg(long, short)]
     parallel: bool,
 
+    /// Split the changes matching this fileset into their own commit (can be
+    /// repeated)
+    ///
+    /// Changes are assigned to the first fileset that matches them. Changes
+    /// not matched by any fileset go into the last commit.
+    #[arg(
+        long,
+        value_name = "FILESET",
+        conflicts_with_all = [
+            "interactive",
+            "tool",
+            "onto",
+            "insert_after",
+            "insert_before",
+            "message_paragraphs",
+            "editor",
+            "paths",
+        ],
+    )]
+    #[arg(add = ArgValueCompleter::new(complete::modified_revision_files))]
+    by: Vec<String>,
+
+    /// Split each changed file into its own commit
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "by",
+            "interactive",
+            "tool",
+            "onto",
+            "insert_after",
+            "insert_before",
+            "message_paragraphs",
+            "editor",
+            "paths",
+        ],
+    )]
+    by_file: bool,
+
     /// Files matching any of these filesets are put in the selected changes
     #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
     #[arg(add = ArgValueCompleter::new(complete::modified_revision_files))]
@@ -219,7 +285,13 @@ impl SplitArgs {
         }
         workspace_command.check_rewritable([target_commit.id()])?;
         let repo = workspace_command.repo();
-        let fileset_expression = workspace_command.parse_file_patterns(ui, &self.paths)?;
+        // We check the parent commit to account for deleted files.
+        let parent_tree = target_commit.parent_tree(repo.as_ref())?;
+        let fileset_expression = workspace_command.parse_file_patterns_in_trees(
+            ui,
+            &self.paths,
+            &[&parent_tree, &target_commit.tree()],
+        )?;
         let matcher = fileset_expression.to_matcher();
         let diff_selector = workspace_command.diff_selector(
             ui,
@@ -245,11 +317,7 @@ impl SplitArgs {
             ui,
             workspace_command,
             &fileset_expression,
-            [
-                // We check the parent commit to account for deleted files.
-                &target_commit.parent_tree(repo.as_ref())?,
-                &target_commit.tree(),
-            ],
+            [&parent_tree, &target_commit.tree()],
         )?;
 
         Ok(ResolvedSplitArgs {
@@ -280,6 +348,9 @@ pub(crate) fn cmd_split(
     command: &CommandHelper,
     args: &SplitArgs,
 ) -> Result<(), CommandError> {
+    if !args.by.is_empty() || args.by_file {
+        return split_by_groups(ui, command, args);
+    }
     let mut workspace_command = command.workspace_helper(ui)?;
     let ResolvedSplitArgs {
         target_commit,
@@ -528,6 +599,130 @@ fn rewrite_descendants(
     Ok((first_commit, second_commit, num_rebased))
 }
 
+/// Splits the target commit into one commit per `--by` fileset or per changed
+/// file, plus one commit for the remaining changes.
+fn split_by_groups(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &SplitArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let target_commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    if target_commit.is_empty(workspace_command.repo().as_ref())? {
+        return Err(user_error_with_hint(
+            format!(
+                "Refusing to split empty commit {}.",
+                target_commit.id().hex()
+            ),
+            "Use `jj new` if you want to create another empty commit.",
+        ));
+    }
+    workspace_command.check_rewritable([target_commit.id()])?;
+    let parent_tree = target_commit.parent_tree(workspace_command.repo().as_ref())?;
+    let target_tree = target_commit.tree();
+    let changed_paths: Vec<RepoPathBuf> = parent_tree
+        .diff_stream(&target_tree, &EverythingMatcher)
+        .map(|entry| entry.path)
+        .collect()
+        .block_on();
+
+    // Assign each changed path to the first group that matches it.
+    let mut groups: Vec<Vec<RepoPathBuf>> = vec![];
+    let mut remaining_paths = changed_paths;
+    if args.by_file {
+        groups.extend(remaining_paths.drain(..).map(|path| vec![path]));
+    } else {
+        for fileset in &args.by {
+            let fileset_expression = workspace_command.parse_file_patterns_in_trees(
+                ui,
+                slice::from_ref(fileset),
+                &[&parent_tree, &target_tree],
+            )?;
+            let matcher = fileset_expression.to_matcher();
+            let (matched, unmatched) = remaining_paths
+                .into_iter()
+                .partition(|path| matcher.matches(path));
+            remaining_paths = unmatched;
+            if matched.is_empty() {
+                writeln!(
+                    ui.warning_default(),
+                    "No remaining changes in the revision match {fileset}"
+                )?;
+            } else {
+                groups.push(matched);
+            }
+        }
+    }
+    if !remaining_paths.is_empty() {
+        groups.push(remaining_paths);
+    }
+    if groups.len() < 2 {
+        return Err(user_error(
+            "Refusing to split the revision into a single commit",
+        ));
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let mut new_commits: Vec<Commit> = vec![];
+    for paths in groups {
+        let (base_tree, parents) = match new_commits.last() {
+            Some(previous) if !args.parallel => (previous.tree(), vec![previous.id().clone()]),
+            _ => (parent_tree.clone(), target_commit.parent_ids().to_vec()),
+        };
+        let new_tree =
+            restore_tree(&target_tree, &base_tree, &FilesMatcher::new(paths)).block_on()?;
+        let mut commit_builder = tx.repo_mut().rewrite_commit(&target_commit).detach();
+        commit_builder.set_parents(parents).set_tree(new_tree);
+        if !new_commits.is_empty() {
+            commit_builder.clear_rewrite_source();
+            // Generate a new change id so that the commit being split doesn't
+            // become divergent.
+            commit_builder.generate_new_change_id();
+        }
+        new_commits.push(commit_builder.write(tx.repo_mut())?);
+    }
+
+    // The target commit has been rewritten into the first commit, so the
+    // descendants are rebased onto the last commit (or onto all the commits if
+    // they're parallel).
+    let first_commit = &new_commits[0];
+    let last_commit = new_commits.last().unwrap();
+    let new_parent_ids = if args.parallel {
+        new_commits.iter().map(|commit| commit.id()).collect_vec()
+    } else {
+        vec![last_commit.id()]
+    };
+    let mut num_rebased = 0;
+    tx.repo_mut().transform_descendants(
+        vec![target_commit.id().clone()],
+        async |mut rewriter| {
+            num_rebased += 1;
+            rewriter.replace_parent(first_commit.id(), new_parent_ids.iter().copied());
+            rewriter.rebase().await?.write()?;
+            Ok(())
+        },
+    )?;
+    for (name, working_copy_commit) in tx.base_repo().clone().view().wc_commit_ids() {
+        if working_copy_commit == target_commit.id() {
+            tx.repo_mut().edit(name.clone(), last_commit)?;
+        }
+    }
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        if num_rebased > 0 {
+            writeln!(formatter, "Rebased {num_rebased} descendant commits")?;
+        }
+        writeln!(formatter, "Split into {} commits:", new_commits.len())?;
+        for commit in &new_commits {
+            write!(formatter, "  ")?;
+            tx.write_commit_summary(formatter.as_mut(), commit)?;
+            writeln!(formatter)?;
+        }
+    }
+    tx.finish(ui, format!("split commit {}", target_commit.id().hex()))?;
+    Ok(())
+}
+
 /// Prompts the user to select the content they want in the first commit and
 /// returns the target commit and the tree corresponding to the selection.
 fn select_diff(
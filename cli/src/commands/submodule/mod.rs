@@ -0,0 +1,73 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod status;
+mod update;
+
+use clap::Subcommand;
+use jj_lib::git_submodule;
+use jj_lib::git_submodule::Submodule;
+use jj_lib::matchers::Matcher;
+use jj_lib::repo::Repo as _;
+use pollster::FutureExt as _;
+use tracing::instrument;
+
+use self::status::SubmoduleStatusArgs;
+use self::status::cmd_submodule_status;
+use self::update::SubmoduleUpdateArgs;
+use self::update::cmd_submodule_update;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::CommandError;
+use crate::command_error::user_error;
+use crate::ui::Ui;
+
+/// Manage the Git submodules of the working-copy commit
+///
+/// Submodules are listed in the `.gitmodules` file, and their commits are
+/// recorded in the tree. jj doesn't track the content of submodules, which are
+/// checked out as nested Git repositories.
+#[derive(Subcommand, Clone, Debug)]
+pub(crate) enum SubmoduleCommand {
+    Status(SubmoduleStatusArgs),
+    Update(SubmoduleUpdateArgs),
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_submodule(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &SubmoduleCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        SubmoduleCommand::Status(args) => cmd_submodule_status(ui, command, args),
+        SubmoduleCommand::Update(args) => cmd_submodule_update(ui, command, args),
+    }
+}
+
+/// Returns the submodules of the working-copy commit matching the `matcher`.
+fn get_wc_submodules(
+    workspace_command: &WorkspaceCommandHelper,
+    matcher: &dyn Matcher,
+) -> Result<Vec<Submodule>, CommandError> {
+    let commit_id = workspace_command
+        .get_wc_commit_id()
+        .ok_or_else(|| user_error("This command requires a working copy"))?;
+    let commit = workspace_command.repo().store().get_commit(commit_id)?;
+    let submodules = git_submodule::get_submodules(&commit.tree()).block_on()?;
+    Ok(submodules
+        .into_iter()
+        .filter(|submodule| matcher.matches(&submodule.path))
+        .collect())
+}
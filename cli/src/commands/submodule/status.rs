@@ -0,0 +1,75 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::git_submodule;
+use jj_lib::git_submodule::SubmoduleState;
+use tracing::instrument;
+
+use super::get_wc_submodules;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::short_commit_hash;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Show the submodules of the working-copy commit and their state
+///
+/// Each submodule is listed with the commit recorded in the working-copy
+/// commit. Submodules which haven't been cloned, or which are checked out at a
+/// different commit, are marked as such.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct SubmoduleStatusArgs {
+    /// Only show submodules at these paths
+    #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_submodule_status(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &SubmoduleStatusArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let matcher = workspace_command
+        .parse_file_patterns(ui, &args.paths)?
+        .to_matcher();
+    let submodules = get_wc_submodules(&workspace_command, matcher.as_ref())?;
+    let mut formatter = ui.stdout_formatter();
+    for submodule in &submodules {
+        let state = git_submodule::submodule_state(workspace_command.workspace_root(), submodule)?;
+        write!(
+            formatter,
+            "{} {}",
+            workspace_command.format_file_path(&submodule.path),
+            short_commit_hash(&submodule.commit_id)
+        )?;
+        match state {
+            SubmoduleState::Uninitialized => {
+                write!(formatter, " (not initialized)")?;
+            }
+            SubmoduleState::CheckedOut(commit_id) if commit_id == submodule.commit_id => {}
+            SubmoduleState::CheckedOut(commit_id) => {
+                write!(
+                    formatter,
+                    " (checked out at {})",
+                    short_commit_hash(&commit_id)
+                )?;
+            }
+        }
+        writeln!(formatter)?;
+    }
+    Ok(())
+}
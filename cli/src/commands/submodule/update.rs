@@ -0,0 +1,75 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::git::GitSubprocessOptions;
+use jj_lib::git_submodule;
+use jj_lib::repo::Repo as _;
+use tracing::instrument;
+
+use super::get_wc_submodules;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::short_commit_hash;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Check out submodules at the commits recorded in the working-copy commit
+///
+/// Submodules which haven't been cloned yet are cloned from the URL in
+/// `.gitmodules`. Relative URLs are resolved against the URL of the `origin`
+/// remote. Set `git.update-submodules = true` to also update the submodules
+/// which have been cloned whenever the working copy is updated.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct SubmoduleUpdateArgs {
+    /// Only update submodules at these paths
+    #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_submodule_update(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &SubmoduleUpdateArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let matcher = workspace_command
+        .parse_file_patterns(ui, &args.paths)?
+        .to_matcher();
+    let submodules = get_wc_submodules(&workspace_command, matcher.as_ref())?;
+    let base_url = git_submodule::superproject_url(workspace_command.repo().store());
+    let mut num_updated = 0;
+    for submodule in &submodules {
+        let updated = git_submodule::update_submodule(
+            workspace_command.workspace_root(),
+            submodule,
+            base_url.as_deref(),
+            GitSubprocessOptions::from_settings(command.settings())?,
+        )?;
+        if updated {
+            num_updated += 1;
+            writeln!(
+                ui.status(),
+                "Checked out submodule {} at {}",
+                workspace_command.format_file_path(&submodule.path),
+                short_commit_hash(&submodule.commit_id)
+            )?;
+        }
+    }
+    if num_updated == 0 {
+        writeln!(ui.status(), "Nothing changed.")?;
+    }
+    Ok(())
+}
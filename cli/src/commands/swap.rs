@@ -0,0 +1,182 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCompleter;
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
+use jj_lib::rewrite::MoveCommitsLocation;
+use jj_lib::rewrite::MoveCommitsTarget;
+use jj_lib::rewrite::RebaseOptions;
+use jj_lib::rewrite::RebasedCommit;
+use jj_lib::rewrite::move_commits;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::print_updated_commits;
+use crate::cli_util::short_commit_hash;
+use crate::command_error::CommandError;
+use crate::command_error::user_error;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Swap a revision with its parent
+///
+/// The revision is moved before its parent, and its descendants are rebased
+/// onto the parent. This is equivalent to `jj rebase -r REV -B REV-`.
+///
+/// ```text
+/// L           L'
+/// |           |
+/// K     =>    J'
+/// |           |
+/// J           K'
+/// |           |
+/// I           I
+/// ```
+///
+/// Two revisions can be given instead, in which case one of them must be the
+/// only parent of the other.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct SwapArgs {
+    /// The revision to swap with its parent, or the two adjacent revisions to
+    /// swap
+    #[arg(
+        default_value = "@",
+        num_args = 1..=2,
+        value_name = "REVSET"
+    )]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_mutable))]
+    revisions: Vec<RevisionArg>,
+
+    /// Only report what the swap would do, including any new conflicts,
+    /// without changing the repo
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_swap(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &SwapArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let commits: Vec<Commit> = args
+        .revisions
+        .iter()
+        .map(|revision| workspace_command.resolve_single_rev(ui, revision))
+        .try_collect()?;
+    let (parent, child) = match commits.as_slice() {
+        [child] => {
+            let parent = child.parents().exactly_one().map_err(|_| {
+                user_error(format!(
+                    "Cannot swap commit {} because it doesn't have exactly one parent",
+                    short_commit_hash(child.id())
+                ))
+            })??;
+            (parent, child.clone())
+        }
+        [first, second] if second.parent_ids() == [first.id().clone()] => {
+            (first.clone(), second.clone())
+        }
+        [first, second] if first.parent_ids() == [second.id().clone()] => {
+            (second.clone(), first.clone())
+        }
+        [first, second] => {
+            return Err(user_error(format!(
+                "Cannot swap commits {} and {} because neither is the only parent of the other",
+                short_commit_hash(first.id()),
+                short_commit_hash(second.id())
+            )));
+        }
+        _ => unreachable!(),
+    };
+    workspace_command.check_rewritable([parent.id(), child.id()])?;
+
+    let mut tx = workspace_command.start_transaction();
+    let loc = MoveCommitsLocation {
+        new_parent_ids: parent.parent_ids().to_vec(),
+        new_child_ids: vec![parent.id().clone()],
+        target: MoveCommitsTarget::Commits(vec![child.id().clone()]),
+    };
+    let stats = move_commits(tx.repo_mut(), &loc, &RebaseOptions::default())?;
+
+    let mut new_conflicts = vec![];
+    for (old_id, rebased) in &stats.rebased_commits {
+        if let RebasedCommit::Rewritten(new_commit) = rebased
+            && new_commit.has_conflict()
+            && !tx.base_repo().store().get_commit(old_id)?.has_conflict()
+        {
+            new_conflicts.push(new_commit.clone());
+        }
+    }
+    new_conflicts.sort_by_key(|commit| commit.committer().timestamp);
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        let verb = if args.dry_run {
+            "Would swap"
+        } else {
+            "Swapped"
+        };
+        let swapped_commits = [parent.id(), child.id()].into_iter().filter_map(|id| {
+            match stats.rebased_commits.get(id) {
+                Some(RebasedCommit::Rewritten(commit)) => Some(commit),
+                _ => None,
+            }
+        });
+        writeln!(formatter, "{verb} commits:")?;
+        print_updated_commits(
+            formatter.as_mut(),
+            &tx.commit_summary_template(),
+            swapped_commits,
+        )?;
+        if stats.num_rebased_descendants > 1 {
+            let num_rebased = stats.num_rebased_descendants - 1;
+            let verb = if args.dry_run {
+                "Would rebase"
+            } else {
+                "Rebased"
+            };
+            writeln!(formatter, "{verb} {num_rebased} descendant commits")?;
+        }
+        if args.dry_run && !new_conflicts.is_empty() {
+            writeln!(
+                formatter,
+                "The swap would introduce conflicts in {} commits:",
+                new_conflicts.len()
+            )?;
+            print_updated_commits(
+                formatter.as_mut(),
+                &tx.commit_summary_template(),
+                &new_conflicts,
+            )?;
+        }
+    }
+    if args.dry_run {
+        writeln!(ui.status(), "Dry-run requested, not swapping.")?;
+        return Ok(());
+    }
+    tx.finish(
+        ui,
+        format!(
+            "swap commit {} with commit {}",
+            child.id().hex(),
+            parent.id().hex()
+        ),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,148 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCompleter;
+use itertools::Itertools as _;
+use jj_lib::backend::Signature;
+use jj_lib::commit::Commit;
+#[cfg(feature = "git")]
+use jj_lib::git;
+use jj_lib::op_store::RefTarget;
+use jj_lib::ref_name::RefNameBuf;
+use jj_lib::repo::MutableRepo;
+#[cfg(feature = "git")]
+use jj_lib::repo::Repo as _;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::complete;
+use crate::description_util::join_message_paragraphs;
+use crate::revset_util;
+use crate::ui::Ui;
+
+/// Create a new tag
+///
+/// By default, a lightweight tag is created. If a message is given with `-m`,
+/// an annotated tag is created in the underlying Git repo instead. Annotated
+/// tags record the tagger and the date in addition to the message.
+#[derive(clap::Args, Clone, Debug)]
+pub struct TagCreateArgs {
+    /// The tag's target revision
+    #[arg(long, short, default_value = "@", value_name = "REVSET")]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
+    revision: RevisionArg,
+
+    /// Create annotated tags with the given message
+    ///
+    /// If multiple `-m` are given, they are joined as separate paragraphs.
+    #[arg(long = "message", short, value_name = "MESSAGE")]
+    message_paragraphs: Vec<String>,
+
+    /// The tags to create
+    #[arg(required = true, value_parser = revset_util::parse_tag_name)]
+    names: Vec<RefNameBuf>,
+}
+
+pub fn cmd_tag_create(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TagCreateArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let target_commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let view = workspace_command.repo().view();
+    for name in &args.names {
+        if view.get_local_tag(name).is_present() {
+            return Err(user_error_with_hint(
+                format!("Tag already exists: {name}", name = name.as_symbol()),
+                "Use `jj tag set --allow-move` to update existing tags.",
+            ));
+        }
+    }
+    if target_commit.is_discardable(workspace_command.repo().as_ref())? {
+        writeln!(ui.warning_default(), "Target revision is empty.")?;
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    if args.message_paragraphs.is_empty() {
+        for name in &args.names {
+            tx.repo_mut()
+                .set_local_tag_target(name, RefTarget::normal(target_commit.id().clone()));
+        }
+    } else {
+        let message = join_message_paragraphs(&args.message_paragraphs);
+        let tagger = command.settings().signature();
+        create_annotated_tags(
+            tx.repo_mut(),
+            &args.names,
+            &target_commit,
+            &tagger,
+            &message,
+        )?;
+    }
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        write!(
+            formatter,
+            "Created {count} tags pointing to ",
+            count = args.names.len()
+        )?;
+        tx.write_commit_summary(formatter.as_mut(), &target_commit)?;
+        writeln!(formatter)?;
+    }
+    tx.finish(
+        ui,
+        format!(
+            "create tag {names} pointing to commit {id}",
+            names = args.names.iter().map(|n| n.as_symbol()).join(", "),
+            id = target_commit.id()
+        ),
+    )?;
+    Ok(())
+}
+
+#[cfg(feature = "git")]
+fn create_annotated_tags(
+    mut_repo: &mut MutableRepo,
+    names: &[RefNameBuf],
+    target_commit: &Commit,
+    tagger: &Signature,
+    message: &str,
+) -> Result<(), CommandError> {
+    if git::get_git_backend(mut_repo.store()).is_err() {
+        return Err(user_error(
+            "Annotated tags are only supported with the Git backend",
+        ));
+    }
+    for name in names {
+        git::create_annotated_tag(mut_repo, name, target_commit.id(), tagger, message)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "git"))]
+fn create_annotated_tags(
+    _mut_repo: &mut MutableRepo,
+    _names: &[RefNameBuf],
+    _target_commit: &Commit,
+    _tagger: &Signature,
+    _message: &str,
+) -> Result<(), CommandError> {
+    Err(user_error(
+        "Annotated tags are only supported with the Git backend",
+    ))
+}
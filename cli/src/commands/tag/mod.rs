@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod create;
 mod delete;
 mod list;
 mod set;
@@ -23,6 +24,8 @@ use jj_lib::ref_name::RefName;
 use jj_lib::str_util::StringExpression;
 use jj_lib::view::View;
 
+use self::create::TagCreateArgs;
+use self::create::cmd_tag_create;
 use self::delete::TagDeleteArgs;
 use self::delete::cmd_tag_delete;
 use self::list::TagListArgs;
@@ -36,6 +39,8 @@ use crate::ui::Ui;
 /// Manage tags.
 #[derive(clap::Subcommand, Clone, Debug)]
 pub enum TagCommand {
+    #[command(visible_alias("c"))]
+    Create(TagCreateArgs),
     #[command(visible_alias("d"))]
     Delete(TagDeleteArgs),
     #[command(visible_alias("l"))]
@@ -50,6 +55,7 @@ pub fn cmd_tag(
     subcommand: &TagCommand,
 ) -> Result<(), CommandError> {
     match subcommand {
+        TagCommand::Create(args) => cmd_tag_create(ui, command, args),
         TagCommand::Delete(args) => cmd_tag_delete(ui, command, args),
         TagCommand::List(args) => cmd_tag_list(ui, command, args),
         TagCommand::Set(args) => cmd_tag_set(ui, command, args),
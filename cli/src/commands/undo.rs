@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use clap::ValueEnum as _;
 use clap_complete::ArgValueCandidates;
 use itertools::Itertools as _;
 use jj_lib::object_id::ObjectId as _;
@@ -57,12 +58,17 @@ pub struct UndoArgs {
     #[arg(add = ArgValueCandidates::new(complete::operations))]
     operation: String,
 
-    /// (deprecated, use `jj op revert --what`)
-    ///
     /// What portions of the local state to restore (can be repeated)
     ///
+    /// An undo which doesn't restore all of the repo state and the
+    /// remote-tracking bookmarks isn't part of the undo-stack. Running `jj
+    /// undo` again reverts it, and `jj redo` can't redo it.
+    ///
+    /// Note that `jj redo --what` doesn't select portions. It shows which
+    /// operation `jj redo` would restore.
+    ///
     /// This option is EXPERIMENTAL.
-    #[arg(long, value_enum, hide = true, default_values_t = DEFAULT_REVERT_WHAT)]
+    #[arg(long, value_enum, default_values_t = DEFAULT_REVERT_WHAT)]
     what: Vec<RevertWhatToRestore>,
 }
 
@@ -80,18 +86,6 @@ pub fn cmd_undo(ui: &mut Ui, command: &CommandHelper, args: &UndoArgs) -> Result
         };
         return cmd_op_revert(ui, command, &args);
     }
-    if args.what != DEFAULT_REVERT_WHAT {
-        writeln!(
-            ui.warning_default(),
-            "`jj undo --what` is deprecated; use `jj op revert --what` instead"
-        )?;
-        let args = OperationRevertArgs {
-            operation: args.operation.clone(),
-            what: args.what.clone(),
-        };
-        return cmd_op_revert(ui, command, &args);
-    }
-
     let mut workspace_command = command.workspace_helper(ui)?;
 
     let mut op_to_undo = workspace_command.resolve_single_op(&args.operation)?;
@@ -136,6 +130,11 @@ pub fn cmd_undo(ui: &mut Ui, command: &CommandHelper, args: &UndoArgs) -> Result
     // restored (as opposed to C). The undo-stack spanning from F to B was
     // "jumped over".
     //
+    // Partial undo-operations (restoring only some portions with `--what`) are
+    // described differently, so they're treated as regular operations. Their
+    // view doesn't match the operation they restored to, so jumping over them
+    // would restore the wrong state.
+    //
     if let Some(id_of_restored_op) = op_to_undo
         .metadata()
         .description
@@ -198,7 +197,7 @@ pub fn cmd_undo(ui: &mut Ui, command: &CommandHelper, args: &UndoArgs) -> Result
     let new_view = view_with_desired_portions_restored(
         op_to_restore.view()?.store_view(),
         tx.base_repo().view().store_view(),
-        &DEFAULT_REVERT_WHAT,
+        &args.what,
     );
     tx.repo_mut().set_view(new_view);
     if let Some(mut formatter) = ui.status_formatter() {
@@ -207,10 +206,25 @@ pub fn cmd_undo(ui: &mut Ui, command: &CommandHelper, args: &UndoArgs) -> Result
         template.format(&op_to_restore, formatter.as_mut())?;
         writeln!(formatter)?;
     }
-    tx.finish(
-        ui,
-        format!("{UNDO_OP_DESC_PREFIX}{}", op_to_restore.id().hex()),
-    )?;
+    let description = if DEFAULT_REVERT_WHAT
+        .iter()
+        .all(|what| args.what.contains(what))
+    {
+        format!("{UNDO_OP_DESC_PREFIX}{}", op_to_restore.id().hex())
+    } else {
+        let portions = args
+            .what
+            .iter()
+            .sorted()
+            .dedup()
+            .map(|what| what.to_possible_value().unwrap().get_name().to_owned())
+            .join(", ");
+        format!(
+            "undo: restore {portions} to operation {}",
+            op_to_restore.id().hex()
+        )
+    };
+    tx.finish(ui, description)?;
 
     Ok(())
 }
@@ -0,0 +1,116 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error as _;
+use std::io::Write as _;
+
+use jj_lib::default_index::DefaultIndexStore;
+use jj_lib::fsck::FsckReport;
+use jj_lib::fsck::check_repo;
+use jj_lib::operation::Operation;
+use jj_lib::repo::RepoLoader;
+use pollster::FutureExt as _;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::command_error::internal_error;
+use crate::command_error::print_error_sources;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::progress::progress_reporter;
+use crate::ui::Ui;
+
+/// Verify the integrity of the repository
+///
+/// Reads all commits reachable from the operation, along with their trees and
+/// file contents, and checks that the objects hash to their IDs (if the
+/// backend supports that). Also checks that the commits referenced by heads,
+/// working copies, bookmarks, and tags are present in the commit index.
+///
+/// Exits with an error if any problem is found.
+#[derive(clap::Args, Clone, Debug)]
+pub struct UtilFsckArgs {
+    /// Rebuild the commit index if it's inconsistent with the backend
+    #[arg(long)]
+    repair: bool,
+}
+
+pub fn cmd_util_fsck(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &UtilFsckArgs,
+) -> Result<(), CommandError> {
+    // Resolve the operation without loading the repo, which would fail if the
+    // index is damaged beyond repair.
+    let workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let op = command.resolve_operation(ui, repo_loader)?;
+    let mut report = check_repo(&*repo_loader.load_at(&op)?)?;
+    print_report(ui, &report)?;
+    if args.repair && report.has_index_issues() {
+        reindex(ui, repo_loader, &op)?;
+        report = check_repo(&*repo_loader.load_at(&op)?)?;
+        print_report(ui, &report)?;
+    }
+
+    let num_issues = report.issues.len();
+    if num_issues == 0 {
+        writeln!(ui.status(), "No problems found.")?;
+        Ok(())
+    } else if report.has_index_issues() {
+        Err(user_error_with_hint(
+            format!("Found {num_issues} problems"),
+            "Run `jj util fsck --repair` to rebuild the commit index.",
+        ))
+    } else {
+        Err(user_error(format!("Found {num_issues} problems")))
+    }
+}
+
+fn print_report(ui: &Ui, report: &FsckReport) -> Result<(), CommandError> {
+    writeln!(
+        ui.status(),
+        "Checked {} commits, {} trees, and {} files.",
+        report.num_commits,
+        report.num_trees,
+        report.num_files
+    )?;
+    for issue in &report.issues {
+        writeln!(ui.warning_default(), "{issue}")?;
+        print_error_sources(ui, issue.source())?;
+    }
+    Ok(())
+}
+
+fn reindex(ui: &Ui, repo_loader: &RepoLoader, op: &Operation) -> Result<(), CommandError> {
+    let index_store = repo_loader.index_store();
+    let Some(default_index_store) = index_store.downcast_ref::<DefaultIndexStore>() else {
+        return Err(user_error(format!(
+            "Cannot reindex indexes of type '{}'",
+            index_store.name()
+        )));
+    };
+    default_index_store.reinit().map_err(internal_error)?;
+    let progress = progress_reporter(ui);
+    let default_index = default_index_store
+        .build_index_at_operation(op, repo_loader.store(), progress.as_ref().map(|x| x as _))
+        .block_on()
+        .map_err(internal_error)?;
+    writeln!(
+        ui.status(),
+        "Rebuilt the commit index with {} commits.",
+        default_index.num_commits()
+    )?;
+    Ok(())
+}
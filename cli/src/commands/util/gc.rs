@@ -12,21 +12,41 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
 use std::slice;
 use std::time::Duration;
 use std::time::SystemTime;
 
+use jj_lib::backend::MillisSinceEpoch;
+use jj_lib::config::ConfigGetResultExt as _;
+use jj_lib::default_index::DefaultIndexStore;
+use jj_lib::op_walk;
+use jj_lib::progress::ProgressScope;
+use jj_lib::progress::ProgressTask;
 use jj_lib::repo::Repo as _;
+use jj_lib::simple_backend::SimpleBackend;
 
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
+use crate::command_error::internal_error;
 use crate::command_error::user_error;
+use crate::commands::bookmark::bookmark_change_index;
+use crate::commands::fix::fix_cache;
+use crate::commands::operation::abandon::abandon_operation_range;
+use crate::progress::progress_reporter;
 use crate::ui::Ui;
 
 /// Run backend-dependent garbage collection.
 ///
 /// To garbage-collect old operations and the commits/objects referenced by
-/// them, run `jj op abandon ..<some old operation>` before `jj util gc`.
+/// them, run `jj op abandon ..<some old operation>` before `jj util gc`, or
+/// set `gc.operation-retention-days` to abandon the operations older than the
+/// given number of days.
+///
+/// This also compacts the commit index. In large repositories, index updates
+/// leave big index files as is, so running `jj util gc` from time to time keeps
+/// commit lookups fast. Cached `jj fix` tool outputs that haven't been used
+/// since the time threshold are removed too.
 #[derive(clap::Args, Clone, Debug)]
 pub struct UtilGcArgs {
     /// Time threshold
@@ -39,6 +59,19 @@ pub struct UtilGcArgs {
     /// release.
     #[arg(long)]
     expire: Option<String>,
+
+    /// Repack the reachable objects into a single pack file
+    ///
+    /// Versions of the same file are stored as deltas against each other.
+    /// Unreachable objects are kept until they expire, as without this flag.
+    /// This only has an effect in repos using the native backend. The objects
+    /// of Git-backed repos are already packed by Git's garbage collection.
+    ///
+    /// Objects are never repacked automatically, so run this from time to
+    /// time, e.g. from a scheduled job, to keep the store small. Packed objects
+    /// are checked against their IDs when they're read.
+    #[arg(long)]
+    compact: bool,
 }
 
 pub fn cmd_util_gc(
@@ -56,11 +89,89 @@ pub fn cmd_util_gc(
         Some("now") => SystemTime::now() - Duration::ZERO,
         _ => return Err(user_error("--expire only accepts 'now'")),
     };
+    if let Some(days) = command
+        .settings()
+        .get::<u64>("gc.operation-retention-days")
+        .optional()?
+    {
+        let keep_ops_newer = SystemTime::now()
+            .checked_sub(Duration::from_secs(days.saturating_mul(86400)))
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        expire_operations(ui, command, keep_ops_newer)?;
+    }
     let workspace_command = command.workspace_helper(ui)?;
 
     let repo = workspace_command.repo();
+    let progress = progress_reporter(ui);
+    let progress = ProgressScope::start(progress.as_ref().map(|x| x as _), ProgressTask::Gc);
     repo.op_store()
         .gc(slice::from_ref(repo.op_id()), keep_newer)?;
+    bookmark_change_index(&workspace_command).gc(slice::from_ref(repo.operation()))?;
+    progress.advance(1, Some(3), Some("operations"));
     repo.store().gc(repo.index(), keep_newer)?;
+    fix_cache(&workspace_command).gc(keep_newer)?;
+    progress.advance(2, Some(3), Some("objects"));
+    if args.compact
+        && let Some(backend) = repo.store().backend_impl::<SimpleBackend>()
+    {
+        let stats = backend.compact(repo.index(), keep_newer)?;
+        writeln!(
+            ui.status(),
+            "Packed {} objects, {} of them as deltas.",
+            stats.num_objects,
+            stats.num_deltas
+        )?;
+    }
+    // Merge index segments that were left unsquashed by incremental updates.
+    if let Some(default_index_store) = repo.index_store().downcast_ref::<DefaultIndexStore>() {
+        default_index_store
+            .compact_index_at_operation(repo.op_id(), repo.store())
+            .map_err(internal_error)?;
+    }
+    progress.advance(3, Some(3), Some("index"));
     Ok(())
 }
+
+/// Abandons the newest operation which ended before `keep_newer`, and all its
+/// ancestors.
+fn expire_operations(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    keep_newer: SystemTime,
+) -> Result<(), CommandError> {
+    let mut workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let current_head_ops = op_walk::get_current_head_ops(
+        repo_loader.op_store(),
+        repo_loader.op_heads_store().as_ref(),
+    )?;
+    let keep_newer = MillisSinceEpoch(
+        keep_newer
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis() as i64),
+    );
+    let mut expired_op = None;
+    for op in op_walk::walk_ancestors(&current_head_ops) {
+        let op = op?;
+        // The current operations are kept even if they are old.
+        if current_head_ops.contains(&op) {
+            continue;
+        }
+        if op.metadata().time.end.timestamp < keep_newer {
+            expired_op = Some(op);
+            break;
+        }
+    }
+    let root_op = repo_loader.root_operation();
+    match expired_op {
+        Some(op) if op != root_op => abandon_operation_range(
+            ui,
+            command,
+            &mut workspace,
+            &root_op,
+            &[op],
+            &current_head_ops,
+        ),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use clap::Subcommand;
+use itertools::Itertools as _;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::command_error::internal_error;
+use crate::command_error::user_error;
+use crate::formatter::FormatterExt as _;
+use crate::ui::Ui;
+
+/// Manage recorded conflict resolutions
+///
+/// If `rerere.enabled` is set, jj records how conflicts are resolved and
+/// resolves the same conflicts automatically when they appear again.
+#[derive(Subcommand, Clone, Debug)]
+pub enum UtilRerereCommand {
+    List(UtilRerereListArgs),
+    Forget(UtilRerereForgetArgs),
+}
+
+/// List recorded conflict resolutions
+///
+/// Prints the fingerprint of each recorded conflict and the path of the file it
+/// was recorded in.
+#[derive(clap::Args, Clone, Debug)]
+pub struct UtilRerereListArgs {}
+
+/// Forget recorded conflict resolutions
+///
+/// Forgotten resolutions are no longer applied automatically.
+#[derive(clap::Args, Clone, Debug)]
+#[command(group(clap::ArgGroup::new("which").args(&["fingerprints", "all"]).required(true)))]
+pub struct UtilRerereForgetArgs {
+    /// Fingerprints of the resolutions to forget
+    ///
+    /// A unique prefix of the fingerprint is also accepted.
+    fingerprints: Vec<String>,
+    /// Forget all recorded resolutions
+    #[arg(long)]
+    all: bool,
+}
+
+pub fn cmd_util_rerere(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &UtilRerereCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        UtilRerereCommand::List(args) => cmd_util_rerere_list(ui, command, args),
+        UtilRerereCommand::Forget(args) => cmd_util_rerere_forget(ui, command, args),
+    }
+}
+
+fn cmd_util_rerere_list(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &UtilRerereListArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let rerere_store = workspace_command.repo().loader().rerere_store();
+    let resolutions = rerere_store.list().map_err(internal_error)?;
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    let formatter = formatter.as_mut();
+    for resolution in &resolutions {
+        write!(
+            formatter.labeled("fingerprint"),
+            "{}",
+            resolution.fingerprint
+        )?;
+        writeln!(
+            formatter,
+            " {}",
+            workspace_command.format_file_path(&resolution.path)
+        )?;
+    }
+    Ok(())
+}
+
+fn cmd_util_rerere_forget(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &UtilRerereForgetArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let rerere_store = workspace_command.repo().loader().rerere_store();
+    let resolutions = rerere_store.list().map_err(internal_error)?;
+    let fingerprints = if args.all {
+        resolutions
+            .iter()
+            .map(|resolution| resolution.fingerprint.as_str())
+            .collect_vec()
+    } else {
+        let mut fingerprints = vec![];
+        for prefix in &args.fingerprints {
+            let matches = resolutions
+                .iter()
+                .filter(|resolution| resolution.fingerprint.starts_with(prefix.as_str()))
+                .collect_vec();
+            match matches[..] {
+                [] => {
+                    return Err(user_error(format!(
+                        "No recorded resolution matches fingerprint {prefix}"
+                    )));
+                }
+                [resolution] => fingerprints.push(resolution.fingerprint.as_str()),
+                _ => {
+                    return Err(user_error(format!(
+                        "Fingerprint prefix {prefix} is ambiguous"
+                    )));
+                }
+            }
+        }
+        fingerprints
+    };
+    let mut num_forgotten = 0;
+    for fingerprint in fingerprints.iter().unique() {
+        if rerere_store.forget(fingerprint).map_err(internal_error)? {
+            num_forgotten += 1;
+        }
+    }
+    writeln!(
+        ui.status(),
+        "Forgot {num_forgotten} recorded conflict resolutions"
+    )?;
+    Ok(())
+}
@@ -21,6 +21,7 @@ use jj_lib::file_util::IoResultExt as _;
 use jj_lib::ref_name::WorkspaceNameBuf;
 use jj_lib::repo::Repo as _;
 use jj_lib::rewrite::merge_commit_trees;
+use jj_lib::working_copy::SparsePatterns;
 use jj_lib::workspace::Workspace;
 use pollster::FutureExt as _;
 use tracing::instrument;
@@ -78,6 +79,14 @@ pub struct WorkspaceAddArgs {
     /// How to handle sparse patterns when creating a new workspace.
     #[arg(long, value_enum, default_value_t = SparseInheritance::Copy)]
     sparse_patterns: SparseInheritance,
+
+    /// Create a throwaway workspace
+    ///
+    /// The working-copy commit of an ephemeral workspace isn't shown in the
+    /// default log (see the `ephemeral_working_copies()` revset function), and
+    /// it's abandoned when the workspace is forgotten, even if it has changes.
+    #[arg(long)]
+    ephemeral: bool,
 }
 
 #[instrument(skip_all)]
@@ -147,12 +156,12 @@ pub fn cmd_workspace_add(
 
     let sparsity = match args.sparse_patterns {
         SparseInheritance::Full => None,
-        SparseInheritance::Empty => Some(vec![]),
+        SparseInheritance::Empty => Some(SparsePatterns::default()),
         SparseInheritance::Copy => {
             let sparse_patterns = old_workspace_command
                 .working_copy()
                 .sparse_patterns()?
-                .to_vec();
+                .clone();
             Some(sparse_patterns)
         }
     };
@@ -201,6 +210,10 @@ pub fn cmd_workspace_add(
     let new_wc_commit = tx.repo_mut().new_commit(parent_ids, tree).write()?;
 
     tx.edit(&new_wc_commit)?;
+    if args.ephemeral {
+        tx.repo_mut()
+            .set_ephemeral_workspace(workspace_name.clone(), true);
+    }
     tx.finish(
         ui,
         format!(
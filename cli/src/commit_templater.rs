@@ -45,6 +45,7 @@ use jj_lib::extensions_map::ExtensionsMap;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
+use jj_lib::fileset::FilesetExtensions;
 use jj_lib::id_prefix::IdPrefixContext;
 use jj_lib::id_prefix::IdPrefixIndex;
 use jj_lib::index::IndexResult;
@@ -105,6 +106,7 @@ use crate::template_builder::TemplateLanguage;
 use crate::template_builder::expect_stringify_expression;
 use crate::template_builder::merge_fn_map;
 use crate::template_parser;
+use crate::template_parser::ExpressionKind;
 use crate::template_parser::ExpressionNode;
 use crate::template_parser::FunctionCallNode;
 use crate::template_parser::TemplateDiagnostics;
@@ -1017,6 +1019,27 @@ fn builtin_commit_template_functions<'repo>()
             Ok(out_property.into_dyn_wrapped())
         },
     );
+    map.insert("diff", |language, diagnostics, build_ctx, function| {
+        let ([from_node, to_node], [files_node]) = function.expect_arguments()?;
+        let from_property = expect_commit_expression(language, diagnostics, build_ctx, from_node)?;
+        let to_property = expect_commit_expression(language, diagnostics, build_ctx, to_node)?;
+        let files = if let Some(node) = files_node {
+            expect_fileset_literal(
+                diagnostics,
+                node,
+                language.path_converter,
+                language.revset_parse_context.extensions.fileset(),
+            )?
+        } else {
+            FilesetExpression::all()
+        };
+        let repo = language.repo;
+        let matcher: Rc<dyn Matcher> = files.to_matcher().into();
+        let out_property = (from_property, to_property).and_then(move |(from, to)| {
+            Ok(TreeDiff::from_commits(repo, &from, &to, matcher.clone())?)
+        });
+        Ok(out_property.into_dyn_wrapped())
+    });
     map
 }
 
@@ -1101,6 +1124,19 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             Ok(out_property.into_dyn_wrapped())
         },
     );
+    map.insert(
+        "note",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            let ([], [ref_node]) = function.expect_arguments()?;
+            let name = match ref_node {
+                Some(node) => template_parser::expect_string_literal(node)?,
+                None => "commits",
+            };
+            let get_note = git_util::git_note_getter(language.repo.store(), name);
+            let out_property = self_property.and_then(move |commit| Ok(get_note(commit.id())?));
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
     map.insert(
         "working_copies",
         |language, _diagnostics, _build_ctx, self_property, function| {
@@ -1311,7 +1347,12 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
         |language, diagnostics, _build_ctx, self_property, function| {
             let ([], [files_node]) = function.expect_arguments()?;
             let files = if let Some(node) = files_node {
-                expect_fileset_literal(diagnostics, node, language.path_converter)?
+                expect_fileset_literal(
+                    diagnostics,
+                    node,
+                    language.path_converter,
+                    language.revset_parse_context.extensions.fileset(),
+                )?
             } else {
                 // TODO: defaults to CLI path arguments?
                 // https://github.com/jj-vcs/jj/issues/2933#issuecomment-1925870731
@@ -1329,7 +1370,12 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
         |language, diagnostics, _build_ctx, self_property, function| {
             let ([], [files_node]) = function.expect_arguments()?;
             let files = if let Some(node) = files_node {
-                expect_fileset_literal(diagnostics, node, language.path_converter)?
+                expect_fileset_literal(
+                    diagnostics,
+                    node,
+                    language.path_converter,
+                    language.revset_parse_context.extensions.fileset(),
+                )?
             } else {
                 // TODO: defaults to CLI path arguments?
                 // https://github.com/jj-vcs/jj/issues/2933#issuecomment-1925870731
@@ -1395,14 +1441,15 @@ fn expect_fileset_literal(
     diagnostics: &mut TemplateDiagnostics,
     node: &ExpressionNode,
     path_converter: &RepoPathUiConverter,
+    extensions: &FilesetExtensions,
 ) -> Result<FilesetExpression, TemplateParseError> {
     template_parser::catch_aliases(diagnostics, node, |diagnostics, node| {
         let text = template_parser::expect_string_literal(node)?;
         let mut inner_diagnostics = FilesetDiagnostics::new();
-        let expression =
-            fileset::parse(&mut inner_diagnostics, text, path_converter).map_err(|err| {
-                TemplateParseError::expression("In fileset expression", node.span).with_source(err)
-            })?;
+        let expression = fileset::parse(&mut inner_diagnostics, text, path_converter, extensions)
+            .map_err(|err| {
+            TemplateParseError::expression("In fileset expression", node.span).with_source(err)
+        })?;
         diagnostics.extend_with(inner_diagnostics, |diag| {
             TemplateParseError::expression("In fileset expression", node.span).with_source(diag)
         });
@@ -1410,6 +1457,54 @@ fn expect_fileset_literal(
     })
 }
 
+/// Builds `Commit` expression. A string literal is evaluated as a revset which
+/// should resolve to exactly one commit.
+fn expect_commit_expression<'repo>(
+    language: &CommitTemplateLanguage<'repo>,
+    diagnostics: &mut TemplateDiagnostics,
+    build_ctx: &BuildContext<CommitTemplatePropertyKind<'repo>>,
+    node: &ExpressionNode,
+) -> TemplateParseResult<BoxedTemplateProperty<'repo, Commit>> {
+    template_parser::catch_aliases(diagnostics, node, |diagnostics, node| {
+        if let ExpressionKind::String(text) = &node.kind {
+            let revset = evaluate_user_revset(language, diagnostics, node.span, text)?;
+            let make_error =
+                || TemplateParseError::expression("Failed to evaluate revset", node.span);
+            let commit_ids: Vec<_> = revset
+                .iter()
+                .take(2)
+                .try_collect()
+                .map_err(|err| make_error().with_source(err))?;
+            let [commit_id] = commit_ids.as_slice() else {
+                return Err(TemplateParseError::expression(
+                    format!("Revset `{text}` should resolve to exactly one commit"),
+                    node.span,
+                ));
+            };
+            let commit = language
+                .repo
+                .store()
+                .get_commit(commit_id)
+                .map_err(|err| make_error().with_source(err))?;
+            return Ok(Literal(commit).into_dyn());
+        }
+        let expression =
+            template_builder::build_expression(language, diagnostics, build_ctx, node)?;
+        let actual_type = expression.type_name();
+        match expression.into_property() {
+            CommitTemplatePropertyKind::Commit(property) => Ok(property),
+            CommitTemplatePropertyKind::CommitOpt(property) => {
+                Ok(property.try_unwrap("Commit").into_dyn())
+            }
+            _ => Err(TemplateParseError::expected_type(
+                "Commit",
+                actual_type,
+                node.span,
+            )),
+        }
+    })
+}
+
 fn evaluate_revset_expression<'repo>(
     language: &CommitTemplateLanguage<'repo>,
     span: pest::Span<'_>,
@@ -1486,7 +1581,12 @@ fn builtin_commit_evolution_entry_methods<'repo>()
         |language, diagnostics, _build_ctx, self_property, function| {
             let ([], [files_node]) = function.expect_arguments()?;
             let files = if let Some(node) = files_node {
-                expect_fileset_literal(diagnostics, node, language.path_converter)?
+                expect_fileset_literal(
+                    diagnostics,
+                    node,
+                    language.path_converter,
+                    language.revset_parse_context.extensions.fileset(),
+                )?
             } else {
                 FilesetExpression::all()
             };
@@ -2280,6 +2380,23 @@ impl TreeDiff {
         })
     }
 
+    fn from_commits(
+        repo: &dyn Repo,
+        from: &Commit,
+        to: &Commit,
+        matcher: Rc<dyn Matcher>,
+    ) -> BackendResult<Self> {
+        let mut copy_records = CopyRecords::default();
+        let records = diff_util::get_copy_records(repo.store(), from.id(), to.id(), &*matcher)?;
+        copy_records.add_records(records)?;
+        Ok(Self {
+            from_tree: from.tree(),
+            to_tree: to.tree(),
+            matcher,
+            copy_records,
+        })
+    }
+
     fn diff_stream(&self) -> BoxStream<'_, CopiesTreeDiffEntry> {
         self.from_tree
             .diff_stream_with_copies(&self.to_tree, &*self.matcher, &self.copy_records)
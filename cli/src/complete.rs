@@ -19,6 +19,7 @@ use std::path::Path;
 use clap::FromArgMatches as _;
 use clap::builder::StyledStr;
 use clap_complete::CompletionCandidate;
+use indoc::formatdoc;
 use indoc::indoc;
 use itertools::Itertools as _;
 use jj_lib::config::ConfigNamePathBuf;
@@ -47,12 +48,15 @@ use crate::ui::Ui;
 
 const BOOKMARK_HELP_TEMPLATE: &str = r#"template-aliases.'bookmark_help()'='''
 " " ++
-if(normal_target,
-    if(normal_target.description(),
-        normal_target.description().first_line(),
-        "(no description set)",
+if(!present,
+    "(deleted bookmark)",
+    if(normal_target,
+        if(normal_target.description(),
+            normal_target.description().first_line(),
+            "(no description set)",
+        ),
+        "(conflicted bookmark)",
     ),
-    "(conflicted bookmark)",
 )
 '''"#;
 const TAG_HELP_TEMPLATE: &str = r#"template-aliases.'tag_help()'='''
@@ -231,6 +235,39 @@ pub fn bookmarks() -> Vec<CompletionCandidate> {
     })
 }
 
+/// Bookmarks that exist on the remotes given by `--remote`, or on any remote
+/// if no `--remote` is given. The remotes are shown as help text.
+pub fn remote_bookmarks() -> Vec<CompletionCandidate> {
+    with_jj(|jj, _| {
+        let mut cmd = jj.build();
+        cmd.arg("bookmark").arg("list");
+        let remotes = parse::remotes();
+        if remotes.is_empty() {
+            cmd.arg("--all-remotes");
+        }
+        for remote in remotes {
+            cmd.arg("--remote").arg(remote);
+        }
+        let output = cmd
+            .arg("--template")
+            .arg(r#"if(remote != "git", if(remote, name ++ " " ++ remote ++ "\n"))"#)
+            .output()
+            .map_err(user_error)?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        Ok((&stdout
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .chunk_by(|(name, _)| *name))
+            .into_iter()
+            .map(|(name, refs)| {
+                let remotes = refs.map(|(_, remote)| remote).join(", ");
+                CompletionCandidate::new(name).help(Some(remotes.into()))
+            })
+            .collect())
+    })
+}
+
 pub fn local_tags() -> Vec<CompletionCandidate> {
     with_jj(|jj, _| {
         let output = jj
@@ -527,6 +564,28 @@ pub fn operations() -> Vec<CompletionCandidate> {
     })
 }
 
+/// Completes an operation, or an operation range as accepted by
+/// `jj op abandon`.
+pub fn operation_range(current: &std::ffi::OsStr) -> Vec<CompletionCandidate> {
+    let Some(current) = current.to_str() else {
+        return Vec::new();
+    };
+    let (prepend, prefix) = match current.rsplit_once("..") {
+        Some((start, end)) => (&current[..start.len() + 2], end),
+        None => ("", current),
+    };
+    operations()
+        .into_iter()
+        .filter(|candidate| {
+            candidate
+                .get_value()
+                .to_str()
+                .is_some_and(|id| id.starts_with(prefix))
+        })
+        .map(|candidate| candidate.add_prefix(prepend))
+        .collect()
+}
+
 pub fn workspaces() -> Vec<CompletionCandidate> {
     let template = indoc! {r#"
         name ++ "\t" ++ if(
@@ -847,22 +906,181 @@ fn path_completion_candidate_from(
     }
 }
 
-fn current_prefix_to_fileset(current: &str) -> String {
-    let cur_esc = globset::escape(current);
-    let dir_pat = format!("{cur_esc}*/**");
-    let path_pat = format!("{cur_esc}*");
-    format!("glob:{dir_pat:?} | glob:{path_pat:?}")
+/// Pattern kinds that take a path relative to the current directory.
+const CWD_PATTERN_KINDS: &[&str] = &[
+    "cwd",
+    "cwd-file",
+    "file",
+    "cwd-glob",
+    "glob",
+    "cwd-glob-i",
+    "glob-i",
+    "cwd-prefix-glob",
+    "prefix-glob",
+    "cwd-prefix-glob-i",
+    "prefix-glob-i",
+];
+
+/// Pattern kinds that take a path relative to the workspace root.
+const ROOT_PATTERN_KINDS: &[&str] = &[
+    "root",
+    "root-file",
+    "root-glob",
+    "root-glob-i",
+    "root-prefix-glob",
+    "root-prefix-glob-i",
+];
+
+const FILESET_FUNCTIONS: &[(&str, &str)] = &[
+    ("all()", "Matches everything"),
+    ("none()", "Matches nothing"),
+    ("size(", "Matches files whose size is within a range"),
+    ("executable()", "Matches executable files"),
+    ("symlink()", "Matches symbolic links"),
+    ("conflict()", "Matches conflicted files"),
+];
+
+/// The path at the end of an incomplete fileset expression.
+#[derive(Debug, Eq, PartialEq)]
+struct TrailingFilesetPath<'a> {
+    /// The expression up to the path, including the pattern kind and the
+    /// opening quote if any.
+    prepend: &'a str,
+    /// The incomplete path.
+    path: &'a str,
+    /// Whether the path may also be the start of a pattern kind or function
+    /// name.
+    may_be_name: bool,
+    /// Whether the path is relative to the workspace root.
+    root_relative: bool,
+}
+
+/// Splits an incomplete fileset expression at the start of its last path,
+/// e.g. `src ~ root:"lib/fo` into `src ~ root:"` and `lib/fo`.
+///
+/// Like [`split_revset_trailing_name()`], this doesn't attempt to parse the
+/// expression. Unquoted paths containing operator characters will be split
+/// in the middle.
+fn split_fileset_trailing_path(incomplete_fileset_str: &str) -> TrailingFilesetPath<'_> {
+    let is_pattern_kind =
+        |kind: &str| CWD_PATTERN_KINDS.contains(&kind) || ROOT_PATTERN_KINDS.contains(&kind);
+    let (kind, path, quoted) = if incomplete_fileset_str.matches('"').count() % 2 == 1 {
+        let (before, path) = incomplete_fileset_str.rsplit_once('"').unwrap();
+        let kind = before.strip_suffix(':').and_then(|before| {
+            let kind = before
+                .rsplit_once(|c: char| !c.is_ascii_lowercase() && c != '-')
+                .map_or(before, |(_, kind)| kind);
+            is_pattern_kind(kind).then_some(kind)
+        });
+        (kind, path, true)
+    } else {
+        let operand = incomplete_fileset_str
+            .rsplit_once(['~', '|', '&', '(', ','])
+            .map_or(incomplete_fileset_str, |(_, rest)| rest)
+            .trim_ascii_start();
+        match operand.split_once(':') {
+            Some((kind, path)) if is_pattern_kind(kind) => (Some(kind), path, false),
+            _ => (None, operand, false),
+        }
+    };
+    TrailingFilesetPath {
+        prepend: &incomplete_fileset_str[..incomplete_fileset_str.len() - path.len()],
+        path,
+        may_be_name: kind.is_none() && !quoted,
+        root_relative: kind.is_some_and(|kind| ROOT_PATTERN_KINDS.contains(&kind)),
+    }
+}
+
+impl TrailingFilesetPath<'_> {
+    /// Fileset matching the files and directories that start with the path.
+    fn to_fileset(&self) -> String {
+        let kind = if self.root_relative {
+            "root-glob"
+        } else {
+            "glob"
+        };
+        let cur_esc = globset::escape(self.path);
+        let dir_pat = format!("{cur_esc}*/**");
+        let path_pat = format!("{cur_esc}*");
+        format!("{kind}:{dir_pat:?} | {kind}:{path_pat:?}")
+    }
+
+    /// Template expression that renders the `path` expression relative to
+    /// the same directory as the incomplete path.
+    fn path_template(&self, path: &str) -> String {
+        if self.root_relative {
+            path.to_owned()
+        } else {
+            format!("{path}.display()")
+        }
+    }
+
+    fn candidate(
+        &self,
+        normalized_prefix_path: &Path,
+        path: &Path,
+        mode: Option<StyledStr>,
+    ) -> Option<CompletionCandidate> {
+        let candidate =
+            path_completion_candidate_from(self.path, normalized_prefix_path, path, mode)?;
+        Some(candidate.add_prefix(self.prepend))
+    }
+
+    /// Pattern kinds and functions that start with the incomplete path.
+    fn name_candidates(&self) -> Vec<CompletionCandidate> {
+        if !self.may_be_name
+            || self.path.is_empty()
+            || !self
+                .path
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c == '-')
+        {
+            return Vec::new();
+        }
+        let kinds = CWD_PATTERN_KINDS
+            .iter()
+            .map(|kind| (format!("{kind}:"), "Path relative to the current directory"))
+            .chain(
+                ROOT_PATTERN_KINDS
+                    .iter()
+                    .map(|kind| (format!("{kind}:"), "Path relative to the workspace root")),
+            );
+        let functions = FILESET_FUNCTIONS
+            .iter()
+            .map(|&(name, help)| (name.to_owned(), help));
+        kinds
+            .chain(functions)
+            .filter(|(name, _)| name.starts_with(self.path))
+            .map(|(name, help)| {
+                CompletionCandidate::new(format!("{}{name}", self.prepend)).help(Some(help.into()))
+            })
+            .collect()
+    }
+}
+
+/// Completes pattern kinds and fileset functions if no paths matched.
+fn or_fileset_names(
+    candidates: Vec<CompletionCandidate>,
+    current: &std::ffi::OsStr,
+) -> Vec<CompletionCandidate> {
+    match current.to_str() {
+        Some(current) if candidates.is_empty() => {
+            split_fileset_trailing_path(current).name_candidates()
+        }
+        _ => candidates,
+    }
 }
 
 fn all_files_from_rev(rev: String, current: &std::ffi::OsStr) -> Vec<CompletionCandidate> {
-    let Some(current) = current.to_str() else {
+    let Some(current_str) = current.to_str() else {
         return Vec::new();
     };
+    let trailing = split_fileset_trailing_path(current_str);
 
-    let normalized_prefix = normalize_path(Path::new(current));
+    let normalized_prefix = normalize_path(Path::new(trailing.path));
     let normalized_prefix = slash_path(&normalized_prefix);
 
-    with_jj(|jj, _| {
+    let candidates = with_jj(|jj, _| {
         let mut child = jj
             .build()
             .arg("file")
@@ -870,8 +1088,8 @@ fn all_files_from_rev(rev: String, current: &std::ffi::OsStr) -> Vec<CompletionC
             .arg("--revision")
             .arg(rev)
             .arg("--template")
-            .arg(r#"path.display() ++ "\n""#)
-            .arg(current_prefix_to_fileset(current))
+            .arg(format!(r#"{} ++ "\n""#, trailing.path_template("path")))
+            .arg(trailing.to_fileset())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::null())
             .spawn()
@@ -882,12 +1100,11 @@ fn all_files_from_rev(rev: String, current: &std::ffi::OsStr) -> Vec<CompletionC
             .lines()
             .take(1_000)
             .map_while(Result::ok)
-            .filter_map(|path| {
-                path_completion_candidate_from(current, &normalized_prefix, Path::new(&path), None)
-            })
+            .filter_map(|path| trailing.candidate(&normalized_prefix, Path::new(&path), None))
             .dedup() // directories may occur multiple times
             .collect())
-    })
+    });
+    or_fileset_names(candidates, current)
 }
 
 fn modified_files_from_rev_with_jj_cmd(
@@ -898,20 +1115,25 @@ fn modified_files_from_rev_with_jj_cmd(
     let Some(current) = current.to_str() else {
         return Ok(Vec::new());
     };
+    let trailing = split_fileset_trailing_path(current);
 
-    let normalized_prefix = normalize_path(Path::new(current));
+    let normalized_prefix = normalize_path(Path::new(trailing.path));
     let normalized_prefix = slash_path(&normalized_prefix);
 
     // In case of a rename, one entry of `diff` results in two suggestions.
-    let template = indoc! {r#"
+    let template = formatdoc! {r#"
         concat(
-          status ++ ' ' ++ path.display() ++ "\n",
-          if(status == 'renamed', 'renamed.source ' ++ source.path().display() ++ "\n"),
+          status ++ ' ' ++ {path} ++ "\n",
+          if(status == 'renamed', 'renamed.source ' ++ {source_path} ++ "\n"),
         )
-    "#};
+    "#,
+        path = trailing.path_template("path"),
+        source_path = trailing.path_template("source.path()"),
+    };
     cmd.arg("diff")
-        .args(["--template", template])
-        .arg(current_prefix_to_fileset(current));
+        .arg("--template")
+        .arg(template)
+        .arg(trailing.to_fileset());
     match rev {
         (rev, None) => cmd.arg("--revisions").arg(rev),
         (from, Some(to)) => cmd.arg("--from").arg(from).arg("--to").arg(to),
@@ -936,7 +1158,7 @@ fn modified_files_from_rev_with_jj_cmd(
                 "copied" => "Copied".into(),
                 _ => format!("unknown mode: '{mode}'").into(),
             };
-            path_completion_candidate_from(current, &normalized_prefix, Path::new(path), Some(mode))
+            trailing.candidate(&normalized_prefix, Path::new(path), Some(mode))
         })
         .collect();
 
@@ -952,42 +1174,40 @@ fn modified_files_from_rev(
     rev: (String, Option<String>),
     current: &std::ffi::OsStr,
 ) -> Vec<CompletionCandidate> {
-    with_jj(|jj, _| modified_files_from_rev_with_jj_cmd(rev, jj.build(), current))
+    let candidates = with_jj(|jj, _| modified_files_from_rev_with_jj_cmd(rev, jj.build(), current));
+    or_fileset_names(candidates, current)
 }
 
 fn conflicted_files_from_rev(rev: &str, current: &std::ffi::OsStr) -> Vec<CompletionCandidate> {
-    let Some(current) = current.to_str() else {
+    let Some(current_str) = current.to_str() else {
         return Vec::new();
     };
+    let trailing = split_fileset_trailing_path(current_str);
 
-    let normalized_prefix = normalize_path(Path::new(current));
+    let normalized_prefix = normalize_path(Path::new(trailing.path));
     let normalized_prefix = slash_path(&normalized_prefix);
 
-    with_jj(|jj, _| {
+    let candidates = with_jj(|jj, _| {
         let output = jj
             .build()
-            .arg("resolve")
-            .arg("--list")
+            .arg("file")
+            .arg("list")
             .arg("--revision")
             .arg(rev)
-            .arg(current_prefix_to_fileset(current))
+            .arg("--template")
+            .arg(format!(r#"{} ++ "\n""#, trailing.path_template("path")))
+            .arg(format!("conflict() & ({})", trailing.to_fileset()))
             .output()
             .map_err(user_error)?;
         let stdout = String::from_utf8_lossy(&output.stdout);
 
         Ok(stdout
             .lines()
-            .filter_map(|line| {
-                let path = line
-                    .split_whitespace()
-                    .next()
-                    .expect("resolve --list should contain whitespace after path");
-
-                path_completion_candidate_from(current, &normalized_prefix, Path::new(path), None)
-            })
+            .filter_map(|path| trailing.candidate(&normalized_prefix, Path::new(path), None))
             .dedup() // directories may occur multiple times
             .collect())
-    })
+    });
+    or_fileset_names(candidates, current)
 }
 
 pub fn modified_files(current: &std::ffi::OsStr) -> Vec<CompletionCandidate> {
@@ -1047,7 +1267,7 @@ pub fn interdiff_files(current: &std::ffi::OsStr) -> Vec<CompletionCandidate> {
     // Complete all modified files in "from" and "to". This will also suggest
     // files that are the same in both, which is a false positive. This approach
     // is more lightweight than actually doing a temporary rebase here.
-    with_jj(|jj, _| {
+    let candidates = with_jj(|jj, _| {
         let mut res = modified_files_from_rev_with_jj_cmd((from, None), jj.build(), current)?;
         res.extend(modified_files_from_rev_with_jj_cmd(
             (to, None),
@@ -1055,7 +1275,8 @@ pub fn interdiff_files(current: &std::ffi::OsStr) -> Vec<CompletionCandidate> {
             current,
         )?);
         Ok(res)
-    })
+    });
+    or_fileset_names(candidates, current)
 }
 
 /// Specific function for completing file paths for `jj log`
@@ -1123,6 +1344,9 @@ fn get_jj_command() -> Result<(JjBuilder, UserSettings), CommandError> {
         config_env
             .reload_workspace_config(&ui, &mut raw_config)
             .ok();
+        config_env
+            .reload_repo_managed_config(&ui, &mut raw_config)
+            .ok();
     }
     let mut config = config_env.resolve_config(&raw_config)?;
     // skip 2 because of the clap_complete prelude: jj -- jj <actual args...>
@@ -1145,6 +1369,9 @@ fn get_jj_command() -> Result<(JjBuilder, UserSettings), CommandError> {
             config_env
                 .reload_workspace_config(&ui, &mut raw_config)
                 .ok();
+            config_env
+                .reload_repo_managed_config(&ui, &mut raw_config)
+                .ok();
             if let Ok(new_config) = config_env.resolve_config(&raw_config) {
                 config = new_config;
             }
@@ -1281,6 +1508,10 @@ mod parse {
             .unwrap_or_else(|| "@".into())
     }
 
+    pub fn remotes() -> Vec<String> {
+        parse_flag(&["--remote"], std::env::args()).collect()
+    }
+
     pub fn parse_range_impl<T>(args: impl Fn() -> T) -> Option<(String, String)>
     where
         T: Iterator<Item = String>,
@@ -1387,6 +1618,36 @@ mod tests {
         assert_eq!(split_revset_trailing_name("foo(bar)"), None);
     }
 
+    #[test]
+    fn test_split_fileset_trailing_path() {
+        let split = |s| {
+            let trailing = split_fileset_trailing_path(s);
+            (
+                trailing.prepend,
+                trailing.path,
+                trailing.may_be_name,
+                trailing.root_relative,
+            )
+        };
+        assert_eq!(split(""), ("", "", true, false));
+        assert_eq!(split("src/fo"), ("", "src/fo", true, false));
+        assert_eq!(split("Foo Ba"), ("", "Foo Ba", true, false));
+        assert_eq!(split("src ~ lib/fo"), ("src ~ ", "lib/fo", true, false));
+        assert_eq!(split("~ex"), ("~", "ex", true, false));
+        assert_eq!(split("glob:src/"), ("glob:", "src/", false, false));
+        assert_eq!(split("root:lib/"), ("root:", "lib/", false, true));
+        assert_eq!(split("a | root:"), ("a | root:", "", false, true));
+        assert_eq!(split("foo:bar"), ("", "foo:bar", true, false));
+
+        // Quoted paths
+        assert_eq!(split(r#""Foo Ba"#), (r#"""#, "Foo Ba", false, false));
+        assert_eq!(
+            split(r#"src ~ root-file:"lib/a b"#),
+            (r#"src ~ root-file:""#, "lib/a b", false, true)
+        );
+        assert_eq!(split(r#""a b" | c"#), (r#""a b" | "#, "c", true, false));
+    }
+
     #[test]
     fn test_config_keys() {
         // Just make sure the schema is parsed without failure.
@@ -18,6 +18,8 @@ use std::collections::HashMap;
 use std::env;
 use std::env::split_paths;
 use std::fmt;
+use std::fs;
+use std::io;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
@@ -37,6 +39,9 @@ use jj_lib::config::ConfigResolutionContext;
 use jj_lib::config::ConfigSource;
 use jj_lib::config::ConfigValue;
 use jj_lib::config::StackedConfig;
+use jj_lib::content_hash::blake2b_hash;
+use jj_lib::file_util::IoResultExt as _;
+use jj_lib::hex_util;
 use jj_lib::secure_config::LoadedSecureConfig;
 use jj_lib::secure_config::SecureConfig;
 use rand::SeedableRng as _;
@@ -56,6 +61,12 @@ pub const CONFIG_SCHEMA: &str = include_str!("config-schema.json");
 
 const REPO_CONFIG_DIR: &str = "repos";
 const WORKSPACE_CONFIG_DIR: &str = "workspaces";
+/// Path to the config file checked in to the repository, relative to the
+/// workspace root.
+const REPO_MANAGED_CONFIG_PATH: &str = ".config/jj/config.toml";
+/// Name of the file that records the hash of the trusted repo-managed config.
+/// It's stored next to the per-repo config file, outside of the repository.
+const REPO_MANAGED_CONFIG_TRUST_FILE: &str = "trusted-repo-config";
 
 /// Parses a TOML value expression. Interprets the given value as string if it
 /// can't be parsed and doesn't look like a TOML expression.
@@ -338,6 +349,7 @@ pub struct ConfigEnv {
     user_config_paths: Vec<ConfigPath>,
     repo_config: Option<SecureConfig>,
     workspace_config: Option<SecureConfig>,
+    repo_managed_config_path: Option<PathBuf>,
     command: Option<String>,
     hostname: Option<String>,
     rng: Arc<Mutex<ChaCha20Rng>>,
@@ -369,6 +381,7 @@ impl ConfigEnv {
             user_config_paths: env.resolve(),
             repo_config: None,
             workspace_config: None,
+            repo_managed_config_path: None,
             command: None,
             hostname: whoami::hostname().ok(),
             // We would ideally use JjRng, but that requires the seed from the
@@ -531,6 +544,7 @@ impl ConfigEnv {
     pub fn reset_workspace_path(&mut self, path: &Path) {
         self.workspace_config = Some(SecureConfig::new_workspace(path.join(".jj")));
         self.workspace_path = Some(path.to_owned());
+        self.repo_managed_config_path = Some(path.join(REPO_MANAGED_CONFIG_PATH));
     }
 
     /// Returns a path to the workspace-specific config file, if it exists.
@@ -589,6 +603,86 @@ impl ConfigEnv {
         Ok(())
     }
 
+    /// Returns a path to the config file checked in to the workspace, if it
+    /// exists.
+    pub fn repo_managed_config_path(&self) -> Option<&Path> {
+        self.repo_managed_config_path.as_deref().filter(|path| {
+            // The workspace may be the home directory, in which case the file
+            // is loaded as user config.
+            path.is_file() && !self.user_config_paths().any(|user_path| user_path == *path)
+        })
+    }
+
+    fn repo_managed_config_trust_path(
+        &self,
+        ui: &Ui,
+        create: bool,
+    ) -> Result<Option<PathBuf>, CommandError> {
+        let repo_config_path = if create {
+            self.repo_config_path(ui)?
+        } else {
+            self.maybe_repo_config_path(ui)?
+        };
+        Ok(repo_config_path.map(|path| path.with_file_name(REPO_MANAGED_CONFIG_TRUST_FILE)))
+    }
+
+    /// Marks the current content of the repo-managed config file as trusted.
+    /// Returns the path to the config file if it exists.
+    ///
+    /// The trusted content is recorded in the per-repo config directory, so a
+    /// change to the checked-in file will have to be trusted again.
+    pub fn trust_repo_managed_config(&self, ui: &Ui) -> Result<Option<PathBuf>, CommandError> {
+        let Some(path) = self.repo_managed_config_path() else {
+            return Ok(None);
+        };
+        let text = fs::read_to_string(path).context(path)?;
+        let Some(trust_path) = self.repo_managed_config_trust_path(ui, true)? else {
+            return Ok(None);
+        };
+        fs::write(&trust_path, repo_managed_config_digest(&text)).context(&trust_path)?;
+        Ok(Some(path.to_owned()))
+    }
+
+    /// Loads the repo-managed config file into the given `config` if its
+    /// content is trusted. The old repo-managed layer will be replaced if any.
+    ///
+    /// Returns the path to the config file if it exists but isn't trusted.
+    #[instrument(skip(ui))]
+    pub fn reload_repo_managed_config(
+        &self,
+        ui: &Ui,
+        config: &mut RawConfig,
+    ) -> Result<Option<PathBuf>, CommandError> {
+        config.as_mut().remove_layers(ConfigSource::RepoManaged);
+        let Some(path) = self.repo_managed_config_path() else {
+            return Ok(None);
+        };
+        let text = fs::read_to_string(path).context(path)?;
+        let trusted_digest = match self.repo_managed_config_trust_path(ui, false)? {
+            Some(trust_path) => match fs::read_to_string(&trust_path).context(&trust_path) {
+                Ok(digest) => Some(digest),
+                Err(err) if err.source.kind() == io::ErrorKind::NotFound => None,
+                Err(err) => return Err(err.into()),
+            },
+            None => None,
+        };
+        if trusted_digest.as_deref().map(str::trim) != Some(&repo_managed_config_digest(&text)) {
+            return Ok(Some(path.to_owned()));
+        }
+        // Parse the text we've hashed rather than reloading the file.
+        let mut layer =
+            ConfigLayer::parse(ConfigSource::RepoManaged, &text).map_err(|err| match err {
+                ConfigLoadError::Parse { error, .. } => ConfigLoadError::Parse {
+                    error,
+                    source_path: Some(path.to_owned()),
+                },
+                err => err,
+            })?;
+        layer.path = Some(path.to_owned());
+        config.as_mut().add_layer(layer);
+        Ok(None)
+    }
+
     /// Resolves conditional scopes within the current environment. Returns new
     /// resolved config.
     pub fn resolve_config(&self, config: &RawConfig) -> Result<StackedConfig, ConfigGetError> {
@@ -603,6 +697,10 @@ impl ConfigEnv {
     }
 }
 
+fn repo_managed_config_digest(text: &str) -> String {
+    hex_util::encode_hex(&blake2b_hash(text))
+}
+
 fn config_files_for(
     config: &RawConfig,
     source: ConfigSource,
@@ -694,6 +792,21 @@ pub fn default_config_layers() -> Vec<ConfigLayer> {
     if cfg!(windows) {
         layers.push(parse(include_str!("config/windows.toml")));
     }
+    // The builtin filesystem monitor daemon is run by this executable.
+    if let Some(exe) = env::current_exe()
+        .inspect_err(|err| tracing::warn!(?err, "failed to get current executable"))
+        .ok()
+        .and_then(|path| path.into_os_string().into_string().ok())
+    {
+        let mut layer = ConfigLayer::empty(ConfigSource::Default);
+        layer
+            .set_value(
+                "fsmonitor.builtin.daemon-command",
+                toml_edit::Array::from_iter([exe.as_str(), "debug", "fsmonitor", "daemon"]),
+            )
+            .unwrap();
+        layers.push(layer);
+    }
     layers
 }
 
@@ -1816,6 +1929,7 @@ mod tests {
             user_config_paths: env.resolve(),
             repo_config: None,
             workspace_config: None,
+            repo_managed_config_path: None,
             command: None,
             hostname: None,
             rng: Arc::new(Mutex::new(ChaCha20Rng::seed_from_u64(0))),
@@ -150,7 +150,7 @@ fn append_blank_line(text: &mut String) {
 
 /// Cleanup a description by normalizing line endings, and removing leading and
 /// trailing blank lines.
-fn cleanup_description_lines<I>(lines: I) -> String
+pub fn cleanup_description_lines<I>(lines: I) -> String
 where
     I: IntoIterator,
     I::Item: AsRef<str>,
@@ -47,6 +47,7 @@ use jj_lib::copies::CopiesTreeDiffEntryPath;
 use jj_lib::copies::CopyOperation;
 use jj_lib::copies::CopyRecords;
 use jj_lib::diff::ContentDiff;
+use jj_lib::diff::DiffAlgorithm;
 use jj_lib::diff::DiffHunk;
 use jj_lib::diff::DiffHunkKind;
 use jj_lib::diff_presentation::DiffTokenType;
@@ -157,6 +158,32 @@ pub struct DiffFormatArgs {
     /// Ignore changes in amount of whitespace when comparing lines.
     #[arg(long, conflicts_with = "ignore_all_space")] // short = 'b'
     ignore_space_change: bool,
+
+    /// Ignore changes whose lines are all blank.
+    #[arg(long)]
+    ignore_blank_lines: bool,
+
+    /// Algorithm to find the changed lines (overrides `diff.algorithm`)
+    #[arg(long, value_enum, value_name = "ALGORITHM")]
+    algorithm: Option<DiffAlgorithmArg>,
+}
+
+/// Command-line counterpart of [`DiffAlgorithm`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, clap::ValueEnum)]
+enum DiffAlgorithmArg {
+    Histogram,
+    Patience,
+    Minimal,
+}
+
+impl From<DiffAlgorithmArg> for DiffAlgorithm {
+    fn from(arg: DiffAlgorithmArg) -> Self {
+        match arg {
+            DiffAlgorithmArg::Histogram => Self::Histogram,
+            DiffAlgorithmArg::Patience => Self::Patience,
+            DiffAlgorithmArg::Minimal => Self::Minimal,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -256,7 +283,7 @@ impl BuiltinFormatKind {
         match self {
             Self::Summary => Ok(DiffFormat::Summary),
             Self::Stat => {
-                let mut options = DiffStatOptions::default();
+                let mut options = DiffStatOptions::from_settings(settings)?;
                 options.merge_args(args);
                 Ok(DiffFormat::Stat(Box::new(options)))
             }
@@ -709,11 +736,25 @@ pub enum ConflictDiffMethod {
 pub struct LineDiffOptions {
     /// How equivalence of lines is tested.
     pub compare_mode: LineCompareMode,
-    // TODO: add --ignore-blank-lines, etc. which aren't mutually exclusive.
+    /// Algorithm to find the unchanged lines.
+    pub algorithm: DiffAlgorithm,
+    /// Whether to ignore changes whose lines are all blank.
+    pub ignore_blank_lines: bool,
 }
 
 impl LineDiffOptions {
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
+        Ok(Self {
+            compare_mode: LineCompareMode::Exact,
+            algorithm: settings.get("diff.algorithm")?,
+            ignore_blank_lines: false,
+        })
+    }
+
     fn merge_args(&mut self, args: &DiffFormatArgs) {
+        if let Some(algorithm) = args.algorithm {
+            self.algorithm = algorithm.into();
+        }
         self.compare_mode = if args.ignore_all_space {
             LineCompareMode::IgnoreAllSpace
         } else if args.ignore_space_change {
@@ -721,6 +762,18 @@ impl LineDiffOptions {
         } else {
             LineCompareMode::Exact
         };
+        self.ignore_blank_lines = args.ignore_blank_lines;
+    }
+
+    fn diff_by_line<'input, T: AsRef<[u8]> + ?Sized + 'input>(
+        &self,
+        inputs: impl IntoIterator<Item = &'input T>,
+    ) -> ContentDiff<'input> {
+        let mut diff = diff_by_line(inputs, &self.compare_mode, self.algorithm);
+        if self.ignore_blank_lines {
+            diff.ignore_blank_line_changes();
+        }
+        diff
     }
 }
 
@@ -752,7 +805,7 @@ impl ColorWordsDiffOptions {
         Ok(Self {
             conflict: settings.get("diff.color-words.conflict")?,
             context: settings.get("diff.color-words.context")?,
-            line_diff: LineDiffOptions::default(),
+            line_diff: LineDiffOptions::from_settings(settings)?,
             max_inline_alternation,
         })
     }
@@ -815,10 +868,9 @@ fn show_color_words_conflict_hunks(
     options: &ColorWordsDiffOptions,
 ) -> io::Result<DiffLineNumber> {
     let num_lefts = contents.before.as_slice().len();
-    let line_diff = diff_by_line(
-        itertools::chain(contents.before, contents.after),
-        &options.line_diff.compare_mode,
-    );
+    let line_diff = options
+        .line_diff
+        .diff_by_line(itertools::chain(contents.before, contents.after));
     // Matching entries shouldn't appear consecutively in diff of two inputs.
     // However, if the inputs have conflicts, there may be a hunk that can be
     // resolved, resulting [matching, resolved, matching] sequence.
@@ -949,7 +1001,7 @@ fn show_color_words_resolved_hunks(
     labels: Diff<&str>,
     options: &ColorWordsDiffOptions,
 ) -> io::Result<DiffLineNumber> {
-    let line_diff = diff_by_line(contents.into_array(), &options.line_diff.compare_mode);
+    let line_diff = options.line_diff.diff_by_line(contents.into_array());
     // Matching entries shouldn't appear consecutively in diff of two inputs.
     let mut context: Option<Diff<&BStr>> = None;
     let mut emitted = false;
@@ -1304,10 +1356,12 @@ fn diff_content_with<T>(
     match value {
         MaterializedTreeValue::Absent => Ok(FileContent {
             is_binary: false,
+            truncated_size: None,
             contents: map_resolved(BString::default()),
         }),
         MaterializedTreeValue::AccessDenied(err) => Ok(FileContent {
             is_binary: false,
+            truncated_size: None,
             contents: map_resolved(format!("Access denied: {err}").into()),
         }),
         MaterializedTreeValue::File(mut file) => {
@@ -1316,19 +1370,23 @@ fn diff_content_with<T>(
         MaterializedTreeValue::Symlink { id: _, target } => Ok(FileContent {
             // Unix file paths can't contain null bytes.
             is_binary: false,
+            truncated_size: None,
             contents: map_resolved(target.into()),
         }),
         MaterializedTreeValue::GitSubmodule(id) => Ok(FileContent {
             is_binary: false,
+            truncated_size: None,
             contents: map_resolved(format!("Git submodule checked out at {id}").into()),
         }),
         // TODO: are we sure this is never binary?
         MaterializedTreeValue::FileConflict(file) => Ok(FileContent {
             is_binary: false,
+            truncated_size: None,
             contents: map_conflict(file.contents, file.labels),
         }),
         MaterializedTreeValue::OtherConflict { id, labels } => Ok(FileContent {
             is_binary: false,
+            truncated_size: None,
             contents: map_resolved(id.describe(&labels).into()),
         }),
         MaterializedTreeValue::Tree(id) => {
@@ -1627,7 +1685,7 @@ impl UnifiedDiffOptions {
     pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
         Ok(Self {
             context: settings.get("diff.git.context")?,
-            line_diff: LineDiffOptions::default(),
+            line_diff: LineDiffOptions::from_settings(settings)?,
         })
     }
 
@@ -1639,11 +1697,9 @@ impl UnifiedDiffOptions {
     }
 }
 
-fn show_unified_diff_hunks(
-    formatter: &mut dyn Formatter,
-    contents: Diff<&BStr>,
-    options: &UnifiedDiffOptions,
-) -> io::Result<()> {
+/// Returns the line number of the `range` to be printed in a unified diff hunk
+/// header.
+pub(crate) fn hunk_line_number(range: Range<usize>) -> usize {
     // "If the chunk size is 0, the first number is one lower than one would
     // expect." - https://www.artima.com/weblogs/viewpost.jsp?thread=164293
     //
@@ -1651,21 +1707,34 @@ fn show_unified_diff_hunks(
     // shall be the number of the preceding line, or 0 if the range is at the
     // start of the file."
     // - https://pubs.opengroup.org/onlinepubs/9799919799/utilities/diff.html
-    fn to_line_number(range: Range<usize>) -> usize {
-        if range.is_empty() {
-            range.start
-        } else {
-            range.start + 1
-        }
+    if range.is_empty() {
+        range.start
+    } else {
+        range.start + 1
     }
+}
 
-    for hunk in unified_diff_hunks(contents, options.context, options.line_diff.compare_mode) {
+fn show_unified_diff_hunks(
+    formatter: &mut dyn Formatter,
+    contents: Diff<&BStr>,
+    options: &UnifiedDiffOptions,
+) -> io::Result<()> {
+    let hunks = unified_diff_hunks(
+        contents,
+        options.context,
+        options.line_diff.compare_mode,
+        options.line_diff.algorithm,
+    );
+    for hunk in hunks {
+        if options.line_diff.ignore_blank_lines && hunk.has_only_blank_line_changes() {
+            continue;
+        }
         writeln!(
             formatter.labeled("hunk_header"),
             "@@ -{},{} +{},{} @@",
-            to_line_number(hunk.left_line_range.clone()),
+            hunk_line_number(hunk.left_line_range.clone()),
             hunk.left_line_range.len(),
-            to_line_number(hunk.right_line_range.clone()),
+            hunk_line_number(hunk.right_line_range.clone()),
             hunk.right_line_range.len()
         )?;
         for (line_type, tokens) in &hunk.lines {
@@ -1763,7 +1832,13 @@ pub async fn show_git_diff(
             }
         }
 
-        if left_part.content.contents == right_part.content.contents {
+        // Binary files may be truncated, so compare them by hash.
+        let unchanged = if left_part.content.is_binary || right_part.content.is_binary {
+            left_part.hash == right_part.hash
+        } else {
+            left_part.content.contents == right_part.content.contents
+        };
+        if unchanged {
             continue; // no content hunks
         }
 
@@ -1876,6 +1951,12 @@ pub struct DiffStatOptions {
 }
 
 impl DiffStatOptions {
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
+        Ok(Self {
+            line_diff: LineDiffOptions::from_settings(settings)?,
+        })
+    }
+
     fn merge_args(&mut self, args: &DiffFormatArgs) {
         self.line_diff.merge_args(args);
     }
@@ -1996,10 +2077,9 @@ fn get_diff_stat_entry(
     let added_removed = if contents.before.is_binary || contents.after.is_binary {
         None
     } else {
-        let diff = diff_by_line(
-            contents.map(|content| &content.contents).into_array(),
-            &options.line_diff.compare_mode,
-        );
+        let diff = options
+            .line_diff
+            .diff_by_line(contents.map(|content| &content.contents).into_array());
         let mut added = 0;
         let mut removed = 0;
         for hunk in diff.hunks() {
@@ -2018,8 +2098,7 @@ fn get_diff_stat_entry(
     DiffStatEntry {
         path,
         added_removed,
-        bytes_delta: contents.after.contents.len() as isize
-            - contents.before.contents.len() as isize,
+        bytes_delta: contents.after.size() as isize - contents.before.size() as isize,
         status,
     }
 }
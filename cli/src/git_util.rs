@@ -20,6 +20,7 @@ use std::io::Write as _;
 use std::iter;
 use std::mem;
 use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
 
@@ -28,6 +29,8 @@ use crossterm::terminal::Clear;
 use crossterm::terminal::ClearType;
 use indoc::writedoc;
 use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
 use jj_lib::fmt_util::binary_prefix;
 use jj_lib::git;
 use jj_lib::git::FailedRefExportReason;
@@ -37,22 +40,40 @@ use jj_lib::git::GitImportStats;
 use jj_lib::git::GitPushStats;
 use jj_lib::git::GitRefKind;
 use jj_lib::git::GitSettings;
+use jj_lib::git::GitSubprocessOptions;
+use jj_lib::git_notes;
+use jj_lib::git_notes::GitNotes;
+use jj_lib::git_notes::GitNotesError;
+use jj_lib::git_submodule;
+use jj_lib::git_submodule::SubmoduleState;
 use jj_lib::op_store::RefTarget;
 use jj_lib::op_store::RemoteRef;
+use jj_lib::progress::ProgressScope;
+use jj_lib::progress::ProgressTask;
 use jj_lib::ref_name::RemoteRefSymbol;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPathUiConverter;
 use jj_lib::settings::RemoteSettingsMap;
+use jj_lib::settings::UserSettings;
+use jj_lib::store::Store;
 use jj_lib::workspace::Workspace;
+use once_cell::unsync::OnceCell;
+use pollster::FutureExt as _;
 use unicode_width::UnicodeWidthStr as _;
 
 use crate::cleanup_guard::CleanupGuard;
+use crate::cli_util::short_commit_hash;
 use crate::command_error::CommandError;
 use crate::command_error::cli_error;
+use crate::command_error::print_error_sources;
 use crate::command_error::user_error;
 use crate::formatter::Formatter;
 use crate::formatter::FormatterExt as _;
+use crate::progress::draw_progress;
+use crate::progress::progress_reporter;
 use crate::revset_util::parse_remote_auto_track_bookmarks_map;
+use crate::ui::ProgressFormat;
 use crate::ui::ProgressOutput;
 use crate::ui::Ui;
 
@@ -120,6 +141,32 @@ pub fn get_remote_web_url(repo: &ReadonlyRepo, remote_name: &str) -> Option<Stri
     git_remote_url_to_web(url)
 }
 
+/// Returns a function which looks up the note attached to a commit in the
+/// notes ref named `name`.
+///
+/// The notes are loaded when the function is first called. Commits without a
+/// note, and commits in repositories not backed by Git, have an empty note.
+pub fn git_note_getter(
+    store: &Arc<Store>,
+    name: &str,
+) -> impl Fn(&CommitId) -> Result<String, GitNotesError> + use<> {
+    let store = store.clone();
+    let ref_name = git_notes::expand_notes_ref(name);
+    let notes = OnceCell::new();
+    move |commit_id| {
+        let notes = notes.get_or_try_init(|| match GitNotes::load(&store, &ref_name) {
+            Ok(notes) => Ok(Some(notes)),
+            Err(GitNotesError::UnexpectedBackend(_)) => Ok(None),
+            Err(err) => Err(err),
+        })?;
+        let note = match notes {
+            Some(notes) => notes.get(commit_id)?.unwrap_or_default(),
+            None => vec![],
+        };
+        Ok(String::from_utf8_lossy(&note).into_owned())
+    }
+}
+
 // Based on Git's implementation: https://github.com/git/git/blob/43072b4ca132437f21975ac6acc6b72dc22fd398/sideband.c#L178
 pub struct GitSidebandProgressMessageWriter {
     display_prefix: &'static [u8],
@@ -141,14 +188,11 @@ impl GitSidebandProgressMessageWriter {
     pub fn write(&mut self, ui: &Ui, progress_message: &[u8]) -> std::io::Result<()> {
         let mut index = 0;
         // Append a suffix to each nonempty line to clear the end of the screen line.
-        loop {
-            let Some(i) = progress_message[index..]
-                .iter()
-                .position(|&c| c == b'\r' || c == b'\n')
-                .map(|i| index + i)
-            else {
-                break;
-            };
+        while let Some(i) = progress_message[index..]
+            .iter()
+            .position(|&c| c == b'\r' || c == b'\n')
+            .map(|i| index + i)
+        {
             let line_length = i - index;
 
             // For messages sent across the packet boundary, there would be a nonempty
@@ -203,16 +247,34 @@ impl GitSidebandProgressMessageWriter {
     }
 }
 
-pub fn with_remote_git_callbacks<T>(ui: &Ui, f: impl FnOnce(git::RemoteCallbacks<'_>) -> T) -> T {
+pub fn with_remote_git_callbacks<T>(
+    ui: &Ui,
+    task: ProgressTask,
+    f: impl FnOnce(git::RemoteCallbacks<'_>) -> T,
+) -> T {
     let mut callbacks = git::RemoteCallbacks::default();
 
+    // Remote transfers have their own progress bar, so the reporter is only
+    // used for machine-readable events.
+    let reporter = match ui.progress_format() {
+        Some(ProgressFormat::Json) => progress_reporter(ui),
+        _ => None,
+    };
+    let progress_scope = ProgressScope::start(reporter.as_ref().map(|r| r as _), task);
     let mut progress_callback;
+    let mut json_progress_callback;
     if let Some(mut output) = ui.progress_output() {
         let mut progress = Progress::new(Instant::now());
         progress_callback = move |x: &git::Progress| {
             progress.update(Instant::now(), x, &mut output).ok();
         };
         callbacks.progress = Some(&mut progress_callback);
+    } else if reporter.is_some() {
+        json_progress_callback = |x: &git::Progress| {
+            let percent = (100.0 * x.overall).round() as u64;
+            progress_scope.advance(percent, Some(100), None);
+        };
+        callbacks.progress = Some(&mut json_progress_callback);
     }
 
     let mut sideband_progress_writer = GitSidebandProgressMessageWriter::new(ui);
@@ -226,6 +288,60 @@ pub fn with_remote_git_callbacks<T>(ui: &Ui, f: impl FnOnce(git::RemoteCallbacks
     result
 }
 
+/// Checks out the submodules of `commit` which have already been cloned at the
+/// commits recorded in it.
+///
+/// This is done after the working copy has been updated, so failures are
+/// reported as warnings.
+pub fn update_cloned_submodules(
+    ui: &Ui,
+    settings: &UserSettings,
+    workspace_root: &Path,
+    path_converter: &RepoPathUiConverter,
+    commit: &Commit,
+) -> Result<(), CommandError> {
+    let submodules = match git_submodule::get_submodules(&commit.tree()).block_on() {
+        Ok(submodules) => submodules,
+        Err(err) => {
+            writeln!(ui.warning_default(), "Failed to read submodules: {err}")?;
+            print_error_sources(ui, error::Error::source(&err))?;
+            return Ok(());
+        }
+    };
+    if submodules.is_empty() {
+        return Ok(());
+    }
+    let options = GitSubprocessOptions::from_settings(settings)?;
+    for submodule in &submodules {
+        let result = git_submodule::submodule_state(workspace_root, submodule).and_then(|state| {
+            if state == SubmoduleState::Uninitialized {
+                return Ok(false);
+            }
+            git_submodule::update_submodule(workspace_root, submodule, None, options.clone())
+        });
+        match result {
+            Ok(true) => {
+                writeln!(
+                    ui.status(),
+                    "Checked out submodule {} at {}",
+                    path_converter.format_file_path(&submodule.path),
+                    short_commit_hash(&submodule.commit_id)
+                )?;
+            }
+            Ok(false) => {}
+            Err(err) => {
+                writeln!(
+                    ui.warning_default(),
+                    "Failed to update submodule {}: {err}",
+                    path_converter.format_file_path(&submodule.path)
+                )?;
+                print_error_sources(ui, error::Error::source(&err))?;
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn load_git_import_options(
     ui: &Ui,
     git_settings: &GitSettings,
@@ -382,23 +498,6 @@ impl Progress {
     }
 }
 
-fn draw_progress(progress: f32, buffer: &mut String, width: usize) {
-    const CHARS: [char; 9] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
-    const RESOLUTION: usize = CHARS.len() - 1;
-    let ticks = (width as f32 * progress.clamp(0.0, 1.0) * RESOLUTION as f32).round() as usize;
-    let whole = ticks / RESOLUTION;
-    for _ in 0..whole {
-        buffer.push(CHARS[CHARS.len() - 1]);
-    }
-    if whole < width {
-        let fraction = ticks % RESOLUTION;
-        buffer.push(CHARS[fraction]);
-    }
-    for _ in (whole + 1)..width {
-        buffer.push(CHARS[0]);
-    }
-}
-
 struct RateEstimate {
     state: Option<RateEstimateState>,
 }
@@ -716,23 +815,6 @@ mod tests {
         assert_eq!(to_web("/path/to/repo"), None);
     }
 
-    #[test]
-    fn test_bar() {
-        let mut buf = String::new();
-        draw_progress(0.0, &mut buf, 10);
-        assert_eq!(buf, "          ");
-        buf.clear();
-        draw_progress(1.0, &mut buf, 10);
-        assert_eq!(buf, "██████████");
-        buf.clear();
-        draw_progress(0.5, &mut buf, 10);
-        assert_eq!(buf, "█████     ");
-        buf.clear();
-        draw_progress(0.54, &mut buf, 10);
-        assert_eq!(buf, "█████▍    ");
-        buf.clear();
-    }
-
     #[test]
     fn test_update() {
         let start = Instant::now();
@@ -32,7 +32,12 @@ pub mod git_util;
 /// A stub module that provides a no-op implementation of some of the functions
 /// in the `git` module.
 pub mod git_util {
+    use std::convert::Infallible;
+    use std::sync::Arc;
+
+    use jj_lib::backend::CommitId;
     use jj_lib::repo::ReadonlyRepo;
+    use jj_lib::store::Store;
     use jj_lib::workspace::Workspace;
 
     pub fn is_colocated_git_workspace(_workspace: &Workspace, _repo: &ReadonlyRepo) -> bool {
@@ -42,11 +47,19 @@ pub mod git_util {
     pub fn get_remote_web_url(_repo: &ReadonlyRepo, _remote_name: &str) -> Option<String> {
         None
     }
+
+    pub fn git_note_getter(
+        _store: &Arc<Store>,
+        _name: &str,
+    ) -> impl Fn(&CommitId) -> Result<String, Infallible> + use<> {
+        |_| Ok(String::new())
+    }
 }
 pub mod graphlog;
 pub mod merge_tools;
 pub mod movement_util;
 pub mod operation_templater;
+mod profile;
 mod progress;
 pub mod revset_util;
 pub mod template_builder;
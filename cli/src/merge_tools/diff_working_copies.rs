@@ -10,8 +10,12 @@ use futures::StreamExt as _;
 use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::fsmonitor::FsmonitorSettings;
 use jj_lib::gitignore::GitIgnoreFile;
+use jj_lib::local_working_copy::CaseCollisionSetting;
+use jj_lib::local_working_copy::ChangeDetection;
 use jj_lib::local_working_copy::EolConversionMode;
 use jj_lib::local_working_copy::ExecChangeSetting;
+use jj_lib::local_working_copy::PathNormalizationSetting;
+use jj_lib::local_working_copy::TouchedFileCheck;
 use jj_lib::local_working_copy::TreeState;
 use jj_lib::local_working_copy::TreeStateError;
 use jj_lib::local_working_copy::TreeStateSettings;
@@ -22,7 +26,9 @@ use jj_lib::merge::Diff;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::merged_tree::TreeDiffEntry;
 use jj_lib::working_copy::CheckoutError;
+use jj_lib::working_copy::CheckoutOptions;
 use jj_lib::working_copy::SnapshotOptions;
+use jj_lib::working_copy::SparsePatterns;
 use pollster::FutureExt as _;
 use tempfile::TempDir;
 use thiserror::Error;
@@ -155,12 +161,21 @@ pub(crate) fn check_out_trees(
         let tree_state_settings = TreeStateSettings {
             conflict_marker_style,
             eol_conversion_mode: EolConversionMode::None,
+            eol_conversion_rules: vec![],
             exec_change_setting: ExecChangeSetting::Auto,
+            case_collision_setting: CaseCollisionSetting::Auto,
+            path_normalization_setting: PathNormalizationSetting::Auto,
             fsmonitor_settings: FsmonitorSettings::None,
+            change_detection: ChangeDetection::Mtime,
+            touched_file_check: TouchedFileCheck::None,
+            untracked_cache: false,
+            use_gitattributes: false,
+            filter_drivers: HashMap::new(),
+            git_lfs: false,
         };
         let mut state = TreeState::init(store.clone(), wc_path, state_dir, &tree_state_settings)?;
-        state.set_sparse_patterns(changed_files.clone())?;
-        state.check_out(tree)?;
+        state.set_sparse_patterns(SparsePatterns::new(changed_files.clone()))?;
+        state.check_out(tree, &CheckoutOptions::default())?;
         Ok(state)
     };
 
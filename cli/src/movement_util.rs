@@ -38,6 +38,7 @@ pub(crate) struct MovementArgs {
     pub edit: bool,
     pub no_edit: bool,
     pub conflict: bool,
+    pub no_interactive: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -45,6 +46,7 @@ struct MovementArgsInternal {
     offset: u64,
     should_edit: bool,
     conflict: bool,
+    no_interactive: bool,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -117,6 +119,26 @@ impl Direction {
         cmd_err
     }
 
+    fn ambiguous_target_error(
+        &self,
+        workspace_command: &WorkspaceCommandHelper,
+        commits: &[Commit],
+    ) -> CommandError {
+        let template = workspace_command.commit_summary_template();
+        let mut cmd_err = user_error(format!("Ambiguous {} commit", self.cmd()));
+        for commit in commits {
+            cmd_err.add_formatted_hint_with(|formatter| {
+                write!(formatter, "Candidate: ")?;
+                template.format(commit, formatter)
+            });
+        }
+        cmd_err.add_hint(
+            "Pass one of them to `jj new` or `jj edit`, or run without `--no-interactive` to \
+             choose one.",
+        );
+        cmd_err
+    }
+
     fn build_target_revset(
         &self,
         working_revset: &Arc<ResolvedRevsetExpression>,
@@ -200,6 +222,9 @@ fn get_target_commit(
                 .try_collect()?;
             return Err(direction.target_not_found_error(workspace_command, args, &start_commits));
         }
+        commits if args.no_interactive => {
+            return Err(direction.ambiguous_target_error(workspace_command, commits));
+        }
         commits => choose_commit(ui, workspace_command, direction, commits)?,
     };
 
@@ -212,13 +237,29 @@ fn choose_commit<'a>(
     direction: Direction,
     commits: &'a [Commit],
 ) -> Result<&'a Commit, CommandError> {
+    let template = workspace_command.commit_summary_template();
+    if ui.can_prompt_select() {
+        let items: Vec<String> = commits
+            .iter()
+            .map(|commit| -> Result<_, CommandError> {
+                let mut output = Vec::new();
+                template.format(commit, ui.new_formatter(&mut output).as_mut())?;
+                Ok(String::from_utf8_lossy(&output).into_owned())
+            })
+            .try_collect()?;
+        let prompt = format!("ambiguous {} commit, choose one to target", direction.cmd());
+        return match ui.prompt_select(&prompt, &items)? {
+            Some(index) => Ok(&commits[index]),
+            None => Err(user_error("ambiguous target commit")),
+        };
+    }
+
     writeln!(
         ui.stderr(),
         "ambiguous {} commit, choose one to target:",
         direction.cmd()
     )?;
     let mut formatter = ui.stderr_formatter();
-    let template = workspace_command.commit_summary_template();
     let mut choices: Vec<String> = Default::default();
     for (i, commit) in commits.iter().enumerate() {
         write!(formatter, "{}: ", i + 1)?;
@@ -257,6 +298,7 @@ pub(crate) fn move_to_commit(
         should_edit: args.edit || (!args.no_edit && config_edit_flag),
         offset: args.offset,
         conflict: args.conflict,
+        no_interactive: args.no_interactive,
     };
 
     let target = get_target_commit(ui, &workspace_command, direction, current_wc_id, &args)?;
@@ -0,0 +1,310 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Collects timings of tracing spans for `--profile`.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use itertools::Itertools as _;
+use tracing::Subscriber;
+use tracing::span;
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Number of spans printed in the summary.
+const SUMMARY_LEN: usize = 20;
+
+/// Timings of a span, stored in the span's extensions while it's open.
+struct SpanTiming {
+    start: Instant,
+    entered_at: Option<Instant>,
+    busy: Duration,
+    children_busy: Duration,
+    thread_id: u64,
+}
+
+/// Timings of a closed span.
+#[derive(Clone, Debug)]
+struct SpanRecord {
+    /// Names of the span and its ancestors, outermost first.
+    stack: Vec<&'static str>,
+    target: &'static str,
+    start: Duration,
+    duration: Duration,
+    busy: Duration,
+    self_busy: Duration,
+    thread_id: u64,
+}
+
+#[derive(Debug, Default)]
+struct ProfileState {
+    enabled: AtomicBool,
+    start: Mutex<Option<Instant>>,
+    records: Mutex<Vec<SpanRecord>>,
+}
+
+/// Tracing layer that records the time spent in each span once profiling is
+/// enabled.
+#[derive(Clone, Debug, Default)]
+pub struct ProfileLayer {
+    state: Arc<ProfileState>,
+}
+
+/// Handle to start profiling and write the results.
+#[derive(Clone, Debug)]
+pub struct ProfileHandle {
+    state: Arc<ProfileState>,
+}
+
+impl ProfileLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn handle(&self) -> ProfileHandle {
+        ProfileHandle {
+            state: self.state.clone(),
+        }
+    }
+}
+
+fn current_thread_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    thread_local! {
+        static THREAD_ID: Cell<u64> = const { Cell::new(0) };
+    }
+    THREAD_ID.with(|id| {
+        if id.get() == 0 {
+            id.set(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+        }
+        id.get()
+    })
+}
+
+impl<S> Layer<S> for ProfileLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        span.extensions_mut().insert(SpanTiming {
+            start: Instant::now(),
+            entered_at: None,
+            busy: Duration::ZERO,
+            children_busy: Duration::ZERO,
+            thread_id: current_thread_id(),
+        });
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>()
+        {
+            timing.entered_at = Some(Instant::now());
+        }
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>()
+            && let Some(entered_at) = timing.entered_at.take()
+        {
+            timing.busy += entered_at.elapsed();
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timing) = span.extensions_mut().remove::<SpanTiming>() else {
+            return;
+        };
+        if let Some(parent) = span.parent()
+            && let Some(parent_timing) = parent.extensions_mut().get_mut::<SpanTiming>()
+        {
+            parent_timing.children_busy += timing.busy;
+        }
+        let Some(profile_start) = *self.state.start.lock().unwrap() else {
+            return;
+        };
+        let stack = span
+            .scope()
+            .from_root()
+            .map(|span| span.metadata().name())
+            .collect();
+        let record = SpanRecord {
+            stack,
+            target: span.metadata().target(),
+            start: timing.start.saturating_duration_since(profile_start),
+            duration: timing.start.elapsed(),
+            busy: timing.busy,
+            self_busy: timing.busy.saturating_sub(timing.children_busy),
+            thread_id: timing.thread_id,
+        };
+        self.state.records.lock().unwrap().push(record);
+    }
+}
+
+impl ProfileHandle {
+    /// Whether spans should be recorded. Spans aren't created at all unless
+    /// profiling or another layer needs them.
+    pub fn is_enabled(&self) -> bool {
+        self.state.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Starts recording spans.
+    pub fn start(&self) {
+        *self.state.start.lock().unwrap() = Some(Instant::now());
+        self.state.enabled.store(true, Ordering::Relaxed);
+    }
+
+    /// Stops recording spans, writes them to `path`, and prints the spans
+    /// that took the most time to `summary`.
+    ///
+    /// The file is in the Chrome trace event format if its name ends with
+    /// `.json`, and in the folded stack format used by flamegraph tools
+    /// otherwise.
+    pub fn finish(&self, path: &Path, summary: &mut dyn Write) -> io::Result<()> {
+        self.state.enabled.store(false, Ordering::Relaxed);
+        let records = std::mem::take(&mut *self.state.records.lock().unwrap());
+        let mut file = io::BufWriter::new(std::fs::File::create(path)?);
+        if path.extension().is_some_and(|ext| ext == "json") {
+            write_chrome_trace(&mut file, &records)?;
+        } else {
+            write_folded_stacks(&mut file, &records)?;
+        }
+        file.flush()?;
+        write_summary(summary, &records)
+    }
+}
+
+/// Writes self times aggregated by stack, as in `a;b;c 123` where the number
+/// is in microseconds.
+fn write_folded_stacks(output: &mut dyn Write, records: &[SpanRecord]) -> io::Result<()> {
+    let mut self_times: HashMap<String, u128> = HashMap::new();
+    for record in records {
+        *self_times.entry(record.stack.join(";")).or_default() += record.self_busy.as_micros();
+    }
+    for (stack, micros) in self_times.iter().sorted() {
+        writeln!(output, "{stack} {micros}")?;
+    }
+    Ok(())
+}
+
+/// Writes spans as "complete" events of the Chrome trace event format, which
+/// can be opened in Perfetto or `chrome://tracing`.
+fn write_chrome_trace(output: &mut dyn Write, records: &[SpanRecord]) -> io::Result<()> {
+    let events = records
+        .iter()
+        .sorted_by_key(|record| record.start)
+        .map(|record| {
+            serde_json::json!({
+                "name": record.stack.last().copied().unwrap_or_default(),
+                "cat": record.target,
+                "ph": "X",
+                "ts": record.start.as_micros() as u64,
+                "dur": record.duration.as_micros() as u64,
+                "pid": 1,
+                "tid": record.thread_id,
+            })
+        })
+        .collect_vec();
+    serde_json::to_writer(&mut *output, &events)?;
+    writeln!(output)
+}
+
+fn write_summary(output: &mut dyn Write, records: &[SpanRecord]) -> io::Result<()> {
+    // Spans of recursive calls are counted once.
+    let mut totals: HashMap<(&str, &str), (Duration, usize)> = HashMap::new();
+    for record in records {
+        let name = record.stack.last().copied().unwrap_or_default();
+        let (total, count) = totals.entry((record.target, name)).or_default();
+        if !record.stack[..record.stack.len() - 1].contains(&name) {
+            *total += record.busy;
+        }
+        *count += 1;
+    }
+    writeln!(output, "{:>10} {:>7}  Span", "Time (ms)", "Calls")?;
+    for ((target, name), (total, count)) in totals
+        .iter()
+        .sorted_by(|(key1, (total1, _)), (key2, (total2, _))| {
+            total2.cmp(total1).then(key1.cmp(key2))
+        })
+        .take(SUMMARY_LEN)
+    {
+        let millis = total.as_secs_f64() * 1000.0;
+        writeln!(output, "{millis:>10.1} {count:>7}  {target}::{name}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(stack: &[&'static str], busy_ms: u64, self_ms: u64) -> SpanRecord {
+        SpanRecord {
+            stack: stack.to_vec(),
+            target: "jj_lib::test",
+            start: Duration::ZERO,
+            duration: Duration::from_millis(busy_ms),
+            busy: Duration::from_millis(busy_ms),
+            self_busy: Duration::from_millis(self_ms),
+            thread_id: 1,
+        }
+    }
+
+    #[test]
+    fn test_write_folded_stacks_and_summary() {
+        let records = [
+            record(&["run", "snapshot"], 3, 3),
+            record(&["run", "evaluate"], 2, 1),
+            record(&["run", "evaluate", "evaluate"], 1, 1),
+            record(&["run", "snapshot"], 4, 4),
+            record(&["run"], 12, 2),
+        ];
+        let mut output = vec![];
+        write_folded_stacks(&mut output, &records).unwrap();
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r"
+        run 2000
+        run;evaluate 1000
+        run;evaluate;evaluate 1000
+        run;snapshot 7000
+        ");
+
+        let mut output = vec![];
+        write_summary(&mut output, &records).unwrap();
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r"
+        Time (ms)   Calls  Span
+             12.0       1  jj_lib::test::run
+              7.0       2  jj_lib::test::snapshot
+              2.0       2  jj_lib::test::evaluate
+        ");
+    }
+}
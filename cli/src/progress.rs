@@ -1,67 +1,205 @@
-use std::path::Path;
+use std::io;
+use std::io::Write as _;
 use std::sync::Mutex;
 use std::time::Duration;
 use std::time::Instant;
 
 use crossterm::terminal::Clear;
 use crossterm::terminal::ClearType;
-use jj_lib::repo_path::RepoPath;
+use jj_lib::progress::Progress;
+use jj_lib::progress::ProgressEvent;
 
 use crate::text_util;
 use crate::ui::OutputGuard;
+use crate::ui::ProgressFormat;
 use crate::ui::ProgressOutput;
 use crate::ui::Ui;
 
 pub const UPDATE_HZ: u32 = 30;
 pub const INITIAL_DELAY: Duration = Duration::from_millis(250);
 
-pub fn snapshot_progress(ui: &Ui) -> Option<impl Fn(&RepoPath) + use<>> {
-    struct State {
+/// Renders progress updates reported by long-running operations to stderr,
+/// either as a progress bar or as line-delimited JSON events.
+pub struct ProgressReporter {
+    state: Mutex<ReporterState>,
+}
+
+enum ReporterState {
+    Text {
         guard: Option<OutputGuard>,
-        output: ProgressOutput<std::io::Stderr>,
+        output: ProgressOutput<io::Stderr>,
         next_display_time: Instant,
-    }
-
-    let output = ui.progress_output()?;
+    },
+    Json {
+        output: io::Stderr,
+        next_display_time: Instant,
+    },
+}
 
-    // Don't clutter the output during fast operations.
-    let next_display_time = Instant::now() + INITIAL_DELAY;
-    let state = Mutex::new(State {
-        guard: None,
-        output,
-        next_display_time,
-    });
+/// Returns the progress reporter for the configured `ui.progress-format`, or
+/// `None` if progress shouldn't be displayed.
+pub fn progress_reporter(ui: &Ui) -> Option<ProgressReporter> {
+    let state = match ui.progress_format()? {
+        ProgressFormat::Text => ReporterState::Text {
+            guard: None,
+            output: ui.progress_output()?,
+            next_display_time: Instant::now(),
+        },
+        ProgressFormat::Json => ReporterState::Json {
+            output: io::stderr(),
+            next_display_time: Instant::now(),
+        },
+    };
+    Some(ProgressReporter {
+        state: Mutex::new(state),
+    })
+}
 
-    Some(move |path: &RepoPath| {
-        let mut state = state.lock().unwrap();
+impl Progress for ProgressReporter {
+    fn report(&self, event: &ProgressEvent<'_>) {
+        let mut state = self.state.lock().unwrap();
         let now = Instant::now();
-        if now < state.next_display_time {
-            // Future work: Display current path after exactly, say, 250ms has elapsed, to
-            // better handle large single files
-            return;
+        match &mut *state {
+            ReporterState::Text {
+                guard,
+                output,
+                next_display_time,
+            } => match event {
+                ProgressEvent::Started { .. } => {
+                    // Don't clutter the output during fast operations.
+                    *next_display_time = now + INITIAL_DELAY;
+                }
+                ProgressEvent::Advanced { .. } => {
+                    if now < *next_display_time {
+                        return;
+                    }
+                    *next_display_time = now + Duration::from_secs(1) / UPDATE_HZ;
+                    if guard.is_none() {
+                        *guard = Some(
+                            output.output_guard(format!("\r{}", Clear(ClearType::CurrentLine))),
+                        );
+                    }
+                    let line_width = output.term_width().map(usize::from).unwrap_or(80);
+                    let line = format_progress_line(event, line_width);
+                    write!(output, "\r{}{line}", Clear(ClearType::CurrentLine)).ok();
+                    output.flush().ok();
+                }
+                ProgressEvent::Finished { .. } => {
+                    // Clears the progress line if any.
+                    *guard = None;
+                }
+            },
+            ReporterState::Json {
+                output,
+                next_display_time,
+            } => {
+                if let ProgressEvent::Advanced { .. } = event {
+                    if now < *next_display_time {
+                        return;
+                    }
+                    *next_display_time = now + Duration::from_secs(1) / UPDATE_HZ;
+                }
+                let line = serde_json::to_string(event).unwrap();
+                writeln!(output, "{line}").ok();
+            }
         }
-        state.next_display_time = now + Duration::from_secs(1) / UPDATE_HZ;
+    }
+}
 
-        if state.guard.is_none() {
-            state.guard = Some(
-                state
-                    .output
-                    .output_guard(format!("\r{}", Clear(ClearType::CurrentLine))),
-            );
+/// Formats a line like `Indexing 12/34 [███▌    ]` or `Snapshotting path`.
+fn format_progress_line(event: &ProgressEvent<'_>, line_width: usize) -> String {
+    let ProgressEvent::Advanced {
+        task,
+        completed,
+        total,
+        item,
+    } = event
+    else {
+        return String::new();
+    };
+    let mut line = task.verb().to_owned();
+    match (total, item) {
+        (Some(total), _) if *total > 0 => {
+            line.push_str(&format!(" {completed}/{total} ["));
+            let bar_width = line_width.saturating_sub(line.len() + 1);
+            draw_progress(*completed as f32 / *total as f32, &mut line, bar_width);
+            line.push(']');
+        }
+        (_, Some(item)) => {
+            let max_item_width = line_width.saturating_sub(line.len() + 1);
+            let (display_item, _) = text_util::elide_start(item, "...", max_item_width);
+            line.push(' ');
+            line.push_str(&display_item);
+        }
+        (_, None) => {
+            line.push_str(&format!(" {completed}"));
         }
+    }
+    line
+}
+
+pub fn draw_progress(progress: f32, buffer: &mut String, width: usize) {
+    const CHARS: [char; 9] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+    const RESOLUTION: usize = CHARS.len() - 1;
+    let ticks = (width as f32 * progress.clamp(0.0, 1.0) * RESOLUTION as f32).round() as usize;
+    let whole = ticks / RESOLUTION;
+    for _ in 0..whole {
+        buffer.push(CHARS[CHARS.len() - 1]);
+    }
+    if whole < width {
+        let fraction = ticks % RESOLUTION;
+        buffer.push(CHARS[fraction]);
+    }
+    for _ in (whole + 1)..width {
+        buffer.push(CHARS[0]);
+    }
+}
 
-        let line_width = state.output.term_width().map(usize::from).unwrap_or(80);
-        let max_path_width = line_width.saturating_sub(13); // Account for "Snapshotting "
-        let fs_path = path.to_fs_path_unchecked(Path::new(""));
-        let (display_path, _) =
-            text_util::elide_start(fs_path.to_str().unwrap(), "...", max_path_width);
+#[cfg(test)]
+mod tests {
+    use jj_lib::progress::ProgressTask;
 
-        write!(
-            state.output,
-            "\r{}Snapshotting {display_path}",
-            Clear(ClearType::CurrentLine),
-        )
-        .ok();
-        state.output.flush().ok();
-    })
+    use super::*;
+
+    #[test]
+    fn test_bar() {
+        let mut buf = String::new();
+        draw_progress(0.0, &mut buf, 10);
+        assert_eq!(buf, "          ");
+        buf.clear();
+        draw_progress(1.0, &mut buf, 10);
+        assert_eq!(buf, "██████████");
+        buf.clear();
+        draw_progress(0.5, &mut buf, 10);
+        assert_eq!(buf, "█████     ");
+        buf.clear();
+        draw_progress(0.54, &mut buf, 10);
+        assert_eq!(buf, "█████▍    ");
+        buf.clear();
+    }
+
+    #[test]
+    fn test_format_progress_line() {
+        let event = ProgressEvent::Advanced {
+            task: ProgressTask::IndexBuild,
+            completed: 5,
+            total: Some(10),
+            item: None,
+        };
+        insta::assert_snapshot!(format_progress_line(&event, 30), @"Indexing 5/10 [███████       ]");
+        let event = ProgressEvent::Advanced {
+            task: ProgressTask::Snapshot,
+            completed: 5,
+            total: None,
+            item: Some("some/long/path/to/file"),
+        };
+        insta::assert_snapshot!(format_progress_line(&event, 30), @"Snapshotting ...g/path/to/file");
+        let event = ProgressEvent::Advanced {
+            task: ProgressTask::Checkout,
+            completed: 5,
+            total: None,
+            item: None,
+        };
+        insta::assert_snapshot!(format_progress_line(&event, 30), @"Checking out 5");
+    }
 }
@@ -15,7 +15,10 @@
 //! Utility for parsing and evaluating user-provided revset expressions.
 
 use std::collections::HashMap;
+use std::collections::hash_map;
+use std::process::Stdio;
 use std::sync::Arc;
+use std::sync::Mutex;
 
 use itertools::Itertools as _;
 use jj_lib::backend::CommitId;
@@ -30,9 +33,11 @@ use jj_lib::ref_name::RemoteName;
 use jj_lib::ref_name::RemoteNameBuf;
 use jj_lib::repo::Repo;
 use jj_lib::revset;
+use jj_lib::revset::BoxedRevsetFunction;
 use jj_lib::revset::ResolvedRevsetExpression;
 use jj_lib::revset::Revset;
 use jj_lib::revset::RevsetAliasesMap;
+use jj_lib::revset::RevsetCommitsExtension;
 use jj_lib::revset::RevsetDiagnostics;
 use jj_lib::revset::RevsetEvaluationError;
 use jj_lib::revset::RevsetExpression;
@@ -45,6 +50,7 @@ use jj_lib::revset::SymbolResolver;
 use jj_lib::revset::SymbolResolverExtension;
 use jj_lib::revset::UserRevsetExpression;
 use jj_lib::settings::RemoteSettingsMap;
+use jj_lib::settings::UserSettings;
 use jj_lib::str_util::StringExpression;
 use jj_lib::str_util::StringMatcher;
 use thiserror::Error;
@@ -55,6 +61,7 @@ use crate::command_error::print_parse_diagnostics;
 use crate::command_error::revset_parse_error_hint;
 use crate::command_error::user_error;
 use crate::command_error::user_error_with_message;
+use crate::config::CommandNameAndArgs;
 use crate::formatter::Formatter;
 use crate::templater::TemplateRenderer;
 use crate::ui::Ui;
@@ -153,6 +160,7 @@ fn warn_user_redefined_builtin(
         ConfigSource::Default => (),
         ConfigSource::EnvBase
         | ConfigSource::User
+        | ConfigSource::RepoManaged
         | ConfigSource::Repo
         | ConfigSource::Workspace
         | ConfigSource::EnvOverrides
@@ -212,6 +220,122 @@ pub fn load_revset_aliases(
     Ok(aliases_map)
 }
 
+/// Commits printed by the command of a function defined in the
+/// `revset-functions` config table.
+#[derive(Debug)]
+struct CommandRevsetCommits {
+    name: String,
+    command: CommandNameAndArgs,
+    args: Vec<String>,
+    /// Output of the command by arguments, shared by all calls of the
+    /// function.
+    cache: Arc<Mutex<HashMap<Vec<String>, Vec<CommitId>>>>,
+}
+
+impl CommandRevsetCommits {
+    fn run(&self) -> Result<Vec<CommitId>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut cmd = self.command.to_command();
+        cmd.args(&self.args);
+        tracing::info!(?cmd, "running revset function command");
+        let output = cmd
+            .stdin(Stdio::null())
+            .stderr(Stdio::inherit())
+            .output()
+            .map_err(|err| {
+                format!(
+                    "Failed to run command of revset function `{}`: {err}",
+                    self.name
+                )
+            })?;
+        if !output.status.success() {
+            return Err(format!(
+                "Command of revset function `{}` failed with {}",
+                self.name, output.status
+            )
+            .into());
+        }
+        let stdout = String::from_utf8(output.stdout)?;
+        let ids = stdout
+            .split_whitespace()
+            .map(|hex| {
+                CommitId::try_from_hex(hex).ok_or_else(|| {
+                    format!(
+                        "Command of revset function `{}` printed invalid commit ID `{hex}`",
+                        self.name
+                    )
+                })
+            })
+            .try_collect()?;
+        Ok(ids)
+    }
+}
+
+impl RevsetCommitsExtension for CommandRevsetCommits {
+    fn resolve(&self, repo: &dyn Repo) -> Result<Vec<CommitId>, RevsetResolutionError> {
+        let mut cache = self.cache.lock().unwrap();
+        let ids = match cache.entry(self.args.clone()) {
+            hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            hash_map::Entry::Vacant(entry) => {
+                entry.insert(self.run().map_err(RevsetResolutionError::Other)?)
+            }
+        };
+        // The data source may know about commits which haven't been fetched
+        let mut commit_ids = vec![];
+        for id in ids.iter() {
+            if repo
+                .index()
+                .has_id(id)
+                .map_err(|err| RevsetResolutionError::Other(err.into()))?
+            {
+                commit_ids.push(id.clone());
+            }
+        }
+        Ok(commit_ids)
+    }
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RevsetFunctionConfig {
+    command: CommandNameAndArgs,
+}
+
+/// Registers the functions defined in the `revset-functions` config table.
+///
+/// Each function runs its command with the function's arguments appended, and
+/// resolves to the commits whose IDs the command prints. The output is cached
+/// per arguments for as long as the `extensions` are alive.
+pub fn add_config_revset_functions(
+    extensions: &mut RevsetExtensions,
+    settings: &UserSettings,
+) -> Result<(), CommandError> {
+    for name in settings.table_keys("revset-functions").sorted() {
+        let config: RevsetFunctionConfig = settings.get(["revset-functions", name])?;
+        let name = name.to_owned();
+        let cache = Arc::new(Mutex::new(HashMap::new()));
+        let func_name = name.clone();
+        let func: BoxedRevsetFunction = Box::new(move |_diagnostics, function, _context| {
+            let ([], arg_nodes) = function.expect_some_arguments()?;
+            let args = arg_nodes
+                .iter()
+                .map(|node| revset::expect_literal("string", node))
+                .try_collect()?;
+            Ok(RevsetExpression::extension_commits(Arc::new(
+                CommandRevsetCommits {
+                    name: func_name.clone(),
+                    command: config.command.clone(),
+                    args,
+                    cache: cache.clone(),
+                },
+            )))
+        });
+        extensions.add_boxed_function(name, func).map_err(|err| {
+            config_error_with_message(format!("Invalid `revset-functions.{}`", err.name), err)
+        })?;
+    }
+    Ok(())
+}
+
 /// Wraps the given `IdPrefixContext` in `SymbolResolver` to be passed in to
 /// `evaluate()`.
 pub fn default_symbol_resolver<'a>(
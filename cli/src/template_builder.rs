@@ -20,6 +20,7 @@ use std::iter;
 use itertools::Itertools as _;
 use jj_lib::backend::Signature;
 use jj_lib::backend::Timestamp;
+use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::config::ConfigNamePathBuf;
 use jj_lib::config::ConfigValue;
 use jj_lib::content_hash::blake2b_hash;
@@ -686,6 +687,11 @@ impl<'a, P: CoreTemplatePropertyVar<'a>> Expression<P> {
         self.property.type_name()
     }
 
+    /// Strips labels and returns the underlying property.
+    pub fn into_property(self) -> P {
+        self.property
+    }
+
     pub fn try_into_boolean(self) -> Option<BoxedTemplateProperty<'a, bool>> {
         self.property.try_into_boolean()
     }
@@ -1139,9 +1145,56 @@ fn builtin_string_methods<'a, L: TemplateLanguage<'a> + ?Sized>()
             }
         },
     );
+    map.insert(
+        "replace_re",
+        |language, diagnostics, build_ctx, self_property, function| {
+            let [pattern_node, replacement_node] = function.expect_exact_arguments()?;
+            let regex = expect_regex_literal(diagnostics, pattern_node)?;
+            let replacement_property =
+                expect_stringify_expression(language, diagnostics, build_ctx, replacement_node)?;
+            let out_property =
+                (self_property, replacement_property).map(move |(haystack, replacement)| {
+                    regex
+                        .replace_all(&haystack, replacement.as_str())
+                        .into_owned()
+                });
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "match_re",
+        |_language, diagnostics, _build_ctx, self_property, function| {
+            let [pattern_node] = function.expect_exact_arguments()?;
+            let regex = expect_regex_literal(diagnostics, pattern_node)?;
+            let out_property = self_property.map(move |haystack| {
+                let Some(captures) = regex.captures(&haystack) else {
+                    return vec![];
+                };
+                // Groups which didn't participate in the match are empty
+                // because we don't have optional strings.
+                captures
+                    .iter()
+                    .map(|m| m.map_or("", |m| m.as_str()).to_owned())
+                    .collect_vec()
+            });
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
     map
 }
 
+fn expect_regex_literal(
+    diagnostics: &mut TemplateDiagnostics,
+    node: &ExpressionNode,
+) -> TemplateParseResult<regex::Regex> {
+    template_parser::catch_aliases(diagnostics, node, |_diagnostics, node| {
+        let pattern = template_parser::expect_string_literal(node)?;
+        regex::Regex::new(pattern).map_err(|err| {
+            TemplateParseError::expression("Invalid regular expression", node.span).with_source(err)
+        })
+    })
+}
+
 /// Clamps and aligns the given index `i` to char boundary.
 ///
 /// Negative index counts from the end. If the index isn't at a char boundary,
@@ -1819,6 +1872,48 @@ fn builtin_functions<'a, L: TemplateLanguage<'a> + ?Sized>() -> TemplateBuildFun
             Ok(L::Property::wrap_template(template))
         },
     );
+    map.insert("format", |language, diagnostics, build_ctx, function| {
+        let [spec_node, content_node] = function.expect_exact_arguments()?;
+        let spec = template_parser::catch_aliases(diagnostics, spec_node, |_diagnostics, node| {
+            let spec = template_parser::expect_string_literal(node)?;
+            FormatSpec::parse(spec)
+                .ok_or_else(|| TemplateParseError::expression("Invalid format spec", node.span))
+        })?;
+        let mut template =
+            expect_template_expression(language, diagnostics, build_ctx, content_node)?;
+        if let Some(precision) = spec.precision {
+            template = new_truncate_template(
+                template,
+                None,
+                Literal(precision).into_dyn(),
+                text_util::write_truncated_end,
+            );
+        }
+        if let Some(width) = spec.width {
+            let fill_char: Box<dyn Template> = Box::new(Literal(spec.fill.to_string()));
+            template = match spec.align {
+                FormatAlign::Start => new_pad_template(
+                    template,
+                    Some(fill_char),
+                    Literal(width).into_dyn(),
+                    text_util::write_padded_end,
+                ),
+                FormatAlign::End => new_pad_template(
+                    template,
+                    Some(fill_char),
+                    Literal(width).into_dyn(),
+                    text_util::write_padded_start,
+                ),
+                FormatAlign::Center => new_pad_template(
+                    template,
+                    Some(fill_char),
+                    Literal(width).into_dyn(),
+                    text_util::write_padded_centered,
+                ),
+            };
+        }
+        Ok(L::Property::wrap_template(template))
+    });
     map.insert("hash", |language, diagnostics, build_ctx, function| {
         let [content_node] = function.expect_exact_arguments()?;
         let content = expect_stringify_expression(language, diagnostics, build_ctx, content_node)?;
@@ -1952,9 +2047,109 @@ fn builtin_functions<'a, L: TemplateLanguage<'a> + ?Sized>() -> TemplateBuildFun
         // .decorated("", "") to trim leading/trailing whitespace
         Ok(Literal(value.decorated("", "")).into_dyn_wrapped())
     });
+    map.insert("var", |language, diagnostics, _build_ctx, function| {
+        let ([name_node], [default_node]) = function.expect_arguments()?;
+        let name = template_parser::catch_aliases(diagnostics, name_node, |_diagnostics, node| {
+            Ok(template_parser::expect_string_literal(node)?.to_owned())
+        })?;
+        // The name is a single key so the lookup can't escape the
+        // [template-vars] table.
+        let path = ConfigNamePathBuf::from_iter(["template-vars", &name]);
+        let value = language
+            .settings()
+            .get_value(&path)
+            .optional()
+            .map_err(|err| {
+                TemplateParseError::expression("Failed to get template variable", name_node.span)
+                    .with_source(err)
+            })?;
+        let value = match (value, default_node) {
+            (Some(value), _) => {
+                if !(value.is_str() || value.is_integer() || value.is_bool()) {
+                    return Err(TemplateParseError::expression(
+                        format!("Template variable `{name}` must be a string, integer, or boolean"),
+                        name_node.span,
+                    ));
+                }
+                value
+            }
+            (None, Some(node)) => {
+                template_parser::catch_aliases(diagnostics, node, |_diagnostics, node| match &node
+                    .kind
+                {
+                    ExpressionKind::String(s) => Ok(ConfigValue::from(s.as_str())),
+                    ExpressionKind::Integer(n) => Ok(ConfigValue::from(*n)),
+                    ExpressionKind::Boolean(b) => Ok(ConfigValue::from(*b)),
+                    _ => Err(TemplateParseError::expression(
+                        "Expected string, integer, or boolean literal",
+                        node.span,
+                    )),
+                })?
+            }
+            (None, None) => {
+                return Err(TemplateParseError::expression(
+                    format!("Template variable `{name}` is not defined"),
+                    name_node.span,
+                ));
+            }
+        };
+        // .decorated("", "") to trim leading/trailing whitespace
+        Ok(Literal(value.decorated("", "")).into_dyn_wrapped())
+    });
     map
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum FormatAlign {
+    Start,
+    End,
+    Center,
+}
+
+/// Parsed `format()` spec, `[[fill]align][width][.precision]`, which is
+/// similar to the format spec of Python and Rust.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct FormatSpec {
+    fill: char,
+    align: FormatAlign,
+    width: Option<usize>,
+    precision: Option<usize>,
+}
+
+impl FormatSpec {
+    fn parse(text: &str) -> Option<Self> {
+        let to_align = |c| match c {
+            '<' => Some(FormatAlign::Start),
+            '>' => Some(FormatAlign::End),
+            '^' => Some(FormatAlign::Center),
+            _ => None,
+        };
+        let mut chars = text.chars();
+        let (fill, align, rest) = match (chars.next(), chars.next().and_then(to_align)) {
+            (Some(fill), Some(align)) => (fill, align, chars.as_str()),
+            _ => match text.chars().next().and_then(to_align) {
+                Some(align) => (' ', align, &text[1..]),
+                None => (' ', FormatAlign::Start, text),
+            },
+        };
+        let (width, precision) = match rest.split_once('.') {
+            Some((width, precision)) => (width, Some(precision.parse().ok()?)),
+            None => (rest, None),
+        };
+        let width = if width.is_empty() {
+            None
+        } else {
+            Some(width.parse().ok()?)
+        };
+        Some(Self {
+            fill,
+            align,
+            width,
+            precision,
+        })
+    }
+}
+
 fn new_pad_template<'a, W>(
     content: Box<dyn Template + 'a>,
     fill_char: Option<Box<dyn Template + 'a>>,
@@ -3244,6 +3439,28 @@ mod tests {
 
         // replace with error
         insta::assert_snapshot!(env.render_ok(r#""hello world".replace("world", bad_string)"#), @"<Error: Bad>");
+
+        // replace_re() takes a plain regex
+        insta::assert_snapshot!(env.render_ok(r#""fix JJ-12 and JJ-345".replace_re('([A-Z]+)-(\d+)', "<$1 ${2}>")"#), @"fix <JJ 12> and <JJ 345>");
+        insta::assert_snapshot!(env.render_ok(r#""a.b".replace_re(".", "x")"#), @"xxx");
+        insta::assert_snapshot!(env.render_ok(r#""hello".replace_re("l", description.first_line())"#), @"hedescription 1description 1o");
+        insta::assert_snapshot!(env.parse_err(r#""hello".replace_re("(", "x")"#), @r#"
+         --> 1:20
+          |
+        1 | "hello".replace_re("(", "x")
+          |                    ^-^
+          |
+          = Invalid regular expression
+        regex parse error:
+            (
+            ^
+        error: unclosed group
+        "#);
+
+        // match_re() returns the capture groups of the first match
+        insta::assert_snapshot!(env.render_ok(r#""fix JJ-12 and JJ-345".match_re('([A-Z]+)-(\d+)')"#), @"JJ-12 JJ 12");
+        insta::assert_snapshot!(env.render_ok(r#""ab".match_re('a(x)?b').map(|s| "[" ++ s ++ "]")"#), @"[ab] []");
+        insta::assert_snapshot!(env.render_ok(r#""hello".match_re('\d').len()"#), @"0");
     }
 
     #[test]
@@ -3613,6 +3830,41 @@ mod tests {
             @"[38;5;9mfo[39mbaz");
     }
 
+    #[test]
+    fn test_format_function() {
+        let mut env = TestTemplateEnv::new();
+        env.add_color("red", crossterm::style::Color::Red);
+
+        insta::assert_snapshot!(env.render_ok(r"'{' ++ format('5', 'foo') ++ '}'"), @"{foo  }");
+        insta::assert_snapshot!(env.render_ok(r"'{' ++ format('>5', 'foo') ++ '}'"), @"{  foo}");
+        insta::assert_snapshot!(env.render_ok(r"'{' ++ format('*^7', 'foo') ++ '}'"), @"{**foo**}");
+        insta::assert_snapshot!(env.render_ok(r"'{' ++ format('.2', 'foo') ++ '}'"), @"{fo}");
+        insta::assert_snapshot!(env.render_ok(r"'{' ++ format('->5.2', 'foo') ++ '}'"), @"{---fo}");
+        insta::assert_snapshot!(env.render_ok(r"'{' ++ format('', 'foo') ++ '}'"), @"{foo}");
+
+        // Labels are preserved
+        insta::assert_snapshot!(
+            env.render_ok(r"format('>5.2', label('red', 'foo'))"),
+            @"   [38;5;9mfo[39m");
+
+        insta::assert_snapshot!(env.parse_err(r"format('x5', 'foo')"), @r"
+         --> 1:8
+          |
+        1 | format('x5', 'foo')
+          |        ^--^
+          |
+          = Invalid format spec
+        ");
+        insta::assert_snapshot!(env.parse_err(r"format(5, 'foo')"), @r"
+         --> 1:8
+          |
+        1 | format(5, 'foo')
+          |        ^
+          |
+          = Expected string literal
+        ");
+    }
+
     #[test]
     fn test_label_function() {
         let mut env = TestTemplateEnv::new();
@@ -31,6 +31,15 @@ use std::process::Stdio;
 use std::thread;
 use std::thread::JoinHandle;
 
+use crossterm::cursor;
+use crossterm::event;
+use crossterm::event::Event;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
+use crossterm::queue;
+use crossterm::terminal;
+use crossterm::terminal::ClearType;
 use itertools::Itertools as _;
 use jj_lib::config::ConfigGetError;
 use jj_lib::config::StackedConfig;
@@ -216,6 +225,7 @@ pub struct Ui {
     quiet: bool,
     pager: PagerConfig,
     progress_indicator: bool,
+    progress_format: ProgressFormat,
     formatter_factory: FormatterFactory,
     output: UiOutput,
 }
@@ -263,6 +273,27 @@ fn prepare_formatter_factory(
     }
 }
 
+/// How progress of long-running operations is displayed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all(deserialize = "kebab-case"))]
+pub enum ProgressFormat {
+    /// Progress bars, only shown if stderr is a terminal.
+    Text,
+    /// Line-delimited JSON events, for programs wrapping `jj`.
+    Json,
+}
+
+/// Value of the `--progress` flag.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, clap::ValueEnum)]
+pub enum ProgressChoice {
+    /// Show progress bars if stderr is a terminal
+    Auto,
+    /// Print line-delimited JSON progress events to stderr
+    Json,
+    /// Don't show progress
+    Never,
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Deserialize)]
 #[serde(rename_all(deserialize = "kebab-case"))]
 pub enum PaginationChoice {
@@ -346,6 +377,7 @@ impl Ui {
             quiet: true,
             pager: PagerConfig::Disabled,
             progress_indicator: false,
+            progress_format: ProgressFormat::Text,
             formatter_factory: FormatterFactory::plain_text(),
             output: UiOutput::Null,
         }
@@ -358,6 +390,7 @@ impl Ui {
             formatter_factory,
             pager: PagerConfig::from_config(config)?,
             progress_indicator: config.get("ui.progress-indicator")?,
+            progress_format: config.get("ui.progress-format")?,
             output: UiOutput::new_terminal(),
         })
     }
@@ -366,6 +399,7 @@ impl Ui {
         self.quiet = config.get("ui.quiet")?;
         self.pager = PagerConfig::from_config(config)?;
         self.progress_indicator = config.get("ui.progress-indicator")?;
+        self.progress_format = config.get("ui.progress-format")?;
         self.formatter_factory = prepare_formatter_factory(config, &io::stdout())?;
         Ok(())
     }
@@ -471,11 +505,22 @@ impl Ui {
     /// Whether continuous feedback should be displayed for long-running
     /// operations
     pub fn use_progress_indicator(&self) -> bool {
-        match &self.output {
-            UiOutput::Terminal { stderr, .. } => self.progress_indicator && stderr.is_terminal(),
-            UiOutput::Paged { .. } => false,
-            UiOutput::BuiltinPaged { .. } => false,
-            UiOutput::Null => false,
+        self.progress_format() == Some(ProgressFormat::Text)
+    }
+
+    /// How progress of long-running operations should be displayed, if at
+    /// all.
+    pub fn progress_format(&self) -> Option<ProgressFormat> {
+        if !self.progress_indicator {
+            return None;
+        }
+        match (&self.output, self.progress_format) {
+            (UiOutput::Null, _) => None,
+            (_, ProgressFormat::Json) => Some(ProgressFormat::Json),
+            (UiOutput::Terminal { stderr, .. }, ProgressFormat::Text) => {
+                stderr.is_terminal().then_some(ProgressFormat::Text)
+            }
+            (UiOutput::Paged { .. } | UiOutput::BuiltinPaged { .. }, ProgressFormat::Text) => None,
         }
     }
 
@@ -677,6 +722,67 @@ impl Ui {
         }
     }
 
+    /// Returns true if [`Self::prompt_select()`] can be used. In addition to
+    /// [`Self::can_prompt()`], this requires both stdin and stderr to be
+    /// connected to a terminal since the keys are read in raw mode.
+    pub fn can_prompt_select(&self) -> bool {
+        Self::can_prompt() && io::stdin().is_terminal() && io::stderr().is_terminal()
+    }
+
+    /// Lets the user choose one of the `items` with the arrow keys and Enter.
+    /// Returns the index of the chosen item, or `None` if the user canceled
+    /// the prompt.
+    ///
+    /// Each item is printed on its own line and may contain color codes. Items
+    /// should be short enough not to wrap.
+    pub fn prompt_select(&self, prompt: &str, items: &[String]) -> io::Result<Option<usize>> {
+        if !self.can_prompt_select() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot prompt for input since the output is not connected to a terminal",
+            ));
+        }
+        assert!(!items.is_empty());
+        let mut stderr = io::stderr().lock();
+        writeln!(
+            stderr,
+            "{prompt} (use arrow keys to move, enter to select, q to quit):"
+        )?;
+        let _raw_mode = RawModeGuard::new()?;
+        queue!(stderr, cursor::Hide)?;
+        let num_lines = u16::try_from(items.len()).unwrap_or(u16::MAX);
+        let mut selected = 0;
+        let choice = loop {
+            for (i, item) in items.iter().enumerate() {
+                let marker = if i == selected { '>' } else { ' ' };
+                queue!(stderr, terminal::Clear(ClearType::CurrentLine))?;
+                write!(stderr, "{marker} {item}\r\n")?;
+            }
+            stderr.flush()?;
+            let Event::Key(key) = event::read()? else {
+                queue!(stderr, cursor::MoveUp(num_lines))?;
+                continue;
+            };
+            if key.kind == KeyEventKind::Press {
+                match key.code {
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        selected = selected.checked_sub(1).unwrap_or(items.len() - 1);
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => selected = (selected + 1) % items.len(),
+                    KeyCode::Enter => break Some(selected),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        break None;
+                    }
+                    KeyCode::Esc | KeyCode::Char('q') => break None,
+                    _ => {}
+                }
+            }
+            queue!(stderr, cursor::MoveUp(num_lines))?;
+        };
+        stderr.flush()?;
+        Ok(choice)
+    }
+
     pub fn prompt_password(&self, prompt: &str) -> io::Result<String> {
         if !io::stdout().is_terminal() {
             return Err(io::Error::new(
@@ -692,6 +798,24 @@ impl Ui {
     }
 }
 
+/// Enables raw mode of the terminal until dropped.
+struct RawModeGuard;
+
+impl RawModeGuard {
+    fn new() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        terminal::disable_raw_mode().ok();
+        // The cursor is hidden while the raw mode is enabled.
+        queue!(io::stderr(), cursor::Show).ok();
+    }
+}
+
 #[derive(Debug)]
 pub struct ProgressOutput<W> {
     output: W,
@@ -0,0 +1,73 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::io;
+use std::io::BufRead as _;
+use std::io::Read as _;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use clap::Parser;
+
+/// A fake backend plugin, useful for testing
+///
+/// Stores objects as files in the given directory, relative to the store
+/// directory the plugin is started in.
+#[derive(Parser, Debug)]
+struct Args {
+    /// Directory to store the objects in
+    #[arg(long, default_value = "fake-plugin-objects")]
+    dir: PathBuf,
+
+    /// Fail all requests to store objects
+    #[arg(long, default_value_t = false)]
+    read_only: bool,
+}
+
+fn main() -> io::Result<()> {
+    let args: Args = Args::parse();
+    fs::create_dir_all(&args.dir)?;
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+    let mut line = String::new();
+    while stdin.read_line(&mut line)? > 0 {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["jj-backend-plugin", "1"] => writeln!(stdout, "ok 0")?,
+            ["get", kind, id] => match fs::read(args.dir.join(format!("{kind}-{id}"))) {
+                Ok(data) => {
+                    writeln!(stdout, "ok {}", data.len())?;
+                    stdout.write_all(&data)?;
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => writeln!(stdout, "missing")?,
+                Err(err) => writeln!(stdout, "error {err}")?,
+            },
+            ["put", kind, id, len] => {
+                let mut data = vec![0; len.parse().unwrap()];
+                stdin.read_exact(&mut data)?;
+                if args.read_only {
+                    writeln!(stdout, "error the object store is read-only")?;
+                } else {
+                    fs::write(args.dir.join(format!("{kind}-{id}")), data)?;
+                    writeln!(stdout, "ok 0")?;
+                }
+            }
+            _ => writeln!(stdout, "error unknown request: {}", line.trim_end())?,
+        }
+        stdout.flush()?;
+        line.clear();
+    }
+    Ok(())
+}
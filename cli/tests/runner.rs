@@ -13,6 +13,8 @@ mod test_absorb_command;
 mod test_acls;
 mod test_advance_bookmarks;
 mod test_alias;
+mod test_api_command;
+mod test_autosquash_command;
 mod test_bisect_command;
 mod test_bookmark_command;
 mod test_builtin_aliases;
@@ -43,6 +45,7 @@ mod test_gerrit_upload;
 mod test_git_clone;
 mod test_git_colocated;
 mod test_git_colocation;
+mod test_git_credential;
 mod test_git_fetch;
 mod test_git_import_export;
 mod test_git_init;
@@ -60,10 +63,12 @@ mod test_log_command;
 mod test_metaedit_command;
 mod test_new_command;
 mod test_next_prev_commands;
+mod test_note_command;
 mod test_op_revert_command;
 mod test_operations;
 mod test_parallelize_command;
 mod test_rebase_command;
+mod test_rebase_descendants_policy;
 mod test_repo_change_report;
 mod test_resolve_command;
 mod test_restore_command;
@@ -77,6 +82,8 @@ mod test_sparse_command;
 mod test_split_command;
 mod test_squash_command;
 mod test_status_command;
+mod test_submodule_command;
+mod test_swap_command;
 mod test_tag_command;
 mod test_templater;
 mod test_undo_redo_commands;
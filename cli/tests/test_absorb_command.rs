@@ -15,6 +15,7 @@
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 use crate::common::TestWorkDir;
+use crate::common::force_interactive;
 
 #[test]
 fn test_absorb_simple() {
@@ -951,6 +952,144 @@ fn test_absorb_immutable() {
     ");
 }
 
+#[test]
+fn test_absorb_dry_run() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.run_jj(["describe", "-m1"]).success();
+    work_dir.write_file("file1", "1a\n1b\n");
+    work_dir.run_jj(["new", "-m2"]).success();
+    work_dir.write_file("file1", "1a\n1b\n2a\n2b\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file1", "1X\n1a\n1b\n2a\n2B\n");
+
+    let output = work_dir.run_jj(["absorb", "--dry-run"]);
+    insta::assert_snapshot!(output, @r"
+    file1	-0,0 +1,1	qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu	e35bcaffcb55944e98c59b986f3a86da3303d1be
+    file1	-4,1 +5,1	kkmpptxzrspxrzommnulwmwkkqwworpl	8105f6460304238eedcb9077e7b6d40888e651f2
+    [EOF]
+    ");
+    // Nothing was rewritten
+    insta::assert_snapshot!(get_diffs(&work_dir, "@"), @r"
+    @  zsuskuln 6104fed8 (no description set)
+    │  diff --git a/file1 b/file1
+    ~  index 3de64a0b0c..86a925908e 100644
+       --- a/file1
+       +++ b/file1
+       @@ -1,4 +1,5 @@
+       +1X
+        1a
+        1b
+        2a
+       -2b
+       +2B
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_absorb_interactive() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.run_jj(["describe", "-m1"]).success();
+    work_dir.write_file("file1", "1a\n1b\n");
+    work_dir.run_jj(["new", "-m2"]).success();
+    work_dir.write_file("file1", "1a\n1b\n2a\n2b\n");
+    work_dir.run_jj(["new", "-m3"]).success();
+    work_dir.write_file("file2", "3a\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file1", "1X\n1a\n1b\n2a\n2B\n");
+    work_dir.run_jj(["debug", "snapshot"]).success();
+    let setup_opid = work_dir.current_operation_id();
+
+    // Skip the first hunk, accept the second one
+    let output = work_dir.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .arg("absorb")
+            .arg("--interactive")
+            .write_stdin("n\ny\n")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    file1 @@ -0,0 +1,1 @@
+    +1X
+    Destination: qpvuntsm e35bcaff 1
+    Absorb this hunk? [y]es, [n]o (skip), [r]etarget: file1 @@ -4,1 +5,1 @@
+    -2b
+    +2B
+    Destination: kkmpptxz 8105f646 2
+    Absorb this hunk? [y]es, [n]o (skip), [r]etarget: Absorbed changes into 1 revisions:
+      kkmpptxz 276e5534 2
+    Rebased 2 descendant commits.
+    Working copy  (@) now at: mzvwutvl 5797b18d (no description set)
+    Parent commit (@-)      : zsuskuln 5d778f8a 3
+    Remaining changes:
+    M file1
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_diffs(&work_dir, "@"), @r"
+    @  mzvwutvl 5797b18d (no description set)
+    │  diff --git a/file1 b/file1
+    ~  index 75e4047831..86a925908e 100644
+       --- a/file1
+       +++ b/file1
+       @@ -1,3 +1,4 @@
+       +1X
+        1a
+        1b
+        2a
+    [EOF]
+    ");
+
+    // Retarget the first hunk to another revision, retrying after invalid
+    // ones
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    let output = work_dir.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .arg("absorb")
+            .arg("--interactive")
+            .write_stdin("r\n@\nno-such-rev\nkkmpptxz\nn\n")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    file1 @@ -0,0 +1,1 @@
+    +1X
+    Destination: qpvuntsm e35bcaff 1
+    Absorb this hunk? [y]es, [n]o (skip), [r]etarget: Enter the revision to absorb the hunk into: Warning: Revision be4f1ffe0da6 is not one of the destination revisions; try again
+    Enter the revision to absorb the hunk into: Warning: Revision `no-such-rev` doesn't exist; try again
+    Enter the revision to absorb the hunk into: file1 @@ -4,1 +5,1 @@
+    -2b
+    +2B
+    Destination: kkmpptxz 8105f646 2
+    Absorb this hunk? [y]es, [n]o (skip), [r]etarget: Absorbed changes into 1 revisions:
+      kkmpptxz 17c349ec 2
+    Rebased 2 descendant commits.
+    Working copy  (@) now at: mzvwutvl 2eecefaa (no description set)
+    Parent commit (@-)      : zsuskuln 2505b677 3
+    Remaining changes:
+    M file1
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_diffs(&work_dir, "kkmpptxz"), @r"
+    ○  kkmpptxz 17c349ec 2
+    │  diff --git a/file1 b/file1
+    ~  index 8c5268f893..d783de0eb2 100644
+       --- a/file1
+       +++ b/file1
+       @@ -1,2 +1,5 @@
+       +1X
+        1a
+        1b
+       +2a
+       +2b
+    [EOF]
+    ");
+}
+
 #[must_use]
 fn get_diffs(work_dir: &TestWorkDir, revision: &str) -> CommandOutput {
     let template = r#"format_commit_summary_with_refs(self, "") ++ "\n""#;
@@ -0,0 +1,217 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_api_queries() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("file1", "foo\n");
+    work_dir.write_file("file2", "foo\n");
+    work_dir.run_jj(["commit", "-mfirst"]).success();
+    work_dir.write_file("file1", "bar\n");
+    work_dir.remove_file("file2");
+    work_dir.write_file("file3", "bar\n");
+
+    let requests = [
+        r#"{"jsonrpc": "2.0", "id": 1, "method": "api.version"}"#,
+        r#"{"jsonrpc": "2.0", "id": 2, "method": "operation.head"}"#,
+        r#"{"jsonrpc": "2.0", "id": 3, "method": "log", "params": {"revisions": "::@", "template": "description"}}"#,
+        r#"{"jsonrpc": "2.0", "id": 4, "method": "diff"}"#,
+        r#"{"jsonrpc": "2.0", "id": 5, "method": "diff", "params": {"revision": "@-"}}"#,
+        // Notifications don't get a response
+        r#"{"jsonrpc": "2.0", "method": "api.version"}"#,
+    ];
+    let output = work_dir.run_jj_with(|cmd| cmd.arg("api").write_stdin(requests.join("\n")));
+    insta::assert_snapshot!(output, @r#"
+    {"id":1,"jsonrpc":"2.0","result":{"version":1}}
+    {"id":2,"jsonrpc":"2.0","result":{"id":"b73e034e7c45f9e8cff4ffaaf4617a1e9bc726781ec88a781bd679dc0952c853a1c6e9bb2d7a92d5d326521434f10754f5b75f3ee618cc165d276a462e61c5d8"}}
+    {"id":3,"jsonrpc":"2.0","result":{"commits":[{"change_id":"rlvkpnrzqnoowoytxnquwvuryrwnrmlp","commit_id":"dff63a84fa5c7fd8b518583a7f28c320fad6ae43","output":""},{"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","commit_id":"958d99fa13bf72ed87d17a9b1021fb044834532d","output":"first\n"},{"change_id":"zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz","commit_id":"0000000000000000000000000000000000000000","output":""}]}}
+    {"id":4,"jsonrpc":"2.0","result":{"commit_id":"dff63a84fa5c7fd8b518583a7f28c320fad6ae43","files":[{"binary":false,"hunks":[{"lines":["-foo\n","+bar\n"],"new_lines":1,"new_start":1,"old_lines":1,"old_start":1}],"path":"file1","status":"modified"},{"binary":false,"hunks":[{"lines":["-foo\n"],"new_lines":0,"new_start":0,"old_lines":1,"old_start":1}],"path":"file2","status":"removed"},{"binary":false,"hunks":[{"lines":["+bar\n"],"new_lines":1,"new_start":1,"old_lines":0,"old_start":0}],"path":"file3","status":"added"}]}}
+    {"id":5,"jsonrpc":"2.0","result":{"commit_id":"958d99fa13bf72ed87d17a9b1021fb044834532d","files":[{"binary":false,"hunks":[{"lines":["+foo\n"],"new_lines":1,"new_start":1,"old_lines":0,"old_start":0}],"path":"file1","status":"added"},{"binary":false,"hunks":[{"lines":["+foo\n"],"new_lines":1,"new_start":1,"old_lines":0,"old_start":0}],"path":"file2","status":"added"}]}}
+    [EOF]
+    "#);
+}
+
+#[test]
+fn test_api_errors() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    let requests = [
+        "not json",
+        r#"{"jsonrpc": "2.0", "id": 1, "method": "unknown"}"#,
+        r#"{"jsonrpc": "2.0", "id": 2, "method": "log", "params": {"revset": "@"}}"#,
+        r#"{"jsonrpc": "2.0", "id": 3, "method": "diff", "params": {"revision": "nonexistent"}}"#,
+        r#"{"jsonrpc": "2.0", "id": 4, "method": "api.version"}"#,
+    ];
+    let output = work_dir.run_jj_with(|cmd| cmd.arg("api").write_stdin(requests.join("\n")));
+    insta::assert_snapshot!(output, @r#"
+    {"error":{"code":-32700,"message":"Parse error: expected ident at line 1 column 2"},"id":null,"jsonrpc":"2.0"}
+    {"error":{"code":-32601,"message":"Method not found: unknown"},"id":1,"jsonrpc":"2.0"}
+    {"error":{"code":-32602,"message":"Invalid params: unknown field `revset`, expected `revisions` or `template`"},"id":2,"jsonrpc":"2.0"}
+    {"error":{"code":1,"message":"Revision `nonexistent` doesn't exist"},"id":3,"jsonrpc":"2.0"}
+    {"id":4,"jsonrpc":"2.0","result":{"version":1}}
+    [EOF]
+    "#);
+
+    // The command fails early outside a repository
+    let output = test_env.run_jj_in(".", ["api"]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Error: There is no jj repo in "."
+    [EOF]
+    [exit status: 1]
+    "#);
+}
+
+#[test]
+fn test_api_status_and_describe() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("file", "foo\n");
+
+    let requests = [
+        r#"{"jsonrpc": "2.0", "id": 1, "method": "status"}"#,
+        r#"{"jsonrpc": "2.0", "id": 2, "method": "describe", "params": {"message": "added file"}}"#,
+        r#"{"jsonrpc": "2.0", "id": 3, "method": "log", "params": {"revisions": "@", "template": "description"}}"#,
+        r#"{"jsonrpc": "2.0", "id": 4, "method": "describe", "params": {"revision": "root()", "message": "root"}}"#,
+        r#"{"jsonrpc": "2.0", "id": 5, "method": "describe"}"#,
+    ];
+    let output = work_dir.run_jj_with(|cmd| cmd.arg("api").write_stdin(requests.join("\n")));
+    insta::assert_snapshot!(output, @r#"
+    {"id":1,"jsonrpc":"2.0","result":{"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","commit_id":"d0c049cd993a8d3a2e69ba6df98788e264ea9fa1","conflicts":[],"files":[{"path":"file","status":"added"}]}}
+    {"id":2,"jsonrpc":"2.0","result":{"commit_id":"d751cd229562c0220701b6adecec6d8cd8e5234d","operation_id":"23dc311d65e246636ece75a51fb91766ff34eb06600d9a1728ebf7d71726c4fad47ee8b82061a41c7de910978969225c4442641de757721cfb7490e940135152"}}
+    {"id":3,"jsonrpc":"2.0","result":{"commits":[{"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","commit_id":"d751cd229562c0220701b6adecec6d8cd8e5234d","output":"added file"}]}}
+    {"error":{"code":1,"message":"The root commit 000000000000 is immutable"},"id":4,"jsonrpc":"2.0"}
+    {"error":{"code":-32602,"message":"Invalid params: missing field `message`"},"id":5,"jsonrpc":"2.0"}
+    [EOF]
+    ------- stderr -------
+    Working copy  (@) now at: qpvuntsm d751cd22 added file
+    Parent commit (@-)      : zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    "#);
+
+    // The description is recorded in the operation log
+    let output = work_dir.run_jj(["op", "log", "-n1", "--no-graph", "-T", "description"]);
+    insta::assert_snapshot!(output, @r"
+    describe commit d0c049cd993a8d3a2e69ba6df98788e264ea9fa1[EOF]
+    ");
+}
+
+#[test]
+fn test_api_operation_wait() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let op_id = work_dir
+        .run_jj(["op", "log", "-n1", "--no-graph", "-T", "id"])
+        .success()
+        .stdout
+        .into_raw();
+
+    // Times out if nothing changed
+    let request = format!(
+        r#"{{"jsonrpc": "2.0", "id": 1, "method": "operation.wait", "params": {{"since": "{op_id}", "timeout_ms": 0}}}}"#
+    );
+    let output = work_dir.run_jj_with(|cmd| cmd.arg("api").write_stdin(request));
+    assert!(output.stdout.raw().contains(&op_id), "{output}");
+
+    // Returns immediately once the working copy changed
+    work_dir.write_file("file", "foo\n");
+    let request = format!(
+        r#"{{"jsonrpc": "2.0", "id": 1, "method": "operation.wait", "params": {{"since": "{op_id}"}}}}"#
+    );
+    let output = work_dir.run_jj_with(|cmd| cmd.arg("api").write_stdin(request));
+    assert!(!output.stdout.raw().contains(&op_id), "{output}");
+    assert!(
+        output.stdout.raw().contains(r#""result":{"id":"#),
+        "{output}"
+    );
+}
+
+#[cfg(feature = "builtin-fsmonitor")]
+#[test]
+fn test_api_operation_wait_watches_changes() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let op_id = work_dir
+        .run_jj(["op", "log", "-n1", "--no-graph", "-T", "id"])
+        .success()
+        .stdout
+        .into_raw();
+
+    // Wakes up when the working copy is changed while waiting
+    let request = format!(
+        r#"{{"jsonrpc": "2.0", "id": 1, "method": "operation.wait", "params": {{"since": "{op_id}", "timeout_ms": 60000}}}}"#
+    );
+    let file_path = work_dir.root().join("file");
+    let start = Instant::now();
+    let output = thread::scope(|s| {
+        s.spawn(|| {
+            thread::sleep(Duration::from_secs(1));
+            std::fs::write(&file_path, "foo\n").unwrap();
+        });
+        work_dir.run_jj_with(|cmd| cmd.arg("api").write_stdin(request))
+    });
+    assert!(!output.stdout.raw().contains(&op_id), "{output}");
+    assert!(start.elapsed() < Duration::from_secs(60), "{output}");
+}
+
+#[cfg(feature = "builtin-fsmonitor")]
+#[test]
+fn test_api_reloads_concurrent_operations() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let op_id = work_dir
+        .run_jj(["op", "log", "-n1", "--no-graph", "-T", "id"])
+        .success()
+        .stdout
+        .into_raw();
+
+    // The repo loaded by the server is reloaded after another process added
+    // an operation
+    let requests = [
+        format!(
+            r#"{{"jsonrpc": "2.0", "id": 1, "method": "operation.wait", "params": {{"since": "{op_id}", "timeout_ms": 60000}}}}"#
+        ),
+        r#"{"jsonrpc": "2.0", "id": 2, "method": "log", "params": {"revisions": "@", "template": "description"}}"#.to_owned(),
+    ];
+    let mut describe_cmd = test_env.new_jj_cmd();
+    describe_cmd
+        .current_dir(work_dir.root())
+        .args(["describe", "-mconcurrent"]);
+    let output = thread::scope(|s| {
+        s.spawn(move || {
+            thread::sleep(Duration::from_secs(1));
+            describe_cmd.assert().success();
+        });
+        work_dir.run_jj_with(|cmd| cmd.arg("api").write_stdin(requests.join("\n")))
+    });
+    assert!(!output.stdout.raw().contains(&op_id), "{output}");
+    assert!(
+        output.stdout.raw().contains(r#""output":"concurrent\n""#),
+        "{output}"
+    );
+}
@@ -0,0 +1,117 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::CommandOutput;
+use crate::common::TestEnvironment;
+use crate::common::TestWorkDir;
+
+#[test]
+fn test_autosquash() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "a\n");
+    work_dir.run_jj(["commit", "-m", "add file1"]).success();
+    work_dir.write_file("file2", "b\n");
+    work_dir.run_jj(["commit", "-m", "add file2"]).success();
+    // Refers to the target by description
+    work_dir.write_file("file1", "a\nfixed\n");
+    work_dir
+        .run_jj(["commit", "-m", "fixup! add file1"])
+        .success();
+    // Refers to the target by change ID
+    work_dir.write_file("file2", "b\nsquashed\n");
+    work_dir
+        .run_jj(["commit", "-m", "squash! rlvkpnrz\n\nMore details"])
+        .success();
+    // Refers to another fixup commit
+    work_dir.write_file("file1", "a\nfixed again\n");
+    work_dir
+        .run_jj(["commit", "-m", "fixup! fixup! add file1"])
+        .success();
+    work_dir
+        .run_jj(["describe", "-m", "fixup! no such commit"])
+        .success();
+    insta::assert_snapshot!(get_log_output(&work_dir), @r"
+    @  royxmykxtrkr fixup! no such commit
+    ○  mzvwutvlkqwt fixup! fixup! add file1
+    │  M file1
+    ○  zsuskulnrvyr squash! rlvkpnrz
+    │
+    │  More details
+    │  M file2
+    ○  kkmpptxzrspx fixup! add file1
+    │  M file1
+    ○  rlvkpnrzqnoo add file2
+    │  A file2
+    ○  qpvuntsmwlqt add file1
+    │  A file1
+    ◆  zzzzzzzzzzzz
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["autosquash"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: Could not find the revision that 080f4f7d4bf0 refers to: no such commit
+    Squashed 3 fixup revisions into 2 revisions:
+      qpvuntsm a0f3d62d add file1
+      rlvkpnrz 53ed6508 add file2
+    Working copy  (@) now at: royxmykx 243dea6e (empty) fixup! no such commit
+    Parent commit (@-)      : rlvkpnrz 53ed6508 add file2
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @r"
+    @  royxmykxtrkr fixup! no such commit
+    ○  rlvkpnrzqnoo add file2
+    │
+    │  More details
+    │  A file2
+    ○  qpvuntsmwlqt add file1
+    │  A file1
+    ◆  zzzzzzzzzzzz
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "file",
+        "show",
+        "-r",
+        "description(exact:'add file1\n')",
+        "file1",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    a
+    fixed again
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["autosquash"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: Could not find the revision that 243dea6ef9d3 refers to: no such commit
+    No fixup revisions to squash.
+    [EOF]
+    ");
+}
+
+#[must_use]
+fn get_log_output(work_dir: &TestWorkDir) -> CommandOutput {
+    work_dir.run_jj([
+        "log",
+        "-T",
+        "separate(' ', change_id.short(), description)",
+        "--summary",
+    ])
+}
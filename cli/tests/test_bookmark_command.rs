@@ -17,6 +17,7 @@ use testutils::git;
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 use crate::common::TestWorkDir;
+use crate::common::force_interactive;
 
 fn create_commit_with_refs(
     repo: &gix::Repository,
@@ -384,6 +385,106 @@ fn test_bookmark_move() {
     ");
 }
 
+#[test]
+fn test_bookmark_protected() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(r#"bookmarks.protect = ["main", "release/*"]"#);
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "main", "release/1", "feature"])
+        .success();
+    work_dir.run_jj(["new"]).success();
+
+    // Protected bookmarks can be moved forward
+    let output = work_dir.run_jj(["bookmark", "move", "--to=@", "main"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: Target revision is empty.
+    Moved 1 bookmarks to kkmpptxz 1c1c95df main | (empty) (no description set)
+    [EOF]
+    ");
+
+    // but not backwards or sideways, even with --allow-backwards
+    let output = work_dir.run_jj(["bookmark", "set", "-r@-", "--allow-backwards", "main"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: Target revision is empty.
+    Error: Refusing to move protected bookmark backwards or sideways: main
+    Hint: Use --allow-protected to allow it.
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["bookmark", "move", "--to=root()", "-B", "main"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: Target revision is empty.
+    Error: Refusing to move protected bookmark backwards or sideways: main
+    Hint: Use --allow-protected to allow it.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Protected bookmarks can't be deleted or renamed
+    let output = work_dir.run_jj(["bookmark", "delete", "*"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Refusing to delete protected bookmark: main
+    Hint: Use --allow-protected to allow it.
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["bookmark", "rename", "release/1", "release/2"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Refusing to delete protected bookmark: release/1
+    Hint: Use --allow-protected to allow it.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Other commands can't move or delete them either
+    let output = work_dir.run_jj(["abandon", "main"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Refusing to delete protected bookmark: main
+    Hint: Use --allow-protected to allow it.
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["bookmark", "forget", "main"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Refusing to delete protected bookmark: main
+    Hint: Use --allow-protected to allow it.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Unprotected bookmarks aren't affected
+    let output = work_dir.run_jj(["bookmark", "delete", "feature"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Deleted 1 bookmarks.
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["bookmark", "set", "-r@-", "-B", "--allow-protected", "main"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: Target revision is empty.
+    Moved 1 bookmarks to qpvuntsm e8849ae1 main release/1 | (empty) (no description set)
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["bookmark", "delete", "--allow-protected", "release/*"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Deleted 1 bookmarks.
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_bookmark_move_matching() {
     let test_env = TestEnvironment::default();
@@ -593,6 +694,136 @@ fn test_bookmark_move_conflicting() {
     ");
 }
 
+#[test]
+fn test_bookmark_resolve() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let get_log = || {
+        let template = r#"separate(" ", description.first_line(), bookmarks)"#;
+        work_dir.run_jj(["log", "-T", template])
+    };
+
+    work_dir.run_jj(["new", "root()", "-mA0"]).success();
+    work_dir.run_jj(["new", "root()", "-mB0"]).success();
+
+    let output = work_dir.run_jj(["bookmark", "resolve"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    No conflicted bookmarks to resolve.
+    [EOF]
+    ");
+
+    // Set up conflicting bookmarks. baz is deleted on one side.
+    work_dir
+        .run_jj(["bookmark", "create", "-rsubject(A0)", "baz"])
+        .success();
+    let setup_opid = work_dir.current_operation_id();
+    work_dir
+        .run_jj(["bookmark", "create", "-rsubject(A0)", "foo", "bar"])
+        .success();
+    work_dir
+        .run_jj(["bookmark", "move", "--to=subject(B0)", "-B", "baz"])
+        .success();
+    work_dir
+        .run_jj([
+            "bookmark",
+            "create",
+            "--at-op",
+            &setup_opid,
+            "-rsubject(B0)",
+            "foo",
+            "bar",
+        ])
+        .success();
+    work_dir
+        .run_jj(["bookmark", "delete", "--at-op", &setup_opid, "baz"])
+        .success();
+    insta::assert_snapshot!(get_log(), @r"
+    @  B0 bar?? baz?? foo??
+    │ ○  A0 bar?? foo??
+    ├─╯
+    ◆
+    [EOF]
+    ------- stderr -------
+    Concurrent modification detected, resolving automatically.
+    [EOF]
+    ");
+
+    // Skip the only matching bookmark
+    let output = work_dir.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .args(["bookmark", "resolve", "foo"])
+            .write_stdin("s\n")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Bookmark foo is conflicted:
+    1: rlvkpnrz f7addb25 bar?? foo?? | (empty) A0
+    2: kkmpptxz 61b17b81 bar?? baz?? foo?? | (empty) B0
+    m: create a merge commit of all targets
+    s: skip this bookmark
+    choose how to resolve foo: No bookmarks were resolved.
+    [EOF]
+    ");
+
+    // Pick a target and delete
+    let output = work_dir.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .args(["bookmark", "resolve", "bar", "baz"])
+            .write_stdin("2\nd\n")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Bookmark bar is conflicted:
+    1: rlvkpnrz f7addb25 bar?? foo?? | (empty) A0
+    2: kkmpptxz 61b17b81 bar?? baz?? foo?? | (empty) B0
+    m: create a merge commit of all targets
+    s: skip this bookmark
+    choose how to resolve bar: Bookmark baz is conflicted:
+    1: kkmpptxz 61b17b81 bar baz?? foo?? | (empty) B0
+    m: create a merge commit of all targets
+    d: delete the bookmark
+    s: skip this bookmark
+    choose how to resolve baz: Resolved 2 bookmarks.
+    [EOF]
+    ");
+
+    // Merge
+    let output = work_dir.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .args(["bookmark", "resolve"])
+            .write_stdin("m\n")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Bookmark foo is conflicted:
+    1: rlvkpnrz f7addb25 foo?? | (empty) A0
+    2: kkmpptxz 61b17b81 bar foo?? | (empty) B0
+    m: create a merge commit of all targets
+    s: skip this bookmark
+    choose how to resolve foo: Created merge commit wqnwkozp 64e83d3e (empty) (no description set)
+    Resolved 1 bookmark.
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log(), @r"
+    ○    foo
+    ├─╮
+    │ @  B0 bar
+    ○ │  A0
+    ├─╯
+    ◆
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["bookmark", "resolve"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    No conflicted bookmarks to resolve.
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_bookmark_rename() {
     let test_env = TestEnvironment::default();
@@ -704,6 +935,46 @@ fn test_bookmark_rename_colocated() {
     insta::assert_snapshot!(output, @"");
 }
 
+#[test]
+fn test_bookmark_log() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let template = r#"id.short() ++ " " ++ description ++ "\n""#;
+
+    let output = work_dir.run_jj(["bookmark", "log", "foo", "-T", template]);
+    insta::assert_snapshot!(output, @"");
+
+    work_dir.run_jj(["describe", "-m=commit-0"]).success();
+    work_dir.run_jj(["bookmark", "create", "foo"]).success();
+    work_dir.run_jj(["new", "-m=commit-1"]).success();
+    work_dir.run_jj(["bookmark", "create", "bar"]).success();
+    work_dir.run_jj(["bookmark", "set", "foo", "-r@"]).success();
+    work_dir.run_jj(["bookmark", "delete", "foo"]).success();
+
+    let output = work_dir.run_jj(["bookmark", "log", "foo", "-T", template]);
+    insta::assert_snapshot!(output, @"
+    725ba585170e delete bookmark foo
+    + (absent)
+    - mzvwutvl 4b8fbb41 bar | (empty) commit-1
+    d89d594aad7b point bookmark foo to commit 4b8fbb41884b235bbb8b730101f99c21aba5c8a9
+    + mzvwutvl 4b8fbb41 bar | (empty) commit-1
+    - qpvuntsm 1937d547 (empty) commit-0
+    2ec29c83b184 create bookmark foo pointing to commit 1937d547d4e216e9d1d178ea07a92fdc9f7efadd
+    + qpvuntsm 1937d547 (empty) commit-0
+    - (absent)
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["bookmark", "log", "foo", "-T", template, "-n1"]);
+    insta::assert_snapshot!(output, @"
+    725ba585170e delete bookmark foo
+    + (absent)
+    - mzvwutvl 4b8fbb41 bar | (empty) commit-1
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_bookmark_forget_glob() {
     let test_env = TestEnvironment::default();
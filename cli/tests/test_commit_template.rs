@@ -1504,6 +1504,83 @@ fn test_log_diff_path_display() {
     ");
 }
 
+#[test]
+fn test_log_diff_between_revisions() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "a\n");
+    work_dir.write_file("file2", "a\n");
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "trunk"])
+        .success();
+    work_dir.run_jj(["new", "-mfirst"]).success();
+    work_dir.write_file("file1", "b\n");
+    work_dir.run_jj(["new", "-msecond"]).success();
+    work_dir.write_file("file2", "b\n");
+    work_dir.write_file("file3", "b\n");
+
+    // Diff against a revision resolved by revset, and against another commit
+    let template = r#"
+    separate(" ",
+      description.first_line(),
+      "trunk:", diff("trunk", self).files().map(|e| e.path()),
+      "@:", diff(self, "@").stat().total_added(),
+      "file1:", diff("trunk", self, "file1").files().map(|e| e.status()),
+    ) ++ "\n"
+    "#;
+    let output = work_dir.run_jj(["log", "--no-graph", "-r=trunk..", "-T", template]);
+    insta::assert_snapshot!(output, @"
+    second trunk: file1 file2 file3 @: 0 file1: modified
+    first trunk: file1 @: 2 file1: modified
+    [EOF]
+    ");
+
+    // Revset should resolve to exactly one commit
+    let output = work_dir.run_jj(["log", "-r@", "-T", r#"diff("all()", self)"#]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Error: Failed to parse template: Revset `all()` should resolve to exactly one commit
+    Caused by:  --> 1:6
+      |
+    1 | diff("all()", self)
+      |      ^-----^
+      |
+      = Revset `all()` should resolve to exactly one commit
+    [EOF]
+    [exit status: 1]
+    "#);
+    let output = work_dir.run_jj(["log", "-r@", "-T", r#"diff("none()", self)"#]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Error: Failed to parse template: Revset `none()` should resolve to exactly one commit
+    Caused by:  --> 1:6
+      |
+    1 | diff("none()", self)
+      |      ^------^
+      |
+      = Revset `none()` should resolve to exactly one commit
+    [EOF]
+    [exit status: 1]
+    "#);
+
+    // Arguments should be commits
+    let output = work_dir.run_jj(["log", "-r@", "-T", r#"diff(self.author(), self)"#]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Failed to parse template: Expected expression of type `Commit`, but actual type is `Signature`
+    Caused by:  --> 1:6
+      |
+    1 | diff(self.author(), self)
+      |      ^-----------^
+      |
+      = Expected expression of type `Commit`, but actual type is `Signature`
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_file_list_entries() {
     let test_env = TestEnvironment::default();
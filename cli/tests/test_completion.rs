@@ -219,7 +219,7 @@ fn test_bookmark_names() {
     let work_dir = test_env.work_dir("repo");
 
     let output = work_dir.complete_fish(["bookmark", "rename", ""]);
-    insta::assert_snapshot!(output, @r"
+    insta::assert_snapshot!(output, @"
     aaa-local	x
     aaa-tracked	x
     bbb-local	x
@@ -227,11 +227,15 @@ fn test_bookmark_names() {
     --repository	Path to repository to operate on
     --ignore-working-copy	Don't snapshot the working copy, and don't update it
     --ignore-immutable	Allow rewriting immutable commits
+    --allow-protected	Allow moving protected bookmarks backwards or deleting them
+    --rebase-descendants	When to rebase the descendants of rewritten commits
     --at-operation	Operation to load the repo at
     --debug	Enable debug logging
+    --profile	Write a profile of the command to the given file
     --color	When to colorize output
     --quiet	Silence non-primary command output
     --no-pager	Disable the pager
+    --progress	How to display the progress of long-running operations
     --config	Additional configuration options (can be repeated)
     --config-file	Additional configuration files (can be repeated)
     --help	Print help (see more with '--help')
@@ -318,11 +322,27 @@ fn test_bookmark_names() {
     [EOF]
     ");
 
+    // Deleted bookmarks can be pushed to delete them on the remote
+    work_dir
+        .run_jj(["bookmark", "delete", "bbb-tracked"])
+        .success();
+    let output = work_dir.complete_fish(["git", "push", "-b", "b"]);
+    insta::assert_snapshot!(output, @r"
+    bbb-local	x
+    bbb-tracked	(deleted bookmark)
+    [EOF]
+    ");
+
     let output = work_dir.complete_fish(["git", "fetch", "-b", "a"]);
     insta::assert_snapshot!(output, @r"
-    aaa-local	x
-    aaa-tracked	x
-    aaa-untracked
+    aaa-tracked	origin, upstream
+    aaa-untracked	origin
+    [EOF]
+    ");
+
+    let output = work_dir.complete_fish(["git", "fetch", "--remote=upstream", "-b", "a"]);
+    insta::assert_snapshot!(output, @r"
+    aaa-tracked	upstream
     [EOF]
     ");
 }
@@ -517,23 +537,23 @@ fn test_default_command_is_resolved(shell: Shell) {
         .take_stdout_n_lines(2);
     match shell {
         Shell::Bash => {
-            insta::assert_snapshot!(output, @r"
+            insta::assert_snapshot!(output, @"
             --revisions
-            --limit
+            --elide
             [EOF]
             ");
         }
         Shell::Zsh => {
-            insta::assert_snapshot!(output, @r"
+            insta::assert_snapshot!(output, @"
             --revisions:Which revisions to show
-            --limit:Limit number of revisions to show
+            --elide:Hide revisions matching this revset from the graph
             [EOF]
             ");
         }
         Shell::Fish => {
-            insta::assert_snapshot!(output, @r"
+            insta::assert_snapshot!(output, @"
             --revisions	Which revisions to show
-            --limit	Limit number of revisions to show
+            --elide	Hide revisions matching this revset from the graph
             [EOF]
             ");
         }
@@ -1102,6 +1122,12 @@ fn test_operations() {
     [EOF]
     ");
 
+    let output = work_dir.complete_fish(["op", "abandon", "..8e"]);
+    insta::assert_snapshot!(output, @r"
+    ..8ed8c16786e6	(2001-02-03 08:05:11) describe commit 3725536d0ae06d69e46911258cee591dbdb66478
+    [EOF]
+    ");
+
     let output = work_dir.complete_fish(["op", "diff", "--op", "8e"]);
     insta::assert_snapshot!(output, @r"
     8ed8c16786e6	(2001-02-03 08:05:11) describe commit 3725536d0ae06d69e46911258cee591dbdb66478
@@ -1162,6 +1188,8 @@ fn test_config() {
     let output = test_env.complete_fish(["config", "get", "f"]);
     insta::assert_snapshot!(output, @r"
     fsmonitor.backend	Whether to use an external filesystem monitor, useful for large repos
+    fsmonitor.builtin.auto-start	Whether to start the builtin monitor daemon automatically when taking a snapshot.
+    fsmonitor.builtin.daemon-command	Command that runs the builtin monitor daemon. Defaults to `jj debug fsmonitor daemon` run with the current executable.
     fsmonitor.watchman.register-snapshot-trigger	Whether to use triggers to monitor for changes in the background.
     [EOF]
     ");
@@ -1170,6 +1198,9 @@ fn test_config() {
     insta::assert_snapshot!(output, @r"
     fsmonitor	External filesystem monitor settings, useful for large repos
     fsmonitor.backend	Whether to use an external filesystem monitor, useful for large repos
+    fsmonitor.builtin
+    fsmonitor.builtin.auto-start	Whether to start the builtin monitor daemon automatically when taking a snapshot.
+    fsmonitor.builtin.daemon-command	Command that runs the builtin monitor daemon. Defaults to `jj debug fsmonitor daemon` run with the current executable.
     fsmonitor.watchman
     fsmonitor.watchman.register-snapshot-trigger	Whether to use triggers to monitor for changes in the background.
     [EOF]
@@ -1178,6 +1209,8 @@ fn test_config() {
     let output = test_env.complete_fish(["log", "--config", "f"]);
     insta::assert_snapshot!(output, @r"
     fsmonitor.backend=	Whether to use an external filesystem monitor, useful for large repos
+    fsmonitor.builtin.auto-start=	Whether to start the builtin monitor daemon automatically when taking a snapshot.
+    fsmonitor.builtin.daemon-command=	Command that runs the builtin monitor daemon. Defaults to `jj debug fsmonitor daemon` run with the current executable.
     fsmonitor.watchman.register-snapshot-trigger=	Whether to use triggers to monitor for changes in the background.
     [EOF]
     ");
@@ -1905,6 +1938,75 @@ fn test_files() {
     ");
 }
 
+#[test]
+fn test_files_in_fileset_expression() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("lib/foo", "foo\n");
+    work_dir.write_file("lib/sub/bar", "bar\n");
+    work_dir.write_file("src/baz", "baz\n");
+    work_dir.run_jj(["status"]).success();
+
+    let output = work_dir.complete_fish(["file", "show", "src/baz | lib/"]);
+    insta::assert_snapshot!(output, @r"
+    src/baz | lib/foo
+    src/baz | lib/sub/
+    [EOF]
+    ");
+
+    let output = work_dir.complete_fish(["file", "show", "glob:li"]);
+    insta::assert_snapshot!(output, @r"
+    glob:lib/
+    [EOF]
+    ");
+
+    let output = work_dir.complete_fish(["diff", r#"~cwd:"lib/"#]);
+    insta::assert_snapshot!(output, @r#"
+    ~cwd:"lib/foo	Added
+    ~cwd:"lib/sub/
+    [EOF]
+    "#);
+
+    // Workspace-relative paths are completed from the root
+    let subdir = work_dir.dir("src");
+    let output = subdir.complete_fish(["file", "show", "root:li"]);
+    insta::assert_snapshot!(output, @r"
+    root:lib/
+    [EOF]
+    ");
+
+    let output = subdir.complete_fish(["diff", "baz & root-glob:lib/s"]);
+    insta::assert_snapshot!(output, @r"
+    baz & root-glob:lib/sub/
+    [EOF]
+    ");
+
+    let output = subdir.complete_fish(["file", "show", "ba"]);
+    insta::assert_snapshot!(output, @r"
+    baz
+    [EOF]
+    ");
+
+    // Pattern kinds and functions are completed if no paths match
+    let output = work_dir.complete_fish(["file", "show", "roo"]);
+    insta::assert_snapshot!(output, @r"
+    root:	Path relative to the workspace root
+    root-file:	Path relative to the workspace root
+    root-glob:	Path relative to the workspace root
+    root-glob-i:	Path relative to the workspace root
+    root-prefix-glob:	Path relative to the workspace root
+    root-prefix-glob-i:	Path relative to the workspace root
+    [EOF]
+    ");
+
+    let output = work_dir.complete_fish(["diff", "lib ~ ex"]);
+    insta::assert_snapshot!(output, @r"
+    lib ~ executable()	Matches executable files
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_command_alias_with_exec() {
     let test_env = TestEnvironment::default();
@@ -572,6 +572,27 @@ fn test_config_layer_workspace() {
     ui.editor = "main-repo"
     [EOF]
     "#);
+
+    // Workspace config overrides repo config only in that workspace
+    secondary_dir
+        .run_jj(["config", "set", "--workspace", config_key, "secondary-ws"])
+        .success();
+    let output = main_dir.run_jj(["config", "list", config_key]);
+    insta::assert_snapshot!(output, @r#"
+    ui.editor = "main-repo"
+    [EOF]
+    "#);
+    let output = secondary_dir.run_jj(["config", "list", config_key]);
+    insta::assert_snapshot!(output, @r#"
+    ui.editor = "secondary-ws"
+    [EOF]
+    "#);
+    let output = secondary_dir.run_jj(["config", "list", "--include-overridden", config_key]);
+    insta::assert_snapshot!(output, @r#"
+    # ui.editor = "main-repo"
+    ui.editor = "secondary-ws"
+    [EOF]
+    "#);
 }
 
 #[test]
@@ -1264,6 +1285,176 @@ fn test_config_edit_repo_outside_repo() {
     ");
 }
 
+#[test]
+fn test_config_doctor() {
+    let test_env = TestEnvironment::default();
+    let output = test_env.run_jj_in(".", ["config", "doctor"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    No problems found in config
+    [EOF]
+    ");
+
+    test_env.add_config(indoc! {"
+        git.auto-local-bookmark = true
+        [ui]
+        pagr = 'less'
+        conflict-marker-style = 'fancy'
+        log-word-wrap = 'yes'
+        [merge-tools.foo]
+        merge-args = ['$left', 2]
+        [revset-aliases]
+        'mine()' = 1
+        [[--scope]]
+        --when.commands = ['log']
+        [--scope.ui]
+        graph.stlye = 'ascii'
+    "});
+    let output = test_env.run_jj_in(".", ["config", "doctor", "--config=ui.editr=vim"]);
+    insta::assert_snapshot!(output, @r#"
+    user config $TEST_ENV/config/config0002.toml:
+      Deprecated setting: `git.auto-local-bookmark` is deprecated; use `remotes.<name>.auto-track-bookmarks` instead.
+        Example: jj config set --user remotes.origin.auto-track-bookmarks '*'
+        For details, see: https://docs.jj-vcs.dev/latest/config/#automatic-tracking-of-bookmarks
+      --scope.ui.graph.stlye: Unknown config key
+      merge-tools.foo.merge-args: Expected string, but found integer
+      revset-aliases."mine()": Expected string, but found integer
+      ui.conflict-marker-style: Expected one of "diff", "diff-experimental", "snapshot", "git", but found "fancy"
+      ui.log-word-wrap: Expected boolean, but found string
+      ui.pagr: Unknown config key
+    cli config:
+      ui.editr: Unknown config key
+    [EOF]
+    ------- stderr -------
+    Warning: Deprecated user-level config: `git.auto-local-bookmark` is deprecated; use `remotes.<name>.auto-track-bookmarks` instead.
+    Example: jj config set --user remotes.origin.auto-track-bookmarks '*'
+    For details, see: https://docs.jj-vcs.dev/latest/config/#automatic-tracking-of-bookmarks
+    Error: Found 8 problems in config
+    [EOF]
+    [exit status: 1]
+    "#);
+}
+
+#[test]
+fn test_config_repo_managed() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    // No file to trust
+    let output = work_dir.run_jj(["config", "trust"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No repo-managed config file found
+    Hint: The file should be located at `.config/jj/config.toml` in the workspace root.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    work_dir.create_dir_all(".config/jj");
+    work_dir.write_file(
+        ".config/jj/config.toml",
+        indoc! {"
+            [revset-aliases]
+            'stack(x)' = 'ancestors(x) & mutable()'
+        "},
+    );
+
+    // Untrusted file isn't loaded
+    let output = work_dir.run_jj(["config", "get", "revset-aliases.'stack(x)'"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: Ignoring untrusted repo config file $TEST_ENV/repo/.config/jj/config.toml
+    Hint: Review the file, then run `jj config trust` to load it.
+    Config error: Value not found for revset-aliases.'stack(x)'
+    For help, see https://docs.jj-vcs.dev/latest/config/ or use `jj help -k config`.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Declined at the prompt
+    let output = work_dir.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .args(["config", "get", "revset-aliases.'stack(x)'"])
+            .write_stdin("n\n")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: This repository has a config file at $TEST_ENV/repo/.config/jj/config.toml, which hasn't been trusted yet. Config can run arbitrary commands, e.g. through aliases.
+    Trust the file and load it? (yN): Warning: Ignoring untrusted repo config file $TEST_ENV/repo/.config/jj/config.toml
+    Hint: Review the file, then run `jj config trust` to load it.
+    Config error: Value not found for revset-aliases.'stack(x)'
+    For help, see https://docs.jj-vcs.dev/latest/config/ or use `jj help -k config`.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = work_dir.run_jj(["config", "trust"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: Ignoring untrusted repo config file $TEST_ENV/repo/.config/jj/config.toml
+    Hint: Review the file, then run `jj config trust` to load it.
+    Trusted config file $TEST_ENV/repo/.config/jj/config.toml
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-r", "stack(@)", "-T", "commit_id.short()"]);
+    insta::assert_snapshot!(output, @r"
+    @  909e12496940
+    │
+    ~
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "config",
+        "list",
+        "revset-aliases.'stack(x)'",
+        "-T",
+        "source ++ ' ' ++ path ++ '\n'",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    repo-managed $TEST_ENV/repo/.config/jj/config.toml
+    [EOF]
+    ");
+
+    // Changed file has to be trusted again
+    work_dir.write_file(
+        ".config/jj/config.toml",
+        indoc! {"
+            [revset-aliases]
+            'stack(x)' = 'x'
+        "},
+    );
+    let output = work_dir.run_jj(["config", "get", "revset-aliases.'stack(x)'"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: Ignoring untrusted repo config file $TEST_ENV/repo/.config/jj/config.toml
+    Hint: Review the file, then run `jj config trust` to load it.
+    Config error: Value not found for revset-aliases.'stack(x)'
+    For help, see https://docs.jj-vcs.dev/latest/config/ or use `jj help -k config`.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Accepted at the prompt
+    let output = work_dir.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .args(["config", "get", "revset-aliases.'stack(x)'"])
+            .write_stdin("y\n")
+    });
+    insta::assert_snapshot!(output, @r"
+    x
+    [EOF]
+    ------- stderr -------
+    Warning: This repository has a config file at $TEST_ENV/repo/.config/jj/config.toml, which hasn't been trusted yet. Config can run arbitrary commands, e.g. through aliases.
+    Trust the file and load it? (yN): [EOF]
+    ");
+    let output = work_dir.run_jj(["config", "get", "revset-aliases.'stack(x)'"]);
+    insta::assert_snapshot!(output, @r"
+    x
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_config_get() {
     let test_env = TestEnvironment::default();
@@ -253,6 +253,41 @@ fn test_debug_reindex() {
     ");
 }
 
+#[test]
+fn test_debug_compact_index() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.run_jj(["new"]).success();
+    work_dir.run_jj(["new"]).success();
+    let output = work_dir.run_jj(["debug", "compact-index"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Compacted index of 4 commits.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["debug", "index"]);
+    assert_snapshot!(filter_index_stats(output), @r"
+    === Commits ===
+    Number of commits: 4
+    Number of merges: 0
+    Max generation number: 3
+    Number of heads: 1
+    Number of changes: 4
+    Stats per level:
+      Level 0:
+        Number of commits: 3
+        Name: [hash]
+      Level 1:
+        Number of commits: 1
+        Name: [hash]
+    === Changed paths ===
+    Indexed commits: none
+    Stats per level:
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_debug_stacked_table() {
     let test_env = TestEnvironment::default();
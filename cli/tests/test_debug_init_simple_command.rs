@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use crate::common::TestEnvironment;
+use crate::common::to_toml_value;
 
 #[test]
 fn test_init_local() {
@@ -58,3 +59,158 @@ fn test_init_local() {
     [exit status: 2]
     ");
 }
+
+#[test]
+fn test_init_encrypted() {
+    let test_env = TestEnvironment::default();
+    let echo_path = assert_cmd::cargo::cargo_bin!("fake-echo");
+    let key_command = |key: &str| {
+        let command = toml_edit::Array::from_iter([echo_path.to_str().unwrap(), key]);
+        format!("--config=simple-backend.encryption-key-command={command}")
+    };
+
+    let output = test_env.run_jj_in(".", ["debug", "init-simple", "--encrypt", "repo1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Failed to access the repository
+    Caused by: `simple-backend.encryption-key-command` must be set to use an encrypted repo
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = test_env.run_jj_in(
+        ".",
+        [
+            "debug",
+            "init-simple",
+            "--encrypt",
+            "repo",
+            &key_command("secret"),
+        ],
+    );
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Initialized repo in "repo"
+    [EOF]
+    "#);
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("file", "sensitive contents\n");
+    let output = work_dir.run_jj(["file", "show", "file", &key_command("secret")]);
+    insta::assert_snapshot!(output, @r"
+    sensitive contents
+    [EOF]
+    ");
+    let files_dir = work_dir.root().join(".jj/repo/store/files");
+    for entry in std::fs::read_dir(files_dir).unwrap() {
+        let contents = std::fs::read(entry.unwrap().path()).unwrap();
+        assert!(!contents.windows(9).any(|w| w == b"sensitive"));
+    }
+
+    let output = work_dir.run_jj(["file", "show", "file", &key_command("other")]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: The encryption key doesn't match the key the repo was encrypted with
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["file", "show", "file"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: `simple-backend.encryption-key-command` must be set to use an encrypted repo
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_init_backend_plugin() {
+    let test_env = TestEnvironment::default();
+    let plugin_path = assert_cmd::cargo::cargo_bin!("fake-backend-plugin");
+    let plugin_path = to_toml_value(plugin_path.to_str().unwrap());
+
+    let output = test_env.run_jj_in(
+        ".",
+        ["debug", "init-simple", "--backend-plugin=fake", "repo1"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Failed to access the repository
+    Caused by: Backend plugin `fake` is not configured
+    [EOF]
+    [exit status: 1]
+    ");
+
+    test_env.add_config(format!("backend-plugins.fake.command = [{plugin_path}]"));
+    let output = test_env.run_jj_in(
+        ".",
+        ["debug", "init-simple", "--backend-plugin=fake", "repo"],
+    );
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Initialized repo in "repo"
+    [EOF]
+    "#);
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("file", "contents\n");
+    work_dir.run_jj(["commit", "-m", "first"]).success();
+    let output = work_dir.run_jj(["file", "show", "-r@-", "file"]);
+    insta::assert_snapshot!(output, @r"
+    contents
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-T", "description"]);
+    insta::assert_snapshot!(output, @r"
+    @
+    ○  first
+    ◆
+    [EOF]
+    ");
+    // The objects are stored by the plugin
+    let objects_dir = work_dir.root().join(".jj/repo/store/fake-plugin-objects");
+    let num_files = std::fs::read_dir(&objects_dir)
+        .unwrap()
+        .filter(|entry| {
+            let name = entry.as_ref().unwrap().file_name();
+            name.to_str().unwrap().starts_with("file-")
+        })
+        .count();
+    assert_eq!(num_files, 1);
+
+    // Objects which don't match their ID are rejected
+    let file_path = std::fs::read_dir(&objects_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| {
+            let name = path.file_name().unwrap().to_str().unwrap();
+            name.starts_with("file-")
+        })
+        .unwrap();
+    std::fs::write(&file_path, "tampered\n").unwrap();
+    let output = work_dir.run_jj(["file", "show", "-r@-", "file"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Internal error: Unexpected error from backend
+    Caused by:
+    1: Error when reading object 300da86d152b38e7263a4c6908e775ce262fd634f2bbdae1742daec259b06c81559b90c51c7dd050bee64aa0b4ecc79ab456976aafe1f1d24dde39924318c85c of type file
+    2: the data returned by the plugin doesn't match the object ID
+    [EOF]
+    [exit status: 255]
+    ");
+    std::fs::write(&file_path, "contents\n").unwrap();
+
+    // Errors from the plugin are reported
+    let output = work_dir.run_jj([
+        "describe",
+        "-m=second",
+        &format!("--config=backend-plugins.fake.command=[{plugin_path}, '--read-only']"),
+    ]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Internal error: Unexpected error from backend
+    Caused by:
+    1: Could not write object of type commit
+    2: the object store is read-only
+    [EOF]
+    [exit status: 255]
+    ");
+}
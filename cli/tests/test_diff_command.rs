@@ -669,6 +669,35 @@ fn test_diff_name_only() {
     ");
 }
 
+#[test]
+fn test_diff_by_predicate() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("deleted-large", "x".repeat(2000));
+    work_dir.write_file("grown", "small");
+    work_dir.write_file("unchanged-large", "x".repeat(2000));
+    work_dir.run_jj(["new"]).success();
+    work_dir.remove_file("deleted-large");
+    work_dir.write_file("grown", "x".repeat(2000));
+    work_dir.write_file("added-small", "small");
+
+    // Files match if they satisfy the predicate on either side
+    let output = work_dir.run_jj(["diff", "--summary", r#"size(">1KiB")"#]);
+    insta::assert_snapshot!(output, @r"
+    D deleted-large
+    M grown
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["diff", "--summary", "size(..1KiB)"]);
+    insta::assert_snapshot!(output, @r"
+    A added-small
+    M grown
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_diff_renamed_file_and_dir() {
     let test_env = TestEnvironment::default();
@@ -1981,6 +2010,229 @@ fn test_diff_ignore_whitespace() {
     ");
 }
 
+#[test]
+fn test_diff_ignore_blank_lines() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file(
+        "file1",
+        indoc! {"
+            a
+            b
+
+            c
+            d
+            e
+            f
+            g
+            h
+        "},
+    );
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file(
+        "file1",
+        indoc! {"
+
+            a
+            b
+            c
+            d
+            e
+            f
+
+            G
+            h
+        "},
+    );
+
+    // Git diff as reference output
+    let output = work_dir.run_jj(["diff", "--git"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index 4f0fa33b01..a39124d496 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,9 +1,10 @@
+    +
+     a
+     b
+    -
+     c
+     d
+     e
+     f
+    -g
+    +
+    +G
+     h
+    [EOF]
+    ");
+
+    // Blank line changes are hidden unless they are in the context of other
+    // changes
+    let output = work_dir.run_jj(["diff", "--git", "--ignore-blank-lines", "--context=1"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index 4f0fa33b01..a39124d496 100644
+    --- a/file1
+    +++ b/file1
+    @@ -7,3 +7,4 @@
+     f
+    -g
+    +
+    +G
+     h
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "diff",
+        "--color-words",
+        "--ignore-blank-lines",
+        "--context=1",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    Modified regular file file1:
+        ...
+       7    7: f
+       8    8: g
+            9: G
+       9   10: h
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["diff", "--stat", "--ignore-blank-lines"]);
+    insta::assert_snapshot!(output, @r"
+    file1 | 3 ++-
+    1 file changed, 2 insertions(+), 1 deletion(-)
+    [EOF]
+    ");
+
+    // Can be combined with whitespace-ignoring options
+    work_dir.write_file(
+        "file1",
+        indoc! {"
+            a
+              b
+            \t
+            c
+            d
+            e
+            f
+            g
+            h
+        "},
+    );
+    let output = work_dir.run_jj([
+        "log",
+        "-r@",
+        "-p",
+        "-Tdescription",
+        "--git",
+        "-w",
+        "--ignore-blank-lines",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    @  diff --git a/file1 b/file1
+    │  index 4f0fa33b01..498160d913 100644
+    ~  --- a/file1
+       +++ b/file1
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_diff_algorithm() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "x\ny\nx\ny\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file1", "y\nx\ny\nx\n");
+
+    // The histogram algorithm anchors on the "x" lines
+    let output = work_dir.run_jj(["diff", "--git"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index 9ac7ee7ff4..2d4069c548 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,4 +1,4 @@
+    +y
+     x
+    -y
+    -x
+     y
+    +x
+    [EOF]
+    ");
+
+    // The patience algorithm has no unique lines to anchor on, so it finds
+    // the minimal diff
+    let output = work_dir.run_jj(["diff", "--git", "--algorithm=patience"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file1 b/file1
+    index 9ac7ee7ff4..2d4069c548 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,4 +1,4 @@
+    -x
+     y
+     x
+     y
+    +x
+    [EOF]
+    ");
+
+    // The algorithm can be set in the config, and is used by `jj log -p`
+    let output = work_dir.run_jj([
+        "log",
+        "-r@",
+        "-p",
+        "--git",
+        "-Tdescription",
+        "--config=diff.algorithm=minimal",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    @  diff --git a/file1 b/file1
+    │  index 9ac7ee7ff4..2d4069c548 100644
+    ~  --- a/file1
+       +++ b/file1
+       @@ -1,4 +1,4 @@
+       -x
+        y
+        x
+        y
+       +x
+    [EOF]
+    ");
+
+    // The flag overrides the config
+    let output = work_dir.run_jj([
+        "diff",
+        "--stat",
+        "--algorithm=histogram",
+        "--config=diff.algorithm=minimal",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    file1 | 4 ++--
+    1 file changed, 2 insertions(+), 2 deletions(-)
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["diff", "--algorithm=myers"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: invalid value 'myers' for '--algorithm <ALGORITHM>'
+      [possible values: histogram, patience, minimal]
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 #[test]
 fn test_diff_skipped_context() {
     let test_env = TestEnvironment::default();
@@ -3863,6 +4115,34 @@ fn test_diff_binary() {
     ");
 }
 
+#[test]
+fn test_diff_large_binary() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    // Only the start of binary files is read, so the change is past it
+    let contents = [b"\0".as_slice(), &[b'a'; 10_000]].concat();
+    work_dir.write_file("binary.png", &contents);
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("binary.png", [&contents[..], &[b'b'; 100]].concat());
+
+    let output = work_dir.run_jj(["diff", "--git"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/binary.png b/binary.png
+    index 2c25c6fcc2..718b22fd01 100644
+    Binary files a/binary.png and b/binary.png differ
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["diff", "--stat"]);
+    insta::assert_snapshot!(output, @r"
+    binary.png | (binary) +100 bytes
+    1 file changed, 0 insertions(+), 0 deletions(-)
+    [EOF]
+    ");
+}
+
 /// Test diff --stat output width for diffs that have different cases of right
 /// side text: solely "(binary)", a mixture of text and binary diffs, and binary
 /// size changes.
@@ -93,3 +93,64 @@ fn test_file_list() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_file_list_by_predicate() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.create_dir("dir");
+    work_dir.write_file("dir/large", "x".repeat(2000));
+    work_dir.write_file("dir/small", "small");
+    work_dir.write_file("exec-file", "content");
+    work_dir
+        .run_jj(["file", "chmod", "x", "exec-file"])
+        .success();
+
+    let output = work_dir.run_jj(["file", "list", r#"size(">1KiB")"#]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r"
+    dir/large
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "list", "dir & size(..1k)"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r"
+    dir/small
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "list", "executable()"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r"
+    exec-file
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "list", "~executable()"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r"
+    dir/large
+    dir/small
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["file", "list", "size(1TiB)"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r"
+    ------- stderr -------
+    Error: Failed to parse fileset: Invalid size range `1TiB`
+    Caused by:  --> 1:6
+      |
+    1 | size(1TiB)
+      |      ^--^
+      |
+      = Invalid size range `1TiB`
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Not supported by commands which only match paths
+    let output = work_dir.run_jj(["file", "show", "executable()"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r"
+    ------- stderr -------
+    Error: Predicates on file properties aren't supported by this command
+    Hint: Use functions such as `size()` with `jj diff`, `jj file list`, or `jj split` instead.
+    [EOF]
+    [exit status: 1]
+    ");
+}
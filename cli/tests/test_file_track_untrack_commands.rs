@@ -297,6 +297,58 @@ fn test_track_ignored_with_flag() {
     ");
 }
 
+#[test]
+fn test_track_ignored_with_force() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file(".gitignore", "*.ignored\n");
+    work_dir.write_file("file1.ignored", "ignored content");
+    work_dir.write_file("file2.ignored", "ignored content");
+
+    // Track ignored files with --force
+    let output = work_dir.run_jj(["file", "track", "--force", "file1.ignored", "file2.ignored"]);
+    insta::assert_snapshot!(output, @"");
+    let output = work_dir.run_jj(["file", "list"]);
+    insta::assert_snapshot!(output, @r"
+    .gitignore
+    file1.ignored
+    file2.ignored
+    [EOF]
+    ");
+
+    // The files are tracked again when they're re-created
+    work_dir.remove_file("file1.ignored");
+    let output = work_dir.run_jj(["file", "list"]);
+    insta::assert_snapshot!(output, @r"
+    .gitignore
+    file2.ignored
+    [EOF]
+    ");
+    work_dir.write_file("file1.ignored", "new content");
+    let output = work_dir.run_jj(["file", "list"]);
+    insta::assert_snapshot!(output, @r"
+    .gitignore
+    file1.ignored
+    file2.ignored
+    [EOF]
+    ");
+
+    // Untracking a file removes it from the forced paths
+    let output = work_dir.run_jj(["file", "untrack", "file1.ignored"]);
+    insta::assert_snapshot!(output, @"");
+    work_dir.remove_file("file1.ignored");
+    work_dir.run_jj(["file", "list"]).success();
+    work_dir.write_file("file1.ignored", "new content");
+    let output = work_dir.run_jj(["file", "list"]);
+    insta::assert_snapshot!(output, @r"
+    .gitignore
+    file2.ignored
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_track_large_file_with_flag() {
     let test_env = TestEnvironment::default();
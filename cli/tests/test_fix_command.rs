@@ -1103,6 +1103,105 @@ fn test_deduplication() {
     ");
 }
 
+#[test]
+fn test_fix_cache() {
+    // Log the tool's output outside the workspace, so it isn't snapshotted.
+    let mut test_env = TestEnvironment::default();
+    set_up_fake_formatter(&mut test_env, &["--uppercase", "--tee", "../fixlog"]);
+    test_env.add_config("fix.tools.fake-formatter.cache = true");
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file", "foo\n");
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "a"])
+        .success();
+    work_dir.run_jj(["new", "root()"]).success();
+    work_dir.write_file("file", "foo\n");
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "b"])
+        .success();
+
+    work_dir.run_jj(["fix", "-s", "a"]).success();
+    let log = std::fs::read_to_string(test_env.env_root().join("fixlog")).unwrap();
+    insta::assert_snapshot!(log, @"FOO");
+
+    // The same content in another revision is fixed using the cached output,
+    // without running the tool.
+    let output = work_dir.run_jj(["fix", "-s", "b"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Fixed 1 commits of 1 checked.
+    Working copy  (@) now at: kkmpptxz fe6d74f6 b | (no description set)
+    Parent commit (@-)      : zzzzzzzz 00000000 (empty) (no description set)
+    Added 0 files, modified 1 files, removed 0 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "file", "-r", "b"]);
+    insta::assert_snapshot!(output, @r"
+    FOO
+    [EOF]
+    ");
+    let log = std::fs::read_to_string(test_env.env_root().join("fixlog")).unwrap();
+    insta::assert_snapshot!(log, @"FOO");
+
+    // The tool is run again with --no-cache.
+    work_dir.run_jj(["new", "root()"]).success();
+    work_dir.write_file("file", "foo\n");
+    let output = work_dir.run_jj(["fix", "-s", "@", "--no-cache"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Fixed 1 commits of 1 checked.
+    Working copy  (@) now at: vruxwmqv b4da2ac3 (no description set)
+    Parent commit (@-)      : zzzzzzzz 00000000 (empty) (no description set)
+    Added 0 files, modified 1 files, removed 0 files
+    [EOF]
+    ");
+    let log = std::fs::read_to_string(test_env.env_root().join("fixlog")).unwrap();
+    insta::assert_snapshot!(log, @r"
+    FOO
+    FOO
+    ");
+}
+
+#[test]
+fn test_fix_cache_inputs_and_gc() {
+    let mut test_env = TestEnvironment::default();
+    set_up_fake_formatter(&mut test_env, &["--uppercase", "--tee", "../fixlog"]);
+    test_env.add_config(indoc! {"
+        [fix.tools.fake-formatter]
+        patterns = ['file']
+        cache = true
+        cache-inputs = ['fmt.toml']
+    "});
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let fix_in_new_commit = |config: &str| {
+        work_dir.run_jj(["new", "root()"]).success();
+        work_dir.write_file("file", "foo\n");
+        work_dir.write_file("fmt.toml", config);
+        work_dir.run_jj(["fix", "-s", "@"]).success();
+        std::fs::read_to_string(test_env.env_root().join("fixlog")).unwrap()
+    };
+
+    insta::assert_snapshot!(fix_in_new_commit("a"), @"FOO");
+    // The cached output is used while the inputs are unchanged.
+    insta::assert_snapshot!(fix_in_new_commit("a"), @"FOO");
+    // The tool is run again after its config file changed.
+    insta::assert_snapshot!(fix_in_new_commit("b"), @r"
+    FOO
+    FOO
+    ");
+
+    // Expired entries are removed by gc.
+    work_dir.run_jj(["util", "gc", "--expire", "now"]).success();
+    insta::assert_snapshot!(fix_in_new_commit("b"), @r"
+    FOO
+    FOO
+    FOO
+    ");
+}
+
 fn sorted_lines(path: PathBuf) -> String {
     let mut log: Vec<_> = std::fs::read_to_string(path.as_os_str())
         .unwrap()
@@ -1629,3 +1728,117 @@ fn test_all_files() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_fix_json_output() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let formatter_path = assert_cmd::cargo::cargo_bin!("fake-formatter");
+    assert!(formatter_path.is_file());
+    let formatter = to_toml_value(formatter_path.to_str().unwrap());
+    let json = to_toml_value(
+        r#"{"content": "class A\n", "rename": "src/a.txt", "create": {"src/b.txt": "class B\n"}}"#,
+    );
+    test_env.add_config(format!(
+        r###"
+        [fix.tools.codemod]
+        command = [{formatter}, "--stdout", {json}]
+        patterns = ["classes.txt"]
+        output = "json"
+        "###,
+    ));
+
+    work_dir.write_file("classes.txt", "class A\nclass B\n");
+    work_dir.write_file("other.txt", "other\n");
+    let output = work_dir.run_jj(["fix"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Fixed 1 commits of 1 checked.
+    Working copy  (@) now at: qpvuntsm 7bce7706 (no description set)
+    Parent commit (@-)      : zzzzzzzz 00000000 (empty) (no description set)
+    Added 2 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "list"]);
+    insta::assert_snapshot!(output, @"
+    other.txt
+    src/a.txt
+    src/b.txt
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "src/a.txt", "src/b.txt"]);
+    insta::assert_snapshot!(output, @"
+    class A
+    class B
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_fix_json_output_conflicts_with_existing_path() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let formatter_path = assert_cmd::cargo::cargo_bin!("fake-formatter");
+    assert!(formatter_path.is_file());
+    let formatter = to_toml_value(formatter_path.to_str().unwrap());
+    test_env.add_config(format!(
+        r###"
+        [fix.tools.codemod]
+        command = [{formatter}, "--stdout", '{{"rename": "existing"}}']
+        patterns = ["file"]
+        output = "json"
+        "###,
+    ));
+
+    work_dir.write_file("file", "content\n");
+    work_dir.write_file("existing", "existing\n");
+    let output = work_dir.run_jj(["fix"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Cannot write fixed file to existing in commit 3387790e9b65189c78bacf914dbc56f0081d7b53: path already exists
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_fix_json_output_invalid() {
+    let mut test_env = TestEnvironment::default();
+    let formatter_path = assert_cmd::cargo::cargo_bin!("fake-formatter");
+    assert!(formatter_path.is_file());
+    let formatter = to_toml_value(formatter_path.to_str().unwrap());
+    test_env.add_paths_to_normalize(formatter_path, "$FAKE_FORMATTER_PATH");
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    test_env.add_config(format!(
+        r###"
+        [fix.tools.bad-json]
+        command = [{formatter}, "--stdout", "not json"]
+        patterns = ["file"]
+        output = "json"
+
+        [fix.tools.bad-path]
+        command = [{formatter}, "--stdout", '{{"rename": "../outside"}}']
+        patterns = ["file"]
+        output = "json"
+        "###,
+    ));
+
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("file", "content\n");
+    let output = work_dir.run_jj(["fix"]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Warning: Fix tool `$FAKE_FORMATTER_PATH` produced invalid output for `file`: expected ident at line 1 column 2
+    Warning: Fix tool `$FAKE_FORMATTER_PATH` produced invalid output for `file`: Invalid repository path "../outside"
+    Fixed 0 commits of 1 checked.
+    Nothing changed.
+    [EOF]
+    "#);
+    let output = work_dir.run_jj(["file", "list"]);
+    insta::assert_snapshot!(output, @"
+    file
+    [EOF]
+    ");
+}
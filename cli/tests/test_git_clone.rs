@@ -1080,6 +1080,85 @@ fn test_git_clone_with_depth() {
     ");
 }
 
+#[test]
+fn test_git_clone_with_filter() {
+    let test_env = TestEnvironment::default();
+    let root_dir = test_env.work_dir("");
+    test_env.add_config("remotes.origin.auto-track-bookmarks = '*'");
+    let clone_dir = test_env.work_dir("clone");
+    let git_repo_path = test_env.env_root().join("source");
+    let git_repo = git::init(&git_repo_path);
+    let commit1 = git::add_commit(&git_repo, "refs/heads/main", "file", b"1\n", "1", &[]);
+    git::add_commit(
+        &git_repo,
+        "refs/heads/main",
+        "file",
+        b"2\n",
+        "2",
+        &[commit1.commit_id],
+    );
+    git::set_symbolic_reference(&git_repo, "HEAD", "refs/heads/main");
+    let run_git = |dir: &path::Path, args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        String::from_utf8(output.stdout).unwrap()
+    };
+    run_git(
+        &git_repo_path,
+        &["config", "uploadpack.allowFilter", "true"],
+    );
+
+    let output = root_dir.run_jj([
+        "git",
+        "clone",
+        "--colocate",
+        "--filter=blob:none",
+        "source",
+        "clone",
+    ]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Fetching into new repo in "$TEST_ENV/clone"
+    bookmark: main@origin [new] tracked
+    Setting the revset alias `trunk()` to `main@origin`
+    Working copy  (@) now at: sqpuoqvx 096aec5c (empty) (no description set)
+    Parent commit (@-)      : lwrptnvv f8477956 main | 2
+    Added 1 files, modified 0 files, removed 0 files
+    Hint: Running `git clean -xdf` will remove `.jj/`!
+    [EOF]
+    "#);
+
+    // Only the blob checked out in the working copy has been fetched
+    let missing_objects = || {
+        let args = ["rev-list", "--objects", "--missing=print", "--all"];
+        run_git(clone_dir.root(), &args)
+            .lines()
+            .filter_map(|line| line.strip_prefix('?'))
+            .map(|oid| oid.to_owned())
+            .collect::<Vec<_>>()
+    };
+    let old_blob = run_git(&git_repo_path, &["rev-parse", "main~:file"]);
+    assert_eq!(missing_objects(), [old_blob.trim_end()]);
+
+    // Missing blobs are fetched when they're read
+    let output = clone_dir.run_jj(["diff", "--git", "-r=main"]);
+    insta::assert_snapshot!(output, @"
+    diff --git a/file b/file
+    index d00491fd7e..0cfbf08886 100644
+    --- a/file
+    +++ b/file
+    @@ -1,1 +1,1 @@
+    -1
+    +2
+    [EOF]
+    ");
+    assert!(missing_objects().is_empty());
+}
+
 #[test]
 fn test_git_clone_invalid_immutable_heads() {
     let test_env = TestEnvironment::default();
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use testutils::git;
+
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 use crate::common::TestWorkDir;
@@ -134,6 +136,59 @@ fn test_git_colocation_enable_empty() {
     assert_eq!(setup_op_id, work_dir.current_operation_id());
 }
 
+#[test]
+fn test_git_colocation_enable_exports_bookmarks() {
+    let test_env = TestEnvironment::default();
+
+    // Initialize a non-colocated Jujutsu/Git workspace
+    test_env
+        .run_jj_in(
+            test_env.env_root(),
+            ["git", "init", "--no-colocate", "repo"],
+        )
+        .success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.run_jj(["describe", "-m", "first"]).success();
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "first"])
+        .success();
+
+    // Bookmarks haven't been exported to the internal Git repository
+    let output = work_dir.run_jj(["bookmark", "list", "--all-remotes"]);
+    insta::assert_snapshot!(output, @r"
+    first: qpvuntsm 68a50538 (empty) first
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["git", "colocation", "enable"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Workspace successfully converted into a colocated Jujutsu/Git workspace.
+    [EOF]
+    ");
+
+    // The bookmarks are now in the colocated Git repository
+    let git_repo = git::open(work_dir.root());
+    let refs = git_repo
+        .references()
+        .unwrap()
+        .local_branches()
+        .unwrap()
+        .map(|r| r.unwrap().name().as_bstr().to_string())
+        .collect::<Vec<_>>();
+    insta::assert_debug_snapshot!(refs, @r#"
+    [
+        "refs/heads/first",
+    ]
+    "#);
+    let output = work_dir.run_jj(["bookmark", "list", "--all-remotes"]);
+    insta::assert_snapshot!(output, @r"
+    first: qpvuntsm 68a50538 (empty) first
+      @git: qpvuntsm 68a50538 (empty) first
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_git_colocation_enable_already_colocated() {
     let test_env = TestEnvironment::default();
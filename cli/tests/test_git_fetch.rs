@@ -648,6 +648,59 @@ fn test_git_fetch_prune_before_updating_tips() {
     ");
 }
 
+#[test]
+fn test_git_fetch_protected_bookmark() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("remotes.origin.auto-track-bookmarks = '*'");
+    test_env.add_config("bookmarks.protect = ['origin']");
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let git_repo = add_git_remote(&test_env, &work_dir, "origin");
+    work_dir.run_jj(["git", "fetch"]).success();
+
+    // The protected bookmark follows the remote even if it's moved sideways
+    git_repo
+        .find_reference("refs/heads/origin")
+        .unwrap()
+        .delete()
+        .unwrap();
+    git::add_commit(
+        &git_repo,
+        "refs/heads/origin",
+        "origin",
+        b"rewritten",
+        "rewritten",
+        &[],
+    );
+    let output = work_dir.run_jj(["git", "fetch"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    bookmark: origin@origin [updated] tracked
+    Abandoned 1 commits that are no longer reachable.
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_bookmark_output(&work_dir), @r"
+    origin: uvnxsytk 7d21c838 rewritten
+      @origin: uvnxsytk 7d21c838 rewritten
+    [EOF]
+    ");
+
+    // ...or deleted
+    git_repo
+        .find_reference("refs/heads/origin")
+        .unwrap()
+        .delete()
+        .unwrap();
+    let output = work_dir.run_jj(["git", "fetch"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    bookmark: origin@origin [deleted] untracked
+    Abandoned 1 commits that are no longer reachable.
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_bookmark_output(&work_dir), @"");
+}
+
 #[test]
 fn test_git_fetch_conflicting_bookmarks() {
     let test_env = TestEnvironment::default();
@@ -2177,3 +2230,67 @@ fn test_git_fetch_auto_track_bookmarks() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_git_fetch_deepen_and_unshallow() {
+    let test_env = TestEnvironment::default();
+    let root_dir = test_env.work_dir("");
+    test_env.add_config("remotes.origin.auto-track-bookmarks = '*'");
+    let git_repo = git::init(test_env.env_root().join("source"));
+    add_commit_to_branch(&git_repo, "main", "1");
+    add_commit_to_branch(&git_repo, "main", "2");
+    add_commit_to_branch(&git_repo, "main", "3");
+    add_commit_to_branch(&git_repo, "main", "4");
+    root_dir
+        .run_jj(["git", "clone", "--depth=1", "source", "clone"])
+        .success();
+    let work_dir = test_env.work_dir("clone");
+    insta::assert_snapshot!(get_log_output(&work_dir), @r#"
+    @  e8849ae12c70 ""
+    │ ◆  b85c69e0ca5c "4" main
+    ├─╯
+    ◆  000000000000 ""
+    [EOF]
+    "#);
+
+    // The parent of the previously shallow commit is fetched and indexed
+    let output = work_dir.run_jj(["git", "fetch", "--deepen=1"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @r#"
+    @  e8849ae12c70 ""
+    │ ◆  b85c69e0ca5c "4" main
+    │ ◆  b45f087335a8 "3"
+    ├─╯
+    ◆  000000000000 ""
+    [EOF]
+    "#);
+
+    let output = work_dir.run_jj(["git", "fetch", "--unshallow"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @r#"
+    @  e8849ae12c70 ""
+    │ ◆  b85c69e0ca5c "4" main
+    │ ◆  b45f087335a8 "3"
+    │ ◆  654f6f497e66 "2"
+    │ ◆  1ebd2334e4b5 "1"
+    ├─╯
+    ◆  000000000000 ""
+    [EOF]
+    "#);
+
+    // Unshallowing a complete repository is a no-op
+    let output = work_dir.run_jj(["git", "fetch", "--unshallow"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+}
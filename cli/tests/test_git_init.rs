@@ -43,6 +43,19 @@ fn init_git_repo(git_repo_path: &Path, bare: bool) -> gix::Repository {
     git_repo
 }
 
+/// Enables a repository format extension the way `git init` would. gix can't
+/// create repos using other object or ref storage formats.
+fn set_git_extension(git_dir: &Path, name: &str, value: &str) {
+    let config_path = git_dir.join("config");
+    let config = std::fs::read_to_string(&config_path).unwrap();
+    let config = config.replace("repositoryformatversion = 0", "repositoryformatversion = 1");
+    std::fs::write(
+        &config_path,
+        format!("{config}[extensions]\n\t{name} = {value}\n"),
+    )
+    .unwrap();
+}
+
 #[must_use]
 fn get_bookmark_output(work_dir: &TestWorkDir) -> CommandOutput {
     work_dir.run_jj(["bookmark", "list", "--all-remotes"])
@@ -375,6 +388,42 @@ fn test_git_init_external_non_existent_git_directory() {
     assert!(!jj_path.exists());
 }
 
+#[test]
+fn test_git_init_external_sha256() {
+    let test_env = TestEnvironment::default();
+    let git_repo_path = test_env.env_root().join("git-repo");
+    git::init(&git_repo_path);
+    set_git_extension(&git_repo_path.join(".git"), "objectFormat", "sha256");
+
+    let output = test_env.run_jj_in(".", ["git", "init", "repo", "--git-repo", "git-repo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Failed to access the repository
+    Caused by: Git object format 'sha256' is not supported
+    [EOF]
+    [exit status: 1]
+    ");
+    assert!(!test_env.env_root().join("repo").join(".jj").exists());
+}
+
+#[test]
+fn test_git_init_colocated_reftable() {
+    let test_env = TestEnvironment::default();
+    let work_dir = test_env.work_dir("repo");
+    init_git_repo(work_dir.root(), false);
+    set_git_extension(&work_dir.root().join(".git"), "refStorage", "reftable");
+
+    let output = test_env.run_jj_in(".", ["git", "init", "--colocate", "repo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Failed to access the repository
+    Caused by: Git ref storage format 'reftable' is not supported
+    [EOF]
+    [exit status: 1]
+    ");
+    assert!(!work_dir.root().join(".jj").exists());
+}
+
 #[test]
 fn test_git_init_colocated_via_git_repo_path() {
     let test_env = TestEnvironment::default();
@@ -1789,6 +1789,55 @@ fn test_git_push_deleted() {
     ");
 }
 
+#[test]
+fn test_git_push_protected() {
+    let test_env = TestEnvironment::default();
+    set_up(&test_env);
+    test_env.add_config("bookmarks.protect = ['bookmark*']");
+    let work_dir = test_env.work_dir("local");
+
+    work_dir
+        .run_jj(["bookmark", "delete", "--allow-protected", "bookmark1"])
+        .success();
+    work_dir
+        .run_jj([
+            "bookmark",
+            "set",
+            "-r=bookmark1@origin",
+            "-B",
+            "--allow-protected",
+            "bookmark2",
+        ])
+        .success();
+
+    let output = work_dir.run_jj(["git", "push", "--deleted"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Refusing to delete protected bookmark: bookmark1
+    Hint: Use --allow-protected to allow it.
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["git", "push", "-b=bookmark2"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Refusing to move protected bookmark backwards or sideways: bookmark2
+    Hint: Use --allow-protected to allow it.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = work_dir.run_jj(["git", "push", "--tracked", "--allow-protected"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: Refusing to push deleted bookmark bookmark1
+    Hint: Push deleted bookmarks with --deleted or forget the bookmark to suppress this warning.
+    Changes to push to origin:
+      Move sideways bookmark bookmark2 from 38a204733702 to 9b2e76de3920
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_git_push_conflicting_bookmarks() {
     let test_env = TestEnvironment::default();
@@ -2319,6 +2368,81 @@ fn test_git_push_rejected_by_remote() {
     });
 }
 
+#[test]
+fn test_git_push_options() {
+    let test_env = TestEnvironment::default();
+    set_up(&test_env);
+    let origin_dir = test_env.work_dir("origin");
+    let origin_git_repo_path = git_repo_dir_for_jj_repo(&origin_dir);
+    let work_dir = test_env.work_dir("local");
+    work_dir.run_jj(["describe", "-m=foo"]).success();
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "feature"])
+        .success();
+
+    // The remote doesn't accept push options by default
+    let output = work_dir.run_jj(["git", "push", "-bfeature", "--push-option=ci.skip"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Add bookmark feature to 6c030d44a076
+    Error: Git process failed: External git program failed:
+    fatal: the receiving end does not support push options
+    fatal: the remote end hung up unexpectedly
+
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Print the received push options from a hook on the remote
+    let mut config = std::fs::read_to_string(origin_git_repo_path.join("config")).unwrap();
+    config.push_str("[receive]\n\tadvertisePushOptions = true\n");
+    std::fs::write(origin_git_repo_path.join("config"), config).unwrap();
+    let hook_path = origin_git_repo_path.join("hooks").join("pre-receive");
+    std::fs::write(
+        &hook_path,
+        indoc! {r#"
+            #!/bin/sh
+            i=0
+            while [ "$i" -lt "${GIT_PUSH_OPTION_COUNT:-0}" ]; do
+                eval "echo option: \$GIT_PUSH_OPTION_$i"
+                i=$((i + 1))
+            done
+        "#},
+    )
+    .unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o700)).unwrap();
+    }
+
+    // Options from the config are sent before the ones from the command line
+    test_env.add_config(r#"git.push-options = ["ci.skip"]"#);
+    let output = work_dir.run_jj([
+        "git",
+        "push",
+        "-bfeature",
+        "-o",
+        "merge_request.create",
+        "--push-option=merge_request.title=Add foo",
+    ]);
+    let mut settings = insta::Settings::clone_current();
+    settings.add_filter(r"\s*\n", "\n");
+    settings.bind(|| {
+        insta::assert_snapshot!(output, @"
+        ------- stderr -------
+        Changes to push to origin:
+          Add bookmark feature to 6c030d44a076
+        remote: option: ci.skip
+        remote: option: merge_request.create
+        remote: option: merge_request.title=Add foo
+        [EOF]
+        ");
+    });
+}
+
 #[test]
 fn test_git_push_unmapped_refs() {
     let test_env = TestEnvironment::default();
@@ -2405,3 +2529,86 @@ fn get_bookmark_output(work_dir: &TestWorkDir) -> CommandOutput {
     // --quiet to suppress deleted bookmarks hint
     work_dir.run_jj(["bookmark", "list", "--all-remotes", "--quiet"])
 }
+
+#[test]
+fn test_git_push_tags() {
+    let test_env = TestEnvironment::default();
+    set_up(&test_env);
+    let origin_dir = test_env.work_dir("origin");
+    let origin_git_repo = git::open(git_repo_dir_for_jj_repo(&origin_dir));
+    let work_dir = test_env.work_dir("local");
+    work_dir
+        .run_jj(["tag", "create", "-rbookmark1", "-mRelease 1", "v1"])
+        .success();
+    work_dir
+        .run_jj(["tag", "create", "-rbookmark2", "light"])
+        .success();
+
+    let output = work_dir.run_jj(["git", "push", "--tag=v1", "--tag=unknown", "--dry-run"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Warning: No matching tags for names: unknown
+    Changes to push to origin:
+      Add tag v1 to 9b2e76de3920
+    Dry-run requested, not pushing.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["git", "push", "--tag=glob:*"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Add tag light to 38a204733702
+      Add tag v1 to 9b2e76de3920
+    [EOF]
+    ");
+
+    // The annotated tag is pushed as annotated tag
+    let kind_of = |name: &str| {
+        let git_ref = origin_git_repo.find_reference(name).unwrap();
+        git_ref.id().object().unwrap().kind
+    };
+    assert_eq!(kind_of("refs/tags/v1"), gix::object::Kind::Tag);
+    assert_eq!(kind_of("refs/tags/light"), gix::object::Kind::Commit);
+
+    // Pushing the same tags again is a no-op
+    let output = work_dir.run_jj(["git", "push", "--tag=v1"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Add tag v1 to 9b2e76de3920
+    [EOF]
+    ");
+
+    // Existing tags on the remote aren't overwritten
+    work_dir
+        .run_jj(["tag", "set", "--allow-move", "-rbookmark2", "v1"])
+        .success();
+    let output = work_dir.run_jj(["git", "push", "--tag=v1"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Add tag v1 to 38a204733702
+    Warning: The following references unexpectedly moved on the remote:
+      refs/tags/v1 (reason: stale info)
+    Hint: Try fetching from the remote, then make the bookmark point to where you want it to be, and push again.
+    Error: Failed to push some tags
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = work_dir.run_jj(["git", "push", "--tag=v1", "--bookmark=bookmark1"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    error: the argument '--tag <TAG>' cannot be used with:
+      --bookmark <BOOKMARK>
+      --change <REVSETS>
+      --revisions <REVSETS>
+      --named <NAME=REVISION>
+
+    Usage: jj git push --tag <TAG> --bookmark <BOOKMARK>
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
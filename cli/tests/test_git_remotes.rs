@@ -418,6 +418,181 @@ fn test_git_remote_set_url() {
     "#);
 }
 
+#[test]
+fn test_git_remote_set_url_multiple() {
+    let test_env = TestEnvironment::default();
+
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir
+        .run_jj(["git", "remote", "add", "foo", "http://example.com/repo/foo"])
+        .success();
+
+    // Add a push URL and a mirror push URL
+    work_dir
+        .run_jj([
+            "git",
+            "remote",
+            "set-url",
+            "foo",
+            "--add",
+            "--push",
+            "http://example.com/repo/push1",
+        ])
+        .success();
+    work_dir
+        .run_jj([
+            "git",
+            "remote",
+            "set-url",
+            "foo",
+            "--add",
+            "--push",
+            "http://example.com/repo/push2",
+        ])
+        .success();
+    // Add a second fetch URL
+    work_dir
+        .run_jj([
+            "git",
+            "remote",
+            "set-url",
+            "foo",
+            "--add",
+            "http://example.com/repo/foo2",
+        ])
+        .success();
+    insta::assert_snapshot!(read_git_config(work_dir.root()), @r#"
+    [core]
+    	repositoryformatversion = 0
+    	bare = true
+    	logallrefupdates = false
+    [remote "foo"]
+    	fetch = +refs/heads/*:refs/remotes/foo/*
+    	url = http://example.com/repo/foo
+    	url = http://example.com/repo/foo2
+    	pushurl = http://example.com/repo/push1
+    	pushurl = http://example.com/repo/push2
+    "#);
+    let output = work_dir.run_jj(["git", "remote", "list"]);
+    insta::assert_snapshot!(output, @r"
+    foo http://example.com/repo/foo (push: http://example.com/repo/push1, http://example.com/repo/push2)
+    [EOF]
+    ");
+
+    // Adding an existing URL fails
+    let output = work_dir.run_jj([
+        "git",
+        "remote",
+        "set-url",
+        "foo",
+        "--add",
+        "--push",
+        "http://example.com/repo/push1",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Git remote named 'foo' already has URL http://example.com/repo/push1
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Renaming the remote keeps all URLs
+    work_dir
+        .run_jj(["git", "remote", "rename", "foo", "bar"])
+        .success();
+    insta::assert_snapshot!(read_git_config(work_dir.root()), @r#"
+    [core]
+    	repositoryformatversion = 0
+    	bare = true
+    	logallrefupdates = false
+    [remote "bar"]
+    	fetch = +refs/heads/*:refs/remotes/bar/*
+    	url = http://example.com/repo/foo
+    	url = http://example.com/repo/foo2
+    	pushurl = http://example.com/repo/push1
+    	pushurl = http://example.com/repo/push2
+    "#);
+
+    // Remove URLs
+    work_dir
+        .run_jj([
+            "git",
+            "remote",
+            "set-url",
+            "bar",
+            "--delete",
+            "--push",
+            "http://example.com/repo/push1",
+        ])
+        .success();
+    work_dir
+        .run_jj([
+            "git",
+            "remote",
+            "set-url",
+            "bar",
+            "--delete",
+            "http://example.com/repo/foo",
+        ])
+        .success();
+    let output = work_dir.run_jj(["git", "remote", "list"]);
+    insta::assert_snapshot!(output, @r"
+    bar http://example.com/repo/foo2 (push: http://example.com/repo/push2)
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "git",
+        "remote",
+        "set-url",
+        "bar",
+        "--delete",
+        "http://example.com/repo/unknown",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Git remote named 'bar' has no URL http://example.com/repo/unknown
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj([
+        "git",
+        "remote",
+        "set-url",
+        "bar",
+        "--delete",
+        "http://example.com/repo/foo2",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Cannot remove the last fetch URL of Git remote named 'bar'
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Setting a URL replaces all URLs of that kind
+    work_dir
+        .run_jj([
+            "git",
+            "remote",
+            "set-url",
+            "bar",
+            "--push",
+            "http://example.com/repo/push3",
+        ])
+        .success();
+    insta::assert_snapshot!(read_git_config(work_dir.root()), @r#"
+    [core]
+    	repositoryformatversion = 0
+    	bare = true
+    	logallrefupdates = false
+    [remote "bar"]
+    	url = http://example.com/repo/foo2
+    	pushurl = http://example.com/repo/push3
+    	fetch = +refs/heads/*:refs/remotes/bar/*
+    "#);
+}
+
 #[test]
 fn test_git_remote_relative_path() {
     let test_env = TestEnvironment::default();
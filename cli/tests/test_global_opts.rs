@@ -630,6 +630,50 @@ fn test_quiet() {
     insta::assert_snapshot!(output, @"");
 }
 
+#[test]
+fn test_progress_json() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    // Only the first update of each task is reported immediately, so use a
+    // single file to make the output deterministic.
+    work_dir.write_file("file", "contents");
+    let output = work_dir.run_jj(["--progress=json", "file", "list"]);
+    insta::assert_snapshot!(output, @r#"
+    file
+    [EOF]
+    ------- stderr -------
+    {"event":"started","task":"snapshot"}
+    {"event":"advanced","task":"snapshot","completed":1,"item":"file"}
+    {"event":"finished","task":"snapshot"}
+    [EOF]
+    "#);
+
+    let output = work_dir.run_jj(["--progress=json", "new", "root()"]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    {"event":"started","task":"snapshot"}
+    {"event":"advanced","task":"snapshot","completed":1,"item":"file"}
+    {"event":"finished","task":"snapshot"}
+    {"event":"started","task":"checkout"}
+    {"event":"advanced","task":"checkout","completed":1,"item":"file"}
+    {"event":"finished","task":"checkout"}
+    Working copy  (@) now at: kkmpptxz 2b17ac71 (empty) (no description set)
+    Parent commit (@-)      : zzzzzzzz 00000000 (empty) (no description set)
+    Added 0 files, modified 0 files, removed 1 files
+    [EOF]
+    "#);
+
+    // Progress can be disabled
+    work_dir.write_file("file", "contents");
+    let output = work_dir.run_jj(["--progress=never", "file", "list"]);
+    insta::assert_snapshot!(output, @r"
+    file
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_early_args() {
     // Test that help output parses early args
@@ -1062,12 +1106,18 @@ fn test_help() {
       -R, --repository <REPOSITORY>      Path to repository to operate on
           --ignore-working-copy          Don't snapshot the working copy, and don't update it
           --ignore-immutable             Allow rewriting immutable commits
+          --allow-protected              Allow moving protected bookmarks backwards or deleting them
+          --rebase-descendants <POLICY>  When to rebase the descendants of rewritten commits [possible
+                                         values: eager, lazy, never]
           --at-operation <AT_OPERATION>  Operation to load the repo at [aliases: --at-op]
           --debug                        Enable debug logging
+          --profile <FILE>               Write a profile of the command to the given file
           --color <WHEN>                 When to colorize output [possible values: always, never, debug,
                                          auto]
           --quiet                        Silence non-primary command output
           --no-pager                     Disable the pager
+          --progress <FORMAT>            How to display the progress of long-running operations
+                                         [possible values: auto, json, never]
           --config <NAME=VALUE>          Additional configuration options (can be repeated)
           --config-file <PATH>           Additional configuration files (can be repeated)
     [EOF]
@@ -1094,3 +1144,41 @@ fn test_debug_logging_enabled() {
     // Luckily, insta will print this in color when reviewing.
     insta::assert_snapshot!(log_line, @"[32m INFO[0m [2mjj_cli::cli_util[0m[2m:[0m debug logging enabled");
 }
+
+#[test]
+fn test_profile() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    // Timings vary, so only check the span names.
+    let output = work_dir
+        .run_jj(["--profile=profile.folded", "log"])
+        .success();
+    let stderr = output.stderr.normalized();
+    assert!(stderr.contains("Time (ms)   Calls  Span"), "{stderr}");
+    assert!(
+        stderr.contains("jj_cli::commands::log::cmd_log"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.ends_with("Wrote profile to $TEST_ENV/repo/profile.folded\n"),
+        "{stderr}"
+    );
+    let folded = std::fs::read_to_string(work_dir.root().join("profile.folded")).unwrap();
+    assert!(
+        folded
+            .lines()
+            .any(|line| line.starts_with("run_command;cmd_log ")),
+        "{folded}"
+    );
+
+    // Chrome trace format
+    work_dir.run_jj(["--profile=profile.json", "log"]).success();
+    let json = std::fs::read_to_string(work_dir.root().join("profile.json")).unwrap();
+    let events: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+    assert!(
+        events.iter().any(|event| event["name"] == "cmd_log"),
+        "{json}"
+    );
+}
@@ -1666,6 +1666,198 @@ fn test_elided() {
     ");
 }
 
+#[test]
+fn test_log_elide() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.run_jj(["describe", "-m", "initial"]).success();
+    work_dir.run_jj(["new", "-m", "main 1"]).success();
+    work_dir
+        .run_jj(["new", "-m", "side", "subject(initial)"])
+        .success();
+    work_dir
+        .run_jj(["new", "-m", "automated merge", "subject('main 1')", "@"])
+        .success();
+    work_dir.run_jj(["new", "-m", "main 2"]).success();
+
+    let template = r#"description ++ "\n""#;
+
+    // Merge commit is collapsed into elided edges
+    let output = work_dir.run_jj(["log", "-T", template, "-r=::", "--elide=merges()"]);
+    insta::assert_snapshot!(output, @"
+    @    main 2
+    ├─╮
+    ╷ ○  side
+    ╷ │
+    ○ │  main 1
+    ├─╯
+    ○  initial
+    │
+    ◆
+    [EOF]
+    ");
+
+    // Multiple revsets are unioned
+    let output = work_dir.run_jj([
+        "log",
+        "-T",
+        template,
+        "-r=::",
+        "--elide=merges()",
+        "--elide=subject(side)",
+    ]);
+    insta::assert_snapshot!(output, @"
+    @  main 2
+    ╷
+    ○  main 1
+    │
+    ○  initial
+    │
+    ◆
+    [EOF]
+    ");
+
+    // Default from config, overridden by the flag
+    test_env.add_config("revsets.log = '::'");
+    test_env.add_config("revsets.log-elide = 'subject(side)'");
+    let output = work_dir.run_jj(["log", "-T", template]);
+    insta::assert_snapshot!(output, @"
+    @  main 2
+    │
+    ○  automated merge
+    │
+    ○  main 1
+    │
+    ○  initial
+    │
+    ◆
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-T", template, "--elide=none()"]);
+    insta::assert_snapshot!(output, @"
+    @  main 2
+    │
+    ○    automated merge
+    ├─╮
+    │ ○  side
+    │ │
+    ○ │  main 1
+    ├─╯
+    ○  initial
+    │
+    ◆
+    [EOF]
+    ");
+
+    // The default doesn't apply to explicitly specified revisions
+    let output = work_dir.run_jj(["log", "-T", template, "-r=subject(side)"]);
+    insta::assert_snapshot!(output, @"
+    ○  side
+    │
+    ~
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_log_compact_graph() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.run_jj(["describe", "-m", "a"]).success();
+    for name in ["b", "c", "d", "e", "f"] {
+        work_dir.run_jj(["new", "-m", name]).success();
+    }
+    work_dir
+        .run_jj(["bookmark", "create", "-r", "subject(d)", "book"])
+        .success();
+    work_dir.run_jj(["new", "-m", "x", "subject(c)"]).success();
+    work_dir.run_jj(["new", "-m", "y", "root()"]).success();
+    work_dir
+        .run_jj(["new", "-m", "merge", "subject(x)", "subject(y)"])
+        .success();
+
+    let template = r#"description ++ "\n""#;
+    let revisions = "subject(a) | subject(f) | subject(x) | subject(merge)";
+
+    // Elided revisions are shown once per target, and the "~" marker for
+    // the missing parent "y" is omitted
+    let output = work_dir.run_jj(["log", "-T", template, "-r", revisions]);
+    insta::assert_snapshot!(output, @r"
+    @    merge
+    ├─╮
+    │ │
+    │ ~
+    │
+    ○  x
+    │
+    ~  (elided revisions)
+    │ ○  f
+    │ │
+    │ ~  (elided revisions)
+    ├─╯
+    ○  a
+    │
+    ~
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-T", template, "-r", revisions, "--compact-graph"]);
+    insta::assert_snapshot!(output, @r"
+    @  merge
+    │
+    ○  x
+    │
+    │ ○  f
+    ├─╯
+    ~  (elided revisions)
+    ○  a
+    │
+    ~
+    [EOF]
+    ");
+
+    // Linear runs are collapsed, except for bookmarked revisions
+    test_env.add_config("ui.graph.compact = true");
+    test_env.add_config("ui.graph.collapse-linear-runs = true");
+    let output = work_dir.run_jj(["log", "-T", template, "-r", "::subject(f)"]);
+    insta::assert_snapshot!(output, @r"
+    ○  f
+    │
+    ○  e
+    │
+    ○  d
+    │
+    ~  (3 revisions)
+    ◆
+    [EOF]
+    ");
+    work_dir.run_jj(["bookmark", "delete", "book"]).success();
+    let output = work_dir.run_jj(["log", "-T", template, "-r", "::subject(f)"]);
+    insta::assert_snapshot!(output, @r"
+    ○  f
+    │
+    ~  (5 revisions)
+    ◆
+    [EOF]
+    ");
+
+    // --no-graph can't be combined with --compact-graph
+    let output = work_dir.run_jj(["log", "--no-graph", "--compact-graph"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: the argument '--no-graph' cannot be used with '--compact-graph'
+
+    Usage: jj log --no-graph [FILESETS]...
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 #[test]
 fn test_log_with_custom_symbols() {
     // Test that elided commits are shown as synthetic nodes.
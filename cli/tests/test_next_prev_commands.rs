@@ -455,6 +455,55 @@ fn test_next_choose_bookmarking_child() {
     ");
 }
 
+#[test]
+fn test_next_prev_no_interactive() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.run_jj(["commit", "-m", "first"]).success();
+    work_dir.run_jj(["commit", "-m", "second"]).success();
+    work_dir.run_jj(["new", "@--"]).success();
+    work_dir.run_jj(["commit", "-m", "third"]).success();
+    work_dir.run_jj(["new", "@--"]).success();
+
+    let output = work_dir.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .args(["next", "--no-interactive"])
+            .write_stdin("2\n")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Ambiguous next commit
+    Hint: Candidate: zsuskuln 6fc6af46 (empty) third
+    Hint: Candidate: rlvkpnrz 9439bf06 (empty) second
+    Hint: Pass one of them to `jj new` or `jj edit`, or run without `--no-interactive` to choose one.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    work_dir.run_jj(["new", "rlvkpnrz", "zsuskuln"]).success();
+    work_dir.run_jj(["new"]).success();
+    let output = work_dir.run_jj(["prev", "--no-interactive"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Ambiguous prev commit
+    Hint: Candidate: zsuskuln 6fc6af46 (empty) third
+    Hint: Candidate: rlvkpnrz 9439bf06 (empty) second
+    Hint: Pass one of them to `jj new` or `jj edit`, or run without `--no-interactive` to choose one.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Unambiguous targets are still moved to
+    let output = work_dir.run_jj(["prev", "--no-interactive", "2"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Working copy  (@) now at: kpqxywon 5448803a (empty) (no description set)
+    Parent commit (@-)      : qpvuntsm 68a50538 (empty) first
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_prev_on_merge_commit() {
     let test_env = TestEnvironment::default();
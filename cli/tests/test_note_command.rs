@@ -0,0 +1,264 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::CommandOutput;
+use crate::common::TestEnvironment;
+use crate::common::TestWorkDir;
+
+fn run_git(work_dir: &TestWorkDir, args: &[&str]) -> CommandOutput {
+    work_dir.run_jj_with(|cmd| {
+        cmd.args(["util", "exec", "--", "git"])
+            .args(args)
+            .env("GIT_AUTHOR_NAME", "Test user")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_AUTHOR_DATE", "2001-02-03T04:05:06+07:00")
+            .env("GIT_COMMITTER_NAME", "Test user")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_DATE", "2001-02-03T04:05:06+07:00")
+    })
+}
+
+fn get_log_output(work_dir: &TestWorkDir) -> CommandOutput {
+    let template =
+        r#"commit_id.short() ++ " " ++ description.first_line() ++ " " ++ note.trim() ++ "\n""#;
+    work_dir.run_jj(["log", "-T", template, "-r", "::"])
+}
+
+#[test]
+fn test_note_add_show_remove() {
+    let mut test_env = TestEnvironment::default();
+    let edit_script = test_env.set_up_fake_editor();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.run_jj(["commit", "-m", "first"]).success();
+
+    let output = work_dir.run_jj(["note", "show", "-r", "@-"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Revision 68a505386f93 has no note in refs/notes/commits
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = work_dir.run_jj(["note", "add", "-r", "@-", "-m", "Tested on CI"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Added note to qpvuntsm 68a50538 (empty) first
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["note", "show", "-r", "@-"]);
+    insta::assert_snapshot!(output, @"
+    Tested on CI
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @  eb9fd2ab82e7
+    ○  68a505386f93 first Tested on CI
+    ◆  000000000000
+    [EOF]
+    ");
+
+    // Adding the same note doesn't change anything
+    let output = work_dir.run_jj(["note", "add", "-r", "@-", "-m", "Tested on CI"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+
+    // The editor is initialized with the existing note
+    std::fs::write(&edit_script, "dump editor0\0write\nPassed\n").unwrap();
+    let output = work_dir.run_jj(["note", "add", "-r", "@-"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Added note to qpvuntsm 68a50538 (empty) first
+    [EOF]
+    ");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor0")).unwrap(), @r#"
+    Tested on CI
+
+    JJ: Enter the note for the revision.
+    JJ: Lines starting with "JJ:" (like this one) will be removed.
+    "#);
+    let output = work_dir.run_jj(["note", "show", "-r", "@-"]);
+    insta::assert_snapshot!(output, @"
+    Passed
+    [EOF]
+    ");
+
+    // Notes in other refs are separate
+    work_dir
+        .run_jj(["note", "add", "--ref", "ci", "-m", "build 42"])
+        .success();
+    let output = work_dir.run_jj(["log", "-r", "@", "-T", r#"self.note("ci")"#]);
+    insta::assert_snapshot!(output, @"
+    @  build 42
+    │
+    ~
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["note", "show"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Revision eb9fd2ab82e7 has no note in refs/notes/commits
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = work_dir.run_jj(["note", "remove", "-r", "@-"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Removed note from qpvuntsm 68a50538 (empty) first
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["note", "remove", "-r", "@-"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @  eb9fd2ab82e7
+    ○  68a505386f93 first
+    ◆  000000000000
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_note_git_interop() {
+    let test_env = TestEnvironment::default();
+    test_env
+        .run_jj_in(".", ["git", "init", "--colocate", "repo"])
+        .success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.run_jj(["commit", "-m", "first"]).success();
+
+    // Notes written by git are visible to jj
+    run_git(&work_dir, &["notes", "add", "-m", "from git", "HEAD"]).success();
+    let output = work_dir.run_jj(["note", "show", "-r", "@-"]);
+    insta::assert_snapshot!(output, @"
+    from git
+    [EOF]
+    ");
+
+    // Notes written by jj are visible to git
+    work_dir
+        .run_jj(["note", "add", "-r", "@-", "-m", "from jj"])
+        .success();
+    let output = run_git(&work_dir, &["notes", "show", "HEAD"]);
+    insta::assert_snapshot!(output, @"
+    from jj
+    [EOF]
+    ");
+
+    // Notes refs aren't imported as bookmarks or tags
+    insta::assert_snapshot!(work_dir.run_jj(["bookmark", "list", "--all"]), @"");
+}
+
+#[test]
+fn test_note_fetch_push() {
+    let test_env = TestEnvironment::default();
+    test_env
+        .run_jj_in(".", ["git", "init", "--colocate", "origin"])
+        .success();
+    let origin_dir = test_env.work_dir("origin");
+    origin_dir.run_jj(["commit", "-m", "first"]).success();
+    origin_dir
+        .run_jj(["bookmark", "create", "-r", "@-", "main"])
+        .success();
+    origin_dir
+        .run_jj(["note", "add", "-r", "main", "-m", "Tested on CI"])
+        .success();
+    test_env
+        .run_jj_in(".", ["git", "clone", "origin", "clone"])
+        .success();
+    let work_dir = test_env.work_dir("clone");
+
+    // Notes aren't fetched by `jj git fetch`
+    let output = work_dir.run_jj(["note", "show", "-r", "main"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Revision 68a505386f93 has no note in refs/notes/commits
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["note", "fetch"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Fetched refs/notes/commits from origin
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["note", "show", "-r", "main"]);
+    insta::assert_snapshot!(output, @"
+    Tested on CI
+    [EOF]
+    ");
+
+    work_dir
+        .run_jj(["note", "add", "-r", "main", "-m", "Deployed"])
+        .success();
+    let output = work_dir.run_jj(["note", "push"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Pushed refs/notes/commits to origin
+    [EOF]
+    ");
+    let output = origin_dir.run_jj(["note", "show", "-r", "main"]);
+    insta::assert_snapshot!(output, @"
+    Deployed
+    [EOF]
+    ");
+
+    // Diverged notes aren't overwritten
+    origin_dir
+        .run_jj(["note", "add", "-r", "main", "-m", "Reverted"])
+        .success();
+    work_dir
+        .run_jj(["note", "add", "-r", "main", "-m", "Released"])
+        .success();
+    let output = work_dir.run_jj(["note", "fetch"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Git process failed: External git program failed:
+    From $TEST_ENV/origin
+     ! [rejected]        refs/notes/commits -> refs/notes/commits  (non-fast-forward)
+
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["note", "push"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Git process failed: External git program failed:
+    To $TEST_ENV/origin
+     ! [rejected]        refs/notes/commits -> refs/notes/commits (non-fast-forward)
+    error: failed to push some refs to '$TEST_ENV/origin'
+    hint: Updates were rejected because a pushed branch tip is behind its remote
+    hint: counterpart. Check out this branch and integrate the remote changes
+    hint: (e.g. 'git pull ...') before pushing again.
+    hint: See the 'Note about fast-forwards' in 'git push --help' for details.
+
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["note", "push", "--remote", "upstream"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: No git remote named 'upstream'
+    [EOF]
+    [exit status: 1]
+    ");
+}
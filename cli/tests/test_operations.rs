@@ -3068,6 +3068,281 @@ fn test_op_log_anonymize() {
     ");
 }
 
+#[test]
+fn test_op_pull_push() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo1"]).success();
+    test_env.run_jj_in(".", ["git", "init", "repo2"]).success();
+    let work_dir1 = test_env.work_dir("repo1");
+    let work_dir2 = test_env.work_dir("repo2");
+    // The working-copy commits are recorded by workspace name
+    work_dir2
+        .run_jj(["workspace", "rename", "second"])
+        .success();
+    work_dir1.write_file("file", "a\n");
+    work_dir1.run_jj(["commit", "-m", "first"]).success();
+    work_dir1
+        .run_jj(["bookmark", "create", "-r@-", "main"])
+        .success();
+
+    // Everything since the root operation is copied
+    let output = work_dir2.run_jj(["op", "pull", "../repo1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Pulled 4 operations
+    Hint: The operation logs have diverged. The operations will be merged the next time the repo is loaded.
+    [EOF]
+    ");
+    let output = work_dir2.run_jj(["op", "log", "-Tdescription"]);
+    insta::assert_snapshot!(output, @r"
+    @    reconcile divergent operations
+    ├─╮
+    ○ │  Renamed workspace 'default' to 'second'
+    ○ │  add workspace 'default'
+    │ ○  create bookmark main pointing to commit f7ad077c1ab53a3e91700ad46e86b263058070bc
+    │ ○  commit e59cede0eef467104591ff2b5519c3341cab32d4
+    │ ○  snapshot working copy
+    │ ○  add workspace 'default'
+    ├─╯
+    ○
+    [EOF]
+    ------- stderr -------
+    Concurrent modification detected, resolving automatically.
+    [EOF]
+    ");
+    let output = work_dir2.run_jj(["log", "-r", "main", "-p"]);
+    insta::assert_snapshot!(output, @r"
+    ○  qpvuntsm test.user@example.com 2001-02-03 08:05:10 main f7ad077c
+    │  first
+    ~  Added regular file file:
+               1: a
+    [EOF]
+    ");
+
+    // Nothing to do if the operations are already there
+    let output = work_dir2.run_jj(["op", "pull", "../repo1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+
+    // Operations descending from the current head replace it
+    let output = work_dir2.run_jj(["op", "push", "../repo1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Pushed 3 operations
+    [EOF]
+    ");
+    work_dir1
+        .run_jj(["describe", "-r", "main", "-m", "first (edited)"])
+        .success();
+    let output = work_dir1.run_jj(["op", "push", "../repo2"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Pushed 1 operations
+    [EOF]
+    ");
+    let output = work_dir2.run_jj(["op", "log", "-Tdescription", "-n3"]);
+    insta::assert_snapshot!(output, @r"
+    @  describe commit f7ad077c1ab53a3e91700ad46e86b263058070bc
+    ○    reconcile divergent operations
+    ├─╮
+    ○ │  Renamed workspace 'default' to 'second'
+    [EOF]
+    ");
+
+    // Operations made in both repos are merged
+    work_dir1
+        .run_jj(["describe", "-r", "main", "-m", "from repo1"])
+        .success();
+    work_dir2
+        .run_jj(["new", "main", "-m", "from repo2"])
+        .success();
+    let output = work_dir2.run_jj(["op", "pull", "../repo1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Pulled 1 operations
+    Hint: The operation logs have diverged. The operations will be merged the next time the repo is loaded.
+    [EOF]
+    ");
+    let output = work_dir2.run_jj(["log", "-Tdescription"]);
+    insta::assert_snapshot!(output, @r"
+    @  from repo2
+    │ ○
+    ├─╯
+    ○  from repo1
+    ◆
+    [EOF]
+    ------- stderr -------
+    Concurrent modification detected, resolving automatically.
+    Rebased 1 descendant commits onto commits rewritten by other operation
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_op_pull_at_op() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo1"]).success();
+    test_env.run_jj_in(".", ["git", "init", "repo2"]).success();
+    let work_dir2 = test_env.work_dir("repo2");
+    let output = work_dir2.run_jj(["op", "pull", "../repo1", "--at-op", "@-"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: --at-op is not respected
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
+#[test]
+fn test_op_pull_push_git_remote() {
+    let test_env = TestEnvironment::default();
+    let remote_path = test_env.env_root().join("remote.git");
+    git::init_bare(&remote_path);
+    test_env.run_jj_in(".", ["git", "init", "repo1"]).success();
+    test_env.run_jj_in(".", ["git", "init", "repo2"]).success();
+    let work_dir1 = test_env.work_dir("repo1");
+    let work_dir2 = test_env.work_dir("repo2");
+    for work_dir in [&work_dir1, &work_dir2] {
+        work_dir
+            .run_jj(["git", "remote", "add", "origin", "../remote.git"])
+            .success();
+    }
+    // The working-copy commits are recorded by workspace name
+    work_dir2
+        .run_jj(["workspace", "rename", "second"])
+        .success();
+    work_dir1.write_file("file", "a\n");
+    work_dir1.run_jj(["commit", "-m", "first"]).success();
+    work_dir1
+        .run_jj(["bookmark", "create", "-r@-", "main"])
+        .success();
+    let get_remote_op_heads = || {
+        let remote_repo = git::open(&remote_path);
+        let refs = remote_repo.references().unwrap();
+        refs.prefixed("refs/jj/op-heads/").unwrap().count()
+    };
+
+    // Nothing has been pushed yet
+    let output = work_dir2.run_jj(["op", "pull", "origin"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+
+    // The commits are pushed along with the operations
+    let output = work_dir1.run_jj(["op", "push", "origin"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Pushed 5 operations
+    [EOF]
+    ");
+    assert_eq!(get_remote_op_heads(), 1);
+    let output = work_dir1.run_jj(["op", "push", "origin"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+
+    let output = work_dir2.run_jj(["op", "pull", "origin"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Pulled 5 operations
+    Hint: The operation logs have diverged. The operations will be merged the next time the repo is loaded.
+    [EOF]
+    ");
+    let output = work_dir2.run_jj(["log", "-r", "main", "-p"]);
+    insta::assert_snapshot!(output, @r"
+    ○  qpvuntsm test.user@example.com 2001-02-03 08:05:12 main c9cda5e1
+    │  first
+    ~  Added regular file file:
+               1: a
+    [EOF]
+    ------- stderr -------
+    Concurrent modification detected, resolving automatically.
+    [EOF]
+    ");
+    let output = work_dir2.run_jj(["op", "pull", "origin"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+
+    // Pushed heads replace their ancestors on the remote
+    let output = work_dir2.run_jj(["op", "push", "origin"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Pushed 4 operations
+    [EOF]
+    ");
+    assert_eq!(get_remote_op_heads(), 1);
+
+    // Heads of the remote which aren't known locally are kept
+    work_dir1
+        .run_jj(["describe", "-r", "main", "-m", "from repo1"])
+        .success();
+    work_dir2
+        .run_jj(["new", "main", "-m", "from repo2"])
+        .success();
+    work_dir2.run_jj(["op", "push", "origin"]).success();
+    let output = work_dir1.run_jj(["op", "push", "origin"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Pushed 1 operations
+    Hint: The remote has operations which aren't in this repo. Run `jj op pull origin` to merge the operation logs.
+    [EOF]
+    ");
+    assert_eq!(get_remote_op_heads(), 2);
+
+    let output = work_dir1.run_jj(["op", "pull", "origin"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Pulled 5 operations
+    Hint: The operation logs have diverged. The operations will be merged the next time the repo is loaded.
+    [EOF]
+    ");
+    let output = work_dir1.run_jj(["log", "-Tdescription"]);
+    insta::assert_snapshot!(output, @r"
+    @
+    │ ○  from repo2
+    ├─╯
+    ○  from repo1
+    ◆
+    [EOF]
+    ------- stderr -------
+    Concurrent modification detected, resolving automatically.
+    Rebased 1 descendant commits onto commits rewritten by other operation
+    [EOF]
+    ");
+    let output = work_dir1.run_jj(["op", "push", "origin"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Pushed 1 operations
+    [EOF]
+    ");
+    assert_eq!(get_remote_op_heads(), 1);
+}
+
+#[test]
+fn test_op_push_no_such_remote() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let output = work_dir.run_jj(["op", "push", "https://example.com/repo.git"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No git remote or repo path named https://example.com/repo.git
+    Hint: Specify the name of a Git remote, or the path to a workspace of another repo.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 fn init_bare_git_repo(git_repo_path: &Path) -> gix::Repository {
     let git_repo = git::init_bare(git_repo_path);
     let commit_result = git::add_commit(
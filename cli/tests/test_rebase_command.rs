@@ -32,9 +32,9 @@ fn test_rebase_invalid() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     error: the following required arguments were not provided:
-      <--onto <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
+      <--onto <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>|--reorder>
 
-    Usage: jj rebase <--onto <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
+    Usage: jj rebase <--onto <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>|--reorder>
 
     For more information, try '--help'.
     [EOF]
@@ -47,7 +47,7 @@ fn test_rebase_invalid() {
     ------- stderr -------
     error: the argument '--revisions <REVSETS>' cannot be used with '--source <REVSETS>'
 
-    Usage: jj rebase --revisions <REVSETS> <--onto <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
+    Usage: jj rebase --revisions <REVSETS> <--onto <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>|--reorder>
 
     For more information, try '--help'.
     [EOF]
@@ -60,7 +60,7 @@ fn test_rebase_invalid() {
     ------- stderr -------
     error: the argument '--branch <REVSETS>' cannot be used with '--source <REVSETS>'
 
-    Usage: jj rebase --branch <REVSETS> <--onto <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
+    Usage: jj rebase --branch <REVSETS> <--onto <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>|--reorder>
 
     For more information, try '--help'.
     [EOF]
@@ -73,7 +73,7 @@ fn test_rebase_invalid() {
     ------- stderr -------
     error: the argument '--onto <REVSETS>' cannot be used with '--insert-after <REVSETS>'
 
-    Usage: jj rebase --revisions <REVSETS> <--onto <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
+    Usage: jj rebase --revisions <REVSETS> <--onto <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>|--reorder>
 
     For more information, try '--help'.
     [EOF]
@@ -86,7 +86,7 @@ fn test_rebase_invalid() {
     ------- stderr -------
     error: the argument '--onto <REVSETS>' cannot be used with '--insert-before <REVSETS>'
 
-    Usage: jj rebase --revisions <REVSETS> <--onto <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
+    Usage: jj rebase --revisions <REVSETS> <--onto <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>|--reorder>
 
     For more information, try '--help'.
     [EOF]
@@ -3006,6 +3006,151 @@ fn test_rebase_skip_duplicate_divergent() {
     ");
 }
 
+#[test]
+fn test_rebase_reorder() {
+    let mut test_env = TestEnvironment::default();
+    let edit_script = test_env.set_up_fake_editor();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(&work_dir, "a", &[], &[("file1", "a\n")]);
+    create_commit_with_files(&work_dir, "b", &["a"], &[("file2", "b\n")]);
+    create_commit_with_files(&work_dir, "c", &["b"], &[("file3", "c\n")]);
+    create_commit_with_files(&work_dir, "d", &["c"], &[("file4", "d\n")]);
+    create_commit_with_files(&work_dir, "e", &["d"], &[("file5", "e\n")]);
+    let setup_opid = work_dir.current_operation_id();
+
+    std::fs::write(&edit_script, "dump editor").unwrap();
+    work_dir
+        .run_jj(["rebase", "-r", "b::d", "--reorder"])
+        .success();
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor")).unwrap(), @r"
+    pick cb3fcb5d52e9 b
+    pick 0a5b222f50eb c
+    pick 4a84d2b4f575 d
+
+    JJ: Reorder the lines above to reorder the revisions. The first line is
+    JJ: the bottom of the stack.
+    JJ:
+    JJ: pick <commit> = keep the revision
+    JJ: squash <commit> = squash the revision into the one on the line above
+    JJ: drop <commit> = abandon the revision
+    ");
+
+    // Move "d" to the bottom and squash "c" into "b"
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    std::fs::write(
+        &edit_script,
+        "write\npick 4a84d2b4f575 d\npick cb3fcb5d52e9 b\nsquash 0a5b222f50eb c\n",
+    )
+    .unwrap();
+    let output = work_dir.run_jj(["rebase", "-r", "b::d", "--reorder"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebased 1 descendant commits
+    Reordered 3 commits into:
+      zsuskuln 97233d3b b c | b
+      vruxwmqv 65509ed2 d | d
+    Working copy  (@) now at: znkkpsqq 87e0a8de e | e
+    Parent commit (@-)      : zsuskuln 97233d3b b c | b
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-T", "description", "--summary"]);
+    insta::assert_snapshot!(output, @r"
+    @  e
+    │  A file5
+    ○  b
+    │
+    │  c
+    │  A file2
+    │  A file3
+    ○  d
+    │  A file4
+    ○  a
+    │  A file1
+    ◆
+    [EOF]
+    ");
+
+    // Drop "c"
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    std::fs::write(
+        &edit_script,
+        "write\npick cb3fcb5d52e9 b\ndrop 0a5b222f50eb c\npick 4a84d2b4f575 d\n",
+    )
+    .unwrap();
+    let output = work_dir.run_jj(["rebase", "-r", "b::d", "--reorder"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebased 1 descendant commits
+    Reordered 3 commits into:
+      vruxwmqv d44439cb d | d
+      zsuskuln bb009e4f b c | b
+    Working copy  (@) now at: znkkpsqq aef01813 e | e
+    Parent commit (@-)      : vruxwmqv d44439cb d | d
+    Added 0 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @r"
+    @  e: d
+    ○  d: b c
+    ○  b c: a
+    ○  a
+    ◆
+    [EOF]
+    ");
+
+    // Revisions can't be removed from the list
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    std::fs::write(&edit_script, "write\npick cb3fcb5d52e9 b\n").unwrap();
+    let output = work_dir.run_jj(["rebase", "-r", "b::d", "--reorder"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Revision 0a5b222f50eb is missing from the reorder todo list
+    Hint: Use `drop` to abandon a revision.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Squash needs a revision to squash into
+    std::fs::write(
+        &edit_script,
+        "write\nsquash cb3fcb5d52e9 b\npick 0a5b222f50eb c\npick 4a84d2b4f575 d\n",
+    )
+    .unwrap();
+    let output = work_dir.run_jj(["rebase", "-r", "b::d", "--reorder"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Cannot squash revision cb3fcb5d52e9 without a picked revision on a line above it
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // The revisions must form a linear stack
+    let output = work_dir.run_jj(["rebase", "-r", "b|d", "--reorder"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: The revisions to reorder must form a linear stack
+    Hint: Commit 4a84d2b4f575 must have commit cb3fcb5d52e9 as its only parent
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // A destination can't be combined with --reorder
+    let output = work_dir.run_jj(["rebase", "-r", "b::d", "--reorder", "-o", "a"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: the argument '--reorder' cannot be used with '--onto <REVSETS>'
+
+    Usage: jj rebase --revisions <REVSETS> <--onto <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>|--reorder>
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 #[must_use]
 fn get_log_output(work_dir: &TestWorkDir) -> CommandOutput {
     let template = "bookmarks ++ surround(': ', '', parents.map(|c| c.bookmarks()))";
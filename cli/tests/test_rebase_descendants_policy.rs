@@ -0,0 +1,262 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::CommandOutput;
+use crate::common::TestEnvironment;
+use crate::common::TestWorkDir;
+
+fn get_log_output(work_dir: &TestWorkDir) -> CommandOutput {
+    let template = r#"separate(" ", description.first_line(), if(divergent, "(divergent)"))"#;
+    work_dir.run_jj(["log", "-r=all()", "-T", template])
+}
+
+fn create_stack(work_dir: &TestWorkDir) {
+    work_dir.run_jj(["describe", "-m=a"]).success();
+    work_dir.run_jj(["new", "-m=b"]).success();
+    work_dir.run_jj(["new", "-m=c"]).success();
+}
+
+#[test]
+fn test_rebase_descendants_lazy() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    test_env.add_config(r#"rewrite.rebase-descendants = "lazy""#);
+    let work_dir = test_env.work_dir("repo");
+    create_stack(&work_dir);
+
+    let output = work_dir.run_jj(["describe", "-r", "subject(a)", "-m=a2"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    The descendants of 1 rewritten commits will be rebased on the next checkout or push
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @  c
+    ○  b
+    ○  a (divergent)
+    │ ○  a2 (divergent)
+    ├─╯
+    ◆
+    [EOF]
+    ");
+
+    // Rewriting the new commit again doesn't rebase the descendants either
+    let output = work_dir.run_jj(["describe", "-r", "subject(a2)", "-m=a3"]);
+    insta::assert_snapshot!(output, @"");
+
+    // Checking out another change rebases the descendants
+    let output = work_dir.run_jj(["new"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 3 descendant commits
+    Working copy  (@) now at: vruxwmqv 7efb8c69 (empty) (no description set)
+    Parent commit (@-)      : zsuskuln 6b63f300 (empty) c
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @
+    ○  c
+    ○  b
+    ○  a3
+    ◆
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_rebase_descendants_lazy_snapshot() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    test_env.add_config(r#"rewrite.rebase-descendants = "lazy""#);
+    let work_dir = test_env.work_dir("repo");
+    create_stack(&work_dir);
+    work_dir.run_jj(["edit", "subject(a)"]).success();
+
+    // Snapshotting the working copy doesn't rebase its descendants
+    work_dir.write_file("file", "a\n");
+    let output = work_dir.run_jj(["status"]);
+    insta::assert_snapshot!(output, @"
+    Working copy changes:
+    A file
+    Working copy  (@) : qpvuntsm/0 1f492afc (divergent) a
+    Parent commit (@-): zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    ------- stderr -------
+    The descendants of 1 rewritten commits will be rebased on the next checkout or push
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @  a (divergent)
+    │ ○  c
+    │ ○  b
+    │ ○  a (divergent)
+    ├─╯
+    ◆
+    [EOF]
+    ");
+
+    // The descendants are rebased onto the latest snapshot
+    work_dir.write_file("file", "b\n");
+    let output = work_dir.run_jj(["edit", "subject(c) & ~divergent()"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 2 descendant commits
+    Working copy  (@) now at: zsuskuln bec89bc0 (empty) c
+    Parent commit (@-)      : kkmpptxz 0d01ad64 (empty) b
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @  c
+    ○  b
+    ○  a
+    ◆
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["file", "show", "file"]), @"
+    b
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_rebase_descendants_lazy_on_push() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "origin"]).success();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    test_env.add_config(r#"rewrite.rebase-descendants = "lazy""#);
+    let work_dir = test_env.work_dir("repo");
+    let origin_git_repo_path = test_env
+        .env_root()
+        .join("origin")
+        .join(".jj")
+        .join("repo")
+        .join("store")
+        .join("git");
+    work_dir
+        .run_jj([
+            "git",
+            "remote",
+            "add",
+            "origin",
+            origin_git_repo_path.to_str().unwrap(),
+        ])
+        .success();
+    create_stack(&work_dir);
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "main"])
+        .success();
+
+    work_dir
+        .run_jj(["describe", "-r", "subject(a)", "-m=a2"])
+        .success();
+    let output = work_dir.run_jj([
+        "git",
+        "push",
+        "-b=main",
+        "--config=remotes.origin.auto-track-bookmarks='*'",
+    ]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 2 descendant commits
+    Working copy  (@) now at: royxmykx baa88058 main | (empty) c
+    Parent commit (@-)      : mzvwutvl e155721a (empty) b
+    Changes to push to origin:
+      Add bookmark main to baa88058d448
+    Warning: The working-copy commit in workspace 'default' became immutable, so a new commit has been created on top of it.
+    Working copy  (@) now at: yostqsxw 24e02f3c (empty) (no description set)
+    Parent commit (@-)      : royxmykx baa88058 main | (empty) c
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @
+    ◆  c
+    ◆  b
+    ◆  a2
+    ◆
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_rebase_descendants_never() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    create_stack(&work_dir);
+
+    let output = work_dir.run_jj([
+        "describe",
+        "-r",
+        "subject(a)",
+        "-m=a2",
+        "--rebase-descendants=never",
+    ]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Did not rebase the descendants of 1 rewritten commits
+    [EOF]
+    ");
+
+    // The descendants are left on the old commit
+    let output = work_dir.run_jj(["new", "--rebase-descendants=never"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Working copy  (@) now at: royxmykx 6aca0a60 (empty) (no description set)
+    Parent commit (@-)      : zsuskuln 7794eb03 (empty) c
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @
+    ○  c
+    ○  b
+    ○  a (divergent)
+    │ ○  a2 (divergent)
+    ├─╯
+    ◆
+    [EOF]
+    ");
+
+    // The flag overrides the config
+    test_env.add_config(r#"rewrite.rebase-descendants = "lazy""#);
+    let output = work_dir.run_jj(["describe", "-r", "subject(b)", "-m=b2"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    The descendants of 1 rewritten commits will be rebased on the next checkout or push
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "describe",
+        "-r",
+        "subject(b2)",
+        "-m=b3",
+        "--rebase-descendants=eager",
+    ]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 2 descendant commits
+    Working copy  (@) now at: royxmykx 52640ddd (empty) (no description set)
+    Parent commit (@-)      : zsuskuln 1bb82e1c (empty) c
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @
+    ○  c
+    ○  b3
+    ○  a (divergent)
+    │ ○  a2 (divergent)
+    ├─╯
+    ◆
+    [EOF]
+    ");
+}
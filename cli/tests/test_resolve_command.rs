@@ -615,6 +615,94 @@ fn test_files_still_have_conflicts() {
     ");
 }
 
+#[test]
+fn test_list_conflicts_in_revset() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(
+        &work_dir,
+        "base",
+        &[],
+        &[("file1", "base\n"), ("file2", "base\n")],
+    );
+    create_commit_with_files(
+        &work_dir,
+        "a",
+        &["base"],
+        &[("file1", "a\n"), ("file2", "a\n")],
+    );
+    create_commit_with_files(
+        &work_dir,
+        "b",
+        &["base"],
+        &[("file1", "b\n"), ("file2", "b\n")],
+    );
+    create_commit_with_files(&work_dir, "conflict", &["a", "b"], &[]);
+    create_commit_with_files(&work_dir, "c", &["conflict"], &[("file1", "c\n")]);
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @  c
+    ×    conflict
+    ├─╮
+    │ ○  b
+    ○ │  a
+    ├─╯
+    ○  base
+    ◆
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["resolve", "--list", "-r", "::@"]);
+    insta::assert_snapshot!(output, @"
+    Conflicts in znkkpsqq 94c51340 c | (conflict) c:
+    file2    2-sided conflict
+    Conflicts in vruxwmqv 9e1c00aa conflict | (conflict) (empty) conflict:
+    file1    2-sided conflict
+    file2    2-sided conflict
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["resolve", "--list", "-r", "::@", "--format=json"]);
+    insta::assert_snapshot!(output, @r#"
+    {"commit_id":"94c513400f8a81aaf96f6d05583488b6d3dca6a2","change_id":"znkkpsqqskklxtlmwpkmkxzsruztxksx","path":"file2","num_sides":2,"sides":["zsuskuln 6c90cb15 \"a\"","royxmykx c8c2fbf5 \"b\""],"bases":["rlvkpnrz 5ef04682 \"base\""]}
+    {"commit_id":"9e1c00aa10f1beedb99317a6f7eba9e32c4e80af","change_id":"vruxwmqvtpmxqkrrksmzyrvxysqqlsxp","path":"file1","num_sides":2,"sides":["zsuskuln 6c90cb15 \"a\"","royxmykx c8c2fbf5 \"b\""],"bases":["rlvkpnrz 5ef04682 \"base\""]}
+    {"commit_id":"9e1c00aa10f1beedb99317a6f7eba9e32c4e80af","change_id":"vruxwmqvtpmxqkrrksmzyrvxysqqlsxp","path":"file2","num_sides":2,"sides":["zsuskuln 6c90cb15 \"a\"","royxmykx c8c2fbf5 \"b\""],"bases":["rlvkpnrz 5ef04682 \"base\""]}
+    [EOF]
+    "#);
+
+    // Paths are matched in all revisions
+    let output = work_dir.run_jj(["resolve", "--list", "-r", "::@", "--format=json", "file1"]);
+    insta::assert_snapshot!(output, @r#"
+    {"commit_id":"9e1c00aa10f1beedb99317a6f7eba9e32c4e80af","change_id":"vruxwmqvtpmxqkrrksmzyrvxysqqlsxp","path":"file1","num_sides":2,"sides":["zsuskuln 6c90cb15 \"a\"","royxmykx c8c2fbf5 \"b\""],"bases":["rlvkpnrz 5ef04682 \"base\""]}
+    [EOF]
+    "#);
+
+    // No conflicts isn't an error with the JSON format
+    let output = work_dir.run_jj(["resolve", "--list", "-r", "a|b"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: No conflicts found at these revisions
+    [EOF]
+    [exit status: 2]
+    ");
+    let output = work_dir.run_jj(["resolve", "--list", "-r", "a|b", "--format=json"]);
+    insta::assert_snapshot!(output, @"");
+
+    // The format only applies to --list
+    let output = work_dir.run_jj(["resolve", "--format=json"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    error: the following required arguments were not provided:
+      --list
+
+    Usage: jj resolve --list --format <FORMAT> [FILESETS]...
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 fn check_resolve_produces_input_file(
     test_env: &mut TestEnvironment,
     root: impl AsRef<Path>,
@@ -587,6 +587,114 @@ fn test_restore_interactive_with_paths() {
     ");
 }
 
+#[test]
+fn test_restore_from_evolog() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("parent", "parent\n");
+    work_dir.run_jj(["describe", "-m", "parent"]).success();
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "parent"])
+        .success();
+    work_dir.run_jj(["new", "-m", "first version"]).success();
+    work_dir.write_file("file", "a\n");
+    work_dir.run_jj(["debug", "snapshot"]).success();
+    let first_version = work_dir
+        .run_jj(["log", "--no-graph", "-r@", "-T", "commit_id"])
+        .success()
+        .stdout
+        .into_raw();
+    work_dir.write_file("file", "b\n");
+    work_dir.write_file("other", "b\n");
+    work_dir
+        .run_jj(["describe", "-m", "second version"])
+        .success();
+    // Changes to the parent are kept when restoring the child.
+    work_dir.run_jj(["edit", "parent"]).success();
+    work_dir.write_file("parent", "parent changed\n");
+    work_dir.run_jj(["new", "children(parent)"]).success();
+
+    // The change is restored to its first version on top of its new parent
+    let output = work_dir.run_jj(["restore", "--from-evolog", &first_version]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebased 1 descendant commits
+    Working copy  (@) now at: yostqsxw 8667e7d3 (empty) (no description set)
+    Parent commit (@-)      : zsuskuln 9d64e2d3 first version
+    Added 0 files, modified 1 files, removed 1 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "--summary", "-T", "description"]);
+    insta::assert_snapshot!(output, @r"
+    @
+    ○  first version
+    │  A file
+    ○  parent
+    │  A parent
+    ◆
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r@-", "file"]);
+    insta::assert_snapshot!(output, @r"
+    a
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r@-", "parent"]);
+    insta::assert_snapshot!(output, @r"
+    parent changed
+    [EOF]
+    ");
+
+    // Restore only the content
+    work_dir.run_jj(["undo"]).success();
+    let output = work_dir.run_jj([
+        "restore",
+        "--from-evolog",
+        &first_version,
+        "--keep-description",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebased 1 descendant commits
+    Working copy  (@) now at: yostqsxw 69e43951 (empty) (no description set)
+    Parent commit (@-)      : zsuskuln 045d76bc second version
+    Added 0 files, modified 1 files, removed 1 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-r@-", "--summary", "-T", "description"]);
+    insta::assert_snapshot!(output, @r"
+    ○  second version
+    │  A file
+    ~
+    [EOF]
+    ");
+
+    // Restoring the same version again does nothing
+    let output = work_dir.run_jj([
+        "restore",
+        "--from-evolog",
+        &first_version,
+        "--keep-description",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+
+    // Versions of other changes can't be restored from
+    let output = work_dir.run_jj(["restore", "--from-evolog", "parent", "--into", "@-"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Commit f75c4c2c4512 is not in the evolution log of revision 045d76bcf753
+    Hint: Use `jj evolog -r zsuskulnrvyr` to list the earlier versions of the change.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[must_use]
 fn get_log_output(work_dir: &TestWorkDir) -> CommandOutput {
     work_dir.run_jj(["log", "-T", "bookmarks"])
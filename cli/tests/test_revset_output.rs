@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use crate::common::TestEnvironment;
+use crate::common::to_toml_value;
 
 #[test]
 fn test_syntax_error() {
@@ -791,6 +792,74 @@ fn test_bad_alias_decl() {
 /// Verifies that the committer_date revset honors the local time zone.
 /// This test cannot run on Windows because The TZ env var does not control
 /// chrono::Local on that platform.
+#[test]
+fn test_config_function() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.run_jj(["commit", "-m", "A"]).success();
+    work_dir.run_jj(["commit", "-m", "B"]).success();
+    let output = work_dir.run_jj(["log", "--no-graph", "-r@--", "-Tcommit_id"]);
+    let commit_id = output.stdout.into_raw();
+
+    // The function's arguments are passed to the command, which prints commit
+    // IDs. Unknown commits are left out.
+    let echo_path = assert_cmd::cargo::cargo_bin!("fake-echo");
+    test_env.add_config(format!(
+        "revset-functions.listed.command = [{}]",
+        to_toml_value(echo_path.to_str().unwrap())
+    ));
+    let unknown_id = "0123456789abcdef0123456789abcdef01234567";
+    let output = work_dir.run_jj([
+        "log",
+        "-Tdescription",
+        "-r",
+        &format!("listed('{commit_id}', '{unknown_id}')::"),
+    ]);
+    insta::assert_snapshot!(output, @r"
+    @
+    ○  B
+    ○  A
+    │
+    ~
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["log", "-r", "listed(x)"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Command of revset function `listed` printed invalid commit ID `x`
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = work_dir.run_jj(["log", "-r", "listed(x::y)"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Failed to parse revset: Expected string
+    Caused by:  --> 1:8
+      |
+    1 | listed(x::y)
+      |        ^--^
+      |
+      = Expected string
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Builtin functions can't be redefined
+    test_env.add_config("revset-functions.all.command = ['true']");
+    let output = work_dir.run_jj(["log"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Config error: Invalid `revset-functions.all`
+    Caused by: Revset function `all` is already defined
+    For help, see https://docs.jj-vcs.dev/latest/config/ or use `jj help -k config`.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 #[cfg(not(target_os = "windows"))]
 fn test_revset_committer_date_with_time_zone() {
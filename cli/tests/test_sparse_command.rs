@@ -193,6 +193,79 @@ fn test_sparse_manage_patterns() {
     "#);
 }
 
+#[test]
+fn test_sparse_excluded_and_cone() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("README", "contents");
+    work_dir.create_dir_all("src/gen");
+    work_dir.create_dir_all("src/lib");
+    work_dir.write_file("src/main", "contents");
+    work_dir.write_file("src/gen/file", "contents");
+    work_dir.write_file("src/lib/file", "contents");
+    work_dir.create_dir("docs");
+    work_dir.write_file("docs/file", "contents");
+
+    // Everything under src/ except src/gen/
+    let output = work_dir.run_jj(["sparse", "set", "--clear", "--add=src", "--add=!src/gen"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Added 0 files, modified 0 files, removed 3 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["sparse", "list"]);
+    insta::assert_snapshot!(output, @r"
+    src
+    !src/gen
+    [EOF]
+    ");
+    assert!(!work_dir.root().join("README").exists());
+    assert!(work_dir.root().join("src/main").exists());
+    assert!(!work_dir.root().join("src/gen/file").exists());
+    assert!(work_dir.root().join("src/lib/file").exists());
+    assert!(!work_dir.root().join("docs/file").exists());
+
+    // The exclusion can be removed
+    let output = work_dir.run_jj(["sparse", "set", "--remove=!src/gen"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    ");
+    assert!(work_dir.root().join("src/gen/file").exists());
+
+    // Cone mode also includes the files in the parent directories
+    let output = work_dir.run_jj(["sparse", "set", "--clear", "--add=src/lib", "--cone"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Added 1 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["sparse", "list"]);
+    insta::assert_snapshot!(output, @r"
+    src/lib
+    [EOF]
+    ------- stderr -------
+    Cone mode is enabled: files in the parent directories of the patterns are also present
+    [EOF]
+    ");
+    assert!(work_dir.root().join("README").exists());
+    assert!(work_dir.root().join("src/main").exists());
+    assert!(!work_dir.root().join("src/gen/file").exists());
+    assert!(work_dir.root().join("src/lib/file").exists());
+    assert!(!work_dir.root().join("docs/file").exists());
+
+    let output = work_dir.run_jj(["sparse", "set", "--no-cone"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Added 0 files, modified 0 files, removed 2 files
+    [EOF]
+    ");
+    assert!(!work_dir.root().join("README").exists());
+}
+
 #[test]
 fn test_sparse_editor_avoids_unc() {
     use std::path::PathBuf;
@@ -1847,3 +1847,121 @@ fn test_split_with_editor_without_message() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_split_by_filesets() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("src/a", "a");
+    work_dir.write_file("src/b", "b");
+    work_dir.write_file("docs/c", "c");
+    work_dir.write_file("README", "readme");
+    work_dir.run_jj(["describe", "-m", "original"]).success();
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("child", "child");
+
+    let output = work_dir.run_jj(["split", "-r@-", "--by", "src", "--by", "docs"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebased 1 descendant commits
+    Split into 3 commits:
+      qpvuntsm e9ed72a8 original
+      zsuskuln 74890f49 original
+      rzvqmyuk fd2ed005 original
+    Working copy  (@) now at: kkmpptxz 1d2578a4 (no description set)
+    Parent commit (@-)      : rzvqmyuk fd2ed005 original
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_with_summary(&work_dir), @r"
+    @  kkmpptxzrspxA child
+    ○  rzvqmyukvvwz original
+    │  A README
+    ○  zsuskulnrvyr original
+    │  A docs/c
+    ○  qpvuntsmwlqt original
+    │  A src/a
+    │  A src/b
+    ◆  zzzzzzzzzzzz
+    [EOF]
+    ");
+
+    // Filesets that don't match any remaining changes are skipped
+    work_dir.run_jj(["undo"]).success();
+    let output = work_dir.run_jj(["split", "-r@-", "--by", "src", "--by", "src/a"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: No remaining changes in the revision match src/a
+    Rebased 1 descendant commits
+    Split into 2 commits:
+      qpvuntsm 5057d849 original
+      yqosqzyt 5d63934e original
+    Working copy  (@) now at: kkmpptxz 7883719d (no description set)
+    Parent commit (@-)      : yqosqzyt 5d63934e original
+    [EOF]
+    ");
+
+    // All changes would end up in one commit
+    work_dir.run_jj(["undo"]).success();
+    let output = work_dir.run_jj(["split", "-r@-", "--by", "all()"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Refusing to split the revision into a single commit
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_split_by_file() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "foo");
+    work_dir.write_file("file2", "foo");
+    work_dir.write_file("file3", "foo");
+
+    let output = work_dir.run_jj(["split", "--by-file"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Split into 3 commits:
+      qpvuntsm a916ed5b (no description set)
+      rlvkpnrz ff9eaa3b (no description set)
+      uuqppmxq cbdcc8fc (no description set)
+    Working copy  (@) now at: uuqppmxq cbdcc8fc (no description set)
+    Parent commit (@-)      : rlvkpnrz ff9eaa3b (no description set)
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_with_summary(&work_dir), @r"
+    @  uuqppmxqsxsnA file3
+    ○  rlvkpnrzqnooA file2
+    ○  qpvuntsmwlqtA file1
+    ◆  zzzzzzzzzzzz
+    [EOF]
+    ");
+
+    work_dir.run_jj(["undo"]).success();
+    let output = work_dir.run_jj(["split", "--by-file", "--parallel"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Split into 3 commits:
+      qpvuntsm 1e2176b9 (no description set)
+      mzvwutvl a3885db8 (no description set)
+      zxsnswpr 9c64874f (no description set)
+    Working copy  (@) now at: zxsnswpr 9c64874f (no description set)
+    Parent commit (@-)      : zzzzzzzz 00000000 (empty) (no description set)
+    Added 0 files, modified 0 files, removed 2 files
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_with_summary(&work_dir), @r"
+    @  zxsnswprywvtA file3
+    │ ○  mzvwutvlkqwtA file2
+    ├─╯
+    │ ○  qpvuntsmwlqtA file1
+    ├─╯
+    ◆  zzzzzzzzzzzz
+    [EOF]
+    ");
+}
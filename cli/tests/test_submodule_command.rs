@@ -0,0 +1,156 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+use crate::common::TestWorkDir;
+
+fn run_git(work_dir: &TestWorkDir, args: &[&str]) {
+    work_dir
+        .run_jj_with(|cmd| {
+            cmd.args(["util", "exec", "--", "git"])
+                // Git normally doesn't allow file:// in submodules.
+                .args(["-c", "protocol.file.allow=always"])
+                .args(args)
+                .env("GIT_AUTHOR_NAME", "Test user")
+                .env("GIT_AUTHOR_EMAIL", "test@example.com")
+                .env("GIT_AUTHOR_DATE", "2001-02-03T04:05:06+07:00")
+                .env("GIT_COMMITTER_NAME", "Test user")
+                .env("GIT_COMMITTER_EMAIL", "test@example.com")
+                .env("GIT_COMMITTER_DATE", "2001-02-03T04:05:06+07:00")
+        })
+        .success();
+}
+
+/// Creates a repo "repo" with a submodule "sub" pointing to the repo
+/// "submodule".
+fn init_repo_with_submodule(test_env: &TestEnvironment) {
+    test_env
+        .run_jj_in(".", ["git", "init", "--colocate", "submodule"])
+        .success();
+    let submodule_dir = test_env.work_dir("submodule");
+    submodule_dir.write_file("file", "1\n");
+    submodule_dir.run_jj(["commit", "-m", "first"]).success();
+
+    test_env
+        .run_jj_in(".", ["git", "init", "--colocate", "repo"])
+        .success();
+    let work_dir = test_env.work_dir("repo");
+    // The relative URL is resolved against the URL of the superproject
+    run_git(
+        &work_dir,
+        &["submodule", "--quiet", "add", "../submodule", "sub"],
+    );
+    run_git(&work_dir, &["commit", "--quiet", "-m", "Add submodule"]);
+}
+
+#[test]
+fn test_submodule_update() {
+    let test_env = TestEnvironment::default();
+    init_repo_with_submodule(&test_env);
+    test_env
+        .run_jj_in(".", ["git", "clone", "repo", "clone"])
+        .success();
+    let work_dir = test_env.work_dir("clone");
+
+    let output = work_dir.run_jj(["submodule", "status"]);
+    insta::assert_snapshot!(output, @"
+    sub 2d7eba5a8c92 (not initialized)
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["submodule", "update"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Checked out submodule sub at 2d7eba5a8c92
+    [EOF]
+    ");
+    assert_eq!(work_dir.read_file("sub/file"), "1\n");
+
+    let output = work_dir.run_jj(["submodule", "status"]);
+    insta::assert_snapshot!(output, @"
+    sub 2d7eba5a8c92
+    [EOF]
+    ");
+
+    // Nothing to do if the submodule is up to date
+    let output = work_dir.run_jj(["submodule", "update"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+
+    // The submodule shouldn't show up as changed
+    let output = work_dir.run_jj(["diff", "--summary"]);
+    insta::assert_snapshot!(output, @"");
+}
+
+#[test]
+fn test_submodule_update_on_checkout() {
+    let test_env = TestEnvironment::default();
+    init_repo_with_submodule(&test_env);
+    let work_dir = test_env.work_dir("repo");
+    let submodule_dir = test_env.work_dir("submodule");
+    submodule_dir.write_file("file", "2\n");
+    submodule_dir.run_jj(["commit", "-m", "second"]).success();
+
+    // Record the new submodule commit in the superproject
+    run_git(&work_dir.dir("sub"), &["pull", "--quiet", "origin", "HEAD"]);
+    run_git(&work_dir, &["commit", "--quiet", "-am", "Update submodule"]);
+    work_dir.run_jj(["git", "import"]).success();
+    assert_eq!(work_dir.read_file("sub/file"), "2\n");
+
+    // Submodules aren't updated by default
+    work_dir
+        .run_jj(["new", "description(substring:'Add submodule')"])
+        .success();
+    assert_eq!(work_dir.read_file("sub/file"), "2\n");
+
+    test_env.add_config("git.update-submodules = true");
+    let output = work_dir.run_jj(["new", "description(substring:'Add submodule')"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Working copy  (@) now at: kpqxywon c8526611 (empty) (no description set)
+    Parent commit (@-)      : ptkuqpsk c8b5289e Add submodule
+    Checked out submodule sub at 2d7eba5a8c92
+    [EOF]
+    ");
+    assert_eq!(work_dir.read_file("sub/file"), "1\n");
+    let output = work_dir.run_jj(["new", "description(substring:'Update submodule')"]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    ignoring git submodule at "sub"
+    Working copy  (@) now at: kmkuslsw 0f7ebe22 (empty) (no description set)
+    Parent commit (@-)      : ktwyonwr 43d1c096 master | Update submodule
+    Added 0 files, modified 1 files, removed 0 files
+    Checked out submodule sub at 4a667e9c0460
+    [EOF]
+    "#);
+    assert_eq!(work_dir.read_file("sub/file"), "2\n");
+}
+
+#[test]
+fn test_submodule_no_gitmodules() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let output = work_dir.run_jj(["submodule", "status"]);
+    insta::assert_snapshot!(output, @"");
+    let output = work_dir.run_jj(["submodule", "update"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+}
@@ -0,0 +1,145 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::CommandOutput;
+use crate::common::TestEnvironment;
+use crate::common::TestWorkDir;
+use crate::common::create_commit_with_files;
+
+#[test]
+fn test_swap() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(&work_dir, "a", &[], &[("file1", "a\n")]);
+    create_commit_with_files(&work_dir, "b", &["a"], &[("file2", "b\n")]);
+    create_commit_with_files(&work_dir, "c", &["b"], &[("file3", "c\n")]);
+    create_commit_with_files(&work_dir, "d", &["c"], &[("file4", "d\n")]);
+    let setup_opid = work_dir.current_operation_id();
+
+    // Swap with the parent
+    let output = work_dir.run_jj(["swap", "c"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Swapped commits:
+      zsuskuln 4ee22bb8 b | b
+      royxmykx 86d0ee4f c | c
+    Rebased 1 descendant commits
+    Working copy  (@) now at: vruxwmqv c2ae279c d | d
+    Parent commit (@-)      : zsuskuln 4ee22bb8 b | b
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @r"
+    @  dA file4
+    ○  bA file2
+    ○  cA file3
+    ○  aA file1
+    ◆
+    [EOF]
+    ");
+
+    // Swap two named revisions, in any order
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    let output = work_dir.run_jj(["swap", "b", "c"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Swapped commits:
+      zsuskuln 0cbfd75f b | b
+      royxmykx 9e8e509e c | c
+    Rebased 1 descendant commits
+    Working copy  (@) now at: vruxwmqv 5f307b70 d | d
+    Parent commit (@-)      : zsuskuln 0cbfd75f b | b
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @r"
+    @  dA file4
+    ○  bA file2
+    ○  cA file3
+    ○  aA file1
+    ◆
+    [EOF]
+    ");
+
+    // Revisions that aren't adjacent can't be swapped
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    let output = work_dir.run_jj(["swap", "a", "c"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Cannot swap commits 76920e024bce and 0a5b222f50eb because neither is the only parent of the other
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // The root commit is immutable
+    let output = work_dir.run_jj(["swap", "a"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: The root commit 000000000000 is immutable
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_swap_dry_run() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(&work_dir, "a", &[], &[("file", "a\n")]);
+    create_commit_with_files(&work_dir, "b", &["a"], &[("file", "b\n")]);
+    create_commit_with_files(&work_dir, "c", &["b"], &[("file", "c\n")]);
+    let setup_opid = work_dir.current_operation_id();
+
+    let output = work_dir.run_jj(["swap", "c", "--dry-run"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Would swap commits:
+      zsuskuln 5164b051 b | b
+      royxmykx 8728d039 c | (conflict) c
+    The swap would introduce conflicts in 1 commits:
+      royxmykx 8728d039 c | (conflict) c
+    Dry-run requested, not swapping.
+    [EOF]
+    ");
+    assert_eq!(work_dir.current_operation_id(), setup_opid);
+
+    let output = work_dir.run_jj(["swap", "c"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Swapped commits:
+      zsuskuln ddd63656 b | b
+      royxmykx 88bde38f c | (conflict) c
+    Working copy  (@) now at: royxmykx 88bde38f c | (conflict) c
+    Parent commit (@-)      : rlvkpnrz b27cb722 a | a
+    Added 0 files, modified 1 files, removed 0 files
+    Warning: There are unresolved conflicts at these paths:
+    file    2-sided conflict
+    New conflicts appeared in 1 commits:
+      royxmykx 88bde38f c | (conflict) c
+    Hint: To resolve the conflicts, start by creating a commit on top of
+    the conflicted commit:
+      jj new royxmykx
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you can inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    [EOF]
+    ");
+}
+
+#[must_use]
+fn get_log_output(work_dir: &TestWorkDir) -> CommandOutput {
+    work_dir.run_jj(["log", "-T", "bookmarks", "--summary"])
+}
@@ -114,6 +114,69 @@ fn test_tag_set_delete() {
     ");
 }
 
+#[test]
+fn test_tag_create() {
+    let test_env = TestEnvironment::default();
+    test_env
+        .run_jj_in(".", ["git", "init", "--colocate", "repo"])
+        .success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.run_jj(["commit", "-mcommit1"]).success();
+
+    let output = work_dir.run_jj(["tag", "create", "-r@-", "light"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Created 1 tags pointing to qpvuntsm b876c5f4 (empty) commit1
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["tag", "create", "-r@-", "-mRelease 1.0", "v1.0"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Created 1 tags pointing to qpvuntsm b876c5f4 (empty) commit1
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @  bbc749308d7f
+    ◆  b876c5f49546 light v1.0
+    ◆  000000000000
+    [EOF]
+    ");
+
+    // The annotated tag is kept as is in Git
+    let output = work_dir.run_jj([
+        "util",
+        "exec",
+        "--",
+        "git",
+        "for-each-ref",
+        "--format=%(refname) %(objecttype) %(*objectname) %(taggername) %(contents:subject)",
+        "refs/tags",
+    ]);
+    insta::assert_snapshot!(output, @"
+    refs/tags/light commit   commit1
+    refs/tags/v1.0 tag b876c5f49546badadb52858bbe7aace109ac8a7b Test User Release 1.0
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["tag", "create", "v1.0"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Tag already exists: v1.0
+    Hint: Use `jj tag set --allow-move` to update existing tags.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = work_dir.run_jj(["tag", "delete", "light", "v1.0"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Deleted 2 tags.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["util", "exec", "--", "git", "tag", "--list"]);
+    insta::assert_snapshot!(output, @"");
+}
+
 #[test]
 fn test_tag_at_root() {
     let test_env = TestEnvironment::default();
@@ -70,7 +70,7 @@ fn test_templater_parse_error() {
       | ^---^
       |
       = Function `oncat` doesn't exist
-    Hint: Did you mean `concat`, `socat`?
+    Hint: Did you mean `concat`, `format`, `socat`?
     [EOF]
     [exit status: 1]
     ");
@@ -541,6 +541,83 @@ fn test_templater_config_function() {
     ");
 }
 
+#[test]
+fn test_templater_var_function() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    test_env.add_config(
+        r#"
+        [template-vars]
+        prefix = "PROJ-"
+        width = 8
+        verbose = true
+        list = ["a", "b"]
+        "#,
+    );
+    let work_dir = test_env.work_dir("repo");
+    let render = |template| get_template_output(&work_dir, "@-", template);
+
+    insta::assert_snapshot!(render("var('prefix').as_string() ++ 42"), @"PROJ-42[EOF]");
+    insta::assert_snapshot!(render("var('width').as_integer() + 1"), @"9[EOF]");
+    insta::assert_snapshot!(render("if(var('verbose').as_boolean(), 'yes')"), @"yes[EOF]");
+
+    // Default is used only if the variable isn't set
+    insta::assert_snapshot!(render("var('prefix', 'X-')"), @r#""PROJ-"[EOF]"#);
+    insta::assert_snapshot!(render("var('unknown', 'X-').as_string()"), @"X-[EOF]");
+    insta::assert_snapshot!(render("var('unknown', 3).as_integer()"), @"3[EOF]");
+    insta::assert_snapshot!(render("var('unknown', false)"), @"false[EOF]");
+
+    // Names are not paths into other config tables
+    insta::assert_snapshot!(render("var('user.name')"), @r"
+    ------- stderr -------
+    Error: Failed to parse template: Template variable `user.name` is not defined
+    Caused by:  --> 1:5
+      |
+    1 | var('user.name')
+      |     ^---------^
+      |
+      = Template variable `user.name` is not defined
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(render("var('unknown')"), @r"
+    ------- stderr -------
+    Error: Failed to parse template: Template variable `unknown` is not defined
+    Caused by:  --> 1:5
+      |
+    1 | var('unknown')
+      |     ^-------^
+      |
+      = Template variable `unknown` is not defined
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(render("var('list')"), @r"
+    ------- stderr -------
+    Error: Failed to parse template: Template variable `list` must be a string, integer, or boolean
+    Caused by:  --> 1:5
+      |
+    1 | var('list')
+      |     ^----^
+      |
+      = Template variable `list` must be a string, integer, or boolean
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(render("var('unknown', description)"), @r"
+    ------- stderr -------
+    Error: Failed to parse template: Expected string, integer, or boolean literal
+    Caused by:  --> 1:16
+      |
+    1 | var('unknown', description)
+      |                ^---------^
+      |
+      = Expected string, integer, or boolean literal
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[must_use]
 fn get_template_output(work_dir: &TestWorkDir, rev: &str, template: &str) -> CommandOutput {
     work_dir.run_jj(["log", "--no-graph", "-r", rev, "-T", template])
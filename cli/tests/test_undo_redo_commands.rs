@@ -164,6 +164,118 @@ fn test_undo_with_rev_arg_falls_back_to_revert() {
     ");
 }
 
+#[test]
+fn test_undo_what() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let log_template = r#"separate(" ", description.first_line(), bookmarks)"#;
+
+    work_dir.run_jj(["describe", "-m", "first"]).success();
+    work_dir.run_jj(["new", "-m", "second"]).success();
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "foo"])
+        .success();
+    insta::assert_snapshot!(work_dir.run_jj(["log", "-T", log_template]), @r"
+    @  second foo
+    ○  first
+    ◆
+    [EOF]
+    ");
+
+    // Abandoning the working-copy commit moves both the bookmark and the
+    // working copy
+    work_dir.run_jj(["abandon"]).success();
+    insta::assert_snapshot!(work_dir.run_jj(["log", "-T", log_template]), @r"
+    @
+    ○  first
+    ◆
+    [EOF]
+    ");
+
+    // Only the bookmark is restored, which makes its target visible again
+    let output = work_dir.run_jj(["undo", "--what", "bookmarks"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Restored to operation: 48bdca5745fe (2001-02-03 08:05:10) create bookmark foo pointing to commit b1cb6b2f9141e6ffee18532a8bf9a2075ca02606
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["log", "-T", log_template]), @r"
+    @
+    │ ○  second foo
+    ├─╯
+    ○  first
+    ◆
+    [EOF]
+    ");
+
+    // Only the working copy is restored
+    let output = work_dir.run_jj(["op", "restore", "--what", "working-copy", "@--"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Restored to operation: 48bdca5745fe (2001-02-03 08:05:10) create bookmark foo pointing to commit b1cb6b2f9141e6ffee18532a8bf9a2075ca02606
+    Working copy  (@) now at: kkmpptxz b1cb6b2f foo | (empty) second
+    Parent commit (@-)      : qpvuntsm 68a50538 (empty) first
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["log", "-T", log_template]), @r"
+    @  second foo
+    │ ○
+    ├─╯
+    ○  first
+    ◆
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_undo_what_is_not_in_undo_stack() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let log_template = r#"separate(" ", description.first_line(), bookmarks)"#;
+
+    work_dir.run_jj(["describe", "-m", "first"]).success();
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "foo"])
+        .success();
+    work_dir.run_jj(["new", "-m", "second"]).success();
+    work_dir
+        .run_jj(["bookmark", "move", "--to=@", "foo"])
+        .success();
+    work_dir.run_jj(["undo"]).success();
+    work_dir
+        .run_jj(["undo", "--what", "working-copy"])
+        .success();
+    insta::assert_snapshot!(work_dir.run_jj(["op", "log", "-n1", "-Tdescription"]), @r"
+    @  undo: restore working-copy to operation b1b7f798a07910a54633634852d237f8ced06b70feeb04b188622ddac26697fd7559e5cf0b765705d5b1f34b366a25090716dc91fc544af2f04eb06c167e9b42
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["log", "-T", log_template]), @r"
+    ○  second
+    @  first foo
+    ◆
+    [EOF]
+    ");
+
+    // The partial undo can't be redone
+    insta::assert_snapshot!(work_dir.run_jj(["redo"]), @r"
+    ------- stderr -------
+    Error: Nothing to redo
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Undoing again reverts the partial undo instead of going further back
+    work_dir.run_jj(["undo"]).success();
+    insta::assert_snapshot!(work_dir.run_jj(["log", "-T", log_template]), @r"
+    @  second
+    ○  first foo
+    ◆
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_can_only_redo_undo_operation() {
     let test_env = TestEnvironment::default();
@@ -209,6 +321,11 @@ fn test_jump_over_old_redo_stack() {
     assert_eq!(work_dir.read_file("state"), "C");
 
     // jump over two adjacent redo-stacks
+    insta::assert_snapshot!(work_dir.run_jj(["redo", "--what"]), @r"
+    Would restore to operation: b7139d67ae55 (2001-02-03 08:05:11) snapshot working copy
+    [EOF]
+    ");
+    assert_eq!(work_dir.read_file("state"), "C");
     work_dir.run_jj(["redo"]).success();
     assert_eq!(work_dir.read_file("state"), "D");
 
@@ -219,4 +336,10 @@ fn test_jump_over_old_redo_stack() {
     [EOF]
     [exit status: 1]
     ");
+    insta::assert_snapshot!(work_dir.run_jj(["redo", "--what"]), @r"
+    ------- stderr -------
+    Error: Nothing to redo
+    [EOF]
+    [exit status: 1]
+    ");
 }
@@ -105,6 +105,130 @@ fn test_gc_operation_log() {
     ");
 }
 
+#[test]
+fn test_gc_operation_retention() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("file", "a change\n");
+    work_dir.run_jj(["commit", "-m", "a change"]).success();
+    work_dir.write_file("file", "another change\n");
+    work_dir
+        .run_jj(["commit", "-m", "another change"])
+        .success();
+
+    // All operations are recent enough to be kept
+    let output = work_dir.run_jj(["util", "gc", "--config=gc.operation-retention-days=100000"]);
+    insta::assert_snapshot!(output, @"");
+    let output = work_dir.run_jj(["op", "log", "-Tdescription"]);
+    insta::assert_snapshot!(output, @r"
+    @  commit 403fc5d84524fba8f6415825e025c3392a4b135b
+    ○  snapshot working copy
+    ○  commit 8b157fea292461303265e56568d4dd7db079c3fa
+    ○  snapshot working copy
+    ○  add workspace 'default'
+    ○
+    [EOF]
+    ");
+
+    // The operations in tests are made in 2001, so only the current operation
+    // is kept
+    let output = work_dir.run_jj(["util", "gc", "--config=gc.operation-retention-days=30"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Abandoned 4 operations and reparented 1 descendant operations.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["op", "log", "-Tdescription"]);
+    insta::assert_snapshot!(output, @r"
+    @  commit 403fc5d84524fba8f6415825e025c3392a4b135b
+    ○
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-Tdescription"]);
+    insta::assert_snapshot!(output, @r"
+    @
+    ○  another change
+    ○  a change
+    ◆
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_util_rerere() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("rerere.enabled = true");
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file", "a\n");
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "base"])
+        .success();
+    for name in ["side1", "side2", "side3"] {
+        work_dir.run_jj(["new", "base"]).success();
+        work_dir
+            .run_jj(["bookmark", "create", "-r@", name])
+            .success();
+        let contents = if name == "side1" { "b\n" } else { "c\n" };
+        work_dir.write_file("file", contents);
+    }
+    work_dir
+        .run_jj(["rebase", "-r=side2", "-d=side1"])
+        .success();
+    let output = work_dir.run_jj(["util", "rerere", "list"]);
+    insta::assert_snapshot!(output, @"");
+
+    // Resolving the conflict in the working copy records the resolution
+    work_dir.run_jj(["edit", "side2"]).success();
+    work_dir.write_file("file", "b\nc\n");
+    let output = work_dir.run_jj(["util", "rerere", "list"]);
+    insta::assert_snapshot!(output, @"
+    4c962039d104e1c441aa3ac63bd7be62fa2b1145 file
+    [EOF]
+    ");
+
+    // The same conflict is resolved when it appears again
+    let output = work_dir.run_jj(["rebase", "-r=side3", "-d=side1"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 1 commits to destination
+    Resolved conflicts using recorded resolutions in 1 commits:
+      yqosqzyt f53de806 side3 | (no description set)
+        file
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r=side3", "file"]);
+    insta::assert_snapshot!(output, @"
+    b
+    c
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["util", "rerere", "forget", "4c96"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Forgot 1 recorded conflict resolutions
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["util", "rerere", "forget", "4c96"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: No recorded resolution matches fingerprint 4c96
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["util", "rerere", "forget", "--all"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Forgot 0 recorded conflict resolutions
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["util", "rerere", "list"]);
+    insta::assert_snapshot!(output, @"");
+}
+
 #[test]
 fn test_shell_completions() {
     #[track_caller]
@@ -261,3 +385,82 @@ fn test_install_man_pages() {
     assert!(man_dir.is_dir());
     assert!(fs::read_dir(man_dir).unwrap().next().is_some());
 }
+
+#[test]
+fn test_fsck() {
+    let test_env = TestEnvironment::default();
+    test_env
+        .run_jj_in(".", ["debug", "init-simple", "repo"])
+        .success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("file1", "foo\n");
+    work_dir.write_file("file2", "bar\n");
+    work_dir.run_jj(["commit", "-m", "first"]).success();
+
+    let output = work_dir.run_jj(["util", "fsck"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Checked 5 commits, 2 trees, and 2 files.
+    No problems found.
+    [EOF]
+    ");
+
+    // Corrupt the contents of one of the files
+    let files_dir = work_dir.root().join(".jj/repo/store/files");
+    let corrupt_path = std::fs::read_dir(&files_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| std::fs::read(path).unwrap() == b"foo\n")
+        .unwrap();
+    std::fs::write(&corrupt_path, "baz\n").unwrap();
+    let output = work_dir.run_jj(["util", "fsck"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Checked 5 commits, 2 trees, and 2 files.
+    Warning: The contents of file d202d7951df2c4b711ca44b4bcc9d7b363fa4252127e058c1a910ec05b6cd038d71cc21221c031c0359f993e746b07f5965cf8c5c3746a58337ad9ab65278e77 hash to fb4f45488f3231ace496a88392b3f2e2c284599224f57978b7bb6a5e09dfd111624ad5810b9cf3b22f462d083870776dd4e9b4aaa78ed3c77a0e110913f3f3e1
+    Error: Found 1 problems
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Rebuilding the index doesn't help
+    let output = work_dir.run_jj(["util", "fsck", "--repair"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Checked 5 commits, 2 trees, and 2 files.
+    Warning: The contents of file d202d7951df2c4b711ca44b4bcc9d7b363fa4252127e058c1a910ec05b6cd038d71cc21221c031c0359f993e746b07f5965cf8c5c3746a58337ad9ab65278e77 hash to fb4f45488f3231ace496a88392b3f2e2c284599224f57978b7bb6a5e09dfd111624ad5810b9cf3b22f462d083870776dd4e9b4aaa78ed3c77a0e110913f3f3e1
+    Error: Found 1 problems
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_gc_compact() {
+    let test_env = TestEnvironment::default();
+    test_env
+        .run_jj_in(".", ["debug", "init-simple", "repo"])
+        .success();
+    let work_dir = test_env.work_dir("repo");
+    let content = (0..100).map(|i| format!("line {i}\n")).collect::<String>();
+    work_dir.write_file("file", &content);
+    work_dir.run_jj(["commit", "-m", "first"]).success();
+    work_dir.write_file("file", content.replace("line 50\n", "modified\n"));
+    work_dir.run_jj(["commit", "-m", "second"]).success();
+
+    let output = work_dir.run_jj(["util", "gc", "--compact"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Packed 12 objects, 1 of them as deltas.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r@--", "file"]);
+    assert_eq!(output.stdout.raw(), content);
+    let output = work_dir.run_jj(["util", "fsck"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Checked 8 commits, 3 trees, and 2 files.
+    No problems found.
+    [EOF]
+    ");
+}
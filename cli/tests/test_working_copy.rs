@@ -164,6 +164,100 @@ fn test_snapshot_large_file_restore() {
     ");
 }
 
+#[test]
+fn test_case_collisions() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("Foo", "upper");
+    work_dir.write_file("foo", "lower");
+    work_dir.write_file("bar", "bar");
+    work_dir.run_jj(["describe", "-mcolliding"]).success();
+    work_dir.run_jj(["new", "root()"]).success();
+    test_env.add_config(r#"working-copy.case-collisions = "skip""#);
+
+    // Only one of the colliding paths is checked out
+    let output = work_dir.run_jj(["new", "subject(colliding)"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Working copy  (@) now at: zsuskuln 741de6b9 (empty) (no description set)
+    Parent commit (@-)      : qpvuntsm 84f205df colliding
+    Added 2 files, modified 0 files, removed 0 files
+    Warning: Skipped checking out paths that differ only in case from other paths:
+      foo (collides with Foo)
+    Hint: The skipped paths are left unchanged in the working-copy commit. Rename them to check them out.
+    [EOF]
+    ");
+    assert!(!work_dir.root().join("foo").exists());
+    let output = work_dir.run_jj(["file", "list"]);
+    insta::assert_snapshot!(output, @r"
+    Foo
+    bar
+    foo
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["diff", "--summary", "--from=subject(colliding)"]);
+    insta::assert_snapshot!(output, @"");
+
+    // Checking out the colliding paths can be refused
+    work_dir.run_jj(["new", "root()"]).success();
+    let output = work_dir.run_jj([
+        "new",
+        "subject(colliding)",
+        "--config=working-copy.case-collisions=error",
+    ]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Error: Failed to check out commit f3a2ac31e60cc3a547af3b20517810c8719cc2bf
+    Caused by: Some paths differ only in case from other paths
+    Hint: Colliding paths:
+      foo (collides with Foo)
+    Hint: Rename the paths, or set `working-copy.case-collisions` to `"skip"` or `"suffix"` to check them out anyway.
+    [EOF]
+    [exit status: 1]
+    "#);
+
+    // The colliding paths can be checked out under other names
+    test_env.add_config(r#"working-copy.case-collisions = "suffix""#);
+    let output = work_dir.run_jj(["workspace", "update-stale"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Working copy  (@) now at: vruxwmqv f3a2ac31 (empty) (no description set)
+    Parent commit (@-)      : qpvuntsm 84f205df colliding
+    Added 3 files, modified 0 files, removed 0 files
+    Warning: Checked out paths that differ only in case from other paths under other names:
+      foo as foo.case-collision-1 (collides with Foo)
+    Hint: Changes to the renamed files are recorded at their original paths.
+    Updated working copy to fresh commit f3a2ac31e60c
+    [EOF]
+    ");
+    assert_eq!(work_dir.read_file("foo.case-collision-1"), "lower");
+    work_dir.write_file("foo.case-collision-1", "lower modified");
+    let output = work_dir.run_jj(["diff", "--summary"]);
+    insta::assert_snapshot!(output, @r"
+    M foo
+    [EOF]
+    ");
+
+    // New files which collide with tracked files aren't tracked
+    work_dir.write_file("BAR", "new");
+    let output = work_dir.run_jj(["status"]);
+    insta::assert_snapshot!(output, @r"
+    Working copy changes:
+    M foo
+    Untracked paths:
+    ? BAR
+    Working copy  (@) : vruxwmqv 4d2e281e (no description set)
+    Parent commit (@-): qpvuntsm 84f205df colliding
+    [EOF]
+    ------- stderr -------
+    Warning: Refused to snapshot some files:
+      BAR: differs only in case from bar
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_materialize_and_snapshot_different_conflict_markers() {
     let test_env = TestEnvironment::default();
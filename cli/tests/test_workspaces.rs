@@ -1384,6 +1384,53 @@ fn test_workspaces_forget_abandon_commits() {
     ");
 }
 
+#[test]
+fn test_workspaces_add_ephemeral() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    let main_dir = test_env.work_dir("main");
+
+    main_dir.write_file("file", "contents");
+    main_dir.run_jj(["commit", "-m", "initial"]).success();
+    main_dir
+        .run_jj(["workspace", "add", "--ephemeral", "../ci"])
+        .success();
+    let ci_dir = test_env.work_dir("ci");
+    ci_dir.write_file("file", "modified");
+    ci_dir.run_jj(["debug", "snapshot"]).success();
+
+    // The ephemeral working-copy commit is hidden from the default log
+    let output = main_dir.run_jj(["log", "-T", "commit_id.short() ++ ' ' ++ working_copies"]);
+    insta::assert_snapshot!(output, @r"
+    @  504e3d8c1bcd default@
+    ○  7b22a8cbe888
+    ◆  000000000000
+    [EOF]
+    ");
+    let output = main_dir.run_jj([
+        "log",
+        "-T",
+        "commit_id.short() ++ ' ' ++ working_copies",
+        "-r",
+        "ephemeral_working_copies()",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    ○  a390e9dc9c4b ci@
+    │
+    ~
+    [EOF]
+    ");
+
+    // Forgetting the workspace abandons the commit even though it has changes
+    main_dir.run_jj(["workspace", "forget", "ci"]).success();
+    insta::assert_snapshot!(get_log_output(&main_dir), @r#"
+    @  504e3d8c1bcd
+    ○  7b22a8cbe888 "initial"
+    ◆  000000000000
+    [EOF]
+    "#);
+}
+
 /// Test context of commit summary template
 #[test]
 fn test_list_workspaces_template() {
@@ -1595,6 +1642,51 @@ fn test_workspaces_rename_workspace() {
     ");
 }
 
+#[test]
+fn test_workspaces_working_copies_revset() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    let main_dir = test_env.work_dir("main");
+    main_dir.run_jj(["commit", "-m", "first"]).success();
+    main_dir
+        .run_jj(["workspace", "add", "--name", "second", "../secondary"])
+        .success();
+    main_dir
+        .run_jj(["workspace", "add", "--name", "third", "-r@-", "../third"])
+        .success();
+
+    // Each working-copy commit is labeled with the workspace it belongs to
+    let template = r#"separate(" ", commit_id.short(), working_copies) ++ "\n""#;
+    let output = main_dir.run_jj([
+        "log",
+        "-T",
+        template,
+        "--no-graph",
+        "-r",
+        "working_copies()",
+    ]);
+    insta::assert_snapshot!(output, @"
+    a2c3d9a9a09f third@
+    882f79c74fcc second@
+    eb9fd2ab82e7 default@
+    [EOF]
+    ");
+
+    let output = main_dir.run_jj([
+        "log",
+        "-T",
+        template,
+        "--no-graph",
+        "-r",
+        "all() ~ working_copies()",
+    ]);
+    insta::assert_snapshot!(output, @"
+    68a505386f93
+    000000000000
+    [EOF]
+    ");
+}
+
 #[must_use]
 fn get_log_output(work_dir: &TestWorkDir) -> CommandOutput {
     let template = r#"
@@ -30,6 +30,7 @@ use crate::annotate::FileAnnotator;
 use crate::backend::BackendError;
 use crate::backend::BackendResult;
 use crate::backend::CommitId;
+use crate::backend::CopyId;
 use crate::backend::TreeValue;
 use crate::commit::Commit;
 use crate::commit::conflict_label_for_commits;
@@ -92,6 +93,44 @@ pub struct SelectedTrees {
     pub skipped_paths: Vec<(RepoPathBuf, String)>,
 }
 
+/// Hunks of the source commit mapped to the destination commits they should be
+/// absorbed into.
+#[derive(Clone, Debug, Default)]
+pub struct AbsorbHunks {
+    /// Modified files with the hunks that can be absorbed.
+    pub files: Vec<AbsorbFile>,
+    /// Paths that were not absorbed for various error reasons.
+    pub skipped_paths: Vec<(RepoPathBuf, String)>,
+}
+
+/// File modified by the source commit.
+#[derive(Clone, Debug)]
+pub struct AbsorbFile {
+    /// Path to the file.
+    pub path: RepoPathBuf,
+    /// File content in the parent of the source commit.
+    pub left_text: BString,
+    /// File content in the source commit.
+    pub right_text: BString,
+    /// Hunks to be absorbed, ordered by position in the file.
+    pub hunks: Vec<AbsorbHunk>,
+    executable: bool,
+    copy_id: CopyId,
+    deleted: bool,
+}
+
+/// Hunk of a modified file and the commit it should be absorbed into.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AbsorbHunk {
+    /// Commit to absorb the hunk into. It can be changed to any other
+    /// destination commit before the trees are built.
+    pub destination: CommitId,
+    /// Byte range in the parent (= left) content.
+    pub left_range: Range<usize>,
+    /// Byte range in the source (= right) content.
+    pub right_range: Range<usize>,
+}
+
 /// Builds trees to be merged into destination commits by splitting source
 /// changes based on file annotation.
 pub async fn split_hunks_to_trees(
@@ -100,7 +139,18 @@ pub async fn split_hunks_to_trees(
     destinations: &Arc<ResolvedRevsetExpression>,
     matcher: &dyn Matcher,
 ) -> Result<SelectedTrees, AbsorbError> {
-    let mut selected_trees = SelectedTrees::default();
+    let hunks = map_hunks_to_destinations(repo, source, destinations, matcher).await?;
+    Ok(hunks.to_selected_trees(repo, source).await?)
+}
+
+/// Maps source changes to destination commits based on file annotation.
+pub async fn map_hunks_to_destinations(
+    repo: &dyn Repo,
+    source: &AbsorbSource,
+    destinations: &Arc<ResolvedRevsetExpression>,
+    matcher: &dyn Matcher,
+) -> Result<AbsorbHunks, AbsorbError> {
+    let mut absorb_hunks = AbsorbHunks::default();
 
     let left_tree = &source.parent_tree;
     let right_tree = source.commit.tree();
@@ -125,7 +175,7 @@ pub async fn split_hunks_to_trees(
             // New file should have no destinations
             Ok(None) => continue,
             Err(reason) => {
-                selected_trees
+                absorb_hunks
                     .skipped_paths
                     .push((left_path.to_owned(), reason));
                 continue;
@@ -135,7 +185,7 @@ pub async fn split_hunks_to_trees(
             Ok(Some(mut value)) => (value.read_all(right_path).await?, false),
             Ok(None) => (vec![], true),
             Err(reason) => {
-                selected_trees
+                absorb_hunks
                     .skipped_paths
                     .push((right_path.to_owned(), reason));
                 continue;
@@ -153,34 +203,87 @@ pub async fn split_hunks_to_trees(
             .collect_vec();
         let diff = ContentDiff::by_line([&left_text, &right_text]);
         let selected_ranges = split_file_hunks(&annotation_ranges, &diff);
-        // Build trees containing parent (= left) contents + selected hunks
-        for (&commit_id, ranges) in &selected_ranges {
-            let tree_builder = selected_trees
-                .target_commits
-                .entry(commit_id.clone())
-                .or_insert_with(|| MergedTreeBuilder::new(left_tree.clone()));
-            let new_text = combine_texts(&left_text, &right_text, ranges);
-            // Since changes to be absorbed are represented as diffs relative to
-            // the source parent, we can propagate file deletion only if the
-            // whole file content is deleted at a single destination commit.
-            let new_tree_value = if new_text.is_empty() && deleted {
-                Merge::absent()
-            } else {
-                let id = repo
-                    .store()
-                    .write_file(left_path, &mut new_text.as_slice())
-                    .await?;
-                Merge::normal(TreeValue::File {
-                    id,
-                    executable,
-                    copy_id: copy_id.clone(),
-                })
-            };
-            tree_builder.set_or_remove(left_path.to_owned(), new_tree_value);
+        let hunks = selected_ranges
+            .into_iter()
+            .flat_map(|(commit_id, ranges)| {
+                ranges
+                    .into_iter()
+                    .map(|(left_range, right_range)| AbsorbHunk {
+                        destination: commit_id.clone(),
+                        left_range,
+                        right_range,
+                    })
+            })
+            .sorted_by_key(|hunk| (hunk.left_range.start, hunk.left_range.end))
+            .collect_vec();
+        drop(diff);
+        if hunks.is_empty() {
+            continue;
         }
+        absorb_hunks.files.push(AbsorbFile {
+            path: left_path.to_owned(),
+            left_text: left_text.into(),
+            right_text: right_text.into(),
+            hunks,
+            executable,
+            copy_id,
+            deleted,
+        });
     }
 
-    Ok(selected_trees)
+    Ok(absorb_hunks)
+}
+
+impl AbsorbHunks {
+    /// Builds trees to be merged into destination commits from the hunks.
+    pub async fn to_selected_trees(
+        &self,
+        repo: &dyn Repo,
+        source: &AbsorbSource,
+    ) -> BackendResult<SelectedTrees> {
+        let mut selected_trees = SelectedTrees {
+            target_commits: HashMap::new(),
+            skipped_paths: self.skipped_paths.clone(),
+        };
+        let left_tree = &source.parent_tree;
+        for file in &self.files {
+            let hunks_by_destination = file
+                .hunks
+                .iter()
+                .into_group_map_by(|hunk| hunk.destination.clone());
+            // Build trees containing parent (= left) contents + selected hunks
+            for (commit_id, hunks) in hunks_by_destination {
+                let tree_builder = selected_trees
+                    .target_commits
+                    .entry(commit_id)
+                    .or_insert_with(|| MergedTreeBuilder::new(left_tree.clone()));
+                let ranges = hunks
+                    .iter()
+                    .map(|hunk| (hunk.left_range.clone(), hunk.right_range.clone()))
+                    .collect_vec();
+                let new_text = combine_texts(&file.left_text, &file.right_text, &ranges);
+                // Since changes to be absorbed are represented as diffs relative
+                // to the source parent, we can propagate file deletion only if
+                // the whole file content is deleted at a single destination
+                // commit.
+                let new_tree_value = if new_text.is_empty() && file.deleted {
+                    Merge::absent()
+                } else {
+                    let id = repo
+                        .store()
+                        .write_file(&file.path, &mut new_text.as_slice())
+                        .await?;
+                    Merge::normal(TreeValue::File {
+                        id,
+                        executable: file.executable,
+                        copy_id: file.copy_id.clone(),
+                    })
+                };
+                tree_builder.set_or_remove(file.path.clone(), new_tree_value);
+            }
+        }
+        Ok(selected_trees)
+    }
 }
 
 type SelectedRange = (Range<usize>, Range<usize>);
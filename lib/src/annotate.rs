@@ -39,6 +39,7 @@ use crate::conflicts::MaterializedTreeValue;
 use crate::conflicts::materialize_merge_result_to_bytes;
 use crate::conflicts::materialize_tree_value;
 use crate::diff::ContentDiff;
+use crate::diff::DiffAlgorithm;
 use crate::diff::DiffHunkKind;
 use crate::files::FileMergeHunkLevel;
 use crate::fileset::FilesetExpression;
@@ -453,6 +454,7 @@ async fn get_file_contents(
                 merge: MergeOptions {
                     hunk_level: FileMergeHunkLevel::Line,
                     same_change: SameChange::Accept,
+                    diff_algorithm: DiffAlgorithm::Histogram,
                 },
             };
             Ok(materialize_merge_result_to_bytes(
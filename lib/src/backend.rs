@@ -437,6 +437,10 @@ pub trait Backend: Any + Send + Sync + Debug {
     /// sent.
     fn concurrency(&self) -> usize;
 
+    /// Reads the contents of the file `id`.
+    ///
+    /// Large files should be read incrementally from the returned stream, so
+    /// they don't have to fit in memory.
     async fn read_file(
         &self,
         path: &RepoPath,
@@ -480,6 +484,25 @@ pub trait Backend: Any + Send + Sync + Debug {
 
     async fn write_tree(&self, path: &RepoPath, contents: &Tree) -> BackendResult<TreeId>;
 
+    /// Computes the ID `write_tree()` would return for the tree, without
+    /// writing it.
+    ///
+    /// This lets the store keep trees in memory until they are referenced by
+    /// a commit. Backends that can't compute tree IDs up front should return
+    /// `None`, in which case trees are always written immediately.
+    fn compute_tree_id(&self, _path: &RepoPath, _contents: &Tree) -> Option<TreeId> {
+        None
+    }
+
+    /// Computes the ID `write_file()` would return for the file contents,
+    /// without writing them.
+    ///
+    /// This is used for verifying stored objects. Backends that can't compute
+    /// file IDs up front should return `None`.
+    fn compute_file_id(&self, _path: &RepoPath, _contents: &[u8]) -> Option<FileId> {
+        None
+    }
+
     async fn read_commit(&self, id: &CommitId) -> BackendResult<Commit>;
 
     /// Writes a commit and returns its ID and the commit itself. The commit
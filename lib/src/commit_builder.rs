@@ -32,6 +32,7 @@ use crate::merge::Merge;
 use crate::merged_tree::MergedTree;
 use crate::repo::MutableRepo;
 use crate::repo::Repo;
+use crate::repo_path::RepoPathBuf;
 use crate::settings::JJRng;
 use crate::settings::SignSettings;
 use crate::settings::UserSettings;
@@ -90,6 +91,13 @@ impl CommitBuilder<'_> {
         self
     }
 
+    /// Sets the paths of the conflicts that were resolved using recorded
+    /// resolutions when the tree was computed.
+    pub(crate) fn set_auto_resolved_paths(mut self, paths: Vec<RepoPathBuf>) -> Self {
+        self.inner.auto_resolved_paths = paths;
+        self
+    }
+
     /// [`Commit::is_empty()`] for the new commit.
     pub fn is_empty(&self) -> BackendResult<bool> {
         self.inner.is_empty(self.mut_repo)
@@ -181,6 +189,8 @@ pub struct DetachedCommitBuilder {
     rewrite_source: Option<Commit>,
     sign_settings: SignSettings,
     record_predecessors_in_commit: bool,
+    /// Paths of the conflicts that were resolved using recorded resolutions.
+    auto_resolved_paths: Vec<RepoPathBuf>,
 }
 
 impl DetachedCommitBuilder {
@@ -219,6 +229,7 @@ impl DetachedCommitBuilder {
             predecessors: vec![],
             sign_settings: settings.sign_settings(),
             record_predecessors_in_commit,
+            auto_resolved_paths: vec![],
         }
     }
 
@@ -267,6 +278,7 @@ impl DetachedCommitBuilder {
             predecessors: vec![predecessor.id().clone()],
             sign_settings: settings.sign_settings(),
             record_predecessors_in_commit,
+            auto_resolved_paths: vec![],
         }
     }
 
@@ -417,6 +429,9 @@ impl DetachedCommitBuilder {
             ));
         }
         mut_repo.add_head(&commit)?;
+        if !self.auto_resolved_paths.is_empty() {
+            mut_repo.set_auto_resolved_paths(commit.id().clone(), self.auto_resolved_paths);
+        }
         mut_repo.set_predecessors(commit.id().clone(), self.predecessors);
         if let Some(rewrite_source) = self.rewrite_source {
             mut_repo.set_rewritten_commit(rewrite_source.id().clone(), commit.id().clone());
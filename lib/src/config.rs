@@ -286,6 +286,9 @@ pub enum ConfigSource {
     EnvBase,
     /// User configuration files.
     User,
+    /// Configuration file checked in to the repository, which is loaded only
+    /// if the user trusts its content.
+    RepoManaged,
     /// Repo configuration files.
     Repo,
     /// Workspace configuration files.
@@ -302,6 +305,7 @@ impl Display for ConfigSource {
         let c = match self {
             Default => "default",
             User => "user",
+            RepoManaged => "repo-managed",
             Repo => "repo",
             Workspace => "workspace",
             CommandArg => "cli",
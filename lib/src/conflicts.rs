@@ -45,10 +45,13 @@ use crate::copies::CopiesTreeDiffEntryPath;
 use crate::diff::ContentDiff;
 use crate::diff::DiffHunk;
 use crate::diff::DiffHunkKind;
+use crate::file_util;
 use crate::files;
+use crate::files::MAX_CONTENT_SIZE;
 use crate::files::MergeResult;
 use crate::merge::Diff;
 use crate::merge::Merge;
+use crate::merge::MergeBuilder;
 use crate::merge::MergedTreeValue;
 use crate::merge::SameChange;
 use crate::repo_path::RepoPath;
@@ -96,24 +99,23 @@ async fn get_file_contents(
     store: &Store,
     path: &RepoPath,
     term: &Option<FileId>,
-) -> BackendResult<BString> {
+    max_size: u64,
+) -> BackendResult<Option<BString>> {
     match term {
         Some(id) => {
-            let mut reader = store.read_file(path, id).await?;
-            let mut content = vec![];
-            reader
-                .read_to_end(&mut content)
+            let reader = store.read_file(path, id).await?;
+            let content = file_util::read_to_end_with_limit(reader, max_size)
                 .await
                 .map_err(|err| BackendError::ReadFile {
                     path: path.to_owned(),
                     id: id.clone(),
                     source: err.into(),
                 })?;
-            Ok(BString::new(content))
+            Ok(content.map(BString::new))
         }
         // If the conflict had removed the file on one side, we pretend that the file
         // was empty there.
-        None => Ok(BString::new(vec![])),
+        None => Ok(Some(BString::new(vec![]))),
     }
 }
 
@@ -122,9 +124,25 @@ pub async fn extract_as_single_hunk(
     store: &Store,
     path: &RepoPath,
 ) -> BackendResult<Merge<BString>> {
-    merge
-        .try_map_async(|term| get_file_contents(store, path, term))
-        .await
+    let contents = extract_as_single_hunk_with_limit(merge, store, path, u64::MAX).await?;
+    Ok(contents.expect("no file should be larger than u64::MAX"))
+}
+
+/// Like [`extract_as_single_hunk()`], but returns `None` if any of the files
+/// is larger than `max_size` bytes. The rest of such a file isn't read.
+pub async fn extract_as_single_hunk_with_limit(
+    merge: &Merge<Option<FileId>>,
+    store: &Store,
+    path: &RepoPath,
+    max_size: u64,
+) -> BackendResult<Option<Merge<BString>>> {
+    let contents = merge
+        .try_map_async(|term| get_file_contents(store, path, term, max_size))
+        .await?;
+    Ok(contents
+        .into_iter()
+        .collect::<Option<MergeBuilder<_>>>()
+        .map(MergeBuilder::build))
 }
 
 /// A type similar to `MergedTreeValue` but with associated data to include in
@@ -259,7 +277,8 @@ async fn materialize_tree_value_no_access_denied(
 }
 
 /// Suppose `conflict` contains only files or absent entries, reads the file
-/// contents.
+/// contents. Returns `None` if any of the files is larger than
+/// [`MAX_CONTENT_SIZE`], so huge files aren't loaded into memory.
 pub async fn try_materialize_file_conflict_value(
     store: &Store,
     path: &RepoPath,
@@ -272,7 +291,11 @@ pub async fn try_materialize_file_conflict_value(
         return Ok(None);
     };
     let (labels, ids) = conflict_labels.simplify_with(&unsimplified_ids);
-    let contents = extract_as_single_hunk(&ids, store, path).await?;
+    let Some(contents) =
+        extract_as_single_hunk_with_limit(&ids, store, path, MAX_CONTENT_SIZE).await?
+    else {
+        return Ok(None);
+    };
     let executable = resolve_file_executable(&executable_bits);
     Ok(Some(MaterializedFileConflictValue {
         unsimplified_ids,
@@ -1021,7 +1044,15 @@ pub async fn update_from_content(
 ) -> BackendResult<Merge<Option<FileId>>> {
     let simplified_file_ids = file_ids.simplify();
 
-    let old_contents = extract_as_single_hunk(&simplified_file_ids, store, path).await?;
+    let Some(old_contents) =
+        extract_as_single_hunk_with_limit(&simplified_file_ids, store, path, MAX_CONTENT_SIZE)
+            .await?
+    else {
+        // Conflicts between huge files aren't materialized with markers, so
+        // the new content is a resolution.
+        let file_id = store.write_file(path, &mut &content[..]).await?;
+        return Ok(Merge::normal(file_id));
+    };
     let old_hunks = files::merge_hunks(&old_contents, store.merge_options());
 
     // Parse conflicts from the new content using the arity of the simplified
@@ -0,0 +1,547 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Content filters selected by the `filter` and `ident` attributes in
+//! `.gitattributes`.
+//!
+//! A filter driver converts file contents when they're snapshotted ("clean")
+//! and when they're checked out ("smudge"). Like in git, a driver can either
+//! run a command per file, or talk to a long-running process using git's
+//! [filter protocol](https://git-scm.com/docs/long-running-process-protocol).
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use std::io::BufReader;
+use std::io::Read as _;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Child;
+use std::process::ChildStdin;
+use std::process::ChildStdout;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use bstr::ByteSlice as _;
+use thiserror::Error;
+
+use crate::backend::FileId;
+use crate::config::ConfigGetError;
+use crate::git_lfs::LfsObjectCache;
+use crate::object_id::ObjectId as _;
+use crate::repo_path::RepoPath;
+use crate::settings::UserSettings;
+
+/// Commands of a filter driver, like git's `filter.<driver>.*` config.
+///
+/// In the command arguments, `$path` is replaced with the path of the file
+/// relative to the workspace root.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FilterDriver {
+    /// Command converting the working-copy contents into the stored contents.
+    #[serde(default, deserialize_with = "deserialize_command")]
+    pub clean: Option<Vec<String>>,
+    /// Command converting the stored contents into the working-copy contents.
+    #[serde(default, deserialize_with = "deserialize_command")]
+    pub smudge: Option<Vec<String>>,
+    /// Long-running process implementing git's filter protocol. Used instead
+    /// of `clean` and `smudge` for the capabilities it supports.
+    #[serde(default, deserialize_with = "deserialize_command")]
+    pub process: Option<Vec<String>>,
+    /// Whether a failing filter is an error. Otherwise, the contents are used
+    /// as is.
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl FilterDriver {
+    /// Loads the filter drivers from the `working-copy.filters` table in the
+    /// [`UserSettings`].
+    pub fn try_from_settings(
+        user_settings: &UserSettings,
+    ) -> Result<HashMap<String, Self>, ConfigGetError> {
+        user_settings.get("working-copy.filters")
+    }
+}
+
+fn deserialize_command<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let command: Option<Vec<String>> = serde::Deserialize::deserialize(deserializer)?;
+    if command.as_ref().is_some_and(|command| command.is_empty()) {
+        return Err(serde::de::Error::invalid_length(0, &"a non-empty command"));
+    }
+    Ok(command)
+}
+
+/// Direction in which a filter converts the file contents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterDirection {
+    /// From the working copy to the store.
+    Clean,
+    /// From the store to the working copy.
+    Smudge,
+}
+
+impl FilterDirection {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Clean => "clean",
+            Self::Smudge => "smudge",
+        }
+    }
+}
+
+impl fmt::Display for FilterDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error from a required filter.
+#[derive(Debug, Error)]
+#[error("Failed to run the {direction} filter of {name} on {path}")]
+pub struct FilterError {
+    name: String,
+    direction: FilterDirection,
+    path: String,
+    #[source]
+    source: io::Error,
+}
+
+/// Runs the configured filter drivers.
+pub(crate) struct ContentFilters {
+    drivers: HashMap<String, FilterDriver>,
+    working_copy_path: PathBuf,
+    // Implements the `lfs` driver unless it's configured.
+    lfs_objects: Option<LfsObjectCache>,
+    // Processes are started on first use. A process which failed is removed,
+    // and is started again when it's next needed.
+    processes: Mutex<HashMap<String, Arc<Mutex<FilterProcess>>>>,
+}
+
+impl ContentFilters {
+    pub(crate) fn new(
+        drivers: HashMap<String, FilterDriver>,
+        working_copy_path: PathBuf,
+        lfs_objects: Option<LfsObjectCache>,
+    ) -> Self {
+        Self {
+            drivers,
+            working_copy_path,
+            lfs_objects,
+            processes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Describes the clean conversion of the filter `name`, which changes if
+    /// the configured commands change.
+    pub(crate) fn clean_fingerprint(&self, name: &str) -> String {
+        match self.drivers.get(name) {
+            Some(driver) => format!("{:?} {:?}", driver.clean, driver.process),
+            None if name == "lfs" && self.lfs_objects.is_some() => "lfs".to_owned(),
+            None => String::new(),
+        }
+    }
+
+    /// Applies the filter driver `name` to the `contents` of the file at
+    /// `path`.
+    ///
+    /// Unknown drivers leave the contents unchanged, as do failing drivers
+    /// unless they're required.
+    pub(crate) fn apply(
+        &self,
+        name: &str,
+        direction: FilterDirection,
+        path: &RepoPath,
+        contents: Vec<u8>,
+    ) -> Result<Vec<u8>, FilterError> {
+        let Some(driver) = self.drivers.get(name) else {
+            if name == "lfs"
+                && let Some(lfs_objects) = &self.lfs_objects
+            {
+                let result = match direction {
+                    FilterDirection::Clean => lfs_objects.clean(contents),
+                    FilterDirection::Smudge => lfs_objects.smudge(contents),
+                };
+                return result.map_err(|err| FilterError {
+                    name: name.to_owned(),
+                    direction,
+                    path: path.as_internal_file_string().to_owned(),
+                    source: err,
+                });
+            }
+            return Ok(contents);
+        };
+        match self.run(name, driver, direction, path, &contents) {
+            Ok(Some(output)) => Ok(output),
+            Ok(None) => Ok(contents),
+            Err(err) if driver.required => Err(FilterError {
+                name: name.to_owned(),
+                direction,
+                path: path.as_internal_file_string().to_owned(),
+                source: err,
+            }),
+            Err(err) => {
+                tracing::warn!(?err, name, %direction, ?path, "Filter failed");
+                Ok(contents)
+            }
+        }
+    }
+
+    fn run(
+        &self,
+        name: &str,
+        driver: &FilterDriver,
+        direction: FilterDirection,
+        path: &RepoPath,
+        contents: &[u8],
+    ) -> io::Result<Option<Vec<u8>>> {
+        if let Some(command) = &driver.process {
+            let process = self.process(name, command)?;
+            let mut process = process.lock().unwrap();
+            if process.capabilities.contains(direction.as_str()) {
+                let result = process.filter(direction, path, contents);
+                if result.is_err() {
+                    self.processes.lock().unwrap().remove(name);
+                }
+                return result.map(Some);
+            }
+        }
+        let command = match direction {
+            FilterDirection::Clean => &driver.clean,
+            FilterDirection::Smudge => &driver.smudge,
+        };
+        let Some(command) = command else {
+            return Ok(None);
+        };
+        run_filter_command(command, path, &self.working_copy_path, contents).map(Some)
+    }
+
+    fn process(&self, name: &str, command: &[String]) -> io::Result<Arc<Mutex<FilterProcess>>> {
+        let mut processes = self.processes.lock().unwrap();
+        if let Some(process) = processes.get(name) {
+            return Ok(process.clone());
+        }
+        let process = Arc::new(Mutex::new(FilterProcess::start(
+            command,
+            &self.working_copy_path,
+        )?));
+        processes.insert(name.to_owned(), process.clone());
+        Ok(process)
+    }
+}
+
+fn filter_command(
+    command: &[String],
+    path: &RepoPath,
+    working_copy_path: &Path,
+) -> io::Result<Command> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty filter command"))?;
+    let mut cmd = Command::new(program);
+    cmd.args(
+        args.iter()
+            .map(|arg| arg.replace("$path", path.as_internal_file_string())),
+    );
+    cmd.current_dir(working_copy_path);
+    Ok(cmd)
+}
+
+fn run_filter_command(
+    command: &[String],
+    path: &RepoPath,
+    working_copy_path: &Path,
+    contents: &[u8],
+) -> io::Result<Vec<u8>> {
+    let mut cmd = filter_command(command, path, working_copy_path)?;
+    tracing::info!(?cmd, "running filter command");
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take().unwrap();
+    // Write the input from another thread so a filter writing its output
+    // before consuming all input doesn't block.
+    let output = thread::scope(|scope| {
+        let writer = scope.spawn(move || stdin.write_all(contents));
+        let output = child.wait_with_output();
+        (writer.join().unwrap(), output)
+    });
+    let (write_result, output) = output;
+    let output = output?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "{}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        )));
+    }
+    write_result?;
+    Ok(output.stdout)
+}
+
+/// How long a filter process is given to exit after its input is closed.
+const FILTER_PROCESS_EXIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Maximum size of the data in a packet of git's pkt-line format.
+const MAX_PACKET_DATA_LEN: usize = 65516;
+
+/// Long-running filter process speaking git's filter protocol version 2.
+struct FilterProcess {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+    capabilities: HashSet<String>,
+}
+
+impl FilterProcess {
+    fn start(command: &[String], working_copy_path: &Path) -> io::Result<Self> {
+        let mut cmd = filter_command(command, RepoPath::root(), working_copy_path)?;
+        tracing::info!(?cmd, "starting filter process");
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut process = Self {
+            child,
+            stdin: Some(stdin),
+            stdout,
+            capabilities: HashSet::new(),
+        };
+        process.write_text_packets(&["git-filter-client", "version=2"])?;
+        let welcome = process.read_text_packets()?;
+        if welcome != ["git-filter-server", "version=2"] {
+            return Err(io::Error::other(format!(
+                "unexpected filter process handshake: {welcome:?}"
+            )));
+        }
+        process.write_text_packets(&["capability=clean", "capability=smudge"])?;
+        process.capabilities = process
+            .read_text_packets()?
+            .iter()
+            .filter_map(|line| line.strip_prefix("capability="))
+            .map(|capability| capability.to_owned())
+            .collect();
+        Ok(process)
+    }
+
+    fn filter(
+        &mut self,
+        direction: FilterDirection,
+        path: &RepoPath,
+        contents: &[u8],
+    ) -> io::Result<Vec<u8>> {
+        self.write_text_packets(&[
+            &format!("command={direction}"),
+            &format!("pathname={}", path.as_internal_file_string()),
+        ])?;
+        for chunk in contents.chunks(MAX_PACKET_DATA_LEN) {
+            self.write_packet(chunk)?;
+        }
+        self.write_flush()?;
+        let status = self.read_status(None)?;
+        if status != "success" {
+            return Err(io::Error::other(format!("filter process status: {status}")));
+        }
+        let mut output = vec![];
+        while let Some(packet) = self.read_packet()? {
+            output.extend_from_slice(&packet);
+        }
+        // The status may be updated after the contents.
+        let status = self.read_status(Some(status))?;
+        if status != "success" {
+            return Err(io::Error::other(format!("filter process status: {status}")));
+        }
+        Ok(output)
+    }
+
+    fn read_status(&mut self, previous: Option<String>) -> io::Result<String> {
+        let lines = self.read_text_packets()?;
+        lines
+            .iter()
+            .rev()
+            .find_map(|line| line.strip_prefix("status="))
+            .map(|status| status.to_owned())
+            .or(previous)
+            .ok_or_else(|| io::Error::other("filter process didn't report a status"))
+    }
+
+    fn stdin(&mut self) -> &mut ChildStdin {
+        self.stdin.as_mut().unwrap()
+    }
+
+    fn write_packet(&mut self, data: &[u8]) -> io::Result<()> {
+        assert!(!data.is_empty() && data.len() <= MAX_PACKET_DATA_LEN);
+        let stdin = self.stdin();
+        write!(stdin, "{:04x}", data.len() + 4)?;
+        stdin.write_all(data)
+    }
+
+    fn write_flush(&mut self) -> io::Result<()> {
+        let stdin = self.stdin();
+        stdin.write_all(b"0000")?;
+        stdin.flush()
+    }
+
+    /// Writes the `lines` as text packets followed by a flush packet.
+    fn write_text_packets(&mut self, lines: &[&str]) -> io::Result<()> {
+        for line in lines {
+            self.write_packet(format!("{line}\n").as_bytes())?;
+        }
+        self.write_flush()
+    }
+
+    /// Reads a packet, or returns `None` for a flush packet.
+    fn read_packet(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut len = [0; 4];
+        self.stdout.read_exact(&mut len)?;
+        let len = str::from_utf8(&len)
+            .ok()
+            .and_then(|len| usize::from_str_radix(len, 16).ok())
+            .ok_or_else(|| io::Error::other("invalid packet length"))?;
+        match len {
+            0 => Ok(None),
+            1..=4 => Err(io::Error::other("invalid packet length")),
+            _ => {
+                let mut data = vec![0; len - 4];
+                self.stdout.read_exact(&mut data)?;
+                Ok(Some(data))
+            }
+        }
+    }
+
+    /// Reads text packets up to the next flush packet.
+    fn read_text_packets(&mut self) -> io::Result<Vec<String>> {
+        let mut lines = vec![];
+        while let Some(packet) = self.read_packet()? {
+            let line = packet.strip_suffix(b"\n").unwrap_or(&packet);
+            lines.push(String::from_utf8_lossy(line).into_owned());
+        }
+        Ok(lines)
+    }
+}
+
+impl Drop for FilterProcess {
+    fn drop(&mut self) {
+        // Closing stdin tells the process to exit. Kill it if it doesn't.
+        drop(self.stdin.take());
+        let deadline = Instant::now() + FILTER_PROCESS_EXIT_TIMEOUT;
+        while let Ok(None) = self.child.try_wait() {
+            if Instant::now() >= deadline {
+                self.child.kill().ok();
+                self.child.wait().ok();
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+/// Collapses `$Id: ... $` to `$Id$`, like git does for files with the `ident`
+/// attribute when they're snapshotted.
+pub(crate) fn collapse_ident(contents: &[u8]) -> Cow<'_, [u8]> {
+    let mut output = vec![];
+    let mut rest = contents;
+    while let Some(start) = rest.find(b"$Id:") {
+        let keyword_end = start + b"$Id:".len();
+        let Some(end) = rest[keyword_end..]
+            .find_byteset(b"$\n")
+            .map(|pos| keyword_end + pos)
+            .filter(|&end| rest[end] == b'$')
+        else {
+            output.extend_from_slice(&rest[..keyword_end]);
+            rest = &rest[keyword_end..];
+            continue;
+        };
+        output.extend_from_slice(&rest[..start]);
+        output.extend_from_slice(b"$Id$");
+        rest = &rest[end + 1..];
+    }
+    if output.is_empty() {
+        Cow::Borrowed(contents)
+    } else {
+        output.extend_from_slice(rest);
+        Cow::Owned(output)
+    }
+}
+
+/// Expands `$Id$` to `$Id: <file id> $`, like git does for files with the
+/// `ident` attribute when they're checked out.
+pub(crate) fn expand_ident<'a>(contents: &'a [u8], id: &FileId) -> Cow<'a, [u8]> {
+    if contents.contains_str(b"$Id$") {
+        Cow::Owned(contents.replace(b"$Id$", format!("$Id: {} $", id.hex())))
+    } else {
+        Cow::Borrowed(contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_driver_rejects_empty_command() {
+        let driver: FilterDriver = toml_edit::de::from_str("clean = ['a']").unwrap();
+        assert_eq!(driver.clean, Some(vec!["a".to_owned()]));
+        assert!(toml_edit::de::from_str::<FilterDriver>("clean = []").is_err());
+        assert!(toml_edit::de::from_str::<FilterDriver>("process = []").is_err());
+    }
+
+    #[test]
+    fn test_collapse_ident() {
+        assert_eq!(collapse_ident(b"foo"), b"foo".as_slice());
+        assert_eq!(collapse_ident(b"$Id$"), b"$Id$".as_slice());
+        assert_eq!(collapse_ident(b"a $Id: 1234 $ b"), b"a $Id$ b".as_slice());
+        assert_eq!(
+            collapse_ident(b"$Id: 1 $ $Id: 2 $"),
+            b"$Id$ $Id$".as_slice()
+        );
+        // The keyword can't span lines
+        assert_eq!(
+            collapse_ident(b"$Id: 1\n $ $Id: 2 $"),
+            b"$Id: 1\n $ $Id$".as_slice()
+        );
+        assert_eq!(collapse_ident(b"$Id: 1"), b"$Id: 1".as_slice());
+    }
+
+    #[test]
+    fn test_expand_ident() {
+        let id = FileId::from_hex("0123456789abcdef");
+        assert_eq!(expand_ident(b"foo", &id), b"foo".as_slice());
+        assert_eq!(
+            expand_ident(b"a $Id$ b $Id$", &id),
+            b"a $Id: 0123456789abcdef $ b $Id: 0123456789abcdef $".as_slice()
+        );
+        assert_eq!(
+            collapse_ident(&expand_ident(b"a $Id$", &id)),
+            b"a $Id$".as_slice()
+        );
+    }
+}
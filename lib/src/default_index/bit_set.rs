@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::cmp::min;
+use std::iter;
 
 use super::composite::CompositeCommitIndex;
 use super::entry::GlobalCommitPosition;
@@ -120,6 +121,114 @@ impl PositionsBitSet {
         *word |= bit;
         old
     }
+
+    /// Returns `true` if the given `pos` is set. Unlike [`Self::get()`], this
+    /// returns `false` if the `pos` exceeds the capacity.
+    pub fn contains(&self, pos: GlobalCommitPosition) -> bool {
+        pos.0 / u64::BITS < self.bitset_len && self.get(pos)
+    }
+
+    /// Returns the number of positions set.
+    pub fn count(&self) -> u32 {
+        self.data.iter().map(|word| word.count_ones()).sum()
+    }
+
+    /// Returns the greatest position set which is less than `end`.
+    pub fn last_before(&self, end: GlobalCommitPosition) -> Option<GlobalCommitPosition> {
+        let end = min(end.0, self.bitset_len * u64::BITS);
+        let (first_bitset_pos, last_bit_pos) =
+            self.to_bitset_pos(GlobalCommitPosition(end.checked_sub(1)?));
+        let mask = u64::MAX >> (u64::BITS - last_bit_pos - 1);
+        let first_bitset_pos = usize::try_from(first_bitset_pos).unwrap();
+        let first_word = *self.data.get(first_bitset_pos)? & mask;
+        iter::once(first_word)
+            .chain(self.data[first_bitset_pos + 1..].iter().copied())
+            .enumerate()
+            .find(|(_, word)| *word != 0)
+            .map(|(offset, word)| {
+                let bitset_pos = u32::try_from(first_bitset_pos + offset).unwrap();
+                let bit_pos = u64::BITS - word.leading_zeros() - 1; // from MSB
+                self.to_global_pos((bitset_pos, bit_pos))
+            })
+    }
+
+    /// Serializes this set in run-length encoded form.
+    ///
+    /// Runs of all-zero or all-one words are stored as counters, which makes
+    /// dense ancestor sets of long history compact.
+    pub fn encode_compressed(&self, buf: &mut Vec<u8>) {
+        buf.extend(self.bitset_len.to_le_bytes());
+        buf.extend(u32::try_from(self.data.len()).unwrap().to_le_bytes());
+        let mut words = &self.data[..];
+        while !words.is_empty() {
+            let fill = if words[0] == u64::MAX { u64::MAX } else { 0 };
+            let num_fill_words = words
+                .iter()
+                .take(MAX_FILL_WORDS)
+                .take_while(|&&word| word == fill)
+                .count();
+            words = &words[num_fill_words..];
+            let num_literal_words = words
+                .iter()
+                .take(MAX_LITERAL_WORDS)
+                .take_while(|&&word| word != 0 && word != u64::MAX)
+                .count();
+            let fill_flag = if fill == 0 { 0 } else { FILL_ONES_FLAG };
+            buf.extend((fill_flag | u32::try_from(num_fill_words).unwrap()).to_le_bytes());
+            buf.extend(u32::try_from(num_literal_words).unwrap().to_le_bytes());
+            for word in &words[..num_literal_words] {
+                buf.extend(word.to_le_bytes());
+            }
+            words = &words[num_literal_words..];
+        }
+    }
+
+    /// Deserializes set encoded by [`Self::encode_compressed()`].
+    ///
+    /// Returns `None` if the data is malformed.
+    pub fn decode_compressed(data: &mut &[u8]) -> Option<Self> {
+        let bitset_len = read_u32(data)?;
+        let data_len = usize::try_from(read_u32(data)?).unwrap();
+        if data_len > usize::try_from(bitset_len).unwrap() {
+            return None;
+        }
+        let mut words = Vec::with_capacity(data_len);
+        while words.len() < data_len {
+            let fill_header = read_u32(data)?;
+            let num_literal_words = usize::try_from(read_u32(data)?).unwrap();
+            let fill = if fill_header & FILL_ONES_FLAG == 0 {
+                0
+            } else {
+                u64::MAX
+            };
+            let num_fill_words = usize::try_from(fill_header & !FILL_ONES_FLAG).unwrap();
+            let num_words = num_fill_words + num_literal_words;
+            if num_words == 0 || words.len() + num_words > data_len {
+                return None;
+            }
+            words.resize(words.len() + num_fill_words, fill);
+            for _ in 0..num_literal_words {
+                let (bytes, rest) = data.split_first_chunk()?;
+                words.push(u64::from_le_bytes(*bytes));
+                *data = rest;
+            }
+        }
+        Some(Self {
+            data: words,
+            bitset_len,
+        })
+    }
+}
+
+const FILL_ONES_FLAG: u32 = 0x8000_0000;
+const MAX_FILL_WORDS: usize = 0x7fff_ffff;
+const MAX_LITERAL_WORDS: usize = 0xffff_ffff;
+
+/// Reads little-endian `u32` from the front of the `data`.
+pub(super) fn read_u32(data: &mut &[u8]) -> Option<u32> {
+    let (bytes, rest) = data.split_first_chunk()?;
+    *data = rest;
+    Some(u32::from_le_bytes(*bytes))
 }
 
 /// Computes ancestors set lazily.
@@ -197,6 +306,20 @@ impl AncestorsBitSet {
         }
         self.next_bitset_pos_to_visit = last_bitset_pos_to_visit + 1;
     }
+
+    /// Returns the greatest position in the set which is less than `end`.
+    ///
+    /// The returned position is an ancestor of the heads, but its parents may
+    /// not have been visited yet.
+    pub fn last_before(&self, end: GlobalCommitPosition) -> Option<GlobalCommitPosition> {
+        self.bitset.last_before(end)
+    }
+
+    /// Returns the underlying set. The set is complete only if all ancestors
+    /// have been visited.
+    pub fn into_bitset(self) -> PositionsBitSet {
+        self.bitset
+    }
 }
 
 #[cfg(test)]
@@ -31,6 +31,7 @@ use futures::TryStreamExt as _;
 use itertools::Itertools as _;
 use tempfile::NamedTempFile;
 
+use super::compaction;
 use super::entry::GlobalCommitPosition;
 use super::readonly::ReadonlyIndexLoadError;
 use crate::backend::BackendResult;
@@ -520,25 +521,24 @@ impl CompositeChangedPathIndex {
     /// Squashes parent segments if the mutable segment has more than half the
     /// commits of its parent segment. This is done recursively, so the stack of
     /// index segments has O(log n) files.
-    pub(super) fn maybe_squash_with_ancestors(&mut self) {
+    ///
+    /// The squashed segment will have no more than `max_commits` commits.
+    pub(super) fn maybe_squash_with_ancestors(&mut self, max_commits: u32) {
         let Some(mutable_segment) = self.mutable_segment.as_deref() else {
             return;
         };
-        let mut num_new_commits = mutable_segment.num_local_commits();
-        let mut squash_start = self.readonly_segments.len();
-        for segment in self.readonly_segments.iter().rev() {
-            // TODO: We should probably also squash if the parent segment has
-            // less than N commits, regardless of how many (few) are in
-            // `mutable_segment`.
-            if 2 * num_new_commits < segment.num_local_commits() {
-                break;
-            }
-            num_new_commits += segment.num_local_commits();
-            squash_start -= 1;
-        }
-        if squash_start == self.readonly_segments.len() {
+        let num_segments_to_squash = compaction::num_segments_to_squash(
+            mutable_segment.num_local_commits(),
+            self.readonly_segments
+                .iter()
+                .rev()
+                .map(|segment| segment.num_local_commits()),
+            max_commits,
+        );
+        if num_segments_to_squash == 0 {
             return;
         }
+        let squash_start = self.readonly_segments.len() - num_segments_to_squash;
         let mut squashed_segment = Box::new(MutableChangedPathIndexSegment::empty());
         for segment in self.readonly_segments.drain(squash_start..) {
             squashed_segment.extend_from_readonly_segment(&segment);
@@ -547,6 +547,37 @@ impl CompositeChangedPathIndex {
         self.mutable_segment = Some(squashed_segment);
     }
 
+    /// Merges readonly segments so that the stack has O(log n) files again,
+    /// and saves the new segments in `dir`. Returns true if any segments were
+    /// merged.
+    ///
+    /// Panics if this index has a mutable segment.
+    pub(super) fn compact_in(&mut self, dir: &Path) -> Result<bool, PathError> {
+        assert!(self.mutable_segment.is_none());
+        let groups = compaction::compaction_groups(
+            self.readonly_segments
+                .iter()
+                .map(|segment| segment.num_local_commits()),
+        );
+        if groups.iter().all(|group| group.len() == 1) {
+            return Ok(false);
+        }
+        let mut new_segments = Vec::with_capacity(groups.len());
+        for group in groups {
+            if let [segment] = &self.readonly_segments[group.clone()] {
+                new_segments.push(segment.clone());
+                continue;
+            }
+            let mut squashed_segment = MutableChangedPathIndexSegment::empty();
+            for segment in &self.readonly_segments[group] {
+                squashed_segment.extend_from_readonly_segment(segment);
+            }
+            new_segments.push(squashed_segment.save_in(dir)?);
+        }
+        self.readonly_segments = new_segments;
+        Ok(true)
+    }
+
     /// Writes mutable segment if exists, turns it into readonly segment.
     pub(super) fn save_in(&mut self, dir: &Path) -> Result<(), PathError> {
         let Some(segment) = self.mutable_segment.take() else {
@@ -812,28 +843,28 @@ mod tests {
         let mut index = CompositeChangedPathIndex::empty(GlobalCommitPosition(0));
         index.make_mutable();
         index.add_changed_paths(vec![repo_path_buf("0")]);
-        index.maybe_squash_with_ancestors();
+        index.maybe_squash_with_ancestors(u32::MAX);
         index.save_in(temp_dir.path()).unwrap();
         assert_eq!(index.readonly_segments.len(), 1);
         assert_eq!(index.readonly_segments[0].num_local_commits(), 1);
 
         index.make_mutable();
         index.add_changed_paths(vec![repo_path_buf("1")]);
-        index.maybe_squash_with_ancestors();
+        index.maybe_squash_with_ancestors(u32::MAX);
         index.save_in(temp_dir.path()).unwrap();
         assert_eq!(index.readonly_segments.len(), 1);
         assert_eq!(index.readonly_segments[0].num_local_commits(), 2);
 
         index.make_mutable();
         index.add_changed_paths(vec![repo_path_buf("2")]);
-        index.maybe_squash_with_ancestors();
+        index.maybe_squash_with_ancestors(u32::MAX);
         index.save_in(temp_dir.path()).unwrap();
         assert_eq!(index.readonly_segments.len(), 1);
         assert_eq!(index.readonly_segments[0].num_local_commits(), 3);
 
         index.make_mutable();
         index.add_changed_paths(vec![repo_path_buf("3")]);
-        index.maybe_squash_with_ancestors();
+        index.maybe_squash_with_ancestors(u32::MAX);
         index.save_in(temp_dir.path()).unwrap();
         assert_eq!(index.readonly_segments.len(), 2);
         assert_eq!(index.readonly_segments[0].num_local_commits(), 3);
@@ -842,7 +873,7 @@ mod tests {
         index.make_mutable();
         index.add_changed_paths(vec![repo_path_buf("4")]);
         index.add_changed_paths(vec![repo_path_buf("5")]);
-        index.maybe_squash_with_ancestors();
+        index.maybe_squash_with_ancestors(u32::MAX);
         index.save_in(temp_dir.path()).unwrap();
         assert_eq!(index.readonly_segments.len(), 1);
         assert_eq!(index.readonly_segments[0].num_local_commits(), 6);
@@ -873,4 +904,39 @@ mod tests {
             Some(vec![repo_path("5")])
         );
     }
+
+    #[test]
+    fn test_composite_compact_segments() {
+        let temp_dir = new_temp_dir();
+        let mut index = CompositeChangedPathIndex::empty(GlobalCommitPosition(0));
+        for i in 0..3 {
+            index.make_mutable();
+            index.add_changed_paths(vec![repo_path_buf(format!("{i}a"))]);
+            index.add_changed_paths(vec![repo_path_buf(format!("{i}b"))]);
+            index.maybe_squash_with_ancestors(2);
+            index.save_in(temp_dir.path()).unwrap();
+        }
+        // Segments aren't squashed beyond the cap
+        assert_eq!(index.readonly_segments.len(), 3);
+
+        assert!(index.compact_in(temp_dir.path()).unwrap());
+        assert_eq!(index.readonly_segments.len(), 1);
+        assert_eq!(index.readonly_segments[0].num_local_commits(), 6);
+        assert_eq!(index.num_commits(), 6);
+        assert!(!index.compact_in(temp_dir.path()).unwrap());
+
+        // Compacted segments should preserve the original entries.
+        assert_eq!(
+            collect_changed_paths(&index, GlobalCommitPosition(0)),
+            Some(vec![repo_path("0a")])
+        );
+        assert_eq!(
+            collect_changed_paths(&index, GlobalCommitPosition(3)),
+            Some(vec![repo_path("1b")])
+        );
+        assert_eq!(
+            collect_changed_paths(&index, GlobalCommitPosition(5)),
+            Some(vec![repo_path("2b")])
+        );
+    }
 }
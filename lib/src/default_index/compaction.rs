@@ -0,0 +1,142 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Policy for squashing stacked index segments.
+//!
+//! Index segments form a stack where the newest segment is on top. Writes only
+//! ever append a new segment, and squash it together with the segments below
+//! it if the stack would otherwise get too deep. To keep the write path cheap
+//! in large repositories, squashing on write is capped at
+//! [`MAX_INCREMENTAL_SQUASH_COMMITS`]. Segments that grew past the cap are
+//! left as separate shards until the stack is compacted, either explicitly or
+//! in the background once [`needs_compaction()`] says so.
+
+use std::ops::Range;
+
+use itertools::Itertools as _;
+
+/// Maximum number of commits that may be rewritten when a new segment is
+/// squashed with its ancestors on write.
+pub(super) const MAX_INCREMENTAL_SQUASH_COMMITS: u32 = 200_000;
+
+/// Returns the number of parent segments that should be squashed into a new
+/// segment of `num_new_commits`.
+///
+/// `parent_sizes` are the numbers of commits in the parent segments, newest
+/// first. A parent segment is squashed if the new segment has at least half
+/// its commits, and if the squashed segment would have no more than
+/// `max_commits`.
+pub(super) fn num_segments_to_squash(
+    num_new_commits: u32,
+    parent_sizes: impl IntoIterator<Item = u32>,
+    max_commits: u32,
+) -> usize {
+    let mut num_commits = num_new_commits;
+    let mut num_segments = 0;
+    for parent_size in parent_sizes {
+        // TODO: We should probably also squash if the parent segment has less
+        // than N commits, regardless of how many (few) are in the new segment.
+        if num_commits.saturating_mul(2) < parent_size {
+            break;
+        }
+        match num_commits.checked_add(parent_size) {
+            Some(n) if n <= max_commits => num_commits = n,
+            _ => break,
+        }
+        num_segments += 1;
+    }
+    num_segments
+}
+
+/// Groups stacked segments so that each group has less than half the commits
+/// of the group below it, which keeps the stack at O(log n) segments.
+///
+/// `sizes` are the numbers of commits in the segments, oldest first. Returns
+/// ranges of segment indices, oldest first.
+pub(super) fn compaction_groups(sizes: impl IntoIterator<Item = u32>) -> Vec<Range<usize>> {
+    let mut groups: Vec<(Range<usize>, u64)> = vec![];
+    for (i, size) in sizes.into_iter().enumerate() {
+        let mut group = (i..i + 1, u64::from(size));
+        while let Some((range, num_commits)) = groups.pop_if(|(_, n)| 2 * group.1 >= *n) {
+            group = (range.start..group.0.end, num_commits + group.1);
+        }
+        groups.push(group);
+    }
+    groups.into_iter().map(|(range, _)| range).collect()
+}
+
+/// Returns true if compacting the stack of segments of `sizes` would merge
+/// segments that are too large to be squashed on write.
+///
+/// Stacks which are only deeper than necessary because of the write-time
+/// squashing policy aren't worth compacting in the background.
+pub(super) fn needs_compaction(sizes: impl IntoIterator<Item = u32>) -> bool {
+    let sizes = sizes.into_iter().collect_vec();
+    compaction_groups(sizes.iter().copied())
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .any(|group| {
+            let num_commits: u64 = sizes[group].iter().copied().map(u64::from).sum();
+            num_commits > u64::from(MAX_INCREMENTAL_SQUASH_COMMITS)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+
+    use super::*;
+
+    #[test]
+    fn test_num_segments_to_squash() {
+        assert_eq!(num_segments_to_squash(1, [], u32::MAX), 0);
+        assert_eq!(num_segments_to_squash(1, [3], u32::MAX), 0);
+        assert_eq!(num_segments_to_squash(1, [2], u32::MAX), 1);
+        assert_eq!(num_segments_to_squash(1, [2, 6, 20], u32::MAX), 2);
+        assert_eq!(num_segments_to_squash(1, [2, 6, 18], u32::MAX), 3);
+
+        // Squashing stops at the cap
+        assert_eq!(num_segments_to_squash(1, [2, 6, 18], 9), 2);
+        assert_eq!(num_segments_to_squash(1, [2, 6, 18], 8), 1);
+        assert_eq!(num_segments_to_squash(5, [2], 6), 0);
+        assert_eq!(num_segments_to_squash(u32::MAX, [1], u32::MAX), 0);
+    }
+
+    #[test]
+    fn test_compaction_groups() {
+        assert!(compaction_groups(iter::empty()).is_empty());
+        assert_eq!(compaction_groups([1]), vec![0..1]);
+        assert_eq!(compaction_groups([8, 3, 1]), vec![0..1, 1..2, 2..3]);
+        assert_eq!(compaction_groups([8, 4]), vec![0..2]);
+        // Shards left by capped squashing are merged
+        assert_eq!(compaction_groups([10, 10, 10, 2]), vec![0..3, 3..4]);
+        assert_eq!(
+            compaction_groups([10, 10, 10, 10, 10, 10]),
+            vec![0..5, 5..6]
+        );
+        assert_eq!(compaction_groups([100, 10, 10, 3]), vec![0..1, 1..3, 3..4]);
+    }
+
+    #[test]
+    fn test_needs_compaction() {
+        const MAX: u32 = MAX_INCREMENTAL_SQUASH_COMMITS;
+        assert!(!needs_compaction(iter::empty()));
+        assert!(!needs_compaction([MAX * 4, MAX, 1]));
+        // Small segments are squashed on the next write anyway
+        assert!(!needs_compaction([MAX * 4, 10, 10]));
+        // Shards left by capped squashing are merged
+        assert!(needs_compaction([MAX * 4, MAX, MAX]));
+        assert!(needs_compaction([MAX, MAX, MAX, 10]));
+    }
+}
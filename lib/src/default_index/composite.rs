@@ -35,6 +35,7 @@ use super::entry::LocalCommitPosition;
 use super::entry::SmallGlobalCommitPositionsVec;
 use super::entry::SmallLocalCommitPositionsVec;
 use super::mutable::MutableCommitIndexSegment;
+use super::reachability::ReachabilityCache;
 use super::readonly::ReadonlyCommitIndexSegment;
 use super::rev_walk::filter_slice_by_range;
 use super::revset_engine;
@@ -524,26 +525,31 @@ enum CompositeCommitIndexSegment {
 pub(super) struct CompositeIndex {
     commits: CompositeCommitIndexSegment,
     changed_paths: CompositeChangedPathIndex,
+    reachability: Arc<ReachabilityCache>,
 }
 
 impl CompositeIndex {
     pub(super) fn from_readonly(
         commits: Arc<ReadonlyCommitIndexSegment>,
         changed_paths: CompositeChangedPathIndex,
+        reachability: Arc<ReachabilityCache>,
     ) -> Self {
         Self {
             commits: CompositeCommitIndexSegment::Readonly(commits),
             changed_paths,
+            reachability,
         }
     }
 
     pub(super) fn from_mutable(
         commits: Box<MutableCommitIndexSegment>,
         changed_paths: CompositeChangedPathIndex,
+        reachability: Arc<ReachabilityCache>,
     ) -> Self {
         Self {
             commits: CompositeCommitIndexSegment::Mutable(commits),
             changed_paths,
+            reachability,
         }
     }
 
@@ -585,6 +591,10 @@ impl CompositeIndex {
     pub(super) fn changed_paths_mut(&mut self) -> &mut CompositeChangedPathIndex {
         &mut self.changed_paths
     }
+
+    pub(super) fn reachability(&self) -> &Arc<ReachabilityCache> {
+        &self.reachability
+    }
 }
 
 impl AsCompositeIndex for CompositeIndex {
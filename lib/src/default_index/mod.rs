@@ -21,9 +21,11 @@
 
 mod bit_set;
 mod changed_path;
+mod compaction;
 mod composite;
 mod entry;
 mod mutable;
+mod reachability;
 mod readonly;
 mod rev_walk;
 mod rev_walk_queue;
@@ -65,6 +67,7 @@ mod tests {
     use super::entry::GlobalCommitPosition;
     use super::entry::SmallGlobalCommitPositionsVec;
     use super::mutable::MutableCommitIndexSegment;
+    use super::reachability::ReachabilityCache;
     use super::readonly::ReadonlyCommitIndexSegment;
     use super::*;
     use crate::backend::ChangeId;
@@ -98,7 +101,9 @@ mod tests {
 
     fn get_commit_index_stats(commits: &Arc<ReadonlyCommitIndexSegment>) -> IndexStats {
         let changed_paths = CompositeChangedPathIndex::null();
-        let index = DefaultReadonlyIndex::from_segment(commits.clone(), changed_paths);
+        let reachability = Arc::new(ReachabilityCache::in_memory());
+        let index =
+            DefaultReadonlyIndex::from_segment(commits.clone(), changed_paths, reachability);
         index.stats()
     }
 
@@ -383,6 +388,73 @@ mod tests {
         assert_eq!(entry_6.generation_number(), 2);
     }
 
+    #[test]
+    fn index_squash_capped_and_compacted() {
+        let temp_dir = new_temp_dir();
+        let mut new_commit_id = commit_id_generator();
+        let mut new_change_id = change_id_generator();
+        let mut commit_ids = vec![];
+        let mut add_commits = |segment: &mut MutableCommitIndexSegment, count: usize| {
+            for _ in 0..count {
+                let commit_id = new_commit_id();
+                let parent_ids = commit_ids.last().cloned().into_iter().collect_vec();
+                segment.add_commit_data(commit_id.clone(), new_change_id(), &parent_ids);
+                commit_ids.push(commit_id);
+            }
+        };
+        let level_sizes = |file: &Arc<ReadonlyCommitIndexSegment>| {
+            get_commit_index_stats(file)
+                .commit_levels
+                .iter()
+                .map(|level| level.num_commits)
+                .collect_vec()
+        };
+
+        let mut mutable_segment = MutableCommitIndexSegment::full(FieldLengths {
+            commit_id: 16,
+            change_id: 16,
+        });
+        add_commits(&mut mutable_segment, 8);
+        let mut file = mutable_segment.save_in(temp_dir.path()).unwrap();
+        for _ in 0..6 {
+            let mut mutable_segment = MutableCommitIndexSegment::incremental(file);
+            add_commits(&mut mutable_segment, 2);
+            file = mutable_segment
+                .maybe_squash_with_ancestors(4)
+                .save_in(temp_dir.path())
+                .unwrap();
+        }
+        // Segments larger than the cap are never squashed
+        assert_eq!(level_sizes(&file), vec![8, 4, 4, 4]);
+
+        let compacted = MutableCommitIndexSegment::compact(&file, temp_dir.path()).unwrap();
+        assert_eq!(level_sizes(&compacted), vec![20]);
+
+        // Compacting again is a no-op
+        let recompacted = MutableCommitIndexSegment::compact(&compacted, temp_dir.path()).unwrap();
+        assert_eq!(recompacted.id(), compacted.id());
+
+        // Only the segments above the merged ones are rewritten
+        let mut mutable_segment = MutableCommitIndexSegment::incremental(compacted.clone());
+        add_commits(&mut mutable_segment, 4);
+        let file = mutable_segment.save_in(temp_dir.path()).unwrap();
+        let mut mutable_segment = MutableCommitIndexSegment::incremental(file);
+        add_commits(&mut mutable_segment, 4);
+        let file = mutable_segment.save_in(temp_dir.path()).unwrap();
+        assert_eq!(level_sizes(&file), vec![20, 4, 4]);
+        let compacted2 = MutableCommitIndexSegment::compact(&file, temp_dir.path()).unwrap();
+        assert_eq!(level_sizes(&compacted2), vec![20, 8]);
+        assert_eq!(compacted2.parent_file().unwrap().id(), compacted.id());
+
+        let index = compacted2.as_composite();
+        assert_eq!(index.num_commits(), 28);
+        for (pos, commit_id) in (0..).zip(&commit_ids) {
+            let entry = index.entry_by_id(commit_id).unwrap();
+            assert_eq!(entry.position(), GlobalCommitPosition(pos));
+            assert_eq!(entry.generation_number(), pos);
+        }
+    }
+
     #[test]
     fn resolve_commit_id_prefix() {
         let temp_dir = new_temp_dir();
@@ -33,6 +33,7 @@ use tempfile::NamedTempFile;
 
 use super::changed_path::CompositeChangedPathIndex;
 use super::changed_path::collect_changed_paths;
+use super::compaction;
 use super::composite::AsCompositeIndex;
 use super::composite::ChangeIdIndexImpl;
 use super::composite::CommitIndexSegment;
@@ -44,6 +45,7 @@ use super::entry::GlobalCommitPosition;
 use super::entry::LocalCommitPosition;
 use super::entry::SmallGlobalCommitPositionsVec;
 use super::entry::SmallLocalCommitPositionsVec;
+use super::reachability::ReachabilityCache;
 use super::readonly::COMMIT_INDEX_SEGMENT_FILE_FORMAT_VERSION;
 use super::readonly::DefaultReadonlyIndex;
 use super::readonly::FieldLengths;
@@ -315,27 +317,29 @@ impl MutableCommitIndexSegment {
     /// If the mutable segment has more than half the commits of its parent
     /// segment, return mutable segment with the commits from both. This is done
     /// recursively, so the stack of index segments has O(log n) files.
-    pub(super) fn maybe_squash_with_ancestors(self) -> Self {
-        let mut num_new_commits = self.num_local_commits();
-        let mut files_to_squash = vec![];
-        let mut base_parent_file = None;
-        for parent_file in self.as_composite().ancestor_files_without_local() {
-            // TODO: We should probably also squash if the parent file has less than N
-            // commits, regardless of how many (few) are in `self`.
-            if 2 * num_new_commits < parent_file.num_local_commits() {
-                base_parent_file = Some(parent_file.clone());
-                break;
-            }
-            num_new_commits += parent_file.num_local_commits();
-            files_to_squash.push(parent_file.clone());
-        }
-
-        if files_to_squash.is_empty() {
+    ///
+    /// The squashed segment will have no more than `max_commits` commits.
+    /// Larger segments are left as is, and have to be merged by
+    /// [`Self::compact()`].
+    pub(super) fn maybe_squash_with_ancestors(self, max_commits: u32) -> Self {
+        let parent_files = self.as_composite().ancestor_files_without_local();
+        let num_files_to_squash = compaction::num_segments_to_squash(
+            self.num_local_commits(),
+            parent_files.map(|parent_file| parent_file.num_local_commits()),
+            max_commits,
+        );
+        if num_files_to_squash == 0 {
             return self;
         }
+        let files_to_squash = self
+            .as_composite()
+            .ancestor_files_without_local()
+            .take(num_files_to_squash)
+            .collect_vec();
+        let base_parent_file = files_to_squash.last().unwrap().parent_file();
 
         let mut squashed = if let Some(parent_file) = base_parent_file {
-            Self::incremental(parent_file)
+            Self::incremental(parent_file.clone())
         } else {
             Self::full(self.field_lengths)
         };
@@ -346,6 +350,43 @@ impl MutableCommitIndexSegment {
         squashed
     }
 
+    /// Rewrites the stack of segments ending at `file` so that the stack has
+    /// O(log n) files again, and saves the new segments in `dir`.
+    ///
+    /// Segments at the bottom of the stack that don't need to be merged are
+    /// reused as is. Returns the original `file` if nothing had to be merged.
+    pub(super) fn compact(
+        file: &Arc<ReadonlyCommitIndexSegment>,
+        dir: &Path,
+    ) -> Result<Arc<ReadonlyCommitIndexSegment>, PathError> {
+        let mut files = iter::once(file)
+            .chain(file.as_composite().ancestor_files_without_local())
+            .collect_vec();
+        files.reverse();
+        let groups =
+            compaction::compaction_groups(files.iter().map(|file| file.num_local_commits()));
+        let Some(first_merged) = groups.iter().position(|group| group.len() > 1) else {
+            return Ok(file.clone());
+        };
+
+        let mut parent_file = groups[first_merged]
+            .start
+            .checked_sub(1)
+            .map(|pos| files[pos].clone());
+        for group in &groups[first_merged..] {
+            let mut segment = if let Some(parent_file) = parent_file {
+                Self::incremental(parent_file)
+            } else {
+                Self::full(file.field_lengths())
+            };
+            for file in &files[group.clone()] {
+                segment.add_commits_from(file.as_ref());
+            }
+            parent_file = Some(segment.save_in(dir)?);
+        }
+        Ok(parent_file.unwrap())
+    }
+
     pub(super) fn save_in(
         mut self,
         dir: &Path,
@@ -463,7 +504,12 @@ impl DefaultMutableIndex {
         // Changed-path index isn't enabled by default.
         let mut changed_paths = CompositeChangedPathIndex::null();
         changed_paths.make_mutable();
-        Self(CompositeIndex::from_mutable(commits, changed_paths))
+        let reachability = Arc::new(ReachabilityCache::in_memory());
+        Self(CompositeIndex::from_mutable(
+            commits,
+            changed_paths,
+            reachability,
+        ))
     }
 
     pub(super) fn incremental(parent_index: &DefaultReadonlyIndex) -> Self {
@@ -472,7 +518,17 @@ impl DefaultMutableIndex {
         ));
         let mut changed_paths = parent_index.changed_paths().clone();
         changed_paths.make_mutable();
-        Self(CompositeIndex::from_mutable(commits, changed_paths))
+        // Positions of the existing commits don't change, so the cached
+        // ancestor sets of these commits are still valid.
+        let reachability = Arc::new(ReachabilityCache::incremental(
+            parent_index.reachability().clone(),
+            parent_index.num_commits(),
+        ));
+        Self(CompositeIndex::from_mutable(
+            commits,
+            changed_paths,
+            reachability,
+        ))
     }
 
     pub(super) fn into_segment(
@@ -0,0 +1,568 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cache of ancestor bit sets.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Debug;
+use std::fs;
+use std::io::Write as _;
+use std::mem;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use blake2::Blake2b512;
+use blake2::Digest as _;
+use itertools::Itertools as _;
+use tempfile::NamedTempFile;
+
+use super::bit_set::AncestorsBitSet;
+use super::bit_set::PositionsBitSet;
+use super::bit_set::read_u32;
+use super::composite::CommitIndexSegment as _;
+use super::composite::CompositeIndex;
+use super::entry::GlobalCommitPosition;
+use super::readonly::ReadonlyCommitIndexSegment;
+use super::rev_walk::RevWalk;
+use crate::file_util::persist_temp_file;
+use crate::hex_util;
+use crate::object_id::ObjectId as _;
+
+const REACHABILITY_FILE_FORMAT_VERSION: u32 = 1;
+
+/// Maximum number of ancestor sets to keep in memory.
+const MAX_IN_MEMORY_ENTRIES: usize = 16;
+
+/// Maximum number of ancestor set files to keep on disk.
+const MAX_PERSISTED_ENTRIES: usize = 32;
+
+/// Ancestor sets smaller than this are cheap to recompute, and are not saved
+/// to disk.
+const MIN_PERSISTED_ANCESTORS: u32 = 10_000;
+
+/// Cache of ancestor sets of commits keyed by heads.
+///
+/// Since commit positions are stable while the index grows incrementally, the
+/// ancestor sets of commits in a readonly index are also valid in the mutable
+/// index derived from it. A mutable index has its own cache, which looks up
+/// sets of commits in the parent index in the parent's cache. The on-disk
+/// files refer to the readonly segment containing the heads, and are discarded
+/// if the current index doesn't contain that segment (e.g. because the segment
+/// was squashed or the index was rebuilt.)
+pub(super) struct ReachabilityCache {
+    dir: Option<PathBuf>,
+    min_persisted_ancestors: u32,
+    /// Cache of the parent index, and the number of commits in it.
+    parent: Option<(Arc<Self>, u32)>,
+    entries: Mutex<HashMap<Vec<GlobalCommitPosition>, Arc<PositionsBitSet>>>,
+}
+
+impl ReachabilityCache {
+    /// Creates cache which doesn't persist ancestor sets.
+    pub fn in_memory() -> Self {
+        Self {
+            dir: None,
+            min_persisted_ancestors: MIN_PERSISTED_ANCESTORS,
+            parent: None,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Creates cache which saves large ancestor sets in the given `dir`.
+    pub fn persistent(dir: PathBuf) -> Self {
+        Self {
+            dir: Some(dir),
+            ..Self::in_memory()
+        }
+    }
+
+    /// Creates cache for index which adds commits to the parent index of
+    /// `num_parent_commits`.
+    pub fn incremental(parent: Arc<Self>, num_parent_commits: u32) -> Self {
+        Self {
+            parent: Some((parent, num_parent_commits)),
+            ..Self::in_memory()
+        }
+    }
+
+    #[cfg(test)]
+    fn with_min_persisted_ancestors(self, min_persisted_ancestors: u32) -> Self {
+        Self {
+            min_persisted_ancestors,
+            ..self
+        }
+    }
+
+    /// Returns the parent cache if the ancestors of the `heads` can be looked
+    /// up there.
+    fn parent_for(&self, heads: &[GlobalCommitPosition]) -> Option<&Self> {
+        let (parent, num_parent_commits) = self.parent.as_ref()?;
+        let max_pos = heads.last().map_or(0, |pos| pos.0);
+        (max_pos < *num_parent_commits).then_some(parent)
+    }
+
+    /// Returns set of the `heads` and their ancestors if cached.
+    pub fn get(
+        &self,
+        index: &CompositeIndex,
+        heads: &[GlobalCommitPosition],
+    ) -> Option<Arc<PositionsBitSet>> {
+        self.get_by_key(index, &to_key(heads))
+    }
+
+    fn get_by_key(
+        &self,
+        index: &CompositeIndex,
+        key: &[GlobalCommitPosition],
+    ) -> Option<Arc<PositionsBitSet>> {
+        if let Some(parent) = self.parent_for(key) {
+            return parent.get_by_key(index, key);
+        }
+        if let Some(set) = self.entries.lock().unwrap().get(key) {
+            return Some(set.clone());
+        }
+        let dir = self.dir.as_ref()?;
+        let path = dir.join(entry_file_name(index, key));
+        let set = Arc::new(load_entry(index, &path, key)?);
+        self.insert_in_memory(key.to_vec(), set.clone());
+        Some(set)
+    }
+
+    /// Saves `set` of the `heads` and their ancestors.
+    pub fn insert(
+        &self,
+        index: &CompositeIndex,
+        heads: &[GlobalCommitPosition],
+        set: PositionsBitSet,
+    ) -> Arc<PositionsBitSet> {
+        self.insert_by_key(index, to_key(heads), set)
+    }
+
+    fn insert_by_key(
+        &self,
+        index: &CompositeIndex,
+        key: Vec<GlobalCommitPosition>,
+        set: PositionsBitSet,
+    ) -> Arc<PositionsBitSet> {
+        if let Some(parent) = self.parent_for(&key) {
+            return parent.insert_by_key(index, key, set);
+        }
+        let set = Arc::new(set);
+        if let Some(dir) = &self.dir
+            && set.count() >= self.min_persisted_ancestors
+        {
+            // Failure to write cache isn't fatal.
+            let file_name = entry_file_name(index, &key);
+            let _ = save_entry(index, dir, &file_name, &key, &set);
+        }
+        self.insert_in_memory(key, set.clone());
+        set
+    }
+
+    fn insert_in_memory(&self, key: Vec<GlobalCommitPosition>, set: Arc<PositionsBitSet>) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_IN_MEMORY_ENTRIES {
+            entries.clear();
+        }
+        entries.insert(key, set);
+    }
+}
+
+impl Debug for ReachabilityCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReachabilityCache")
+            .field("dir", &self.dir)
+            .field("parent", &self.parent)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Walks the heads and their ancestors in descending order of positions.
+///
+/// The ancestors are visited lazily, so queries which only need the first few
+/// ancestors (e.g. `latest(::@)`) don't compute the whole set. The set is saved
+/// to the cache when the walk completes.
+#[derive(Clone, Debug)]
+pub(super) struct CachingAncestorsWalk {
+    heads: Vec<GlobalCommitPosition>,
+    set: AncestorsBitSet,
+    end: GlobalCommitPosition,
+    done: bool,
+}
+
+impl CachingAncestorsWalk {
+    pub fn new(heads: &[GlobalCommitPosition]) -> Self {
+        let heads = to_key(heads);
+        let len = heads.last().map_or(0, |pos| pos.0 + 1);
+        let mut set = AncestorsBitSet::with_capacity(len);
+        for &pos in &heads {
+            set.add_head(pos);
+        }
+        Self {
+            heads,
+            set,
+            end: GlobalCommitPosition(len),
+            done: false,
+        }
+    }
+}
+
+impl RevWalk<CompositeIndex> for CachingAncestorsWalk {
+    type Item = GlobalCommitPosition;
+
+    fn next(&mut self, index: &CompositeIndex) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        // Positions greater than `end` have been visited, so no more bits can
+        // be set between `pos` and `end`.
+        if let Some(pos) = self.set.last_before(self.end) {
+            self.set.visit_until(index.commits(), pos);
+            self.end = pos;
+            return Some(pos);
+        }
+        self.done = true;
+        let set = mem::replace(&mut self.set, AncestorsBitSet::with_capacity(0));
+        index
+            .reachability()
+            .insert(index, &self.heads, set.into_bitset());
+        None
+    }
+}
+
+fn to_key(heads: &[GlobalCommitPosition]) -> Vec<GlobalCommitPosition> {
+    heads.iter().copied().sorted_unstable().dedup().collect()
+}
+
+fn entry_file_name(index: &CompositeIndex, heads: &[GlobalCommitPosition]) -> String {
+    let mut hasher = Blake2b512::new();
+    for &pos in heads {
+        hasher.update(index.commits().entry_by_pos(pos).commit_id().as_bytes());
+    }
+    hex_util::encode_hex(&hasher.finalize())
+}
+
+/// Finds the smallest readonly segment which contains the given `pos`.
+fn find_readonly_segment(
+    index: &CompositeIndex,
+    pos: GlobalCommitPosition,
+) -> Option<&Arc<ReadonlyCommitIndexSegment>> {
+    index
+        .readonly_commits()
+        .into_iter()
+        .chain(index.commits().ancestor_files_without_local())
+        .find(|segment| pos.0 >= segment.num_parent_commits())
+        .filter(|segment| pos.0 < segment.num_parent_commits() + segment.num_local_commits())
+}
+
+fn save_entry(
+    index: &CompositeIndex,
+    dir: &Path,
+    file_name: &str,
+    heads: &[GlobalCommitPosition],
+    set: &PositionsBitSet,
+) -> Option<()> {
+    let segment = find_readonly_segment(index, *heads.last()?)?;
+    let segment_id = segment.id().as_bytes();
+    let mut buf = Vec::new();
+    buf.extend(REACHABILITY_FILE_FORMAT_VERSION.to_le_bytes());
+    buf.extend(u32::try_from(segment_id.len()).unwrap().to_le_bytes());
+    buf.extend(segment_id);
+    buf.extend(u32::try_from(heads.len()).unwrap().to_le_bytes());
+    for pos in heads {
+        buf.extend(pos.0.to_le_bytes());
+    }
+    set.encode_compressed(&mut buf);
+
+    let mut temp_file = NamedTempFile::new_in(dir).ok()?;
+    temp_file.as_file_mut().write_all(&buf).ok()?;
+    persist_temp_file(temp_file, dir.join(file_name)).ok()?;
+    evict_old_entries(dir);
+    Some(())
+}
+
+fn load_entry(
+    index: &CompositeIndex,
+    path: &Path,
+    heads: &[GlobalCommitPosition],
+) -> Option<PositionsBitSet> {
+    let buf = fs::read(path).ok()?;
+    let mut data = &buf[..];
+    if read_u32(&mut data)? != REACHABILITY_FILE_FORMAT_VERSION {
+        return None;
+    }
+    let segment_id_len = usize::try_from(read_u32(&mut data)?).unwrap();
+    let (segment_id, rest) = data.split_at_checked(segment_id_len)?;
+    data = rest;
+    let num_heads = usize::try_from(read_u32(&mut data)?).unwrap();
+    let stored_heads: Vec<_> = (0..num_heads)
+        .map(|_| read_u32(&mut data).map(GlobalCommitPosition))
+        .collect::<Option<_>>()?;
+    if stored_heads != heads {
+        return None;
+    }
+    // Positions are stable up to the end of the segment which contains the
+    // heads. If the segment isn't found, the index has been rewritten.
+    let segment = find_readonly_segment(index, *heads.last()?)?;
+    if segment.id().as_bytes() != segment_id {
+        return None;
+    }
+    PositionsBitSet::decode_compressed(&mut data)
+}
+
+fn evict_old_entries(dir: &Path) {
+    let Ok(entries) = dir.read_dir() else {
+        return;
+    };
+    let mut files = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, entry.path()))
+        })
+        .collect_vec();
+    if files.len() <= MAX_PERSISTED_ENTRIES {
+        return;
+    }
+    files.sort_unstable();
+    for (_, path) in &files[..files.len() - MAX_PERSISTED_ENTRIES] {
+        fs::remove_file(path).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::changed_path::CompositeChangedPathIndex;
+    use super::super::mutable::MutableCommitIndexSegment;
+    use super::super::readonly::FieldLengths;
+    use super::*;
+    use crate::backend::ChangeId;
+    use crate::backend::CommitId;
+    use crate::tests::new_temp_dir;
+
+    const TEST_FIELD_LENGTHS: FieldLengths = FieldLengths {
+        commit_id: 16,
+        change_id: 16,
+    };
+
+    /// Creates linear history of `len` commits, and merge of the last one and
+    /// the root commit.
+    fn build_segment(dir: &Path, len: u128) -> Arc<ReadonlyCommitIndexSegment> {
+        let mut segment = MutableCommitIndexSegment::full(TEST_FIELD_LENGTHS);
+        let new_id = |n: u128| CommitId::new(n.to_le_bytes().into());
+        let new_change_id = |n: u128| ChangeId::new(n.to_le_bytes().into());
+        segment.add_commit_data(new_id(0), new_change_id(0), &[]);
+        for n in 1..len {
+            segment.add_commit_data(new_id(n), new_change_id(n), &[new_id(n - 1)]);
+        }
+        segment.add_commit_data(new_id(len), new_change_id(len), &[]);
+        segment.save_in(dir).unwrap()
+    }
+
+    /// Walks all ancestors of the `heads`.
+    fn walk_ancestors(
+        index: &CompositeIndex,
+        heads: &[GlobalCommitPosition],
+    ) -> Vec<GlobalCommitPosition> {
+        CachingAncestorsWalk::new(heads).attach(index).collect_vec()
+    }
+
+    #[test]
+    fn test_ancestors_walk() {
+        let temp_dir = new_temp_dir();
+        let segment = build_segment(temp_dir.path(), 100);
+        let index = CompositeIndex::from_readonly(
+            segment,
+            CompositeChangedPathIndex::null(),
+            Arc::new(ReachabilityCache::in_memory()),
+        );
+        let cache = index.reachability();
+
+        assert_eq!(walk_ancestors(&index, &[]), vec![]);
+        assert_eq!(cache.get(&index, &[]).unwrap().count(), 0);
+
+        // Partial walk isn't cached
+        let heads = [GlobalCommitPosition(49)];
+        let mut walk = CachingAncestorsWalk::new(&heads);
+        assert_eq!(walk.next(&index), Some(GlobalCommitPosition(49)));
+        assert_eq!(walk.next(&index), Some(GlobalCommitPosition(48)));
+        assert!(cache.get(&index, &heads).is_none());
+
+        let positions = walk_ancestors(&index, &heads);
+        assert_eq!(positions.len(), 50);
+        assert_eq!(positions.first(), Some(&GlobalCommitPosition(49)));
+        assert_eq!(positions.last(), Some(&GlobalCommitPosition(0)));
+        let set = cache.get(&index, &heads).unwrap();
+        assert_eq!(set.count(), 50);
+        assert!(set.contains(GlobalCommitPosition(0)));
+        assert!(set.contains(GlobalCommitPosition(49)));
+        assert!(!set.contains(GlobalCommitPosition(50)));
+        assert!(!set.contains(GlobalCommitPosition(100)));
+        assert!(Arc::ptr_eq(&set, &cache.get(&index, &heads).unwrap()));
+
+        let heads = [GlobalCommitPosition(100), GlobalCommitPosition(10)];
+        let positions = walk_ancestors(&index, &heads);
+        assert_eq!(positions.len(), 12);
+        assert_eq!(
+            positions[..2],
+            [GlobalCommitPosition(100), GlobalCommitPosition(10)]
+        );
+        let set = cache.get(&index, &heads).unwrap();
+        assert_eq!(set.count(), 12);
+        assert_eq!(
+            set.last_before(GlobalCommitPosition(101)),
+            Some(GlobalCommitPosition(100))
+        );
+        assert_eq!(
+            set.last_before(GlobalCommitPosition(100)),
+            Some(GlobalCommitPosition(10))
+        );
+        assert_eq!(set.last_before(GlobalCommitPosition(0)), None);
+    }
+
+    #[test]
+    fn test_ancestors_persistent() {
+        let temp_dir = new_temp_dir();
+        let segments_dir = temp_dir.path().join("segments");
+        let cache_dir = temp_dir.path().join("reachability");
+        fs::create_dir(&segments_dir).unwrap();
+        fs::create_dir(&cache_dir).unwrap();
+        let segment = build_segment(&segments_dir, 1000);
+        let heads = [GlobalCommitPosition(999)];
+
+        // Small sets aren't persisted
+        let index = CompositeIndex::from_readonly(
+            segment.clone(),
+            CompositeChangedPathIndex::null(),
+            Arc::new(ReachabilityCache::persistent(cache_dir.clone())),
+        );
+        walk_ancestors(&index, &heads);
+        assert_eq!(cache_dir.read_dir().unwrap().count(), 0);
+
+        let index = CompositeIndex::from_readonly(
+            segment.clone(),
+            CompositeChangedPathIndex::null(),
+            Arc::new(
+                ReachabilityCache::persistent(cache_dir.clone()).with_min_persisted_ancestors(100),
+            ),
+        );
+        walk_ancestors(&index, &heads);
+        assert_eq!(cache_dir.read_dir().unwrap().count(), 1);
+        let file_name = entry_file_name(&index, &heads);
+        let loaded = load_entry(&index, &cache_dir.join(&file_name), &heads).unwrap();
+        assert_eq!(loaded.count(), 1000);
+        assert!(loaded.contains(GlobalCommitPosition(0)));
+        assert!(loaded.contains(GlobalCommitPosition(999)));
+        assert!(!loaded.contains(GlobalCommitPosition(1000)));
+
+        // Loaded by another cache
+        let cache = ReachabilityCache::persistent(cache_dir.clone());
+        assert_eq!(cache.get(&index, &heads).unwrap().count(), 1000);
+
+        // Different heads
+        assert!(
+            load_entry(
+                &index,
+                &cache_dir.join(&file_name),
+                &[GlobalCommitPosition(1)]
+            )
+            .is_none()
+        );
+
+        // The segment no longer exists in the rebuilt index
+        let other_segment = build_segment(&segments_dir, 1001);
+        let other_index = CompositeIndex::from_readonly(
+            other_segment,
+            CompositeChangedPathIndex::null(),
+            Arc::new(ReachabilityCache::in_memory()),
+        );
+        assert!(load_entry(&other_index, &cache_dir.join(&file_name), &heads).is_none());
+    }
+
+    #[test]
+    fn test_ancestors_persistent_in_mutable_segment() {
+        let temp_dir = new_temp_dir();
+        let cache_dir = temp_dir.path().join("reachability");
+        fs::create_dir(&cache_dir).unwrap();
+        let segment = build_segment(temp_dir.path(), 10);
+        let mut mutable_segment = MutableCommitIndexSegment::incremental(segment);
+        let new_id = |n: u128| CommitId::new(n.to_le_bytes().into());
+        mutable_segment.add_commit_data(new_id(11), ChangeId::new(vec![1; 16]), &[new_id(9)]);
+        let cache =
+            ReachabilityCache::persistent(cache_dir.clone()).with_min_persisted_ancestors(0);
+        let index = CompositeIndex::from_mutable(
+            Box::new(mutable_segment),
+            CompositeChangedPathIndex::null(),
+            Arc::new(cache),
+        );
+
+        // Heads in the mutable segment can't be persisted
+        let heads = [GlobalCommitPosition(11)];
+        assert_eq!(walk_ancestors(&index, &heads).len(), 11);
+        assert_eq!(
+            index.reachability().get(&index, &heads).unwrap().count(),
+            11
+        );
+        assert_eq!(cache_dir.read_dir().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_ancestors_in_sibling_mutable_indexes() {
+        let temp_dir = new_temp_dir();
+        let segment = build_segment(temp_dir.path(), 10);
+        let parent_cache = Arc::new(ReachabilityCache::in_memory());
+        let new_id = |n: u128| CommitId::new(n.to_le_bytes().into());
+        // The new commits get the same position in both indexes
+        let new_index = |id: u128, parent_id: u128| {
+            let mut mutable_segment = MutableCommitIndexSegment::incremental(segment.clone());
+            mutable_segment.add_commit_data(
+                new_id(id),
+                ChangeId::new(vec![1; 16]),
+                &[new_id(parent_id)],
+            );
+            CompositeIndex::from_mutable(
+                Box::new(mutable_segment),
+                CompositeChangedPathIndex::null(),
+                Arc::new(ReachabilityCache::incremental(parent_cache.clone(), 11)),
+            )
+        };
+        let index1 = new_index(11, 9);
+        let index2 = new_index(12, 10);
+
+        let heads = [GlobalCommitPosition(11)];
+        assert_eq!(walk_ancestors(&index1, &heads).len(), 11);
+        assert_eq!(walk_ancestors(&index2, &heads).len(), 2);
+        assert_eq!(
+            index1.reachability().get(&index1, &heads).unwrap().count(),
+            11
+        );
+        assert_eq!(
+            index2.reachability().get(&index2, &heads).unwrap().count(),
+            2
+        );
+        assert!(parent_cache.get(&index1, &heads).is_none());
+
+        // Ancestors of the existing commits are shared with the parent
+        let heads = [GlobalCommitPosition(9)];
+        assert_eq!(walk_ancestors(&index1, &heads).len(), 10);
+        assert_eq!(parent_cache.get(&index1, &heads).unwrap().count(), 10);
+        assert_eq!(
+            index2.reachability().get(&index2, &heads).unwrap().count(),
+            10
+        );
+    }
+}
@@ -31,6 +31,7 @@ use smallvec::smallvec;
 use thiserror::Error;
 
 use super::changed_path::CompositeChangedPathIndex;
+use super::compaction;
 use super::composite::AsCompositeIndex;
 use super::composite::ChangeIdIndexImpl;
 use super::composite::CommitIndexSegment;
@@ -42,6 +43,7 @@ use super::entry::LocalCommitPosition;
 use super::entry::SmallGlobalCommitPositionsVec;
 use super::entry::SmallLocalCommitPositionsVec;
 use super::mutable::DefaultMutableIndex;
+use super::reachability::ReachabilityCache;
 use super::revset_engine;
 use super::revset_engine::RevsetImpl;
 use crate::backend::ChangeId;
@@ -616,8 +618,13 @@ impl DefaultReadonlyIndex {
     pub(super) fn from_segment(
         commits: Arc<ReadonlyCommitIndexSegment>,
         changed_paths: CompositeChangedPathIndex,
+        reachability: Arc<ReachabilityCache>,
     ) -> Self {
-        Self(CompositeIndex::from_readonly(commits, changed_paths))
+        Self(CompositeIndex::from_readonly(
+            commits,
+            changed_paths,
+            reachability,
+        ))
     }
 
     pub(super) fn readonly_commits(&self) -> &Arc<ReadonlyCommitIndexSegment> {
@@ -628,6 +635,10 @@ impl DefaultReadonlyIndex {
         self.0.changed_paths()
     }
 
+    pub(super) fn reachability(&self) -> &Arc<ReachabilityCache> {
+        self.0.reachability()
+    }
+
     pub(super) fn has_id_impl(&self, commit_id: &CommitId) -> bool {
         self.0.commits().has_id(commit_id)
     }
@@ -637,6 +648,26 @@ impl DefaultReadonlyIndex {
         self.0.commits().num_commits()
     }
 
+    /// Returns true if index updates left large segments that should be merged
+    /// by [`DefaultIndexStore::compact_index_at_operation()`].
+    ///
+    /// [`DefaultIndexStore::compact_index_at_operation()`]:
+    /// super::DefaultIndexStore::compact_index_at_operation
+    pub fn needs_compaction(&self) -> bool {
+        let commits = self.readonly_commits();
+        let mut commit_sizes = iter::successors(Some(commits), |segment| segment.parent_file())
+            .map(|segment| segment.num_local_commits())
+            .collect_vec();
+        commit_sizes.reverse();
+        let changed_path_sizes = self
+            .changed_paths()
+            .readonly_segments()
+            .iter()
+            .map(|segment| segment.num_local_commits());
+        compaction::needs_compaction(commit_sizes)
+            || compaction::needs_compaction(changed_path_sizes)
+    }
+
     /// Collects statistics of indexed commits and segments.
     pub fn stats(&self) -> IndexStats {
         let commits = self.readonly_commits();
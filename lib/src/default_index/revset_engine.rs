@@ -29,9 +29,11 @@ use futures::StreamExt as _;
 use itertools::Itertools as _;
 use pollster::FutureExt as _;
 
+use super::bit_set::PositionsBitSet;
 use super::composite::AsCompositeIndex;
 use super::composite::CompositeIndex;
 use super::entry::GlobalCommitPosition;
+use super::reachability::CachingAncestorsWalk;
 use super::rev_walk::EagerRevWalk;
 use super::rev_walk::PeekableRevWalk;
 use super::rev_walk::RevWalk;
@@ -59,6 +61,7 @@ use crate::object_id::ObjectId as _;
 use crate::object_id::PrefixResolution;
 use crate::repo_path::RepoPath;
 use crate::revset::GENERATION_RANGE_FULL;
+use crate::revset::PARENTS_RANGE_FULL;
 use crate::revset::ResolvedExpression;
 use crate::revset::ResolvedPredicateExpression;
 use crate::revset::Revset;
@@ -316,6 +319,34 @@ impl ToPredicateFn for EagerRevset {
     }
 }
 
+/// Adapter for precomputed set of `GlobalCommitPosition`s.
+#[derive(Debug)]
+struct BitSetRevset {
+    set: Arc<PositionsBitSet>,
+}
+
+impl InternalRevset for BitSetRevset {
+    fn positions<'a>(&self) -> BoxedRevWalk<'a>
+    where
+        Self: 'a,
+    {
+        let set = self.set.clone();
+        let first_pos = set.last_before(GlobalCommitPosition::MAX);
+        let iter = iter::successors(first_pos, move |&pos| set.last_before(pos));
+        Box::new(EagerRevWalk::new(iter).map(|_index, pos| Ok(pos)))
+    }
+}
+
+impl ToPredicateFn for BitSetRevset {
+    fn to_predicate_fn<'a>(&self) -> BoxedPredicateFn<'a>
+    where
+        Self: 'a,
+    {
+        let set = self.set.clone();
+        Box::new(move |_index, pos| Ok(set.contains(pos)))
+    }
+}
+
 /// Adapter for infallible `RevWalk` of `GlobalCommitPosition`s.
 struct RevWalkRevset<W> {
     walk: W,
@@ -761,9 +792,19 @@ impl EvaluationContext<'_> {
                 parents_range,
             } => {
                 let head_set = self.evaluate(heads)?;
-                let head_positions = head_set.positions().attach(index);
+                let head_positions: Vec<_> = head_set.positions().attach(index).try_collect()?;
+                if generation == &GENERATION_RANGE_FULL && parents_range == &PARENTS_RANGE_FULL {
+                    // Full ancestor sets are often evaluated repeatedly (e.g.
+                    // `::trunk()`), so look up the cached bit set. If there's
+                    // none, the set will be cached once the walk completes.
+                    if let Some(set) = index.reachability().get(index, &head_positions) {
+                        return Ok(Box::new(BitSetRevset { set }));
+                    }
+                    let walk = CachingAncestorsWalk::new(&head_positions);
+                    return Ok(Box::new(RevWalkRevset { walk }));
+                }
                 let builder = RevWalkBuilder::new(index)
-                    .wanted_heads(head_positions.try_collect()?)
+                    .wanted_heads(head_positions)
                     .wanted_parents_range(parents_range.clone());
                 if generation == &GENERATION_RANGE_FULL {
                     let walk = builder.ancestors().detach();
@@ -1490,6 +1531,7 @@ mod tests {
     use super::*;
     use crate::default_index::DefaultMutableIndex;
     use crate::default_index::readonly::FieldLengths;
+    use crate::diff::DiffAlgorithm;
     use crate::files::FileMergeHunkLevel;
     use crate::merge::SameChange;
     use crate::str_util::StringPattern;
@@ -1883,6 +1925,7 @@ mod tests {
             let options = MergeOptions {
                 hunk_level: FileMergeHunkLevel::Line,
                 same_change: SameChange::Accept,
+                diff_algorithm: DiffAlgorithm::Histogram,
             };
             diff_match_lines(&left1, &left2, &matcher, &options).unwrap()
         };
@@ -1908,6 +1951,7 @@ mod tests {
             let options = MergeOptions {
                 hunk_level: FileMergeHunkLevel::Line,
                 same_change: SameChange::Accept,
+                diff_algorithm: DiffAlgorithm::Histogram,
             };
             diff_match_lines(&conflict1, &conflict2, &matcher, &options).unwrap()
         };
@@ -1937,6 +1981,7 @@ mod tests {
             let options = MergeOptions {
                 hunk_level: FileMergeHunkLevel::Line,
                 same_change: SameChange::Accept,
+                diff_algorithm: DiffAlgorithm::Histogram,
             };
             diff_match_lines(&base, &conflict2, &matcher, &options).unwrap()
         };
@@ -33,10 +33,13 @@ use thiserror::Error;
 use super::changed_path::ChangedPathIndexSegmentId;
 use super::changed_path::CompositeChangedPathIndex;
 use super::changed_path::collect_changed_paths;
+use super::compaction::MAX_INCREMENTAL_SQUASH_COMMITS;
 use super::composite::AsCompositeIndex as _;
 use super::composite::CommitIndexSegmentId;
 use super::entry::GlobalCommitPosition;
 use super::mutable::DefaultMutableIndex;
+use super::mutable::MutableCommitIndexSegment;
+use super::reachability::ReachabilityCache;
 use super::readonly::DefaultReadonlyIndex;
 use super::readonly::FieldLengths;
 use super::readonly::ReadonlyCommitIndexSegment;
@@ -60,6 +63,9 @@ use crate::op_store::OpStoreError;
 use crate::op_store::OperationId;
 use crate::op_walk;
 use crate::operation::Operation;
+use crate::progress::Progress;
+use crate::progress::ProgressScope;
+use crate::progress::ProgressTask;
 use crate::store::Store;
 
 // BLAKE2b-512 hash length in hex string
@@ -132,6 +138,7 @@ impl DefaultIndexStore {
         // will be created by the other process.
         file_util::remove_dir_contents(&self.commit_segments_dir())?;
         file_util::remove_dir_contents(&self.changed_path_segments_dir())?;
+        file_util::remove_dir_contents(&self.reachability_dir())?;
         // jj <= 0.14 created segment files in the top directory
         for entry in self.dir.read_dir().context(&self.dir)? {
             let entry = entry.context(&self.dir)?;
@@ -151,6 +158,7 @@ impl DefaultIndexStore {
             self.legacy_operations_dir(),
             self.commit_segments_dir(),
             self.changed_path_segments_dir(),
+            self.reachability_dir(),
         ] {
             file_util::create_or_reuse_dir(&dir).context(&dir)?;
         }
@@ -177,6 +185,11 @@ impl DefaultIndexStore {
         self.dir.join("changed_paths")
     }
 
+    /// Directory for cached ancestor sets.
+    fn reachability_dir(&self) -> PathBuf {
+        self.dir.join("reachability")
+    }
+
     fn load_index_at_operation(
         &self,
         op_id: &OperationId,
@@ -237,18 +250,24 @@ impl DefaultIndexStore {
         } else {
             CompositeChangedPathIndex::null()
         };
-        Ok(DefaultReadonlyIndex::from_segment(commits, changed_paths))
+        let reachability = Arc::new(ReachabilityCache::persistent(self.reachability_dir()));
+        Ok(DefaultReadonlyIndex::from_segment(
+            commits,
+            changed_paths,
+            reachability,
+        ))
     }
 
     /// Rebuilds index for the given `operation`.
     ///
     /// The index to be built will be calculated from one of the ancestor
     /// operations if exists. Use `reinit()` to rebuild index from scratch.
-    #[tracing::instrument(skip(self, store))]
+    #[tracing::instrument(skip(self, store, progress))]
     pub async fn build_index_at_operation(
         &self,
         operation: &Operation,
         store: &Arc<Store>,
+        progress: Option<&dyn Progress>,
     ) -> Result<DefaultReadonlyIndex, DefaultIndexStoreError> {
         tracing::info!("scanning operations to index");
         let op_links_dir = self.op_links_dir();
@@ -375,13 +394,16 @@ impl DefaultIndexStore {
             },
             |_| panic!("graph has cycle"),
         )?;
-        for (CommitByCommitterTimestamp(commit), op_id) in commits.iter().rev() {
+        let progress = ProgressScope::start(progress, ProgressTask::IndexBuild);
+        let total = commits.len() as u64;
+        for (i, (CommitByCommitterTimestamp(commit), op_id)) in commits.iter().rev().enumerate() {
             mutable_index.add_commit(commit).await.map_err(|source| {
                 DefaultIndexStoreError::IndexCommits {
                     op_id: op_id.clone(),
                     source,
                 }
             })?;
+            progress.advance(i as u64 + 1, Some(total), None);
         }
 
         let index = self.save_mutable_index(mutable_index, operation.id())?;
@@ -394,13 +416,13 @@ impl DefaultIndexStore {
     ///
     /// At most `max_commits` number of commits will be scanned from the latest
     /// unindexed commit.
-    #[tracing::instrument(skip(self, store))]
+    #[tracing::instrument(skip(self, store, progress))]
     pub async fn build_changed_path_index_at_operation(
         &self,
         op_id: &OperationId,
         store: &Arc<Store>,
         max_commits: u32,
-        // TODO: add progress callback?
+        progress: Option<&dyn Progress>,
     ) -> Result<DefaultReadonlyIndex, DefaultIndexStoreError> {
         // Create directories in case the store was initialized by jj < 0.33.
         self.ensure_base_dirs()
@@ -446,6 +468,10 @@ impl DefaultIndexStore {
             Ok(())
         };
 
+        let progress = ProgressScope::start(progress, ProgressTask::IndexBuild);
+        let total = u64::from((pre_end - pre_start) + (post_end - post_start));
+        let mut completed = 0;
+
         // Index pre range
         let mut new_changed_paths =
             CompositeChangedPathIndex::empty(GlobalCommitPosition(pre_start));
@@ -455,6 +481,8 @@ impl DefaultIndexStore {
             index_commit(&mut new_changed_paths, pos)
                 .await
                 .map_err(to_index_err)?;
+            completed += 1;
+            progress.advance(completed, Some(total), None);
         }
         new_changed_paths
             .save_in(&self.changed_path_segments_dir())
@@ -470,15 +498,61 @@ impl DefaultIndexStore {
             index_commit(&mut new_changed_paths, pos)
                 .await
                 .map_err(to_index_err)?;
+            completed += 1;
+            progress.advance(completed, Some(total), None);
         }
-        new_changed_paths.maybe_squash_with_ancestors();
+        new_changed_paths.maybe_squash_with_ancestors(MAX_INCREMENTAL_SQUASH_COMMITS);
         new_changed_paths
             .save_in(&self.changed_path_segments_dir())
             .map_err(DefaultIndexStoreError::SaveIndex)?;
 
         // Update the operation link to point to the new segments
         let commits = index.readonly_commits().clone();
-        let index = DefaultReadonlyIndex::from_segment(commits, new_changed_paths);
+        let reachability = index.reachability().clone();
+        let index = DefaultReadonlyIndex::from_segment(commits, new_changed_paths, reachability);
+        self.associate_index_with_operation(&index, op_id)
+            .map_err(|source| DefaultIndexStoreError::AssociateIndex {
+                op_id: op_id.to_owned(),
+                source,
+            })?;
+        Ok(index)
+    }
+
+    /// Merges the index segments associated with the specified operation so
+    /// the stack of segments has O(log n) files.
+    ///
+    /// Index updates never rewrite large segments, so the stack may grow
+    /// deeper over time in big repositories. This function is expensive, and
+    /// is supposed to be run out of band (e.g. by `jj util gc`.)
+    #[tracing::instrument(skip(self, store))]
+    pub fn compact_index_at_operation(
+        &self,
+        op_id: &OperationId,
+        store: &Arc<Store>,
+    ) -> Result<DefaultReadonlyIndex, DefaultIndexStoreError> {
+        // Create directories in case the store was initialized by jj < 0.33.
+        self.ensure_base_dirs()
+            .map_err(DefaultIndexStoreError::SaveIndex)?;
+        let field_lengths = FieldLengths {
+            commit_id: store.commit_id_length(),
+            change_id: store.change_id_length(),
+        };
+        let index = self.load_index_at_operation(op_id, field_lengths)?;
+        let commits = MutableCommitIndexSegment::compact(
+            index.readonly_commits(),
+            &self.commit_segments_dir(),
+        )
+        .map_err(DefaultIndexStoreError::SaveIndex)?;
+        let mut changed_paths = index.changed_paths().clone();
+        let changed_paths_compacted = changed_paths
+            .compact_in(&self.changed_path_segments_dir())
+            .map_err(DefaultIndexStoreError::SaveIndex)?;
+        if commits.id() == index.readonly_commits().id() && !changed_paths_compacted {
+            return Ok(index);
+        }
+
+        let reachability = index.reachability().clone();
+        let index = DefaultReadonlyIndex::from_segment(commits, changed_paths, reachability);
         self.associate_index_with_operation(&index, op_id)
             .map_err(|source| DefaultIndexStoreError::AssociateIndex {
                 op_id: op_id.to_owned(),
@@ -497,14 +571,17 @@ impl DefaultIndexStore {
             .map_err(DefaultIndexStoreError::SaveIndex)?;
         let (commits, mut changed_paths) = index.into_segment();
         let commits = commits
-            .maybe_squash_with_ancestors()
+            .maybe_squash_with_ancestors(MAX_INCREMENTAL_SQUASH_COMMITS)
             .save_in(&self.commit_segments_dir())
             .map_err(DefaultIndexStoreError::SaveIndex)?;
-        changed_paths.maybe_squash_with_ancestors();
+        changed_paths.maybe_squash_with_ancestors(MAX_INCREMENTAL_SQUASH_COMMITS);
         changed_paths
             .save_in(&self.changed_path_segments_dir())
             .map_err(DefaultIndexStoreError::SaveIndex)?;
-        let index = DefaultReadonlyIndex::from_segment(commits, changed_paths);
+        // Don't keep the chain of caches of the parent indexes alive. Large
+        // ancestor sets can still be loaded from disk.
+        let reachability = Arc::new(ReachabilityCache::persistent(self.reachability_dir()));
+        let index = DefaultReadonlyIndex::from_segment(commits, changed_paths, reachability);
         self.associate_index_with_operation(&index, op_id)
             .map_err(|source| DefaultIndexStoreError::AssociateIndex {
                 op_id: op_id.to_owned(),
@@ -570,7 +647,7 @@ impl IndexStore for DefaultIndexStore {
             Err(DefaultIndexStoreError::LoadAssociation(PathError { source: error, .. }))
                 if error.kind() == io::ErrorKind::NotFound =>
             {
-                self.build_index_at_operation(op, store).block_on()
+                self.build_index_at_operation(op, store, None).block_on()
             }
             Err(DefaultIndexStoreError::LoadIndex(err)) if err.is_corrupt_or_not_found() => {
                 // If the index was corrupt (maybe it was written in a different format),
@@ -592,7 +669,7 @@ impl IndexStore for DefaultIndexStore {
                 }
                 self.reinit()
                     .map_err(|err| IndexStoreError::Read(err.into()))?;
-                self.build_index_at_operation(op, store).block_on()
+                self.build_index_at_operation(op, store, None).block_on()
             }
             result => result,
         }
@@ -183,6 +183,24 @@ impl CompareBytes for CompareBytesIgnoreWhitespaceAmount {
     }
 }
 
+/// Algorithm used to find the unchanged regions between inputs.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiffAlgorithm {
+    /// Anchors on the least frequent words shared by the inputs, then recurses
+    /// into the regions between them.
+    #[default]
+    Histogram,
+    /// Anchors only on words that occur exactly once in each input, then
+    /// recurses into the regions between them. Regions without such words
+    /// are compared with the `Minimal` algorithm.
+    Patience,
+    /// Finds the longest common subsequence of words, so the number of
+    /// changed words is minimal. This can be slow if the inputs are large and
+    /// very different.
+    Minimal,
+}
+
 // Not implementing Eq because the text should be compared by WordComparator.
 #[derive(Clone, Copy, Debug)]
 struct HashedWord<'input> {
@@ -423,17 +441,26 @@ fn collect_unchanged_words<C: CompareBytes, S: BuildHasher>(
     left: &LocalDiffSource,
     right: &LocalDiffSource,
     comp: &WordComparator<C, S>,
+    algorithm: DiffAlgorithm,
 ) {
     if left.ranges.is_empty() || right.ranges.is_empty() {
         return;
     }
+    if algorithm == DiffAlgorithm::Minimal {
+        collect_unchanged_words_minimal(found_positions, left, right, comp);
+        return;
+    }
 
     // Prioritize LCS-based algorithm than leading/trailing matches
     let old_len = found_positions.len();
-    collect_unchanged_words_lcs(found_positions, left, right, comp);
+    collect_unchanged_words_lcs(found_positions, left, right, comp, algorithm);
     if found_positions.len() != old_len {
         return;
     }
+    if algorithm == DiffAlgorithm::Patience {
+        collect_unchanged_words_minimal(found_positions, left, right, comp);
+        return;
+    }
 
     // Trim leading common ranges (i.e. grow previous unchanged region)
     let common_leading_len = iter::zip(left.hashed_words(), right.hashed_words())
@@ -468,6 +495,7 @@ fn collect_unchanged_words_lcs<C: CompareBytes, S: BuildHasher>(
     left: &LocalDiffSource,
     right: &LocalDiffSource,
     comp: &WordComparator<C, S>,
+    algorithm: DiffAlgorithm,
 ) {
     let max_occurrences = 100;
     let left_histogram = Histogram::calculate(left, comp, max_occurrences);
@@ -480,18 +508,26 @@ fn collect_unchanged_words_lcs<C: CompareBytes, S: BuildHasher>(
     // Look for words with few occurrences in `left` (could equally well have picked
     // `right`?). If any of them also occur in `right`, then we add the words to
     // the LCS.
+    // The patience algorithm only considers words which are unique in `left`
+    // and `right`.
+    let max_count = match algorithm {
+        DiffAlgorithm::Patience => 1,
+        DiffAlgorithm::Histogram | DiffAlgorithm::Minimal => max_occurrences,
+    };
     let Some(uncommon_shared_word_positions) =
-        left_count_to_entries.values().find_map(|left_entries| {
-            let mut both_positions = left_entries
-                .iter()
-                .filter_map(|&(word, left_positions)| {
-                    let right_positions = right_histogram.positions_by_word(*word, comp)?;
-                    (left_positions.len() == right_positions.len())
-                        .then_some((left_positions, right_positions))
-                })
-                .peekable();
-            both_positions.peek().is_some().then_some(both_positions)
-        })
+        left_count_to_entries
+            .range(..=max_count)
+            .find_map(|(_, left_entries)| {
+                let mut both_positions = left_entries
+                    .iter()
+                    .filter_map(|&(word, left_positions)| {
+                        let right_positions = right_histogram.positions_by_word(*word, comp)?;
+                        (left_positions.len() == right_positions.len())
+                            .then_some((left_positions, right_positions))
+                    })
+                    .peekable();
+                both_positions.peek().is_some().then_some(both_positions)
+            })
     else {
         return;
     };
@@ -530,6 +566,7 @@ fn collect_unchanged_words_lcs<C: CompareBytes, S: BuildHasher>(
             &left.narrowed(previous_left_position..left_position),
             &right.narrowed(previous_right_position..right_position),
             comp,
+            algorithm,
         );
         found_positions.push((
             left.map_to_global(left_position),
@@ -544,9 +581,128 @@ fn collect_unchanged_words_lcs<C: CompareBytes, S: BuildHasher>(
         &left.narrowed(previous_left_position..LocalWordPosition(left.ranges.len())),
         &right.narrowed(previous_right_position..LocalWordPosition(right.ranges.len())),
         comp,
+        algorithm,
     );
 }
 
+/// Finds the longest common subsequence of words, and adds the positions of
+/// the matching words.
+fn collect_unchanged_words_minimal<C: CompareBytes, S: BuildHasher>(
+    found_positions: &mut Vec<(WordPosition, WordPosition)>,
+    left: &LocalDiffSource,
+    right: &LocalDiffSource,
+    comp: &WordComparator<C, S>,
+) {
+    let left_words = left.hashed_words().collect_vec();
+    let right_words = right.hashed_words().collect_vec();
+    // Common leading and trailing words are trivially part of the LCS, and
+    // skipping them makes the search faster.
+    let common_leading_len = iter::zip(&left_words, &right_words)
+        .take_while(|&(&l, &r)| comp.eq_hashed(l, r))
+        .count();
+    let common_trailing_len = iter::zip(
+        left_words[common_leading_len..].iter().rev(),
+        right_words[common_leading_len..].iter().rev(),
+    )
+    .take_while(|&(&l, &r)| comp.eq_hashed(l, r))
+    .count();
+    let left_middle = &left_words[common_leading_len..left_words.len() - common_trailing_len];
+    let right_middle = &right_words[common_leading_len..right_words.len() - common_trailing_len];
+    let middle_lcs = find_lcs_myers(left_middle.len(), right_middle.len(), |l, r| {
+        comp.eq_hashed(left_middle[l], right_middle[r])
+    });
+
+    let to_global = |l: usize, r: usize| {
+        (
+            left.map_to_global(LocalWordPosition(l)),
+            right.map_to_global(LocalWordPosition(r)),
+        )
+    };
+    found_positions.extend(itertools::chain!(
+        (0..common_leading_len).map(|i| to_global(i, i)),
+        middle_lcs
+            .into_iter()
+            .map(|(l, r)| to_global(common_leading_len + l, common_leading_len + r)),
+        (1..=common_trailing_len)
+            .rev()
+            .map(|i| to_global(left_words.len() - i, right_words.len() - i)),
+    ));
+}
+
+/// Finds the longest common subsequence of two sequences of lengths `left_len`
+/// and `right_len` using Myers' O(ND) algorithm. Returns the pairs of matching
+/// `(left, right)` indices in ascending order.
+fn find_lcs_myers(
+    left_len: usize,
+    right_len: usize,
+    eq: impl Fn(usize, usize) -> bool,
+) -> Vec<(usize, usize)> {
+    let max_edits = left_len + right_len;
+    if max_edits == 0 {
+        return vec![];
+    }
+    // `furthest[k + max_edits]` is the furthest left position reached on
+    // diagonal `k = left - right`. The states of diagonals `-d..=d` after `d`
+    // edits are recorded in `trace[d]` to trace the path back.
+    let index = |k: isize| k.checked_add_unsigned(max_edits).unwrap() as usize;
+    let mut furthest = vec![0_usize; 2 * max_edits + 1];
+    let mut trace: Vec<Vec<usize>> = vec![];
+    'search: for d in 0..=max_edits as isize {
+        for k in (-d..=d).step_by(2) {
+            let mut l = if k == -d || (k != d && furthest[index(k - 1)] < furthest[index(k + 1)]) {
+                furthest[index(k + 1)]
+            } else {
+                furthest[index(k - 1)] + 1
+            };
+            let mut r = l.checked_add_signed(-k).unwrap();
+            while l < left_len && r < right_len && eq(l, r) {
+                l += 1;
+                r += 1;
+            }
+            furthest[index(k)] = l;
+            if l >= left_len && r >= right_len {
+                trace.push(furthest[index(-d)..=index(d)].to_vec());
+                break 'search;
+            }
+        }
+        trace.push(furthest[index(-d)..=index(d)].to_vec());
+    }
+
+    let mut result = vec![];
+    let (mut l, mut r) = (left_len, right_len);
+    for d in (1..trace.len() as isize).rev() {
+        let previous = &trace[d as usize - 1];
+        let previous_at = |k: isize| previous[(k + d - 1) as usize];
+        let k = l as isize - r as isize;
+        let previous_k = if k == -d || (k != d && previous_at(k - 1) < previous_at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_l = previous_at(previous_k);
+        let previous_r = previous_l.checked_add_signed(-previous_k).unwrap();
+        // The edit moves from the previous position to the start of the snake.
+        let (snake_l, snake_r) = if previous_k == k + 1 {
+            (previous_l, previous_r + 1)
+        } else {
+            (previous_l + 1, previous_r)
+        };
+        while l > snake_l && r > snake_r {
+            l -= 1;
+            r -= 1;
+            result.push((l, r));
+        }
+        (l, r) = (previous_l, previous_r);
+    }
+    while l > 0 && r > 0 {
+        l -= 1;
+        r -= 1;
+        result.push((l, r));
+    }
+    result.reverse();
+    result
+}
+
 /// Intersects two sorted sequences of `(base, other)` word positions by
 /// `base`. `base` positions should refer to the same source text.
 fn intersect_unchanged_words(
@@ -612,6 +768,17 @@ impl<'input> ContentDiff<'input> {
         inputs: impl IntoIterator<Item = &'input T>,
         tokenizer: impl Fn(&[u8]) -> Vec<Range<usize>>,
         compare: impl CompareBytes,
+    ) -> Self {
+        Self::for_tokenizer_with_algorithm(inputs, tokenizer, compare, DiffAlgorithm::default())
+    }
+
+    /// Like [`Self::for_tokenizer()`], but finds unchanged regions using the
+    /// given `algorithm`.
+    pub fn for_tokenizer_with_algorithm<T: AsRef<[u8]> + ?Sized + 'input>(
+        inputs: impl IntoIterator<Item = &'input T>,
+        tokenizer: impl Fn(&[u8]) -> Vec<Range<usize>>,
+        compare: impl CompareBytes,
+        algorithm: DiffAlgorithm,
     ) -> Self {
         let mut inputs = inputs.into_iter().map(BStr::new);
         let base_input = inputs.next().expect("inputs must not be empty");
@@ -639,6 +806,7 @@ impl<'input> ContentDiff<'input> {
             &base_token_ranges,
             &other_token_ranges,
             compare,
+            algorithm,
         )
     }
 
@@ -648,6 +816,7 @@ impl<'input> ContentDiff<'input> {
         base_token_ranges: &[Range<usize>],
         other_token_ranges: &[Vec<Range<usize>>],
         compare: impl CompareBytes,
+        algorithm: DiffAlgorithm,
     ) -> Self {
         assert_eq!(other_inputs.len(), other_token_ranges.len());
         let comp = WordComparator::new(compare);
@@ -680,6 +849,7 @@ impl<'input> ContentDiff<'input> {
                     &base_source.local(),
                     &first_other_source.local(),
                     &comp,
+                    algorithm,
                 );
                 if tail_other_sources.is_empty() {
                     unchanged_regions.extend(first_positions.iter().map(
@@ -706,6 +876,7 @@ impl<'input> ContentDiff<'input> {
                                 &base_source.local(),
                                 &other_source.local(),
                                 &comp,
+                                algorithm,
                             );
                             intersect_unchanged_words(current_positions, &new_positions)
                         },
@@ -835,6 +1006,31 @@ impl<'input> ContentDiff<'input> {
         self.compact_unchanged_regions();
     }
 
+    /// Merges changed regions which consist only of blank lines on all sides
+    /// into the surrounding unchanged regions.
+    ///
+    /// Unlike other unchanged regions, the merged regions may have different
+    /// contents on each side.
+    pub fn ignore_blank_line_changes(&mut self) {
+        let is_blank = |text: &BStr| text.iter().all(|b| b.is_ascii_whitespace());
+        let mut merged: Vec<UnchangedRange> = vec![];
+        for current in &self.unchanged_regions {
+            let is_blank_change = merged
+                .last()
+                .is_some_and(|previous| self.hunk_between(previous, current).all(is_blank));
+            if is_blank_change {
+                let previous = merged.last_mut().unwrap();
+                previous.base.end = current.base.end;
+                for (prev, cur) in iter::zip(&mut previous.others, &current.others) {
+                    prev.end = cur.end;
+                }
+            } else {
+                merged.push(current.clone());
+            }
+        }
+        self.unchanged_regions = merged;
+    }
+
     fn compact_unchanged_regions(&mut self) {
         let mut compacted = vec![];
         let mut maybe_previous: Option<UnchangedRange> = None;
@@ -1135,6 +1331,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_lcs_myers() {
+        let lcs = |left: &[u8], right: &[u8]| {
+            find_lcs_myers(left.len(), right.len(), |l, r| left[l] == right[r])
+        };
+        assert_eq!(lcs(b"", b""), vec![]);
+        assert_eq!(lcs(b"abc", b""), vec![]);
+        assert_eq!(lcs(b"", b"abc"), vec![]);
+        assert_eq!(lcs(b"abc", b"abc"), vec![(0, 0), (1, 1), (2, 2)]);
+        assert_eq!(lcs(b"abc", b"xyz"), vec![]);
+        assert_eq!(
+            lcs(b"abcabba", b"cbabac"),
+            vec![(2, 0), (3, 2), (4, 3), (6, 4)]
+        );
+    }
+
     #[test]
     fn test_find_word_ranges_many_words() {
         assert_eq!(
@@ -1193,14 +1405,28 @@ mod tests {
     }
 
     fn unchanged_ranges(
+        left: (&[u8], &[Range<usize>]),
+        right: (&[u8], &[Range<usize>]),
+    ) -> Vec<(Range<usize>, Range<usize>)> {
+        unchanged_ranges_with_algorithm(left, right, DiffAlgorithm::Histogram)
+    }
+
+    fn unchanged_ranges_with_algorithm(
         (left_text, left_ranges): (&[u8], &[Range<usize>]),
         (right_text, right_ranges): (&[u8], &[Range<usize>]),
+        algorithm: DiffAlgorithm,
     ) -> Vec<(Range<usize>, Range<usize>)> {
         let comp = WordComparator::new(CompareBytesExactly);
         let left = DiffSource::new(left_text, left_ranges, &comp);
         let right = DiffSource::new(right_text, right_ranges, &comp);
         let mut positions = Vec::new();
-        collect_unchanged_words(&mut positions, &left.local(), &right.local(), &comp);
+        collect_unchanged_words(
+            &mut positions,
+            &left.local(),
+            &right.local(),
+            &comp,
+            algorithm,
+        );
         positions
             .into_iter()
             .map(|(left_pos, right_pos)| (left.range_at(left_pos), right.range_at(right_pos)))
@@ -1335,6 +1561,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_unchanged_ranges_minimal() {
+        // The histogram algorithm gives up on the non-unique "a"s, but the
+        // minimal algorithm matches as many of them as possible.
+        assert_eq!(
+            unchanged_ranges_with_algorithm(
+                (b"a a a a", &[0..1, 2..3, 4..5, 6..7]),
+                (b"a b a c", &[0..1, 2..3, 4..5, 6..7]),
+                DiffAlgorithm::Minimal,
+            ),
+            vec![(0..1, 0..1), (2..3, 4..5)]
+        );
+        assert_eq!(
+            unchanged_ranges_with_algorithm(
+                (b"b a a c", &[0..1, 2..3, 4..5, 6..7]),
+                (b"a a a a", &[0..1, 2..3, 4..5, 6..7]),
+                DiffAlgorithm::Minimal,
+            ),
+            vec![(2..3, 0..1), (4..5, 2..3)]
+        );
+    }
+
+    #[test]
+    fn test_unchanged_ranges_patience() {
+        // "|" is unique on both sides, so it matches first.
+        assert_eq!(
+            unchanged_ranges_with_algorithm(
+                (b"a b | b", &[0..1, 2..3, 4..5, 6..7]),
+                (b"b c d |", &[0..1, 2..3, 4..5, 6..7]),
+                DiffAlgorithm::Patience,
+            ),
+            vec![(2..3, 0..1), (4..5, 6..7)]
+        );
+        // No word is unique, so the words are matched by the minimal algorithm,
+        // whereas the histogram algorithm anchors on the "x"s.
+        assert_eq!(
+            unchanged_ranges_with_algorithm(
+                (b"x y x y", &[0..1, 2..3, 4..5, 6..7]),
+                (b"y x y x", &[0..1, 2..3, 4..5, 6..7]),
+                DiffAlgorithm::Patience,
+            ),
+            vec![(2..3, 0..1), (4..5, 2..3), (6..7, 4..5)]
+        );
+        assert_eq!(
+            unchanged_ranges_with_algorithm(
+                (b"x y x y", &[0..1, 2..3, 4..5, 6..7]),
+                (b"y x y x", &[0..1, 2..3, 4..5, 6..7]),
+                DiffAlgorithm::Histogram,
+            ),
+            vec![(0..1, 2..3), (6..7, 4..5)]
+        );
+    }
+
     #[test]
     fn test_diff_single_input() {
         assert_eq!(diff(["abc"]), vec![DiffHunk::matching(["abc"])]);
@@ -1558,6 +1837,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_diff_ignore_blank_line_changes() {
+        fn diff(inputs: [&str; 2]) -> Vec<DiffHunk<'_>> {
+            let mut diff = ContentDiff::by_line(inputs);
+            diff.ignore_blank_line_changes();
+            diff.hunks().collect()
+        }
+
+        assert_eq!(diff(["", "\n"]), vec![DiffHunk::matching(["", "\n"])]);
+        // blank lines added and removed between unchanged lines
+        assert_eq!(
+            diff(["a\nb\n\nc\n", "a\n\n  \nb\nc\n"]),
+            vec![DiffHunk::matching(["a\nb\n\nc\n", "a\n\n  \nb\nc\n"])]
+        );
+        // blank lines are only ignored if the whole change is blank
+        assert_eq!(
+            diff(["a\n\nb\n", "a\nx\nb\n"]),
+            vec![
+                DiffHunk::matching(["a\n", "a\n"]),
+                DiffHunk::different(["\n", "x\n"]),
+                DiffHunk::matching(["b\n", "b\n"]),
+            ]
+        );
+        assert_eq!(
+            diff(["a\nb\n", "\na\nx\n\n"]),
+            vec![
+                DiffHunk::matching(["a\n", "\na\n"]),
+                DiffHunk::different(["b\n", "x\n\n"]),
+            ]
+        );
+    }
+
     #[test]
     fn test_diff_hunk_iterator() {
         let diff = ContentDiff::by_word(["a b c", "a XX c", "a b "]);
@@ -17,21 +17,26 @@
 #![expect(missing_docs)]
 
 use std::borrow::Borrow;
+use std::io;
 use std::mem;
 
 use bstr::BString;
 use itertools::Itertools as _;
 use pollster::FutureExt as _;
+use tokio::io::AsyncReadExt as _;
 
+use crate::backend::BackendError;
 use crate::backend::BackendResult;
 use crate::conflicts::MaterializedFileValue;
 use crate::diff::CompareBytesExactly;
 use crate::diff::CompareBytesIgnoreAllWhitespace;
 use crate::diff::CompareBytesIgnoreWhitespaceAmount;
 use crate::diff::ContentDiff;
+use crate::diff::DiffAlgorithm;
 use crate::diff::DiffHunk;
 use crate::diff::DiffHunkKind;
 use crate::diff::find_line_ranges;
+use crate::files::MAX_CONTENT_SIZE;
 use crate::merge::Diff;
 use crate::repo_path::RepoPath;
 
@@ -51,9 +56,21 @@ type DiffTokenVec<'content> = Vec<(DiffTokenType, &'content [u8])>;
 pub struct FileContent<T> {
     /// false if this file is likely text; true if it is likely binary.
     pub is_binary: bool,
+    /// Size of the file if `contents` only holds the start of it. Binary files
+    /// and files larger than [`MAX_CONTENT_SIZE`] aren't read into memory
+    /// entirely.
+    pub truncated_size: Option<u64>,
     pub contents: T,
 }
 
+impl FileContent<BString> {
+    /// Returns the size of the file in bytes.
+    pub fn size(&self) -> u64 {
+        self.truncated_size
+            .unwrap_or_else(|| self.contents.len() as u64)
+    }
+}
+
 pub fn file_content_for_diff<T>(
     path: &RepoPath,
     file: &mut MaterializedFileValue,
@@ -63,14 +80,43 @@ pub fn file_content_for_diff<T>(
     // Determine whether it's binary by whether the first 8k bytes contain a null
     // character; this is the same heuristic used by git as of writing: https://github.com/git/git/blob/eea0e59ffbed6e33d171ace5be13cde9faa41639/xdiff-interface.c#L192-L198
     const PEEK_SIZE: usize = 8000;
-    // TODO: currently we look at the whole file, even though for binary files we
-    // only need to know the file size. To change that we'd have to extend all
-    // the data backends to support getting the length.
-    let contents = BString::new(file.read_all(path).block_on()?);
-    let start = &contents[..PEEK_SIZE.min(contents.len())];
+    let to_read_err = |err: io::Error| BackendError::ReadFile {
+        path: path.to_owned(),
+        id: file.id.clone(),
+        source: err.into(),
+    };
+    let reader = &mut file.reader;
+    let mut contents = vec![];
+    (&mut *reader)
+        .take(PEEK_SIZE as u64)
+        .read_to_end(&mut contents)
+        .block_on()
+        .map_err(to_read_err)?;
+    let mut is_binary = contents.contains(&b'\0');
+    if !is_binary {
+        // Like Git, treat files that are too large to diff as binary.
+        (&mut *reader)
+            .take(MAX_CONTENT_SIZE + 1 - contents.len() as u64)
+            .read_to_end(&mut contents)
+            .block_on()
+            .map_err(to_read_err)?;
+        is_binary = contents.len() as u64 > MAX_CONTENT_SIZE;
+    }
+    let mut truncated_size = None;
+    if is_binary {
+        // Only the size of the rest is needed.
+        let rest_size = tokio::io::copy(reader, &mut tokio::io::sink())
+            .block_on()
+            .map_err(to_read_err)?;
+        if rest_size > 0 || contents.len() > PEEK_SIZE {
+            truncated_size = Some(contents.len() as u64 + rest_size);
+            contents.truncate(PEEK_SIZE);
+        }
+    }
     Ok(FileContent {
-        is_binary: start.contains(&b'\0'),
-        contents: map_resolved(contents),
+        is_binary,
+        truncated_size,
+        contents: map_resolved(contents.into()),
     })
 }
 
@@ -88,21 +134,27 @@ pub enum LineCompareMode {
 pub fn diff_by_line<'input, T: AsRef<[u8]> + ?Sized + 'input>(
     inputs: impl IntoIterator<Item = &'input T>,
     options: &LineCompareMode,
+    algorithm: DiffAlgorithm,
 ) -> ContentDiff<'input> {
-    // TODO: If we add --ignore-blank-lines, its tokenizer will have to attach
-    // blank lines to the preceding range. Maybe it can also be implemented as a
-    // post-process (similar to refine_changed_regions()) that expands unchanged
-    // regions across blank lines.
     match options {
-        LineCompareMode::Exact => {
-            ContentDiff::for_tokenizer(inputs, find_line_ranges, CompareBytesExactly)
-        }
-        LineCompareMode::IgnoreAllSpace => {
-            ContentDiff::for_tokenizer(inputs, find_line_ranges, CompareBytesIgnoreAllWhitespace)
-        }
-        LineCompareMode::IgnoreSpaceChange => {
-            ContentDiff::for_tokenizer(inputs, find_line_ranges, CompareBytesIgnoreWhitespaceAmount)
-        }
+        LineCompareMode::Exact => ContentDiff::for_tokenizer_with_algorithm(
+            inputs,
+            find_line_ranges,
+            CompareBytesExactly,
+            algorithm,
+        ),
+        LineCompareMode::IgnoreAllSpace => ContentDiff::for_tokenizer_with_algorithm(
+            inputs,
+            find_line_ranges,
+            CompareBytesIgnoreAllWhitespace,
+            algorithm,
+        ),
+        LineCompareMode::IgnoreSpaceChange => ContentDiff::for_tokenizer_with_algorithm(
+            inputs,
+            find_line_ranges,
+            CompareBytesIgnoreWhitespaceAmount,
+            algorithm,
+        ),
     }
 }
 
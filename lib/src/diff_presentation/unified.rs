@@ -32,6 +32,7 @@ use crate::conflicts::ConflictMaterializeOptions;
 use crate::conflicts::MaterializedTreeValue;
 use crate::conflicts::materialize_merge_result_to_bytes;
 use crate::diff::ContentDiff;
+use crate::diff::DiffAlgorithm;
 use crate::diff::DiffHunkKind;
 use crate::merge::Diff;
 use crate::object_id::ObjectId as _;
@@ -72,6 +73,7 @@ pub fn git_diff_part(
                 hash: DUMMY_HASH.to_owned(),
                 content: FileContent {
                     is_binary: false,
+                    truncated_size: None,
                     contents: BString::default(),
                 },
             });
@@ -93,6 +95,7 @@ pub fn git_diff_part(
             content = FileContent {
                 // Unix file paths can't contain null bytes.
                 is_binary: false,
+                truncated_size: None,
                 contents: target.into(),
             };
         }
@@ -102,6 +105,7 @@ pub fn git_diff_part(
             hash = id.hex();
             content = FileContent {
                 is_binary: false,
+                truncated_size: None,
                 contents: BString::default(),
             };
         }
@@ -113,6 +117,7 @@ pub fn git_diff_part(
             hash = DUMMY_HASH.to_owned();
             content = FileContent {
                 is_binary: false, // TODO: are we sure this is never binary?
+                truncated_size: None,
                 contents: materialize_merge_result_to_bytes(
                     &file.contents,
                     &file.labels,
@@ -125,6 +130,7 @@ pub fn git_diff_part(
             hash = DUMMY_HASH.to_owned();
             content = FileContent {
                 is_binary: false,
+                truncated_size: None,
                 contents: id.describe(&labels).into(),
             };
         }
@@ -154,6 +160,15 @@ pub struct UnifiedDiffHunk<'content> {
 }
 
 impl<'content> UnifiedDiffHunk<'content> {
+    /// Returns true if all removed and added lines are blank.
+    pub fn has_only_blank_line_changes(&self) -> bool {
+        self.lines
+            .iter()
+            .filter(|(line_type, _)| !matches!(line_type, DiffLineType::Context))
+            .flat_map(|(_, tokens)| tokens)
+            .all(|(_, content)| content.iter().all(|b| b.is_ascii_whitespace()))
+    }
+
     fn extend_context_lines(&mut self, lines: impl IntoIterator<Item = &'content [u8]>) {
         let old_len = self.lines.len();
         self.lines.extend(lines.into_iter().map(|line| {
@@ -183,6 +198,7 @@ pub fn unified_diff_hunks<'content>(
     contents: Diff<&'content BStr>,
     context: usize,
     options: LineCompareMode,
+    algorithm: DiffAlgorithm,
 ) -> Vec<UnifiedDiffHunk<'content>> {
     let mut hunks = vec![];
     let mut current_hunk = UnifiedDiffHunk {
@@ -190,7 +206,7 @@ pub fn unified_diff_hunks<'content>(
         right_line_range: 0..0,
         lines: vec![],
     };
-    let diff = diff_by_line(contents.into_array(), &options);
+    let diff = diff_by_line(contents.into_array(), &options, algorithm);
     let mut diff_hunks = diff.hunks().peekable();
     while let Some(hunk) = diff_hunks.next() {
         match hunk.kind {
@@ -15,10 +15,16 @@
 use std::io::Cursor;
 
 use bstr::ByteSlice as _;
+use globset::GlobBuilder;
+use globset::GlobSet;
+use globset::GlobSetBuilder;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt as _;
 
 use crate::config::ConfigGetError;
+use crate::gitattributes::AttributeState;
+use crate::gitattributes::GitAttributes;
+use crate::repo_path::RepoPath;
 use crate::settings::UserSettings;
 
 fn is_binary(bytes: &[u8]) -> bool {
@@ -29,11 +35,7 @@ fn is_binary(bytes: &[u8]) -> bool {
     while let Some(byte) = bytes.next() {
         match *byte {
             b'\0' => return true,
-            b'\r' => {
-                if bytes.peek() != Some(&&b'\n') {
-                    return true;
-                }
-            }
+            b'\r' if bytes.peek() != Some(&&b'\n') => return true,
             _ => {}
         }
     }
@@ -43,12 +45,47 @@ fn is_binary(bytes: &[u8]) -> bool {
 #[derive(Clone)]
 pub(crate) struct TargetEolStrategy {
     eol_conversion_mode: EolConversionMode,
+    eol_conversion_rules: Vec<EolConversionRule>,
 }
 
 impl TargetEolStrategy {
-    pub(crate) fn new(eol_conversion_mode: EolConversionMode) -> Self {
+    pub(crate) fn new(
+        eol_conversion_mode: EolConversionMode,
+        eol_conversion_rules: Vec<EolConversionRule>,
+    ) -> Self {
         Self {
             eol_conversion_mode,
+            eol_conversion_rules,
+        }
+    }
+
+    /// Returns the conversion mode to use for the file at `path`.
+    ///
+    /// The `text` and `eol` attributes take precedence. Otherwise, the last
+    /// rule matching the path wins. If no rule matches, the default mode is
+    /// used.
+    pub(crate) fn mode_for_path(
+        &self,
+        path: &RepoPath,
+        attributes: &GitAttributes,
+    ) -> EolConversionMode {
+        let configured_mode = || {
+            self.eol_conversion_rules
+                .iter()
+                .rev()
+                .find(|rule| rule.matches(path))
+                .map_or(self.eol_conversion_mode, |rule| rule.eol_conversion_mode)
+        };
+        match (attributes.get("text"), attributes.value("eol")) {
+            (Some(AttributeState::Unset), _) => EolConversionMode::None,
+            (_, Some("crlf")) => EolConversionMode::InputOutput,
+            (_, Some("lf")) => EolConversionMode::Input,
+            // Files with the text attribute are always normalized.
+            (Some(AttributeState::Set | AttributeState::Value(_)), _) => match configured_mode() {
+                EolConversionMode::None => EolConversionMode::Input,
+                mode => mode,
+            },
+            (None, _) => configured_mode(),
         }
     }
 
@@ -85,9 +122,11 @@ impl TargetEolStrategy {
 
     pub(crate) async fn convert_eol_for_snapshot<'a>(
         &self,
+        path: &RepoPath,
+        attributes: &GitAttributes,
         mut contents: impl AsyncRead + Send + Unpin + 'a,
     ) -> Result<Box<dyn AsyncRead + Send + Unpin + 'a>, std::io::Error> {
-        match self.eol_conversion_mode {
+        match self.mode_for_path(path, attributes) {
             EolConversionMode::None => Ok(Box::new(contents)),
             EolConversionMode::Input | EolConversionMode::InputOutput => {
                 let mut peek = vec![];
@@ -105,9 +144,11 @@ impl TargetEolStrategy {
 
     pub(crate) async fn convert_eol_for_update<'a>(
         &self,
+        path: &RepoPath,
+        attributes: &GitAttributes,
         mut contents: impl AsyncRead + Send + Unpin + 'a,
     ) -> Result<Box<dyn AsyncRead + Send + Unpin + 'a>, std::io::Error> {
-        match self.eol_conversion_mode {
+        match self.mode_for_path(path, attributes) {
             EolConversionMode::None | EolConversionMode::Input => Ok(Box::new(contents)),
             EolConversionMode::InputOutput => {
                 let mut peek = vec![];
@@ -148,6 +189,57 @@ impl EolConversionMode {
     }
 }
 
+/// Overrides the [`EolConversionMode`] for files matching some glob patterns.
+///
+/// Like in `.gitattributes`, a pattern without a slash matches the file name
+/// in any directory. Other patterns are matched against the whole path
+/// relative to the workspace root.
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(try_from = "RawEolConversionRule")]
+pub struct EolConversionRule {
+    patterns: GlobSet,
+    eol_conversion_mode: EolConversionMode,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawEolConversionRule {
+    patterns: Vec<String>,
+    eol_conversion: EolConversionMode,
+}
+
+impl TryFrom<RawEolConversionRule> for EolConversionRule {
+    type Error = globset::Error;
+
+    fn try_from(rule: RawEolConversionRule) -> Result<Self, Self::Error> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &rule.patterns {
+            let pattern = if pattern.trim_end_matches('/').contains('/') {
+                pattern.trim_start_matches('/').to_owned()
+            } else {
+                format!("**/{pattern}")
+            };
+            builder.add(GlobBuilder::new(&pattern).literal_separator(true).build()?);
+        }
+        Ok(Self {
+            patterns: builder.build()?,
+            eol_conversion_mode: rule.eol_conversion,
+        })
+    }
+}
+
+impl EolConversionRule {
+    /// Loads the rules from the `working-copy.eol-conversion-rules` setting in
+    /// the [`UserSettings`].
+    pub fn try_from_settings(user_settings: &UserSettings) -> Result<Vec<Self>, ConfigGetError> {
+        user_settings.get("working-copy.eol-conversion-rules")
+    }
+
+    fn matches(&self, path: &RepoPath) -> bool {
+        self.patterns.is_match(path.as_internal_file_string())
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum TargetEol {
     Lf,
@@ -293,30 +385,14 @@ mod tests {
     }
 
     #[tokio::main(flavor = "current_thread")]
-    #[test_case(TargetEolStrategy {
-          eol_conversion_mode: EolConversionMode::None,
-      }, b"\r\n", b"\r\n"; "none settings")]
-    #[test_case(TargetEolStrategy {
-          eol_conversion_mode: EolConversionMode::Input,
-      }, b"\r\n", b"\n"; "input settings text input")]
-    #[test_case(TargetEolStrategy {
-          eol_conversion_mode: EolConversionMode::InputOutput,
-      }, b"\r\n", b"\n"; "input output settings text input")]
-    #[test_case(TargetEolStrategy {
-          eol_conversion_mode: EolConversionMode::Input,
-      }, b"\0\r\n", b"\0\r\n"; "input settings binary input")]
-    #[test_case(TargetEolStrategy {
-          eol_conversion_mode: EolConversionMode::InputOutput,
-      }, b"\0\r\n", b"\0\r\n"; "input output settings binary input with NUL")]
-    #[test_case(TargetEolStrategy {
-          eol_conversion_mode: EolConversionMode::InputOutput,
-      }, b"\r\r\n", b"\r\r\n"; "input output settings binary input with lone CR")]
-    #[test_case(TargetEolStrategy {
-          eol_conversion_mode: EolConversionMode::Input,
-      }, &[0; 20 << 10], &[0; 20 << 10]; "input settings long binary input")]
-    #[test_case(TargetEolStrategy {
-          eol_conversion_mode: EolConversionMode::Input,
-      }, &test_probe_limit_input_crlf(), &test_probe_limit_input_lf(); "input settings with CRLF on probe boundary")]
+    #[test_case(TargetEolStrategy::new(EolConversionMode::None, vec![]), b"\r\n", b"\r\n"; "none settings")]
+    #[test_case(TargetEolStrategy::new(EolConversionMode::Input, vec![]), b"\r\n", b"\n"; "input settings text input")]
+    #[test_case(TargetEolStrategy::new(EolConversionMode::InputOutput, vec![]), b"\r\n", b"\n"; "input output settings text input")]
+    #[test_case(TargetEolStrategy::new(EolConversionMode::Input, vec![]), b"\0\r\n", b"\0\r\n"; "input settings binary input")]
+    #[test_case(TargetEolStrategy::new(EolConversionMode::InputOutput, vec![]), b"\0\r\n", b"\0\r\n"; "input output settings binary input with NUL")]
+    #[test_case(TargetEolStrategy::new(EolConversionMode::InputOutput, vec![]), b"\r\r\n", b"\r\r\n"; "input output settings binary input with lone CR")]
+    #[test_case(TargetEolStrategy::new(EolConversionMode::Input, vec![]), &[0; 20 << 10], &[0; 20 << 10]; "input settings long binary input")]
+    #[test_case(TargetEolStrategy::new(EolConversionMode::Input, vec![]), &test_probe_limit_input_crlf(), &test_probe_limit_input_lf(); "input settings with CRLF on probe boundary")]
     async fn test_eol_strategy_convert_eol_for_snapshot(
         strategy: TargetEolStrategy,
         contents: &[u8],
@@ -324,7 +400,11 @@ mod tests {
     ) {
         let mut actual_output = vec![];
         strategy
-            .convert_eol_for_snapshot(contents)
+            .convert_eol_for_snapshot(
+                RepoPath::from_internal_string("file").unwrap(),
+                &GitAttributes::default(),
+                contents,
+            )
             .await
             .unwrap()
             .read_to_end(&mut actual_output)
@@ -334,21 +414,11 @@ mod tests {
     }
 
     #[tokio::main(flavor = "current_thread")]
-    #[test_case(TargetEolStrategy {
-          eol_conversion_mode: EolConversionMode::None,
-      }, b"\n", b"\n"; "none settings")]
-    #[test_case(TargetEolStrategy {
-          eol_conversion_mode: EolConversionMode::Input,
-      }, b"\n", b"\n"; "input settings")]
-    #[test_case(TargetEolStrategy {
-          eol_conversion_mode: EolConversionMode::InputOutput,
-      }, b"\n", b"\r\n"; "input output settings text input")]
-    #[test_case(TargetEolStrategy {
-          eol_conversion_mode: EolConversionMode::InputOutput,
-      }, b"\0\n", b"\0\n"; "input output settings binary input")]
-    #[test_case(TargetEolStrategy {
-          eol_conversion_mode: EolConversionMode::Input,
-      }, &[0; 20 << 10], &[0; 20 << 10]; "input output settings long binary input")]
+    #[test_case(TargetEolStrategy::new(EolConversionMode::None, vec![]), b"\n", b"\n"; "none settings")]
+    #[test_case(TargetEolStrategy::new(EolConversionMode::Input, vec![]), b"\n", b"\n"; "input settings")]
+    #[test_case(TargetEolStrategy::new(EolConversionMode::InputOutput, vec![]), b"\n", b"\r\n"; "input output settings text input")]
+    #[test_case(TargetEolStrategy::new(EolConversionMode::InputOutput, vec![]), b"\0\n", b"\0\n"; "input output settings binary input")]
+    #[test_case(TargetEolStrategy::new(EolConversionMode::Input, vec![]), &[0; 20 << 10], &[0; 20 << 10]; "input output settings long binary input")]
     async fn test_eol_strategy_convert_eol_for_update(
         strategy: TargetEolStrategy,
         contents: &[u8],
@@ -356,7 +426,11 @@ mod tests {
     ) {
         let mut actual_output = vec![];
         strategy
-            .convert_eol_for_update(contents)
+            .convert_eol_for_update(
+                RepoPath::from_internal_string("file").unwrap(),
+                &GitAttributes::default(),
+                contents,
+            )
             .await
             .unwrap()
             .read_to_end(&mut actual_output)
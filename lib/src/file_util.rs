@@ -281,6 +281,47 @@ impl FileIdentity {
     }
 }
 
+/// Whether file names in this directory are matched case-insensitively, as on
+/// the default filesystems of macOS and Windows.
+pub fn check_case_insensitivity(dir: impl AsRef<Path>) -> io::Result<bool> {
+    let temp_file = NamedTempFile::with_prefix_in(".case-check-", dir)?;
+    let path = temp_file.path();
+    let name = path.file_name().unwrap().to_string_lossy();
+    match FileIdentity::from_symlink_path(path.with_file_name(name.to_uppercase())) {
+        Ok(identity) => Ok(identity == FileIdentity::from_symlink_path(path)?),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Whether file names in this directory are matched regardless of their
+/// Unicode normalization form, as on the default filesystems of macOS.
+pub fn check_normalization_insensitivity(dir: impl AsRef<Path>) -> io::Result<bool> {
+    // "\u{e9}" and "e\u{301}" are the NFC and NFD forms of "é".
+    let temp_file = NamedTempFile::with_prefix_in(".normalization-check-\u{e9}-", dir)?;
+    let path = temp_file.path();
+    let name = path.file_name().unwrap().to_string_lossy();
+    match FileIdentity::from_symlink_path(path.with_file_name(name.replace('\u{e9}', "e\u{301}"))) {
+        Ok(identity) => Ok(identity == FileIdentity::from_symlink_path(path)?),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Reads `reader` to the end unless it's longer than `max_size` bytes. Returns
+/// `None` without reading the rest if it is.
+pub async fn read_to_end_with_limit<R: AsyncRead + Unpin>(
+    reader: R,
+    max_size: u64,
+) -> io::Result<Option<Vec<u8>>> {
+    let mut buf = Vec::new();
+    reader
+        .take(max_size.saturating_add(1))
+        .read_to_end(&mut buf)
+        .await?;
+    Ok((buf.len() as u64 <= max_size).then_some(buf))
+}
+
 /// Reads from an async source and writes to a sync destination. Does not spawn
 /// a task, so writes will block.
 pub async fn copy_async_to_sync<R: AsyncRead, W: Write + ?Sized>(
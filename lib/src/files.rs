@@ -24,13 +24,21 @@ use bstr::BString;
 use either::Either;
 use itertools::Itertools as _;
 
+use crate::diff::CompareBytesExactly;
 use crate::diff::ContentDiff;
 use crate::diff::DiffHunk;
 use crate::diff::DiffHunkKind;
+use crate::diff::find_line_ranges;
 use crate::merge::Merge;
 use crate::merge::SameChange;
 use crate::tree_merge::MergeOptions;
 
+/// Maximum size of file contents to be loaded into memory for diffing or
+/// merging. Larger files are diffed like binary files, and their conflicts
+/// aren't resolved or materialized with conflict markers. This is the default
+/// of Git's `core.bigFileThreshold`.
+pub const MAX_CONTENT_SIZE: u64 = 512 << 20;
+
 /// A diff line which may contain small hunks originating from both sides.
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub struct DiffLine<'a> {
@@ -315,7 +323,12 @@ where
     // usually done for 3-way conflicts. Are there better heuristics when there are
     // more than 3 parts?
     let num_diffs = inputs.removes().len();
-    let diff = ContentDiff::by_line(inputs.removes().chain(inputs.adds()));
+    let diff = ContentDiff::for_tokenizer_with_algorithm(
+        inputs.removes().chain(inputs.adds()),
+        find_line_ranges,
+        CompareBytesExactly,
+        options.diff_algorithm,
+    );
     let hunks = resolve_diff_hunks(&diff, num_diffs, options.same_change);
     match options.hunk_level {
         FileMergeHunkLevel::Line => B::from_hunks(hunks.map(MergeHunk::Borrowed)),
@@ -492,6 +505,7 @@ mod tests {
     use indoc::indoc;
 
     use super::*;
+    use crate::diff::DiffAlgorithm;
 
     fn conflict<const N: usize>(values: [&[u8]; N]) -> Merge<BString> {
         Merge::from_vec(values.map(hunk).to_vec())
@@ -855,6 +869,7 @@ mod tests {
         let options = MergeOptions {
             hunk_level: FileMergeHunkLevel::Line,
             same_change: SameChange::Accept,
+            diff_algorithm: DiffAlgorithm::Histogram,
         };
         let merge_hunks = |inputs: &_| merge_hunks(inputs, &options);
         // Unchanged and empty on all sides
@@ -971,6 +986,7 @@ mod tests {
         let options = MergeOptions {
             hunk_level: FileMergeHunkLevel::Line,
             same_change: SameChange::Accept,
+            diff_algorithm: DiffAlgorithm::Histogram,
         };
         let merge_hunks = |inputs: &_| merge_hunks(inputs, &options);
         let merge = |inputs: &_| merge(inputs, &options);
@@ -1078,11 +1094,32 @@ mod tests {
         assert_eq!(merge(&conflict([left, base, right])), resolved(merged));
     }
 
+    #[test]
+    fn test_merge_diff_algorithm() {
+        let options = |diff_algorithm| MergeOptions {
+            hunk_level: FileMergeHunkLevel::Line,
+            same_change: SameChange::Accept,
+            diff_algorithm,
+        };
+        // The left side rotates the lines, and the right side modifies the
+        // middle "x".
+        let inputs = conflict([b"y\nx\ny\nx\n", b"x\ny\nx\ny\n", b"x\ny\nX\ny\n"]);
+        // The histogram algorithm considers the middle "x" removed on the left
+        // side, which conflicts with the right side.
+        assert_eq!(try_merge(&inputs, &options(DiffAlgorithm::Histogram)), None);
+        // The minimal algorithm keeps the middle "x" on the left side.
+        assert_eq!(
+            try_merge(&inputs, &options(DiffAlgorithm::Minimal)),
+            Some(hunk(b"y\nX\ny\nx\n"))
+        );
+    }
+
     #[test]
     fn test_merge_hunk_by_word() {
         let options = MergeOptions {
             hunk_level: FileMergeHunkLevel::Word,
             same_change: SameChange::Accept,
+            diff_algorithm: DiffAlgorithm::Histogram,
         };
         let merge = |inputs: &_| merge(inputs, &options);
         // No context line in between, but "\n" is a context word
@@ -14,8 +14,11 @@
 
 //! Functional language for selecting a set of paths.
 
+use std::collections::BTreeSet;
 use std::collections::HashMap;
+use std::collections::hash_map;
 use std::iter;
+use std::ops::Range;
 use std::path;
 use std::slice;
 use std::sync::LazyLock;
@@ -25,17 +28,20 @@ use globset::GlobBuilder;
 use itertools::Itertools as _;
 use thiserror::Error;
 
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::TreeValue;
 use crate::dsl_util::collect_similar;
 use crate::fileset_parser;
-use crate::fileset_parser::BinaryOp;
-use crate::fileset_parser::ExpressionKind;
-use crate::fileset_parser::ExpressionNode;
+pub use crate::fileset_parser::BinaryOp;
+pub use crate::fileset_parser::ExpressionKind;
+pub use crate::fileset_parser::ExpressionNode;
 pub use crate::fileset_parser::FilesetDiagnostics;
 pub use crate::fileset_parser::FilesetParseError;
 pub use crate::fileset_parser::FilesetParseErrorKind;
 pub use crate::fileset_parser::FilesetParseResult;
-use crate::fileset_parser::FunctionCallNode;
-use crate::fileset_parser::UnaryOp;
+pub use crate::fileset_parser::FunctionCallNode;
+pub use crate::fileset_parser::UnaryOp;
 use crate::matchers::DifferenceMatcher;
 use crate::matchers::EverythingMatcher;
 use crate::matchers::FilesMatcher;
@@ -45,11 +51,14 @@ use crate::matchers::Matcher;
 use crate::matchers::NothingMatcher;
 use crate::matchers::PrefixMatcher;
 use crate::matchers::UnionMatcher;
+use crate::merge::MergedTreeValue;
+use crate::merged_tree::MergedTree;
 use crate::repo_path::RelativePathParseError;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathUiConverter;
 use crate::repo_path::UiPathParseError;
+use crate::store::Store;
 
 /// Error occurred during file pattern parsing.
 #[derive(Debug, Error)]
@@ -321,6 +330,120 @@ fn split_glob_path_i(input: &str) -> (&str, &str) {
     input.split_at(prefix_len)
 }
 
+/// Predicate on the properties of a file, which can only be evaluated against
+/// a tree.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum FilePredicate {
+    /// Matches files whose size in bytes is within the range.
+    Size(Range<u64>),
+    /// Matches executable files.
+    Executable,
+    /// Matches symlinks.
+    Symlink,
+    /// Matches conflicted paths.
+    Conflict,
+}
+
+impl FilePredicate {
+    /// Returns true if the `value` at `path` satisfies this predicate. For
+    /// conflicts, any of the added terms may satisfy it.
+    async fn matches(
+        &self,
+        store: &Store,
+        path: &RepoPath,
+        value: &MergedTreeValue,
+    ) -> BackendResult<bool> {
+        match self {
+            Self::Size(range) => {
+                for id in value.adds().flatten().filter_map(|value| match value {
+                    TreeValue::File { id, .. } => Some(id),
+                    _ => None,
+                }) {
+                    let mut reader = store.read_file(path, id).await?;
+                    let size = tokio::io::copy(&mut reader, &mut tokio::io::sink())
+                        .await
+                        .map_err(|err| BackendError::ReadFile {
+                            path: path.to_owned(),
+                            id: id.clone(),
+                            source: err.into(),
+                        })?;
+                    if range.contains(&size) {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            Self::Executable => Ok(value.adds().flatten().any(|value| {
+                matches!(
+                    value,
+                    TreeValue::File {
+                        executable: true,
+                        ..
+                    }
+                )
+            })),
+            Self::Symlink => Ok(value
+                .adds()
+                .flatten()
+                .any(|value| matches!(value, TreeValue::Symlink(_)))),
+            Self::Conflict => Ok(!value.is_resolved()),
+        }
+    }
+}
+
+/// Parses file size such as `10`, `1KiB`, or `1.5MB`.
+fn parse_file_size(text: &str) -> Option<u64> {
+    let unit_start = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(unit_start);
+    let multiplier: u64 = match unit {
+        "" | "B" => 1,
+        "k" | "K" | "KiB" => 1 << 10,
+        "M" | "MiB" => 1 << 20,
+        "G" | "GiB" => 1 << 30,
+        "kB" | "KB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        _ => return None,
+    };
+    if let Ok(number) = number.parse::<u64>() {
+        number.checked_mul(multiplier)
+    } else {
+        let number: f64 = number.parse().ok()?;
+        let size = (number * multiplier as f64).round();
+        (size.is_finite() && size <= u64::MAX as f64).then_some(size as u64)
+    }
+}
+
+/// Parses size range such as `>1MiB`, `<=100`, or `1KiB..1MiB`.
+fn parse_file_size_range(text: &str) -> Option<Range<u64>> {
+    if let Some(rest) = text.strip_prefix(">=") {
+        Some(parse_file_size(rest)?..u64::MAX)
+    } else if let Some(rest) = text.strip_prefix('>') {
+        Some(parse_file_size(rest)?.checked_add(1)?..u64::MAX)
+    } else if let Some(rest) = text.strip_prefix("<=") {
+        Some(0..parse_file_size(rest)?.saturating_add(1))
+    } else if let Some(rest) = text.strip_prefix('<') {
+        Some(0..parse_file_size(rest)?)
+    } else if let Some((start, end)) = text.split_once("..") {
+        let start = if start.is_empty() {
+            0
+        } else {
+            parse_file_size(start)?
+        };
+        let end = if end.is_empty() {
+            u64::MAX
+        } else {
+            parse_file_size(end)?
+        };
+        Some(start..end)
+    } else {
+        let size = parse_file_size(text)?;
+        Some(size..size.saturating_add(1))
+    }
+}
+
 /// AST-level representation of the fileset expression.
 #[derive(Clone, Debug)]
 pub enum FilesetExpression {
@@ -330,6 +453,9 @@ pub enum FilesetExpression {
     All,
     /// Matches basic pattern.
     Pattern(FilePattern),
+    /// Matches paths by file properties. Must be resolved against trees by
+    /// `FilesetExpression::resolve_predicates()` before building a matcher.
+    Predicate(FilePredicate),
     /// Matches any of the expressions.
     ///
     /// Use `FilesetExpression::union_all()` to construct a union expression.
@@ -400,7 +526,7 @@ impl FilesetExpression {
         iter::from_fn(move || {
             let expr = stack.pop()?;
             match expr {
-                Self::None | Self::All | Self::Pattern(_) => {}
+                Self::None | Self::All | Self::Pattern(_) | Self::Predicate(_) => {}
                 Self::UnionAll(exprs) => stack.extend(exprs.iter().rev()),
                 Self::Intersection(expr1, expr2) | Self::Difference(expr1, expr2) => {
                     stack.push(expr2);
@@ -424,7 +550,101 @@ impl FilesetExpression {
         })
     }
 
+    /// Returns true if this expression contains predicates on file
+    /// properties, which have to be resolved against trees.
+    pub fn has_predicates(&self) -> bool {
+        self.dfs_pre()
+            .any(|expr| matches!(expr, Self::Predicate(_)))
+    }
+
+    /// Replaces predicates on file properties with the paths which satisfy
+    /// them in any of the `trees`.
+    pub async fn resolve_predicates(&self, trees: &[&MergedTree]) -> BackendResult<Self> {
+        if !self.has_predicates() {
+            return Ok(self.clone());
+        }
+        // Only paths which may match the whole expression have to be checked.
+        let candidates = self.without_predicates(true);
+        let candidates_matcher = candidates.to_matcher();
+        let predicates: Vec<&FilePredicate> = self
+            .dfs_pre()
+            .filter_map(|expr| match expr {
+                Self::Predicate(predicate) => Some(predicate),
+                _ => None,
+            })
+            .unique()
+            .collect();
+        let mut matched_paths = vec![BTreeSet::new(); predicates.len()];
+        for tree in trees {
+            for (path, value) in tree.entries_matching(candidates_matcher.as_ref()) {
+                let value = value?;
+                for (predicate, paths) in iter::zip(&predicates, &mut matched_paths) {
+                    if predicate.matches(tree.store(), &path, &value).await? {
+                        paths.insert(path.clone());
+                    }
+                }
+            }
+        }
+        let resolved = self.map_predicates(&|predicate| {
+            let index = predicates.iter().position(|p| *p == predicate).unwrap();
+            let paths = matched_paths[index].iter().cloned().map(Self::file_path);
+            Self::union_all(paths.collect())
+        });
+        // A negated predicate would otherwise match paths which weren't checked.
+        Ok(resolved.intersection(candidates))
+    }
+
+    /// Replaces predicates with `all()` if `upper` is true, or `none()`
+    /// otherwise, so the resulting expression matches a superset (or subset)
+    /// of the paths.
+    fn without_predicates(&self, upper: bool) -> Self {
+        match self {
+            Self::None | Self::All | Self::Pattern(_) => self.clone(),
+            Self::Predicate(_) => {
+                if upper {
+                    Self::All
+                } else {
+                    Self::None
+                }
+            }
+            Self::UnionAll(exprs) => Self::UnionAll(
+                exprs
+                    .iter()
+                    .map(|expr| expr.without_predicates(upper))
+                    .collect(),
+            ),
+            Self::Intersection(expr1, expr2) => Self::Intersection(
+                Box::new(expr1.without_predicates(upper)),
+                Box::new(expr2.without_predicates(upper)),
+            ),
+            Self::Difference(expr1, expr2) => Self::Difference(
+                Box::new(expr1.without_predicates(upper)),
+                Box::new(expr2.without_predicates(!upper)),
+            ),
+        }
+    }
+
+    fn map_predicates(&self, f: &impl Fn(&FilePredicate) -> Self) -> Self {
+        match self {
+            Self::None | Self::All | Self::Pattern(_) => self.clone(),
+            Self::Predicate(predicate) => f(predicate),
+            Self::UnionAll(exprs) => {
+                Self::UnionAll(exprs.iter().map(|expr| expr.map_predicates(f)).collect())
+            }
+            Self::Intersection(expr1, expr2) => Self::Intersection(
+                Box::new(expr1.map_predicates(f)),
+                Box::new(expr2.map_predicates(f)),
+            ),
+            Self::Difference(expr1, expr2) => Self::Difference(
+                Box::new(expr1.map_predicates(f)),
+                Box::new(expr2.map_predicates(f)),
+            ),
+        }
+    }
+
     /// Transforms the expression tree to `Matcher` object.
+    ///
+    /// Unresolved predicates on file properties match no paths.
     pub fn to_matcher(&self) -> Box<dyn Matcher> {
         build_union_matcher(self.as_union_all())
     }
@@ -445,6 +665,7 @@ fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher> {
             // None and All are supposed to be simplified by caller.
             FilesetExpression::None => Box::new(NothingMatcher),
             FilesetExpression::All => Box::new(EverythingMatcher),
+            FilesetExpression::Predicate(_) => Box::new(NothingMatcher),
             FilesetExpression::Pattern(pattern) => {
                 match pattern {
                     FilePattern::FilePath(path) => file_paths.push(path),
@@ -509,6 +730,18 @@ type FilesetFunction = fn(
     &FunctionCallNode,
 ) -> FilesetParseResult<FilesetExpression>;
 
+/// Fileset function which may capture state, e.g. a handle to the data source
+/// it looks up paths in.
+pub type BoxedFilesetFunction = Box<
+    dyn Fn(
+            &mut FilesetDiagnostics,
+            &RepoPathUiConverter,
+            &FunctionCallNode,
+        ) -> FilesetParseResult<FilesetExpression>
+        + Send
+        + Sync,
+>;
+
 static BUILTIN_FUNCTION_MAP: LazyLock<HashMap<&str, FilesetFunction>> = LazyLock::new(|| {
     // Not using maplit::hashmap!{} or custom declarative macro here because
     // code completion inside macro is quite restricted.
@@ -521,21 +754,105 @@ static BUILTIN_FUNCTION_MAP: LazyLock<HashMap<&str, FilesetFunction>> = LazyLock
         function.expect_no_arguments()?;
         Ok(FilesetExpression::all())
     });
+    map.insert("size", |_diagnostics, _path_converter, function| {
+        let [arg] = function.expect_exact_arguments()?;
+        let text = match &arg.kind {
+            ExpressionKind::Identifier(text) => *text,
+            ExpressionKind::String(text) => text.as_str(),
+            _ => {
+                return Err(FilesetParseError::expression(
+                    "Expected size range",
+                    arg.span,
+                ));
+            }
+        };
+        let range = parse_file_size_range(text).ok_or_else(|| {
+            FilesetParseError::expression(format!("Invalid size range `{text}`"), arg.span)
+        })?;
+        Ok(FilesetExpression::Predicate(FilePredicate::Size(range)))
+    });
+    map.insert("executable", |_diagnostics, _path_converter, function| {
+        function.expect_no_arguments()?;
+        Ok(FilesetExpression::Predicate(FilePredicate::Executable))
+    });
+    map.insert("symlink", |_diagnostics, _path_converter, function| {
+        function.expect_no_arguments()?;
+        Ok(FilesetExpression::Predicate(FilePredicate::Symlink))
+    });
+    map.insert("conflict", |_diagnostics, _path_converter, function| {
+        function.expect_no_arguments()?;
+        Ok(FilesetExpression::Predicate(FilePredicate::Conflict))
+    });
     map
 });
 
+/// Error registering a fileset function under a name which is already taken.
+#[derive(Debug, Error)]
+#[error("Fileset function `{name}` is already defined")]
+pub struct FilesetFunctionConflictError {
+    /// Name of the function.
+    pub name: String,
+}
+
+/// A set of extensions for fileset parsing.
+#[derive(Default)]
+pub struct FilesetExtensions {
+    function_map: HashMap<String, BoxedFilesetFunction>,
+}
+
+impl FilesetExtensions {
+    /// Creates an empty set of extensions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a function, which may capture state. Fails if a builtin or
+    /// previously registered function has the same name.
+    pub fn add_function(
+        &mut self,
+        name: impl Into<String>,
+        func: BoxedFilesetFunction,
+    ) -> Result<(), FilesetFunctionConflictError> {
+        let name = name.into();
+        if BUILTIN_FUNCTION_MAP.contains_key(name.as_str()) {
+            return Err(FilesetFunctionConflictError { name });
+        }
+        match self.function_map.entry(name) {
+            hash_map::Entry::Occupied(entry) => Err(FilesetFunctionConflictError {
+                name: entry.key().clone(),
+            }),
+            hash_map::Entry::Vacant(entry) => {
+                entry.insert(func);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns true if a function of the given name is registered.
+    pub fn has_function(&self, name: &str) -> bool {
+        BUILTIN_FUNCTION_MAP.contains_key(name) || self.function_map.contains_key(name)
+    }
+}
+
 fn resolve_function(
     diagnostics: &mut FilesetDiagnostics,
     path_converter: &RepoPathUiConverter,
+    extensions: &FilesetExtensions,
     function: &FunctionCallNode,
 ) -> FilesetParseResult<FilesetExpression> {
     if let Some(func) = BUILTIN_FUNCTION_MAP.get(function.name) {
         func(diagnostics, path_converter, function)
+    } else if let Some(func) = extensions.function_map.get(function.name) {
+        func(diagnostics, path_converter, function)
     } else {
+        let names = BUILTIN_FUNCTION_MAP
+            .keys()
+            .copied()
+            .chain(extensions.function_map.keys().map(String::as_str));
         Err(FilesetParseError::new(
             FilesetParseErrorKind::NoSuchFunction {
                 name: function.name.to_owned(),
-                candidates: collect_similar(function.name, BUILTIN_FUNCTION_MAP.keys()),
+                candidates: collect_similar(function.name, names),
             },
             function.name_span,
         ))
@@ -545,6 +862,7 @@ fn resolve_function(
 fn resolve_expression(
     diagnostics: &mut FilesetDiagnostics,
     path_converter: &RepoPathUiConverter,
+    extensions: &FilesetExtensions,
     node: &ExpressionNode,
 ) -> FilesetParseResult<FilesetExpression> {
     let wrap_pattern_error =
@@ -566,14 +884,14 @@ fn resolve_expression(
             Ok(FilesetExpression::pattern(pattern))
         }
         ExpressionKind::Unary(op, arg_node) => {
-            let arg = resolve_expression(diagnostics, path_converter, arg_node)?;
+            let arg = resolve_expression(diagnostics, path_converter, extensions, arg_node)?;
             match op {
                 UnaryOp::Negate => Ok(FilesetExpression::all().difference(arg)),
             }
         }
         ExpressionKind::Binary(op, lhs_node, rhs_node) => {
-            let lhs = resolve_expression(diagnostics, path_converter, lhs_node)?;
-            let rhs = resolve_expression(diagnostics, path_converter, rhs_node)?;
+            let lhs = resolve_expression(diagnostics, path_converter, extensions, lhs_node)?;
+            let rhs = resolve_expression(diagnostics, path_converter, extensions, rhs_node)?;
             match op {
                 BinaryOp::Intersection => Ok(lhs.intersection(rhs)),
                 BinaryOp::Difference => Ok(lhs.difference(rhs)),
@@ -582,12 +900,12 @@ fn resolve_expression(
         ExpressionKind::UnionAll(nodes) => {
             let expressions = nodes
                 .iter()
-                .map(|node| resolve_expression(diagnostics, path_converter, node))
+                .map(|node| resolve_expression(diagnostics, path_converter, extensions, node))
                 .try_collect()?;
             Ok(FilesetExpression::union_all(expressions))
         }
         ExpressionKind::FunctionCall(function) => {
-            resolve_function(diagnostics, path_converter, function)
+            resolve_function(diagnostics, path_converter, extensions, function)
         }
     }
 }
@@ -597,10 +915,11 @@ pub fn parse(
     diagnostics: &mut FilesetDiagnostics,
     text: &str,
     path_converter: &RepoPathUiConverter,
+    extensions: &FilesetExtensions,
 ) -> FilesetParseResult<FilesetExpression> {
     let node = fileset_parser::parse_program(text)?;
     // TODO: add basic tree substitution pass to eliminate redundant expressions
-    resolve_expression(diagnostics, path_converter, &node)
+    resolve_expression(diagnostics, path_converter, extensions, &node)
 }
 
 /// Parses text into `FilesetExpression` with bare string fallback.
@@ -611,10 +930,11 @@ pub fn parse_maybe_bare(
     diagnostics: &mut FilesetDiagnostics,
     text: &str,
     path_converter: &RepoPathUiConverter,
+    extensions: &FilesetExtensions,
 ) -> FilesetParseResult<FilesetExpression> {
     let node = fileset_parser::parse_program_or_bare_string(text)?;
     // TODO: add basic tree substitution pass to eliminate redundant expressions
-    resolve_expression(diagnostics, path_converter, &node)
+    resolve_expression(diagnostics, path_converter, extensions, &node)
 }
 
 #[cfg(test)]
@@ -660,7 +980,14 @@ mod tests {
             cwd: PathBuf::from("/ws/cur"),
             base: PathBuf::from("/ws"),
         };
-        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &path_converter);
+        let parse = |text| {
+            parse_maybe_bare(
+                &mut FilesetDiagnostics::new(),
+                text,
+                &path_converter,
+                &FilesetExtensions::new(),
+            )
+        };
 
         // cwd-relative patterns
         insta::assert_debug_snapshot!(
@@ -720,7 +1047,14 @@ mod tests {
             cwd: PathBuf::from("/ws/cur*"),
             base: PathBuf::from("/ws"),
         };
-        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &path_converter);
+        let parse = |text| {
+            parse_maybe_bare(
+                &mut FilesetDiagnostics::new(),
+                text,
+                &path_converter,
+                &FilesetExtensions::new(),
+            )
+        };
 
         // cwd-relative, without meta characters
         insta::assert_debug_snapshot!(
@@ -938,7 +1272,14 @@ mod tests {
             cwd: PathBuf::from("/ws/cur"),
             base: PathBuf::from("/ws"),
         };
-        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &path_converter);
+        let parse = |text| {
+            parse_maybe_bare(
+                &mut FilesetDiagnostics::new(),
+                text,
+                &path_converter,
+                &FilesetExtensions::new(),
+            )
+        };
 
         // cwd-relative case-insensitive glob
         insta::assert_debug_snapshot!(
@@ -1062,7 +1403,14 @@ mod tests {
             cwd: PathBuf::from("/ws/cur*"),
             base: PathBuf::from("/ws"),
         };
-        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &path_converter);
+        let parse = |text| {
+            parse_maybe_bare(
+                &mut FilesetDiagnostics::new(),
+                text,
+                &path_converter,
+                &FilesetExtensions::new(),
+            )
+        };
 
         // cwd-relative, without meta/case-insensitive characters
         insta::assert_debug_snapshot!(
@@ -1159,7 +1507,14 @@ mod tests {
             cwd: PathBuf::from("/ws/cur"),
             base: PathBuf::from("/ws"),
         };
-        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &path_converter);
+        let parse = |text| {
+            parse_maybe_bare(
+                &mut FilesetDiagnostics::new(),
+                text,
+                &path_converter,
+                &FilesetExtensions::new(),
+            )
+        };
 
         insta::assert_debug_snapshot!(parse("all()").unwrap(), @"All");
         insta::assert_debug_snapshot!(parse("none()").unwrap(), @"None");
@@ -1179,6 +1534,136 @@ mod tests {
         "#);
     }
 
+    #[test]
+    fn test_parse_extension_function() {
+        let settings = insta_settings();
+        let _guard = settings.bind_to_scope();
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: PathBuf::from("/ws/cur"),
+            base: PathBuf::from("/ws"),
+        };
+        let owners = HashMap::from([("docs", vec!["README.md", "docs"])]);
+        let mut extensions = FilesetExtensions::new();
+        extensions
+            .add_function(
+                "owned_by",
+                Box::new(move |_diagnostics, _path_converter, function| {
+                    let [arg] = function.expect_exact_arguments()?;
+                    let ExpressionKind::Identifier(team) = arg.kind else {
+                        return Err(FilesetParseError::expression("Expected team", arg.span));
+                    };
+                    let paths = owners.get(team).map_or(&[][..], |paths| paths);
+                    Ok(FilesetExpression::union_all(
+                        paths
+                            .iter()
+                            .map(|path| FilesetExpression::prefix_path(repo_path_buf(*path)))
+                            .collect(),
+                    ))
+                }),
+            )
+            .unwrap();
+        assert!(extensions.has_function("owned_by"));
+        assert!(extensions.has_function("all"));
+        let parse = |text| {
+            parse_maybe_bare(
+                &mut FilesetDiagnostics::new(),
+                text,
+                &path_converter,
+                &extensions,
+            )
+        };
+
+        insta::assert_debug_snapshot!(parse("owned_by(docs) & ~x").unwrap(), @r#"
+        Intersection(
+            UnionAll(
+                [
+                    Pattern(PrefixPath("README.md")),
+                    Pattern(PrefixPath("docs")),
+                ],
+            ),
+            Difference(
+                All,
+                Pattern(PrefixPath("cur/x")),
+            ),
+        )
+        "#);
+        insta::assert_debug_snapshot!(parse("owned_bx()").unwrap_err().kind(), @r#"
+        NoSuchFunction {
+            name: "owned_bx",
+            candidates: [
+                "owned_by",
+            ],
+        }
+        "#);
+
+        // Names of builtin or already registered functions can't be reused
+        let func = || -> BoxedFilesetFunction { Box::new(|_, _, _| Ok(FilesetExpression::all())) };
+        assert_eq!(
+            extensions
+                .add_function("owned_by", func())
+                .unwrap_err()
+                .name,
+            "owned_by"
+        );
+        assert_eq!(
+            extensions.add_function("none", func()).unwrap_err().name,
+            "none"
+        );
+    }
+
+    #[test]
+    fn test_parse_predicate_function() {
+        let settings = insta_settings();
+        let _guard = settings.bind_to_scope();
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: PathBuf::from("/ws/cur"),
+            base: PathBuf::from("/ws"),
+        };
+        let parse = |text| {
+            parse_maybe_bare(
+                &mut FilesetDiagnostics::new(),
+                text,
+                &path_converter,
+                &FilesetExtensions::new(),
+            )
+        };
+
+        insta::assert_debug_snapshot!(parse("size(1KiB..)").unwrap(), @"Predicate(Size(1024..18446744073709551615))");
+        insta::assert_debug_snapshot!(parse("x & ~executable()").unwrap(), @r#"
+        Intersection(
+            Pattern(PrefixPath("cur/x")),
+            Difference(
+                All,
+                Predicate(Executable),
+            ),
+        )
+        "#);
+        insta::assert_debug_snapshot!(parse("size(>1)").unwrap_err().kind(), @"SyntaxError");
+        insta::assert_debug_snapshot!(parse("size(x)").unwrap_err().kind(), @r#"Expression("Invalid size range `x`")"#);
+        insta::assert_debug_snapshot!(parse("symlink(x)").unwrap_err().kind(), @r#"
+        InvalidArguments {
+            name: "symlink",
+            message: "Expected 0 arguments",
+        }
+        "#);
+    }
+
+    #[test]
+    fn test_parse_file_size_range() {
+        assert_eq!(parse_file_size_range("10"), Some(10..11));
+        assert_eq!(parse_file_size_range(">1KiB"), Some(1025..u64::MAX));
+        assert_eq!(parse_file_size_range(">=1k"), Some(1024..u64::MAX));
+        assert_eq!(parse_file_size_range("<1MB"), Some(0..1_000_000));
+        assert_eq!(parse_file_size_range("<=1M"), Some(0..1_048_577));
+        assert_eq!(parse_file_size_range("1.5KB..2GiB"), Some(1500..1 << 31));
+        assert_eq!(parse_file_size_range("..10B"), Some(0..10));
+        assert_eq!(parse_file_size_range("10.."), Some(10..u64::MAX));
+        assert_eq!(parse_file_size_range(""), None);
+        assert_eq!(parse_file_size_range(">"), None);
+        assert_eq!(parse_file_size_range("1TB"), None);
+        assert_eq!(parse_file_size_range("99999999999G"), None);
+    }
+
     #[test]
     fn test_parse_compound_expression() {
         let settings = insta_settings();
@@ -1187,7 +1672,14 @@ mod tests {
             cwd: PathBuf::from("/ws/cur"),
             base: PathBuf::from("/ws"),
         };
-        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &path_converter);
+        let parse = |text| {
+            parse_maybe_bare(
+                &mut FilesetDiagnostics::new(),
+                text,
+                &path_converter,
+                &FilesetExtensions::new(),
+            )
+        };
 
         insta::assert_debug_snapshot!(parse("~x").unwrap(), @r#"
         Difference(
@@ -169,27 +169,38 @@ fn rename_rules_in_pest_error(err: pest::error::Error<Rule>) -> pest::error::Err
     })
 }
 
+/// Kind of a fileset AST node.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ExpressionKind<'i> {
+    /// Bare word, e.g. `foo`
     Identifier(&'i str),
+    /// Quoted string, e.g. `"foo"`
     String(String),
+    /// Pattern with a kind prefix, e.g. `glob:"*.rs"`
     StringPattern {
+        /// The kind, e.g. `glob`
         kind: &'i str,
+        /// The pattern
         value: String,
     },
+    /// Unary operator expression
     Unary(UnaryOp, Box<ExpressionNode<'i>>),
+    /// Binary operator expression
     Binary(BinaryOp, Box<ExpressionNode<'i>>, Box<ExpressionNode<'i>>),
     /// `x | y | ..`
     UnionAll(Vec<ExpressionNode<'i>>),
+    /// Function call, e.g. `all()`
     FunctionCall(Box<FunctionCallNode<'i>>),
 }
 
+/// Unary fileset operator.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum UnaryOp {
     /// `~`
     Negate,
 }
 
+/// Binary fileset operator.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum BinaryOp {
     /// `&`
@@ -198,7 +209,9 @@ pub enum BinaryOp {
     Difference,
 }
 
+/// Fileset AST node.
 pub type ExpressionNode<'i> = dsl_util::ExpressionNode<'i, ExpressionKind<'i>>;
+/// Fileset function call AST node.
 pub type FunctionCallNode<'i> = dsl_util::FunctionCallNode<'i, ExpressionKind<'i>>;
 
 fn union_nodes<'i>(lhs: ExpressionNode<'i>, rhs: ExpressionNode<'i>) -> ExpressionNode<'i> {
@@ -17,15 +17,24 @@
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::path::PathBuf;
 use std::sync::mpsc::channel;
+use std::time::SystemTime;
 
+use blake2::Blake2b512;
+use blake2::Digest as _;
 use futures::StreamExt as _;
 use itertools::Itertools as _;
 use jj_lib::backend::BackendError;
 use jj_lib::backend::CommitId;
+use jj_lib::backend::CopyId;
 use jj_lib::backend::FileId;
 use jj_lib::backend::TreeValue;
 use jj_lib::matchers::Matcher;
+use jj_lib::merge::Merge;
 use jj_lib::merged_tree::TreeDiffEntry;
 use jj_lib::merged_tree_builder::MergedTreeBuilder;
 use jj_lib::repo::MutableRepo;
@@ -36,9 +45,15 @@ use jj_lib::revset::RevsetIteratorExt as _;
 use jj_lib::store::Store;
 use rayon::iter::IntoParallelIterator as _;
 use rayon::prelude::ParallelIterator as _;
+use tempfile::NamedTempFile;
 
+use crate::file_util::persist_content_addressed_temp_file;
+use crate::hex_util;
 use crate::revset::RevsetEvaluationError;
 
+/// Number of bytes of the content hash used as [FixCacheKey].
+const FIX_CACHE_KEY_LEN: usize = 32;
+
 /// Represents a file whose content may be transformed by a FileFixer.
 // TODO: Add the set of changed line/byte ranges, so those can be passed into code formatters via
 // flags. This will help avoid introducing unrelated changes when working on code with out of date
@@ -55,6 +70,27 @@ pub struct FileToFix {
     pub repo_path: RepoPathBuf,
 }
 
+/// Describes how a [FileToFix] was changed by a [FileFixer].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FixedFile {
+    /// The new file content, or `None` if the content is unchanged.
+    pub file_id: Option<FileId>,
+    /// The path the file should be moved to, or `None` if it stays in place.
+    pub new_path: Option<RepoPathBuf>,
+    /// Files to add next to the fixed file, along with their content. The
+    /// paths must not exist in the revisions being fixed.
+    pub new_files: Vec<(RepoPathBuf, FileId)>,
+}
+
+impl From<FileId> for FixedFile {
+    fn from(file_id: FileId) -> Self {
+        Self {
+            file_id: Some(file_id),
+            ..Self::default()
+        }
+    }
+}
+
 /// Error fixing files.
 #[derive(Debug, thiserror::Error)]
 pub enum FixError {
@@ -70,6 +106,17 @@ pub enum FixError {
     /// Error occurred while processing the file content.
     #[error(transparent)]
     FixContent(Box<dyn std::error::Error + Send + Sync>),
+    /// A file would be renamed or created at a path which is already in use.
+    #[error(
+        "Cannot write fixed file to {path} in commit {commit_id}: path already exists",
+        path = path.as_internal_file_string()
+    )]
+    PathConflict {
+        /// The commit being fixed.
+        commit_id: CommitId,
+        /// The path which is already in use.
+        path: RepoPathBuf,
+    },
 }
 
 /// Fixes a set of files.
@@ -78,11 +125,11 @@ pub enum FixError {
 /// code using a code formatter.
 pub trait FileFixer {
     /// Fixes a set of files. Stores the resulting file content (for modified
-    /// files).
+    /// or created files).
     ///
     /// Returns a map describing the subset of `files_to_fix` that resulted in
-    /// changed file content (unchanged files should not be present in the map),
-    /// pointing to the new FileId for the file.
+    /// changes (unchanged files should not be present in the map), pointing to
+    /// the new content, path, and any created files for the file.
     ///
     /// TODO: Better error handling so we can tell the user what went wrong with
     /// each failed input.
@@ -90,7 +137,7 @@ pub trait FileFixer {
         &mut self,
         store: &Store,
         files_to_fix: &'a HashSet<FileToFix>,
-    ) -> Result<HashMap<&'a FileToFix, FileId>, FixError>;
+    ) -> Result<HashMap<&'a FileToFix, FixedFile>, FixError>;
 }
 
 /// Aggregate information about the outcome of the file fixer.
@@ -106,6 +153,104 @@ pub struct FixSummary {
     pub num_fixed_commits: i32,
 }
 
+/// Key of a [FixCache] entry.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FixCacheKey(String);
+
+impl FixCacheKey {
+    /// Computes the key from everything that determines the output of a tool,
+    /// such as its command line and its input content.
+    pub fn new<'a>(parts: impl IntoIterator<Item = &'a [u8]>) -> Self {
+        let mut hasher = Blake2b512::new();
+        for part in parts {
+            hasher.update(u64::try_from(part.len()).unwrap().to_le_bytes());
+            hasher.update(part);
+        }
+        Self(hex_util::encode_hex(
+            &hasher.finalize()[..FIX_CACHE_KEY_LEN],
+        ))
+    }
+
+    /// Returns the key as a hex string.
+    pub fn hex(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Stores the outputs of fix tools in a directory, one file per
+/// [FixCacheKey].
+///
+/// Fix tools are expected to be deterministic, so an entry stays valid as long
+/// as its key is computed from all of the tool's inputs. This allows re-running
+/// a tool on content it has already processed, for example in other revisions
+/// of a stack, to be skipped.
+///
+/// The modification time of an entry is updated when it's used, so
+/// [`FixCache::gc()`] only removes the entries that haven't been used
+/// recently.
+#[derive(Debug)]
+pub struct FixCache {
+    dir: PathBuf,
+}
+
+impl FixCache {
+    /// Creates a cache backed by `dir`. The directory is created when the
+    /// first entry is written.
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Returns the cached output for `key`, if any.
+    pub fn get(&self, key: &FixCacheKey) -> Result<Option<Vec<u8>>, FixError> {
+        let path = self.dir.join(key.hex());
+        match fs::read(&path) {
+            Ok(content) => {
+                // Failing to mark the entry as used only makes it expire early.
+                fs::File::options()
+                    .write(true)
+                    .open(&path)
+                    .and_then(|file| file.set_modified(SystemTime::now()))
+                    .ok();
+                Ok(Some(content))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Records `content` as the output for `key`.
+    pub fn insert(&self, key: &FixCacheKey, content: &[u8]) -> Result<(), FixError> {
+        fs::create_dir_all(&self.dir)?;
+        let mut temp_file = NamedTempFile::new_in(&self.dir)?;
+        temp_file.write_all(content)?;
+        persist_content_addressed_temp_file(temp_file, self.dir.join(key.hex()))?;
+        Ok(())
+    }
+
+    /// Removes the entries that were last written or used before
+    /// `keep_newer`.
+    pub fn gc(&self, keep_newer: SystemTime) -> Result<(), FixError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let modified = entry.metadata()?.modified()?;
+            if modified < keep_newer {
+                match fs::remove_file(entry.path()) {
+                    Ok(()) => {}
+                    // Another process may have removed it concurrently.
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 /// A [FileFixer] that applies fix_fn to each file, in parallel.
 ///
 /// The implementation is currently based on [rayon].
@@ -116,9 +261,10 @@ pub struct ParallelFileFixer<T> {
     fix_fn: T,
 }
 
-impl<T> ParallelFileFixer<T>
+impl<T, R> ParallelFileFixer<T>
 where
-    T: Fn(&Store, &FileToFix) -> Result<Option<FileId>, FixError> + Sync + Send,
+    T: Fn(&Store, &FileToFix) -> Result<Option<R>, FixError> + Sync + Send,
+    R: Into<FixedFile> + Send,
 {
     /// Creates a ParallelFileFixer.
     pub fn new(fix_fn: T) -> Self {
@@ -126,24 +272,25 @@ where
     }
 }
 
-impl<T> FileFixer for ParallelFileFixer<T>
+impl<T, R> FileFixer for ParallelFileFixer<T>
 where
-    T: Fn(&Store, &FileToFix) -> Result<Option<FileId>, FixError> + Sync + Send,
+    T: Fn(&Store, &FileToFix) -> Result<Option<R>, FixError> + Sync + Send,
+    R: Into<FixedFile> + Send,
 {
     /// Applies `fix_fn()` to the inputs and stores the resulting file content.
     fn fix_files<'a>(
         &mut self,
         store: &Store,
         files_to_fix: &'a HashSet<FileToFix>,
-    ) -> Result<HashMap<&'a FileToFix, FileId>, FixError> {
+    ) -> Result<HashMap<&'a FileToFix, FixedFile>, FixError> {
         let (updates_tx, updates_rx) = channel();
         files_to_fix.into_par_iter().try_for_each_init(
             || updates_tx.clone(),
             |updates_tx, file_to_fix| -> Result<(), FixError> {
                 let result = (self.fix_fn)(store, file_to_fix)?;
                 match result {
-                    Some(new_file_id) => {
-                        updates_tx.send((file_to_fix, new_file_id)).unwrap();
+                    Some(fixed_file) => {
+                        updates_tx.send((file_to_fix, fixed_file.into())).unwrap();
                         Ok(())
                     }
                     None => Ok(()),
@@ -152,8 +299,8 @@ where
         )?;
         drop(updates_tx);
         let mut result = HashMap::new();
-        while let Ok((file_to_fix, new_file_id)) = updates_rx.recv() {
-            result.insert(file_to_fix, new_file_id);
+        while let Ok((file_to_fix, fixed_file)) = updates_rx.recv() {
+            result.insert(file_to_fix, fixed_file);
         }
         Ok(result)
     }
@@ -170,6 +317,9 @@ where
 /// that the fixes are not lost. This will never result in new conflicts. Files
 /// with existing conflicts are updated on all sides of the conflict, which
 /// can potentially increase or decrease the number of conflict markers.
+///
+/// The FileFixer may also move the fixed files or create new files next to
+/// them. Moved and created files must not replace existing paths.
 pub async fn fix_files(
     root_commits: Vec<CommitId>,
     matcher: &dyn Matcher,
@@ -270,10 +420,13 @@ pub async fn fix_files(
     let fixed_file_ids = file_fixer.fix_files(repo_mut.store().as_ref(), &unique_files_to_fix)?;
     tracing::debug!(?fixed_file_ids, "file fixer fixed these files:");
 
-    // Substitute the fixed file IDs into all of the affected commits. Currently,
-    // fixes cannot delete or rename files, change the executable bit, or modify
-    // other parts of the commit like the description.
-    repo_mut.transform_descendants(root_commits, async |rewriter| {
+    // Substitute the fixed file IDs into all of the affected commits, and apply
+    // any renames and created files. Renames and created files are only applied
+    // to files without conflicts. Currently, fixes cannot delete files, change
+    // the executable bit, or modify other parts of the commit like the
+    // description.
+    let mut path_conflict = None;
+    let result = repo_mut.transform_descendants(root_commits, async |rewriter| {
         // TODO: Build the trees in parallel before `transform_descendants()` and only
         // keep the tree IDs in memory, so we can pass them to the rewriter.
         let old_commit_id = rewriter.old_commit().id().clone();
@@ -281,6 +434,9 @@ pub async fn fix_files(
         let old_tree = rewriter.old_commit().tree();
         let mut tree_builder = MergedTreeBuilder::new(old_tree.clone());
         let mut has_changes = false;
+        // Paths written by renames and created files, which must not overlap.
+        let mut added_paths: HashSet<RepoPathBuf> = HashSet::new();
+        let mut added_values = vec![];
         for repo_path in repo_paths {
             let old_value = old_tree.path_value_async(repo_path).await?;
             let new_value = old_value.map(|old_term| {
@@ -294,7 +450,9 @@ pub async fn fix_files(
                         file_id: id.clone(),
                         repo_path: repo_path.clone(),
                     };
-                    if let Some(new_id) = fixed_file_ids.get(&file_to_fix) {
+                    if let Some(fixed_file) = fixed_file_ids.get(&file_to_fix)
+                        && let Some(new_id) = &fixed_file.file_id
+                    {
                         return Some(TreeValue::File {
                             id: new_id.clone(),
                             executable: *executable,
@@ -304,11 +462,48 @@ pub async fn fix_files(
                 }
                 old_term.clone()
             });
+            let fixed_file = old_value.as_normal().and_then(|old_term| match old_term {
+                TreeValue::File { id, .. } => fixed_file_ids.get(&FileToFix {
+                    file_id: id.clone(),
+                    repo_path: repo_path.clone(),
+                }),
+                _ => None,
+            });
+            let mut is_moved = false;
+            if let Some(fixed_file) = fixed_file {
+                if let Some(new_path) = &fixed_file.new_path
+                    && new_path != repo_path
+                {
+                    added_values.push((new_path.clone(), new_value.clone()));
+                    is_moved = true;
+                }
+                for (path, id) in &fixed_file.new_files {
+                    let value = TreeValue::File {
+                        id: id.clone(),
+                        executable: false,
+                        copy_id: CopyId::placeholder(),
+                    };
+                    added_values.push((path.clone(), Merge::normal(value)));
+                }
+            }
+            let new_value = if is_moved { Merge::absent() } else { new_value };
             if new_value != old_value {
                 tree_builder.set_or_remove(repo_path.clone(), new_value);
                 has_changes = true;
             }
         }
+        for (path, value) in added_values {
+            if !added_paths.insert(path.clone())
+                || old_tree.path_value_async(&path).await?.is_present()
+            {
+                path_conflict = Some((old_commit_id, path));
+                return Err(BackendError::Other(
+                    "fixed files conflict with existing paths".into(),
+                ));
+            }
+            tree_builder.set_or_remove(path, value);
+            has_changes = true;
+        }
         summary.num_checked_commits += 1;
         if has_changes {
             summary.num_fixed_commits += 1;
@@ -325,7 +520,11 @@ pub async fn fix_files(
                 .insert(old_commit_id, new_commit.id().clone());
         }
         Ok(())
-    })?;
+    });
+    if let Some((commit_id, path)) = path_conflict {
+        return Err(FixError::PathConflict { commit_id, path });
+    }
+    result?;
 
     tracing::debug!(?summary);
     Ok(summary)
@@ -0,0 +1,294 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verifies the integrity of a repo.
+//!
+//! All commits reachable from the index heads and from the references in the
+//! view are read from the backend along with their trees and file contents.
+//! Trees and files are re-hashed if the backend can compute their IDs, and
+//! every referenced commit is checked to be present in the index.
+
+use std::collections::HashSet;
+
+use pollster::FutureExt as _;
+use thiserror::Error;
+use tokio::io::AsyncReadExt as _;
+
+use crate::backend::Backend;
+use crate::backend::BackendError;
+use crate::backend::CommitId;
+use crate::backend::FileId;
+use crate::backend::TreeId;
+use crate::backend::TreeValue;
+use crate::index::Index;
+use crate::index::IndexError;
+use crate::object_id::ObjectId as _;
+use crate::repo::ReadonlyRepo;
+use crate::repo::Repo as _;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+
+/// Problem found by [`check_repo()`].
+#[derive(Debug, Error)]
+pub enum FsckIssue {
+    /// A referenced commit is missing from the index.
+    #[error("Commit {commit_id} referenced by {referrer} is not indexed")]
+    UnindexedCommit {
+        /// The unindexed commit.
+        commit_id: CommitId,
+        /// Description of what references the commit.
+        referrer: String,
+    },
+    /// An object couldn't be read from the backend.
+    #[error("Failed to read {object_type} {id}")]
+    UnreadableObject {
+        /// Type of the object, e.g. "commit".
+        object_type: &'static str,
+        /// Hex ID of the object.
+        id: String,
+        /// The underlying error.
+        #[source]
+        source: BackendError,
+    },
+    /// An object's contents don't hash to its ID.
+    #[error("The contents of {object_type} {id} hash to {actual_id}")]
+    HashMismatch {
+        /// Type of the object, e.g. "tree".
+        object_type: &'static str,
+        /// Hex ID the object is stored under.
+        id: String,
+        /// Hex ID computed from the contents.
+        actual_id: String,
+    },
+}
+
+/// Result of [`check_repo()`].
+#[derive(Debug, Default)]
+pub struct FsckReport {
+    /// Number of commits checked.
+    pub num_commits: usize,
+    /// Number of trees checked.
+    pub num_trees: usize,
+    /// Number of files and symlinks checked.
+    pub num_files: usize,
+    /// Problems found.
+    pub issues: Vec<FsckIssue>,
+}
+
+impl FsckReport {
+    /// Returns true if any problem was found.
+    pub fn has_issues(&self) -> bool {
+        !self.issues.is_empty()
+    }
+
+    /// Returns true if any of the problems can be fixed by rebuilding the
+    /// index.
+    pub fn has_index_issues(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| matches!(issue, FsckIssue::UnindexedCommit { .. }))
+    }
+}
+
+/// Checks the objects reachable from the `repo` and the `repo`'s index.
+///
+/// Problems with the objects are collected in the returned report. An error
+/// is returned only if the index can't be queried.
+pub fn check_repo(repo: &ReadonlyRepo) -> Result<FsckReport, IndexError> {
+    let mut checker = Checker {
+        backend: repo.store().backend(),
+        index: repo.index(),
+        report: FsckReport::default(),
+        visited_commits: HashSet::new(),
+        visited_trees: HashSet::new(),
+        visited_files: HashSet::new(),
+    };
+
+    let view = repo.view();
+    let mut references: Vec<(CommitId, String)> = vec![];
+    for id in view.heads() {
+        references.push((id.clone(), "a head".to_owned()));
+    }
+    for (name, id) in view.wc_commit_ids() {
+        let referrer = format!("the working copy of workspace {}", name.as_symbol());
+        references.push((id.clone(), referrer));
+    }
+    for (name, target) in view.local_bookmarks() {
+        for id in target.added_ids() {
+            references.push((id.clone(), format!("bookmark {}", name.as_symbol())));
+        }
+    }
+    for (symbol, remote_ref) in view.all_remote_bookmarks() {
+        for id in remote_ref.target.added_ids() {
+            references.push((id.clone(), format!("remote bookmark {symbol}")));
+        }
+    }
+    for (name, target) in view.local_tags() {
+        for id in target.added_ids() {
+            references.push((id.clone(), format!("tag {}", name.as_symbol())));
+        }
+    }
+    for (name, target) in view.git_refs() {
+        for id in target.added_ids() {
+            references.push((id.clone(), format!("Git ref {}", name.as_str())));
+        }
+    }
+
+    let mut work_ids = vec![];
+    for (id, referrer) in references {
+        checker.check_indexed(&id, referrer)?;
+        work_ids.push(id);
+    }
+    match checker.index.all_heads_for_gc() {
+        Ok(heads) => work_ids.extend(heads),
+        Err(IndexError::AllHeadsForGcUnsupported) => {}
+        Err(err) => return Err(err),
+    }
+    while let Some(id) = work_ids.pop() {
+        work_ids.extend(checker.check_commit(&id)?);
+    }
+    Ok(checker.report)
+}
+
+struct Checker<'a> {
+    backend: &'a dyn Backend,
+    index: &'a dyn Index,
+    report: FsckReport,
+    visited_commits: HashSet<CommitId>,
+    visited_trees: HashSet<TreeId>,
+    visited_files: HashSet<FileId>,
+}
+
+impl Checker<'_> {
+    fn check_indexed(&mut self, id: &CommitId, referrer: String) -> Result<(), IndexError> {
+        if !self.index.has_id(id)? {
+            self.report.issues.push(FsckIssue::UnindexedCommit {
+                commit_id: id.clone(),
+                referrer,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks the commit and its trees. Returns the parents to check next.
+    fn check_commit(&mut self, id: &CommitId) -> Result<Vec<CommitId>, IndexError> {
+        if !self.visited_commits.insert(id.clone()) {
+            return Ok(vec![]);
+        }
+        self.report.num_commits += 1;
+        let commit = match self.backend.read_commit(id).block_on() {
+            Ok(commit) => commit,
+            Err(source) => {
+                self.report.issues.push(FsckIssue::UnreadableObject {
+                    object_type: "commit",
+                    id: id.hex(),
+                    source,
+                });
+                return Ok(vec![]);
+            }
+        };
+        for parent_id in &commit.parents {
+            self.check_indexed(parent_id, format!("commit {id}"))?;
+        }
+        for tree_id in &commit.root_tree {
+            self.check_tree(RepoPath::root(), tree_id);
+        }
+        Ok(commit.parents)
+    }
+
+    fn check_tree(&mut self, path: &RepoPath, id: &TreeId) {
+        if !self.visited_trees.insert(id.clone()) {
+            return;
+        }
+        self.report.num_trees += 1;
+        let tree = match self.backend.read_tree(path, id).block_on() {
+            Ok(tree) => tree,
+            Err(source) => {
+                self.report.issues.push(FsckIssue::UnreadableObject {
+                    object_type: "tree",
+                    id: id.hex(),
+                    source,
+                });
+                return;
+            }
+        };
+        if let Some(actual_id) = self.backend.compute_tree_id(path, &tree)
+            && actual_id != *id
+        {
+            self.report.issues.push(FsckIssue::HashMismatch {
+                object_type: "tree",
+                id: id.hex(),
+                actual_id: actual_id.hex(),
+            });
+        }
+        for entry in tree.entries() {
+            let entry_path = path.join(entry.name());
+            match entry.value() {
+                TreeValue::File { id, .. } => self.check_file(&entry_path, id),
+                TreeValue::Symlink(id) => {
+                    self.report.num_files += 1;
+                    if let Err(source) = self.backend.read_symlink(&entry_path, id).block_on() {
+                        self.report.issues.push(FsckIssue::UnreadableObject {
+                            object_type: "symlink",
+                            id: id.hex(),
+                            source,
+                        });
+                    }
+                }
+                TreeValue::Tree(id) => self.check_tree(&entry_path, id),
+                TreeValue::GitSubmodule(_) => {}
+            }
+        }
+    }
+
+    fn check_file(&mut self, path: &RepoPathBuf, id: &FileId) {
+        if !self.visited_files.insert(id.clone()) {
+            return;
+        }
+        self.report.num_files += 1;
+        let read_contents = async {
+            let mut reader = self.backend.read_file(path, id).await?;
+            let mut contents = vec![];
+            reader
+                .read_to_end(&mut contents)
+                .await
+                .map_err(|err| BackendError::ReadFile {
+                    path: path.clone(),
+                    id: id.clone(),
+                    source: err.into(),
+                })?;
+            Ok(contents)
+        };
+        let contents = match read_contents.block_on() {
+            Ok(contents) => contents,
+            Err(source) => {
+                self.report.issues.push(FsckIssue::UnreadableObject {
+                    object_type: "file",
+                    id: id.hex(),
+                    source,
+                });
+                return;
+            }
+        };
+        if let Some(actual_id) = self.backend.compute_file_id(path, &contents)
+            && actual_id != *id
+        {
+            self.report.issues.push(FsckIssue::HashMismatch {
+                object_type: "file",
+                id: id.hex(),
+                actual_id: actual_id.hex(),
+            });
+        }
+    }
+}
@@ -25,6 +25,7 @@
 use std::path::PathBuf;
 
 use crate::config::ConfigGetError;
+use crate::config::ConfigGetResultExt as _;
 use crate::settings::UserSettings;
 
 /// Config for Watchman filesystem monitor (<https://facebook.github.io/watchman/>).
@@ -34,12 +35,26 @@ pub struct WatchmanConfig {
     pub register_trigger: bool,
 }
 
+/// Config for the filesystem monitor built into `jj`.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct BuiltinFsmonitorConfig {
+    /// Whether to start the monitor daemon automatically if it isn't running.
+    pub auto_start: bool,
+    /// Command that runs the monitor daemon in the foreground, to which the
+    /// `--working-copy` and `--state-dir` arguments are appended. The daemon
+    /// isn't started automatically if this is unset.
+    pub daemon_command: Option<Vec<String>>,
+}
+
 /// The recognized kinds of filesystem monitors.
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub enum FsmonitorSettings {
     /// The Watchman filesystem monitor (<https://facebook.github.io/watchman/>).
     Watchman(WatchmanConfig),
 
+    /// The filesystem monitor daemon built into `jj`.
+    Builtin(BuiltinFsmonitorConfig),
+
     /// Only used in tests.
     Test {
         /// The set of changed files to pretend that the filesystem monitor is
@@ -63,6 +78,12 @@ impl FsmonitorSettings {
                 register_trigger: settings
                     .get_bool("fsmonitor.watchman.register-snapshot-trigger")?,
             })),
+            "builtin" => Ok(Self::Builtin(BuiltinFsmonitorConfig {
+                auto_start: settings.get_bool("fsmonitor.builtin.auto-start")?,
+                daemon_command: settings
+                    .get("fsmonitor.builtin.daemon-command")
+                    .optional()?,
+            })),
             "test" => Err(ConfigGetError::Type {
                 name: name.to_owned(),
                 error: "Cannot use test fsmonitor in real repository".into(),
@@ -333,3 +354,485 @@ pub mod watchman {
         }
     }
 }
+
+/// Filesystem monitor integration using a daemon built into `jj`.
+///
+/// The daemon watches the working copy using the native notification API of
+/// the platform (inotify, FSEvents, or ReadDirectoryChangesW), and appends the
+/// changed paths to a journal file in the working-copy state directory. A
+/// [`Clock`] records how much of the journal has been consumed, so subsequent
+/// queries only have to read the newly appended paths.
+#[cfg(feature = "builtin-fsmonitor")]
+pub mod builtin {
+    use std::fs;
+    use std::fs::File;
+    use std::fs::TryLockError;
+    use std::io;
+    use std::io::Read as _;
+    use std::io::Seek as _;
+    use std::io::SeekFrom;
+    use std::io::Write as _;
+    use std::ops::ControlFlow;
+    use std::path::Component;
+    use std::path::Path;
+    use std::path::PathBuf;
+    use std::process;
+    use std::process::Stdio;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+    use std::time::Instant;
+    use std::time::SystemTime;
+
+    use notify::EventKind;
+    use notify::RecursiveMode;
+    use notify::Watcher as _;
+    use notify::event::ModifyKind;
+    use tempfile::NamedTempFile;
+    use thiserror::Error;
+    use tracing::info;
+    use tracing::instrument;
+
+    use crate::file_util::IoResultExt as _;
+    use crate::file_util::PathError;
+    use crate::file_util::path_from_bytes;
+    use crate::file_util::path_to_bytes;
+    use crate::file_util::persist_temp_file;
+    use crate::file_util::slash_path;
+
+    const JOURNAL_FILE_NAME: &str = "journal";
+    const JOURNAL_HEADER_PREFIX: &[u8] = b"jj-fsmonitor-journal 1 ";
+    const LOCK_FILE_NAME: &str = "daemon.lock";
+    const COOKIES_DIR_NAME: &str = "cookies";
+    const STOP_COOKIE_NAME: &str = "stop";
+    /// The journal is started over (forcing clients to crawl the working copy
+    /// once) when it grows larger than this.
+    const MAX_JOURNAL_SIZE: u64 = 16 << 20;
+    /// The daemon exits if it hasn't been queried for this long.
+    const IDLE_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+    const SYNC_TIMEOUT: Duration = Duration::from_secs(2);
+    const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Represents an instance in time from the perspective of the filesystem
+    /// monitor.
+    ///
+    /// A clock is only meaningful to the same run of the daemon. If the daemon
+    /// was restarted, queries with an older clock will report that the whole
+    /// working copy has to be crawled.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct Clock {
+        instance_id: String,
+        journal_offset: u64,
+    }
+
+    impl From<crate::protos::local_working_copy::BuiltinFsmonitorClock> for Clock {
+        fn from(clock: crate::protos::local_working_copy::BuiltinFsmonitorClock) -> Self {
+            Self {
+                instance_id: clock.instance_id,
+                journal_offset: clock.journal_offset,
+            }
+        }
+    }
+
+    impl From<Clock> for crate::protos::local_working_copy::BuiltinFsmonitorClock {
+        fn from(clock: Clock) -> Self {
+            Self {
+                instance_id: clock.instance_id,
+                journal_offset: clock.journal_offset,
+            }
+        }
+    }
+
+    #[expect(missing_docs)]
+    #[derive(Debug, Error)]
+    pub enum Error {
+        #[error("The filesystem monitor daemon is not running")]
+        DaemonNotRunning,
+
+        #[error("Failed to start the filesystem monitor daemon")]
+        SpawnDaemon(#[source] io::Error),
+
+        #[error("Timed out waiting for the filesystem monitor daemon")]
+        SyncTimeout,
+
+        #[error("The filesystem monitor journal is corrupt")]
+        CorruptJournal,
+
+        #[error("Failed to watch the working copy")]
+        Watch(#[source] notify::Error),
+
+        #[error(transparent)]
+        Io(#[from] PathError),
+    }
+
+    /// Handle to the state directory shared by the daemon and its clients.
+    #[derive(Clone, Debug)]
+    pub struct Fsmonitor {
+        state_dir: PathBuf,
+    }
+
+    impl Fsmonitor {
+        /// Creates a handle to the monitor whose state is stored in
+        /// `state_dir`.
+        pub fn new(state_dir: PathBuf) -> Self {
+            Self { state_dir }
+        }
+
+        /// Returns whether a daemon is watching the working copy.
+        pub fn is_daemon_running(&self) -> Result<bool, Error> {
+            let lock_path = self.state_dir.join(LOCK_FILE_NAME);
+            let lock_file = match File::open(&lock_path) {
+                Ok(file) => file,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+                Err(err) => return Err(err).context(&lock_path)?,
+            };
+            match lock_file.try_lock() {
+                Ok(()) => Ok(false),
+                Err(TryLockError::WouldBlock) => Ok(true),
+                Err(TryLockError::Error(err)) => Err(err).context(&lock_path)?,
+            }
+        }
+
+        /// Query for changed files since the previous point in time.
+        ///
+        /// The returned list of paths is relative to the working copy root.
+        /// A path may refer to a directory, in which case everything below it
+        /// may have changed. If the list is `None`, then the caller must
+        /// crawl the entire working copy themselves.
+        #[instrument(skip(self))]
+        pub fn query_changed_files(
+            &self,
+            previous_clock: Option<Clock>,
+        ) -> Result<(Clock, Option<Vec<PathBuf>>), Error> {
+            if !self.is_daemon_running()? {
+                return Err(Error::DaemonNotRunning);
+            }
+            // Make sure events for the changes made so far are in the journal.
+            self.sync()?;
+
+            let journal_path = self.state_dir.join(JOURNAL_FILE_NAME);
+            let mut journal = File::open(&journal_path).context(&journal_path)?;
+            let instance_id = read_journal_header(&mut journal)
+                .context(&journal_path)?
+                .ok_or(Error::CorruptJournal)?;
+            let header_len = journal.stream_position().context(&journal_path)?;
+            let previous_offset = previous_clock
+                .filter(|clock| clock.instance_id == instance_id)
+                .map(|clock| clock.journal_offset)
+                .filter(|&offset| offset >= header_len);
+            let start_offset = previous_offset.unwrap_or(header_len);
+            journal
+                .seek(SeekFrom::Start(start_offset))
+                .context(&journal_path)?;
+            let mut data = vec![];
+            journal.read_to_end(&mut data).context(&journal_path)?;
+            // The last entry may be still being written.
+            let complete_len = data
+                .iter()
+                .rposition(|&b| b == b'\0')
+                .map_or(0, |pos| pos + 1);
+            data.truncate(complete_len);
+            let clock = Clock {
+                instance_id,
+                journal_offset: start_offset + u64::try_from(complete_len).unwrap(),
+            };
+            if previous_offset.is_none() {
+                info!("Filesystem monitor has no history for the previous clock");
+                return Ok((clock, None));
+            }
+
+            let mut paths: Vec<PathBuf> = data
+                .split(|&b| b == b'\0')
+                .filter(|entry| !entry.is_empty())
+                .filter_map(|entry| path_from_bytes(entry).ok())
+                .map(|path| path.to_owned())
+                .collect();
+            paths.sort_unstable();
+            paths.dedup();
+            Ok((clock, Some(paths)))
+        }
+
+        /// Starts the daemon in the background by running `daemon_command`
+        /// with the working copy and state directory arguments appended.
+        #[instrument(skip(self))]
+        pub fn spawn_daemon(
+            &self,
+            daemon_command: &[String],
+            working_copy_path: &Path,
+        ) -> Result<(), Error> {
+            info!("Starting filesystem monitor daemon...");
+            let Some((program, args)) = daemon_command.split_first() else {
+                return Err(Error::SpawnDaemon(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "The daemon command is empty",
+                )));
+            };
+            let mut cmd = process::Command::new(program);
+            cmd.args(args)
+                .arg("--working-copy")
+                .arg(working_copy_path)
+                .arg("--state-dir")
+                .arg(&self.state_dir)
+                .current_dir(working_copy_path)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null());
+            // Detach the daemon so it won't be killed by Ctrl-C in the terminal.
+            #[cfg(unix)]
+            {
+                use std::os::unix::process::CommandExt as _;
+                cmd.process_group(0);
+            }
+            #[cfg(windows)]
+            {
+                use std::os::windows::process::CommandExt as _;
+                const DETACHED_PROCESS: u32 = 0x00000008;
+                const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
+                cmd.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+            }
+            cmd.spawn().map_err(Error::SpawnDaemon)?;
+            Ok(())
+        }
+
+        /// Asks the running daemon to exit. Returns false if no daemon was
+        /// running.
+        pub fn stop_daemon(&self) -> Result<bool, Error> {
+            if !self.is_daemon_running()? {
+                return Ok(false);
+            }
+            let cookie_path = self.state_dir.join(COOKIES_DIR_NAME).join(STOP_COOKIE_NAME);
+            File::create(&cookie_path).context(&cookie_path)?;
+            Ok(true)
+        }
+
+        /// Waits until the daemon has processed the events that precede a
+        /// newly created cookie file.
+        fn sync(&self) -> Result<(), Error> {
+            let nanos = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos();
+            let cookie_path = self
+                .state_dir
+                .join(COOKIES_DIR_NAME)
+                .join(format!("{}-{nanos}", process::id()));
+            File::create(&cookie_path).context(&cookie_path)?;
+            let deadline = Instant::now() + SYNC_TIMEOUT;
+            while cookie_path.try_exists().context(&cookie_path)? {
+                if Instant::now() > deadline {
+                    fs::remove_file(&cookie_path).ok();
+                    return Err(Error::SyncTimeout);
+                }
+                thread::sleep(Duration::from_millis(1));
+            }
+            Ok(())
+        }
+    }
+
+    fn read_journal_header(journal: &mut File) -> io::Result<Option<String>> {
+        let mut buf = [0; 128];
+        let len = journal.read(&mut buf)?;
+        let Some(line_len) = buf[..len].iter().position(|&b| b == b'\n') else {
+            return Ok(None);
+        };
+        let Some(instance_id) = buf[..line_len].strip_prefix(JOURNAL_HEADER_PREFIX) else {
+            return Ok(None);
+        };
+        let Ok(instance_id) = str::from_utf8(instance_id) else {
+            return Ok(None);
+        };
+        journal.seek(SeekFrom::Start(u64::try_from(line_len + 1).unwrap()))?;
+        Ok(Some(instance_id.to_owned()))
+    }
+
+    /// Watches the working copy and records changes until the daemon is
+    /// stopped, the state directory is deleted, or it has been idle for too
+    /// long.
+    ///
+    /// Returns immediately if another daemon is already running for the same
+    /// state directory.
+    #[instrument]
+    pub fn run_daemon(working_copy_path: &Path, state_dir: &Path) -> Result<(), Error> {
+        let cookies_dir = state_dir.join(COOKIES_DIR_NAME);
+        fs::create_dir_all(&cookies_dir).context(&cookies_dir)?;
+        let lock_path = state_dir.join(LOCK_FILE_NAME);
+        let lock_file = File::create(&lock_path).context(&lock_path)?;
+        match lock_file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                info!("Filesystem monitor daemon is already running");
+                return Ok(());
+            }
+            Err(TryLockError::Error(err)) => return Err(err).context(&lock_path)?,
+        }
+        // Cookies left by clients that gave up waiting
+        for entry in fs::read_dir(&cookies_dir).context(&cookies_dir)? {
+            let path = entry.context(&cookies_dir)?.path();
+            fs::remove_file(&path).context(&path)?;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let watcher = notify::recommended_watcher(tx).map_err(Error::Watch)?;
+        let mut daemon = Daemon {
+            root: dunce::canonicalize(working_copy_path).context(working_copy_path)?,
+            state_dir: state_dir.to_owned(),
+            cookies_dir: dunce::canonicalize(&cookies_dir).context(&cookies_dir)?,
+            journal: None,
+            journal_len: 0,
+            watcher,
+            last_activity: Instant::now(),
+        };
+        daemon.watch_working_copy()?;
+        daemon
+            .watcher
+            .watch(&daemon.cookies_dir, RecursiveMode::NonRecursive)
+            .map_err(Error::Watch)?;
+        // Start the journal after setting up the watches so no changes will
+        // be missed.
+        daemon.start_journal()?;
+        info!("Filesystem monitor daemon started");
+
+        loop {
+            match rx.recv_timeout(POLL_INTERVAL) {
+                Ok(Ok(event)) => {
+                    if daemon.handle_event(event)?.is_break() {
+                        break;
+                    }
+                }
+                Ok(Err(err)) => {
+                    tracing::warn!(?err, "Filesystem watcher error");
+                    daemon.start_journal()?;
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+            if !daemon.cookies_dir.exists() || daemon.last_activity.elapsed() > IDLE_TIMEOUT {
+                break;
+            }
+        }
+        info!("Filesystem monitor daemon exiting");
+        fs::remove_file(daemon.cookies_dir.join(STOP_COOKIE_NAME)).ok();
+        Ok(())
+    }
+
+    struct Daemon {
+        root: PathBuf,
+        state_dir: PathBuf,
+        cookies_dir: PathBuf,
+        journal: Option<File>,
+        journal_len: u64,
+        watcher: notify::RecommendedWatcher,
+        last_activity: Instant,
+    }
+
+    impl Daemon {
+        /// Watches the root directory, and recursively each top-level
+        /// directory other than the internal `.jj` and `.git` directories.
+        fn watch_working_copy(&mut self) -> Result<(), Error> {
+            self.watcher
+                .watch(&self.root, RecursiveMode::NonRecursive)
+                .map_err(Error::Watch)?;
+            for entry in fs::read_dir(&self.root).context(&self.root)? {
+                let entry = entry.context(&self.root)?;
+                let path = entry.path();
+                if !is_internal_path(Path::new(&entry.file_name())) && path.is_dir() {
+                    self.watcher
+                        .watch(&path, RecursiveMode::Recursive)
+                        .map_err(Error::Watch)?;
+                }
+            }
+            Ok(())
+        }
+
+        /// Starts a new journal with a fresh instance id. Clients with a clock
+        /// from the previous journal will crawl the working copy.
+        fn start_journal(&mut self) -> Result<(), Error> {
+            let nanos = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos();
+            let instance_id = format!("{}-{nanos}", process::id());
+            let mut temp_file = NamedTempFile::new_in(&self.state_dir).context(&self.state_dir)?;
+            let mut header = JOURNAL_HEADER_PREFIX.to_vec();
+            header.extend(instance_id.as_bytes());
+            header.push(b'\n');
+            temp_file.write_all(&header).context(temp_file.path())?;
+            let journal_path = self.state_dir.join(JOURNAL_FILE_NAME);
+            let journal = persist_temp_file(temp_file, &journal_path).context(&journal_path)?;
+            self.journal = Some(journal);
+            self.journal_len = u64::try_from(header.len()).unwrap();
+            Ok(())
+        }
+
+        fn append_to_journal(&mut self, path: &Path) -> Result<(), Error> {
+            let Ok(bytes) = path_to_bytes(&slash_path(path)).map(|bytes| bytes.to_owned()) else {
+                // Paths that can't be encoded can't be tracked either.
+                return Ok(());
+            };
+            let mut entry = bytes;
+            entry.push(b'\0');
+            let journal_path = self.state_dir.join(JOURNAL_FILE_NAME);
+            let journal = self.journal.as_mut().expect("journal should have started");
+            journal.write_all(&entry).context(&journal_path)?;
+            self.journal_len += u64::try_from(entry.len()).unwrap();
+            Ok(())
+        }
+
+        fn handle_event(&mut self, event: notify::Event) -> Result<ControlFlow<()>, Error> {
+            if event.need_rescan() {
+                info!("Filesystem watcher lost events, starting new journal");
+                self.start_journal()?;
+                return Ok(ControlFlow::Continue(()));
+            }
+            if matches!(event.kind, EventKind::Access(_)) {
+                return Ok(ControlFlow::Continue(()));
+            }
+            for path in &event.paths {
+                if let Ok(cookie_name) = path.strip_prefix(&self.cookies_dir) {
+                    if matches!(event.kind, EventKind::Create(_))
+                        && !cookie_name.as_os_str().is_empty()
+                    {
+                        self.last_activity = Instant::now();
+                        if cookie_name == Path::new(STOP_COOKIE_NAME) {
+                            return Ok(ControlFlow::Break(()));
+                        }
+                        // The journal is written unbuffered, so the events that
+                        // precede the cookie are already recorded.
+                        fs::remove_file(path).ok();
+                    }
+                    continue;
+                }
+                let Ok(relative_path) = path.strip_prefix(&self.root) else {
+                    continue;
+                };
+                if relative_path.as_os_str().is_empty() || is_internal_path(relative_path) {
+                    continue;
+                }
+                let is_top_level = relative_path.components().count() == 1;
+                let may_be_new_dir = matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))
+                );
+                if is_top_level && may_be_new_dir && path.is_dir() {
+                    self.watcher
+                        .watch(path, RecursiveMode::Recursive)
+                        .map_err(Error::Watch)?;
+                }
+                self.append_to_journal(relative_path)?;
+            }
+            if self.journal_len > MAX_JOURNAL_SIZE {
+                self.start_journal()?;
+            }
+            Ok(ControlFlow::Continue(()))
+        }
+    }
+
+    /// Returns true if the path is inside the internal `.jj` or `.git`
+    /// directories.
+    fn is_internal_path(relative_path: &Path) -> bool {
+        match relative_path.components().next() {
+            Some(Component::Normal(name)) => name == ".jj" || name == ".git",
+            _ => false,
+        }
+    }
+}
@@ -37,12 +37,15 @@ use thiserror::Error;
 use crate::backend::BackendError;
 use crate::backend::BackendResult;
 use crate::backend::CommitId;
+use crate::backend::Signature;
 use crate::backend::TreeValue;
 use crate::commit::Commit;
 use crate::config::ConfigGetError;
+use crate::config::ConfigGetResultExt as _;
 use crate::file_util::IoResultExt as _;
 use crate::file_util::PathError;
 use crate::git_backend::GitBackend;
+use crate::git_backend::signature_to_git;
 use crate::git_subprocess::GitSubprocessContext;
 use crate::git_subprocess::GitSubprocessError;
 use crate::index::IndexError;
@@ -90,6 +93,9 @@ pub struct GitSettings {
     pub auto_local_bookmark: bool,
     pub abandon_unreachable_commits: bool,
     pub executable_path: PathBuf,
+    pub lfs: bool,
+    pub push_options: Vec<String>,
+    pub credential_helper: Option<String>,
     pub write_change_id_header: bool,
 }
 
@@ -99,6 +105,9 @@ impl GitSettings {
             auto_local_bookmark: settings.get_bool("git.auto-local-bookmark")?,
             abandon_unreachable_commits: settings.get_bool("git.abandon-unreachable-commits")?,
             executable_path: settings.get("git.executable-path")?,
+            lfs: settings.get_bool("git.lfs")?,
+            push_options: settings.get("git.push-options")?,
+            credential_helper: settings.get("git.credential-helper").optional()?,
             write_change_id_header: settings.get("git.write-change-id-header")?,
         })
     }
@@ -107,6 +116,9 @@ impl GitSettings {
         GitSubprocessOptions {
             executable_path: self.executable_path.clone(),
             environment: HashMap::new(),
+            lfs: self.lfs,
+            push_options: self.push_options.clone(),
+            credential_helper: self.credential_helper.clone(),
         }
     }
 }
@@ -120,6 +132,14 @@ pub struct GitSubprocessOptions {
     /// Setting per-subcommand environment variables avoids the need for unsafe
     /// code and process-wide state.
     pub environment: HashMap<OsString, OsString>,
+    /// Whether to transfer Git LFS objects when fetching and pushing.
+    pub lfs: bool,
+    /// Options to transmit to the server when pushing, like `git push
+    /// --push-option`.
+    pub push_options: Vec<String>,
+    /// Credential helper to consult in addition to the ones configured in
+    /// Git, like `git -c credential.helper=<helper>`.
+    pub credential_helper: Option<String>,
 }
 
 impl GitSubprocessOptions {
@@ -127,6 +147,9 @@ impl GitSubprocessOptions {
         Ok(Self {
             executable_path: settings.get("git.executable-path")?,
             environment: HashMap::new(),
+            lfs: settings.get_bool("git.lfs")?,
+            push_options: settings.get("git.push-options")?,
+            credential_helper: settings.get("git.credential-helper").optional()?,
         })
     }
 }
@@ -1442,6 +1465,62 @@ fn update_git_head(
     Ok(())
 }
 
+#[derive(Debug, Error)]
+pub enum GitCreateTagError {
+    #[error("Git tag {} already exists", .0.as_symbol())]
+    AlreadyExists(RefNameBuf),
+    #[error("Failed to create Git tag {}", .0.as_symbol())]
+    Git(
+        RefNameBuf,
+        #[source] Box<dyn std::error::Error + Send + Sync>,
+    ),
+    #[error(transparent)]
+    UnexpectedBackend(#[from] UnexpectedGitBackendError),
+}
+
+/// Creates an annotated tag `name` pointing to the `target` commit.
+///
+/// The tag object is written to the backing Git repo, and `refs/tags/<name>`
+/// is pointed to it. The local tag and the known Git ref are updated in the
+/// view so that the tag won't be exported as a lightweight tag.
+pub fn create_annotated_tag(
+    mut_repo: &mut MutableRepo,
+    name: &RefName,
+    target: &CommitId,
+    tagger: &Signature,
+    message: &str,
+) -> Result<(), GitCreateTagError> {
+    let git_repo = get_git_repo(mut_repo.store())?;
+    let git_ref_name = to_git_ref_name(
+        GitRefKind::Tag,
+        name.to_remote_symbol(REMOTE_NAME_FOR_LOCAL_GIT_REPO),
+    )
+    .ok_or_else(|| GitCreateTagError::Git(name.to_owned(), "Invalid tag name".into()))?;
+    if git_repo
+        .try_find_reference(git_ref_name.as_str())
+        .map_err(|err| GitCreateTagError::Git(name.to_owned(), err.into()))?
+        .is_some()
+    {
+        return Err(GitCreateTagError::AlreadyExists(name.to_owned()));
+    }
+    let tagger = signature_to_git(tagger);
+    let mut time_buf = gix::date::parse::TimeBuf::default();
+    git_repo
+        .tag(
+            name.as_str(),
+            gix::ObjectId::from_bytes_or_panic(target.as_bytes()),
+            gix::object::Kind::Commit,
+            Some(tagger.to_ref(&mut time_buf)),
+            message,
+            gix::refs::transaction::PreviousValue::MustNotExist,
+        )
+        .map_err(|err| GitCreateTagError::Git(name.to_owned(), err.into()))?;
+    let ref_target = RefTarget::normal(target.clone());
+    mut_repo.set_git_ref_target(&git_ref_name, ref_target.clone());
+    mut_repo.set_local_tag_target(name, ref_target);
+    Ok(())
+}
+
 #[derive(Debug, Error)]
 pub enum GitResetHeadError {
     #[error(transparent)]
@@ -1817,6 +1896,12 @@ pub enum GitRemoteManagementError {
     RemoteName(#[from] GitRemoteNameError),
     #[error("Git remote named '{}' has nonstandard configuration", .0.as_symbol())]
     NonstandardConfiguration(RemoteNameBuf),
+    #[error("Git remote named '{}' has no URL {}", .0.as_symbol(), .1)]
+    NoSuchUrl(RemoteNameBuf, String),
+    #[error("Git remote named '{}' already has URL {}", .0.as_symbol(), .1)]
+    UrlAlreadyExists(RemoteNameBuf, String),
+    #[error("Cannot remove the last fetch URL of Git remote named '{}'", .0.as_symbol())]
+    LastFetchUrl(RemoteNameBuf),
     #[error("Error saving Git configuration")]
     GitConfigSaveError(#[source] std::io::Error),
     #[error("Unexpected Git error when managing remotes")]
@@ -1912,6 +1997,59 @@ fn save_remote(
     Ok(())
 }
 
+fn read_remote_urls(config: &gix::config::File, remote_name: &RemoteName) -> GitRemoteUrls {
+    let read = |key: &str| {
+        config
+            .strings_by("remote", Some(remote_name.as_str().into()), key)
+            .unwrap_or_default()
+            .iter()
+            .map(|url| url.to_string())
+            .collect()
+    };
+    GitRemoteUrls {
+        fetch: read("url"),
+        push: read("pushurl"),
+    }
+}
+
+/// Replaces all `url` and `pushurl` values of the remote with the given URLs.
+///
+/// gitoxide only keeps track of one URL of each kind, so this has to be
+/// called after [`save_remote()`] to preserve any additional URLs.
+fn write_remote_urls(
+    config: &mut gix::config::File<'static>,
+    remote_name: &RemoteName,
+    urls: &GitRemoteUrls,
+) -> Result<(), GitRemoteManagementError> {
+    // Keep the layout written by gitoxide if there's nothing to change.
+    if read_remote_urls(config, remote_name) == *urls {
+        return Ok(());
+    }
+    let subsection_name = BStr::new(remote_name.as_str());
+    let section_ids = config
+        .sections_and_ids_by_name("remote")
+        .into_iter()
+        .flatten()
+        .filter(|(section, _)| section.header().subsection_name() == Some(subsection_name))
+        .map(|(_, id)| id)
+        .collect_vec();
+    for id in section_ids {
+        let mut section = config.section_mut_by_id(id).expect("just queried");
+        while section.remove("url").is_some() {}
+        while section.remove("pushurl").is_some() {}
+    }
+    let mut section = config
+        .section_mut_or_create_new("remote", Some(subsection_name))
+        .map_err(GitRemoteManagementError::from_git)?;
+    for (key, urls) in [("url", &urls.fetch), ("pushurl", &urls.push)] {
+        for url in urls {
+            let key = key.try_into().expect("valid key");
+            section.push(key, Some(url.as_str().into()));
+        }
+    }
+    Ok(())
+}
+
 fn git_config_branch_section_ids_by_remote(
     config: &gix::config::File,
     remote_name: &RemoteName,
@@ -1990,6 +2128,7 @@ fn remove_remote_git_config_sections(
         .map(|section| {
             if section.value_names().any(|name| {
                 !name.eq_ignore_ascii_case(b"url")
+                    && !name.eq_ignore_ascii_case(b"pushurl")
                     && !name.eq_ignore_ascii_case(b"fetch")
                     && !name.eq_ignore_ascii_case(b"tagOpt")
             }) {
@@ -2178,7 +2317,9 @@ pub fn rename_remote(
         .expect("default refspec to be valid");
 
     let mut config = git_repo.config_snapshot().clone();
+    let urls = read_remote_urls(&config, old_remote_name);
     save_remote(&mut config, new_remote_name, &mut remote)?;
+    write_remote_urls(&mut config, new_remote_name, &urls)?;
     rename_remote_in_git_branch_config_sections(&mut config, old_remote_name, new_remote_name)?;
     remove_remote_git_config_sections(&mut config, old_remote_name)?;
     save_git_config(&config).map_err(GitRemoteManagementError::GitConfigSaveError)?;
@@ -2270,7 +2411,123 @@ pub fn set_remote_urls(
     }
 
     let mut config = git_repo.config_snapshot().clone();
+    let mut urls = read_remote_urls(&config, remote_name);
+    if let Some(url) = new_url {
+        urls.fetch = vec![url.to_owned()];
+    }
+    if let Some(url) = new_push_url {
+        urls.push = vec![url.to_owned()];
+    }
     save_remote(&mut config, remote_name, &mut remote)?;
+    write_remote_urls(&mut config, remote_name, &urls)?;
+    save_git_config(&config).map_err(GitRemoteManagementError::GitConfigSaveError)?;
+
+    Ok(())
+}
+
+/// URLs configured for a Git remote.
+///
+/// Git fetches from the first fetch URL. It pushes to every push URL, or to
+/// every fetch URL if the remote has no push URLs.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GitRemoteUrls {
+    /// Values of `remote.<name>.url`, in configuration order.
+    pub fetch: Vec<String>,
+    /// Values of `remote.<name>.pushurl`, in configuration order.
+    pub push: Vec<String>,
+}
+
+/// Returns the URLs of the remote as configured, without applying URL rewrite
+/// rules.
+pub fn get_remote_urls(
+    store: &Store,
+    remote_name: &RemoteName,
+) -> Result<GitRemoteUrls, GitRemoteManagementError> {
+    let git_repo = get_git_repo(store)?;
+    if git_repo
+        .try_find_remote_without_url_rewrite(remote_name.as_str())
+        .is_none()
+    {
+        return Err(GitRemoteManagementError::NoSuchRemote(
+            remote_name.to_owned(),
+        ));
+    }
+    Ok(read_remote_urls(&git_repo.config_snapshot(), remote_name))
+}
+
+/// Adds a fetch or push URL to the remote, keeping the existing URLs.
+pub fn add_remote_url(
+    store: &Store,
+    remote_name: &RemoteName,
+    url: &str,
+    direction: gix::remote::Direction,
+) -> Result<(), GitRemoteManagementError> {
+    edit_remote_urls(store, remote_name, direction, |remote_name, urls| {
+        if urls.iter().any(|existing| existing == url) {
+            return Err(GitRemoteManagementError::UrlAlreadyExists(
+                remote_name.to_owned(),
+                url.to_owned(),
+            ));
+        }
+        gix::url::parse(url.into()).map_err(GitRemoteManagementError::from_git)?;
+        urls.push(url.to_owned());
+        Ok(())
+    })
+}
+
+/// Removes a fetch or push URL from the remote.
+///
+/// The last fetch URL cannot be removed.
+pub fn remove_remote_url(
+    store: &Store,
+    remote_name: &RemoteName,
+    url: &str,
+    direction: gix::remote::Direction,
+) -> Result<(), GitRemoteManagementError> {
+    edit_remote_urls(store, remote_name, direction, |remote_name, urls| {
+        let len = urls.len();
+        urls.retain(|existing| existing != url);
+        if urls.len() == len {
+            return Err(GitRemoteManagementError::NoSuchUrl(
+                remote_name.to_owned(),
+                url.to_owned(),
+            ));
+        }
+        if urls.is_empty() && direction == gix::remote::Direction::Fetch {
+            return Err(GitRemoteManagementError::LastFetchUrl(
+                remote_name.to_owned(),
+            ));
+        }
+        Ok(())
+    })
+}
+
+fn edit_remote_urls(
+    store: &Store,
+    remote_name: &RemoteName,
+    direction: gix::remote::Direction,
+    edit: impl FnOnce(&RemoteName, &mut Vec<String>) -> Result<(), GitRemoteManagementError>,
+) -> Result<(), GitRemoteManagementError> {
+    let git_repo = get_git_repo(store)?;
+
+    validate_remote_name(remote_name)?;
+
+    if git_repo
+        .try_find_remote_without_url_rewrite(remote_name.as_str())
+        .is_none()
+    {
+        return Err(GitRemoteManagementError::NoSuchRemote(
+            remote_name.to_owned(),
+        ));
+    }
+
+    let mut config = git_repo.config_snapshot().clone();
+    let mut urls = read_remote_urls(&config, remote_name);
+    match direction {
+        gix::remote::Direction::Fetch => edit(remote_name, &mut urls.fetch)?,
+        gix::remote::Direction::Push => edit(remote_name, &mut urls.push)?,
+    }
+    write_remote_urls(&mut config, remote_name, &urls)?;
     save_git_config(&config).map_err(GitRemoteManagementError::GitConfigSaveError)?;
 
     Ok(())
@@ -2311,6 +2568,10 @@ pub enum GitFetchError {
     RemoteName(#[from] GitRemoteNameError),
     #[error(transparent)]
     Subprocess(#[from] GitSubprocessError),
+    #[error("Failed to read the fetched refs")]
+    ReadRefs(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("Failed to read the shallow commits")]
+    ReadShallowCommits(#[source] Box<dyn std::error::Error + Send + Sync>),
 }
 
 #[derive(Error, Debug)]
@@ -2621,7 +2882,10 @@ pub struct GitFetch<'a> {
     git_repo: Box<gix::Repository>,
     git_ctx: GitSubprocessContext,
     import_options: &'a GitImportOptions,
+    fetch_lfs_objects: bool,
     fetched: Vec<FetchedBranches>,
+    unshallowed_ids: Vec<CommitId>,
+    shallow_boundary_moved: bool,
 }
 
 impl<'a> GitFetch<'a> {
@@ -2632,13 +2896,17 @@ impl<'a> GitFetch<'a> {
     ) -> Result<Self, UnexpectedGitBackendError> {
         let git_backend = get_git_backend(mut_repo.store())?;
         let git_repo = Box::new(git_backend.git_repo());
+        let fetch_lfs_objects = subprocess_options.lfs;
         let git_ctx = GitSubprocessContext::from_git_backend(git_backend, subprocess_options);
         Ok(GitFetch {
             mut_repo,
             git_repo,
             git_ctx,
             import_options,
+            fetch_lfs_objects,
             fetched: vec![],
+            unshallowed_ids: vec![],
+            shallow_boundary_moved: false,
         })
     }
 
@@ -2657,7 +2925,8 @@ impl<'a> GitFetch<'a> {
             negative_refspecs,
         }: ExpandedFetchRefSpecs,
         mut callbacks: RemoteCallbacks,
-        depth: Option<NonZeroU32>,
+        depth: Option<FetchDepth>,
+        filter: Option<&str>,
         fetch_tags_override: Option<FetchTagsOverride>,
     ) -> Result<(), GitFetchError> {
         validate_remote_name(remote_name)?;
@@ -2676,6 +2945,10 @@ impl<'a> GitFetch<'a> {
             return Ok(());
         }
 
+        let shallow_ids_before = self.shallow_commit_ids()?;
+        // git refuses to unshallow a complete repository
+        let depth = depth.filter(|&d| d != FetchDepth::Unshallow || !shallow_ids_before.is_empty());
+
         let mut branches_to_prune = Vec::new();
         // git unfortunately errors out if one of the many refspecs is not found
         //
@@ -2690,6 +2963,7 @@ impl<'a> GitFetch<'a> {
             &negative_refspecs,
             &mut callbacks,
             depth,
+            filter,
             fetch_tags_override,
         )? {
             tracing::debug!(failing_refspec, "failed to fetch ref");
@@ -2707,13 +2981,72 @@ impl<'a> GitFetch<'a> {
         // pruned on fetch
         self.git_ctx.spawn_branch_prune(&branches_to_prune)?;
 
+        let shallow_ids_after = self.shallow_commit_ids()?;
+        if shallow_ids_after != shallow_ids_before {
+            self.unshallowed_ids.extend(
+                shallow_ids_before
+                    .into_iter()
+                    .filter(|id| !shallow_ids_after.contains(id)),
+            );
+            self.shallow_boundary_moved = true;
+        }
+
+        let bookmark_matcher = bookmark_expr.to_matcher();
+        if self.fetch_lfs_objects {
+            let refs = self
+                .fetched_remote_refs(remote_name, &bookmark_matcher)
+                .map_err(GitFetchError::ReadRefs)?;
+            self.git_ctx.spawn_lfs_fetch(remote_name, &refs)?;
+        }
+
         self.fetched.push(FetchedBranches {
             remote: remote_name.to_owned(),
-            bookmark_matcher: bookmark_expr.to_matcher(),
+            bookmark_matcher,
         });
         Ok(())
     }
 
+    fn shallow_commit_ids(&self) -> Result<Vec<CommitId>, GitFetchError> {
+        let maybe_oids = self
+            .git_repo
+            .shallow_commits()
+            .map_err(|err| GitFetchError::ReadShallowCommits(err.into()))?;
+        let ids = maybe_oids.map_or_else(Vec::new, |oids| {
+            oids.iter()
+                .map(|oid| CommitId::from_bytes(oid.as_bytes()))
+                .collect()
+        });
+        Ok(ids)
+    }
+
+    /// Returns true if the fetched history has moved the shallow boundary.
+    ///
+    /// Since the parents of the commits at the boundary change, the index
+    /// should be rebuilt after the fetched refs are imported.
+    pub fn has_moved_shallow_boundary(&self) -> bool {
+        self.shallow_boundary_moved
+    }
+
+    /// Returns the remote-tracking refs of the bookmarks matching the
+    /// `bookmark_matcher`.
+    fn fetched_remote_refs(
+        &self,
+        remote_name: &RemoteName,
+        bookmark_matcher: &StringMatcher,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let prefix = format!("refs/remotes/{remote}/", remote = remote_name.as_str());
+        let mut refs = vec![];
+        for git_ref in self.git_repo.references()?.prefixed(prefix.as_str())? {
+            let git_ref = git_ref?;
+            let full_name = str::from_utf8(git_ref.name().as_bstr())?.to_owned();
+            let name = &full_name[prefix.len()..];
+            if name != "HEAD" && bookmark_matcher.is_match(name) {
+                refs.push(full_name);
+            }
+        }
+        Ok(refs)
+    }
+
     /// Queries remote for the default branch name.
     #[tracing::instrument(skip(self))]
     pub fn get_default_branch(
@@ -2742,6 +3075,14 @@ impl<'a> GitFetch<'a> {
     #[tracing::instrument(skip(self))]
     pub fn import_refs(&mut self) -> Result<GitImportStats, GitImportError> {
         tracing::debug!("import_refs");
+        if self.shallow_boundary_moved {
+            let store = self.mut_repo.store();
+            let git_backend = get_git_backend(store)?;
+            git_backend.reload_shallow_root_ids(&self.unshallowed_ids)?;
+            // Cached commits may have stale parents.
+            store.clear_caches();
+            self.unshallowed_ids.clear();
+        }
         let refs_to_import = diff_refs_to_import(
             self.mut_repo.view(),
             &self.git_repo,
@@ -2867,7 +3208,7 @@ pub fn push_updates(
     subprocess_options: GitSubprocessOptions,
     remote_name: &RemoteName,
     updates: &[GitRefUpdate],
-    mut callbacks: RemoteCallbacks,
+    callbacks: RemoteCallbacks,
 ) -> Result<GitPushStats, GitPushError> {
     let mut qualified_remote_refs_expected_locations = HashMap::new();
     let mut refspecs = vec![];
@@ -2889,8 +3230,74 @@ pub fn push_updates(
         }
     }
 
+    let new_commits = updates
+        .iter()
+        .filter_map(|update| update.new_target.as_ref())
+        .collect_vec();
+    push_refspecs(
+        repo,
+        subprocess_options,
+        remote_name,
+        &refspecs,
+        &qualified_remote_refs_expected_locations,
+        &new_commits,
+        callbacks,
+    )
+}
+
+/// Pushes the specified local tags without updating the repo view.
+///
+/// Tags are pushed from the backing Git repo, so annotated tags are pushed as
+/// annotated tags. Tags which already exist on the remote are never
+/// overwritten.
+pub fn push_tags(
+    repo: &dyn Repo,
+    subprocess_options: GitSubprocessOptions,
+    remote_name: &RemoteName,
+    names: &[RefNameBuf],
+    callbacks: RemoteCallbacks,
+) -> Result<GitPushStats, GitPushError> {
+    validate_remote_name(remote_name)?;
+    let qualified_names: Vec<GitRefNameBuf> = names
+        .iter()
+        .map(|name| format!("refs/tags/{name}", name = name.as_str()).into())
+        .collect();
+    // The lease expects the tags not to exist on the remote.
+    let expected_locations = qualified_names
+        .iter()
+        .map(|name| (name.as_ref(), None))
+        .collect();
+    let refspecs = qualified_names
+        .iter()
+        .map(|name| RefSpec::forced(name.as_str(), name.as_str()))
+        .collect_vec();
+    let new_commits = names
+        .iter()
+        .filter_map(|name| repo.view().get_local_tag(name).as_normal())
+        .collect_vec();
+    push_refspecs(
+        repo,
+        subprocess_options,
+        remote_name,
+        &refspecs,
+        &expected_locations,
+        &new_commits,
+        callbacks,
+    )
+}
+
+fn push_refspecs(
+    repo: &dyn Repo,
+    subprocess_options: GitSubprocessOptions,
+    remote_name: &RemoteName,
+    refspecs: &[RefSpec],
+    expected_locations: &HashMap<&GitRefName, Option<&CommitId>>,
+    new_commits: &[&CommitId],
+    mut callbacks: RemoteCallbacks,
+) -> Result<GitPushStats, GitPushError> {
     let git_backend = get_git_backend(repo.store())?;
     let git_repo = git_backend.git_repo();
+    let push_lfs_objects = subprocess_options.lfs;
     let git_ctx = GitSubprocessContext::from_git_backend(git_backend, subprocess_options);
 
     // check the remote exists
@@ -2898,9 +3305,16 @@ pub fn push_updates(
         return Err(GitPushError::NoSuchRemote(remote_name.to_owned()));
     }
 
+    // Like the pre-push hook installed by `git lfs`, upload the objects before
+    // the commits referring to them are pushed.
+    if push_lfs_objects {
+        let commits = new_commits.iter().map(|id| id.hex()).unique().collect_vec();
+        git_ctx.spawn_lfs_push(remote_name, &commits)?;
+    }
+
     let refs_to_push: Vec<RefToPush> = refspecs
         .iter()
-        .map(|full_refspec| RefToPush::new(full_refspec, &qualified_remote_refs_expected_locations))
+        .map(|full_refspec| RefToPush::new(full_refspec, expected_locations))
         .collect();
 
     let mut push_stats = git_ctx.spawn_push(remote_name, &refs_to_push, &mut callbacks)?;
@@ -2955,6 +3369,18 @@ pub struct Progress {
     pub overall: f32,
 }
 
+/// Controls how much history a single `git fetch` operation downloads
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FetchDepth {
+    /// Limit the history to the given number of commits from the tips
+    Depth(NonZeroU32),
+    /// Extend the history of a shallow repository by the given number of
+    /// commits
+    Deepen(NonZeroU32),
+    /// Fetch the complete history of a shallow repository
+    Unshallow,
+}
+
 /// Allows temporarily overriding the behavior of a single `git fetch`
 /// operation as to whether tags are fetched
 #[derive(Copy, Clone, Debug)]
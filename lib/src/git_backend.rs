@@ -20,13 +20,20 @@ use std::fmt::Debug;
 use std::fmt::Error;
 use std::fmt::Formatter;
 use std::fs;
+use std::fs::File;
 use std::io;
+use std::io::BufReader;
 use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek as _;
+use std::io::SeekFrom;
+use std::io::Write as _;
 use std::path::Path;
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::process::Command;
 use std::process::ExitStatus;
+use std::process::Stdio;
 use std::str::Utf8Error;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -36,10 +43,12 @@ use std::time::SystemTime;
 use async_trait::async_trait;
 use futures::stream::BoxStream;
 use gix::bstr::BString;
+use gix::features::zlib;
 use gix::objs::CommitRefIter;
+use gix::objs::Write as _;
 use gix::objs::WriteTo as _;
+use gix::odb::pack;
 use itertools::Itertools as _;
-use once_cell::sync::OnceCell as OnceLock;
 use pollster::FutureExt as _;
 use prost::Message as _;
 use smallvec::SmallVec;
@@ -72,6 +81,7 @@ use crate::backend::make_root_commit;
 use crate::config::ConfigGetError;
 use crate::file_util;
 use crate::file_util::BadPathEncoding;
+use crate::file_util::BlockingAsyncReader;
 use crate::file_util::IoResultExt as _;
 use crate::file_util::PathError;
 use crate::git::GitSettings;
@@ -92,6 +102,12 @@ use crate::stacked_table::TableStoreError;
 
 const HASH_LENGTH: usize = 20;
 const CHANGE_ID_LENGTH: usize = 16;
+/// Files at least this large are spooled to a temporary file when written.
+const FILE_SPOOL_THRESHOLD: usize = 1 << 20;
+
+/// Packed blobs at least this large are decompressed incrementally when read.
+/// Smaller blobs are cheaper to read through the object cache.
+const FILE_STREAM_THRESHOLD: u64 = 1 << 20;
 /// Ref namespace used only for preventing GC.
 const NO_GC_REF_NAMESPACE: &str = "refs/jj/keep/";
 
@@ -99,12 +115,23 @@ pub const JJ_TREES_COMMIT_HEADER: &str = "jj:trees";
 pub const JJ_CONFLICT_LABELS_COMMIT_HEADER: &str = "jj:conflict-labels";
 pub const CHANGE_ID_COMMIT_HEADER: &str = "change-id";
 
+/// Git repository format which the Git backend can't read or write.
+#[derive(Debug, Error)]
+pub enum UnsupportedGitFormatError {
+    #[error("Git object format '{0}' is not supported")]
+    ObjectFormat(BString),
+    #[error("Git ref storage format '{0}' is not supported")]
+    RefStorage(BString),
+}
+
 #[derive(Debug, Error)]
 pub enum GitBackendInitError {
     #[error("Failed to initialize git repository")]
     InitRepository(#[source] gix::init::Error),
     #[error("Failed to open git repository")]
     OpenRepository(#[source] gix::open::Error),
+    #[error(transparent)]
+    UnsupportedFormat(UnsupportedGitFormatError),
     #[error("Failed to encode git repository path")]
     EncodeRepositoryPath(#[source] BadPathEncoding),
     #[error(transparent)]
@@ -123,6 +150,8 @@ impl From<Box<GitBackendInitError>> for BackendInitError {
 pub enum GitBackendLoadError {
     #[error("Failed to open git repository")]
     OpenRepository(#[source] gix::open::Error),
+    #[error(transparent)]
+    UnsupportedFormat(UnsupportedGitFormatError),
     #[error("Failed to decode git repository path")]
     DecodeRepositoryPath(#[source] BadPathEncoding),
     #[error(transparent)]
@@ -160,6 +189,14 @@ pub enum GitGcError {
     GcCommandErrorStatus(ExitStatus),
 }
 
+#[derive(Debug, Error)]
+pub enum GitLazyFetchError {
+    #[error("Failed to run git cat-file command")]
+    CatFileCommand(#[source] std::io::Error),
+    #[error("git cat-file command exited with an error: {status}\n{stderr}")]
+    CatFileCommandErrorStatus { status: ExitStatus, stderr: String },
+}
+
 pub struct GitBackend {
     // While gix::Repository can be created from gix::ThreadSafeRepository, it's
     // cheaper to cache the thread-local instance behind a mutex than creating
@@ -170,7 +207,7 @@ pub struct GitBackend {
     root_commit_id: CommitId,
     root_change_id: ChangeId,
     empty_tree_id: TreeId,
-    shallow_root_ids: OnceLock<Vec<CommitId>>,
+    shallow_root_ids: Mutex<Option<Arc<[CommitId]>>>,
     extra_metadata_store: TableStore,
     cached_extra_metadata: Mutex<Option<Arc<ReadonlyTable>>>,
     git_executable: PathBuf,
@@ -197,7 +234,7 @@ impl GitBackend {
             root_commit_id,
             root_change_id,
             empty_tree_id,
-            shallow_root_ids: OnceLock::new(),
+            shallow_root_ids: Mutex::new(None),
             extra_metadata_store,
             cached_extra_metadata: Mutex::new(None),
             git_executable: git_settings.executable_path,
@@ -264,7 +301,11 @@ impl GitBackend {
             canonical_git_repo_path,
             gix_open_opts_from_settings(settings),
         )
-        .map_err(GitBackendInitError::OpenRepository)?;
+        .map_err(|err| match unsupported_git_format(&err) {
+            Some(format) => GitBackendInitError::UnsupportedFormat(format),
+            None => GitBackendInitError::OpenRepository(err),
+        })?;
+        check_ref_storage(&git_repo).map_err(GitBackendInitError::UnsupportedFormat)?;
         let git_settings =
             GitSettings::from_settings(settings).map_err(GitBackendInitError::Config)?;
         Self::init_with_repo(store_path, git_repo_path, git_repo, git_settings)
@@ -321,7 +362,11 @@ impl GitBackend {
             git_repo_path,
             gix_open_opts_from_settings(settings),
         )
-        .map_err(GitBackendLoadError::OpenRepository)?;
+        .map_err(|err| match unsupported_git_format(&err) {
+            Some(format) => GitBackendLoadError::UnsupportedFormat(format),
+            None => GitBackendLoadError::OpenRepository(err),
+        })?;
+        check_ref_storage(&repo).map_err(GitBackendLoadError::UnsupportedFormat)?;
         let extra_metadata_store = TableStore::load(store_path.join("extra"), HASH_LENGTH);
         let git_settings =
             GitSettings::from_settings(settings).map_err(GitBackendLoadError::Config)?;
@@ -347,23 +392,50 @@ impl GitBackend {
         self.base_repo.work_dir()
     }
 
-    fn shallow_root_ids(&self, git_repo: &gix::Repository) -> BackendResult<&[CommitId]> {
+    fn shallow_root_ids(&self, git_repo: &gix::Repository) -> BackendResult<Arc<[CommitId]>> {
         // The list of shallow roots is cached by gix, but it's still expensive
         // to stat file on every read_object() call. Refreshing shallow roots is
-        // also bad for consistency reasons.
-        self.shallow_root_ids
-            .get_or_try_init(|| {
-                let maybe_oids = git_repo
-                    .shallow_commits()
-                    .map_err(|err| BackendError::Other(err.into()))?;
-                let commit_ids = maybe_oids.map_or(vec![], |oids| {
-                    oids.iter()
-                        .map(|oid| CommitId::from_bytes(oid.as_bytes()))
-                        .collect()
-                });
-                Ok(commit_ids)
-            })
-            .map(AsRef::as_ref)
+        // also bad for consistency reasons, so it's reloaded only by fetch.
+        let mut locked_ids = self.shallow_root_ids.lock().unwrap();
+        if let Some(ids) = &*locked_ids {
+            return Ok(ids.clone());
+        }
+        let ids = load_shallow_root_ids(git_repo)?;
+        *locked_ids = Some(ids.clone());
+        Ok(ids)
+    }
+
+    /// Reloads the list of shallow commits after the Git repo is fetched.
+    ///
+    /// The ancestors of the `unshallowed_ids`, which were previously hidden
+    /// behind the shallow boundary, are imported. Since the parents of these
+    /// commits change, the cached commits and the index have to be rebuilt.
+    pub fn reload_shallow_root_ids(&self, unshallowed_ids: &[CommitId]) -> BackendResult<()> {
+        let locked_repo = self.lock_git_repo();
+        let shallow_root_ids = load_shallow_root_ids(&locked_repo)?;
+        *self.shallow_root_ids.lock().unwrap() = Some(shallow_root_ids.clone());
+
+        let mut parent_ids = HashSet::new();
+        for id in unshallowed_ids {
+            let git_object = locked_repo
+                .find_object(validate_git_object_id(id)?)
+                .map_err(|err| map_not_found_err(err, id))?;
+            let commit = commit_from_git_without_root_parent(id, &git_object, false)?;
+            parent_ids.extend(commit.parents);
+        }
+        if parent_ids.is_empty() {
+            return Ok(());
+        }
+        let (table, table_lock) = self.read_extra_metadata_table_locked()?;
+        let mut mut_table = table.start_mutation();
+        import_extra_metadata_entries_from_heads(
+            &locked_repo,
+            &mut mut_table,
+            &table_lock,
+            &parent_ids.iter().collect(),
+            &shallow_root_ids,
+        )?;
+        self.save_extra_metadata_table(mut_table, &table_lock)
     }
 
     fn cached_extra_metadata_table(&self) -> BackendResult<Arc<ReadonlyTable>> {
@@ -441,20 +513,97 @@ impl GitBackend {
             &mut mut_table,
             &table_lock,
             &head_ids,
-            self.shallow_root_ids(&locked_repo)?,
+            &self.shallow_root_ids(&locked_repo)?,
         )?;
         self.save_extra_metadata_table(mut_table, &table_lock)
     }
 
+    /// Opens the blob `id` if it's stored as a loose object in the repository.
+    fn open_loose_blob(&self, id: &FileId) -> BackendResult<Option<BlobReader>> {
+        validate_git_object_id(id)?;
+        let hex = id.hex();
+        let path = self.base_repo.objects_dir().join(&hex[..2]).join(&hex[2..]);
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(to_read_object_err(err, id)),
+        };
+        BlobReader::from_loose_object(file)
+            .map(Some)
+            .map_err(|err| to_read_object_err(err, id))
+    }
+
+    /// Opens the blob `id` if it's large and stored in a pack as a whole (not
+    /// as a delta).
+    ///
+    /// Deltified objects have to be reconstructed in memory, but Git doesn't
+    /// delta-compress files larger than `core.bigFileThreshold`.
+    fn open_packed_blob(&self, id: &FileId) -> BackendResult<Option<BlobReader>> {
+        let git_blob_id = validate_git_object_id(id)?;
+        let (header, hash_kind) = {
+            let locked_repo = self.lock_git_repo();
+            let header = locked_repo
+                .try_find_header(git_blob_id)
+                .map_err(|err| to_read_object_err(err, id))?;
+            (header, locked_repo.object_hash())
+        };
+        let Some(gix::odb::find::Header::Packed(header)) = header else {
+            return Ok(None);
+        };
+        if header.kind != gix::object::Kind::Blob
+            || header.num_deltas > 0
+            || header.object_size < FILE_STREAM_THRESHOLD
+        {
+            return Ok(None);
+        }
+        let pack_dir = self.base_repo.objects_dir().join("pack");
+        let entries = fs::read_dir(&pack_dir).map_err(|err| to_read_object_err(err, id))?;
+        for entry in entries {
+            let index_path = entry.map_err(|err| to_read_object_err(err, id))?.path();
+            if index_path.extension() != Some(OsStr::new("idx")) {
+                continue;
+            }
+            let index = pack::index::File::at(&index_path, hash_kind)
+                .map_err(|err| to_read_object_err(err, id))?;
+            let Some(entry_index) = index.lookup(git_blob_id) else {
+                continue;
+            };
+            let pack_offset = index.pack_offset_at_index(entry_index);
+            let pack_file = File::open(index_path.with_extension("pack"))
+                .map_err(|err| to_read_object_err(err, id))?;
+            return BlobReader::from_pack_entry(pack_file, pack_offset, hash_kind.len_in_bytes())
+                .map_err(|err| to_read_object_err(err, id));
+        }
+        // The object may be stored in an alternate object database.
+        Ok(None)
+    }
+
     fn read_file_sync(&self, id: &FileId) -> BackendResult<Vec<u8>> {
+        self.read_blob(id)
+    }
+
+    fn read_blob(&self, id: &impl ObjectId) -> BackendResult<Vec<u8>> {
         let git_blob_id = validate_git_object_id(id)?;
         let locked_repo = self.lock_git_repo();
-        let mut blob = locked_repo
-            .find_object(git_blob_id)
-            .map_err(|err| map_not_found_err(err, id))?
-            .try_into_blob()
-            .map_err(|err| to_read_object_err(err, id))?;
-        Ok(blob.take_data())
+        match locked_repo.find_object(git_blob_id) {
+            Ok(object) => {
+                let mut blob = object
+                    .try_into_blob()
+                    .map_err(|err| to_read_object_err(err, id))?;
+                return Ok(blob.take_data());
+            }
+            Err(gix::object::find::existing::Error::NotFound { .. })
+                if is_partial_clone(self.git_repo_path()) => {}
+            Err(err) => return Err(map_not_found_err(err, id)),
+        }
+        // Don't block other readers while the object is fetched.
+        drop(locked_repo);
+        fetch_missing_blob(
+            self.git_executable.as_ref(),
+            self.git_repo_path(),
+            &git_blob_id,
+        )
+        .map_err(|err| to_read_object_err(err, id))
     }
 
     fn new_diff_platform(&self) -> BackendResult<gix::diff::blob::Platform> {
@@ -515,6 +664,37 @@ pub fn canonicalize_git_repo_path(path: &Path) -> io::Result<PathBuf> {
     }
 }
 
+/// Returns the unsupported repository format `err` was caused by, if any.
+///
+/// gix can't open SHA-256 repositories, and the backend assumes 20-byte commit
+/// ids throughout, so those are reported as unsupported instead of as an
+/// invalid config value.
+fn unsupported_git_format(err: &gix::open::Error) -> Option<UnsupportedGitFormatError> {
+    match err {
+        gix::open::Error::Config(gix::config::Error::ConfigTypedString(err))
+            if err.key == "extensions.objectFormat" =>
+        {
+            let format = err.value.clone().unwrap_or_default();
+            Some(UnsupportedGitFormatError::ObjectFormat(format))
+        }
+        _ => None,
+    }
+}
+
+/// Checks that refs are stored as loose refs and packed-refs.
+///
+/// gix doesn't know about the reftable format, and would silently read and
+/// write loose refs which Git ignores.
+fn check_ref_storage(repo: &gix::ThreadSafeRepository) -> Result<(), UnsupportedGitFormatError> {
+    let repo = repo.to_thread_local();
+    match repo.config_snapshot().string("extensions.refStorage") {
+        Some(format) if !format.eq_ignore_ascii_case(b"files") => {
+            Err(UnsupportedGitFormatError::RefStorage(format.into_owned()))
+        }
+        _ => Ok(()),
+    }
+}
+
 fn gix_open_opts_from_settings(settings: &UserSettings) -> gix::open::Options {
     let user_name = settings.user_name();
     let user_email = settings.user_email();
@@ -591,8 +771,8 @@ fn commit_from_git_without_root_parent(
         .unwrap_or_else(|| synthetic_change_id_from_git_commit_id(id));
 
     // shallow commits don't have parents their parents actually fetched, so we
-    // discard them here
-    // TODO: This causes issues when a shallow repository is deepened/unshallowed
+    // discard them here. If the repository is deepened, the commit index has to
+    // be rebuilt.
     let parents = if is_shallow {
         vec![]
     } else {
@@ -707,7 +887,7 @@ fn signature_from_git(signature: gix::actor::SignatureRef) -> Signature {
     }
 }
 
-fn signature_to_git(signature: &Signature) -> gix::actor::Signature {
+pub(crate) fn signature_to_git(signature: &Signature) -> gix::actor::Signature {
     // git does not support empty names or emails
     let name = if !signature.name.is_empty() {
         &signature.name
@@ -892,6 +1072,68 @@ fn run_git_gc(program: &OsStr, git_dir: &Path, keep_newer: SystemTime) -> Result
     Ok(())
 }
 
+fn load_shallow_root_ids(git_repo: &gix::Repository) -> BackendResult<Arc<[CommitId]>> {
+    let maybe_oids = git_repo
+        .shallow_commits()
+        .map_err(|err| BackendError::Other(err.into()))?;
+    let commit_ids = maybe_oids.map_or_else(Default::default, |oids| {
+        oids.iter()
+            .map(|oid| CommitId::from_bytes(oid.as_bytes()))
+            .collect()
+    });
+    Ok(commit_ids)
+}
+
+/// Returns true if objects may be missing from the repo because it was cloned
+/// with a filter, in which case they can be fetched from a promisor remote.
+fn is_partial_clone(git_dir: &Path) -> bool {
+    // The promisor remote is configured by "git fetch", so the config loaded
+    // by the backend may be out of date.
+    let Ok(config) = gix::config::File::from_path_no_includes(
+        git_dir.join("config"),
+        gix::config::Source::Local,
+    ) else {
+        return false;
+    };
+    config.string("extensions.partialClone").is_some()
+        || config
+            .sections_by_name("remote")
+            .into_iter()
+            .flatten()
+            .any(|section| {
+                section
+                    .value("promisor")
+                    .is_some_and(|value| gix::config::Boolean::try_from(value).is_ok_and(|b| b.0))
+            })
+}
+
+/// Reads the blob `id` with `git cat-file`, which fetches it from the promisor
+/// remote if it's missing.
+fn fetch_missing_blob(
+    program: &OsStr,
+    git_dir: &Path,
+    id: &gix::oid,
+) -> Result<Vec<u8>, GitLazyFetchError> {
+    let mut git = Command::new(program);
+    git.arg("--git-dir=.") // turn off discovery
+        .args(["cat-file", "blob"])
+        .arg(id.to_string())
+        .stdin(Stdio::null());
+    // See run_git_gc() for why the directory isn't specified by --git-dir.
+    git.current_dir(git_dir);
+    tracing::info!(?git, "fetching missing object");
+    let output = git.output().map_err(GitLazyFetchError::CatFileCommand)?;
+    if !output.status.success() {
+        return Err(GitLazyFetchError::CatFileCommandErrorStatus {
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr)
+                .trim_end()
+                .to_owned(),
+        });
+    }
+    Ok(output.stdout)
+}
+
 fn validate_git_object_id(id: &impl ObjectId) -> BackendResult<gix::ObjectId> {
     if id.as_bytes().len() != HASH_LENGTH {
         return Err(BackendError::InvalidHashLength {
@@ -904,6 +1146,72 @@ fn validate_git_object_id(id: &impl ObjectId) -> BackendResult<gix::ObjectId> {
     Ok(gix::ObjectId::from_bytes_or_panic(id.as_bytes()))
 }
 
+/// Reader of the contents of a blob object, decompressing it as it's read.
+struct BlobReader {
+    file: BufReader<File>,
+    state: zlib::Decompress,
+}
+
+impl BlobReader {
+    /// Reads the header of a loose object, leaving the reader at the start of
+    /// the blob contents.
+    fn from_loose_object(file: File) -> io::Result<Self> {
+        let mut reader = Self {
+            file: BufReader::new(file),
+            state: zlib::Decompress::new(),
+        };
+        // The header is "blob <size>\0". Read it byte by byte so that none of
+        // the contents are consumed.
+        let mut header = Vec::new();
+        let mut byte = [0];
+        while header.len() < 32 {
+            if reader.read(&mut byte)? == 0 {
+                break;
+            }
+            if byte[0] == 0 {
+                return if header.starts_with(b"blob ") {
+                    Ok(reader)
+                } else {
+                    Err(io::Error::new(io::ErrorKind::InvalidData, "not a blob"))
+                };
+            }
+            header.push(byte[0]);
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid loose object header",
+        ))
+    }
+
+    /// Reads the header of the pack entry at `pack_offset`, leaving the reader
+    /// at the start of the blob contents. Returns `None` if the entry is a
+    /// delta.
+    fn from_pack_entry(
+        mut file: File,
+        pack_offset: u64,
+        hash_len: usize,
+    ) -> io::Result<Option<Self>> {
+        file.seek(SeekFrom::Start(pack_offset))?;
+        let mut file = BufReader::new(file);
+        let entry = pack::data::Entry::from_read(&mut file, pack_offset, hash_len)?;
+        if entry.header != pack::data::entry::Header::Blob {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            file,
+            state: zlib::Decompress::new(),
+        }))
+    }
+}
+
+impl Read for BlobReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Stops at the end of the compressed stream, so the next pack entry
+        // isn't read.
+        zlib::stream::inflate::read(&mut self.file, &mut self.state, buf)
+    }
+}
+
 fn map_not_found_err(err: gix::object::find::existing::Error, id: &impl ObjectId) -> BackendError {
     if matches!(err, gix::object::find::existing::Error::NotFound { .. }) {
         BackendError::ObjectNotFound {
@@ -1010,6 +1318,14 @@ impl Backend for GitBackend {
         _path: &RepoPath,
         id: &FileId,
     ) -> BackendResult<Pin<Box<dyn AsyncRead + Send>>> {
+        // gix inflates whole objects, so loose objects (as written by jj) and
+        // large packed objects are decompressed incrementally instead.
+        if let Some(reader) = self.open_loose_blob(id)? {
+            return Ok(Box::pin(BlockingAsyncReader::new(reader)));
+        }
+        if let Some(reader) = self.open_packed_blob(id)? {
+            return Ok(Box::pin(BlockingAsyncReader::new(reader)));
+        }
         let data = self.read_file_sync(id)?;
         Ok(Box::pin(Cursor::new(data)))
     }
@@ -1019,27 +1335,56 @@ impl Backend for GitBackend {
         _path: &RepoPath,
         contents: &mut (dyn AsyncRead + Send + Unpin),
     ) -> BackendResult<FileId> {
+        let to_write_err = |err: io::Error| BackendError::WriteObject {
+            object_type: "file",
+            source: Box::new(err),
+        };
         let mut bytes = Vec::new();
-        contents.read_to_end(&mut bytes).await.unwrap();
+        (&mut *contents)
+            .take(FILE_SPOOL_THRESHOLD as u64)
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(to_write_err)?;
+        if bytes.len() < FILE_SPOOL_THRESHOLD {
+            let locked_repo = self.lock_git_repo();
+            let oid = locked_repo
+                .write_blob(bytes)
+                .map_err(|err| BackendError::WriteObject {
+                    object_type: "file",
+                    source: Box::new(err),
+                })?;
+            return Ok(FileId::new(oid.as_bytes().to_vec()));
+        }
+
+        // The size of a loose object has to be known before its contents are
+        // written, so large files are spooled to disk instead of memory.
+        let mut spool = tempfile::tempfile_in(self.git_repo_path()).map_err(to_write_err)?;
+        spool.write_all(&bytes).map_err(to_write_err)?;
+        let mut size = bytes.len() as u64;
+        loop {
+            let bytes_read = contents.read(&mut bytes).await.map_err(to_write_err)?;
+            if bytes_read == 0 {
+                break;
+            }
+            spool
+                .write_all(&bytes[..bytes_read])
+                .map_err(to_write_err)?;
+            size += bytes_read as u64;
+        }
+        spool.rewind().map_err(to_write_err)?;
         let locked_repo = self.lock_git_repo();
         let oid = locked_repo
-            .write_blob(bytes)
+            .objects
+            .write_stream(gix::object::Kind::Blob, size, &mut spool)
             .map_err(|err| BackendError::WriteObject {
                 object_type: "file",
-                source: Box::new(err),
+                source: err,
             })?;
         Ok(FileId::new(oid.as_bytes().to_vec()))
     }
 
     async fn read_symlink(&self, _path: &RepoPath, id: &SymlinkId) -> BackendResult<String> {
-        let git_blob_id = validate_git_object_id(id)?;
-        let locked_repo = self.lock_git_repo();
-        let mut blob = locked_repo
-            .find_object(git_blob_id)
-            .map_err(|err| map_not_found_err(err, id))?
-            .try_into_blob()
-            .map_err(|err| to_read_object_err(err, id))?;
-        let target = String::from_utf8(blob.take_data())
+        let target = String::from_utf8(self.read_blob(id)?)
             .map_err(|err| to_invalid_utf8_err(err.utf8_error(), id))?;
         Ok(target)
     }
@@ -1136,53 +1481,10 @@ impl Backend for GitBackend {
     }
 
     async fn write_tree(&self, _path: &RepoPath, contents: &Tree) -> BackendResult<TreeId> {
-        // Tree entries to be written must be sorted by Entry::filename(), which
-        // is slightly different from the order of our backend::Tree.
-        let entries = contents
-            .entries()
-            .map(|entry| {
-                let filename = BString::from(entry.name().as_internal_str());
-                match entry.value() {
-                    TreeValue::File {
-                        id,
-                        executable: false,
-                        copy_id: _, // TODO: Use the value
-                    } => gix::objs::tree::Entry {
-                        mode: gix::object::tree::EntryKind::Blob.into(),
-                        filename,
-                        oid: gix::ObjectId::from_bytes_or_panic(id.as_bytes()),
-                    },
-                    TreeValue::File {
-                        id,
-                        executable: true,
-                        copy_id: _, // TODO: Use the value
-                    } => gix::objs::tree::Entry {
-                        mode: gix::object::tree::EntryKind::BlobExecutable.into(),
-                        filename,
-                        oid: gix::ObjectId::from_bytes_or_panic(id.as_bytes()),
-                    },
-                    TreeValue::Symlink(id) => gix::objs::tree::Entry {
-                        mode: gix::object::tree::EntryKind::Link.into(),
-                        filename,
-                        oid: gix::ObjectId::from_bytes_or_panic(id.as_bytes()),
-                    },
-                    TreeValue::Tree(id) => gix::objs::tree::Entry {
-                        mode: gix::object::tree::EntryKind::Tree.into(),
-                        filename,
-                        oid: gix::ObjectId::from_bytes_or_panic(id.as_bytes()),
-                    },
-                    TreeValue::GitSubmodule(id) => gix::objs::tree::Entry {
-                        mode: gix::object::tree::EntryKind::Commit.into(),
-                        filename,
-                        oid: gix::ObjectId::from_bytes_or_panic(id.as_bytes()),
-                    },
-                }
-            })
-            .sorted_unstable()
-            .collect();
+        let git_tree = tree_to_git(contents);
         let locked_repo = self.lock_git_repo();
         let oid = locked_repo
-            .write_object(gix::objs::Tree { entries })
+            .write_object(git_tree)
             .map_err(|err| BackendError::WriteObject {
                 object_type: "tree",
                 source: Box::new(err),
@@ -1190,6 +1492,21 @@ impl Backend for GitBackend {
         Ok(TreeId::from_bytes(oid.as_bytes()))
     }
 
+    fn compute_tree_id(&self, _path: &RepoPath, contents: &Tree) -> Option<TreeId> {
+        let git_tree = tree_to_git(contents);
+        let mut data = Vec::new();
+        git_tree.write_to(&mut data).ok()?;
+        let hash_kind = self.lock_git_repo().object_hash();
+        let oid = gix::objs::compute_hash(hash_kind, gix::object::Kind::Tree, &data).ok()?;
+        Some(TreeId::from_bytes(oid.as_bytes()))
+    }
+
+    fn compute_file_id(&self, _path: &RepoPath, contents: &[u8]) -> Option<FileId> {
+        let hash_kind = self.lock_git_repo().object_hash();
+        let oid = gix::objs::compute_hash(hash_kind, gix::object::Kind::Blob, contents).ok()?;
+        Some(FileId::from_bytes(oid.as_bytes()))
+    }
+
     #[tracing::instrument(skip(self))]
     async fn read_commit(&self, id: &CommitId) -> BackendResult<Commit> {
         if *id == self.root_commit_id {
@@ -1486,6 +1803,54 @@ impl Backend for GitBackend {
 
 /// Write a tree conflict as a special tree with `.jjconflict-base-N` and
 /// `.jjconflict-base-N` subtrees. This ensure that the parts are not GC'd.
+fn tree_to_git(contents: &Tree) -> gix::objs::Tree {
+    // Tree entries to be written must be sorted by Entry::filename(), which
+    // is slightly different from the order of our backend::Tree.
+    let entries = contents
+        .entries()
+        .map(|entry| {
+            let filename = BString::from(entry.name().as_internal_str());
+            match entry.value() {
+                TreeValue::File {
+                    id,
+                    executable: false,
+                    copy_id: _, // TODO: Use the value
+                } => gix::objs::tree::Entry {
+                    mode: gix::object::tree::EntryKind::Blob.into(),
+                    filename,
+                    oid: gix::ObjectId::from_bytes_or_panic(id.as_bytes()),
+                },
+                TreeValue::File {
+                    id,
+                    executable: true,
+                    copy_id: _, // TODO: Use the value
+                } => gix::objs::tree::Entry {
+                    mode: gix::object::tree::EntryKind::BlobExecutable.into(),
+                    filename,
+                    oid: gix::ObjectId::from_bytes_or_panic(id.as_bytes()),
+                },
+                TreeValue::Symlink(id) => gix::objs::tree::Entry {
+                    mode: gix::object::tree::EntryKind::Link.into(),
+                    filename,
+                    oid: gix::ObjectId::from_bytes_or_panic(id.as_bytes()),
+                },
+                TreeValue::Tree(id) => gix::objs::tree::Entry {
+                    mode: gix::object::tree::EntryKind::Tree.into(),
+                    filename,
+                    oid: gix::ObjectId::from_bytes_or_panic(id.as_bytes()),
+                },
+                TreeValue::GitSubmodule(id) => gix::objs::tree::Entry {
+                    mode: gix::object::tree::EntryKind::Commit.into(),
+                    filename,
+                    oid: gix::ObjectId::from_bytes_or_panic(id.as_bytes()),
+                },
+            }
+        })
+        .sorted_unstable()
+        .collect();
+    gix::objs::Tree { entries }
+}
+
 fn write_tree_conflict(
     repo: &gix::Repository,
     conflict: &Merge<TreeId>,
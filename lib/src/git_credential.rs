@@ -0,0 +1,288 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Credentials for HTTPS remotes, exchanged with Git over the
+//! [git-credential] helper protocol.
+//!
+//! Credentials aren't stored by `jj` itself. [`CredentialHelpers`] passes them
+//! to the credential helpers configured in Git, which can keep them in the
+//! system keychain, e.g. `osxkeychain`, `manager`, or `libsecret`.
+//!
+//! [git-credential]: https://git-scm.com/docs/git-credential
+
+use std::io;
+use std::io::Write as _;
+use std::process::Command;
+use std::process::Stdio;
+
+use bstr::ByteSlice as _;
+use thiserror::Error;
+
+use crate::git::GitSubprocessOptions;
+
+/// Error reading or writing credentials.
+#[derive(Debug, Error)]
+pub enum GitCredentialError {
+    /// The credential description couldn't be parsed.
+    #[error("Invalid credential description: {0}")]
+    Parse(String),
+    /// The URL to store credentials for couldn't be parsed.
+    #[error("Invalid URL {url}")]
+    InvalidUrl {
+        /// The URL as given.
+        url: String,
+        /// The underlying error.
+        #[source]
+        source: gix::url::parse::Error,
+    },
+    /// Git couldn't be run.
+    #[error("Failed to run git")]
+    Spawn(#[source] io::Error),
+    /// Git exited with an error.
+    #[error("git credential {action} failed: {message}")]
+    Git {
+        /// The `git credential` action.
+        action: &'static str,
+        /// The error output of Git.
+        message: String,
+    },
+}
+
+/// Credential description as exchanged by the git-credential protocol.
+///
+/// Attributes not listed here are ignored.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GitCredential {
+    /// Protocol over which the credential is used, e.g. `https`.
+    pub protocol: Option<String>,
+    /// Host name, including the port number if specified.
+    pub host: Option<String>,
+    /// Path of the repository on the host. Git only sends it if
+    /// `credential.useHttpPath` is set.
+    pub path: Option<String>,
+    /// User name.
+    pub username: Option<String>,
+    /// Password or access token.
+    pub password: Option<String>,
+}
+
+impl GitCredential {
+    /// Creates a credential description matching the remote `url`.
+    pub fn from_url(url: &str) -> Result<Self, GitCredentialError> {
+        let parsed =
+            gix::url::parse(url.into()).map_err(|source| GitCredentialError::InvalidUrl {
+                url: url.to_owned(),
+                source,
+            })?;
+        let host = parsed.host().map(|host| match parsed.port {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_owned(),
+        });
+        let path = parsed.path.trim_start_with(|c| c == '/');
+        Ok(Self {
+            protocol: Some(parsed.scheme.as_str().to_owned()),
+            host,
+            path: (!path.is_empty()).then(|| path.to_str_lossy().into_owned()),
+            username: parsed.user().map(|user| user.to_owned()),
+            password: parsed.password().map(|password| password.to_owned()),
+        })
+    }
+
+    /// Parses a description consisting of `key=value` lines, terminated by a
+    /// blank line or the end of input.
+    pub fn parse(input: &str) -> Result<Self, GitCredentialError> {
+        let mut credential = Self::default();
+        for line in input.lines() {
+            if line.is_empty() {
+                break;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| GitCredentialError::Parse(format!("Line without '=': {line}")))?;
+            let value = Some(value.to_owned());
+            match key {
+                "protocol" => credential.protocol = value,
+                "host" => credential.host = value,
+                "path" => credential.path = value,
+                "username" => credential.username = value,
+                "password" => credential.password = value,
+                _ => {}
+            }
+        }
+        Ok(credential)
+    }
+
+    /// Writes the description as `key=value` lines, without the terminating
+    /// blank line.
+    pub fn write(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        let attributes = [
+            ("protocol", &self.protocol),
+            ("host", &self.host),
+            ("path", &self.path),
+            ("username", &self.username),
+            ("password", &self.password),
+        ];
+        for (key, value) in attributes {
+            if let Some(value) = value {
+                writeln!(out, "{key}={value}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Credential helpers configured in Git, plus the one from the
+/// `git.credential-helper` setting.
+#[derive(Clone, Debug)]
+pub struct CredentialHelpers {
+    options: GitSubprocessOptions,
+}
+
+impl CredentialHelpers {
+    /// Creates an interface to the helpers used by Git subprocesses run with
+    /// the `options`.
+    pub fn new(options: GitSubprocessOptions) -> Self {
+        Self { options }
+    }
+
+    /// Returns the helpers Git would consult, in order.
+    pub fn list(&self) -> Result<Vec<String>, GitCredentialError> {
+        let output = self
+            .git_command()
+            .args(["config", "--get-all", "credential.helper"])
+            .stdin(Stdio::null())
+            .output()
+            .map_err(GitCredentialError::Spawn)?;
+        // Exits with 1 if the variable isn't set.
+        if !output.status.success() && output.status.code() != Some(1) {
+            return Err(GitCredentialError::Git {
+                action: "list",
+                message: output.stderr.to_str_lossy().trim_end().to_owned(),
+            });
+        }
+        let mut helpers = vec![];
+        for line in output.stdout.lines() {
+            // An empty value resets the list of helpers.
+            if line.is_empty() {
+                helpers.clear();
+            } else {
+                helpers.push(line.to_str_lossy().into_owned());
+            }
+        }
+        Ok(helpers)
+    }
+
+    /// Asks the helpers to store the `credential`, like Git does after the
+    /// server accepted it.
+    pub fn approve(&self, credential: &GitCredential) -> Result<(), GitCredentialError> {
+        self.run("approve", credential)
+    }
+
+    /// Asks the helpers to remove the credentials matching `credential`, like
+    /// Git does after the server rejected it.
+    pub fn reject(&self, credential: &GitCredential) -> Result<(), GitCredentialError> {
+        self.run("reject", credential)
+    }
+
+    fn git_command(&self) -> Command {
+        let mut cmd = Command::new(&self.options.executable_path);
+        cmd.envs(&self.options.environment);
+        if let Some(helper) = &self.options.credential_helper {
+            cmd.arg("-c").arg(format!("credential.helper={helper}"));
+        }
+        cmd
+    }
+
+    fn run(
+        &self,
+        action: &'static str,
+        credential: &GitCredential,
+    ) -> Result<(), GitCredentialError> {
+        let mut child = self
+            .git_command()
+            .args(["credential", action])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(GitCredentialError::Spawn)?;
+        let mut stdin = child.stdin.take().unwrap();
+        let mut input = vec![];
+        credential.write(&mut input).unwrap();
+        writeln!(input).unwrap();
+        // The helpers may exit without reading all input.
+        stdin.write_all(&input).ok();
+        drop(stdin);
+        let output = child
+            .wait_with_output()
+            .map_err(GitCredentialError::Spawn)?;
+        if !output.status.success() {
+            return Err(GitCredentialError::Git {
+                action,
+                message: output.stderr.to_str_lossy().trim_end().to_owned(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_write() {
+        let input = "protocol=https\nhost=example.com\nwwwauth[]=Basic\nusername=alice\n\n";
+        let parsed = GitCredential::parse(input).unwrap();
+        assert_eq!(
+            parsed,
+            GitCredential {
+                protocol: Some("https".to_owned()),
+                host: Some("example.com".to_owned()),
+                path: None,
+                username: Some("alice".to_owned()),
+                password: None,
+            }
+        );
+        let mut out = vec![];
+        parsed.write(&mut out).unwrap();
+        assert_eq!(out, b"protocol=https\nhost=example.com\nusername=alice\n");
+
+        assert!(GitCredential::parse("protocol").is_err());
+    }
+
+    #[test]
+    fn test_from_url() {
+        assert_eq!(
+            GitCredential::from_url("https://alice@example.com:8443/org/repo.git").unwrap(),
+            GitCredential {
+                protocol: Some("https".to_owned()),
+                host: Some("example.com:8443".to_owned()),
+                path: Some("org/repo.git".to_owned()),
+                username: Some("alice".to_owned()),
+                password: None,
+            }
+        );
+        assert_eq!(
+            GitCredential::from_url("https://example.com").unwrap(),
+            GitCredential {
+                protocol: Some("https".to_owned()),
+                host: Some("example.com".to_owned()),
+                path: None,
+                username: None,
+                password: None,
+            }
+        );
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Support for [Git LFS](https://git-lfs.com/) pointer files.
+//!
+//! Files with the `filter=lfs` attribute are stored as small pointer files
+//! which refer to the actual contents by their SHA-256 hash. The contents are
+//! kept in the same local object cache as the one used by `git lfs`, so objects
+//! downloaded by either tool can be checked out by the other.
+
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use sha2::Digest as _;
+use sha2::Sha256;
+use tempfile::NamedTempFile;
+
+use crate::file_util::persist_content_addressed_temp_file;
+use crate::hex_util::encode_hex;
+
+const POINTER_VERSION: &str = "https://git-lfs.github.com/spec/v1";
+// Pointer files are small. Larger files are never pointers.
+const MAX_POINTER_SIZE: usize = 1024;
+
+/// Contents of a Git LFS pointer file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LfsPointer {
+    /// Hex-encoded SHA-256 hash of the object.
+    pub oid: String,
+    /// Size of the object in bytes.
+    pub size: u64,
+}
+
+impl LfsPointer {
+    /// Creates a pointer to the object with the given `contents`.
+    pub fn for_contents(contents: &[u8]) -> Self {
+        Self {
+            oid: encode_hex(&Sha256::digest(contents)),
+            size: contents.len() as u64,
+        }
+    }
+
+    /// Parses the contents of a pointer file. Returns `None` if the contents
+    /// aren't a valid pointer.
+    pub fn parse(contents: &[u8]) -> Option<Self> {
+        if contents.len() > MAX_POINTER_SIZE {
+            return None;
+        }
+        let text = str::from_utf8(contents).ok()?;
+        let mut lines = text.strip_suffix('\n')?.split('\n');
+        if lines.next()? != format!("version {POINTER_VERSION}") {
+            return None;
+        }
+        let mut oid = None;
+        let mut size = None;
+        for line in lines {
+            // Other keys are used by extensions, which we don't support.
+            let (key, value) = line.split_once(' ')?;
+            match key {
+                "oid" => {
+                    let hash = value.strip_prefix("sha256:")?;
+                    if hash.len() != 64
+                        || !hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+                    {
+                        return None;
+                    }
+                    oid = Some(hash.to_owned());
+                }
+                "size" => size = Some(value.parse().ok()?),
+                _ => return None,
+            }
+        }
+        Some(Self {
+            oid: oid?,
+            size: size?,
+        })
+    }
+
+    /// Returns the contents of the pointer file.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        format!(
+            "version {POINTER_VERSION}\noid sha256:{oid}\nsize {size}\n",
+            oid = self.oid,
+            size = self.size
+        )
+        .into_bytes()
+    }
+}
+
+/// Local cache of Git LFS objects, laid out like `git lfs` does in the Git
+/// directory.
+#[derive(Clone, Debug)]
+pub struct LfsObjectCache {
+    objects_dir: PathBuf,
+    tmp_dir: PathBuf,
+}
+
+impl LfsObjectCache {
+    /// Returns the cache in the Git directory `git_dir`.
+    pub fn for_git_dir(git_dir: &Path) -> Self {
+        Self {
+            objects_dir: git_dir.join("lfs").join("objects"),
+            tmp_dir: git_dir.join("lfs").join("tmp"),
+        }
+    }
+
+    /// Returns the path of the object `oid` in the cache.
+    pub fn object_path(&self, oid: &str) -> PathBuf {
+        self.objects_dir.join(&oid[..2]).join(&oid[2..4]).join(oid)
+    }
+
+    /// Reads the object referenced by the `pointer`. Returns `None` if the
+    /// object hasn't been downloaded.
+    pub fn read(&self, pointer: &LfsPointer) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.object_path(&pointer.oid)) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Adds the `contents` to the cache and returns the pointer to them.
+    pub fn write(&self, contents: &[u8]) -> io::Result<LfsPointer> {
+        let pointer = LfsPointer::for_contents(contents);
+        let path = self.object_path(&pointer.oid);
+        if path.exists() {
+            return Ok(pointer);
+        }
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::create_dir_all(&self.tmp_dir)?;
+        let mut temp_file = NamedTempFile::new_in(&self.tmp_dir)?;
+        temp_file.write_all(contents)?;
+        persist_content_addressed_temp_file(temp_file, &path)?;
+        Ok(pointer)
+    }
+
+    /// Converts working-copy `contents` into a pointer file, adding them to
+    /// the cache. Contents which already are a pointer are kept as is.
+    pub fn clean(&self, contents: Vec<u8>) -> io::Result<Vec<u8>> {
+        if LfsPointer::parse(&contents).is_some() {
+            return Ok(contents);
+        }
+        Ok(self.write(&contents)?.to_bytes())
+    }
+
+    /// Converts a pointer file into the contents it refers to. If the object
+    /// hasn't been downloaded, or the `contents` aren't a pointer, they're
+    /// returned as is.
+    pub fn smudge(&self, contents: Vec<u8>) -> io::Result<Vec<u8>> {
+        let Some(pointer) = LfsPointer::parse(&contents) else {
+            return Ok(contents);
+        };
+        match self.read(&pointer)? {
+            Some(object) => Ok(object),
+            None => {
+                tracing::warn!(oid = pointer.oid, "Git LFS object hasn't been downloaded");
+                Ok(contents)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::new_temp_dir;
+
+    const HELLO_OID: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    #[test]
+    fn test_pointer_round_trip() {
+        let pointer = LfsPointer::for_contents(b"hello");
+        assert_eq!(pointer.oid, HELLO_OID);
+        assert_eq!(pointer.size, 5);
+        let bytes = pointer.to_bytes();
+        assert_eq!(
+            bytes,
+            format!("version https://git-lfs.github.com/spec/v1\noid sha256:{HELLO_OID}\nsize 5\n")
+                .as_bytes()
+        );
+        assert_eq!(LfsPointer::parse(&bytes), Some(pointer));
+    }
+
+    #[test]
+    fn test_pointer_parse_invalid() {
+        let parse = |text: String| LfsPointer::parse(text.as_bytes());
+        let version = "version https://git-lfs.github.com/spec/v1";
+        assert!(parse(format!("{version}\noid sha256:{HELLO_OID}\nsize 5\n")).is_some());
+        // Missing trailing newline
+        assert!(parse(format!("{version}\noid sha256:{HELLO_OID}\nsize 5")).is_none());
+        // Unknown version
+        assert!(parse(format!("version foo\noid sha256:{HELLO_OID}\nsize 5\n")).is_none());
+        // Missing keys
+        assert!(parse(format!("{version}\nsize 5\n")).is_none());
+        assert!(parse(format!("{version}\noid sha256:{HELLO_OID}\n")).is_none());
+        // Invalid values
+        assert!(parse(format!("{version}\noid sha1:{HELLO_OID}\nsize 5\n")).is_none());
+        assert!(parse(format!("{version}\noid sha256:abc\nsize 5\n")).is_none());
+        assert!(parse(format!("{version}\noid sha256:{HELLO_OID}\nsize -1\n")).is_none());
+        // Unsupported extension
+        assert!(
+            parse(format!(
+                "{version}\next-0-foo sha256:{HELLO_OID}\noid sha256:{HELLO_OID}\nsize 5\n"
+            ))
+            .is_none()
+        );
+        assert!(parse("hello\n".to_owned()).is_none());
+    }
+
+    #[test]
+    fn test_object_cache() {
+        let temp_dir = new_temp_dir();
+        let cache = LfsObjectCache::for_git_dir(temp_dir.path());
+        let pointer_bytes = cache.clean(b"hello".to_vec()).unwrap();
+        let pointer = LfsPointer::parse(&pointer_bytes).unwrap();
+        assert!(
+            temp_dir
+                .path()
+                .join("lfs/objects/2c/f2")
+                .join(HELLO_OID)
+                .is_file()
+        );
+        assert_eq!(cache.read(&pointer).unwrap(), Some(b"hello".to_vec()));
+        // Pointers are stored as is
+        assert_eq!(cache.clean(pointer_bytes.clone()).unwrap(), pointer_bytes);
+        assert_eq!(cache.smudge(pointer_bytes.clone()).unwrap(), b"hello");
+        // Missing objects are checked out as pointers
+        let missing_bytes = LfsPointer::for_contents(b"missing").to_bytes();
+        assert_eq!(cache.smudge(missing_bytes.clone()).unwrap(), missing_bytes);
+        assert_eq!(cache.smudge(b"foo".to_vec()).unwrap(), b"foo");
+    }
+}
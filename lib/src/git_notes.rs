@@ -0,0 +1,318 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Support for [Git notes](https://git-scm.com/docs/git-notes).
+//!
+//! Notes are stored in the tree of the commit pointed to by a ref under
+//! `refs/notes/`. The note of a commit is a blob named after the hex id of the
+//! commit. Git may split the names into directories by their leading digits
+//! ("fanout") if there are many notes, which is understood when reading notes.
+//! Notes are written without fanout.
+//!
+//! Notes refs aren't tracked by the operation log, so changes to notes can't
+//! be undone.
+
+use gix::objs::tree::EntryKind;
+use thiserror::Error;
+
+use crate::backend::CommitId;
+use crate::backend::Signature;
+use crate::git::GitSubprocessOptions;
+use crate::git::UnexpectedGitBackendError;
+use crate::git::get_git_backend;
+use crate::git_backend::signature_to_git;
+use crate::git_subprocess::GitSubprocessContext;
+use crate::git_subprocess::GitSubprocessError;
+use crate::object_id::ObjectId as _;
+use crate::ref_name::RemoteName;
+use crate::ref_name::RemoteNameBuf;
+use crate::store::Store;
+
+/// Notes ref used by default, like `git notes` does.
+pub const DEFAULT_NOTES_REF: &str = "refs/notes/commits";
+
+/// Error reading, writing, or transferring notes.
+#[derive(Debug, Error)]
+pub enum GitNotesError {
+    /// The repository isn't backed by Git.
+    #[error(transparent)]
+    UnexpectedBackend(#[from] UnexpectedGitBackendError),
+    /// The notes ref couldn't be read.
+    #[error("Failed to read notes from {ref_name}")]
+    Read {
+        /// Full name of the notes ref.
+        ref_name: String,
+        /// The underlying error.
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// The notes ref couldn't be updated.
+    #[error("Failed to write notes to {ref_name}")]
+    Write {
+        /// Full name of the notes ref.
+        ref_name: String,
+        /// The underlying error.
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// The remote to transfer the notes from or to doesn't exist.
+    #[error("No git remote named '{}'", .0.as_symbol())]
+    NoSuchRemote(RemoteNameBuf),
+    /// Error running git.
+    #[error(transparent)]
+    Subprocess(#[from] GitSubprocessError),
+}
+
+/// Expands the short name of a notes ref like `git notes --ref` does.
+///
+/// `ci` and `notes/ci` are expanded to `refs/notes/ci`. Full names are kept
+/// as is.
+pub fn expand_notes_ref(name: &str) -> String {
+    if name.starts_with("refs/notes/") {
+        name.to_owned()
+    } else if name.starts_with("notes/") {
+        format!("refs/{name}")
+    } else {
+        format!("refs/notes/{name}")
+    }
+}
+
+/// Notes stored in a notes ref, as of the time they were loaded.
+pub struct GitNotes {
+    git_repo: gix::Repository,
+    ref_name: String,
+    /// Commit the notes ref points to, if it exists.
+    head_id: Option<gix::ObjectId>,
+    tree_id: gix::ObjectId,
+}
+
+impl GitNotes {
+    /// Loads the notes of the full notes ref `ref_name` in the Git repository
+    /// backing the `store`. A missing ref is treated as having no notes.
+    pub fn load(store: &Store, ref_name: &str) -> Result<Self, GitNotesError> {
+        let git_repo = get_git_backend(store)?.git_repo();
+        let read_err = |source: Box<dyn std::error::Error + Send + Sync>| GitNotesError::Read {
+            ref_name: ref_name.to_owned(),
+            source,
+        };
+        let (head_id, tree_id) = match git_repo
+            .try_find_reference(ref_name)
+            .map_err(|err| read_err(err.into()))?
+        {
+            Some(mut reference) => {
+                let commit = reference
+                    .peel_to_commit()
+                    .map_err(|err| read_err(err.into()))?;
+                let tree_id = commit.tree_id().map_err(|err| read_err(err.into()))?;
+                (Some(commit.id), tree_id.detach())
+            }
+            None => (None, gix::ObjectId::empty_tree(gix::hash::Kind::Sha1)),
+        };
+        Ok(Self {
+            git_repo,
+            ref_name: ref_name.to_owned(),
+            head_id,
+            tree_id,
+        })
+    }
+
+    /// Full name of the notes ref.
+    pub fn ref_name(&self) -> &str {
+        &self.ref_name
+    }
+
+    /// Returns the contents of the note attached to `commit_id`.
+    pub fn get(&self, commit_id: &CommitId) -> Result<Option<Vec<u8>>, GitNotesError> {
+        let Some((_, blob_id)) = self.find_note(commit_id)? else {
+            return Ok(None);
+        };
+        let mut blob = self
+            .git_repo
+            .find_blob(blob_id)
+            .map_err(|err| self.read_err(err.into()))?;
+        Ok(Some(blob.take_data()))
+    }
+
+    /// Attaches the `note` to `commit_id`, replacing the existing note, or
+    /// removes the note if `note` is `None`.
+    ///
+    /// The notes ref is updated with a new commit with the given `message`,
+    /// unless the notes didn't change. Returns true if the notes changed.
+    pub fn set(
+        &mut self,
+        commit_id: &CommitId,
+        note: Option<&[u8]>,
+        signature: &Signature,
+        message: &str,
+    ) -> Result<bool, GitNotesError> {
+        let existing = self.find_note(commit_id)?;
+        let mut editor = self
+            .git_repo
+            .edit_tree(self.tree_id)
+            .map_err(|err| self.write_err(err.into()))?;
+        if let Some((path, _)) = &existing {
+            editor
+                .remove(path)
+                .map_err(|err| self.write_err(err.into()))?;
+        }
+        if let Some(note) = note {
+            let blob_id = self
+                .git_repo
+                .write_blob(note)
+                .map_err(|err| self.write_err(err.into()))?;
+            editor
+                .upsert(commit_id.hex(), EntryKind::Blob, blob_id)
+                .map_err(|err| self.write_err(err.into()))?;
+        }
+        let tree_id = editor
+            .write()
+            .map_err(|err| self.write_err(err.into()))?
+            .detach();
+        if tree_id == self.tree_id {
+            return Ok(false);
+        }
+
+        let signature = signature_to_git(signature);
+        let mut time_buf = gix::date::parse::TimeBuf::default();
+        let signature = signature.to_ref(&mut time_buf);
+        // The ref is only updated if it still points to the commit the notes
+        // were loaded from.
+        let head_id = self
+            .git_repo
+            .commit_as(
+                signature,
+                signature,
+                self.ref_name.as_str(),
+                message,
+                tree_id,
+                self.head_id,
+            )
+            .map_err(|err| self.write_err(err.into()))?
+            .detach();
+        self.head_id = Some(head_id);
+        self.tree_id = tree_id;
+        Ok(true)
+    }
+
+    /// Returns the path and blob id of the note attached to `commit_id`.
+    fn find_note(
+        &self,
+        commit_id: &CommitId,
+    ) -> Result<Option<(String, gix::ObjectId)>, GitNotesError> {
+        let hex = commit_id.hex();
+        let mut tree = self
+            .git_repo
+            .find_tree(self.tree_id)
+            .map_err(|err| self.read_err(err.into()))?;
+        let mut path = String::new();
+        let mut rest = hex.as_str();
+        loop {
+            if let Some(entry) = tree.find_entry(rest)
+                && entry.mode().is_blob()
+            {
+                path.push_str(rest);
+                return Ok(Some((path, entry.oid().to_owned())));
+            }
+            if rest.len() <= 2 {
+                return Ok(None);
+            }
+            let (dir, tail) = rest.split_at(2);
+            let Some(entry) = tree.find_entry(dir).filter(|entry| entry.mode().is_tree()) else {
+                return Ok(None);
+            };
+            let subtree_id = entry.oid().to_owned();
+            tree = self
+                .git_repo
+                .find_tree(subtree_id)
+                .map_err(|err| self.read_err(err.into()))?;
+            path.push_str(dir);
+            path.push('/');
+            rest = tail;
+        }
+    }
+
+    fn read_err(&self, source: Box<dyn std::error::Error + Send + Sync>) -> GitNotesError {
+        GitNotesError::Read {
+            ref_name: self.ref_name.clone(),
+            source,
+        }
+    }
+
+    fn write_err(&self, source: Box<dyn std::error::Error + Send + Sync>) -> GitNotesError {
+        GitNotesError::Write {
+            ref_name: self.ref_name.clone(),
+            source,
+        }
+    }
+}
+
+/// Fetches the notes ref `ref_name` from the remote.
+///
+/// The local notes ref is only updated if it's a fast-forward.
+pub fn fetch_notes(
+    store: &Store,
+    subprocess_options: GitSubprocessOptions,
+    remote_name: &RemoteName,
+    ref_name: &str,
+) -> Result<(), GitNotesError> {
+    let git_ctx = remote_git_context(store, subprocess_options, remote_name)?;
+    git_ctx.spawn_fetch_notes(remote_name, ref_name)?;
+    Ok(())
+}
+
+/// Pushes the notes ref `ref_name` to the remote.
+///
+/// The push is rejected if it isn't a fast-forward of the remote notes ref.
+pub fn push_notes(
+    store: &Store,
+    subprocess_options: GitSubprocessOptions,
+    remote_name: &RemoteName,
+    ref_name: &str,
+) -> Result<(), GitNotesError> {
+    let git_ctx = remote_git_context(store, subprocess_options, remote_name)?;
+    git_ctx.spawn_push_notes(remote_name, ref_name)?;
+    Ok(())
+}
+
+fn remote_git_context(
+    store: &Store,
+    subprocess_options: GitSubprocessOptions,
+    remote_name: &RemoteName,
+) -> Result<GitSubprocessContext, GitNotesError> {
+    let git_backend = get_git_backend(store)?;
+    if git_backend
+        .git_repo()
+        .try_find_remote(remote_name.as_str())
+        .is_none()
+    {
+        return Err(GitNotesError::NoSuchRemote(remote_name.to_owned()));
+    }
+    Ok(GitSubprocessContext::from_git_backend(
+        git_backend,
+        subprocess_options,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_notes_ref() {
+        assert_eq!(expand_notes_ref("commits"), "refs/notes/commits");
+        assert_eq!(expand_notes_ref("ci/results"), "refs/notes/ci/results");
+        assert_eq!(expand_notes_ref("notes/ci"), "refs/notes/ci");
+        assert_eq!(expand_notes_ref("refs/notes/ci"), "refs/notes/ci");
+    }
+}
@@ -0,0 +1,460 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! OAuth login to forges with the [device authorization grant][rfc8628].
+//!
+//! The user opens a URL in a browser, possibly on another machine, and enters
+//! the code shown by `jj`. Meanwhile, `jj` polls the forge until it hands out
+//! an access token, which can then be stored with
+//! [`CredentialHelpers`](crate::git_credential::CredentialHelpers).
+//!
+//! The HTTP requests are sent by `curl`.
+//!
+//! [rfc8628]: https://datatracker.ietf.org/doc/html/rfc8628
+
+use std::io;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+use std::time::Duration;
+
+use bstr::ByteSlice as _;
+use serde::Deserialize;
+use thiserror::Error;
+
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+// Default polling interval and increment on "slow_down" per RFC 8628.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(5);
+const SLOW_DOWN_INCREMENT: Duration = Duration::from_secs(5);
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Error logging in with OAuth.
+#[derive(Debug, Error)]
+pub enum OAuthError {
+    /// The HTTP client couldn't be run.
+    #[error("Failed to run {program}")]
+    Spawn {
+        /// The HTTP client executable.
+        program: String,
+        /// The underlying error.
+        #[source]
+        source: io::Error,
+    },
+    /// The HTTP request failed.
+    #[error("Request to {url} failed: {message}")]
+    Http {
+        /// The requested URL.
+        url: String,
+        /// The error output of the HTTP client.
+        message: String,
+    },
+    /// The response couldn't be parsed.
+    #[error("Unexpected response from {url}")]
+    InvalidResponse {
+        /// The requested URL.
+        url: String,
+        /// The underlying error.
+        #[source]
+        source: serde_json::Error,
+    },
+    /// The user denied the authorization request.
+    #[error("The authorization request was denied")]
+    AccessDenied,
+    /// The device code expired before the user authorized it.
+    #[error("The device code expired before the login was completed")]
+    Expired,
+    /// The server returned an error.
+    #[error(
+        "The server returned an error: {}",
+        format_server_error(error, description)
+    )]
+    Server {
+        /// The OAuth error code, e.g. `invalid_client`.
+        error: String,
+        /// The human-readable description, if any.
+        description: Option<String>,
+    },
+}
+
+fn format_server_error(error: &str, description: &Option<String>) -> String {
+    match description {
+        Some(description) => format!("{error} ({description})"),
+        None => error.to_owned(),
+    }
+}
+
+/// Sends form-encoded POST requests to the OAuth endpoints.
+pub trait OAuthTransport {
+    /// Posts the `form` to the `url`, and returns the response body. HTTP error
+    /// statuses aren't errors since the endpoints describe errors in the body.
+    fn post(&mut self, url: &str, form: &[(&str, &str)]) -> Result<Vec<u8>, OAuthError>;
+}
+
+/// Sends requests by running `curl`.
+///
+/// The form fields are passed through a config file on stdin so the device
+/// code doesn't show up in the process list.
+#[derive(Clone, Debug)]
+pub struct CurlTransport {
+    executable: PathBuf,
+}
+
+impl CurlTransport {
+    /// Creates a transport running the given `curl` executable.
+    pub fn new(executable: impl Into<PathBuf>) -> Self {
+        Self {
+            executable: executable.into(),
+        }
+    }
+}
+
+impl OAuthTransport for CurlTransport {
+    fn post(&mut self, url: &str, form: &[(&str, &str)]) -> Result<Vec<u8>, OAuthError> {
+        let spawn_err = |source| OAuthError::Spawn {
+            program: self.executable.display().to_string(),
+            source,
+        };
+        let mut child = Command::new(&self.executable)
+            .args(["--silent", "--show-error", "--config", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(spawn_err)?;
+        let mut config = vec![];
+        writeln!(config, "url = {}", quote_curl_config(url)).unwrap();
+        writeln!(config, "header = \"Accept: application/json\"").unwrap();
+        for (name, value) in form {
+            let field = format!("{name}={value}");
+            writeln!(config, "data-urlencode = {}", quote_curl_config(&field)).unwrap();
+        }
+        let mut stdin = child.stdin.take().unwrap();
+        // Failures are reported by curl itself.
+        stdin.write_all(&config).ok();
+        drop(stdin);
+        let output = child.wait_with_output().map_err(spawn_err)?;
+        if !output.status.success() {
+            return Err(OAuthError::Http {
+                url: url.to_owned(),
+                message: output.stderr.to_str_lossy().trim_end().to_owned(),
+            });
+        }
+        Ok(output.stdout)
+    }
+}
+
+fn quote_curl_config(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// OAuth app registered with a forge, and the forge's endpoints.
+#[derive(Clone, Debug)]
+pub struct OAuthProvider {
+    /// Client ID of the OAuth app.
+    pub client_id: String,
+    /// URL of the device authorization endpoint.
+    pub device_authorization_url: String,
+    /// URL of the token endpoint.
+    pub token_url: String,
+    /// Scopes to request.
+    pub scopes: Vec<String>,
+}
+
+/// Device authorization request waiting for the user.
+#[derive(Clone, Debug)]
+pub struct DeviceAuthorization {
+    /// Code the user should enter.
+    pub user_code: String,
+    /// URL the user should open.
+    pub verification_uri: String,
+    /// URL including the user code, if the forge provides one.
+    pub verification_uri_complete: Option<String>,
+    device_code: String,
+    interval: Duration,
+    expires_in: Duration,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceAuthorizationResponse {
+    device_code: String,
+    user_code: String,
+    #[serde(alias = "verification_url")]
+    verification_uri: String,
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    interval: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TokenResponse {
+    Token {
+        access_token: String,
+    },
+    Error {
+        error: String,
+        error_description: Option<String>,
+        interval: Option<u64>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    error: String,
+    error_description: Option<String>,
+}
+
+impl OAuthProvider {
+    /// Starts the login by requesting a device code.
+    pub fn authorize_device(
+        &self,
+        transport: &mut dyn OAuthTransport,
+    ) -> Result<DeviceAuthorization, OAuthError> {
+        let scope = self.scopes.join(" ");
+        let mut form = vec![("client_id", self.client_id.as_str())];
+        if !scope.is_empty() {
+            form.push(("scope", &scope));
+        }
+        let url = &self.device_authorization_url;
+        let body = transport.post(url, &form)?;
+        let response: DeviceAuthorizationResponse = match serde_json::from_slice(&body) {
+            Ok(response) => response,
+            Err(source) => {
+                return Err(match serde_json::from_slice::<ErrorResponse>(&body) {
+                    Ok(ErrorResponse {
+                        error,
+                        error_description,
+                    }) => OAuthError::Server {
+                        error,
+                        description: error_description,
+                    },
+                    Err(_) => OAuthError::InvalidResponse {
+                        url: url.clone(),
+                        source,
+                    },
+                });
+            }
+        };
+        Ok(DeviceAuthorization {
+            user_code: response.user_code,
+            verification_uri: response.verification_uri,
+            verification_uri_complete: response.verification_uri_complete,
+            device_code: response.device_code,
+            interval: response
+                .interval
+                .map_or(DEFAULT_INTERVAL, Duration::from_secs)
+                .max(MIN_INTERVAL),
+            expires_in: Duration::from_secs(response.expires_in),
+        })
+    }
+
+    /// Polls the token endpoint until the user completes the `authorization`,
+    /// and returns the access token. `sleep` is called to wait between
+    /// requests.
+    pub fn wait_for_token(
+        &self,
+        transport: &mut dyn OAuthTransport,
+        authorization: &DeviceAuthorization,
+        sleep: &mut dyn FnMut(Duration),
+    ) -> Result<String, OAuthError> {
+        let form = [
+            ("grant_type", DEVICE_CODE_GRANT_TYPE),
+            ("device_code", authorization.device_code.as_str()),
+            ("client_id", self.client_id.as_str()),
+        ];
+        let mut interval = authorization.interval;
+        let mut elapsed = Duration::ZERO;
+        loop {
+            if elapsed >= authorization.expires_in {
+                return Err(OAuthError::Expired);
+            }
+            sleep(interval);
+            elapsed += interval;
+            let body = transport.post(&self.token_url, &form)?;
+            let response =
+                serde_json::from_slice(&body).map_err(|source| OAuthError::InvalidResponse {
+                    url: self.token_url.clone(),
+                    source,
+                })?;
+            match response {
+                TokenResponse::Token { access_token } => return Ok(access_token),
+                TokenResponse::Error {
+                    error,
+                    error_description,
+                    interval: new_interval,
+                } => match error.as_str() {
+                    "authorization_pending" => {}
+                    "slow_down" => {
+                        interval = new_interval
+                            .map_or(interval + SLOW_DOWN_INCREMENT, Duration::from_secs)
+                            .max(MIN_INTERVAL);
+                    }
+                    "access_denied" => return Err(OAuthError::AccessDenied),
+                    "expired_token" => return Err(OAuthError::Expired),
+                    _ => {
+                        return Err(OAuthError::Server {
+                            error,
+                            description: error_description,
+                        });
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    struct FakeTransport {
+        requests: Vec<(String, Vec<(String, String)>)>,
+        responses: VecDeque<&'static str>,
+    }
+
+    impl FakeTransport {
+        fn new(responses: impl IntoIterator<Item = &'static str>) -> Self {
+            Self {
+                requests: vec![],
+                responses: responses.into_iter().collect(),
+            }
+        }
+    }
+
+    impl OAuthTransport for FakeTransport {
+        fn post(&mut self, url: &str, form: &[(&str, &str)]) -> Result<Vec<u8>, OAuthError> {
+            let form = form
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect();
+            self.requests.push((url.to_owned(), form));
+            Ok(self.responses.pop_front().unwrap().into())
+        }
+    }
+
+    fn provider() -> OAuthProvider {
+        OAuthProvider {
+            client_id: "client".to_owned(),
+            device_authorization_url: "https://example.com/device".to_owned(),
+            token_url: "https://example.com/token".to_owned(),
+            scopes: vec!["repo".to_owned(), "workflow".to_owned()],
+        }
+    }
+
+    const DEVICE_RESPONSE: &str = r#"{
+        "device_code": "device",
+        "user_code": "ABCD-1234",
+        "verification_uri": "https://example.com/activate",
+        "expires_in": 900,
+        "interval": 5
+    }"#;
+
+    #[test]
+    fn test_device_flow() {
+        let provider = provider();
+        let mut transport = FakeTransport::new([
+            DEVICE_RESPONSE,
+            r#"{"error": "authorization_pending"}"#,
+            r#"{"error": "slow_down"}"#,
+            r#"{"error": "slow_down", "interval": 20}"#,
+            r#"{"access_token": "token", "token_type": "bearer"}"#,
+        ]);
+        let authorization = provider.authorize_device(&mut transport).unwrap();
+        assert_eq!(authorization.user_code, "ABCD-1234");
+        assert_eq!(
+            authorization.verification_uri,
+            "https://example.com/activate"
+        );
+        let mut sleeps = vec![];
+        let token = provider
+            .wait_for_token(&mut transport, &authorization, &mut |d| sleeps.push(d))
+            .unwrap();
+        assert_eq!(token, "token");
+        assert_eq!(
+            sleeps,
+            [5, 5, 10, 20].map(Duration::from_secs),
+            "interval should be increased on slow_down"
+        );
+        let (url, form) = &transport.requests[0];
+        assert_eq!(url, "https://example.com/device");
+        assert_eq!(
+            form,
+            &[
+                ("client_id".to_owned(), "client".to_owned()),
+                ("scope".to_owned(), "repo workflow".to_owned()),
+            ]
+        );
+        let (url, form) = &transport.requests[1];
+        assert_eq!(url, "https://example.com/token");
+        assert_eq!(form[1], ("device_code".to_owned(), "device".to_owned()));
+    }
+
+    #[test]
+    fn test_device_flow_errors() {
+        let provider = provider();
+        let mut transport = FakeTransport::new([
+            r#"{"error": "invalid_client", "error_description": "Unknown client"}"#,
+        ]);
+        assert_matches!(
+            provider.authorize_device(&mut transport),
+            Err(OAuthError::Server { error, description: Some(_) }) if error == "invalid_client"
+        );
+
+        let mut transport = FakeTransport::new([DEVICE_RESPONSE, r#"{"error": "access_denied"}"#]);
+        let authorization = provider.authorize_device(&mut transport).unwrap();
+        assert_matches!(
+            provider.wait_for_token(&mut transport, &authorization, &mut |_| {}),
+            Err(OAuthError::AccessDenied)
+        );
+
+        // Gives up once the device code expires, even if the server keeps
+        // answering.
+        let mut transport = FakeTransport::new([DEVICE_RESPONSE].into_iter().chain(
+            std::iter::repeat_n(r#"{"error": "authorization_pending"}"#, 180),
+        ));
+        let authorization = provider.authorize_device(&mut transport).unwrap();
+        assert_matches!(
+            provider.wait_for_token(&mut transport, &authorization, &mut |_| {}),
+            Err(OAuthError::Expired)
+        );
+        assert_eq!(transport.requests.len(), 181);
+    }
+
+    #[test]
+    fn test_quote_curl_config() {
+        assert_eq!(quote_curl_config("a=b c"), r#""a=b c""#);
+        assert_eq!(quote_curl_config(r#"a"b\c"#), r#""a\"b\\c""#);
+        assert_eq!(quote_curl_config("a\nb"), r#""a\nb""#);
+    }
+}
@@ -0,0 +1,363 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Support for materializing Git submodules in the working copy.
+//!
+//! Submodules are described by the `.gitmodules` file in the root of the tree,
+//! and their commits are recorded as [`TreeValue::GitSubmodule`] entries. A
+//! submodule is checked out as a nested Git repository with its own `.git`
+//! directory, which the working copy otherwise leaves alone.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use bstr::ByteSlice as _;
+use thiserror::Error;
+use tokio::io::AsyncReadExt as _;
+
+use crate::backend::BackendError;
+use crate::backend::CommitId;
+use crate::backend::TreeValue;
+use crate::git::GitSubprocessOptions;
+use crate::git::get_git_repo;
+use crate::git_subprocess::GitSubprocessContext;
+use crate::git_subprocess::GitSubprocessError;
+use crate::merged_tree::MergedTree;
+use crate::object_id::ObjectId as _;
+use crate::repo_path::InvalidRepoPathError;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::store::Store;
+
+const GITMODULES_PATH: &str = ".gitmodules";
+const SUBMODULE_REMOTE_NAME: &str = "origin";
+
+/// A submodule recorded in a tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Submodule {
+    /// Name of the submodule in `.gitmodules`.
+    pub name: String,
+    /// Path of the submodule in the tree.
+    pub path: RepoPathBuf,
+    /// URL to fetch the submodule from, as written in `.gitmodules`.
+    pub url: Option<String>,
+    /// Commit the submodule should be checked out at.
+    pub commit_id: CommitId,
+}
+
+/// State of a submodule in the working copy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SubmoduleState {
+    /// The submodule hasn't been cloned.
+    Uninitialized,
+    /// The submodule is checked out at the given commit.
+    CheckedOut(CommitId),
+}
+
+/// Error reading or updating submodules.
+#[derive(Debug, Error)]
+pub enum GitSubmoduleError {
+    /// Error reading the tree.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+    /// The `.gitmodules` file couldn't be parsed.
+    #[error("Failed to parse .gitmodules")]
+    InvalidGitmodules(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// The submodule has no URL to fetch from.
+    #[error("Submodule {name} has no URL")]
+    MissingUrl {
+        /// Name of the submodule.
+        name: String,
+    },
+    /// The URL of the submodule is relative, but the repository has no URL to
+    /// resolve it against.
+    #[error("Cannot resolve relative URL {url} of submodule {name} without a remote named origin")]
+    UnresolvableUrl {
+        /// Name of the submodule.
+        name: String,
+        /// URL of the submodule.
+        url: String,
+    },
+    /// The submodule path can't be checked out.
+    #[error(transparent)]
+    InvalidPath(#[from] InvalidRepoPathError),
+    /// The checked-out submodule repository couldn't be read or created.
+    #[error("Failed to access submodule repository at {path}", path = path.display())]
+    Repository {
+        /// Path of the submodule on disk.
+        path: PathBuf,
+        /// The underlying error.
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// Error running git.
+    #[error(transparent)]
+    Subprocess(#[from] GitSubprocessError),
+}
+
+/// Returns the submodules listed in the `.gitmodules` file of the `tree` which
+/// have a commit recorded in the tree.
+///
+/// Submodules are returned in the order they're listed in `.gitmodules`.
+pub async fn get_submodules(tree: &MergedTree) -> Result<Vec<Submodule>, GitSubmoduleError> {
+    let gitmodules_path = RepoPath::from_internal_string(GITMODULES_PATH).unwrap();
+    let Ok(Some(TreeValue::File { id, .. })) = tree.path_value(gitmodules_path)?.into_resolved()
+    else {
+        return Ok(vec![]);
+    };
+    let mut content = vec![];
+    let mut reader = tree.store().read_file(gitmodules_path, &id).await?;
+    reader
+        .read_to_end(&mut content)
+        .await
+        .map_err(|err| BackendError::ReadFile {
+            path: gitmodules_path.to_owned(),
+            id: id.clone(),
+            source: err.into(),
+        })?;
+    let mut submodules = vec![];
+    for (name, path, url) in parse_gitmodules(&content)? {
+        // Entries without a commit in the tree are stale, and entries whose
+        // path isn't a valid repo path can't be checked out anyway.
+        let Ok(path) = RepoPathBuf::from_relative_path(&path) else {
+            continue;
+        };
+        if let Ok(Some(TreeValue::GitSubmodule(commit_id))) =
+            tree.path_value(&path)?.into_resolved()
+        {
+            submodules.push(Submodule {
+                name,
+                path,
+                url,
+                commit_id,
+            });
+        }
+    }
+    Ok(submodules)
+}
+
+/// Parses `.gitmodules` content into the name, path, and URL of each entry.
+fn parse_gitmodules(
+    content: &[u8],
+) -> Result<Vec<(String, String, Option<String>)>, GitSubmoduleError> {
+    let file = gix::submodule::File::from_bytes(content, None, &Default::default())
+        .map_err(|err| GitSubmoduleError::InvalidGitmodules(err.into()))?;
+    let entries = file
+        .names()
+        .filter_map(|name| {
+            let path = file.path(name).ok()?;
+            let url = file
+                .config()
+                .string(format!("submodule.{name}.url"))
+                .filter(|url| !url.is_empty());
+            Some((
+                name.to_str_lossy().into_owned(),
+                path.to_str_lossy().into_owned(),
+                url.map(|url| url.to_str_lossy().into_owned()),
+            ))
+        })
+        .collect();
+    Ok(entries)
+}
+
+/// Resolves the submodule `url` against the URL of the remote of the
+/// superproject, like `git submodule` does for URLs starting with `./` or
+/// `../`. Other URLs are returned as is.
+pub fn resolve_submodule_url(url: &str, base_url: Option<&str>) -> Option<String> {
+    if !url.starts_with("./") && !url.starts_with("../") {
+        return Some(url.to_owned());
+    }
+    let mut base = base_url?.trim_end_matches('/');
+    base = base.strip_suffix("/.git").unwrap_or(base);
+    let mut rest = url;
+    loop {
+        if let Some(stripped) = rest.strip_prefix("./") {
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix("../") {
+            rest = stripped;
+            // The host of scp-like URLs is separated by ':', which is kept.
+            let index = base.rfind(['/', ':'])?;
+            base = if base[index..].starts_with(':') {
+                &base[..=index]
+            } else {
+                &base[..index]
+            };
+        } else {
+            break;
+        }
+    }
+    let separator = if base.ends_with(':') { "" } else { "/" };
+    Some(format!("{base}{separator}{rest}"))
+}
+
+/// Returns the URL of the remote of the repository, which relative submodule
+/// URLs are resolved against.
+pub fn superproject_url(store: &Store) -> Option<String> {
+    let git_repo = get_git_repo(store).ok()?;
+    let remote = git_repo
+        .try_find_remote_without_url_rewrite(SUBMODULE_REMOTE_NAME)?
+        .ok()?;
+    let url = remote.url(gix::remote::Direction::Fetch)?;
+    Some(url.to_bstring().to_str_lossy().into_owned())
+}
+
+/// Returns the state of the `submodule` in the working copy at
+/// `workspace_root`.
+pub fn submodule_state(
+    workspace_root: &Path,
+    submodule: &Submodule,
+) -> Result<SubmoduleState, GitSubmoduleError> {
+    let disk_path = submodule.path.to_fs_path(workspace_root)?;
+    // `.git` may be a directory, or a file pointing to the Git directory.
+    if fs::symlink_metadata(disk_path.join(".git")).is_err() {
+        return Ok(SubmoduleState::Uninitialized);
+    }
+    let to_error = |err: Box<dyn std::error::Error + Send + Sync>| GitSubmoduleError::Repository {
+        path: disk_path.clone(),
+        source: err,
+    };
+    let repo = gix::open(&disk_path).map_err(|err| to_error(err.into()))?;
+    match repo.head_id() {
+        Ok(id) => Ok(SubmoduleState::CheckedOut(CommitId::from_bytes(
+            id.as_bytes(),
+        ))),
+        // The repository has been created, but nothing has been checked out.
+        Err(gix::reference::head_id::Error::PeelToId(_)) => Ok(SubmoduleState::Uninitialized),
+        Err(err) => Err(to_error(err.into())),
+    }
+}
+
+/// Checks out the recorded commit of the `submodule`, cloning it first if it
+/// isn't initialized.
+///
+/// Relative submodule URLs are resolved against `base_url`, which should be
+/// the URL of the remote of the superproject. Returns whether anything was
+/// changed.
+pub fn update_submodule(
+    workspace_root: &Path,
+    submodule: &Submodule,
+    base_url: Option<&str>,
+    options: GitSubprocessOptions,
+) -> Result<bool, GitSubmoduleError> {
+    let state = submodule_state(workspace_root, submodule)?;
+    if state == SubmoduleState::CheckedOut(submodule.commit_id.clone()) {
+        return Ok(false);
+    }
+    let disk_path = submodule.path.to_fs_path(workspace_root)?;
+    let to_error = |err: Box<dyn std::error::Error + Send + Sync>| GitSubmoduleError::Repository {
+        path: disk_path.clone(),
+        source: err,
+    };
+    let context = GitSubprocessContext::for_work_tree(&disk_path, options);
+    if fs::symlink_metadata(disk_path.join(".git")).is_err() {
+        let url = submodule
+            .url
+            .as_ref()
+            .ok_or_else(|| GitSubmoduleError::MissingUrl {
+                name: submodule.name.clone(),
+            })?;
+        let url = resolve_submodule_url(url, base_url).ok_or_else(|| {
+            GitSubmoduleError::UnresolvableUrl {
+                name: submodule.name.clone(),
+                url: url.clone(),
+            }
+        })?;
+        match fs::create_dir(&disk_path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(to_error(err.into())),
+        }
+        gix::init(&disk_path).map_err(|err| to_error(err.into()))?;
+        context.spawn_remote_add(SUBMODULE_REMOTE_NAME, &url)?;
+    }
+    let has_commit = || -> Result<bool, GitSubmoduleError> {
+        let repo = gix::open(&disk_path).map_err(|err| to_error(err.into()))?;
+        let id = gix::ObjectId::from_bytes_or_panic(submodule.commit_id.as_bytes());
+        Ok(repo.find_commit(id).is_ok())
+    };
+    if !has_commit()? {
+        context.spawn_fetch_commit(SUBMODULE_REMOTE_NAME, None)?;
+        if !has_commit()? {
+            context.spawn_fetch_commit(SUBMODULE_REMOTE_NAME, Some(&submodule.commit_id))?;
+        }
+    }
+    context.spawn_checkout_detached(&submodule.commit_id)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gitmodules() {
+        let content = br#"
+[submodule "foo"]
+	path = sub/foo
+	url = https://example.com/foo.git
+[submodule "no-url"]
+	path = bar
+[submodule "no-path"]
+	url = https://example.com/baz.git
+"#;
+        assert_eq!(
+            parse_gitmodules(content).unwrap(),
+            vec![
+                (
+                    "foo".to_owned(),
+                    "sub/foo".to_owned(),
+                    Some("https://example.com/foo.git".to_owned())
+                ),
+                ("no-url".to_owned(), "bar".to_owned(), None),
+            ]
+        );
+        assert!(parse_gitmodules(b"[submodule").is_err());
+    }
+
+    #[test]
+    fn test_resolve_submodule_url() {
+        let base = Some("https://example.com/org/repo.git");
+        assert_eq!(
+            resolve_submodule_url("https://example.com/other.git", base).as_deref(),
+            Some("https://example.com/other.git")
+        );
+        assert_eq!(
+            resolve_submodule_url("../sub.git", base).as_deref(),
+            Some("https://example.com/org/sub.git")
+        );
+        assert_eq!(
+            resolve_submodule_url("./sub.git", base).as_deref(),
+            Some("https://example.com/org/repo.git/sub.git")
+        );
+        assert_eq!(
+            resolve_submodule_url("../../sub.git", base).as_deref(),
+            Some("https://example.com/sub.git")
+        );
+        assert_eq!(
+            resolve_submodule_url("../sub.git", Some("git@example.com:org/repo.git")).as_deref(),
+            Some("git@example.com:org/sub.git")
+        );
+        assert_eq!(
+            resolve_submodule_url("../sub.git", Some("git@example.com:repo.git")).as_deref(),
+            Some("git@example.com:sub.git")
+        );
+        assert_eq!(
+            resolve_submodule_url("../sub", Some("/path/to/repo/")).as_deref(),
+            Some("/path/to/sub")
+        );
+        assert_eq!(resolve_submodule_url("../sub.git", None), None);
+    }
+}
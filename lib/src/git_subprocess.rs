@@ -15,7 +15,8 @@
 use std::io;
 use std::io::BufReader;
 use std::io::Read;
-use std::num::NonZeroU32;
+use std::io::Write as _;
+use std::path::Path;
 use std::path::PathBuf;
 use std::process::Child;
 use std::process::Command;
@@ -27,6 +28,8 @@ use bstr::ByteSlice as _;
 use itertools::Itertools as _;
 use thiserror::Error;
 
+use crate::backend::CommitId;
+use crate::git::FetchDepth;
 use crate::git::FetchTagsOverride;
 use crate::git::GitPushStats;
 use crate::git::GitSubprocessOptions;
@@ -36,6 +39,7 @@ use crate::git::RefSpec;
 use crate::git::RefToPush;
 use crate::git::RemoteCallbacks;
 use crate::git_backend::GitBackend;
+use crate::object_id::ObjectId as _;
 use crate::ref_name::GitRefNameBuf;
 use crate::ref_name::RefNameBuf;
 use crate::ref_name::RemoteName;
@@ -72,6 +76,8 @@ pub enum GitSubprocessError {
          {MINIMUM_GIT_VERSION})"
     )]
     UnsupportedGitOption(String),
+    #[error("Git LFS is not installed")]
+    LfsNotInstalled,
     #[error("Git process failed: {0}")]
     External(String),
 }
@@ -79,6 +85,7 @@ pub enum GitSubprocessError {
 /// Context for creating Git subprocesses
 pub(crate) struct GitSubprocessContext {
     git_dir: PathBuf,
+    work_tree: Option<PathBuf>,
     options: GitSubprocessOptions,
 }
 
@@ -86,6 +93,17 @@ impl GitSubprocessContext {
     pub(crate) fn new(git_dir: impl Into<PathBuf>, options: GitSubprocessOptions) -> Self {
         Self {
             git_dir: git_dir.into(),
+            work_tree: None,
+            options,
+        }
+    }
+
+    /// Creates a context for the non-bare repository checked out at
+    /// `work_tree`, such as a submodule.
+    pub(crate) fn for_work_tree(work_tree: &Path, options: GitSubprocessOptions) -> Self {
+        Self {
+            git_dir: work_tree.join(".git"),
+            work_tree: Some(work_tree.to_owned()),
             options,
         }
     }
@@ -136,6 +154,12 @@ impl GitSubprocessContext {
             .stdin(Stdio::null())
             .stderr(Stdio::piped());
 
+        if let Some(helper) = &self.options.credential_helper {
+            git_cmd.arg("-c").arg(format!("credential.helper={helper}"));
+        }
+        if let Some(work_tree) = &self.work_tree {
+            git_cmd.arg("--work-tree").arg(work_tree);
+        }
         git_cmd.envs(&self.options.environment);
 
         git_cmd
@@ -164,13 +188,15 @@ impl GitSubprocessContext {
     ///
     /// This returns a fully qualified ref that wasn't fetched successfully
     /// Note that git only returns one failed ref at a time
+    #[expect(clippy::too_many_arguments)]
     pub(crate) fn spawn_fetch(
         &self,
         remote_name: &RemoteName,
         refspecs: &[RefSpec],
         negative_refspecs: &[NegativeRefSpec],
         callbacks: &mut RemoteCallbacks<'_>,
-        depth: Option<NonZeroU32>,
+        depth: Option<FetchDepth>,
+        filter: Option<&str>,
         fetch_tags_override: Option<FetchTagsOverride>,
     ) -> Result<Option<String>, GitSubprocessError> {
         if refspecs.is_empty() {
@@ -184,8 +210,20 @@ impl GitSubprocessContext {
         if callbacks.progress.is_some() {
             command.arg("--progress");
         }
-        if let Some(d) = depth {
-            command.arg(format!("--depth={d}"));
+        match depth {
+            Some(FetchDepth::Depth(d)) => {
+                command.arg(format!("--depth={d}"));
+            }
+            Some(FetchDepth::Deepen(d)) => {
+                command.arg(format!("--deepen={d}"));
+            }
+            Some(FetchDepth::Unshallow) => {
+                command.arg("--unshallow");
+            }
+            None => {}
+        }
+        if let Some(filter) = filter {
+            command.arg(format!("--filter={filter}"));
         }
         match fetch_tags_override {
             Some(FetchTagsOverride::AllTags) => {
@@ -278,6 +316,12 @@ impl GitSubprocessContext {
         if callbacks.progress.is_some() {
             command.arg("--progress");
         }
+        command.args(
+            self.options
+                .push_options
+                .iter()
+                .map(|option| format!("--push-option={option}")),
+        );
         command.args(
             references
                 .iter()
@@ -296,6 +340,202 @@ impl GitSubprocessContext {
 
         parse_git_push_output(output)
     }
+
+    /// Download the Git LFS objects referenced by the trees of `refs`
+    ///
+    /// `git lfs` batches the requests, and skips objects which are already in
+    /// the local object cache.
+    pub(crate) fn spawn_lfs_fetch(
+        &self,
+        remote_name: &RemoteName,
+        refs: &[String],
+    ) -> Result<(), GitSubprocessError> {
+        if refs.is_empty() {
+            return Ok(());
+        }
+        let mut command = self.create_command();
+        command.stdout(Stdio::null());
+        command.args(["lfs", "fetch", "--", remote_name.as_str()]);
+        command.args(refs);
+        let output = wait_with_output(self.spawn_cmd(command)?)?;
+        parse_git_lfs_output(output)
+    }
+
+    /// Upload the Git LFS objects referenced by the history of `commits`
+    ///
+    /// Objects which are reachable from the remote-tracking refs of the remote
+    /// are assumed to be uploaded already.
+    pub(crate) fn spawn_lfs_push(
+        &self,
+        remote_name: &RemoteName,
+        commits: &[String],
+    ) -> Result<(), GitSubprocessError> {
+        if commits.is_empty() {
+            return Ok(());
+        }
+        let mut command = self.create_command();
+        command.stdout(Stdio::null());
+        command.args(["lfs", "push", "--", remote_name.as_str()]);
+        command.args(commits);
+        let output = wait_with_output(self.spawn_cmd(command)?)?;
+        parse_git_lfs_output(output)
+    }
+
+    /// Add a remote named `remote_name` fetching from `url`
+    pub(crate) fn spawn_remote_add(
+        &self,
+        remote_name: &str,
+        url: &str,
+    ) -> Result<(), GitSubprocessError> {
+        let mut command = self.create_command();
+        command.stdout(Stdio::null());
+        command.args(["remote", "add", "--", remote_name, url]);
+        let output = wait_with_output(self.spawn_cmd(command)?)?;
+        parse_git_simple_output(output)
+    }
+
+    /// Fetch the branches of the remote, or only `commit_id` if specified
+    ///
+    /// Fetching a commit by id requires the server to allow it, which most
+    /// servers do for commits reachable from a ref.
+    pub(crate) fn spawn_fetch_commit(
+        &self,
+        remote_name: &str,
+        commit_id: Option<&CommitId>,
+    ) -> Result<(), GitSubprocessError> {
+        let mut command = self.create_command();
+        command.stdout(Stdio::null());
+        command.args([
+            "fetch",
+            "--quiet",
+            "--no-write-fetch-head",
+            "--",
+            remote_name,
+        ]);
+        if let Some(commit_id) = commit_id {
+            command.arg(commit_id.hex());
+        }
+        let output = wait_with_output(self.spawn_cmd(command)?)?;
+        parse_git_simple_output(output)
+    }
+
+    /// Fetch the `commit_ids` from the Git repository at `source_git_dir`,
+    /// creating `refs/jj/keep/*` refs for them locally
+    ///
+    /// The commits don't have to be referenced in the source repository.
+    pub(crate) fn spawn_fetch_commits(
+        &self,
+        source_git_dir: &Path,
+        commit_ids: &[CommitId],
+    ) -> Result<(), GitSubprocessError> {
+        let mut command = self.create_command();
+        command.stdin(Stdio::piped()).stdout(Stdio::null());
+        // The refspecs are passed on stdin as there can be many of them.
+        command.args(["fetch", "--quiet", "--no-write-fetch-head", "--stdin", "--"]);
+        command.arg(source_git_dir);
+        let mut child = self.spawn_cmd(command)?;
+        let mut stdin = child.stdin.take().expect("stdin should be piped");
+        let refspecs: String = commit_ids
+            .iter()
+            .map(|id| format!("+{id}:refs/jj/keep/{id}\n", id = id.hex()))
+            .collect();
+        // Git reports the error if it exits without reading the input.
+        stdin.write_all(refspecs.as_bytes()).ok();
+        drop(stdin);
+        let output = wait_with_output(child)?;
+        parse_git_simple_output(output)
+    }
+
+    /// Fetch the notes ref `notes_ref` of the remote into the same local ref
+    ///
+    /// The local ref is only updated if it's a fast-forward, so that notes
+    /// which haven't been pushed yet aren't lost.
+    pub(crate) fn spawn_fetch_notes(
+        &self,
+        remote_name: &RemoteName,
+        notes_ref: &str,
+    ) -> Result<(), GitSubprocessError> {
+        let mut command = self.create_command();
+        command.stdout(Stdio::null());
+        // Without --quiet, rejected updates are reported on stderr
+        command.args(["fetch", "--no-write-fetch-head", "--", remote_name.as_str()]);
+        command.arg(format!("{notes_ref}:{notes_ref}"));
+        let output = wait_with_output(self.spawn_cmd(command)?)?;
+        parse_git_simple_output(output)
+    }
+
+    /// Push the notes ref `notes_ref` to the same ref of the remote
+    ///
+    /// Like `git push`, this is rejected if it isn't a fast-forward.
+    pub(crate) fn spawn_push_notes(
+        &self,
+        remote_name: &RemoteName,
+        notes_ref: &str,
+    ) -> Result<(), GitSubprocessError> {
+        let mut command = self.create_command();
+        command.stdout(Stdio::null());
+        command.args(["push", "--quiet", "--no-verify", "--", remote_name.as_str()]);
+        command.arg(format!("{notes_ref}:{notes_ref}"));
+        let output = wait_with_output(self.spawn_cmd(command)?)?;
+        parse_git_simple_output(output)
+    }
+
+    /// Fetch the refs under `remote_prefix` of the remote into the same names
+    /// under `local_prefix`, removing the local refs which no longer exist on
+    /// the remote
+    pub(crate) fn spawn_fetch_ref_namespace(
+        &self,
+        remote_name: &RemoteName,
+        remote_prefix: &str,
+        local_prefix: &str,
+    ) -> Result<(), GitSubprocessError> {
+        let mut command = self.create_command();
+        command.stdout(Stdio::null());
+        command.args([
+            "fetch",
+            "--quiet",
+            "--prune",
+            "--no-tags",
+            "--no-write-fetch-head",
+            "--",
+            remote_name.as_str(),
+        ]);
+        command.arg(format!("+{remote_prefix}*:{local_prefix}*"));
+        let output = wait_with_output(self.spawn_cmd(command)?)?;
+        parse_git_simple_output(output)
+    }
+
+    /// Point the refs of the remote to the given objects, or delete them if
+    /// the object is `None`
+    pub(crate) fn spawn_push_refs(
+        &self,
+        remote_name: &RemoteName,
+        updates: &[(String, Option<gix::ObjectId>)],
+    ) -> Result<(), GitSubprocessError> {
+        let mut command = self.create_command();
+        command.stdout(Stdio::null());
+        command.args(["push", "--quiet", "--no-verify", "--", remote_name.as_str()]);
+        command.args(updates.iter().map(|(ref_name, id)| match id {
+            Some(id) => format!("{id}:{ref_name}"),
+            None => format!(":{ref_name}"),
+        }));
+        let output = wait_with_output(self.spawn_cmd(command)?)?;
+        parse_git_simple_output(output)
+    }
+
+    /// Check out `commit_id` in the work tree, detaching HEAD
+    pub(crate) fn spawn_checkout_detached(
+        &self,
+        commit_id: &CommitId,
+    ) -> Result<(), GitSubprocessError> {
+        let mut command = self.create_command();
+        command.stdout(Stdio::null());
+        command.args(["checkout", "--quiet", "--detach"]);
+        command.arg(commit_id.hex());
+        command.arg("--");
+        let output = wait_with_output(self.spawn_cmd(command)?)?;
+        parse_git_simple_output(output)
+    }
 }
 
 /// Generate a GitSubprocessError::ExternalGitError if the stderr output was not
@@ -430,6 +670,35 @@ fn parse_git_branch_prune_output(output: Output) -> Result<(), GitSubprocessErro
     Err(external_git_error(&output.stderr))
 }
 
+fn parse_git_simple_output(output: Output) -> Result<(), GitSubprocessError> {
+    if output.status.success() {
+        return Ok(());
+    }
+
+    if let Some(option) = parse_unknown_option(&output.stderr) {
+        return Err(GitSubprocessError::UnsupportedGitOption(option));
+    }
+
+    Err(external_git_error(&output.stderr))
+}
+
+fn parse_git_lfs_output(output: Output) -> Result<(), GitSubprocessError> {
+    if output.status.success() {
+        return Ok(());
+    }
+
+    if parse_lfs_not_installed(&output.stderr) {
+        return Err(GitSubprocessError::LfsNotInstalled);
+    }
+
+    Err(external_git_error(&output.stderr))
+}
+
+/// Parse the error printed by git if `git lfs` isn't installed
+fn parse_lfs_not_installed(stderr: &[u8]) -> bool {
+    stderr.starts_with(b"git: 'lfs' is not a git command")
+}
+
 fn parse_git_remote_show_output(output: Output) -> Result<Output, GitSubprocessError> {
     if output.status.success() {
         return Ok(output);
@@ -479,6 +748,9 @@ fn parse_git_remote_show_default_branch(
 // <summary> is extra info (commit ranges or reason for rejected)
 //
 // <reason> is a human-readable explanation
+//
+// If the remote has several push URLs, a block of lines starting with `To
+// <url>` and ending with `Done` is printed for each of them.
 fn parse_ref_pushes(stdout: &[u8]) -> Result<GitPushStats, GitSubprocessError> {
     if !stdout.starts_with(b"To ") {
         return Err(GitSubprocessError::External(format!(
@@ -487,79 +759,112 @@ fn parse_ref_pushes(stdout: &[u8]) -> Result<GitPushStats, GitSubprocessError> {
         )));
     }
 
-    let mut push_stats = GitPushStats::default();
-    for (idx, line) in stdout
-        .lines()
-        .skip(1)
-        .take_while(|line| line != b"Done")
-        .enumerate()
-    {
-        tracing::debug!("response #{idx}: {}", line.to_str_lossy());
-        let [flag, reference, summary] = line.split_str("\t").collect_array().ok_or_else(|| {
+    let mut url_push_stats: Vec<GitPushStats> = vec![];
+    for (idx, line) in stdout.lines().enumerate() {
+        if line.starts_with(b"To ") {
+            url_push_stats.push(GitPushStats::default());
+            continue;
+        } else if line == b"Done" {
+            continue;
+        }
+        let push_stats = url_push_stats.last_mut().unwrap();
+        parse_ref_push_line(idx, line, push_stats)?;
+    }
+
+    // A ref is pushed only if it was accepted by all URLs.
+    let mut url_push_stats = url_push_stats.into_iter();
+    let mut push_stats = url_push_stats.next().unwrap();
+    for stats in url_push_stats {
+        push_stats
+            .pushed
+            .retain(|reference| stats.pushed.contains(reference));
+        for (reference, reason) in stats.rejected {
+            if !push_stats.rejected.iter().any(|(r, _)| *r == reference) {
+                push_stats.rejected.push((reference, reason));
+            }
+        }
+        for (reference, reason) in stats.remote_rejected {
+            if !push_stats
+                .remote_rejected
+                .iter()
+                .any(|(r, _)| *r == reference)
+            {
+                push_stats.remote_rejected.push((reference, reason));
+            }
+        }
+    }
+    Ok(push_stats)
+}
+
+fn parse_ref_push_line(
+    idx: usize,
+    line: &[u8],
+    push_stats: &mut GitPushStats,
+) -> Result<(), GitSubprocessError> {
+    tracing::debug!("response #{idx}: {}", line.to_str_lossy());
+    let [flag, reference, summary] = line.split_str("\t").collect_array().ok_or_else(|| {
+        GitSubprocessError::External(format!(
+            "Line #{idx} of git-push has unknown format: {}",
+            line.to_str_lossy()
+        ))
+    })?;
+    let full_refspec = reference
+        .to_str()
+        .map_err(|e| {
+            format!(
+                "Line #{} of git-push has non-utf8 refspec {}: {}",
+                idx,
+                reference.to_str_lossy(),
+                e
+            )
+        })
+        .map_err(GitSubprocessError::External)?;
+
+    let reference: GitRefNameBuf = full_refspec
+        .split_once(':')
+        .map(|(_refname, reference)| reference.into())
+        .ok_or_else(|| {
             GitSubprocessError::External(format!(
-                "Line #{idx} of git-push has unknown format: {}",
-                line.to_str_lossy()
+                "Line #{idx} of git-push has full refspec without named ref: {full_refspec}"
             ))
         })?;
-        let full_refspec = reference
-            .to_str()
-            .map_err(|e| {
-                format!(
-                    "Line #{} of git-push has non-utf8 refspec {}: {}",
-                    idx,
-                    reference.to_str_lossy(),
-                    e
-                )
-            })
-            .map_err(GitSubprocessError::External)?;
-
-        let reference: GitRefNameBuf = full_refspec
-            .split_once(':')
-            .map(|(_refname, reference)| reference.into())
-            .ok_or_else(|| {
-                GitSubprocessError::External(format!(
-                    "Line #{idx} of git-push has full refspec without named ref: {full_refspec}"
-                ))
-            })?;
-
-        match flag {
-            // ' ' for a successfully pushed fast-forward;
-            //  + for a successful forced update
-            //  - for a successfully deleted ref
-            //  * for a successfully pushed new ref
-            //  =  for a ref that was up to date and did not need pushing.
-            b"+" | b"-" | b"*" | b"=" | b" " => {
-                push_stats.pushed.push(reference);
-            }
-            // ! for a ref that was rejected or failed to push; and
-            b"!" => {
-                if let Some(reason) = summary.strip_prefix(b"[remote rejected]") {
-                    let reason = reason
-                        .strip_prefix(b" (")
-                        .and_then(|r| r.strip_suffix(b")"))
-                        .map(|x| x.to_str_lossy().into_owned());
-                    push_stats.remote_rejected.push((reference, reason));
-                } else {
-                    let reason = summary
-                        .split_once_str("]")
-                        .and_then(|(_, reason)| reason.strip_prefix(b" ("))
-                        .and_then(|r| r.strip_suffix(b")"))
-                        .map(|x| x.to_str_lossy().into_owned());
-                    push_stats.rejected.push((reference, reason));
-                }
-            }
-            unknown => {
-                return Err(GitSubprocessError::External(format!(
-                    "Line #{} of git-push starts with an unknown flag '{}': '{}'",
-                    idx,
-                    unknown.to_str_lossy(),
-                    line.to_str_lossy()
-                )));
+
+    match flag {
+        // ' ' for a successfully pushed fast-forward;
+        //  + for a successful forced update
+        //  - for a successfully deleted ref
+        //  * for a successfully pushed new ref
+        //  =  for a ref that was up to date and did not need pushing.
+        b"+" | b"-" | b"*" | b"=" | b" " => {
+            push_stats.pushed.push(reference);
+        }
+        // ! for a ref that was rejected or failed to push; and
+        b"!" => {
+            if let Some(reason) = summary.strip_prefix(b"[remote rejected]") {
+                let reason = reason
+                    .strip_prefix(b" (")
+                    .and_then(|r| r.strip_suffix(b")"))
+                    .map(|x| x.to_str_lossy().into_owned());
+                push_stats.remote_rejected.push((reference, reason));
+            } else {
+                let reason = summary
+                    .split_once_str("]")
+                    .and_then(|(_, reason)| reason.strip_prefix(b" ("))
+                    .and_then(|r| r.strip_suffix(b")"))
+                    .map(|x| x.to_str_lossy().into_owned());
+                push_stats.rejected.push((reference, reason));
             }
         }
+        unknown => {
+            return Err(GitSubprocessError::External(format!(
+                "Line #{} of git-push starts with an unknown flag '{}': '{}'",
+                idx,
+                unknown.to_str_lossy(),
+                line.to_str_lossy()
+            )));
+        }
     }
-
-    Ok(push_stats)
+    Ok(())
 }
 
 // on Ok, return a tuple with
@@ -921,6 +1226,42 @@ Done";
         assert!(parse_ref_pushes(SAMPLE_OK_STDERR).is_err());
     }
 
+    #[test]
+    fn test_parse_ref_pushes_multiple_urls() {
+        let stdout = b"To primary
+*\tdeadbeef:refs/heads/bookmark1\t[new branch]
+*\tdeadbeef:refs/heads/bookmark2\t[new branch]
+!\tdeadbeef:refs/heads/bookmark3\t[rejected] (stale info)
+Done
+To mirror
+*\tdeadbeef:refs/heads/bookmark1\t[new branch]
+!\tdeadbeef:refs/heads/bookmark2\t[remote rejected] (hook declined)
+!\tdeadbeef:refs/heads/bookmark3\t[rejected] (stale info)
+Done
+";
+        let GitPushStats {
+            pushed,
+            rejected,
+            remote_rejected,
+            unexported_bookmarks: _,
+        } = parse_ref_pushes(stdout).unwrap();
+        assert_eq!(pushed, [GitRefNameBuf::from("refs/heads/bookmark1")]);
+        assert_eq!(
+            rejected,
+            [(
+                "refs/heads/bookmark3".into(),
+                Some("stale info".to_string())
+            )]
+        );
+        assert_eq!(
+            remote_rejected,
+            [(
+                "refs/heads/bookmark2".into(),
+                Some("hook declined".to_string())
+            )]
+        );
+    }
+
     #[test]
     fn test_read_to_end_with_progress() {
         let read = |sample: &[u8]| {
@@ -992,6 +1333,17 @@ Done";
         assert_eq!(read_progress_line(b"fatal: this is a git error\n"), None);
     }
 
+    #[test]
+    fn test_parse_lfs_not_installed() {
+        assert!(parse_lfs_not_installed(
+            b"git: 'lfs' is not a git command. See 'git --help'.\n"
+        ));
+        assert!(!parse_lfs_not_installed(
+            b"batch request: missing protocol\n"
+        ));
+        assert!(!parse_lfs_not_installed(SAMPLE_OK_STDERR));
+    }
+
     #[test]
     fn test_parse_unknown_option() {
         assert_eq!(
@@ -0,0 +1,388 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing and matching of `.gitattributes` files.
+
+use std::collections::HashMap;
+use std::iter;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use ignore::gitignore;
+use thiserror::Error;
+
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::repo_path::RepoPathComponent;
+
+/// Error while parsing a `.gitattributes` file.
+#[derive(Debug, Error)]
+pub enum GitAttributesError {
+    /// The file contains invalid UTF-8.
+    #[error("Invalid UTF-8 for attributes in {path} on line #{line_num_for_display}: {line}")]
+    InvalidUtf8 {
+        /// Path to the `.gitattributes` file.
+        path: PathBuf,
+        /// 1-based line number.
+        line_num_for_display: usize,
+        /// The invalid line.
+        line: String,
+        /// Source error.
+        source: std::str::Utf8Error,
+    },
+    /// The file contains an invalid pattern.
+    #[error("Failed to parse attribute pattern in {path} on line #{line_num_for_display}")]
+    Pattern {
+        /// Path to the `.gitattributes` file.
+        path: PathBuf,
+        /// 1-based line number.
+        line_num_for_display: usize,
+        /// Source error.
+        source: ignore::Error,
+    },
+    /// The file couldn't be read.
+    #[error("Failed to read attributes from file {path}")]
+    ReadFile {
+        /// Path to the `.gitattributes` file.
+        path: PathBuf,
+        /// Source error.
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+/// State of an attribute which is specified for a path.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AttributeState {
+    /// The attribute is set, e.g. `text`.
+    Set,
+    /// The attribute is unset, e.g. `-text`.
+    Unset,
+    /// The attribute is set to a value, e.g. `eol=crlf`.
+    Value(String),
+}
+
+/// Attributes which apply to a single path.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GitAttributes {
+    states: HashMap<String, AttributeState>,
+}
+
+impl GitAttributes {
+    /// Returns the state of the attribute `name`, or `None` if it's
+    /// unspecified.
+    pub fn get(&self, name: &str) -> Option<&AttributeState> {
+        self.states.get(name)
+    }
+
+    /// Returns the value of the attribute `name` if it's set to a value.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            AttributeState::Value(value) => Some(value),
+            AttributeState::Set | AttributeState::Unset => None,
+        }
+    }
+
+    /// Returns true if no attribute is specified.
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+}
+
+#[derive(Debug)]
+struct AttributesLine {
+    matcher: gitignore::Gitignore,
+    // `None` state means the attribute is explicitly unspecified (`!attr`).
+    attributes: Vec<(String, Option<AttributeState>)>,
+}
+
+/// Models the effective contents of multiple `.gitattributes` files.
+#[derive(Debug)]
+pub struct GitAttributesFile {
+    parent: Option<Arc<Self>>,
+    // Directory of the lines, with a trailing slash unless it's the root.
+    prefix: String,
+    lines: Vec<AttributesLine>,
+}
+
+impl GitAttributesFile {
+    /// Creates an empty attributes file.
+    pub fn empty() -> Arc<Self> {
+        Arc::new(Self {
+            parent: None,
+            prefix: String::new(),
+            lines: vec![],
+        })
+    }
+
+    /// Concatenates new `.gitattributes` content at the `prefix` directory.
+    ///
+    /// The `prefix` should be a slash-separated path relative to the workspace
+    /// root.
+    pub fn chain(
+        self: &Arc<Self>,
+        prefix: &str,
+        attributes_path: &Path,
+        input: &[u8],
+    ) -> Result<Arc<Self>, GitAttributesError> {
+        let mut lines = vec![];
+        for (i, input_line) in input.split(|b| *b == b'\n').enumerate() {
+            let line =
+                str::from_utf8(input_line).map_err(|err| GitAttributesError::InvalidUtf8 {
+                    path: attributes_path.to_path_buf(),
+                    line_num_for_display: i + 1,
+                    line: String::from_utf8_lossy(input_line).to_string(),
+                    source: err,
+                })?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_ascii_whitespace();
+            let pattern = words.next().unwrap();
+            // Negative patterns are forbidden, and patterns matching directories
+            // don't apply to the files in them.
+            if pattern.starts_with('!') || pattern.ends_with('/') {
+                continue;
+            }
+            let mut builder = gitignore::GitignoreBuilder::new(prefix);
+            builder
+                .add_line(None, pattern)
+                .map_err(|err| GitAttributesError::Pattern {
+                    path: attributes_path.to_path_buf(),
+                    line_num_for_display: i + 1,
+                    source: err,
+                })?;
+            let matcher = builder.build().map_err(|err| GitAttributesError::Pattern {
+                path: attributes_path.to_path_buf(),
+                line_num_for_display: i + 1,
+                source: err,
+            })?;
+            let attributes = words.flat_map(parse_attribute).collect();
+            lines.push(AttributesLine {
+                matcher,
+                attributes,
+            });
+        }
+        let parent = if self.lines.is_empty() {
+            self.parent.clone() // omit the empty root
+        } else {
+            Some(self.clone())
+        };
+        Ok(Arc::new(Self {
+            parent,
+            prefix: prefix.to_owned(),
+            lines,
+        }))
+    }
+
+    /// Returns the attributes of the file at `path`.
+    ///
+    /// The `path` should be a slash-separated path relative to the workspace
+    /// root.
+    pub fn attributes(&self, path: &str) -> GitAttributes {
+        let files = iter::successors(Some(self), |file| file.parent.as_deref()).collect::<Vec<_>>();
+        let mut states = HashMap::new();
+        // Lines in deeper files, and later lines in the same file, take
+        // precedence.
+        for file in files.iter().rev() {
+            if !path.starts_with(&file.prefix) {
+                continue;
+            }
+            for line in &file.lines {
+                if !line.matcher.matched(path, false).is_ignore() {
+                    continue;
+                }
+                for (name, state) in &line.attributes {
+                    match state {
+                        Some(state) => states.insert(name.clone(), state.clone()),
+                        None => states.remove(name),
+                    };
+                }
+            }
+        }
+        GitAttributes { states }
+    }
+}
+
+type DynError = Box<dyn std::error::Error + Send + Sync>;
+
+type LoadFn<'a> = dyn Fn(&RepoPath) -> Result<Option<Vec<u8>>, DynError> + Send + Sync + 'a;
+
+/// Loads the `.gitattributes` files of directories as they're needed.
+pub(crate) struct GitAttributesLoader<'a> {
+    load: Box<LoadFn<'a>>,
+    files: Mutex<HashMap<RepoPathBuf, Arc<GitAttributesFile>>>,
+}
+
+impl<'a> GitAttributesLoader<'a> {
+    /// Creates a loader which reads the contents of `.gitattributes` files
+    /// with `load`. If `load` returns `None`, the file doesn't exist.
+    pub(crate) fn new(
+        load: impl Fn(&RepoPath) -> Result<Option<Vec<u8>>, DynError> + Send + Sync + 'a,
+    ) -> Self {
+        Self {
+            load: Box::new(load),
+            files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Creates a loader which doesn't find any attributes.
+    pub(crate) fn empty() -> Self {
+        Self::new(|_| Ok(None))
+    }
+
+    /// Returns the attributes of the file at `path`.
+    pub(crate) fn attributes(&self, path: &RepoPath) -> Result<GitAttributes, GitAttributesError> {
+        let dir = path.parent().expect("file path shouldn't be the root");
+        let file = self.file_for_dir(dir)?;
+        Ok(file.attributes(path.as_internal_file_string()))
+    }
+
+    fn file_for_dir(&self, dir: &RepoPath) -> Result<Arc<GitAttributesFile>, GitAttributesError> {
+        if let Some(file) = self.files.lock().unwrap().get(dir) {
+            return Ok(file.clone());
+        }
+        let parent = match dir.parent() {
+            Some(parent) => self.file_for_dir(parent)?,
+            None => GitAttributesFile::empty(),
+        };
+        let attributes_path = dir.join(RepoPathComponent::new(".gitattributes").unwrap());
+        let fs_path = attributes_path.to_fs_path_unchecked(Path::new(""));
+        let input =
+            (self.load)(&attributes_path).map_err(|source| GitAttributesError::ReadFile {
+                path: fs_path.clone(),
+                source,
+            })?;
+        let file = if let Some(input) = input {
+            let prefix = if dir.is_root() {
+                String::new()
+            } else {
+                format!("{}/", dir.as_internal_file_string())
+            };
+            parent.chain(&prefix, &fs_path, &input)?
+        } else {
+            parent
+        };
+        self.files
+            .lock()
+            .unwrap()
+            .insert(dir.to_owned(), file.clone());
+        Ok(file)
+    }
+}
+
+fn parse_attribute(word: &str) -> Vec<(String, Option<AttributeState>)> {
+    let (name, state) = if let Some(name) = word.strip_prefix('-') {
+        (name, Some(AttributeState::Unset))
+    } else if let Some(name) = word.strip_prefix('!') {
+        (name, None)
+    } else if let Some((name, value)) = word.split_once('=') {
+        (name, Some(AttributeState::Value(value.to_owned())))
+    } else {
+        (word, Some(AttributeState::Set))
+    };
+    // The only built-in macro attribute.
+    if name == "binary" && state == Some(AttributeState::Set) {
+        ["diff", "merge", "text"]
+            .map(|name| (name.to_owned(), Some(AttributeState::Unset)))
+            .into_iter()
+            .chain([(name.to_owned(), state)])
+            .collect()
+    } else {
+        vec![(name.to_owned(), state)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attributes(file: &Arc<GitAttributesFile>, path: &str) -> Vec<(String, AttributeState)> {
+        let mut states = file.attributes(path).states.into_iter().collect::<Vec<_>>();
+        states.sort();
+        states
+    }
+
+    fn set(name: &str) -> (String, AttributeState) {
+        (name.to_owned(), AttributeState::Set)
+    }
+
+    fn unset(name: &str) -> (String, AttributeState) {
+        (name.to_owned(), AttributeState::Unset)
+    }
+
+    fn value(name: &str, value: &str) -> (String, AttributeState) {
+        (name.to_owned(), AttributeState::Value(value.to_owned()))
+    }
+
+    #[test]
+    fn test_gitattributes_patterns() {
+        let file = GitAttributesFile::empty()
+            .chain(
+                "",
+                Path::new(""),
+                b"# comment\n*.txt text\n/root.bin -text\ndir/*.c eol=crlf\ndir/ text\n",
+            )
+            .unwrap();
+        assert_eq!(attributes(&file, "a.txt"), vec![set("text")]);
+        assert_eq!(attributes(&file, "dir/sub/a.txt"), vec![set("text")]);
+        assert_eq!(attributes(&file, "root.bin"), vec![unset("text")]);
+        assert_eq!(attributes(&file, "dir/root.bin"), vec![]);
+        assert_eq!(attributes(&file, "dir/a.c"), vec![value("eol", "crlf")]);
+        assert_eq!(attributes(&file, "dir/sub/a.c"), vec![]);
+        assert_eq!(attributes(&file, "dir/a.rs"), vec![]);
+    }
+
+    #[test]
+    fn test_gitattributes_precedence() {
+        let file = GitAttributesFile::empty()
+            .chain("", Path::new(""), b"* text filter=a\n*.bin binary\n")
+            .unwrap()
+            .chain("dir/", Path::new(""), b"*.bin !text\n*.txt filter=b\n")
+            .unwrap();
+        assert_eq!(
+            attributes(&file, "a.txt"),
+            vec![value("filter", "a"), set("text")]
+        );
+        assert_eq!(
+            attributes(&file, "a.bin"),
+            vec![
+                set("binary"),
+                unset("diff"),
+                value("filter", "a"),
+                unset("merge"),
+                unset("text"),
+            ]
+        );
+        assert_eq!(
+            attributes(&file, "dir/a.txt"),
+            vec![value("filter", "b"), set("text")]
+        );
+        assert_eq!(
+            attributes(&file, "dir/a.bin"),
+            vec![
+                set("binary"),
+                unset("diff"),
+                value("filter", "a"),
+                unset("merge"),
+            ]
+        );
+        assert_eq!(
+            attributes(&file, "other/a.txt"),
+            vec![value("filter", "a"), set("text")]
+        );
+    }
+}
@@ -38,6 +38,7 @@ pub mod config;
 mod config_resolver;
 pub mod conflict_labels;
 pub mod conflicts;
+pub mod content_filter;
 pub mod copies;
 pub mod dag_walk;
 pub mod default_index;
@@ -54,13 +55,24 @@ pub mod fileset;
 mod fileset_parser;
 pub mod fix;
 pub mod fmt_util;
+pub mod fsck;
 pub mod fsmonitor;
 #[cfg(feature = "git")]
 pub mod git;
 #[cfg(feature = "git")]
 pub mod git_backend;
 #[cfg(feature = "git")]
+pub mod git_credential;
+pub mod git_lfs;
+#[cfg(feature = "git")]
+pub mod git_notes;
+#[cfg(feature = "git")]
+pub mod git_oauth;
+#[cfg(feature = "git")]
+pub mod git_submodule;
+#[cfg(feature = "git")]
 mod git_subprocess;
+pub mod gitattributes;
 pub mod gitignore;
 pub mod gpg_signing;
 pub mod graph;
@@ -77,14 +89,20 @@ pub mod merged_tree_builder;
 pub mod object_id;
 pub mod op_heads_store;
 pub mod op_store;
+#[cfg(feature = "git")]
+pub mod op_sync;
 pub mod op_walk;
 pub mod operation;
+pub mod plugin_backend;
+pub mod progress;
+pub mod protected_bookmarks;
 #[expect(missing_docs)]
 pub mod protos;
 pub mod ref_name;
 pub mod refs;
 pub mod repo;
 pub mod repo_path;
+pub mod rerere;
 pub mod revset;
 mod revset_parser;
 pub mod rewrite;
@@ -98,8 +116,10 @@ pub mod tree_merge;
 // in the lib it should be moved to the examples (e.g
 // "examples/simple-backend/").
 pub mod simple_backend;
+mod simple_encryption;
 pub mod simple_op_heads_store;
 pub mod simple_op_store;
+mod simple_pack;
 pub mod ssh_signing;
 pub mod stacked_table;
 pub mod store;
@@ -16,16 +16,19 @@
 
 use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::error::Error;
 use std::fs;
-use std::fs::DirEntry;
 use std::fs::File;
 use std::fs::Metadata;
 use std::fs::OpenOptions;
 use std::io;
+use std::io::Cursor;
 use std::io::Read as _;
+use std::io::Seek as _;
+use std::io::SeekFrom;
 use std::io::Write as _;
 use std::iter;
 use std::mem;
@@ -37,11 +40,16 @@ use std::path::PathBuf;
 use std::slice;
 use std::sync::Arc;
 use std::sync::OnceLock;
+use std::sync::atomic;
+use std::sync::atomic::AtomicU64;
 use std::sync::mpsc::Sender;
 use std::sync::mpsc::channel;
 use std::time::SystemTime;
 
 use async_trait::async_trait;
+use blake2::Blake2b512;
+use blake2::Digest as _;
+use bstr::ByteSlice as _;
 use either::Either;
 use futures::StreamExt as _;
 use itertools::EitherOrBoth;
@@ -49,7 +57,7 @@ use itertools::Itertools as _;
 use once_cell::unsync::OnceCell;
 use pollster::FutureExt as _;
 use prost::Message as _;
-use rayon::iter::IntoParallelIterator as _;
+use rayon::iter::IntoParallelRefIterator as _;
 use rayon::prelude::IndexedParallelIterator as _;
 use rayon::prelude::ParallelIterator as _;
 use tempfile::NamedTempFile;
@@ -58,6 +66,7 @@ use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt as _;
 use tracing::instrument;
 use tracing::trace_span;
+use unicode_normalization::UnicodeNormalization as _;
 
 use crate::backend::BackendError;
 use crate::backend::CopyId;
@@ -77,7 +86,13 @@ use crate::conflicts::MaterializedTreeValue;
 use crate::conflicts::choose_materialized_conflict_marker_len;
 use crate::conflicts::materialize_merge_result_to_bytes;
 use crate::conflicts::materialize_tree_value;
+use crate::content_filter::ContentFilters;
+use crate::content_filter::FilterDirection;
+pub use crate::content_filter::FilterDriver;
+use crate::content_filter::collapse_ident;
+use crate::content_filter::expand_ident;
 pub use crate::eol::EolConversionMode;
+pub use crate::eol::EolConversionRule;
 use crate::eol::TargetEolStrategy;
 use crate::file_util::BlockingAsyncReader;
 use crate::file_util::FileIdentity;
@@ -85,11 +100,21 @@ use crate::file_util::check_symlink_support;
 use crate::file_util::copy_async_to_sync;
 use crate::file_util::persist_temp_file;
 use crate::file_util::symlink_file;
+#[cfg(feature = "builtin-fsmonitor")]
+use crate::fsmonitor::BuiltinFsmonitorConfig;
 use crate::fsmonitor::FsmonitorSettings;
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::WatchmanConfig;
+#[cfg(feature = "builtin-fsmonitor")]
+use crate::fsmonitor::builtin;
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::watchman;
+#[cfg(feature = "git")]
+use crate::git_backend::GitBackend;
+use crate::git_lfs::LfsObjectCache;
+use crate::gitattributes::AttributeState;
+use crate::gitattributes::GitAttributes;
+use crate::gitattributes::GitAttributesLoader;
 use crate::gitignore::GitIgnoreFile;
 use crate::lock::FileLock;
 use crate::matchers::DifferenceMatcher;
@@ -108,6 +133,9 @@ use crate::merged_tree::TreeDiffEntry;
 use crate::merged_tree_builder::MergedTreeBuilder;
 use crate::object_id::ObjectId as _;
 use crate::op_store::OperationId;
+use crate::progress::Progress;
+use crate::progress::ProgressScope;
+use crate::progress::ProgressTask;
 use crate::ref_name::WorkspaceName;
 use crate::ref_name::WorkspaceNameBuf;
 use crate::repo_path::RepoPath;
@@ -115,14 +143,16 @@ use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathComponent;
 use crate::settings::UserSettings;
 use crate::store::Store;
+use crate::working_copy::CaseCollision;
 use crate::working_copy::CheckoutError;
+use crate::working_copy::CheckoutOptions;
 use crate::working_copy::CheckoutStats;
 use crate::working_copy::LockedWorkingCopy;
 use crate::working_copy::ResetError;
 use crate::working_copy::SnapshotError;
 use crate::working_copy::SnapshotOptions;
-use crate::working_copy::SnapshotProgress;
 use crate::working_copy::SnapshotStats;
+use crate::working_copy::SparsePatterns;
 use crate::working_copy::UntrackedReason;
 use crate::working_copy::WorkingCopy;
 use crate::working_copy::WorkingCopyFactory;
@@ -177,6 +207,191 @@ pub enum ExecChangeSetting {
     Auto,
 }
 
+/// How to check out paths that differ only in case from other paths, such as
+/// `Foo.rs` and `foo.rs`, as exposed to the user.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CaseCollisionSetting {
+    /// Skip colliding paths if the filesystem of the working copy is
+    /// case-insensitive.
+    #[default]
+    Auto,
+    /// Check out only one of the colliding paths.
+    Skip,
+    /// Check out the other colliding paths under names with a suffix, such as
+    /// `foo.rs.case-collision-1`.
+    Suffix,
+    /// Refuse to check out trees with colliding paths.
+    Error,
+    /// Check out all paths, letting one overwrite the other on
+    /// case-insensitive filesystems.
+    Ignore,
+}
+
+/// How to check out paths that differ only in case from other paths.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum CaseCollisionPolicy {
+    Ignore,
+    Skip,
+    Suffix,
+    Error,
+}
+
+impl CaseCollisionPolicy {
+    /// Get the case collision policy based on user settings and case
+    /// sensitivity of the filesystem of the working copy's state path.
+    fn new(setting: CaseCollisionSetting, state_path: &Path) -> Self {
+        match setting {
+            CaseCollisionSetting::Auto => {
+                match crate::file_util::check_case_insensitivity(state_path) {
+                    Ok(true) => Self::Skip,
+                    Ok(false) => Self::Ignore,
+                    Err(err) => {
+                        tracing::warn!(?err, "Error when checking for case sensitivity");
+                        Self::Ignore
+                    }
+                }
+            }
+            CaseCollisionSetting::Skip => Self::Skip,
+            CaseCollisionSetting::Suffix => Self::Suffix,
+            CaseCollisionSetting::Error => Self::Error,
+            CaseCollisionSetting::Ignore => Self::Ignore,
+        }
+    }
+}
+
+/// Unicode normalization form of the names of new files, as exposed to the
+/// user.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PathNormalizationSetting {
+    /// Use NFC if the filesystem of the working copy ignores the normalization
+    /// of file names, and keep names as they are otherwise.
+    #[default]
+    Auto,
+    /// Convert names to the composed form (NFC).
+    Nfc,
+    /// Convert names to the decomposed form (NFD).
+    Nfd,
+    /// Keep names as they are on disk.
+    None,
+}
+
+/// Unicode normalization form of the names of new files.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum PathNormalization {
+    None,
+    Nfc,
+    Nfd,
+}
+
+impl PathNormalization {
+    /// Get the normalization based on user settings and normalization
+    /// sensitivity of the filesystem of the working copy's state path.
+    fn new(setting: PathNormalizationSetting, state_path: &Path) -> Self {
+        match setting {
+            PathNormalizationSetting::Auto => {
+                match crate::file_util::check_normalization_insensitivity(state_path) {
+                    Ok(true) => Self::Nfc,
+                    Ok(false) => Self::None,
+                    Err(err) => {
+                        tracing::warn!(?err, "Error when checking for normalization sensitivity");
+                        Self::None
+                    }
+                }
+            }
+            PathNormalizationSetting::Nfc => Self::Nfc,
+            PathNormalizationSetting::Nfd => Self::Nfd,
+            PathNormalizationSetting::None => Self::None,
+        }
+    }
+
+    fn normalize(self, name: &str) -> Cow<'_, str> {
+        match self {
+            Self::None => Cow::Borrowed(name),
+            Self::Nfc if unicode_normalization::is_nfc(name) => Cow::Borrowed(name),
+            Self::Nfc => Cow::Owned(name.nfc().collect()),
+            Self::Nfd if unicode_normalization::is_nfd(name) => Cow::Borrowed(name),
+            Self::Nfd => Cow::Owned(name.nfd().collect()),
+        }
+    }
+}
+
+/// How to tell whether a file was modified since it was last snapshotted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangeDetection {
+    /// Consider files unchanged if their size and mtime are unchanged.
+    #[default]
+    Mtime,
+    /// Hash files whose size is unchanged, even if their mtime is unchanged.
+    /// Implies [`TouchedFileCheck::Full`].
+    Checksum,
+}
+
+/// How to tell whether a file whose mtime changed since it was last written or
+/// snapshotted was actually modified.
+///
+/// If enabled, a hash of the file contents is recorded in the file state.
+/// Files with the same size and hash as recorded aren't written to the store
+/// again, which is much cheaper if e.g. a build system touched many files.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TouchedFileCheck {
+    /// Always write files with changed mtime to the store.
+    #[default]
+    None,
+    /// Hash the beginning, middle, and end of large files. Modifications which
+    /// don't change the size of the file and happen elsewhere may go unnoticed.
+    Sampled,
+    /// Hash the whole file.
+    Full,
+}
+
+impl TouchedFileCheck {
+    /// Size of each chunk hashed by [`TouchedFileCheck::Sampled`].
+    const SAMPLE_SIZE: usize = 16 << 10;
+
+    /// Computes the hash of the file at `disk_path`, or returns `None` if the
+    /// check is disabled. The `conversion_key` describes how the contents are
+    /// converted when they're written to the store.
+    fn quick_hash(self, disk_path: &Path, conversion_key: &[u8]) -> io::Result<Option<u64>> {
+        let mut hasher = Blake2b512::new();
+        // Hashes computed with a different setting shouldn't match.
+        match self {
+            Self::None => return Ok(None),
+            Self::Sampled => hasher.update(b"sampled"),
+            Self::Full => hasher.update(b"full"),
+        }
+        // The same contents are stored differently if the conversion changed.
+        hasher.update(u64::try_from(conversion_key.len()).unwrap().to_le_bytes());
+        hasher.update(conversion_key);
+        let mut file = File::open(disk_path)?;
+        let len = file.metadata()?.len();
+        let sample_size = Self::SAMPLE_SIZE as u64;
+        if self == Self::Sampled && len > 3 * sample_size {
+            hasher.update(len.to_le_bytes());
+            let mut buf = vec![0; Self::SAMPLE_SIZE];
+            for offset in [0, (len - sample_size) / 2, len - sample_size] {
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_exact(&mut buf)?;
+                hasher.update(&buf);
+            }
+        } else {
+            let mut buf = vec![0; Self::SAMPLE_SIZE];
+            loop {
+                let n = file.read(&mut buf)?;
+                if n == 0 {
+                    break;
+                }
+                hasher.update(&buf[..n]);
+            }
+        }
+        let digest = hasher.finalize();
+        Ok(Some(u64::from_le_bytes(digest[..8].try_into().unwrap())))
+    }
+}
+
 impl ExecChangePolicy {
     /// Get the executable bit policy based on user settings and executable bit
     /// support in the working copy's state path.
@@ -295,6 +510,8 @@ pub struct FileState {
     pub mtime: MillisSinceEpoch,
     pub size: u64,
     pub materialized_conflict_data: Option<MaterializedConflictData>,
+    /// Hash of the file contents, if computed. See [`TouchedFileCheck`].
+    pub quick_hash: Option<u64>,
     /* TODO: What else do we need here? Git stores a lot of fields.
      * TODO: Could possibly handle case-insensitive file systems keeping an
      *       Option<PathBuf> with the actual path here. */
@@ -319,6 +536,7 @@ impl FileState {
             mtime: MillisSinceEpoch(0),
             size: 0,
             materialized_conflict_data: None,
+            quick_hash: None,
         }
     }
 
@@ -332,6 +550,7 @@ impl FileState {
             mtime: mtime_from_metadata(metadata)?,
             size,
             materialized_conflict_data: None,
+            quick_hash: None,
         })
     }
 
@@ -344,6 +563,7 @@ impl FileState {
             mtime: mtime_from_metadata(metadata)?,
             size: metadata.len(),
             materialized_conflict_data: None,
+            quick_hash: None,
         })
     }
 
@@ -353,6 +573,7 @@ impl FileState {
             mtime: MillisSinceEpoch(0),
             size: 0,
             materialized_conflict_data: None,
+            quick_hash: None,
         }
     }
 }
@@ -543,6 +764,45 @@ impl<'a> FileStates<'a> {
         start..(start + len)
     }
 
+    /// Returns the sorted names of the entries directly under `dir` which are
+    /// tracked as directories and as files respectively. Submodules are
+    /// returned as directories. Requires that all entries share the same
+    /// prefix `dir`.
+    fn child_names(&self, dir: &RepoPath) -> (Vec<&'a str>, Vec<&'a str>) {
+        debug_assert!(self.paths().all(|path| path.starts_with(dir)));
+        let slash_len = usize::from(!dir.is_root());
+        let prefix_len = dir.as_internal_file_string().len() + slash_len;
+        let mut dirs = vec![];
+        let mut files = vec![];
+        let mut data = self.data;
+        while let Some(entry) = data.first() {
+            let tail = entry.path.get(prefix_len..).unwrap_or("");
+            if let Some((name, _)) = tail.split_once('/') {
+                dirs.push(name);
+                // Skip the remaining entries in the subdirectory.
+                let len = data.partition_point(|entry| {
+                    let tail = entry.path.get(prefix_len..).unwrap_or("");
+                    tail.split_once('/').is_some_and(|(pre, _)| pre == name)
+                });
+                data = &data[len..];
+                continue;
+            }
+            // `dir` itself may be a file on file->dir transition.
+            if !tail.is_empty() {
+                let (_, state) = file_state_entry_from_proto(entry);
+                if state.file_type == FileType::GitSubmodule {
+                    dirs.push(tail);
+                } else {
+                    files.push(tail);
+                }
+            }
+            data = &data[1..];
+        }
+        dirs.sort_unstable();
+        dirs.dedup();
+        (dirs, files)
+    }
+
     /// Iterates file state entries sorted by path.
     pub fn iter(&self) -> FileStatesIter<'a> {
         self.data.iter().map(file_state_entry_from_proto)
@@ -596,6 +856,7 @@ fn file_state_from_proto(proto: &crate::protos::local_working_copy::FileState) -
                 conflict_marker_len: data.conflict_marker_len,
             }
         }),
+        quick_hash: proto.quick_hash,
     }
 }
 
@@ -620,6 +881,7 @@ fn file_state_to_proto(file_state: &FileState) -> crate::protos::local_working_c
             conflict_marker_len: data.conflict_marker_len,
         }
     });
+    proto.quick_hash = file_state.quick_hash;
     proto
 }
 
@@ -650,18 +912,24 @@ fn is_file_state_entries_proto_unique_and_sorted(
 
 fn sparse_patterns_from_proto(
     proto: Option<&crate::protos::local_working_copy::SparsePatterns>,
-) -> Vec<RepoPathBuf> {
-    let mut sparse_patterns = vec![];
+) -> SparsePatterns {
     if let Some(proto_sparse_patterns) = proto {
-        for prefix in &proto_sparse_patterns.prefixes {
-            sparse_patterns.push(RepoPathBuf::from_internal_string(prefix).unwrap());
+        let to_paths = |prefixes: &[String]| {
+            prefixes
+                .iter()
+                .map(|prefix| RepoPathBuf::from_internal_string(prefix).unwrap())
+                .collect()
+        };
+        SparsePatterns {
+            included: to_paths(&proto_sparse_patterns.prefixes),
+            excluded: to_paths(&proto_sparse_patterns.excluded_prefixes),
+            cone: proto_sparse_patterns.cone,
         }
     } else {
         // For compatibility with old working copies.
         // TODO: Delete this is late 2022 or so.
-        sparse_patterns.push(RepoPathBuf::root());
+        SparsePatterns::full()
     }
-    sparse_patterns
 }
 
 /// Creates intermediate directories from the `working_copy_path` to the
@@ -795,6 +1063,37 @@ fn can_create_new_file(disk_path: &Path) -> Result<bool, CheckoutError> {
     Ok(new_file_created)
 }
 
+/// Returns the Git LFS object cache of the Git repo backing the `store`.
+#[cfg(feature = "git")]
+fn lfs_object_cache(store: &Store) -> Option<LfsObjectCache> {
+    let git_backend = store.backend_impl::<GitBackend>()?;
+    Some(LfsObjectCache::for_git_dir(git_backend.git_repo_path()))
+}
+
+#[cfg(not(feature = "git"))]
+fn lfs_object_cache(_store: &Store) -> Option<LfsObjectCache> {
+    None
+}
+
+/// Reads the `.gitattributes` file at `path` from the `tree`. Conflicted and
+/// non-file entries are treated as missing.
+fn read_gitattributes_from_tree(
+    tree: &MergedTree,
+    path: &RepoPath,
+) -> Result<Option<Vec<u8>>, Box<dyn Error + Send + Sync>> {
+    let value = tree.path_value(path)?;
+    let Ok(Some(TreeValue::File { id, .. })) = value.into_resolved() else {
+        return Ok(None);
+    };
+    let mut contents = vec![];
+    tree.store()
+        .read_file(path, &id)
+        .block_on()?
+        .read_to_end(&mut contents)
+        .block_on()?;
+    Ok(Some(contents))
+}
+
 const RESERVED_DIR_NAMES: &[&str] = &[".git", ".jj"];
 
 fn file_identity_from_symlink_path(disk_path: &Path) -> io::Result<Option<FileIdentity>> {
@@ -927,15 +1226,213 @@ fn file_state(metadata: &Metadata) -> Result<Option<FileState>, MtimeOutOfRange>
             mtime: mtime_from_metadata(metadata)?,
             size: metadata.len(),
             materialized_conflict_data: None,
+            quick_hash: None,
         }))
     } else {
         Ok(None)
     }
 }
 
+/// Entries of a directory as read by the last snapshot.
+///
+/// Adding, removing, or renaming an entry updates the mtime of the directory,
+/// so the listing can be reused as long as the mtime stays the same.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct DirectoryListing {
+    mtime: MillisSinceEpoch,
+    /// Sorted names of subdirectories.
+    dirs: Vec<String>,
+    /// Sorted names of all other entries.
+    files: Vec<String>,
+}
+
+impl DirectoryListing {
+    fn read(disk_dir: &Path, mtime: MillisSinceEpoch) -> Result<Self, SnapshotError> {
+        let read_dir_err = |err: io::Error| SnapshotError::Other {
+            message: format!("Failed to read directory {}", disk_dir.display()),
+            err: err.into(),
+        };
+        let mut dirs = vec![];
+        let mut files = vec![];
+        for entry in disk_dir.read_dir().map_err(read_dir_err)? {
+            let entry = entry.map_err(read_dir_err)?;
+            let name = entry
+                .file_name()
+                .into_string()
+                .map_err(|path| SnapshotError::InvalidUtf8Path { path })?;
+            if entry.file_type().map_err(read_dir_err)?.is_dir() {
+                dirs.push(name);
+            } else {
+                files.push(name);
+            }
+        }
+        dirs.sort_unstable();
+        files.sort_unstable();
+        Ok(Self { mtime, dirs, files })
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.contains_dir(name) || self.contains_file(name)
+    }
+
+    fn contains_dir(&self, name: &str) -> bool {
+        self.dirs.binary_search_by(|n| n.as_str().cmp(name)).is_ok()
+    }
+
+    fn contains_file(&self, name: &str) -> bool {
+        self.files
+            .binary_search_by(|n| n.as_str().cmp(name))
+            .is_ok()
+    }
+}
+
+/// Restores directory listings persisted by [`directory_listings_to_proto()`]
+/// by adding back the tracked entries.
+fn directory_listings_from_proto(
+    listings: Vec<crate::protos::local_working_copy::DirectoryListing>,
+    file_states: FileStates<'_>,
+) -> BTreeMap<RepoPathBuf, DirectoryListing> {
+    listings
+        .into_iter()
+        .filter_map(|listing| {
+            let path = RepoPathBuf::from_internal_string(listing.path).ok()?;
+            let (tracked_dirs, tracked_files) = file_states.prefixed(&path).child_names(&path);
+            let merge = |mut names: Vec<String>, tracked: Vec<&str>| {
+                names.extend(tracked.into_iter().map(|name| name.to_owned()));
+                names.sort_unstable();
+                names.dedup();
+                names
+            };
+            let listing = DirectoryListing {
+                mtime: MillisSinceEpoch(listing.mtime_millis_since_epoch),
+                dirs: merge(listing.untracked_dirs, tracked_dirs),
+                files: merge(listing.untracked_files, tracked_files),
+            };
+            Some((path, listing))
+        })
+        .collect()
+}
+
+/// Converts directory listings to protos. Entries tracked in `file_states`
+/// are omitted to keep the state file small, since most entries are usually
+/// tracked.
+fn directory_listings_to_proto(
+    listings: &BTreeMap<RepoPathBuf, DirectoryListing>,
+    file_states: FileStates<'_>,
+) -> Vec<crate::protos::local_working_copy::DirectoryListing> {
+    listings
+        .iter()
+        .map(|(path, listing)| {
+            let (tracked_dirs, tracked_files) = file_states.prefixed(path).child_names(path);
+            let untracked = |names: &[String], tracked: &[&str]| {
+                names
+                    .iter()
+                    .filter(|name| tracked.binary_search(&name.as_str()).is_err())
+                    .cloned()
+                    .collect()
+            };
+            crate::protos::local_working_copy::DirectoryListing {
+                path: path.as_internal_file_string().to_owned(),
+                mtime_millis_since_epoch: listing.mtime.0,
+                untracked_dirs: untracked(&listing.dirs, &tracked_dirs),
+                untracked_files: untracked(&listing.files, &tracked_files),
+            }
+        })
+        .collect()
+}
+
+fn case_collisions_from_proto(
+    proto: Vec<crate::protos::local_working_copy::CaseCollision>,
+) -> Vec<CaseCollision> {
+    let to_path = |path: &str| RepoPathBuf::from_internal_string(path).unwrap();
+    proto
+        .iter()
+        .map(|collision| CaseCollision {
+            path: to_path(&collision.path),
+            other_path: to_path(&collision.other_path),
+            checked_out_as: (!collision.checked_out_as.is_empty())
+                .then(|| to_path(&collision.checked_out_as)),
+        })
+        .collect()
+}
+
+fn case_collisions_to_proto(
+    collisions: &[CaseCollision],
+) -> Vec<crate::protos::local_working_copy::CaseCollision> {
+    collisions
+        .iter()
+        .map(
+            |collision| crate::protos::local_working_copy::CaseCollision {
+                path: collision.path.as_internal_file_string().to_owned(),
+                other_path: collision.other_path.as_internal_file_string().to_owned(),
+                checked_out_as: collision
+                    .checked_out_as
+                    .as_ref()
+                    .map_or("", |path| path.as_internal_file_string())
+                    .to_owned(),
+            },
+        )
+        .collect()
+}
+
+/// Maps the paths of `collisions` which are checked out under other names to
+/// those names.
+fn case_collision_disk_paths<'a>(
+    collisions: impl IntoIterator<Item = &'a CaseCollision>,
+) -> HashMap<RepoPathBuf, RepoPathBuf> {
+    collisions
+        .into_iter()
+        .filter_map(|collision| {
+            let disk_path = collision.checked_out_as.clone()?;
+            Some((collision.path.clone(), disk_path))
+        })
+        .collect()
+}
+
+/// Updates the cached directory listings with the directories visited by a
+/// snapshot. `Some` listing replaces the cached one, `None` means the cached
+/// listing was reused. Listings of directories that no longer exist are
+/// removed. Returns true if anything changed.
+fn update_directory_listings(
+    listings: &mut BTreeMap<RepoPathBuf, DirectoryListing>,
+    visited: Vec<(RepoPathBuf, Option<DirectoryListing>)>,
+) -> bool {
+    let mut changed = false;
+    for (dir, new_listing) in visited {
+        if let Some(new_listing) = new_listing
+            && listings.get(&dir) != Some(&new_listing)
+        {
+            listings.insert(dir, new_listing);
+            changed = true;
+        }
+    }
+    // Parent directories sort before their children, so whether the parent
+    // is removed is known by the time the children are checked.
+    let mut removed: HashSet<&RepoPath> = HashSet::new();
+    for dir in listings.keys() {
+        let Some((parent, name)) = dir.split() else {
+            continue;
+        };
+        let is_listed = !removed.contains(parent)
+            && listings
+                .get(parent)
+                .is_some_and(|listing| listing.contains_dir(name.as_internal_str()));
+        if !is_listed {
+            removed.insert(dir);
+        }
+    }
+    if removed.is_empty() {
+        return changed;
+    }
+    let removed: HashSet<RepoPathBuf> = removed.into_iter().map(|dir| dir.to_owned()).collect();
+    listings.retain(|dir, _| !removed.contains(dir));
+    true
+}
+
 struct FsmonitorMatcher {
     matcher: Option<Box<dyn Matcher>>,
     watchman_clock: Option<crate::protos::local_working_copy::WatchmanClock>,
+    builtin_fsmonitor_clock: Option<crate::protos::local_working_copy::BuiltinFsmonitorClock>,
 }
 
 /// Settings specific to the tree state of the [`LocalWorkingCopy`] backend.
@@ -948,10 +1445,31 @@ pub struct TreeStateSettings {
     /// file to the backend, and vice versa when it checks out code onto your
     /// filesystem.
     pub eol_conversion_mode: EolConversionMode,
+    /// Per-pattern overrides of `eol_conversion_mode`.
+    pub eol_conversion_rules: Vec<EolConversionRule>,
     /// Whether to ignore changes to the executable bit for files on Unix.
     pub exec_change_setting: ExecChangeSetting,
+    /// How to check out paths that differ only in case from other paths.
+    pub case_collision_setting: CaseCollisionSetting,
+    /// Unicode normalization form of the names of new files.
+    pub path_normalization_setting: PathNormalizationSetting,
     /// The fsmonitor (e.g. Watchman) to use, if any.
     pub fsmonitor_settings: FsmonitorSettings,
+    /// How to tell whether files were modified.
+    pub change_detection: ChangeDetection,
+    /// How to check files whose mtime changed.
+    pub touched_file_check: TouchedFileCheck,
+    /// Whether to reuse the listings of directories whose mtime didn't change
+    /// since the last snapshot instead of reading them again.
+    pub untracked_cache: bool,
+    /// Whether to honor the `text`, `eol`, `filter`, and `ident` attributes
+    /// in `.gitattributes` files.
+    pub use_gitattributes: bool,
+    /// Filter drivers which can be selected by the `filter` attribute.
+    pub filter_drivers: HashMap<String, FilterDriver>,
+    /// Whether to store files with the `filter=lfs` attribute as Git LFS
+    /// pointers, unless an `lfs` filter driver is configured.
+    pub git_lfs: bool,
 }
 
 impl TreeStateSettings {
@@ -960,8 +1478,17 @@ impl TreeStateSettings {
         Ok(Self {
             conflict_marker_style: user_settings.get("ui.conflict-marker-style")?,
             eol_conversion_mode: EolConversionMode::try_from_settings(user_settings)?,
+            eol_conversion_rules: EolConversionRule::try_from_settings(user_settings)?,
             exec_change_setting: user_settings.get("working-copy.exec-bit-change")?,
+            case_collision_setting: user_settings.get("working-copy.case-collisions")?,
+            path_normalization_setting: user_settings.get("working-copy.path-normalization")?,
             fsmonitor_settings: FsmonitorSettings::from_settings(user_settings)?,
+            change_detection: user_settings.get("snapshot.change-detection")?,
+            touched_file_check: user_settings.get("working-copy.touched-file-check")?,
+            untracked_cache: user_settings.get_bool("working-copy.untracked-cache")?,
+            use_gitattributes: user_settings.get_bool("working-copy.use-gitattributes")?,
+            filter_drivers: FilterDriver::try_from_settings(user_settings)?,
+            git_lfs: user_settings.get_bool("git.lfs")?,
         })
     }
 }
@@ -972,8 +1499,12 @@ pub struct TreeState {
     state_path: PathBuf,
     tree: MergedTree,
     file_states: FileStatesMap,
-    // Currently only path prefixes
-    sparse_patterns: Vec<RepoPathBuf>,
+    directory_listings: BTreeMap<RepoPathBuf, DirectoryListing>,
+    sparse_patterns: SparsePatterns,
+    force_tracked_paths: Vec<RepoPathBuf>,
+    /// Paths which aren't checked out at their own paths because they differ
+    /// only in case from other paths. Sorted by path.
+    case_collisions: Vec<CaseCollision>,
     own_mtime: MillisSinceEpoch,
     symlink_support: bool,
 
@@ -982,10 +1513,20 @@ pub struct TreeState {
     /// Watchman has been queried at least once.
     watchman_clock: Option<crate::protos::local_working_copy::WatchmanClock>,
 
+    /// The most recent clock value returned by the builtin filesystem monitor.
+    builtin_fsmonitor_clock: Option<crate::protos::local_working_copy::BuiltinFsmonitorClock>,
+
     conflict_marker_style: ConflictMarkerStyle,
     exec_policy: ExecChangePolicy,
+    case_collision_policy: CaseCollisionPolicy,
+    path_normalization: PathNormalization,
     fsmonitor_settings: FsmonitorSettings,
     target_eol_strategy: TargetEolStrategy,
+    change_detection: ChangeDetection,
+    touched_file_check: TouchedFileCheck,
+    untracked_cache: bool,
+    use_gitattributes: bool,
+    content_filters: ContentFilters,
 }
 
 #[derive(Debug, Error)]
@@ -1018,12 +1559,58 @@ impl TreeState {
         self.file_states.all()
     }
 
-    pub fn sparse_patterns(&self) -> &Vec<RepoPathBuf> {
+    pub fn sparse_patterns(&self) -> &SparsePatterns {
         &self.sparse_patterns
     }
 
+    /// Sorted paths which are tracked even if they're ignored or too large.
+    pub fn force_tracked_paths(&self) -> &[RepoPathBuf] {
+        &self.force_tracked_paths
+    }
+
+    /// Sets the paths which are tracked even if they're ignored or too large.
+    pub fn set_force_tracked_paths(&mut self, mut paths: Vec<RepoPathBuf>) {
+        paths.sort_unstable();
+        paths.dedup();
+        self.force_tracked_paths = paths;
+    }
+
     fn sparse_matcher(&self) -> Box<dyn Matcher> {
-        Box::new(PrefixMatcher::new(&self.sparse_patterns))
+        self.sparse_patterns.to_matcher()
+    }
+
+    /// Returns a loader of the `.gitattributes` files on disk. Files outside
+    /// the sparse patterns are read from the current tree instead.
+    fn working_copy_gitattributes(&self) -> GitAttributesLoader<'_> {
+        if !self.use_gitattributes {
+            return GitAttributesLoader::empty();
+        }
+        let sparse_matcher = self.sparse_matcher();
+        GitAttributesLoader::new(move |path| {
+            if !sparse_matcher.matches(path) {
+                return read_gitattributes_from_tree(&self.tree, path);
+            }
+            match fs::read(path.to_fs_path_unchecked(&self.working_copy_path)) {
+                Ok(contents) => Ok(Some(contents)),
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::NotFound | io::ErrorKind::NotADirectory
+                    ) =>
+                {
+                    Ok(None)
+                }
+                Err(err) => Err(err.into()),
+            }
+        })
+    }
+
+    /// Returns a loader of the `.gitattributes` files in `tree`.
+    fn tree_gitattributes<'a>(&self, tree: &'a MergedTree) -> GitAttributesLoader<'a> {
+        if !self.use_gitattributes {
+            return GitAttributesLoader::empty();
+        }
+        GitAttributesLoader::new(|path| read_gitattributes_from_tree(tree, path))
     }
 
     pub fn init(
@@ -1044,25 +1631,58 @@ impl TreeState {
         &TreeStateSettings {
             conflict_marker_style,
             eol_conversion_mode,
+            ref eol_conversion_rules,
             exec_change_setting,
+            case_collision_setting,
+            path_normalization_setting,
             ref fsmonitor_settings,
+            change_detection,
+            touched_file_check,
+            untracked_cache,
+            use_gitattributes,
+            ref filter_drivers,
+            git_lfs,
         }: &TreeStateSettings,
     ) -> Self {
         let exec_policy = ExecChangePolicy::new(exec_change_setting, &state_path);
+        let case_collision_policy = CaseCollisionPolicy::new(case_collision_setting, &state_path);
+        let path_normalization = PathNormalization::new(path_normalization_setting, &state_path);
+        let content_filters = ContentFilters::new(
+            filter_drivers.clone(),
+            working_copy_path.clone(),
+            git_lfs.then(|| lfs_object_cache(&store)).flatten(),
+        );
         Self {
             store: store.clone(),
             working_copy_path,
             state_path,
             tree: store.empty_merged_tree(),
             file_states: FileStatesMap::new(),
-            sparse_patterns: vec![RepoPathBuf::root()],
+            directory_listings: BTreeMap::new(),
+            sparse_patterns: SparsePatterns::full(),
+            force_tracked_paths: vec![],
+            case_collisions: vec![],
             own_mtime: MillisSinceEpoch(0),
             symlink_support: check_symlink_support().unwrap_or(false),
             watchman_clock: None,
+            builtin_fsmonitor_clock: None,
             conflict_marker_style,
             exec_policy,
+            case_collision_policy,
+            path_normalization,
             fsmonitor_settings: fsmonitor_settings.clone(),
-            target_eol_strategy: TargetEolStrategy::new(eol_conversion_mode),
+            target_eol_strategy: TargetEolStrategy::new(
+                eol_conversion_mode,
+                eol_conversion_rules.clone(),
+            ),
+            change_detection,
+            touched_file_check: match change_detection {
+                ChangeDetection::Mtime => touched_file_check,
+                ChangeDetection::Checksum => TouchedFileCheck::Full,
+            },
+            untracked_cache,
+            use_gitattributes,
+            content_filters,
         }
     }
 
@@ -1101,6 +1721,27 @@ impl TreeState {
         }
     }
 
+    /// Whether an entry with this mtime was last modified before the tree
+    /// state was written, so an unchanged mtime means that the entry wasn't
+    /// modified since.
+    ///
+    /// If the entry's mtime was set at the same time as this state file's own
+    /// mtime, we don't know if it was modified before or after the state was
+    /// written. Filesystems such as FAT and ext3 only record mtimes in whole
+    /// seconds (or two seconds), so a file modified shortly after it was
+    /// snapshotted can keep the recorded mtime even if the state file's mtime
+    /// is later. If the state file's mtime looks coarse, we also distrust
+    /// mtimes close to it.
+    fn mtime_predates_state(&self, mtime: MillisSinceEpoch) -> bool {
+        const COARSE_MTIME_RESOLUTION_MILLIS: i64 = 2000;
+        let margin = if self.own_mtime.0 % 1000 == 0 {
+            COARSE_MTIME_RESOLUTION_MILLIS
+        } else {
+            0
+        };
+        mtime.0 < self.own_mtime.0.saturating_sub(margin)
+    }
+
     fn read(&mut self, tree_state_path: &Path, mut file: File) -> Result<(), TreeStateError> {
         self.update_own_mtime();
         let mut buf = Vec::new();
@@ -1135,8 +1776,17 @@ impl TreeState {
         }
         self.file_states =
             FileStatesMap::from_proto(proto.file_states, proto.is_file_states_sorted);
+        self.directory_listings =
+            directory_listings_from_proto(proto.directory_listings, self.file_states.all());
         self.sparse_patterns = sparse_patterns_from_proto(proto.sparse_patterns.as_ref());
+        self.force_tracked_paths = proto
+            .force_tracked_paths
+            .iter()
+            .map(|path| RepoPathBuf::from_internal_string(path).unwrap())
+            .collect();
+        self.case_collisions = case_collisions_from_proto(proto.case_collisions);
         self.watchman_clock = proto.watchman_clock;
+        self.builtin_fsmonitor_clock = proto.builtin_fsmonitor_clock;
         Ok(())
     }
 
@@ -1153,14 +1803,23 @@ impl TreeState {
         proto.file_states = self.file_states.data.clone();
         // `FileStatesMap` is guaranteed to be sorted.
         proto.is_file_states_sorted = true;
-        let mut sparse_patterns = crate::protos::local_working_copy::SparsePatterns::default();
-        for path in &self.sparse_patterns {
-            sparse_patterns
-                .prefixes
-                .push(path.as_internal_file_string().to_owned());
-        }
-        proto.sparse_patterns = Some(sparse_patterns);
+        proto.directory_listings =
+            directory_listings_to_proto(&self.directory_listings, self.file_states.all());
+        let to_strings = |paths: &[RepoPathBuf]| {
+            paths
+                .iter()
+                .map(|path| path.as_internal_file_string().to_owned())
+                .collect()
+        };
+        proto.sparse_patterns = Some(crate::protos::local_working_copy::SparsePatterns {
+            prefixes: to_strings(&self.sparse_patterns.included),
+            excluded_prefixes: to_strings(&self.sparse_patterns.excluded),
+            cone: self.sparse_patterns.cone,
+        });
+        proto.force_tracked_paths = to_strings(&self.force_tracked_paths);
+        proto.case_collisions = case_collisions_to_proto(&self.case_collisions);
         proto.watchman_clock = self.watchman_clock.clone();
+        proto.builtin_fsmonitor_clock = self.builtin_fsmonitor_clock.clone();
 
         let wrap_write_err = |source| TreeStateError::WriteTreeState {
             path: self.state_path.clone(),
@@ -1220,6 +1879,39 @@ impl TreeState {
         }
     }
 
+    #[cfg(feature = "builtin-fsmonitor")]
+    fn builtin_fsmonitor(&self) -> builtin::Fsmonitor {
+        builtin_fsmonitor_at(&self.state_path)
+    }
+
+    /// Queries the builtin filesystem monitor, starting the daemon if needed.
+    ///
+    /// Returns `None` clock if the daemon isn't running yet.
+    #[cfg(feature = "builtin-fsmonitor")]
+    #[instrument(skip(self))]
+    pub fn query_builtin_fsmonitor(
+        &self,
+        config: &BuiltinFsmonitorConfig,
+    ) -> Result<(Option<builtin::Clock>, Option<Vec<PathBuf>>), TreeStateError> {
+        let fsmonitor = self.builtin_fsmonitor();
+        let previous_clock = self
+            .builtin_fsmonitor_clock
+            .clone()
+            .map(builtin::Clock::from);
+        match fsmonitor.query_changed_files(previous_clock) {
+            Ok((clock, changed_files)) => Ok((Some(clock), changed_files)),
+            Err(builtin::Error::DaemonNotRunning) if config.auto_start => {
+                if let Some(daemon_command) = &config.daemon_command {
+                    fsmonitor
+                        .spawn_daemon(daemon_command, &self.working_copy_path)
+                        .map_err(|err| TreeStateError::Fsmonitor(Box::new(err)))?;
+                }
+                Ok((None, None))
+            }
+            Err(err) => Err(TreeStateError::Fsmonitor(Box::new(err))),
+        }
+    }
+
     #[cfg(feature = "watchman")]
     #[instrument(skip(self))]
     pub async fn is_watchman_trigger_registered(
@@ -1267,12 +1959,18 @@ impl TreeState {
         } = options;
 
         let sparse_matcher = self.sparse_matcher();
+        let force_tracked_matcher = FilesMatcher::new(&self.force_tracked_paths);
+        let start_tracking_matcher =
+            &UnionMatcher::new(start_tracking_matcher, &force_tracked_matcher);
+        let force_tracking_matcher =
+            &UnionMatcher::new(force_tracking_matcher, &force_tracked_matcher);
 
         let fsmonitor_clock_needs_save = self.fsmonitor_settings != FsmonitorSettings::None;
         let mut is_dirty = fsmonitor_clock_needs_save;
         let FsmonitorMatcher {
             matcher: fsmonitor_matcher,
             watchman_clock,
+            builtin_fsmonitor_clock,
         } = self
             .make_fsmonitor_matcher(&self.fsmonitor_settings)
             .await?;
@@ -1288,6 +1986,7 @@ impl TreeState {
         if matcher.visit(RepoPath::root()).is_nothing() {
             // No need to load the current tree, set up channels, etc.
             self.watchman_clock = watchman_clock;
+            self.builtin_fsmonitor_clock = builtin_fsmonitor_clock;
             return Ok((is_dirty, SnapshotStats::default()));
         }
 
@@ -1295,11 +1994,20 @@ impl TreeState {
         let (file_states_tx, file_states_rx) = channel();
         let (untracked_paths_tx, untracked_paths_rx) = channel();
         let (deleted_files_tx, deleted_files_rx) = channel();
+        let (directory_listings_tx, directory_listings_rx) = channel();
+        // Maps the paths of files checked out under other names to their
+        // paths in the tree.
+        let renamed_paths: HashMap<RepoPathBuf, RepoPathBuf> = self
+            .case_collisions
+            .iter()
+            .filter_map(|c| Some((c.checked_out_as.clone()?, c.path.clone())))
+            .collect();
 
         trace_span!("traverse filesystem").in_scope(|| -> Result<(), SnapshotError> {
             let snapshotter = FileSnapshotter {
                 tree_state: self,
                 current_tree: &self.tree,
+                renamed_paths: &renamed_paths,
                 matcher: &matcher,
                 start_tracking_matcher,
                 force_tracking_matcher,
@@ -1308,9 +2016,12 @@ impl TreeState {
                 file_states_tx,
                 untracked_paths_tx,
                 deleted_files_tx,
+                directory_listings_tx,
                 error: OnceLock::new(),
-                progress,
+                progress: ProgressScope::start(progress, ProgressTask::Snapshot),
+                visited_files: AtomicU64::new(0),
                 max_new_file_size,
+                gitattributes: self.working_copy_gitattributes(),
             };
             let directory_to_visit = DirectoryToVisit {
                 dir: RepoPathBuf::root(),
@@ -1327,32 +2038,65 @@ impl TreeState {
             snapshotter.into_result()
         })?;
 
-        let stats = SnapshotStats {
+        let mut stats = SnapshotStats {
             untracked_paths: untracked_paths_rx.into_iter().collect(),
         };
+        let mut tree_entries = tree_entries_rx.into_iter().collect_vec();
+        let deleted_files: HashSet<_> = deleted_files_rx.into_iter().collect();
+        let mut changed_file_states = file_states_rx.into_iter().collect_vec();
+        trace_span!("check new paths for case collisions").in_scope(|| {
+            let colliding_paths =
+                self.find_new_case_collisions(&tree_entries, &deleted_files, &renamed_paths);
+            if colliding_paths.is_empty() {
+                return;
+            }
+            tree_entries.retain(|(path, _)| !colliding_paths.contains_key(path));
+            changed_file_states.retain(|(path, _)| !colliding_paths.contains_key(path));
+            stats.untracked_paths.extend(
+                colliding_paths.into_iter().map(|(path, other_path)| {
+                    (path, UntrackedReason::CaseCollision { other_path })
+                }),
+            );
+        });
         let mut tree_builder = MergedTreeBuilder::new(self.tree.clone());
         trace_span!("process tree entries").in_scope(|| {
-            for (path, tree_values) in &tree_entries_rx {
+            for (path, tree_values) in tree_entries {
                 tree_builder.set_or_remove(path, tree_values);
             }
         });
-        let deleted_files = trace_span!("process deleted tree entries").in_scope(|| {
-            let deleted_files = HashSet::from_iter(deleted_files_rx);
+        trace_span!("process deleted tree entries").in_scope(|| {
             is_dirty |= !deleted_files.is_empty();
             for file in &deleted_files {
-                tree_builder.set_or_remove(file.clone(), Merge::absent());
+                let path = renamed_paths.get(file).unwrap_or(file);
+                tree_builder.set_or_remove(path.clone(), Merge::absent());
             }
-            deleted_files
+            // Deleted files which were checked out under other names no
+            // longer collide with anything.
+            self.case_collisions.retain(|c| {
+                c.checked_out_as
+                    .as_ref()
+                    .is_none_or(|path| !deleted_files.contains(path))
+            });
         });
         trace_span!("process file states").in_scope(|| {
-            let changed_file_states = file_states_rx
-                .iter()
-                .sorted_unstable_by(|(path1, _), (path2, _)| path1.cmp(path2))
-                .collect_vec();
+            changed_file_states.sort_unstable_by(|(path1, _), (path2, _)| path1.cmp(path2));
             is_dirty |= !changed_file_states.is_empty();
             self.file_states
                 .merge_in(changed_file_states, &deleted_files);
         });
+        trace_span!("process directory listings").in_scope(|| {
+            if self.untracked_cache {
+                is_dirty |= update_directory_listings(
+                    &mut self.directory_listings,
+                    directory_listings_rx.into_iter().collect(),
+                );
+            } else if !self.directory_listings.is_empty() {
+                // Drop listings saved while the cache was enabled, since they
+                // would be stale by the time it's enabled again.
+                self.directory_listings.clear();
+                is_dirty = true;
+            }
+        });
         trace_span!("write tree").in_scope(|| -> Result<(), BackendError> {
             let new_tree = tree_builder.write_tree()?;
             is_dirty |= new_tree.tree_ids_and_labels() != self.tree.tree_ids_and_labels();
@@ -1360,12 +2104,21 @@ impl TreeState {
             Ok(())
         })?;
         if cfg!(debug_assertions) {
+            let disk_paths = case_collision_disk_paths(&self.case_collisions);
+            let skipped_paths: HashSet<_> = self
+                .case_collisions
+                .iter()
+                .filter(|c| c.checked_out_as.is_none())
+                .map(|c| &c.path)
+                .collect();
+            let file_states = self.file_states.all();
             let tree_paths: HashSet<_> = self
                 .tree
                 .entries_matching(sparse_matcher.as_ref())
                 .filter_map(|(path, result)| result.is_ok().then_some(path))
+                .filter(|path| !skipped_paths.contains(path))
+                .map(|path| disk_paths.get(&path).cloned().unwrap_or(path))
                 .collect();
-            let file_states = self.file_states.all();
             let state_paths: HashSet<_> = file_states.paths().map(|path| path.to_owned()).collect();
             assert_eq!(state_paths, tree_paths);
         }
@@ -1377,6 +2130,11 @@ impl TreeState {
         } else {
             tracing::info!("not updating watchman clock because there are untracked files");
         }
+        if stats.untracked_paths.is_empty() || builtin_fsmonitor_clock.is_none() {
+            self.builtin_fsmonitor_clock = builtin_fsmonitor_clock;
+        } else {
+            tracing::info!("not updating fsmonitor clock because there are untracked files");
+        }
         Ok((is_dirty, stats))
     }
 
@@ -1385,15 +2143,17 @@ impl TreeState {
         &self,
         fsmonitor_settings: &FsmonitorSettings,
     ) -> Result<FsmonitorMatcher, SnapshotError> {
-        let (watchman_clock, changed_files) = match fsmonitor_settings {
-            FsmonitorSettings::None => (None, None),
-            FsmonitorSettings::Test { changed_files } => (None, Some(changed_files.clone())),
+        let (watchman_clock, builtin_fsmonitor_clock, changed_files) = match fsmonitor_settings {
+            FsmonitorSettings::None => (None, None, None),
+            FsmonitorSettings::Test { changed_files } => (None, None, Some(changed_files.clone())),
             #[cfg(feature = "watchman")]
             FsmonitorSettings::Watchman(config) => match self.query_watchman(config).await {
-                Ok((watchman_clock, changed_files)) => (Some(watchman_clock.into()), changed_files),
+                Ok((watchman_clock, changed_files)) => {
+                    (Some(watchman_clock.into()), None, changed_files)
+                }
                 Err(err) => {
                     tracing::warn!(?err, "Failed to query filesystem monitor");
-                    (None, None)
+                    (None, None, None)
                 }
             },
             #[cfg(not(feature = "watchman"))]
@@ -1405,7 +2165,28 @@ impl TreeState {
                         .into(),
                 });
             }
+            #[cfg(feature = "builtin-fsmonitor")]
+            FsmonitorSettings::Builtin(config) => match self.query_builtin_fsmonitor(config) {
+                Ok((clock, changed_files)) => (None, clock.map(Into::into), changed_files),
+                Err(err) => {
+                    tracing::warn!(?err, "Failed to query filesystem monitor");
+                    (None, None, None)
+                }
+            },
+            #[cfg(not(feature = "builtin-fsmonitor"))]
+            FsmonitorSettings::Builtin(_) => {
+                return Err(SnapshotError::Other {
+                    message: "Failed to query the filesystem monitor".to_string(),
+                    err: "Cannot use the builtin filesystem monitor because jj was not compiled \
+                          with the `builtin-fsmonitor` feature (consider disabling \
+                          `fsmonitor.backend`)"
+                        .into(),
+                });
+            }
         };
+        // Paths reported by the builtin monitor may be directories, in which
+        // case everything below them has to be visited.
+        let match_prefixes = matches!(fsmonitor_settings, FsmonitorSettings::Builtin(_));
         let matcher: Option<Box<dyn Matcher>> = match changed_files {
             None => None,
             Some(changed_files) => {
@@ -1416,12 +2197,17 @@ impl TreeState {
                         .collect_vec()
                 });
 
-                Some(Box::new(FilesMatcher::new(repo_paths)))
+                if match_prefixes {
+                    Some(Box::new(PrefixMatcher::new(repo_paths)))
+                } else {
+                    Some(Box::new(FilesMatcher::new(repo_paths)))
+                }
             }
         };
         Ok(FsmonitorMatcher {
             matcher,
             watchman_clock,
+            builtin_fsmonitor_clock,
         })
     }
 }
@@ -1449,6 +2235,9 @@ struct PresentDirEntries {
 struct FileSnapshotter<'a> {
     tree_state: &'a TreeState,
     current_tree: &'a MergedTree,
+    /// Maps the paths of files checked out under other names to their paths
+    /// in the tree.
+    renamed_paths: &'a HashMap<RepoPathBuf, RepoPathBuf>,
     matcher: &'a dyn Matcher,
     start_tracking_matcher: &'a dyn Matcher,
     force_tracking_matcher: &'a dyn Matcher,
@@ -1456,9 +2245,12 @@ struct FileSnapshotter<'a> {
     file_states_tx: Sender<(RepoPathBuf, FileState)>,
     untracked_paths_tx: Sender<(RepoPathBuf, UntrackedReason)>,
     deleted_files_tx: Sender<RepoPathBuf>,
+    directory_listings_tx: Sender<(RepoPathBuf, Option<DirectoryListing>)>,
     error: OnceLock<SnapshotError>,
-    progress: Option<&'a SnapshotProgress<'a>>,
+    progress: ProgressScope<'a>,
+    visited_files: AtomicU64,
     max_new_file_size: u64,
+    gitattributes: GitAttributesLoader<'a>,
 }
 
 impl FileSnapshotter<'_> {
@@ -1499,23 +2291,63 @@ impl FileSnapshotter<'_> {
             file_states,
         } = directory_to_visit;
 
-        let git_ignore = git_ignore
-            .chain_with_file(&dir.to_internal_dir_string(), disk_dir.join(".gitignore"))?;
-        let dir_entries: Vec<_> = disk_dir
-            .read_dir()
-            .and_then(|entries| entries.try_collect())
-            .map_err(|err| SnapshotError::Other {
-                message: format!("Failed to read directory {}", disk_dir.display()),
-                err: err.into(),
-            })?;
+        let listing = self.list_directory(&dir, &disk_dir)?;
+        // If a submodule was added in commit C, and a user decides to run
+        // `jj new <something before C>` from after C, then the submodule
+        // files stick around but it is no longer seen as a submodule.
+        // We need to ensure that it is not tracked as if it was added to
+        // the main repo.
+        // See https://github.com/jj-vcs/jj/issues/4349.
+        // To solve this, we ignore all nested repos entirely.
+        if !dir.is_root()
+            && RESERVED_DIR_NAMES
+                .iter()
+                .any(|&name| listing.contains(name))
+        {
+            let present_entries = PresentDirEntries {
+                dirs: HashSet::new(),
+                files: HashSet::new(),
+            };
+            self.emit_deleted_files(&dir, file_states, &present_entries);
+            return Ok(());
+        }
+
+        let git_ignore = if listing.contains_file(".gitignore") {
+            git_ignore
+                .chain_with_file(&dir.to_internal_dir_string(), disk_dir.join(".gitignore"))?
+        } else {
+            git_ignore
+        };
+        let renamed_entries = self.normalize_entry_names(&dir, &listing, file_states);
+        let dir_entries = listing
+            .dirs
+            .par_iter()
+            .map(|name| (PresentDirEntryKind::Dir, name.as_str()))
+            .chain(
+                listing
+                    .files
+                    .par_iter()
+                    .map(|name| (PresentDirEntryKind::File, name.as_str())),
+            );
         let (dirs, files) = dir_entries
-            .into_par_iter()
             // Don't split into too many small jobs. For a small directory,
             // sequential scan should be fast enough.
             .with_min_len(100)
-            .filter_map(|entry| {
-                self.process_dir_entry(&dir, &git_ignore, file_states, &entry, scope)
-                    .transpose()
+            .filter_map(|(kind, disk_name)| {
+                let name = renamed_entries
+                    .get(disk_name)
+                    .map_or(disk_name, String::as_str);
+                self.process_dir_entry(
+                    &dir,
+                    &disk_dir,
+                    &git_ignore,
+                    file_states,
+                    kind,
+                    disk_name,
+                    name,
+                    scope,
+                )
+                .transpose()
             })
             .map(|item| match item {
                 Ok((PresentDirEntryKind::Dir, name)) => Ok(Either::Left(name)),
@@ -1528,25 +2360,118 @@ impl FileSnapshotter<'_> {
         Ok(())
     }
 
+    /// Returns the entries of the directory, reusing the listing from the
+    /// previous snapshot if the untracked cache is enabled and the directory
+    /// hasn't changed since.
+    ///
+    /// Only the listing can be reused. Modifying a file doesn't update the
+    /// mtime of its directory, so the entries still have to be checked. Some
+    /// filesystems don't update the mtime of a directory when entries are
+    /// added to it either, which is why the cache is opt-in.
+    fn list_directory(
+        &self,
+        dir: &RepoPath,
+        disk_dir: &Path,
+    ) -> Result<Cow<'_, DirectoryListing>, SnapshotError> {
+        // Stat the directory before reading it so changes made while reading
+        // will be picked up by the next snapshot.
+        let metadata = disk_dir
+            .symlink_metadata()
+            .map_err(|err| SnapshotError::Other {
+                message: format!("Failed to stat directory {}", disk_dir.display()),
+                err: err.into(),
+            })?;
+        let mtime = mtime_from_metadata(&metadata)
+            .map_err(|err| snapshot_error_for_mtime_out_of_range(err, disk_dir))?;
+        if !self.tree_state.untracked_cache {
+            return Ok(Cow::Owned(DirectoryListing::read(disk_dir, mtime)?));
+        }
+        // Like a file state, the listing can only be trusted if the directory
+        // was last modified before the tree state was written. Otherwise, it
+        // could have been modified again without changing the mtime.
+        if let Some(listing) = self.tree_state.directory_listings.get(dir)
+            && listing.mtime == mtime
+            && self.tree_state.mtime_predates_state(mtime)
+        {
+            self.directory_listings_tx.send((dir.to_owned(), None)).ok();
+            return Ok(Cow::Borrowed(listing));
+        }
+        let listing = DirectoryListing::read(disk_dir, mtime)?;
+        self.directory_listings_tx
+            .send((dir.to_owned(), Some(listing.clone())))
+            .ok();
+        Ok(Cow::Owned(listing))
+    }
+
+    /// Maps names of untracked entries in the directory listing to the names
+    /// they should be tracked as.
+    ///
+    /// Some filesystems change the Unicode normalization form of file names,
+    /// so an entry whose name is equivalent to a missing tracked entry is
+    /// matched with it. Other names are converted to the configured
+    /// normalization form.
+    fn normalize_entry_names(
+        &self,
+        dir: &RepoPath,
+        listing: &DirectoryListing,
+        file_states: FileStates<'_>,
+    ) -> HashMap<String, String> {
+        let normalization = self.tree_state.path_normalization;
+        let mut disk_names = listing.dirs.iter().chain(&listing.files);
+        if normalization == PathNormalization::None || disk_names.all(|name| name.is_ascii()) {
+            return HashMap::new();
+        }
+        let tracked_names: HashSet<&str> = file_states
+            .paths()
+            .filter_map(|path| path.strip_prefix(dir)?.components().next())
+            .map(|name| name.as_internal_str())
+            .collect();
+        let mut missing_names: HashMap<String, &str> = tracked_names
+            .iter()
+            .filter(|name| !name.is_ascii() && !listing.contains(name))
+            .map(|name| (name.nfc().collect(), *name))
+            .collect();
+        let mut renamed_entries = HashMap::new();
+        let mut new_names = HashSet::new();
+        for disk_name in listing.dirs.iter().chain(&listing.files) {
+            if disk_name.is_ascii() || tracked_names.contains(disk_name.as_str()) {
+                continue;
+            }
+            let nfc_name: String = disk_name.nfc().collect();
+            let name = match missing_names.remove(&nfc_name) {
+                Some(tracked_name) => Cow::Borrowed(tracked_name),
+                None => normalization.normalize(disk_name),
+            };
+            if name != disk_name.as_str()
+                && !listing.contains(&name)
+                && new_names.insert(name.to_string())
+            {
+                renamed_entries.insert(disk_name.clone(), name.into_owned());
+            }
+        }
+        renamed_entries
+    }
+
+    /// Processes the entry `disk_name` of the directory, which is tracked as
+    /// `name_str`.
+    #[expect(clippy::too_many_arguments)]
     fn process_dir_entry<'scope>(
         &'scope self,
         dir: &RepoPath,
+        disk_dir: &Path,
         git_ignore: &Arc<GitIgnoreFile>,
         file_states: FileStates<'scope>,
-        entry: &DirEntry,
+        kind: PresentDirEntryKind,
+        disk_name: &str,
+        name_str: &str,
         scope: &rayon::Scope<'scope>,
     ) -> Result<Option<(PresentDirEntryKind, String)>, SnapshotError> {
-        let file_type = entry.file_type().unwrap();
-        let file_name = entry.file_name();
-        let name_string = file_name
-            .into_string()
-            .map_err(|path| SnapshotError::InvalidUtf8Path { path })?;
-
-        if RESERVED_DIR_NAMES.contains(&name_string.as_str()) {
+        if RESERVED_DIR_NAMES.contains(&name_str) {
             return Ok(None);
         }
-        let name = RepoPathComponent::new(&name_string).unwrap();
+        let name = RepoPathComponent::new(name_str).unwrap();
         let path = dir.join(name);
+        let disk_path = disk_dir.join(disk_name);
         let maybe_current_file_state = file_states.get_at(dir, name);
         if let Some(file_state) = &maybe_current_file_state
             && file_state.file_type == FileType::GitSubmodule
@@ -1554,25 +2479,18 @@ impl FileSnapshotter<'_> {
             return Ok(None);
         }
 
-        if file_type.is_dir() {
+        if kind == PresentDirEntryKind::Dir {
             let file_states = file_states.prefixed_at(dir, name);
-            // If a submodule was added in commit C, and a user decides to run
-            // `jj new <something before C>` from after C, then the submodule
-            // files stick around but it is no longer seen as a submodule.
-            // We need to ensure that it is not tracked as if it was added to
-            // the main repo.
-            // See https://github.com/jj-vcs/jj/issues/4349.
-            // To solve this, we ignore all nested repos entirely.
-            let disk_dir = entry.path();
-            for &name in RESERVED_DIR_NAMES {
-                if disk_dir.join(name).symlink_metadata().is_ok() {
-                    return Ok(None);
-                }
-            }
-
             if git_ignore.matches(&path.to_internal_dir_string())
                 && self.force_tracking_matcher.visit(&path).is_nothing()
             {
+                // Nested repos are otherwise detected while visiting the
+                // directory. See visit_directory().
+                for &name in RESERVED_DIR_NAMES {
+                    if disk_path.join(name).symlink_metadata().is_ok() {
+                        return Ok(None);
+                    }
+                }
                 // If the whole directory is ignored by .gitignore, visit only
                 // paths we're already tracking. This is because .gitignore in
                 // ignored directory must be ignored. It's also more efficient.
@@ -1582,7 +2500,7 @@ impl FileSnapshotter<'_> {
             } else if !self.matcher.visit(&path).is_nothing() {
                 let directory_to_visit = DirectoryToVisit {
                     dir: path,
-                    disk_dir,
+                    disk_dir: disk_path,
                     git_ignore: git_ignore.clone(),
                     file_states,
                 };
@@ -1592,11 +2510,11 @@ impl FileSnapshotter<'_> {
             }
             // Whether or not the directory path matches, any child file entries
             // shouldn't be touched within the current recursion step.
-            Ok(Some((PresentDirEntryKind::Dir, name_string)))
+            Ok(Some((PresentDirEntryKind::Dir, name_str.to_owned())))
         } else if self.matcher.matches(&path) {
-            if let Some(progress) = self.progress {
-                progress(&path);
-            }
+            let visited_files = self.visited_files.fetch_add(1, atomic::Ordering::Relaxed) + 1;
+            self.progress
+                .advance(visited_files, None, Some(path.as_internal_file_string()));
             if maybe_current_file_state.is_none()
                 && (git_ignore.matches(path.as_internal_file_string())
                     && !self.force_tracking_matcher.matches(&path))
@@ -1613,10 +2531,13 @@ impl FileSnapshotter<'_> {
                     .ok();
                 Ok(None)
             } else {
-                let metadata = entry.metadata().map_err(|err| SnapshotError::Other {
-                    message: format!("Failed to stat file {}", entry.path().display()),
-                    err: err.into(),
-                })?;
+                let metadata =
+                    disk_path
+                        .symlink_metadata()
+                        .map_err(|err| SnapshotError::Other {
+                            message: format!("Failed to stat file {}", disk_path.display()),
+                            err: err.into(),
+                        })?;
                 if maybe_current_file_state.is_none()
                     && (metadata.len() > self.max_new_file_size
                         && !self.force_tracking_matcher.matches(&path))
@@ -1629,15 +2550,15 @@ impl FileSnapshotter<'_> {
                     self.untracked_paths_tx.send((path, reason)).ok();
                     Ok(None)
                 } else if let Some(new_file_state) = file_state(&metadata)
-                    .map_err(|err| snapshot_error_for_mtime_out_of_range(err, &entry.path()))?
+                    .map_err(|err| snapshot_error_for_mtime_out_of_range(err, &disk_path))?
                 {
                     self.process_present_file(
                         path,
-                        &entry.path(),
+                        &disk_path,
                         maybe_current_file_state.as_ref(),
                         new_file_state,
                     )?;
-                    Ok(Some((PresentDirEntryKind::File, name_string)))
+                    Ok(Some((PresentDirEntryKind::File, name_str.to_owned())))
                 } else {
                     // Special file is not considered present
                     Ok(None)
@@ -1692,11 +2613,12 @@ impl FileSnapshotter<'_> {
         maybe_current_file_state: Option<&FileState>,
         mut new_file_state: FileState,
     ) -> Result<(), SnapshotError> {
+        let repo_path = self.renamed_paths.get(&path).unwrap_or(&path);
         let update = self.get_updated_tree_value(
-            &path,
+            repo_path,
             disk_path,
             maybe_current_file_state,
-            &new_file_state,
+            &mut new_file_state,
         )?;
         // Preserve materialized conflict data for normal, non-resolved files
         if matches!(new_file_state.file_type, FileType::Normal { .. })
@@ -1706,7 +2628,9 @@ impl FileSnapshotter<'_> {
                 maybe_current_file_state.and_then(|state| state.materialized_conflict_data);
         }
         if let Some(tree_value) = update {
-            self.tree_entries_tx.send((path.clone(), tree_value)).ok();
+            self.tree_entries_tx
+                .send((repo_path.clone(), tree_value))
+                .ok();
         }
         if Some(&new_file_state) != maybe_current_file_state {
             self.file_states_tx.send((path, new_file_state)).ok();
@@ -1752,7 +2676,7 @@ impl FileSnapshotter<'_> {
         repo_path: &RepoPath,
         disk_path: &Path,
         maybe_current_file_state: Option<&FileState>,
-        new_file_state: &FileState,
+        new_file_state: &mut FileState,
     ) -> Result<Option<MergedTreeValue>, SnapshotError> {
         let clean = match maybe_current_file_state {
             None => {
@@ -1760,13 +2684,41 @@ impl FileSnapshotter<'_> {
                 false
             }
             Some(current_file_state) => {
-                // If the file's mtime was set at the same time as this state file's own mtime,
-                // then we don't know if the file was modified before or after this state file.
                 new_file_state.is_clean(current_file_state)
-                    && current_file_state.mtime < self.tree_state.own_mtime
+                    && self.tree_state.mtime_predates_state(current_file_state.mtime)
+                    // With checksum-based detection, regular files are hashed
+                    // even if they look clean.
+                    && !(self.tree_state.change_detection == ChangeDetection::Checksum
+                        && matches!(new_file_state.file_type, FileType::Normal { .. }))
             }
         };
+        if !clean
+            && matches!(new_file_state.file_type, FileType::Normal { .. })
+            && self.tree_state.touched_file_check != TouchedFileCheck::None
+        {
+            // Hash the file before writing it to the store. If it's modified in
+            // between, the hash won't match next time.
+            let attributes = self.attributes(repo_path)?;
+            let conversion_key = self.tree_state.conversion_key(repo_path, &attributes);
+            new_file_state.quick_hash = self
+                .tree_state
+                .touched_file_check
+                .quick_hash(disk_path, &conversion_key)
+                .map_err(|err| SnapshotError::Other {
+                    message: format!("Failed to read file {}", disk_path.display()),
+                    err: err.into(),
+                })?;
+        }
         if clean {
+            new_file_state.quick_hash = maybe_current_file_state.and_then(|state| state.quick_hash);
+            Ok(None)
+        } else if let Some(current_file_state) = maybe_current_file_state
+            && new_file_state.quick_hash.is_some()
+            && new_file_state.quick_hash == current_file_state.quick_hash
+            && new_file_state.file_type == current_file_state.file_type
+            && new_file_state.size == current_file_state.size
+        {
+            // Only the mtime changed.
             Ok(None)
         } else {
             let current_tree_values = self.current_tree.path_value(repo_path)?;
@@ -1799,6 +2751,14 @@ impl FileSnapshotter<'_> {
                 }
                 FileType::GitSubmodule => panic!("git submodule cannot be written to store"),
             };
+            // If the file was modified after it was hashed, the stored contents
+            // may not match the hash, and changing the file back would go
+            // unnoticed. Don't record the hash in that case.
+            if new_file_state.quick_hash.is_some()
+                && !is_unmodified_since(disk_path, new_file_state)
+            {
+                new_file_state.quick_hash = None;
+            }
             if new_tree_values != current_tree_values {
                 Ok(Some(new_tree_values))
             } else {
@@ -1820,7 +2780,14 @@ impl FileSnapshotter<'_> {
         materialized_conflict_data: Option<MaterializedConflictData>,
     ) -> Result<MergedTreeValue, SnapshotError> {
         if let Some(current_tree_value) = current_tree_values.as_resolved() {
-            let id = self.write_file_to_store(repo_path, disk_path).await?;
+            let current_file_id = if let Some(TreeValue::File { id, .. }) = current_tree_value {
+                Some(id)
+            } else {
+                None
+            };
+            let id = self
+                .write_file_to_store(repo_path, disk_path, current_file_id)
+                .await?;
             // On Windows, we preserve the executable bit from the current tree.
             let executable = exec_bit.for_tree_value(self.tree_state.exec_policy, || {
                 if let Some(TreeValue::File {
@@ -1865,9 +2832,10 @@ impl FileSnapshotter<'_> {
                 message: format!("Failed to open file {}", disk_path.display()),
                 err: err.into(),
             })?;
+            let attributes = self.attributes(repo_path)?;
             self.tree_state
                 .target_eol_strategy
-                .convert_eol_for_snapshot(BlockingAsyncReader::new(file))
+                .convert_eol_for_snapshot(repo_path, &attributes, BlockingAsyncReader::new(file))
                 .await
                 .map_err(|err| SnapshotError::Other {
                     message: "Failed to convert the EOL".to_string(),
@@ -1924,21 +2892,109 @@ impl FileSnapshotter<'_> {
         &self,
         path: &RepoPath,
         disk_path: &Path,
+        current_file_id: Option<&FileId>,
     ) -> Result<FileId, SnapshotError> {
-        let file = File::open(disk_path).map_err(|err| SnapshotError::Other {
+        let attributes = self.attributes(path)?;
+        let mut file = File::open(disk_path).map_err(|err| SnapshotError::Other {
             message: format!("Failed to open file {}", disk_path.display()),
             err: err.into(),
         })?;
-        let mut contents = self
-            .tree_state
-            .target_eol_strategy
-            .convert_eol_for_snapshot(BlockingAsyncReader::new(file))
-            .await
+        // Like git, the clean filter runs first, then the EOL conversion, and
+        // then the `ident` collapsing.
+        let contents: Box<dyn AsyncRead + Send + Unpin> =
+            if let Some(filter) = attributes.value("filter") {
+                let mut contents = vec![];
+                file.read_to_end(&mut contents)
+                    .map_err(|err| SnapshotError::Other {
+                        message: format!("Failed to read file {}", disk_path.display()),
+                        err: err.into(),
+                    })?;
+                let contents = self
+                    .tree_state
+                    .content_filters
+                    .apply(filter, FilterDirection::Clean, path, contents)
+                    .map_err(|err| SnapshotError::Other {
+                        message: format!("Failed to filter file {}", disk_path.display()),
+                        err: err.into(),
+                    })?;
+                Box::new(Cursor::new(contents))
+            } else {
+                Box::new(BlockingAsyncReader::new(file))
+            };
+        // Like git, don't normalize files that were committed with CRLF line
+        // endings. Otherwise, they would show up as modified as soon as they're
+        // touched.
+        let mut contents = if let Some(id) = current_file_id
+            && self
+                .tree_state
+                .target_eol_strategy
+                .mode_for_path(path, &attributes)
+                != EolConversionMode::None
+            && self.stored_file_has_crlf(path, id).await?
+        {
+            contents
+        } else {
+            self.tree_state
+                .target_eol_strategy
+                .convert_eol_for_snapshot(path, &attributes, contents)
+                .await
+                .map_err(|err| SnapshotError::Other {
+                    message: "Failed to convert the EOL".to_string(),
+                    err: err.into(),
+                })?
+        };
+        if attributes.get("ident") == Some(&AttributeState::Set) {
+            let mut buf = vec![];
+            contents
+                .read_to_end(&mut buf)
+                .await
+                .map_err(|err| SnapshotError::Other {
+                    message: format!("Failed to read file {}", disk_path.display()),
+                    err: err.into(),
+                })?;
+            contents = Box::new(Cursor::new(collapse_ident(&buf).into_owned()));
+        }
+        Ok(self.store().write_file(path, &mut contents).await?)
+    }
+
+    fn attributes(&self, path: &RepoPath) -> Result<GitAttributes, SnapshotError> {
+        self.gitattributes
+            .attributes(path)
             .map_err(|err| SnapshotError::Other {
-                message: "Failed to convert the EOL".to_string(),
+                message: format!(
+                    "Failed to read the attributes of {}",
+                    path.as_internal_file_string()
+                ),
                 err: err.into(),
-            })?;
-        Ok(self.store().write_file(path, &mut contents).await?)
+            })
+    }
+
+    async fn stored_file_has_crlf(
+        &self,
+        path: &RepoPath,
+        id: &FileId,
+    ) -> Result<bool, SnapshotError> {
+        let mut reader = self.store().read_file(path, id).await?;
+        let mut buf = vec![0; 64 << 10];
+        let mut ends_with_cr = false;
+        loop {
+            let n = reader
+                .read(&mut buf)
+                .await
+                .map_err(|err| SnapshotError::Other {
+                    message: format!("Failed to read file {}", path.as_internal_file_string()),
+                    err: err.into(),
+                })?;
+            let chunk = &buf[..n];
+            if chunk.is_empty() {
+                return Ok(false);
+            }
+            // The CR and LF may be split across chunks.
+            if (ends_with_cr && chunk[0] == b'\n') || chunk.contains_str("\r\n") {
+                return Ok(true);
+            }
+            ends_with_cr = chunk.last() == Some(&b'\r');
+        }
     }
 
     async fn write_symlink_to_store(
@@ -1980,12 +3036,76 @@ fn snapshot_error_for_mtime_out_of_range(err: MtimeOutOfRange, path: &Path) -> S
 
 /// Functions to update local-disk files from the store.
 impl TreeState {
+    /// Converts the stored `contents` of the file `id` at `repo_path` into the
+    /// contents to write to disk.
+    ///
+    /// This is the reverse of the snapshot conversion: the `ident` expansion
+    /// runs first, then the EOL conversion, and then the smudge filter.
+    /// Describes how the contents of `path` are converted when they're written
+    /// to the store, so that a recorded [`FileState::quick_hash`] doesn't match
+    /// once the attributes or the conversion settings changed.
+    fn conversion_key(&self, path: &RepoPath, attributes: &GitAttributes) -> Vec<u8> {
+        let eol_mode = self.target_eol_strategy.mode_for_path(path, attributes);
+        let filter = attributes
+            .value("filter")
+            .map(|name| (name, self.content_filters.clean_fingerprint(name)));
+        let ident = attributes.get("ident");
+        format!("{eol_mode:?} {filter:?} {ident:?}").into_bytes()
+    }
+
+    async fn convert_file_for_update<'a>(
+        &self,
+        repo_path: &RepoPath,
+        attributes: &GitAttributes,
+        id: &FileId,
+        mut contents: impl AsyncRead + Send + Unpin + 'a,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin + 'a>, CheckoutError> {
+        let read_error = |err: io::Error| CheckoutError::Other {
+            message: format!(
+                "Failed to read the content of {}",
+                repo_path.as_internal_file_string()
+            ),
+            err: err.into(),
+        };
+        let contents: Box<dyn AsyncRead + Send + Unpin + 'a> =
+            if attributes.get("ident") == Some(&AttributeState::Set) {
+                let mut buf = vec![];
+                contents.read_to_end(&mut buf).await.map_err(read_error)?;
+                Box::new(Cursor::new(expand_ident(&buf, id).into_owned()))
+            } else {
+                Box::new(contents)
+            };
+        let mut contents = self
+            .target_eol_strategy
+            .convert_eol_for_update(repo_path, attributes, contents)
+            .await
+            .map_err(|err| CheckoutError::Other {
+                message: "Failed to convert the EOL for the content".to_string(),
+                err: err.into(),
+            })?;
+        let Some(filter) = attributes.value("filter") else {
+            return Ok(contents);
+        };
+        let mut buf = vec![];
+        contents.read_to_end(&mut buf).await.map_err(read_error)?;
+        let buf = self
+            .content_filters
+            .apply(filter, FilterDirection::Smudge, repo_path, buf)
+            .map_err(|err| CheckoutError::Other {
+                message: format!(
+                    "Failed to filter the content of {}",
+                    repo_path.as_internal_file_string()
+                ),
+                err: err.into(),
+            })?;
+        Ok(Box::new(Cursor::new(buf)))
+    }
+
     async fn write_file(
         &self,
         disk_path: &Path,
         contents: impl AsyncRead + Send + Unpin,
         exec_bit: ExecBit,
-        apply_eol_conversion: bool,
     ) -> Result<FileState, CheckoutError> {
         let mut file = File::options()
             .write(true)
@@ -1995,17 +3115,6 @@ impl TreeState {
                 message: format!("Failed to open file {} for writing", disk_path.display()),
                 err: err.into(),
             })?;
-        let contents = if apply_eol_conversion {
-            self.target_eol_strategy
-                .convert_eol_for_update(contents)
-                .await
-                .map_err(|err| CheckoutError::Other {
-                    message: "Failed to convert the EOL for the content".to_string(),
-                    err: err.into(),
-                })?
-        } else {
-            Box::new(contents)
-        };
         let size = copy_async_to_sync(contents, &mut file)
             .await
             .map_err(|err| CheckoutError::Other {
@@ -2068,13 +3177,15 @@ impl TreeState {
 
     async fn write_conflict(
         &self,
+        repo_path: &RepoPath,
+        attributes: &GitAttributes,
         disk_path: &Path,
         contents: &[u8],
         exec_bit: ExecBit,
     ) -> Result<FileState, CheckoutError> {
         let contents = self
             .target_eol_strategy
-            .convert_eol_for_update(contents)
+            .convert_eol_for_update(repo_path, attributes, contents)
             .await
             .map_err(|err| CheckoutError::Other {
                 message: "Failed to convert the EOL when writing a merge conflict".to_string(),
@@ -2103,28 +3214,91 @@ impl TreeState {
             .map_err(|err| checkout_error_for_mtime_out_of_range(err, disk_path))
     }
 
-    pub fn check_out(&mut self, new_tree: &MergedTree) -> Result<CheckoutStats, CheckoutError> {
+    pub fn check_out(
+        &mut self,
+        new_tree: &MergedTree,
+        options: &CheckoutOptions,
+    ) -> Result<CheckoutStats, CheckoutError> {
         let old_tree = self.tree.clone();
-        let stats = self
-            .update(&old_tree, new_tree, self.sparse_matcher().as_ref())
+        let sparse_matcher = self.sparse_matcher();
+        let old_collisions = self.case_collisions.clone();
+        let new_collisions = self.find_case_collisions(new_tree, sparse_matcher.as_ref());
+        self.check_case_collisions(&new_collisions)?;
+        let moved_stats = self
+            .remove_moved_case_collisions(&old_tree, &old_collisions, &new_collisions)
+            .block_on()?;
+        let colliding_matcher = FilesMatcher::new(
+            old_collisions
+                .iter()
+                .chain(&new_collisions)
+                .map(|c| &c.path),
+        );
+        let mut stats = self
+            .update(
+                &old_tree,
+                new_tree,
+                &DifferenceMatcher::new(&sparse_matcher, &colliding_matcher),
+                options.progress,
+            )
+            .block_on()?;
+        let collision_stats = self
+            .update_case_collisions(
+                &old_tree,
+                new_tree,
+                sparse_matcher.as_ref(),
+                &old_collisions,
+                new_collisions,
+            )
             .block_on()?;
+        stats.updated_files += collision_stats.updated_files;
+        stats.added_files += collision_stats.added_files;
+        stats.removed_files += moved_stats.removed_files;
+        stats.skipped_files += collision_stats.skipped_files;
+        stats.case_collisions = collision_stats.case_collisions;
         self.tree = new_tree.clone();
         Ok(stats)
     }
 
     pub fn set_sparse_patterns(
         &mut self,
-        sparse_patterns: Vec<RepoPathBuf>,
+        sparse_patterns: SparsePatterns,
     ) -> Result<CheckoutStats, CheckoutError> {
         let tree = self.tree.clone();
-        let old_matcher = PrefixMatcher::new(&self.sparse_patterns);
-        let new_matcher = PrefixMatcher::new(&sparse_patterns);
+        let old_matcher = self.sparse_patterns.to_matcher();
+        let new_matcher = sparse_patterns.to_matcher();
         let added_matcher = DifferenceMatcher::new(&new_matcher, &old_matcher);
         let removed_matcher = DifferenceMatcher::new(&old_matcher, &new_matcher);
+        let old_collisions = self.case_collisions.clone();
+        let new_collisions = self.find_case_collisions(&tree, &new_matcher);
+        self.check_case_collisions(&new_collisions)?;
+        let moved_stats = self
+            .remove_moved_case_collisions(&tree, &old_collisions, &new_collisions)
+            .block_on()?;
+        let colliding_matcher = FilesMatcher::new(
+            old_collisions
+                .iter()
+                .chain(&new_collisions)
+                .map(|c| &c.path),
+        );
         let empty_tree = self.store.empty_merged_tree();
-        let added_stats = self.update(&empty_tree, &tree, &added_matcher).block_on()?;
+        let added_stats = self
+            .update(
+                &empty_tree,
+                &tree,
+                &DifferenceMatcher::new(&added_matcher, &colliding_matcher),
+                None,
+            )
+            .block_on()?;
         let removed_stats = self
-            .update(&tree, &empty_tree, &removed_matcher)
+            .update(
+                &tree,
+                &empty_tree,
+                &DifferenceMatcher::new(&removed_matcher, &colliding_matcher),
+                None,
+            )
+            .block_on()?;
+        let collision_stats = self
+            .update_case_collisions(&tree, &tree, &new_matcher, &old_collisions, new_collisions)
             .block_on()?;
         self.sparse_patterns = sparse_patterns;
         assert_eq!(added_stats.updated_files, 0);
@@ -2132,20 +3306,307 @@ impl TreeState {
         assert_eq!(removed_stats.updated_files, 0);
         assert_eq!(removed_stats.added_files, 0);
         assert_eq!(removed_stats.skipped_files, 0);
+        assert_eq!(collision_stats.updated_files, 0);
         Ok(CheckoutStats {
             updated_files: 0,
-            added_files: added_stats.added_files,
-            removed_files: removed_stats.removed_files,
-            skipped_files: added_stats.skipped_files,
+            added_files: added_stats.added_files + collision_stats.added_files,
+            removed_files: removed_stats.removed_files + moved_stats.removed_files,
+            skipped_files: added_stats.skipped_files + collision_stats.skipped_files,
+            case_collisions: collision_stats.case_collisions,
         })
     }
 
+    /// Finds paths in `tree` that differ only in case from other paths, and
+    /// therefore can't be checked out at their own paths according to the
+    /// case collision policy. Paths which are already checked out take
+    /// precedence over the others, and are never reported.
+    ///
+    /// With the suffix policy, the shallowest colliding file or directory of
+    /// each path is renamed by appending `.case-collision-<n>` to its name.
+    fn find_case_collisions(&self, tree: &MergedTree, matcher: &dyn Matcher) -> Vec<CaseCollision> {
+        if self.case_collision_policy == CaseCollisionPolicy::Ignore {
+            return vec![];
+        }
+        let fold = |path: &RepoPath| path.as_internal_file_string().to_lowercase();
+        let file_states = self.file_states();
+        let tree_paths = tree
+            .entries_matching(matcher)
+            .map(|(path, _)| path)
+            .collect_vec();
+        // Suffixed names must not collide with any path in the tree.
+        let mut taken_names: HashSet<String> = tree_paths
+            .iter()
+            .flat_map(|path| path.ancestors())
+            .filter(|ancestor| !ancestor.is_root())
+            .map(fold)
+            .collect();
+        let (checked_out_paths, other_paths): (Vec<_>, Vec<_>) = tree_paths
+            .iter()
+            .partition(|path| file_states.contains_path(path));
+        // Maps case-folded file and directory paths to the paths they're
+        // checked out as.
+        let mut folded_paths: HashMap<String, RepoPathBuf> = HashMap::new();
+        // Maps colliding directories and files to the names they're renamed to.
+        let mut suffixed_paths: HashMap<RepoPathBuf, RepoPathBuf> = HashMap::new();
+        let mut collisions = vec![];
+        let all_paths = itertools::chain(
+            checked_out_paths.into_iter().map(|path| (path, true)),
+            other_paths.into_iter().map(|path| (path, false)),
+        );
+        'paths: for (path, checked_out) in all_paths {
+            let mut disk_path = path.clone();
+            let mut other_path = None;
+            while let Some((ancestor, other)) = disk_path
+                .ancestors()
+                .filter(|ancestor| !ancestor.is_root())
+                .collect_vec()
+                .into_iter()
+                .rev()
+                .find_map(|ancestor| {
+                    let other = folded_paths.get(&fold(ancestor))?;
+                    (other.as_ref() != ancestor).then(|| (ancestor.to_owned(), other.clone()))
+                })
+            {
+                // The filesystem must be case-sensitive if both paths have been
+                // checked out.
+                if checked_out {
+                    continue 'paths;
+                }
+                other_path.get_or_insert(other);
+                if self.case_collision_policy != CaseCollisionPolicy::Suffix {
+                    collisions.push(CaseCollision {
+                        path: path.clone(),
+                        other_path: other_path.unwrap(),
+                        checked_out_as: None,
+                    });
+                    continue 'paths;
+                }
+                let suffixed = suffixed_paths.entry(ancestor.clone()).or_insert_with(|| {
+                    let (parent, name) = ancestor.split().unwrap();
+                    (1..)
+                        .map(|n| {
+                            let name = format!("{}.case-collision-{n}", name.as_internal_str());
+                            parent.join(RepoPathComponent::new(&name).unwrap())
+                        })
+                        .find(|suffixed| taken_names.insert(fold(suffixed)))
+                        .unwrap()
+                });
+                let mut suffixed_path = suffixed.clone();
+                suffixed_path.extend(disk_path.strip_prefix(&ancestor).unwrap().components());
+                disk_path = suffixed_path;
+            }
+            folded_paths.extend(
+                disk_path
+                    .ancestors()
+                    .filter(|ancestor| !ancestor.is_root())
+                    .map(|ancestor| (fold(ancestor), ancestor.to_owned())),
+            );
+            if let Some(other_path) = other_path {
+                collisions.push(CaseCollision {
+                    path: path.clone(),
+                    other_path,
+                    checked_out_as: Some(disk_path),
+                });
+            }
+        }
+        collisions.sort_unstable_by(|c1, c2| c1.path.cmp(&c2.path));
+        collisions
+    }
+
+    fn check_case_collisions(&self, collisions: &[CaseCollision]) -> Result<(), CheckoutError> {
+        if self.case_collision_policy == CaseCollisionPolicy::Error && !collisions.is_empty() {
+            return Err(CheckoutError::CaseCollision(collisions.to_vec()));
+        }
+        Ok(())
+    }
+
+    /// Finds the new files among the snapshotted `tree_entries` which differ
+    /// only in case from other paths in the working-copy commit, and maps
+    /// them to the paths they collide with. Of colliding new files, the first
+    /// one is accepted.
+    fn find_new_case_collisions(
+        &self,
+        tree_entries: &[(RepoPathBuf, MergedTreeValue)],
+        deleted_files: &HashSet<RepoPathBuf>,
+        renamed_paths: &HashMap<RepoPathBuf, RepoPathBuf>,
+    ) -> HashMap<RepoPathBuf, RepoPathBuf> {
+        if self.case_collision_policy == CaseCollisionPolicy::Ignore {
+            return HashMap::new();
+        }
+        let file_states = self.file_states();
+        let renamed_tree_paths: HashSet<&RepoPath> =
+            renamed_paths.values().map(AsRef::as_ref).collect();
+        let new_paths = tree_entries
+            .iter()
+            .map(|(path, _)| path.as_ref())
+            .filter(|path| !file_states.contains_path(path) && !renamed_tree_paths.contains(path))
+            .sorted_unstable()
+            .collect_vec();
+        if new_paths.is_empty() {
+            return HashMap::new();
+        }
+        let fold = |path: &RepoPath| path.as_internal_file_string().to_lowercase();
+        // The files that stay checked out take precedence over the files that
+        // aren't checked out.
+        let existing_paths = file_states
+            .paths()
+            .filter(|path| !deleted_files.contains(*path))
+            .map(|path| renamed_paths.get(path).map_or(path, AsRef::as_ref))
+            .chain(
+                self.case_collisions
+                    .iter()
+                    .filter(|c| c.checked_out_as.is_none())
+                    .map(|c| c.path.as_ref()),
+            );
+        let mut folded_paths: HashMap<String, &RepoPath> = HashMap::new();
+        for path in existing_paths {
+            for ancestor in path.ancestors().filter(|ancestor| !ancestor.is_root()) {
+                folded_paths.entry(fold(ancestor)).or_insert(ancestor);
+            }
+        }
+        let mut collisions = HashMap::new();
+        for path in new_paths {
+            let folded_ancestors = path
+                .ancestors()
+                .filter(|ancestor| !ancestor.is_root())
+                .map(|ancestor| (fold(ancestor), ancestor))
+                .collect_vec();
+            let other_path = folded_ancestors
+                .iter()
+                .rev()
+                .find_map(|(folded, ancestor)| {
+                    folded_paths
+                        .get(folded)
+                        .filter(|other_path| *other_path != ancestor)
+                });
+            if let Some(other_path) = other_path {
+                collisions.insert(path.to_owned(), (*other_path).to_owned());
+            } else {
+                folded_paths.extend(folded_ancestors);
+            }
+        }
+        collisions
+    }
+
+    /// Removes the files of `old_collisions` that were checked out under
+    /// other names, unless they're checked out under the same names in
+    /// `new_collisions`. This has to happen before the other paths are
+    /// updated, which might reuse the names.
+    async fn remove_moved_case_collisions(
+        &mut self,
+        old_tree: &MergedTree,
+        old_collisions: &[CaseCollision],
+        new_collisions: &[CaseCollision],
+    ) -> Result<CheckoutStats, CheckoutError> {
+        let moved_collisions = old_collisions
+            .iter()
+            .filter(|c| c.checked_out_as.is_some() && !new_collisions.contains(c))
+            .collect_vec();
+        if moved_collisions.is_empty() {
+            return Ok(CheckoutStats::default());
+        }
+        let disk_paths = case_collision_disk_paths(moved_collisions.iter().copied());
+        let empty_tree = self.store.empty_merged_tree();
+        self.update_at_disk_paths(
+            old_tree,
+            &empty_tree,
+            &FilesMatcher::new(moved_collisions.iter().map(|c| &c.path)),
+            &disk_paths,
+            None,
+        )
+        .await
+    }
+
+    /// Checks out the paths in `new_tree` that collide with other paths under
+    /// their new names, and the paths that no longer collide at their own
+    /// paths. The paths in either list must have been excluded from the
+    /// preceding update.
+    async fn update_case_collisions(
+        &mut self,
+        old_tree: &MergedTree,
+        new_tree: &MergedTree,
+        matcher: &dyn Matcher,
+        old_collisions: &[CaseCollision],
+        new_collisions: Vec<CaseCollision>,
+    ) -> Result<CheckoutStats, CheckoutError> {
+        let empty_tree = self.store.empty_merged_tree();
+        let new_paths: HashSet<&RepoPath> =
+            new_collisions.iter().map(|c| c.path.as_ref()).collect();
+        let (kept_collisions, added_collisions): (Vec<_>, Vec<_>) = new_collisions
+            .iter()
+            .filter(|c| c.checked_out_as.is_some())
+            .partition(|c| old_collisions.contains(c));
+        let mut stats = CheckoutStats::default();
+        for (old_tree, collisions) in [(old_tree, kept_collisions), (&empty_tree, added_collisions)]
+        {
+            if collisions.is_empty() {
+                continue;
+            }
+            let disk_paths = case_collision_disk_paths(collisions.iter().copied());
+            let files_matcher = FilesMatcher::new(collisions.iter().map(|c| &c.path));
+            let suffixed_stats = self
+                .update_at_disk_paths(
+                    old_tree,
+                    new_tree,
+                    &IntersectionMatcher::new(matcher, &files_matcher),
+                    &disk_paths,
+                    None,
+                )
+                .await?;
+            stats.updated_files += suffixed_stats.updated_files;
+            stats.added_files += suffixed_stats.added_files;
+            stats.skipped_files += suffixed_stats.skipped_files;
+        }
+        let uncovered_paths = old_collisions
+            .iter()
+            .map(|c| c.path.as_ref())
+            .filter(|path| !new_paths.contains(path))
+            .collect_vec();
+        if !uncovered_paths.is_empty() {
+            let uncovered_matcher = FilesMatcher::new(uncovered_paths);
+            let uncovered_stats = self
+                .update(
+                    &empty_tree,
+                    new_tree,
+                    &IntersectionMatcher::new(matcher, &uncovered_matcher),
+                    None,
+                )
+                .await?;
+            stats.added_files += uncovered_stats.added_files;
+            stats.skipped_files += uncovered_stats.skipped_files;
+        }
+        stats.case_collisions = new_collisions
+            .iter()
+            .filter(|c| !old_collisions.contains(c))
+            .cloned()
+            .collect();
+        self.case_collisions = new_collisions;
+        Ok(stats)
+    }
+
     async fn update(
         &mut self,
         old_tree: &MergedTree,
         new_tree: &MergedTree,
         matcher: &dyn Matcher,
+        progress: Option<&dyn Progress>,
+    ) -> Result<CheckoutStats, CheckoutError> {
+        self.update_at_disk_paths(old_tree, new_tree, matcher, &HashMap::new(), progress)
+            .await
+    }
+
+    /// Like `update()`, but checks out the paths in `disk_paths` at the
+    /// mapped paths instead.
+    async fn update_at_disk_paths(
+        &mut self,
+        old_tree: &MergedTree,
+        new_tree: &MergedTree,
+        matcher: &dyn Matcher,
+        disk_paths: &HashMap<RepoPathBuf, RepoPathBuf>,
+        progress: Option<&dyn Progress>,
     ) -> Result<CheckoutStats, CheckoutError> {
+        let progress = ProgressScope::start(progress, ProgressTask::Checkout);
+        let mut processed_files = 0;
         // TODO: maybe it's better not include the skipped counts in the "intended"
         // counts
         let mut stats = CheckoutStats {
@@ -2153,15 +3614,35 @@ impl TreeState {
             added_files: 0,
             removed_files: 0,
             skipped_files: 0,
+            case_collisions: vec![],
         };
         let mut changed_file_states = Vec::new();
         let mut deleted_files = HashSet::new();
         let mut prev_created_path: RepoPathBuf = RepoPathBuf::root();
+        let gitattributes = self.tree_gitattributes(new_tree);
+        let attributes_for_update = |path: &RepoPath| {
+            gitattributes
+                .attributes(path)
+                .map_err(|err| CheckoutError::Other {
+                    message: format!(
+                        "Failed to read the attributes of {}",
+                        path.as_internal_file_string()
+                    ),
+                    err: err.into(),
+                })
+        };
 
         let mut process_diff_entry = async |path: RepoPathBuf,
                                             before: MergedTreeValue,
                                             after: MaterializedTreeValue|
                -> Result<(), CheckoutError> {
+            // The file state is tracked at the path the file is checked out
+            // as, but its attributes are those of the path in the tree.
+            let (path, renamed_path) = match disk_paths.get(&path) {
+                Some(disk_path) => (disk_path.clone(), Some(path)),
+                None => (path, None),
+            };
+            let repo_path = renamed_path.as_ref().unwrap_or(&path);
             if after.is_absent() {
                 stats.removed_files += 1;
             } else if before.is_absent() {
@@ -2271,15 +3752,34 @@ impl TreeState {
                 MaterializedTreeValue::File(file) => {
                     let exec_bit =
                         ExecBit::new_from_repo(file.executable, self.exec_policy, get_prev_exec);
-                    self.write_file(&disk_path, file.reader, exec_bit, true)
-                        .await?
+                    let attributes = attributes_for_update(repo_path)?;
+                    let contents = self
+                        .convert_file_for_update(repo_path, &attributes, &file.id, file.reader)
+                        .await?;
+                    let mut file_state = self.write_file(&disk_path, contents, exec_bit).await?;
+                    let conversion_key = self.conversion_key(repo_path, &attributes);
+                    file_state.quick_hash = self
+                        .touched_file_check
+                        .quick_hash(&disk_path, &conversion_key)
+                        .map_err(|err| CheckoutError::Other {
+                            message: format!("Failed to read file {}", disk_path.display()),
+                            err: err.into(),
+                        })?;
+                    // The hash may not be of the checked-out contents if the
+                    // file was modified in the meantime.
+                    if file_state.quick_hash.is_some()
+                        && !is_unmodified_since(&disk_path, &file_state)
+                    {
+                        file_state.quick_hash = None;
+                    }
+                    file_state
                 }
                 MaterializedTreeValue::Symlink { id: _, target } => {
                     if self.symlink_support {
                         self.write_symlink(&disk_path, target)?
                     } else {
                         // The fake symlink file shouldn't be executable.
-                        self.write_file(&disk_path, target.as_bytes(), ExecBit(false), false)
+                        self.write_file(&disk_path, target.as_bytes(), ExecBit(false))
                             .await?
                     }
                 }
@@ -2305,8 +3805,10 @@ impl TreeState {
                     );
                     let contents =
                         materialize_merge_result_to_bytes(&file.contents, &file.labels, &options);
-                    let mut file_state =
-                        self.write_conflict(&disk_path, &contents, exec_bit).await?;
+                    let attributes = attributes_for_update(repo_path)?;
+                    let mut file_state = self
+                        .write_conflict(repo_path, &attributes, &disk_path, &contents, exec_bit)
+                        .await?;
                     file_state.materialized_conflict_data = Some(MaterializedConflictData {
                         conflict_marker_len: conflict_marker_len.try_into().unwrap_or(u32::MAX),
                     });
@@ -2317,8 +3819,15 @@ impl TreeState {
                     // better than trying to describe the merge.
                     let contents = id.describe(&labels);
                     // Since this is a dummy file, it shouldn't be executable.
-                    self.write_conflict(&disk_path, contents.as_bytes(), ExecBit(false))
-                        .await?
+                    let attributes = attributes_for_update(repo_path)?;
+                    self.write_conflict(
+                        repo_path,
+                        &attributes,
+                        &disk_path,
+                        contents.as_bytes(),
+                        ExecBit(false),
+                    )
+                    .await?
                 }
             };
             changed_file_states.push((path, file_state));
@@ -2361,6 +3870,8 @@ impl TreeState {
         while let Some((path, data)) = diff_stream.next().await {
             let (before, after) = data?;
             conflicts_to_rematerialize.remove(&path);
+            processed_files += 1;
+            progress.advance(processed_files, None, Some(path.as_internal_file_string()));
             process_diff_entry(path, before, after).await?;
         }
 
@@ -2369,12 +3880,17 @@ impl TreeState {
                 let materialized =
                     materialize_tree_value(&self.store, &path, conflict.clone(), new_tree.labels())
                         .await?;
+                processed_files += 1;
+                progress.advance(processed_files, None, Some(path.as_internal_file_string()));
                 process_diff_entry(path, conflict, materialized).await?;
             }
 
             // We need to re-sort the changed file states since we may have inserted a
             // conflicted file out of order.
             changed_file_states.sort_unstable_by(|(path1, _), (path2, _)| path1.cmp(path2));
+        } else if !disk_paths.is_empty() {
+            // The renamed paths may be out of order too.
+            changed_file_states.sort_unstable_by(|(path1, _), (path2, _)| path1.cmp(path2));
         }
 
         self.file_states
@@ -2383,12 +3899,43 @@ impl TreeState {
     }
 
     pub async fn reset(&mut self, new_tree: &MergedTree) -> Result<(), ResetError> {
-        let matcher = self.sparse_matcher();
-        let mut changed_file_states = Vec::new();
-        let mut deleted_files = HashSet::new();
+        let sparse_matcher = self.sparse_matcher();
+        let old_collisions = mem::take(&mut self.case_collisions);
+        let new_collisions = self.find_case_collisions(new_tree, sparse_matcher.as_ref());
+        // Paths that differ only in case from other paths aren't checked out
+        // at their own paths, and paths that no longer collide have to be
+        // reset even if they're unchanged.
+        let new_colliding_paths: HashSet<&RepoPath> =
+            new_collisions.iter().map(|c| c.path.as_ref()).collect();
+        let colliding_matcher = FilesMatcher::new(
+            old_collisions
+                .iter()
+                .chain(&new_collisions)
+                .map(|c| &c.path),
+        );
+        let uncovered_matcher = FilesMatcher::new(
+            old_collisions
+                .iter()
+                .map(|c| c.path.as_ref())
+                .filter(|path| !new_colliding_paths.contains(path)),
+        );
+        let matcher = DifferenceMatcher::new(&sparse_matcher, &colliding_matcher);
+        let uncovered_matcher = IntersectionMatcher::new(&sparse_matcher, &uncovered_matcher);
+        let mut changed_file_states = new_collisions
+            .iter()
+            .filter_map(|c| Some((c.checked_out_as.clone()?, FileState::placeholder())))
+            .collect_vec();
+        let mut deleted_files: HashSet<_> = old_collisions
+            .iter()
+            .filter_map(|c| c.checked_out_as.clone())
+            .chain(new_collisions.iter().map(|c| c.path.clone()))
+            .filter(|path| !changed_file_states.iter().any(|(p, _)| p == path))
+            .collect();
+        let empty_tree = self.store.empty_merged_tree();
         let mut diff_stream = self
             .tree
-            .diff_stream_for_file_system(new_tree, matcher.as_ref());
+            .diff_stream_for_file_system(new_tree, &matcher)
+            .chain(empty_tree.diff_stream_for_file_system(new_tree, &uncovered_matcher));
         while let Some(TreeDiffEntry { path, values }) = diff_stream.next().await {
             let after = values?.after;
             if after.is_absent() {
@@ -2427,12 +3974,18 @@ impl TreeState {
                     mtime: MillisSinceEpoch(0),
                     size: 0,
                     materialized_conflict_data: None,
+                    quick_hash: None,
                 };
                 changed_file_states.push((path, file_state));
             }
         }
+        changed_file_states.sort_unstable_by(|(path1, _), (path2, _)| path1.cmp(path2));
         self.file_states
             .merge_in(changed_file_states, &deleted_files);
+        self.case_collisions = new_collisions;
+        // The file states no longer match the files on disk, so the tracked
+        // entries can't be omitted from the persisted directory listings.
+        self.directory_listings.clear();
         self.tree = new_tree.clone();
         Ok(())
     }
@@ -2444,6 +3997,21 @@ impl TreeState {
     }
 }
 
+/// Returns true if the file at `disk_path` still has the mtime and size
+/// recorded in `old_file_state`.
+fn is_unmodified_since(disk_path: &Path, old_file_state: &FileState) -> bool {
+    let Ok(metadata) = disk_path.symlink_metadata() else {
+        return false;
+    };
+    match file_state(&metadata) {
+        Ok(Some(new_file_state)) => {
+            new_file_state.mtime == old_file_state.mtime
+                && new_file_state.size == old_file_state.size
+        }
+        Ok(None) | Err(_) => false,
+    }
+}
+
 fn checkout_error_for_stat_error(err: io::Error, path: &Path) -> CheckoutError {
     CheckoutError::Other {
         message: format!("Failed to stat file {}", path.display()),
@@ -2536,10 +4104,14 @@ impl WorkingCopy for LocalWorkingCopy {
         Ok(self.tree_state()?.current_tree())
     }
 
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError> {
+    fn sparse_patterns(&self) -> Result<&SparsePatterns, WorkingCopyStateError> {
         Ok(self.tree_state()?.sparse_patterns())
     }
 
+    fn force_tracked_paths(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError> {
+        Ok(self.tree_state()?.force_tracked_paths())
+    }
+
     fn start_mutation(&self) -> Result<Box<dyn LockedWorkingCopy>, WorkingCopyStateError> {
         let lock_path = self.state_path.join("working_copy.lock");
         let lock = FileLock::lock(lock_path).map_err(|err| WorkingCopyStateError {
@@ -2698,6 +4270,32 @@ impl LocalWorkingCopy {
     }
 }
 
+#[cfg(feature = "builtin-fsmonitor")]
+impl LocalWorkingCopy {
+    /// Returns a handle to the builtin filesystem monitor for this working
+    /// copy. The daemon may not be running.
+    pub fn builtin_fsmonitor(&self) -> builtin::Fsmonitor {
+        builtin_fsmonitor_at(&self.state_path)
+    }
+
+    pub fn query_builtin_fsmonitor(
+        &self,
+        config: &BuiltinFsmonitorConfig,
+    ) -> Result<(Option<builtin::Clock>, Option<Vec<PathBuf>>), WorkingCopyStateError> {
+        self.tree_state()?
+            .query_builtin_fsmonitor(config)
+            .map_err(|err| WorkingCopyStateError {
+                message: "Failed to query filesystem monitor".to_string(),
+                err: err.into(),
+            })
+    }
+}
+
+#[cfg(feature = "builtin-fsmonitor")]
+fn builtin_fsmonitor_at(state_path: &Path) -> builtin::Fsmonitor {
+    builtin::Fsmonitor::new(state_path.join("fsmonitor"))
+}
+
 pub struct LocalWorkingCopyFactory {}
 
 impl WorkingCopyFactory for LocalWorkingCopyFactory {
@@ -2767,13 +4365,17 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
         Ok((tree_state.current_tree().clone(), stats))
     }
 
-    async fn check_out(&mut self, commit: &Commit) -> Result<CheckoutStats, CheckoutError> {
+    async fn check_out(
+        &mut self,
+        commit: &Commit,
+        options: &CheckoutOptions,
+    ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: Write a "pending_checkout" file with the new TreeId so we can
         // continue an interrupted update if we find such a file.
         let new_tree = commit.tree();
         let tree_state = self.wc.tree_state_mut()?;
         if tree_state.tree.tree_ids_and_labels() != new_tree.tree_ids_and_labels() {
-            let stats = tree_state.check_out(&new_tree)?;
+            let stats = tree_state.check_out(&new_tree, options)?;
             self.tree_state_dirty = true;
             Ok(stats)
         } else {
@@ -2799,13 +4401,13 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
         Ok(())
     }
 
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError> {
+    fn sparse_patterns(&self) -> Result<&SparsePatterns, WorkingCopyStateError> {
         self.wc.sparse_patterns()
     }
 
     async fn set_sparse_patterns(
         &mut self,
-        new_sparse_patterns: Vec<RepoPathBuf>,
+        new_sparse_patterns: SparsePatterns,
     ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: Write a "pending_checkout" file with new sparse patterns so we can
         // continue an interrupted update if we find such a file.
@@ -2817,6 +4419,19 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
         Ok(stats)
     }
 
+    fn force_tracked_paths(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError> {
+        self.wc.force_tracked_paths()
+    }
+
+    fn set_force_tracked_paths(
+        &mut self,
+        paths: Vec<RepoPathBuf>,
+    ) -> Result<(), WorkingCopyStateError> {
+        self.wc.tree_state_mut()?.set_force_tracked_paths(paths);
+        self.tree_state_dirty = true;
+        Ok(())
+    }
+
     #[instrument(skip_all)]
     async fn finish(
         mut self: Box<Self>,
@@ -2879,6 +4494,7 @@ mod tests {
             mtime: MillisSinceEpoch(0),
             size,
             materialized_conflict_data: None,
+            quick_hash: None,
         }
     }
 
@@ -3047,6 +4663,104 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_update_directory_listings() {
+        let new_listing = |mtime, dirs: &[&str]| DirectoryListing {
+            mtime: MillisSinceEpoch(mtime),
+            dirs: dirs.iter().map(|name| name.to_string()).collect(),
+            files: vec![],
+        };
+        let mut listings: BTreeMap<_, _> = [
+            ("", new_listing(0, &["a", "b"])),
+            ("a", new_listing(0, &["c"])),
+            ("a/c", new_listing(0, &[])),
+            ("b", new_listing(0, &[])),
+        ]
+        .into_iter()
+        .map(|(path, listing)| (repo_path(path).to_owned(), listing))
+        .collect();
+
+        // Reused and unvisited listings are kept
+        let visited = vec![(repo_path("").to_owned(), None)];
+        assert!(!update_directory_listings(&mut listings, visited));
+        assert_eq!(listings.len(), 4);
+
+        // Listings of removed directories are dropped, recursively
+        let visited = vec![
+            (repo_path("").to_owned(), Some(new_listing(1, &["b"]))),
+            (repo_path("b").to_owned(), None),
+        ];
+        assert!(update_directory_listings(&mut listings, visited));
+        assert_eq!(
+            listings.keys().map(|path| path.as_ref()).collect_vec(),
+            ["", "b"].map(repo_path)
+        );
+        assert_eq!(listings[repo_path("")], new_listing(1, &["b"]));
+    }
+
+    #[test]
+    fn test_directory_listings_proto_omits_tracked_entries() {
+        let new_proto_entry = |path: &str, size| {
+            file_state_entry_to_proto(repo_path(path).to_owned(), &new_state(size))
+        };
+        let data = vec![
+            new_proto_entry("a/b/c", 0),
+            new_proto_entry("a/d", 1),
+            new_proto_entry("a#", 2), // '#' < '/'
+            new_proto_entry("e", 3),
+        ];
+        let file_states = FileStates::from_sorted(&data);
+        let new_listing = |dirs: &[&str], files: &[&str]| DirectoryListing {
+            mtime: MillisSinceEpoch(1),
+            dirs: dirs.iter().map(|name| name.to_string()).collect(),
+            files: files.iter().map(|name| name.to_string()).collect(),
+        };
+        let listings: BTreeMap<_, _> = [
+            ("", new_listing(&["a", "target"], &["a#", "e", "untracked"])),
+            ("a", new_listing(&["b"], &["d"])),
+        ]
+        .into_iter()
+        .map(|(path, listing)| (repo_path(path).to_owned(), listing))
+        .collect();
+
+        let proto = directory_listings_to_proto(&listings, file_states);
+        assert_eq!(
+            proto
+                .iter()
+                .map(|listing| (
+                    listing.path.as_str(),
+                    &listing.untracked_dirs[..],
+                    &listing.untracked_files[..]
+                ))
+                .collect_vec(),
+            [
+                (
+                    "",
+                    &["target".to_owned()][..],
+                    &["untracked".to_owned()][..]
+                ),
+                ("a", &[][..], &[][..]),
+            ]
+        );
+        assert_eq!(directory_listings_from_proto(proto, file_states), listings);
+    }
+
+    #[test]
+    fn test_is_unmodified_since() {
+        let temp_dir = crate::tests::new_temp_dir();
+        let disk_path = temp_dir.path().join("file");
+        std::fs::write(&disk_path, "a").unwrap();
+        let metadata = disk_path.symlink_metadata().unwrap();
+        let state = file_state(&metadata).unwrap().unwrap();
+        assert!(is_unmodified_since(&disk_path, &state));
+
+        std::fs::write(&disk_path, "ab").unwrap();
+        assert!(!is_unmodified_since(&disk_path, &state));
+
+        std::fs::remove_file(&disk_path).unwrap();
+        assert!(!is_unmodified_since(&disk_path, &state));
+    }
+
     #[test]
     fn test_system_time_to_millis() {
         let epoch = SystemTime::UNIX_EPOCH;
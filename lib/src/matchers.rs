@@ -220,6 +220,53 @@ impl Matcher for PrefixMatcher {
     }
 }
 
+/// Matches paths under the given prefixes, and files directly in the ancestor
+/// directories of the prefixes, like Git's cone-mode sparse checkout.
+#[derive(Debug)]
+pub struct ConeMatcher {
+    tree: RepoPathTree<PrefixNodeKind>,
+}
+
+impl ConeMatcher {
+    #[instrument(skip(prefixes))]
+    pub fn new(prefixes: impl IntoIterator<Item = impl AsRef<RepoPath>>) -> Self {
+        let mut tree = RepoPathTree::default();
+        for prefix in prefixes {
+            tree.add(prefix.as_ref()).set_value(PrefixNodeKind::Prefix);
+        }
+        Self { tree }
+    }
+}
+
+impl Matcher for ConeMatcher {
+    fn matches(&self, file: &RepoPath) -> bool {
+        self.tree.walk_to(file).any(|(sub, tail_path)| {
+            // 'file' is under a prefix, or is a direct child of an ancestor
+            // directory of prefix paths
+            *sub.value() == PrefixNodeKind::Prefix
+                || tail_path.parent().is_some_and(|dir| dir.is_root())
+        })
+    }
+
+    fn visit(&self, dir: &RepoPath) -> Visit {
+        for (sub, tail_path) in self.tree.walk_to(dir) {
+            // ancestor of 'dir' matches prefix paths
+            if *sub.value() == PrefixNodeKind::Prefix {
+                return Visit::AllRecursively;
+            }
+            // 'dir' found, and is an ancestor of prefix paths
+            if tail_path.is_root() {
+                let dirs = sub.children().map(|(name, _)| name.to_owned()).collect();
+                return Visit::Specific {
+                    dirs: VisitDirs::Set(dirs),
+                    files: VisitFiles::All,
+                };
+            }
+        }
+        Visit::Nothing
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 enum PrefixNodeKind {
     /// Represents an intermediate directory.
@@ -730,6 +777,43 @@ mod tests {
         assert_eq!(m.visit(repo_path("foo/bar/baz")), Visit::AllRecursively);
     }
 
+    #[test]
+    fn test_cone_matcher() {
+        let m = ConeMatcher::new([repo_path("foo/bar")]);
+
+        // Files directly in the ancestor directories should match
+        assert!(m.matches(repo_path("README")));
+        assert!(m.matches(repo_path("foo/README")));
+        assert!(m.matches(repo_path("foo/bar")));
+        // Files under the prefix should match
+        assert!(m.matches(repo_path("foo/bar/baz")));
+        assert!(m.matches(repo_path("foo/bar/baz/qux")));
+        // Files in sibling directories should not match
+        assert!(!m.matches(repo_path("bar/README")));
+        assert!(!m.matches(repo_path("foo/baz/README")));
+
+        // All files in the ancestor directories should be visited, but only the
+        // directories leading to the prefix
+        assert_eq!(
+            m.visit(RepoPath::root()),
+            Visit::Specific {
+                dirs: VisitDirs::Set(hashset! {repo_path_component_buf("foo")}),
+                files: VisitFiles::All,
+            }
+        );
+        assert_eq!(
+            m.visit(repo_path("foo")),
+            Visit::Specific {
+                dirs: VisitDirs::Set(hashset! {repo_path_component_buf("bar")}),
+                files: VisitFiles::All,
+            }
+        );
+        assert_eq!(m.visit(repo_path("foo/bar")), Visit::AllRecursively);
+        assert_eq!(m.visit(repo_path("foo/bar/baz")), Visit::AllRecursively);
+        assert_eq!(m.visit(repo_path("bar")), Visit::Nothing);
+        assert_eq!(m.visit(repo_path("foo/baz")), Visit::Nothing);
+    }
+
     #[test]
     fn test_file_globs_matcher_rooted() {
         let m = new_file_globs_matcher(&[(RepoPath::root(), glob("*.rs"))]);
@@ -16,6 +16,7 @@
 
 use std::any::Any;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt::Debug;
@@ -31,6 +32,7 @@ use crate::backend::CommitId;
 use crate::backend::MillisSinceEpoch;
 use crate::backend::Timestamp;
 use crate::content_hash::ContentHash;
+use crate::content_hash::DigestUpdate;
 use crate::merge::Merge;
 use crate::object_id::HexPrefix;
 use crate::object_id::ObjectId as _;
@@ -246,7 +248,7 @@ pub struct LocalRemoteRefTarget<'a> {
 
 /// Represents the way the repo looks at a given time, just like how a Tree
 /// object represents how the file system looks at a given time.
-#[derive(ContentHash, PartialEq, Eq, Clone, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub struct View {
     /// All head commits. There should be at least one head commit.
     pub head_ids: HashSet<CommitId>,
@@ -262,6 +264,13 @@ pub struct View {
     // (.jj/working_copy/) has the source of truth about which commit *is* checked out (to be
     // precise: the commit to which we most recently completed an update to).
     pub wc_commit_ids: BTreeMap<WorkspaceNameBuf, CommitId>,
+    /// Commits which were rewritten without rebasing their descendants, mapped
+    /// to their new versions. The descendants will be rebased onto the new
+    /// versions later.
+    pub deferred_rewrites: BTreeMap<CommitId, CommitId>,
+    /// Workspaces whose working-copy commits are throwaway. They are hidden
+    /// from the default log and abandoned when the workspace is forgotten.
+    pub ephemeral_workspaces: BTreeSet<WorkspaceNameBuf>,
 }
 
 impl View {
@@ -275,6 +284,39 @@ impl View {
             git_refs: BTreeMap::new(),
             git_head: RefTarget::absent(),
             wc_commit_ids: BTreeMap::new(),
+            deferred_rewrites: BTreeMap::new(),
+            ephemeral_workspaces: BTreeSet::new(),
+        }
+    }
+}
+
+impl ContentHash for View {
+    fn hash(&self, state: &mut impl DigestUpdate) {
+        let Self {
+            head_ids,
+            local_bookmarks,
+            local_tags,
+            remote_views,
+            git_refs,
+            git_head,
+            wc_commit_ids,
+            deferred_rewrites,
+            ephemeral_workspaces,
+        } = self;
+        head_ids.hash(state);
+        local_bookmarks.hash(state);
+        local_tags.hash(state);
+        remote_views.hash(state);
+        git_refs.hash(state);
+        git_head.hash(state);
+        wc_commit_ids.hash(state);
+        // Not hashed if empty so the ids of views without deferred rewrites
+        // stay the same as before the field was added.
+        if !deferred_rewrites.is_empty() {
+            deferred_rewrites.hash(state);
+        }
+        if !ephemeral_workspaces.is_empty() {
+            ephemeral_workspaces.hash(state);
         }
     }
 }
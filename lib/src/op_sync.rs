@@ -0,0 +1,621 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Transfers operations between copies of a repo, e.g. on different machines.
+//!
+//! The operations missing from the destination repo are copied along with
+//! their views, and the commits they reference are fetched from the backing
+//! Git repository of the source repo. The transferred operation heads are
+//! then added to the destination's op heads, so that diverged operations are
+//! merged when the repo is loaded next, just like concurrent operations.
+//!
+//! The other repo can either be accessible on the local file system, or be a
+//! Git remote. Git remotes have no operation store, so each operation is
+//! stored as a Git commit there (see [`push_operations()`].)
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
+use thiserror::Error;
+
+use crate::backend::BackendError;
+use crate::backend::CommitId;
+use crate::backend::Signature;
+use crate::dag_walk;
+use crate::git::GitSubprocessOptions;
+use crate::git::UnexpectedGitBackendError;
+use crate::git::get_git_backend;
+use crate::git_backend::GitBackend;
+use crate::git_backend::signature_to_git;
+use crate::git_subprocess::GitSubprocessContext;
+use crate::git_subprocess::GitSubprocessError;
+use crate::object_id::ObjectId as _;
+use crate::op_heads_store::OpHeadsStoreError;
+use crate::op_store;
+use crate::op_store::OpStoreError;
+use crate::op_store::Operation;
+use crate::op_store::OperationId;
+use crate::operation;
+use crate::ref_name::RemoteName;
+use crate::ref_name::RemoteNameBuf;
+use crate::repo::RepoLoader;
+use crate::simple_op_store;
+use crate::view::View;
+
+/// Namespace of the refs pointing to the commits of the operation heads in a
+/// Git repository operations are pushed to.
+pub const OP_HEADS_REF_PREFIX: &str = "refs/jj/op-heads/";
+
+/// Namespace of the local refs the operation heads of Git remotes are fetched
+/// to, e.g. `refs/jj/remote-op-heads/origin/` for the remote `origin`.
+const REMOTE_OP_HEADS_REF_PREFIX: &str = "refs/jj/remote-op-heads/";
+
+/// Names of the blobs storing the operation and its view in the tree of an
+/// operation commit.
+const OPERATION_FILE_NAME: &str = "operation";
+const VIEW_FILE_NAME: &str = "view";
+
+/// Error transferring operations between repos.
+#[derive(Debug, Error)]
+pub enum OpSyncError {
+    /// One of the repos isn't backed by Git.
+    #[error(transparent)]
+    UnexpectedBackend(#[from] UnexpectedGitBackendError),
+    /// An operation or view couldn't be read or written.
+    #[error(transparent)]
+    OpStore(#[from] OpStoreError),
+    /// The operation heads couldn't be read or updated.
+    #[error(transparent)]
+    OpHeadsStore(#[from] OpHeadsStoreError),
+    /// An operation was stored under a different id in the destination repo,
+    /// which means the repos use incompatible operation stores.
+    #[error("Operation {0} changed its id when copied")]
+    IdMismatch(String),
+    /// The commits couldn't be imported into the destination repo.
+    #[error("Failed to import commits")]
+    Backend(#[from] BackendError),
+    /// Error running git to transfer the commits.
+    #[error(transparent)]
+    Subprocess(#[from] GitSubprocessError),
+    /// The Git remote to transfer the operations from or to doesn't exist.
+    #[error("No git remote named '{}'", .0.as_symbol())]
+    NoSuchRemote(RemoteNameBuf),
+    /// A commit fetched from the Git remote doesn't store an operation.
+    #[error("Invalid operation commit {commit_id}")]
+    InvalidOperationCommit {
+        /// Hex id of the Git commit.
+        commit_id: String,
+        /// The underlying error.
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// The operation commits or refs couldn't be read or written.
+    #[error("Failed to access operations in the Git repository")]
+    Git(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// Result of [`transfer_operations()`], [`pull_operations()`], and
+/// [`push_operations()`].
+#[derive(Clone, Debug, Default)]
+pub struct OpSyncStats {
+    /// Operations copied to the destination repo.
+    pub transferred_operations: Vec<OperationId>,
+    /// Operation heads added to the destination repo.
+    pub new_heads: Vec<OperationId>,
+    /// Whether the destination repo has other operation heads than the
+    /// transferred ones. The operations will be merged when the repo is loaded
+    /// next.
+    pub diverged: bool,
+}
+
+/// Copies the operations of the `src` repo which are missing from the `dst`
+/// repo, along with the commits they reference.
+///
+/// Both repos must be backed by Git. The commits missing from the destination
+/// are fetched with `git` from the source repo's backing Git repository.
+pub fn transfer_operations(
+    src: &RepoLoader,
+    dst: &RepoLoader,
+    subprocess_options: GitSubprocessOptions,
+) -> Result<OpSyncStats, OpSyncError> {
+    let src_git_backend = get_git_backend(src.store())?;
+    let dst_git_backend = get_git_backend(dst.store())?;
+
+    let src_head_ids = src.op_heads_store().get_op_heads().block_on()?;
+    let missing_ops = find_missing_operations(src, dst, &src_head_ids)?;
+    if missing_ops.is_empty() {
+        return Ok(OpSyncStats::default());
+    }
+
+    // Fetch all commits first so that the destination repo stays loadable
+    // even if the transfer is interrupted.
+    let mut views = Vec::with_capacity(missing_ops.len());
+    let mut commit_ids: HashSet<CommitId> = HashSet::new();
+    for (_, op) in &missing_ops {
+        let view = src.op_store().read_view(&op.view_id).block_on()?;
+        add_referenced_commit_ids(op, &view, &mut commit_ids);
+        views.push(view);
+    }
+    let dst_git_repo = dst_git_backend.git_repo();
+    let missing_commit_ids = commit_ids
+        .iter()
+        .filter(|id| *id != dst.store().root_commit_id())
+        .filter(|id| !dst_git_repo.has_object(gix::ObjectId::from_bytes_or_panic(id.as_bytes())))
+        .sorted()
+        .cloned()
+        .collect_vec();
+    if !missing_commit_ids.is_empty() {
+        let git_ctx = GitSubprocessContext::from_git_backend(dst_git_backend, subprocess_options);
+        git_ctx.spawn_fetch_commits(src_git_backend.git_repo_path(), &missing_commit_ids)?;
+    }
+    let ops = missing_ops
+        .iter()
+        .zip(&views)
+        .map(|((op_id, op), view)| (op_id, op, view));
+    let (new_heads, diverged) =
+        add_operations(dst, dst_git_backend, ops, &commit_ids, src_head_ids)?;
+    Ok(OpSyncStats {
+        transferred_operations: missing_ops.into_iter().map(|(id, _)| id).collect(),
+        new_heads,
+        diverged,
+    })
+}
+
+/// Fetches the operations of the Git remote `remote_name` which are missing
+/// from `repo`, along with the commits they reference.
+///
+/// The operation heads of the remote are fetched to local refs, so the
+/// operations transferred by previous pulls aren't fetched again.
+pub fn pull_operations(
+    repo: &RepoLoader,
+    subprocess_options: GitSubprocessOptions,
+    remote_name: &RemoteName,
+) -> Result<OpSyncStats, OpSyncError> {
+    let git_backend = get_git_backend(repo.store())?;
+    let git_repo = git_backend.git_repo();
+    let remote_head_commit_ids =
+        fetch_remote_op_heads(git_backend, subprocess_options, remote_name)?;
+    let root_op_id = repo.op_store().root_operation_id();
+
+    let mut src_head_ids = vec![];
+    let mut missing_ops = vec![];
+    let mut visited: HashSet<gix::ObjectId> = HashSet::new();
+    let mut work_ids = remote_head_commit_ids.clone();
+    while let Some(commit_id) = work_ids.pop() {
+        if !visited.insert(commit_id) {
+            continue;
+        }
+        let op_commit = read_op_commit(&git_repo, commit_id, root_op_id)?;
+        if remote_head_commit_ids.contains(&commit_id) {
+            src_head_ids.push(op_commit.op_id.clone());
+        }
+        match repo.op_store().read_operation(&op_commit.op_id).block_on() {
+            Ok(_) => continue,
+            Err(OpStoreError::ObjectNotFound { .. }) => {}
+            Err(err) => return Err(err.into()),
+        }
+        work_ids.extend(op_commit.parent_commit_ids.iter().copied());
+        missing_ops.push(op_commit);
+    }
+    if missing_ops.is_empty() {
+        return Ok(OpSyncStats::default());
+    }
+
+    // The commits were fetched along with the operation commits referencing
+    // them.
+    let mut commit_ids: HashSet<CommitId> = HashSet::new();
+    for op_commit in &missing_ops {
+        add_referenced_commit_ids(&op_commit.operation, &op_commit.view, &mut commit_ids);
+    }
+    let ops = missing_ops
+        .iter()
+        .map(|op_commit| (&op_commit.op_id, &op_commit.operation, &op_commit.view));
+    let (new_heads, diverged) = add_operations(repo, git_backend, ops, &commit_ids, src_head_ids)?;
+    Ok(OpSyncStats {
+        transferred_operations: missing_ops.into_iter().map(|op| op.op_id).collect(),
+        new_heads,
+        diverged,
+    })
+}
+
+/// Pushes the operations of `repo` which are missing from the Git remote
+/// `remote_name`, along with the commits they reference.
+///
+/// Each operation is stored as a Git commit. Its tree contains the operation
+/// and its view, encoded like the operation store does, and its message is the
+/// operation id. The parents of the commit are the commits of the parent
+/// operations, followed by the commits first referenced by this operation, so
+/// that all commits referenced by the operation log are pushed along. The
+/// operation heads are stored as `refs/jj/op-heads/<operation id>`.
+///
+/// Operation commits are built deterministically, so the same operation is
+/// stored as the same commit when pushed from different repos. Heads of the
+/// remote which are ancestors of the pushed heads are deleted. Other heads are
+/// kept, and are merged with the pushed ones when they're pulled.
+pub fn push_operations(
+    repo: &RepoLoader,
+    subprocess_options: GitSubprocessOptions,
+    remote_name: &RemoteName,
+) -> Result<OpSyncStats, OpSyncError> {
+    let git_backend = get_git_backend(repo.store())?;
+    let git_repo = git_backend.git_repo();
+    let remote_head_commit_ids =
+        fetch_remote_op_heads(git_backend, subprocess_options.clone(), remote_name)?;
+    let root_op_id = repo.op_store().root_operation_id();
+
+    // Map the operations of the remote to their commits.
+    let mut remote_head_ids = vec![];
+    let mut op_commit_ids: HashMap<OperationId, gix::ObjectId> = HashMap::new();
+    let mut visited: HashSet<gix::ObjectId> = HashSet::new();
+    let mut work_ids = remote_head_commit_ids.clone();
+    while let Some(commit_id) = work_ids.pop() {
+        if !visited.insert(commit_id) {
+            continue;
+        }
+        let op_commit = read_op_commit(&git_repo, commit_id, root_op_id)?;
+        if remote_head_commit_ids.contains(&commit_id) {
+            remote_head_ids.push(op_commit.op_id.clone());
+        }
+        work_ids.extend(op_commit.parent_commit_ids.iter().copied());
+        op_commit_ids.insert(op_commit.op_id, commit_id);
+    }
+
+    // Walk the local operations back to the ones the remote has.
+    let local_head_ids = repo.op_heads_store().get_op_heads().block_on()?;
+    let mut missing_ops: HashMap<OperationId, Operation> = HashMap::new();
+    let mut work_ids = local_head_ids.clone();
+    while let Some(id) = work_ids.pop() {
+        if id == *root_op_id || op_commit_ids.contains_key(&id) || missing_ops.contains_key(&id) {
+            continue;
+        }
+        let op = repo.op_store().read_operation(&id).block_on()?;
+        work_ids.extend(op.parents.iter().cloned());
+        missing_ops.insert(id, op);
+    }
+    let sorted_ids = dag_walk::topo_order_forward(
+        missing_ops.keys().sorted().cloned(),
+        |id| id.clone(),
+        |id| {
+            let parent_ids = &missing_ops[id].parents;
+            parent_ids
+                .iter()
+                .filter(|id| missing_ops.contains_key(*id))
+                .cloned()
+                .collect_vec()
+        },
+        |id| panic!("graph has cycle at operation {id}"),
+    )
+    .unwrap();
+
+    // Create the operation commits, parents first.
+    let mut referenced_commit_ids: HashMap<OperationId, HashSet<CommitId>> = HashMap::new();
+    for op_id in &sorted_ids {
+        let op = &missing_ops[op_id];
+        let view = repo.op_store().read_view(&op.view_id).block_on()?;
+        let mut parent_commit_ids = vec![];
+        let mut inherited_commit_ids: HashSet<CommitId> = HashSet::new();
+        for parent_id in op.parents.iter().filter(|id| *id != root_op_id) {
+            parent_commit_ids.push(op_commit_ids[parent_id]);
+            // Parents which were pushed before aren't cached yet.
+            if !referenced_commit_ids.contains_key(parent_id) {
+                let parent_op = repo.op_store().read_operation(parent_id).block_on()?;
+                let parent_view = repo.op_store().read_view(&parent_op.view_id).block_on()?;
+                let mut commit_ids = HashSet::new();
+                add_referenced_commit_ids(&parent_op, &parent_view, &mut commit_ids);
+                referenced_commit_ids.insert(parent_id.clone(), commit_ids);
+            }
+            inherited_commit_ids.extend(referenced_commit_ids[parent_id].iter().cloned());
+        }
+        let mut commit_ids = HashSet::new();
+        add_referenced_commit_ids(op, &view, &mut commit_ids);
+        let new_commit_ids = commit_ids
+            .iter()
+            .filter(|id| *id != repo.store().root_commit_id() && !inherited_commit_ids.contains(id))
+            .sorted();
+        parent_commit_ids
+            .extend(new_commit_ids.map(|id| gix::ObjectId::from_bytes_or_panic(id.as_bytes())));
+        let commit_id = write_op_commit(&git_repo, op_id, op, &view, parent_commit_ids)?;
+        op_commit_ids.insert(op_id.clone(), commit_id);
+        referenced_commit_ids.insert(op_id.clone(), commit_ids);
+    }
+
+    // Heads of the remote which we have are ancestors of our heads, and are
+    // replaced by them.
+    let mut updates = vec![];
+    for head_id in &local_head_ids {
+        if head_id != root_op_id && !remote_head_ids.contains(head_id) {
+            let ref_name = format!("{OP_HEADS_REF_PREFIX}{}", head_id.hex());
+            updates.push((ref_name, Some(op_commit_ids[head_id])));
+        }
+    }
+    let mut num_remaining_heads = local_head_ids.len();
+    for head_id in &remote_head_ids {
+        if local_head_ids.contains(head_id) {
+            continue;
+        }
+        match repo.op_store().read_operation(head_id).block_on() {
+            Ok(_) => {
+                let ref_name = format!("{OP_HEADS_REF_PREFIX}{}", head_id.hex());
+                updates.push((ref_name, None));
+            }
+            Err(OpStoreError::ObjectNotFound { .. }) => num_remaining_heads += 1,
+            Err(err) => return Err(err.into()),
+        }
+    }
+    if updates.is_empty() {
+        return Ok(OpSyncStats::default());
+    }
+    let git_ctx = GitSubprocessContext::from_git_backend(git_backend, subprocess_options);
+    git_ctx.spawn_push_refs(remote_name, &updates)?;
+
+    Ok(OpSyncStats {
+        transferred_operations: sorted_ids,
+        new_heads: local_head_ids
+            .into_iter()
+            .filter(|id| !remote_head_ids.contains(id))
+            .collect(),
+        diverged: num_remaining_heads > 1,
+    })
+}
+
+/// Writes the `ops` and their views to `dst`, and adds `src_head_ids` to its
+/// operation heads. Returns the added heads, and whether `dst` has other heads.
+///
+/// The `commit_ids` referenced by the operations must exist in the Git
+/// repository of `dst`.
+fn add_operations<'a>(
+    dst: &RepoLoader,
+    dst_git_backend: &GitBackend,
+    ops: impl IntoIterator<Item = (&'a OperationId, &'a Operation, &'a op_store::View)>,
+    commit_ids: &HashSet<CommitId>,
+    src_head_ids: Vec<OperationId>,
+) -> Result<(Vec<OperationId>, bool), OpSyncError> {
+    dst_git_backend.import_head_commits(commit_ids)?;
+
+    // The operations aren't reachable until the op heads are updated, so
+    // nothing is visible if the transfer is interrupted here.
+    for (op_id, op, view) in ops {
+        let view_id = dst.op_store().write_view(view).block_on()?;
+        if view_id != op.view_id {
+            return Err(OpSyncError::IdMismatch(op_id.hex()));
+        }
+        let new_op_id = dst.op_store().write_operation(op).block_on()?;
+        if new_op_id != *op_id {
+            return Err(OpSyncError::IdMismatch(op_id.hex()));
+        }
+    }
+
+    // Heads of the destination repo which are ancestors of the transferred
+    // operations are replaced by them. The other heads are kept, and will be
+    // merged with the new heads when the repo is loaded.
+    let _lock = dst.op_heads_store().lock().block_on()?;
+    let dst_head_ids = dst.op_heads_store().get_op_heads().block_on()?;
+    let head_ids = op_heads(dst, dst_head_ids.iter().chain(&src_head_ids))?;
+    let replaced_head_ids = dst_head_ids
+        .iter()
+        .filter(|id| !head_ids.contains(*id))
+        .cloned()
+        .collect_vec();
+    let new_heads = src_head_ids
+        .into_iter()
+        .filter(|id| head_ids.contains(id) && !dst_head_ids.contains(id))
+        .unique()
+        .collect_vec();
+    for head_id in &new_heads {
+        dst.op_heads_store()
+            .update_op_heads(&replaced_head_ids, head_id)
+            .block_on()?;
+    }
+    Ok((new_heads, head_ids.len() > 1))
+}
+
+/// Fetches the operation heads of the Git remote, and returns their commits.
+fn fetch_remote_op_heads(
+    git_backend: &GitBackend,
+    subprocess_options: GitSubprocessOptions,
+    remote_name: &RemoteName,
+) -> Result<Vec<gix::ObjectId>, OpSyncError> {
+    let git_repo = git_backend.git_repo();
+    if git_repo.try_find_remote(remote_name.as_str()).is_none() {
+        return Err(OpSyncError::NoSuchRemote(remote_name.to_owned()));
+    }
+    let local_prefix = format!("{REMOTE_OP_HEADS_REF_PREFIX}{}/", remote_name.as_str());
+    let git_ctx = GitSubprocessContext::from_git_backend(git_backend, subprocess_options);
+    git_ctx.spawn_fetch_ref_namespace(remote_name, OP_HEADS_REF_PREFIX, &local_prefix)?;
+    let git_err = |err: Box<dyn std::error::Error + Send + Sync>| OpSyncError::Git(err);
+    let mut commit_ids = vec![];
+    let refs = git_repo.references().map_err(|err| git_err(err.into()))?;
+    for git_ref in refs
+        .prefixed(local_prefix.as_str())
+        .map_err(|err| git_err(err.into()))?
+    {
+        let mut git_ref = git_ref.map_err(git_err)?;
+        let commit = git_ref
+            .peel_to_commit()
+            .map_err(|err| git_err(err.into()))?;
+        commit_ids.push(commit.id);
+    }
+    Ok(commit_ids)
+}
+
+/// Operation stored as a Git commit by [`push_operations()`].
+struct OpCommit {
+    op_id: OperationId,
+    operation: Operation,
+    view: op_store::View,
+    /// Commits of the parent operations, except for the root operation.
+    parent_commit_ids: Vec<gix::ObjectId>,
+}
+
+fn read_op_commit(
+    git_repo: &gix::Repository,
+    commit_id: gix::ObjectId,
+    root_op_id: &OperationId,
+) -> Result<OpCommit, OpSyncError> {
+    let invalid =
+        |source: Box<dyn std::error::Error + Send + Sync>| OpSyncError::InvalidOperationCommit {
+            commit_id: commit_id.to_string(),
+            source,
+        };
+    let commit = git_repo
+        .find_commit(commit_id)
+        .map_err(|err| invalid(err.into()))?;
+    let message = commit.message_raw().map_err(|err| invalid(err.into()))?;
+    let op_id = OperationId::try_from_hex(message.trim_ascii())
+        .ok_or_else(|| invalid("The message isn't an operation id".into()))?;
+    let tree = commit.tree().map_err(|err| invalid(err.into()))?;
+    let read_file = |name: &str| {
+        let entry = tree
+            .find_entry(name)
+            .ok_or_else(|| invalid(format!("Missing {name} file").into()))?;
+        let mut blob = entry
+            .object()
+            .map_err(|err| invalid(err.into()))?
+            .try_into_blob()
+            .map_err(|err| invalid(err.into()))?;
+        Ok::<_, OpSyncError>(blob.take_data())
+    };
+    let operation =
+        simple_op_store::decode_operation(&read_file(OPERATION_FILE_NAME)?).map_err(invalid)?;
+    let view = simple_op_store::decode_view(&read_file(VIEW_FILE_NAME)?).map_err(invalid)?;
+    let num_op_parents = operation
+        .parents
+        .iter()
+        .filter(|id| *id != root_op_id)
+        .count();
+    let parent_commit_ids = commit
+        .parent_ids()
+        .take(num_op_parents)
+        .map(|id| id.detach())
+        .collect_vec();
+    if parent_commit_ids.len() != num_op_parents {
+        return Err(invalid("Missing parent operation commits".into()));
+    }
+    Ok(OpCommit {
+        op_id,
+        operation,
+        view,
+        parent_commit_ids,
+    })
+}
+
+fn write_op_commit(
+    git_repo: &gix::Repository,
+    op_id: &OperationId,
+    op: &Operation,
+    view: &op_store::View,
+    parent_commit_ids: Vec<gix::ObjectId>,
+) -> Result<gix::ObjectId, OpSyncError> {
+    let git_err = |err: Box<dyn std::error::Error + Send + Sync>| OpSyncError::Git(err);
+    let mut entries = vec![];
+    for (name, data) in [
+        (OPERATION_FILE_NAME, simple_op_store::encode_operation(op)),
+        (VIEW_FILE_NAME, simple_op_store::encode_view(view)),
+    ] {
+        let blob_id = git_repo
+            .write_blob(data)
+            .map_err(|err| git_err(err.into()))?;
+        entries.push(gix::objs::tree::Entry {
+            mode: gix::object::tree::EntryKind::Blob.into(),
+            filename: name.into(),
+            oid: blob_id.detach(),
+        });
+    }
+    // Tree entries to be written must be sorted by Entry::filename().
+    entries.sort_unstable();
+    let tree_id = git_repo
+        .write_object(gix::objs::Tree { entries })
+        .map_err(|err| git_err(err.into()))?;
+    // The signature is derived from the operation, so that the commit doesn't
+    // depend on who pushes it.
+    let signature = signature_to_git(&Signature {
+        name: op.metadata.username.clone(),
+        email: op.metadata.hostname.clone(),
+        timestamp: op.metadata.time.end,
+    });
+    let commit = gix::objs::Commit {
+        message: format!("{}\n", op_id.hex()).into(),
+        tree: tree_id.detach(),
+        author: signature.clone(),
+        committer: signature,
+        encoding: None,
+        parents: parent_commit_ids.into(),
+        extra_headers: vec![],
+    };
+    let commit_id = git_repo
+        .write_object(&commit)
+        .map_err(|err| git_err(err.into()))?;
+    Ok(commit_id.detach())
+}
+
+/// Returns the operations among `ids` which aren't ancestors of the others.
+fn op_heads<'a>(
+    repo: &RepoLoader,
+    ids: impl IntoIterator<Item = &'a OperationId>,
+) -> Result<HashSet<OperationId>, OpStoreError> {
+    let ops: Vec<_> = ids
+        .into_iter()
+        .unique()
+        .map(|id| repo.load_operation(id))
+        .try_collect()?;
+    let heads = dag_walk::heads_ok(
+        ops.into_iter().map(Ok),
+        |op: &operation::Operation| op.id().clone(),
+        |op: &operation::Operation| op.parents().collect_vec(),
+    )?;
+    Ok(heads.iter().map(|op| op.id().clone()).collect())
+}
+
+/// Walks the operations of `src` from `head_ids`, stopping at operations
+/// which exist in `dst`.
+fn find_missing_operations(
+    src: &RepoLoader,
+    dst: &RepoLoader,
+    head_ids: &[OperationId],
+) -> Result<Vec<(OperationId, Operation)>, OpSyncError> {
+    let mut missing_ops = vec![];
+    let mut visited: HashSet<OperationId> = HashSet::new();
+    let mut work_ids = head_ids.to_vec();
+    while let Some(id) = work_ids.pop() {
+        if !visited.insert(id.clone()) {
+            continue;
+        }
+        match dst.op_store().read_operation(&id).block_on() {
+            Ok(_) => continue,
+            Err(OpStoreError::ObjectNotFound { .. }) => {}
+            Err(err) => return Err(err.into()),
+        }
+        let op = src.op_store().read_operation(&id).block_on()?;
+        work_ids.extend(op.parents.iter().cloned());
+        missing_ops.push((id, op));
+    }
+    Ok(missing_ops)
+}
+
+fn add_referenced_commit_ids(
+    op: &Operation,
+    view: &op_store::View,
+    commit_ids: &mut HashSet<CommitId>,
+) {
+    if let Some(map) = &op.commit_predecessors {
+        for (new_id, old_ids) in map {
+            commit_ids.insert(new_id.clone());
+            commit_ids.extend(old_ids.iter().cloned());
+        }
+    }
+    let view = View::new(view.clone());
+    commit_ids.extend(view.all_referenced_commit_ids().cloned());
+}
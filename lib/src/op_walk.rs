@@ -15,27 +15,41 @@
 //! Utility for operation id resolution and traversal.
 
 use std::cmp::Ordering;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
 use std::slice;
 use std::sync::Arc;
 
 use itertools::Itertools as _;
 use pollster::FutureExt as _;
+use tempfile::NamedTempFile;
 use thiserror::Error;
 
 use crate::dag_walk;
+use crate::file_util::persist_content_addressed_temp_file;
 use crate::object_id::HexPrefix;
+use crate::object_id::ObjectId as _;
 use crate::object_id::PrefixResolution;
 use crate::op_heads_store;
 use crate::op_heads_store::OpHeadResolutionError;
 use crate::op_heads_store::OpHeadsStore;
 use crate::op_heads_store::OpHeadsStoreError;
+use crate::op_store;
 use crate::op_store::OpStore;
 use crate::op_store::OpStoreError;
 use crate::op_store::OpStoreResult;
 use crate::op_store::OperationId;
+use crate::op_store::RefTarget;
+use crate::op_store::RefTargetOptionExt as _;
 use crate::operation::Operation;
+use crate::ref_name::RefName;
+use crate::ref_name::RefNameBuf;
 use crate::repo::ReadonlyRepo;
 use crate::repo::Repo as _;
 use crate::repo::RepoLoader;
@@ -273,6 +287,190 @@ pub fn walk_ancestors(
     .map_ok(|OperationByEndTime(op)| op)
 }
 
+/// Change of a local bookmark made by an operation.
+#[derive(Clone, Debug)]
+pub struct BookmarkChange {
+    /// Operation that changed the bookmark.
+    pub operation: Operation,
+    /// Target of the bookmark at the (first) parent operation.
+    pub old_target: RefTarget,
+    /// Target of the bookmark after the operation.
+    pub new_target: RefTarget,
+}
+
+/// Index of the local bookmarks changed by each operation.
+///
+/// An operation is considered to have changed a bookmark if the new target
+/// differs from the targets at all of its parent operations. Since operations
+/// are immutable, the changed bookmark names are computed once per operation
+/// and stored in a directory, one file per operation. Walking the history of
+/// a bookmark then only needs to read the views of the operations that
+/// changed it.
+#[derive(Debug)]
+pub struct BookmarkChangeIndex {
+    dir: PathBuf,
+}
+
+impl BookmarkChangeIndex {
+    /// Creates an index backed by `dir`. The directory is created when the
+    /// first entry is written.
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Returns the names of the local bookmarks changed by `op`. The operation
+    /// is indexed if it hasn't been yet.
+    pub fn changed_bookmarks(&self, op: &Operation) -> OpStoreResult<Vec<RefNameBuf>> {
+        let path = self.dir.join(op.id().hex());
+        match fs::read(&path) {
+            Ok(data) => {
+                if let Some(names) = decode_bookmark_names(&data) {
+                    return Ok(names);
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(OpStoreError::Other(err.into())),
+        }
+        let names = compute_changed_bookmarks(op)?;
+        self.write_entry(&path, &names)
+            .map_err(|err| OpStoreError::Other(err.into()))?;
+        Ok(names)
+    }
+
+    /// Removes the entries of operations that aren't ancestors of `head_ops`.
+    pub fn gc(&self, head_ops: &[Operation]) -> OpStoreResult<()> {
+        let reachable: HashSet<String> = walk_ancestors(head_ops)
+            .map_ok(|op| op.id().hex())
+            .try_collect()?;
+        let to_other_err = |err: io::Error| OpStoreError::Other(err.into());
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(to_other_err(err)),
+        };
+        for entry in entries {
+            let entry = entry.map_err(to_other_err)?;
+            let file_name = entry.file_name();
+            if file_name
+                .to_str()
+                .is_some_and(|name| reachable.contains(name))
+            {
+                continue;
+            }
+            match fs::remove_file(entry.path()) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(to_other_err(err)),
+            }
+        }
+        Ok(())
+    }
+
+    fn write_entry(&self, path: &Path, names: &[RefNameBuf]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let mut temp_file = NamedTempFile::new_in(&self.dir)?;
+        temp_file.write_all(&encode_bookmark_names(names))?;
+        persist_content_addressed_temp_file(temp_file, path)?;
+        Ok(())
+    }
+}
+
+fn compute_changed_bookmarks(op: &Operation) -> OpStoreResult<Vec<RefNameBuf>> {
+    let view = op.op_store().read_view(op.view_id()).block_on()?;
+    let parent_views: Vec<_> = op
+        .parents()
+        .map_ok(|parent| parent.view_id().clone())
+        .map(|view_id| op.op_store().read_view(&view_id?).block_on())
+        .try_collect()?;
+    let all_names: BTreeSet<&RefName> = view
+        .local_bookmarks
+        .keys()
+        .chain(
+            parent_views
+                .iter()
+                .flat_map(|view| view.local_bookmarks.keys()),
+        )
+        .map(AsRef::as_ref)
+        .collect();
+    fn get_target<'a>(view: &'a op_store::View, name: &RefName) -> &'a RefTarget {
+        view.local_bookmarks.get(name).flatten()
+    }
+    let names = all_names
+        .into_iter()
+        .filter(|&name| {
+            let new_target = get_target(&view, name);
+            if parent_views.is_empty() {
+                new_target.is_present()
+            } else {
+                parent_views
+                    .iter()
+                    .all(|parent_view| get_target(parent_view, name) != new_target)
+            }
+        })
+        .map(RefNameBuf::from)
+        .collect();
+    Ok(names)
+}
+
+fn encode_bookmark_names(names: &[RefNameBuf]) -> Vec<u8> {
+    let mut buf = vec![];
+    for name in names {
+        let name = name.as_str().as_bytes();
+        buf.extend(u32::try_from(name.len()).unwrap().to_le_bytes());
+        buf.extend(name);
+    }
+    buf
+}
+
+/// Decodes an index entry. Returns `None` if the entry is corrupted.
+fn decode_bookmark_names(mut data: &[u8]) -> Option<Vec<RefNameBuf>> {
+    let mut names = vec![];
+    while !data.is_empty() {
+        let (len, rest) = data.split_first_chunk::<4>()?;
+        let len = usize::try_from(u32::from_le_bytes(*len)).ok()?;
+        let (name, rest) = rest.split_at_checked(len)?;
+        names.push(str::from_utf8(name).ok()?.into());
+        data = rest;
+    }
+    Some(names)
+}
+
+/// Walks `head_ops` and their ancestors in reverse topological order, yielding
+/// operations that changed the local bookmark `name`.
+///
+/// Operations are looked up in `index`, so only the views of the operations
+/// that changed the bookmark (and of their first parents) are read once the
+/// operations are indexed.
+pub fn walk_bookmark_changes(
+    index: &BookmarkChangeIndex,
+    head_ops: &[Operation],
+    name: &RefName,
+) -> impl Iterator<Item = OpStoreResult<BookmarkChange>> {
+    let get_target = move |op: &Operation| -> OpStoreResult<RefTarget> {
+        let view = op.op_store().read_view(op.view_id()).block_on()?;
+        Ok(view.local_bookmarks.get(name).flatten().clone())
+    };
+    walk_ancestors(head_ops).filter_map(move |op| {
+        let visit = || -> OpStoreResult<Option<BookmarkChange>> {
+            let op = op?;
+            if !index.changed_bookmarks(&op)?.iter().any(|n| n == name) {
+                return Ok(None);
+            }
+            let new_target = get_target(&op)?;
+            let old_target = match op.parents().next().transpose()? {
+                Some(parent) => get_target(&parent)?,
+                None => RefTarget::absent(),
+            };
+            Ok(Some(BookmarkChange {
+                operation: op,
+                old_target,
+                new_target,
+            }))
+        };
+        visit().transpose()
+    })
+}
+
 /// Walks ancestors from `head_ops` in reverse topological order, excluding
 /// ancestors of `root_ops`.
 pub fn walk_ancestors_range(
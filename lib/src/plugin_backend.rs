@@ -0,0 +1,582 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Backend which stores objects through an external plugin process.
+//!
+//! Objects are serialized and hashed the same way as by the simple backend.
+//! The plugin only stores them as opaque blobs keyed by kind and ID, so a
+//! plugin for an object service just has to implement "get" and "put". Which
+//! plugin a repo uses is recorded in the store, and the command to run it is
+//! looked up in the `backend-plugins.<name>.command` config, so no custom `jj`
+//! binary is needed. The plugin runs in its own process, and only exchanges
+//! data with `jj` through its stdin and stdout.
+//!
+//! # Protocol
+//!
+//! The command is started in the store directory when the repo is loaded, and
+//! exits when its stdin is closed. `jj` sends requests on the plugin's stdin,
+//! one at a time, and the plugin answers each request on its stdout. Requests
+//! and responses are lines, and some are followed by raw data whose length is
+//! given on the line:
+//!
+//! * `jj-backend-plugin 1`: the handshake, sent once. The number is the
+//!   protocol version.
+//! * `get <kind> <id>`: requests an object.
+//! * `put <kind> <id> <length>`, followed by the object data: stores an object.
+//!   Objects are immutable, so storing an object which already exists must
+//!   succeed.
+//!
+//! `<kind>` is one of `commit`, `tree`, `file`, or `symlink`, and `<id>` is
+//! the hex ID of the object. The responses are:
+//!
+//! * `ok <length>`, followed by the object data for `get`, or with length 0 for
+//!   the other requests.
+//! * `missing` if the requested object doesn't exist.
+//! * `error <message>` if the request failed.
+//!
+//! The data returned by `get` is checked against the object ID, so a plugin
+//! can't substitute other contents. Lengths above 4 GiB are rejected. If the
+//! plugin sends an invalid response or the pipe breaks, the process is killed,
+//! and a new one is started for the next request.
+
+#![expect(missing_docs)]
+
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+use std::io::BufRead as _;
+use std::io::BufReader;
+use std::io::Cursor;
+use std::io::Read as _;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::process::Child;
+use std::process::ChildStdin;
+use std::process::ChildStdout;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use blake2::Blake2b512;
+use blake2::Digest as _;
+use futures::stream;
+use futures::stream::BoxStream;
+use pollster::FutureExt as _;
+use prost::Message as _;
+use thiserror::Error;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt as _;
+
+use crate::backend::Backend;
+use crate::backend::BackendError;
+use crate::backend::BackendInitError;
+use crate::backend::BackendLoadError;
+use crate::backend::BackendResult;
+use crate::backend::ChangeId;
+use crate::backend::Commit;
+use crate::backend::CommitId;
+use crate::backend::CopyHistory;
+use crate::backend::CopyId;
+use crate::backend::CopyRecord;
+use crate::backend::FileId;
+use crate::backend::SecureSig;
+use crate::backend::SigningFn;
+use crate::backend::SymlinkId;
+use crate::backend::Tree;
+use crate::backend::TreeId;
+use crate::backend::make_root_commit;
+use crate::config::ConfigGetError;
+use crate::config::ConfigGetResultExt as _;
+use crate::config::ConfigNamePathBuf;
+use crate::content_hash::blake2b_hash;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::index::Index;
+use crate::object_id::ObjectId;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::settings::UserSettings;
+use crate::simple_backend;
+
+const PROTOCOL_VERSION: u32 = 1;
+/// Maximum length of the data in a response, so that a broken plugin can't
+/// make us allocate arbitrary amounts of memory.
+const MAX_DATA_LENGTH: u64 = 1 << 32;
+/// File in the store directory containing the name of the plugin.
+const PLUGIN_NAME_FILE: &str = "plugin";
+
+/// Error starting a backend plugin.
+#[derive(Debug, Error)]
+pub enum PluginBackendError {
+    #[error("Backend plugin `{0}` is not configured")]
+    NotConfigured(String),
+    #[error(transparent)]
+    Config(#[from] ConfigGetError),
+    #[error("Failed to start backend plugin `{name}`")]
+    Start {
+        name: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Failed to initialize the store")]
+    Backend(#[from] BackendError),
+    #[error(transparent)]
+    Path(#[from] PathError),
+}
+
+impl From<PluginBackendError> for BackendInitError {
+    fn from(err: PluginBackendError) -> Self {
+        Self(err.into())
+    }
+}
+
+impl From<PluginBackendError> for BackendLoadError {
+    fn from(err: PluginBackendError) -> Self {
+        Self(err.into())
+    }
+}
+
+/// Response to a request sent to the plugin.
+enum PluginResponse {
+    Ok(Vec<u8>),
+    Missing,
+    Error(String),
+}
+
+/// Running plugin process.
+struct PluginProcess {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl PluginProcess {
+    fn start(command: &[String], store_path: &Path) -> io::Result<Self> {
+        let (program, args) = command
+            .split_first()
+            .ok_or_else(|| io::Error::other("the command is empty"))?;
+        let mut cmd = Command::new(program);
+        cmd.args(args).current_dir(store_path);
+        tracing::info!(?cmd, "starting backend plugin");
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut process = Self {
+            child,
+            stdin: Some(stdin),
+            stdout,
+        };
+        match process.request(&format!("jj-backend-plugin {PROTOCOL_VERSION}"), None)? {
+            PluginResponse::Ok(_) => Ok(process),
+            PluginResponse::Missing => Err(io::Error::other("unexpected handshake response")),
+            PluginResponse::Error(message) => Err(io::Error::other(message)),
+        }
+    }
+
+    /// Sends a request and reads the response. If this returns an error, the
+    /// requests and responses may be out of sync, so the process must not be
+    /// used anymore.
+    fn request(&mut self, line: &str, data: Option<&[u8]>) -> io::Result<PluginResponse> {
+        let stdin = self.stdin.as_mut().unwrap();
+        writeln!(stdin, "{line}")?;
+        if let Some(data) = data {
+            stdin.write_all(data)?;
+        }
+        stdin.flush()?;
+
+        let mut response = String::new();
+        if self.stdout.read_line(&mut response)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the plugin exited",
+            ));
+        }
+        let response = response.strip_suffix('\n').unwrap_or(&response);
+        if let Some(len) = response.strip_prefix("ok ") {
+            let len: u64 = len
+                .parse()
+                .ok()
+                .filter(|&len| len <= MAX_DATA_LENGTH)
+                .ok_or_else(|| io::Error::other(format!("invalid response: {response}")))?;
+            // Don't allocate the whole length up front in case the plugin
+            // doesn't actually send that much.
+            let mut data = vec![];
+            (&mut self.stdout).take(len).read_to_end(&mut data)?;
+            if data.len() as u64 != len {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "the plugin exited",
+                ));
+            }
+            Ok(PluginResponse::Ok(data))
+        } else if response == "missing" {
+            Ok(PluginResponse::Missing)
+        } else if let Some(message) = response.strip_prefix("error ") {
+            Ok(PluginResponse::Error(message.to_owned()))
+        } else {
+            Err(io::Error::other(format!("invalid response: {response}")))
+        }
+    }
+}
+
+impl PluginProcess {
+    fn kill(mut self) {
+        self.child.kill().ok();
+    }
+}
+
+impl Drop for PluginProcess {
+    fn drop(&mut self) {
+        // Closing stdin tells the process to exit.
+        drop(self.stdin.take());
+        self.child.wait().ok();
+    }
+}
+
+fn plugin_command(settings: &UserSettings, name: &str) -> Result<Vec<String>, PluginBackendError> {
+    let config_name = ConfigNamePathBuf::from_iter(["backend-plugins", name, "command"]);
+    settings
+        .get::<Vec<String>>(&config_name)
+        .optional()?
+        .filter(|command| !command.is_empty())
+        .ok_or_else(|| PluginBackendError::NotConfigured(name.to_owned()))
+}
+
+fn to_other_err(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> BackendError {
+    BackendError::Other(err.into())
+}
+
+/// Backend which stores objects through a plugin process. See the module
+/// documentation for the protocol.
+pub struct PluginBackend {
+    path: PathBuf,
+    plugin_name: String,
+    command: Vec<String>,
+    /// The running plugin process, or `None` if it has to be restarted.
+    process: Mutex<Option<PluginProcess>>,
+    root_commit_id: CommitId,
+    root_change_id: ChangeId,
+    empty_tree_id: TreeId,
+}
+
+impl Debug for PluginBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PluginBackend")
+            .field("path", &self.path)
+            .field("plugin_name", &self.plugin_name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PluginBackend {
+    pub fn name() -> &'static str {
+        "plugin"
+    }
+
+    /// Initializes a backend which stores objects through the plugin
+    /// configured in `backend-plugins.<plugin_name>.command`.
+    pub fn init(
+        settings: &UserSettings,
+        store_path: &Path,
+        plugin_name: &str,
+    ) -> Result<Self, PluginBackendError> {
+        let name_path = store_path.join(PLUGIN_NAME_FILE);
+        fs::write(&name_path, plugin_name).context(&name_path)?;
+        let backend = Self::start(settings, store_path, plugin_name.to_owned())?;
+        let empty_tree_id = backend
+            .write_tree(RepoPath::root(), &Tree::default())
+            .block_on()?;
+        assert_eq!(empty_tree_id, backend.empty_tree_id);
+        Ok(backend)
+    }
+
+    /// Loads the backend and starts the plugin recorded in the store.
+    pub fn load(settings: &UserSettings, store_path: &Path) -> Result<Self, PluginBackendError> {
+        let name_path = store_path.join(PLUGIN_NAME_FILE);
+        let plugin_name = fs::read_to_string(&name_path).context(&name_path)?;
+        Self::start(settings, store_path, plugin_name.trim().to_owned())
+    }
+
+    fn start(
+        settings: &UserSettings,
+        store_path: &Path,
+        plugin_name: String,
+    ) -> Result<Self, PluginBackendError> {
+        let command = plugin_command(settings, &plugin_name)?;
+        let process = PluginProcess::start(&command, store_path).map_err(|source| {
+            PluginBackendError::Start {
+                name: plugin_name.clone(),
+                source,
+            }
+        })?;
+        Ok(Self {
+            path: store_path.to_owned(),
+            plugin_name,
+            command,
+            process: Mutex::new(Some(process)),
+            root_commit_id: CommitId::from_bytes(&[0; simple_backend::COMMIT_ID_LENGTH]),
+            root_change_id: ChangeId::from_bytes(&[0; simple_backend::CHANGE_ID_LENGTH]),
+            empty_tree_id: TreeId::from_hex(simple_backend::EMPTY_TREE_ID_HEX),
+        })
+    }
+
+    /// Name of the plugin the objects are stored through.
+    pub fn plugin_name(&self) -> &str {
+        &self.plugin_name
+    }
+
+    /// Sends a request to the plugin, restarting the process first if a
+    /// previous request left it out of sync.
+    fn request(&self, line: &str, data: Option<&[u8]>) -> io::Result<PluginResponse> {
+        let mut process = self.process.lock().unwrap();
+        if process.is_none() {
+            tracing::info!(plugin = self.plugin_name, "restarting backend plugin");
+            *process = Some(PluginProcess::start(&self.command, &self.path)?);
+        }
+        let result = process.as_mut().unwrap().request(line, data);
+        if result.is_err() {
+            process.take().unwrap().kill();
+        }
+        result
+    }
+
+    fn get_object(&self, id: &impl ObjectId) -> BackendResult<Vec<u8>> {
+        let line = format!("get {} {}", id.object_type(), id.hex());
+        let read_err =
+            |source: Box<dyn std::error::Error + Send + Sync>| BackendError::ReadObject {
+                object_type: id.object_type(),
+                hash: id.hex(),
+                source,
+            };
+        match self.request(&line, None) {
+            Ok(PluginResponse::Ok(data)) => Ok(data),
+            Ok(PluginResponse::Missing) => Err(BackendError::ObjectNotFound {
+                object_type: id.object_type(),
+                hash: id.hex(),
+                source: format!("not found by backend plugin `{}`", self.plugin_name).into(),
+            }),
+            Ok(PluginResponse::Error(message)) => Err(read_err(message.into())),
+            Err(err) => Err(read_err(Box::new(err))),
+        }
+    }
+
+    fn put_object(
+        &self,
+        object_type: &'static str,
+        id: &impl ObjectId,
+        data: &[u8],
+    ) -> BackendResult<()> {
+        let line = format!("put {object_type} {} {}", id.hex(), data.len());
+        match self.request(&line, Some(data)) {
+            Ok(PluginResponse::Ok(_)) => Ok(()),
+            Ok(PluginResponse::Missing) => Err(to_other_err("unexpected response to put")),
+            Ok(PluginResponse::Error(message)) => Err(BackendError::WriteObject {
+                object_type,
+                source: message.into(),
+            }),
+            Err(err) => Err(BackendError::WriteObject {
+                object_type,
+                source: Box::new(err),
+            }),
+        }
+    }
+}
+
+/// Checks that the data returned by the plugin hashes to the requested `id`.
+fn verify_id(id: &impl ObjectId, hash: &[u8]) -> BackendResult<()> {
+    if id.as_bytes() == hash {
+        Ok(())
+    } else {
+        Err(BackendError::ReadObject {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: "the data returned by the plugin doesn't match the object ID".into(),
+        })
+    }
+}
+
+#[async_trait]
+impl Backend for PluginBackend {
+    fn name(&self) -> &str {
+        Self::name()
+    }
+
+    fn commit_id_length(&self) -> usize {
+        simple_backend::COMMIT_ID_LENGTH
+    }
+
+    fn change_id_length(&self) -> usize {
+        simple_backend::CHANGE_ID_LENGTH
+    }
+
+    fn root_commit_id(&self) -> &CommitId {
+        &self.root_commit_id
+    }
+
+    fn root_change_id(&self) -> &ChangeId {
+        &self.root_change_id
+    }
+
+    fn empty_tree_id(&self) -> &TreeId {
+        &self.empty_tree_id
+    }
+
+    fn concurrency(&self) -> usize {
+        1
+    }
+
+    async fn read_file(
+        &self,
+        _path: &RepoPath,
+        id: &FileId,
+    ) -> BackendResult<Pin<Box<dyn AsyncRead + Send>>> {
+        let data = self.get_object(id)?;
+        verify_id(id, &Blake2b512::digest(&data))?;
+        Ok(Box::pin(Cursor::new(data)))
+    }
+
+    async fn write_file(
+        &self,
+        _path: &RepoPath,
+        contents: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> BackendResult<FileId> {
+        // The length has to be sent before the data.
+        let mut data = vec![];
+        contents
+            .read_to_end(&mut data)
+            .await
+            .map_err(to_other_err)?;
+        let id = FileId::new(Blake2b512::digest(&data).to_vec());
+        self.put_object("file", &id, &data)?;
+        Ok(id)
+    }
+
+    async fn read_symlink(&self, _path: &RepoPath, id: &SymlinkId) -> BackendResult<String> {
+        let data = self.get_object(id)?;
+        verify_id(id, &Blake2b512::digest(&data))?;
+        String::from_utf8(data).map_err(|err| BackendError::InvalidUtf8 {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: err.utf8_error(),
+        })
+    }
+
+    async fn write_symlink(&self, _path: &RepoPath, target: &str) -> BackendResult<SymlinkId> {
+        let id = SymlinkId::new(Blake2b512::digest(target.as_bytes()).to_vec());
+        self.put_object("symlink", &id, target.as_bytes())?;
+        Ok(id)
+    }
+
+    async fn read_copy(&self, _id: &CopyId) -> BackendResult<CopyHistory> {
+        Err(BackendError::Unsupported(
+            "The plugin backend doesn't support copies".to_string(),
+        ))
+    }
+
+    async fn write_copy(&self, _contents: &CopyHistory) -> BackendResult<CopyId> {
+        Err(BackendError::Unsupported(
+            "The plugin backend doesn't support copies".to_string(),
+        ))
+    }
+
+    async fn get_related_copies(&self, _copy_id: &CopyId) -> BackendResult<Vec<CopyHistory>> {
+        Err(BackendError::Unsupported(
+            "The plugin backend doesn't support copies".to_string(),
+        ))
+    }
+
+    async fn read_tree(&self, _path: &RepoPath, id: &TreeId) -> BackendResult<Tree> {
+        let buf = self.get_object(id)?;
+        let proto = crate::protos::simple_store::Tree::decode(&*buf).map_err(to_other_err)?;
+        let tree = simple_backend::tree_from_proto(proto);
+        verify_id(id, &blake2b_hash(&tree))?;
+        Ok(tree)
+    }
+
+    async fn write_tree(&self, _path: &RepoPath, tree: &Tree) -> BackendResult<TreeId> {
+        let id = TreeId::new(blake2b_hash(tree).to_vec());
+        let proto = simple_backend::tree_to_proto(tree);
+        self.put_object("tree", &id, &proto.encode_to_vec())?;
+        Ok(id)
+    }
+
+    fn compute_tree_id(&self, _path: &RepoPath, tree: &Tree) -> Option<TreeId> {
+        Some(TreeId::new(blake2b_hash(tree).to_vec()))
+    }
+
+    fn compute_file_id(&self, _path: &RepoPath, contents: &[u8]) -> Option<FileId> {
+        Some(FileId::new(Blake2b512::digest(contents).to_vec()))
+    }
+
+    async fn read_commit(&self, id: &CommitId) -> BackendResult<Commit> {
+        if *id == self.root_commit_id {
+            return Ok(make_root_commit(
+                self.root_change_id().clone(),
+                self.empty_tree_id.clone(),
+            ));
+        }
+        let buf = self.get_object(id)?;
+        let proto = crate::protos::simple_store::Commit::decode(&*buf).map_err(to_other_err)?;
+        let commit = simple_backend::commit_from_proto(proto);
+        verify_id(id, &blake2b_hash(&commit))?;
+        Ok(commit)
+    }
+
+    async fn write_commit(
+        &self,
+        mut commit: Commit,
+        sign_with: Option<&mut SigningFn>,
+    ) -> BackendResult<(CommitId, Commit)> {
+        assert!(commit.secure_sig.is_none(), "commit.secure_sig was set");
+
+        if commit.parents.is_empty() {
+            return Err(BackendError::Other(
+                "Cannot write a commit with no parents".into(),
+            ));
+        }
+        let mut proto = simple_backend::commit_to_proto(&commit);
+        if let Some(sign) = sign_with {
+            let data = proto.encode_to_vec();
+            let sig = sign(&data).map_err(to_other_err)?;
+            proto.secure_sig = Some(sig.clone());
+            commit.secure_sig = Some(SecureSig { data, sig });
+        }
+        let id = CommitId::new(blake2b_hash(&commit).to_vec());
+        self.put_object("commit", &id, &proto.encode_to_vec())?;
+        Ok((id, commit))
+    }
+
+    fn get_copy_records(
+        &self,
+        _paths: Option<&[RepoPathBuf]>,
+        _root: &CommitId,
+        _head: &CommitId,
+    ) -> BackendResult<BoxStream<'_, BackendResult<CopyRecord>>> {
+        Ok(Box::pin(stream::empty()))
+    }
+
+    fn gc(&self, _index: &dyn Index, _keep_newer: SystemTime) -> BackendResult<()> {
+        // The plugin owns the storage, and it can't tell which objects are
+        // reachable.
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Progress reporting for long-running operations.
+
+use serde::Serialize;
+
+/// Long-running operation that reports progress.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProgressTask {
+    /// Fetching from a remote.
+    Fetch,
+    /// Pushing to a remote.
+    Push,
+    /// Snapshotting the working copy.
+    Snapshot,
+    /// Updating the working copy to a new tree.
+    Checkout,
+    /// Removing unreachable objects and operations.
+    Gc,
+    /// Building or updating an index.
+    IndexBuild,
+}
+
+impl ProgressTask {
+    /// Human-readable verb describing the task, such as "Snapshotting".
+    pub fn verb(self) -> &'static str {
+        match self {
+            Self::Fetch => "Fetching",
+            Self::Push => "Pushing",
+            Self::Snapshot => "Snapshotting",
+            Self::Checkout => "Checking out",
+            Self::Gc => "Collecting garbage",
+            Self::IndexBuild => "Indexing",
+        }
+    }
+}
+
+/// Progress update sent by a long-running operation.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum ProgressEvent<'a> {
+    /// The task has started.
+    Started {
+        /// The task that started.
+        task: ProgressTask,
+    },
+    /// The task has made progress.
+    Advanced {
+        /// The task that made progress.
+        task: ProgressTask,
+        /// Number of units of work completed so far.
+        completed: u64,
+        /// Total number of units of work if known. For remote transfers, the
+        /// unit is a percent.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        total: Option<u64>,
+        /// The item (such as a file path) being processed.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        item: Option<&'a str>,
+    },
+    /// The task has finished, successfully or not.
+    Finished {
+        /// The task that finished.
+        task: ProgressTask,
+    },
+}
+
+impl ProgressEvent<'_> {
+    /// The task this event belongs to.
+    pub fn task(&self) -> ProgressTask {
+        match self {
+            Self::Started { task } | Self::Advanced { task, .. } | Self::Finished { task } => *task,
+        }
+    }
+}
+
+/// Receiver of progress updates.
+///
+/// Implementations are called from the thread doing the work, possibly from
+/// several threads at once, so they should return quickly.
+pub trait Progress: Sync {
+    /// Handles a progress update.
+    fn report(&self, event: &ProgressEvent<'_>);
+}
+
+impl<F: Fn(&ProgressEvent<'_>) + Sync> Progress for F {
+    fn report(&self, event: &ProgressEvent<'_>) {
+        self(event);
+    }
+}
+
+/// Reports `Started` when created and `Finished` when dropped.
+#[must_use]
+pub struct ProgressScope<'a> {
+    progress: Option<&'a dyn Progress>,
+    task: ProgressTask,
+}
+
+impl<'a> ProgressScope<'a> {
+    /// Reports that `task` has started.
+    pub fn start(progress: Option<&'a dyn Progress>, task: ProgressTask) -> Self {
+        if let Some(progress) = progress {
+            progress.report(&ProgressEvent::Started { task });
+        }
+        Self { progress, task }
+    }
+
+    /// Reports that `completed` out of `total` units of work are done.
+    pub fn advance(&self, completed: u64, total: Option<u64>, item: Option<&str>) {
+        if let Some(progress) = self.progress {
+            progress.report(&ProgressEvent::Advanced {
+                task: self.task,
+                completed,
+                total,
+                item,
+            });
+        }
+    }
+}
+
+impl Drop for ProgressScope<'_> {
+    fn drop(&mut self) {
+        if let Some(progress) = self.progress {
+            progress.report(&ProgressEvent::Finished { task: self.task });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[test]
+    fn test_progress_scope() {
+        let events = Mutex::new(vec![]);
+        let progress = |event: &ProgressEvent<'_>| {
+            events.lock().unwrap().push(format!("{event:?}"));
+        };
+        {
+            let scope = ProgressScope::start(Some(&progress), ProgressTask::IndexBuild);
+            scope.advance(1, Some(2), None);
+            scope.advance(2, None, Some("file"));
+        }
+        insta::assert_debug_snapshot!(events.into_inner().unwrap(), @r#"
+        [
+            "Started { task: IndexBuild }",
+            "Advanced { task: IndexBuild, completed: 1, total: Some(2), item: None }",
+            "Advanced { task: IndexBuild, completed: 2, total: None, item: Some(\"file\") }",
+            "Finished { task: IndexBuild }",
+        ]
+        "#);
+    }
+}
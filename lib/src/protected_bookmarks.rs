@@ -0,0 +1,151 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bookmarks that may only be moved forward.
+
+use itertools::Itertools as _;
+use serde::Deserialize as _;
+use serde::de::IntoDeserializer as _;
+use thiserror::Error;
+
+use crate::config::ConfigGetError;
+use crate::index::IndexError;
+use crate::iter_util::fallible_any;
+use crate::op_store::RefTarget;
+use crate::ref_name::RefName;
+use crate::ref_name::RefNameBuf;
+use crate::refs::BookmarkPushUpdate;
+use crate::refs::diff_named_ref_targets;
+use crate::repo::Repo;
+use crate::settings::UserSettings;
+use crate::str_util::StringExpression;
+use crate::str_util::StringMatcher;
+use crate::str_util::StringPattern;
+use crate::view::View;
+
+/// Error from updating a protected bookmark.
+#[derive(Debug, Error)]
+pub enum ProtectedBookmarkError {
+    /// The bookmark would be deleted.
+    #[error("Refusing to delete protected bookmark: {}", name.as_symbol())]
+    Deleted {
+        /// Name of the bookmark.
+        name: RefNameBuf,
+    },
+    /// The bookmark would be moved to a commit that isn't a descendant of its
+    /// current target.
+    #[error(
+        "Refusing to move protected bookmark backwards or sideways: {}",
+        name.as_symbol()
+    )]
+    NotFastForward {
+        /// Name of the bookmark.
+        name: RefNameBuf,
+    },
+    /// Failed to load the `bookmarks.protect` setting.
+    #[error(transparent)]
+    Config(#[from] ConfigGetError),
+    /// Failed to check the ancestry of the targets.
+    #[error(transparent)]
+    Index(#[from] IndexError),
+}
+
+/// Bookmarks matching the `bookmarks.protect` patterns, which can't be moved
+/// backwards or sideways, or deleted.
+#[derive(Debug)]
+pub struct ProtectedBookmarks {
+    matcher: StringMatcher,
+}
+
+impl ProtectedBookmarks {
+    /// Loads the glob patterns of protected bookmarks from the
+    /// `bookmarks.protect` setting.
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
+        let matcher = settings.get_value_with("bookmarks.protect", |value| {
+            let patterns = Vec::<String>::deserialize(value.into_deserializer())?;
+            let expressions: Vec<_> = patterns
+                .iter()
+                .map(|pattern| StringPattern::glob(pattern).map(StringExpression::pattern))
+                .try_collect()?;
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(
+                StringExpression::union_all(expressions).to_matcher(),
+            )
+        })?;
+        Ok(Self { matcher })
+    }
+
+    /// Returns true if the bookmark is protected.
+    pub fn is_protected(&self, name: &RefName) -> bool {
+        self.matcher.is_match(name.as_str())
+    }
+
+    /// Checks that the protected bookmark `name` is moved only forward from
+    /// `old_target` to `new_target`. Creating the bookmark is always allowed.
+    pub fn check_update(
+        &self,
+        repo: &dyn Repo,
+        name: &RefName,
+        old_target: &RefTarget,
+        new_target: &RefTarget,
+    ) -> Result<(), ProtectedBookmarkError> {
+        if !self.is_protected(name) || old_target.is_absent() || old_target == new_target {
+            return Ok(());
+        }
+        if new_target.is_absent() {
+            return Err(ProtectedBookmarkError::Deleted { name: name.into() });
+        }
+        // As with `jj bookmark move`, a conflicted bookmark may be resolved
+        // to a descendant of any of its targets.
+        let is_fast_forward = fallible_any(old_target.added_ids(), |old_id| {
+            fallible_any(new_target.added_ids(), |new_id| {
+                repo.index().is_ancestor(old_id, new_id)
+            })
+        })?;
+        if !is_fast_forward {
+            return Err(ProtectedBookmarkError::NotFastForward { name: name.into() });
+        }
+        Ok(())
+    }
+
+    /// Checks the local bookmarks changed from `old_view` to the view of
+    /// `repo`.
+    pub fn check_local_bookmark_changes(
+        &self,
+        repo: &dyn Repo,
+        old_view: &View,
+    ) -> Result<(), ProtectedBookmarkError> {
+        let new_view = repo.view();
+        for (name, (old_target, new_target)) in
+            diff_named_ref_targets(old_view.local_bookmarks(), new_view.local_bookmarks())
+        {
+            self.check_update(repo, name, old_target, new_target)?;
+        }
+        Ok(())
+    }
+
+    /// Checks an update of the remote bookmark `name` to be pushed.
+    pub fn check_push_update(
+        &self,
+        repo: &dyn Repo,
+        name: &RefName,
+        update: &BookmarkPushUpdate,
+    ) -> Result<(), ProtectedBookmarkError> {
+        self.check_update(
+            repo,
+            name,
+            &RefTarget::resolved(update.old_target.clone()),
+            &RefTarget::resolved(update.new_target.clone()),
+        )
+    }
+}
@@ -15,6 +15,10 @@ pub struct FileState {
     pub file_type: i32,
     #[prost(message, optional, tag = "5")]
     pub materialized_conflict_data: ::core::option::Option<MaterializedConflictData>,
+    /// Hash of the file contents as written or snapshotted, if enabled by the
+    /// `working-copy.touched-file-check` setting.
+    #[prost(fixed64, optional, tag = "6")]
+    pub quick_hash: ::core::option::Option<u64>,
 }
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct FileStateEntry {
@@ -23,10 +27,43 @@ pub struct FileStateEntry {
     #[prost(message, optional, tag = "2")]
     pub state: ::core::option::Option<FileState>,
 }
+/// Entries of a directory as of the last snapshot. Used to skip reading
+/// directories whose mtime hasn't changed since.
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct DirectoryListing {
+    #[prost(string, tag = "1")]
+    pub path: ::prost::alloc::string::String,
+    #[prost(int64, tag = "2")]
+    pub mtime_millis_since_epoch: i64,
+    /// Sorted names of subdirectories which aren't tracked in `file_states`.
+    #[prost(string, repeated, tag = "3")]
+    pub untracked_dirs: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Sorted names of all other entries which aren't tracked in `file_states`.
+    #[prost(string, repeated, tag = "4")]
+    pub untracked_files: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// A path which differs only in case from another path, and therefore isn't
+/// checked out at its own path.
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CaseCollision {
+    #[prost(string, tag = "1")]
+    pub path: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub other_path: ::prost::alloc::string::String,
+    /// The path the file is checked out as, or empty if it's not checked out.
+    #[prost(string, tag = "3")]
+    pub checked_out_as: ::prost::alloc::string::String,
+}
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct SparsePatterns {
     #[prost(string, repeated, tag = "1")]
     pub prefixes: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Prefixes excluded even if they're under one of the `prefixes`.
+    #[prost(string, repeated, tag = "2")]
+    pub excluded_prefixes: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Whether the files in the parent directories of `prefixes` are included.
+    #[prost(bool, tag = "3")]
+    pub cone: bool,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TreeState {
@@ -48,6 +85,17 @@ pub struct TreeState {
     pub sparse_patterns: ::core::option::Option<SparsePatterns>,
     #[prost(message, optional, tag = "4")]
     pub watchman_clock: ::core::option::Option<WatchmanClock>,
+    #[prost(message, optional, tag = "8")]
+    pub builtin_fsmonitor_clock: ::core::option::Option<BuiltinFsmonitorClock>,
+    /// Sorted by path.
+    #[prost(message, repeated, tag = "9")]
+    pub directory_listings: ::prost::alloc::vec::Vec<DirectoryListing>,
+    /// Sorted paths which are tracked even if they're ignored or too large.
+    #[prost(string, repeated, tag = "10")]
+    pub force_tracked_paths: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Sorted by path.
+    #[prost(message, repeated, tag = "11")]
+    pub case_collisions: ::prost::alloc::vec::Vec<CaseCollision>,
 }
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct WatchmanClock {
@@ -65,6 +113,15 @@ pub mod watchman_clock {
     }
 }
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct BuiltinFsmonitorClock {
+    /// Identifies the journal written by a single run of the monitor daemon.
+    #[prost(string, tag = "1")]
+    pub instance_id: ::prost::alloc::string::String,
+    /// Number of journal bytes that have been consumed.
+    #[prost(uint64, tag = "2")]
+    pub journal_offset: u64,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct Checkout {
     /// The operation at which the working copy was updated.
     #[prost(bytes = "vec", tag = "2")]
@@ -139,6 +139,18 @@ pub struct View {
     /// Whether "@git" tags have been migrated to remote_views.
     #[prost(bool, tag = "12")]
     pub has_git_refs_migrated_to_remote_tags: bool,
+    #[prost(message, repeated, tag = "13")]
+    pub deferred_rewrites: ::prost::alloc::vec::Vec<DeferredRewrite>,
+    /// Names of the workspaces whose working-copy commits are throwaway.
+    #[prost(string, repeated, tag = "14")]
+    pub ephemeral_workspaces: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeferredRewrite {
+    #[prost(bytes = "vec", tag = "1")]
+    pub old_commit_id: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub new_commit_id: ::prost::alloc::vec::Vec<u8>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RemoteView {
@@ -78,6 +78,7 @@ use crate::op_store::RemoteRef;
 use crate::op_store::RemoteRefState;
 use crate::op_store::RootOperationData;
 use crate::operation::Operation;
+use crate::plugin_backend::PluginBackend;
 use crate::ref_name::GitRefName;
 use crate::ref_name::RefName;
 use crate::ref_name::RemoteName;
@@ -89,6 +90,8 @@ use crate::refs::diff_named_ref_targets;
 use crate::refs::diff_named_remote_refs;
 use crate::refs::merge_ref_targets;
 use crate::refs::merge_remote_refs;
+use crate::repo_path::RepoPathBuf;
+use crate::rerere::RerereStore;
 use crate::revset;
 use crate::revset::RevsetEvaluationError;
 use crate::revset::RevsetExpression;
@@ -254,6 +257,7 @@ impl ReadonlyRepo {
         fs::write(&submodule_store_type_path, submodule_store.name())
             .context(&submodule_store_type_path)?;
         let submodule_store = Arc::from(submodule_store);
+        let rerere_store = Arc::new(RerereStore::new(repo_path.join("rerere")));
 
         let loader = RepoLoader {
             settings: settings.clone(),
@@ -262,6 +266,7 @@ impl ReadonlyRepo {
             op_heads_store,
             index_store,
             submodule_store,
+            rerere_store,
         };
 
         let root_operation = loader.root_operation();
@@ -428,7 +433,15 @@ impl Default for StoreFactories {
         // Backends
         factories.add_backend(
             SimpleBackend::name(),
-            Box::new(|_settings, store_path| Ok(Box::new(SimpleBackend::load(store_path)))),
+            Box::new(|settings, store_path| {
+                Ok(Box::new(SimpleBackend::load(settings, store_path)?))
+            }),
+        );
+        factories.add_backend(
+            PluginBackend::name(),
+            Box::new(|settings, store_path| {
+                Ok(Box::new(PluginBackend::load(settings, store_path)?))
+            }),
         );
         #[cfg(feature = "git")]
         factories.add_backend(
@@ -668,6 +681,7 @@ pub struct RepoLoader {
     op_heads_store: Arc<dyn OpHeadsStore>,
     index_store: Arc<dyn IndexStore>,
     submodule_store: Arc<dyn SubmoduleStore>,
+    rerere_store: Arc<RerereStore>,
 }
 
 impl RepoLoader {
@@ -678,6 +692,7 @@ impl RepoLoader {
         op_heads_store: Arc<dyn OpHeadsStore>,
         index_store: Arc<dyn IndexStore>,
         submodule_store: Arc<dyn SubmoduleStore>,
+        rerere_store: Arc<RerereStore>,
     ) -> Self {
         Self {
             settings,
@@ -686,6 +701,7 @@ impl RepoLoader {
             op_heads_store,
             index_store,
             submodule_store,
+            rerere_store,
         }
     }
 
@@ -719,6 +735,7 @@ impl RepoLoader {
         let submodule_store = Arc::from(
             store_factories.load_submodule_store(settings, &repo_path.join("submodule_store"))?,
         );
+        let rerere_store = Arc::new(RerereStore::new(repo_path.join("rerere")));
         Ok(Self {
             settings: settings.clone(),
             store,
@@ -726,6 +743,7 @@ impl RepoLoader {
             op_heads_store,
             index_store,
             submodule_store,
+            rerere_store,
         })
     }
 
@@ -753,6 +771,11 @@ impl RepoLoader {
         &self.submodule_store
     }
 
+    /// Returns the store of recorded conflict resolutions.
+    pub fn rerere_store(&self) -> &Arc<RerereStore> {
+        &self.rerere_store
+    }
+
     pub fn load_at_head(&self) -> Result<Arc<ReadonlyRepo>, RepoLoaderError> {
         let op = op_heads_store::resolve_op_heads(
             self.op_heads_store.as_ref(),
@@ -895,6 +918,9 @@ pub struct MutableRepo {
     //   commits. However, if the type is `Abandoned`, a new working-copy commit should be created
     //   on top of all of the new commits instead.
     parent_mapping: HashMap<CommitId, Rewrite>,
+    /// Mapping from new commit to the paths of the conflicts that were resolved
+    /// using recorded resolutions.
+    auto_resolved_paths: BTreeMap<CommitId, Vec<RepoPathBuf>>,
 }
 
 impl MutableRepo {
@@ -907,6 +933,7 @@ impl MutableRepo {
             view: DirtyCell::with_clean(mut_view),
             commit_predecessors: Default::default(),
             parent_mapping: Default::default(),
+            auto_resolved_paths: Default::default(),
         }
     }
 
@@ -962,6 +989,16 @@ impl MutableRepo {
         self.commit_predecessors.insert(id, predecessors);
     }
 
+    pub(crate) fn set_auto_resolved_paths(&mut self, id: CommitId, paths: Vec<RepoPathBuf>) {
+        self.auto_resolved_paths.insert(id, paths);
+    }
+
+    /// Returns the commits written in this transaction that had conflicts
+    /// resolved using recorded resolutions, along with the resolved paths.
+    pub fn auto_resolved_paths(&self) -> &BTreeMap<CommitId, Vec<RepoPathBuf>> {
+        &self.auto_resolved_paths
+    }
+
     /// Record a commit as having been rewritten to another commit in this
     /// transaction.
     ///
@@ -1434,6 +1471,127 @@ impl MutableRepo {
         Ok(num_rebased)
     }
 
+    /// Rebase descendants of the rewritten commits, except for the descendants
+    /// of rewritten (not abandoned) commits which still have children.
+    ///
+    /// Those rewrites are recorded in the view instead, so the descendants can
+    /// be rebased by a later call to [`Self::rebase_deferred_descendants()`],
+    /// possibly in another transaction. References to the rewritten commits
+    /// are updated as usual. Returns the number of rebased descendants, and
+    /// the ids of the commits whose descendants weren't rebased.
+    pub fn defer_rebase_descendants(&mut self) -> BackendResult<(usize, Vec<CommitId>)> {
+        self.update_deferred_rewrites();
+        let old_ids = self.parent_mapping.keys().cloned().collect_vec();
+        let old_commits_expression = RevsetExpression::commits(old_ids.clone());
+        let rewritten_ids = self
+            .parent_mapping
+            .iter()
+            .filter(|(_, rewrite)| matches!(rewrite, Rewrite::Rewritten(_)))
+            .map(|(id, _)| id.clone())
+            .collect_vec();
+        let children: Vec<Commit> = RevsetExpression::commits(rewritten_ids.clone())
+            .children()
+            .minus(&old_commits_expression)
+            .evaluate(self)
+            .map_err(|err| err.into_backend_error())?
+            .iter()
+            .commits(self.store())
+            .try_collect()
+            .map_err(|err| err.into_backend_error())?;
+        let parents_of_children: HashSet<&CommitId> = children
+            .iter()
+            .flat_map(|commit| commit.parent_ids())
+            .collect();
+        let mut deferred_ids = vec![];
+        for old_id in rewritten_ids {
+            if !parents_of_children.contains(&old_id) {
+                continue;
+            }
+            // The new commit may have been abandoned in the same transaction
+            if let [new_id] = &*self.new_parents(slice::from_ref(&old_id)) {
+                self.view_mut()
+                    .set_deferred_rewrite(old_id.clone(), new_id.clone());
+                deferred_ids.push(old_id);
+            }
+        }
+
+        let to_visit: Vec<Commit> = old_commits_expression
+            .descendants()
+            .minus(&old_commits_expression)
+            .minus(&RevsetExpression::commits(deferred_ids.clone()).descendants())
+            .evaluate(self)
+            .map_err(|err| err.into_backend_error())?
+            .iter()
+            .commits(self.store())
+            .try_collect()
+            .map_err(|err| err.into_backend_error())?;
+        let options = RebaseOptions::default();
+        let mut num_rebased = 0;
+        self.transform_commits(
+            to_visit,
+            &HashMap::new(),
+            &options.rewrite_refs,
+            async |rewriter| {
+                if rewriter.parents_changed() {
+                    rebase_commit_with_options(rewriter, &options)?;
+                    num_rebased += 1;
+                }
+                Ok(())
+            },
+        )?;
+        self.parent_mapping.clear();
+        Ok((num_rebased, deferred_ids))
+    }
+
+    /// Rebase the descendants of the commits recorded by
+    /// [`Self::defer_rebase_descendants()`], along with the descendants of the
+    /// commits rewritten in this transaction.
+    ///
+    /// Returns the number of rebased descendants.
+    pub fn rebase_deferred_descendants(&mut self) -> BackendResult<usize> {
+        self.update_deferred_rewrites();
+        let deferred_rewrites = self.view().deferred_rewrites().clone();
+        for (old_id, new_id) in deferred_rewrites {
+            self.view_mut().remove_deferred_rewrite(&old_id);
+            self.parent_mapping
+                .insert(old_id, Rewrite::Rewritten(new_id));
+        }
+        self.rebase_descendants()
+    }
+
+    /// Forgets that the descendants of `old_id` should be rebased onto its new
+    /// version. The descendants will stay on `old_id`.
+    pub fn remove_deferred_rewrite(&mut self, old_id: &CommitId) {
+        self.view_mut().remove_deferred_rewrite(old_id);
+    }
+
+    /// Updates the deferred rewrites in the view to point to the latest
+    /// versions of the commits rewritten in this transaction.
+    fn update_deferred_rewrites(&mut self) {
+        let deferred_rewrites = self.view().deferred_rewrites().clone();
+        for (old_id, new_id) in deferred_rewrites {
+            if self.parent_mapping.contains_key(&old_id) {
+                // The old commit was rewritten again. Its descendants will
+                // follow the latest rewrite.
+                self.view_mut().remove_deferred_rewrite(&old_id);
+            } else if self.parent_mapping.contains_key(&new_id) {
+                match &*self.new_parents(slice::from_ref(&new_id)) {
+                    [new_id] => {
+                        self.view_mut().set_deferred_rewrite(old_id, new_id.clone());
+                    }
+                    new_ids => {
+                        // The new commit was abandoned. Rebase the descendants
+                        // onto its parents now.
+                        let new_ids = new_ids.to_vec();
+                        self.view_mut().remove_deferred_rewrite(&old_id);
+                        self.parent_mapping
+                            .insert(old_id, Rewrite::Abandoned(new_ids));
+                    }
+                }
+            }
+        }
+    }
+
     /// Reparent descendants of the rewritten commits.
     ///
     /// The descendants of the commits registered in `self.parent_mappings` will
@@ -1468,11 +1626,22 @@ impl MutableRepo {
     }
 
     pub fn remove_wc_commit(&mut self, name: &WorkspaceName) -> Result<(), EditCommitError> {
-        self.maybe_abandon_wc_commit(name)?;
+        if self.view().is_ephemeral_workspace(name) {
+            self.abandon_ephemeral_wc_commit(name)?;
+        } else {
+            self.maybe_abandon_wc_commit(name)?;
+        }
         self.view_mut().remove_wc_commit(name);
         Ok(())
     }
 
+    /// Marks the workspace as ephemeral. The working-copy commit of an
+    /// ephemeral workspace is abandoned when the workspace is removed, even if
+    /// it has changes.
+    pub fn set_ephemeral_workspace(&mut self, name: WorkspaceNameBuf, ephemeral: bool) {
+        self.view_mut().set_ephemeral_workspace(name, ephemeral);
+    }
+
     /// Merges working-copy commit. If there's a conflict, and if the workspace
     /// isn't removed at either side, we keep the self side.
     fn merge_wc_commit(
@@ -1532,6 +1701,23 @@ impl MutableRepo {
     fn maybe_abandon_wc_commit(
         &mut self,
         workspace_name: &WorkspaceName,
+    ) -> Result<(), EditCommitError> {
+        self.abandon_wc_commit_if(workspace_name, |repo, wc_commit| {
+            wc_commit.is_discardable(repo)
+        })
+    }
+
+    fn abandon_ephemeral_wc_commit(
+        &mut self,
+        workspace_name: &WorkspaceName,
+    ) -> Result<(), EditCommitError> {
+        self.abandon_wc_commit_if(workspace_name, |_, _| Ok(true))
+    }
+
+    fn abandon_wc_commit_if(
+        &mut self,
+        workspace_name: &WorkspaceName,
+        predicate: impl FnOnce(&Self, &Commit) -> BackendResult<bool>,
     ) -> Result<(), EditCommitError> {
         let is_commit_referenced = |view: &View, commit_id: &CommitId| -> bool {
             view.wc_commit_ids()
@@ -1553,15 +1739,15 @@ impl MutableRepo {
                 .store()
                 .get_commit(&wc_commit_id)
                 .map_err(EditCommitError::WorkingCopyCommitNotFound)?;
-            if wc_commit.is_discardable(self)?
+            if predicate(self, &wc_commit)?
                 && self
                     .view
                     .with_ref(|v| !is_commit_referenced(v, wc_commit.id()))
                 && self.view().heads().contains(wc_commit.id())
             {
                 // Abandon the working-copy commit we're leaving if it's
-                // discardable, not pointed by local bookmark or other working
-                // copies, and a head commit.
+                // discardable (or the workspace is ephemeral), not pointed by
+                // local bookmark or other working copies, and a head commit.
                 self.record_abandoned_commit(&wc_commit);
             }
         }
@@ -1589,6 +1775,9 @@ impl MutableRepo {
                 .collect();
         }
         assert!(!view.head_ids.is_empty());
+        let wc_commit_ids = &view.wc_commit_ids;
+        view.ephemeral_workspaces
+            .retain(|name| wc_commit_ids.contains_key(name));
     }
 
     /// Ensures that the given `head` and ancestor commits are reachable from
@@ -1856,6 +2045,31 @@ impl MutableRepo {
             self.merge_wc_commit(name, base_id, other_id);
         }
 
+        for name in other
+            .ephemeral_workspaces()
+            .difference(base.ephemeral_workspaces())
+        {
+            self.view_mut().set_ephemeral_workspace(name.clone(), true);
+        }
+        for name in base
+            .ephemeral_workspaces()
+            .difference(other.ephemeral_workspaces())
+        {
+            self.view_mut().set_ephemeral_workspace(name.clone(), false);
+        }
+
+        for (old_id, new_id) in other.deferred_rewrites() {
+            if base.deferred_rewrites().get(old_id) != Some(new_id) {
+                self.view_mut()
+                    .set_deferred_rewrite(old_id.clone(), new_id.clone());
+            }
+        }
+        for old_id in base.deferred_rewrites().keys() {
+            if !other.deferred_rewrites().contains_key(old_id) {
+                self.view_mut().remove_deferred_rewrite(old_id);
+            }
+        }
+
         let base_heads = base.heads().iter().cloned().collect_vec();
         let own_heads = self.view().heads().iter().cloned().collect_vec();
         let other_heads = other.heads().iter().cloned().collect_vec();
@@ -0,0 +1,411 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reuse of recorded conflict resolutions, similar to `git rerere`.
+//!
+//! When a file conflict is resolved, the conflict is recorded along with its
+//! resolution, keyed by a fingerprint of the conflicting hunks. If a conflict
+//! with the same fingerprint appears again while rebasing, the recorded
+//! resolution is applied to it.
+//!
+//! The resolution is applied by merging the difference between the recorded
+//! conflict ("preimage") and its resolution ("postimage") into the new
+//! conflict. This allows the resolution to be reused even if the surrounding
+//! non-conflicting lines have changed.
+
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use blake2::Blake2b512;
+use blake2::Digest as _;
+use bstr::BString;
+use bstr::ByteSlice as _;
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::CopyId;
+use crate::backend::TreeValue;
+use crate::commit::Commit;
+use crate::conflict_labels::ConflictLabels;
+use crate::conflicts::ConflictMarkerStyle;
+use crate::conflicts::ConflictMaterializeOptions;
+use crate::conflicts::extract_as_single_hunk;
+use crate::conflicts::materialize_merge_result_to_bytes;
+use crate::conflicts::resolve_file_executable;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::file_util::persist_temp_file;
+use crate::files;
+use crate::files::MergeResult;
+use crate::hex_util;
+use crate::merge::Merge;
+use crate::merge::MergedTreeValue;
+use crate::merged_tree::MergedTree;
+use crate::merged_tree_builder::MergedTreeBuilder;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::store::Store;
+use crate::tree_merge::MergeOptions;
+
+/// Number of bytes of the content hash used as fingerprint.
+const FINGERPRINT_LEN: usize = 20;
+
+/// Error that may occur while recording or applying resolutions.
+#[derive(Debug, Error)]
+pub enum RerereError {
+    /// Failed to access the recorded resolutions.
+    #[error("Failed to access recorded conflict resolutions")]
+    Io(#[from] PathError),
+    /// A recorded resolution couldn't be parsed.
+    #[error("Recorded conflict resolution {fingerprint} is corrupt")]
+    Corrupt {
+        /// Fingerprint of the corrupt record.
+        fingerprint: String,
+    },
+    /// Failed to read or write objects.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
+/// Summary of a recorded resolution.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordedResolution {
+    /// Fingerprint of the conflicting hunks.
+    pub fingerprint: String,
+    /// Path of the file the resolution was recorded for.
+    pub path: RepoPathBuf,
+}
+
+/// Recorded conflict and its resolution.
+#[derive(Clone, Debug)]
+struct Record {
+    path: RepoPathBuf,
+    preimage: BString,
+    postimage: BString,
+}
+
+/// Stores recorded resolutions in a directory, one file per fingerprint.
+#[derive(Debug)]
+pub struct RerereStore {
+    dir: PathBuf,
+}
+
+impl RerereStore {
+    /// Creates a store backed by `dir`. The directory is created when the
+    /// first resolution is recorded.
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Lists recorded resolutions, sorted by path.
+    pub fn list(&self) -> Result<Vec<RecordedResolution>, RerereError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err).context(&self.dir)?,
+        };
+        let mut resolutions = vec![];
+        for entry in entries {
+            let entry = entry.context(&self.dir)?;
+            let Some(fingerprint) = entry.file_name().to_str().map(|name| name.to_owned()) else {
+                continue;
+            };
+            if !is_fingerprint(&fingerprint) {
+                continue;
+            }
+            if let Some(record) = self.read(&fingerprint)? {
+                resolutions.push(RecordedResolution {
+                    fingerprint,
+                    path: record.path,
+                });
+            }
+        }
+        resolutions.sort_by(|a, b| (&a.path, &a.fingerprint).cmp(&(&b.path, &b.fingerprint)));
+        Ok(resolutions)
+    }
+
+    /// Removes the resolution recorded for `fingerprint`. Returns false if
+    /// there was no such resolution.
+    pub fn forget(&self, fingerprint: &str) -> Result<bool, RerereError> {
+        if !is_fingerprint(fingerprint) {
+            return Ok(false);
+        }
+        let path = self.dir.join(fingerprint);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err).context(&path)?,
+        }
+    }
+
+    fn read(&self, fingerprint: &str) -> Result<Option<Record>, RerereError> {
+        let path = self.dir.join(fingerprint);
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).context(&path)?,
+        };
+        let record = parse_record(&data).ok_or_else(|| RerereError::Corrupt {
+            fingerprint: fingerprint.to_owned(),
+        })?;
+        Ok(Some(record))
+    }
+
+    fn write(&self, fingerprint: &str, record: &Record) -> Result<(), RerereError> {
+        fs::create_dir_all(&self.dir).context(&self.dir)?;
+        let mut temp_file = NamedTempFile::new_in(&self.dir).context(&self.dir)?;
+        let path = record.path.as_internal_file_string();
+        let file = temp_file.as_file_mut();
+        writeln!(file, "{} {}", path.len(), record.preimage.len())
+            .and_then(|()| file.write_all(path.as_bytes()))
+            .and_then(|()| file.write_all(&record.preimage))
+            .and_then(|()| file.write_all(&record.postimage))
+            .context(temp_file.path())?;
+        let target = self.dir.join(fingerprint);
+        persist_temp_file(temp_file, &target).context(&target)?;
+        Ok(())
+    }
+}
+
+fn is_fingerprint(name: &str) -> bool {
+    name.len() == FINGERPRINT_LEN * 2 && hex_util::decode_hex(name).is_some()
+}
+
+fn parse_record(data: &[u8]) -> Option<Record> {
+    let (header, rest) = data.split_once_str("\n")?;
+    let (path_len, preimage_len) = header.to_str().ok()?.split_once(' ')?;
+    let path_len: usize = path_len.parse().ok()?;
+    let preimage_len: usize = preimage_len.parse().ok()?;
+    let (path, rest) = rest.split_at_checked(path_len)?;
+    let (preimage, postimage) = rest.split_at_checked(preimage_len)?;
+    Some(Record {
+        path: RepoPathBuf::from_internal_string(path.to_str().ok()?).ok()?,
+        preimage: preimage.into(),
+        postimage: postimage.into(),
+    })
+}
+
+/// Computes the fingerprint of a file conflict from its conflicting hunks.
+///
+/// Hunks that could be merged cleanly don't contribute to the fingerprint, so
+/// the same conflict gets the same fingerprint even if unrelated parts of the
+/// file have changed. Returns `None` if the contents can be merged cleanly.
+pub fn conflict_fingerprint(contents: &Merge<BString>, options: &MergeOptions) -> Option<String> {
+    let MergeResult::Conflict(hunks) = files::merge_hunks(contents, options) else {
+        return None;
+    };
+    let mut hasher = Blake2b512::new();
+    for hunk in hunks.iter().filter(|hunk| !hunk.is_resolved()) {
+        hasher.update(u64::try_from(hunk.as_slice().len()).unwrap().to_le_bytes());
+        for term in hunk {
+            hasher.update(u64::try_from(term.len()).unwrap().to_le_bytes());
+            hasher.update(term);
+        }
+    }
+    Some(hex_util::encode_hex(&hasher.finalize()[..FINGERPRINT_LEN]))
+}
+
+/// Materializes the conflict in a form that doesn't depend on the conflict
+/// labels or the user's marker style.
+fn materialize_preimage(contents: &Merge<BString>, options: &MergeOptions) -> BString {
+    let options = ConflictMaterializeOptions {
+        marker_style: ConflictMarkerStyle::Snapshot,
+        marker_len: None,
+        merge: options.clone(),
+    };
+    materialize_merge_result_to_bytes(contents, &ConflictLabels::unlabeled(), &options)
+}
+
+async fn file_conflict_contents(
+    store: &Store,
+    path: &RepoPath,
+    value: &MergedTreeValue,
+) -> BackendResult<Option<Merge<BString>>> {
+    let Some(file_ids) = value.to_file_merge() else {
+        return Ok(None);
+    };
+    let contents = extract_as_single_hunk(&file_ids.simplify(), store, path).await?;
+    Ok(Some(contents))
+}
+
+/// Records the resolutions of file conflicts that `old_commit` had and
+/// `new_commit` resolved. Returns the number of recorded resolutions.
+///
+/// Resolutions are only recorded if both commits have the same parents,
+/// since a conflict disappearing after a rebase isn't a resolution.
+pub async fn record_resolutions(
+    rerere_store: &RerereStore,
+    old_commit: &Commit,
+    new_commit: &Commit,
+) -> Result<usize, RerereError> {
+    if old_commit.parent_ids() != new_commit.parent_ids() || !old_commit.has_conflict() {
+        return Ok(0);
+    }
+    let store = new_commit.store();
+    let options = store.merge_options();
+    let new_tree = new_commit.tree();
+    let mut count = 0;
+    for (path, value) in old_commit.tree().conflicts() {
+        let value = value?;
+        let new_value = new_tree.path_value_async(&path).await?;
+        let Some(Some(TreeValue::File { id, .. })) = new_value.as_resolved() else {
+            continue;
+        };
+        let Some(contents) = file_conflict_contents(store, &path, &value).await? else {
+            continue;
+        };
+        let Some(fingerprint) = conflict_fingerprint(&contents, options) else {
+            continue;
+        };
+        let postimage = extract_as_single_hunk(&Merge::resolved(Some(id.clone())), store, &path)
+            .await?
+            .into_resolved()
+            .unwrap();
+        let record = Record {
+            preimage: materialize_preimage(&contents, options),
+            postimage,
+            path,
+        };
+        rerere_store.write(&fingerprint, &record)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Applies recorded resolutions to the file conflicts in `tree`. Returns the
+/// new tree and the paths that were resolved.
+pub async fn apply_resolutions(
+    rerere_store: &RerereStore,
+    store: &Arc<Store>,
+    tree: MergedTree,
+) -> Result<(MergedTree, Vec<RepoPathBuf>), RerereError> {
+    if !tree.has_conflict() {
+        return Ok((tree, vec![]));
+    }
+    let options = store.merge_options();
+    let mut resolved_paths = vec![];
+    let mut tree_builder = MergedTreeBuilder::new(tree.clone());
+    for (path, value) in tree.conflicts() {
+        let value = value?;
+        let Some(contents) = file_conflict_contents(store, &path, &value).await? else {
+            continue;
+        };
+        let Some(fingerprint) = conflict_fingerprint(&contents, options) else {
+            continue;
+        };
+        let Some(record) = rerere_store.read(&fingerprint)? else {
+            continue;
+        };
+        let preimage = materialize_preimage(&contents, options);
+        let inputs = Merge::from_vec(vec![record.postimage, record.preimage, preimage]);
+        let Some(resolved) = files::try_merge(&inputs, options) else {
+            tracing::info!(?path, "Recorded resolution doesn't apply cleanly");
+            continue;
+        };
+        let executable = value
+            .to_executable_merge()
+            .as_ref()
+            .and_then(resolve_file_executable)
+            .unwrap_or(false);
+        let id = store.write_file(&path, &mut resolved.as_slice()).await?;
+        let new_value = TreeValue::File {
+            id,
+            executable,
+            copy_id: CopyId::placeholder(),
+        };
+        tree_builder.set_or_remove(path.clone(), Merge::normal(new_value));
+        resolved_paths.push(path);
+    }
+    if resolved_paths.is_empty() {
+        return Ok((tree, resolved_paths));
+    }
+    let new_tree = tree_builder.write_tree()?;
+    Ok((new_tree, resolved_paths))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(terms: &[&str]) -> Merge<BString> {
+        Merge::from_vec(
+            terms
+                .iter()
+                .map(|term| BString::from(*term))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_conflict_fingerprint() {
+        let options = MergeOptions {
+            hunk_level: files::FileMergeHunkLevel::Line,
+            same_change: crate::merge::SameChange::Accept,
+            diff_algorithm: crate::diff::DiffAlgorithm::Histogram,
+        };
+        let fingerprint = |terms: &[&str]| conflict_fingerprint(&merge(terms), &options);
+
+        // Clean merges have no fingerprint
+        assert_eq!(fingerprint(&["a\n", "b\n", "b\n"]), None);
+
+        let base = fingerprint(&["a\nleft\nc\n", "a\nbase\nc\n", "a\nright\nc\n"]).unwrap();
+        assert!(is_fingerprint(&base));
+        // Changes outside the conflicting hunk don't affect the fingerprint
+        assert_eq!(
+            fingerprint(&["x\nleft\nz\n", "x\nbase\nz\n", "x\nright\nz\n"]),
+            Some(base.clone())
+        );
+        // Swapping sides changes the fingerprint
+        assert_ne!(
+            fingerprint(&["a\nright\nc\n", "a\nbase\nc\n", "a\nleft\nc\n"]),
+            Some(base)
+        );
+    }
+
+    #[test]
+    fn test_parse_record() {
+        let record = Record {
+            path: RepoPathBuf::from_internal_string("dir/file").unwrap(),
+            preimage: "pre\n".into(),
+            postimage: "post\n".into(),
+        };
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = RerereStore::new(temp_dir.path().join("rerere"));
+        let fingerprint = "00".repeat(FINGERPRINT_LEN);
+        assert_eq!(store.list().unwrap(), vec![]);
+        store.write(&fingerprint, &record).unwrap();
+        let read = store.read(&fingerprint).unwrap().unwrap();
+        assert_eq!(read.path, record.path);
+        assert_eq!(read.preimage, record.preimage);
+        assert_eq!(read.postimage, record.postimage);
+        assert_eq!(
+            store.list().unwrap(),
+            vec![RecordedResolution {
+                fingerprint: fingerprint.clone(),
+                path: record.path,
+            }]
+        );
+        assert!(store.forget(&fingerprint).unwrap());
+        assert!(!store.forget(&fingerprint).unwrap());
+        assert_eq!(store.list().unwrap(), vec![]);
+
+        assert!(parse_record(b"").is_none());
+        assert!(parse_record(b"3 10\nabc").is_none());
+    }
+}
@@ -16,6 +16,7 @@
 
 use std::any::Any;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::collections::hash_map;
 use std::convert::Infallible;
 use std::fmt;
@@ -34,8 +35,11 @@ use crate::commit::Commit;
 use crate::dsl_util;
 use crate::dsl_util::collect_similar;
 use crate::fileset;
+use crate::fileset::BoxedFilesetFunction;
 use crate::fileset::FilesetDiagnostics;
 use crate::fileset::FilesetExpression;
+use crate::fileset::FilesetExtensions;
+use crate::fileset::FilesetFunctionConflictError;
 use crate::graph::GraphNode;
 use crate::id_prefix::IdPrefixContext;
 use crate::id_prefix::IdPrefixIndex;
@@ -72,6 +76,7 @@ use crate::str_util::StringExpression;
 use crate::str_util::StringPattern;
 use crate::time_util::DatePattern;
 use crate::time_util::DatePatternContext;
+use crate::view::View;
 
 /// Error occurred during symbol resolution.
 #[derive(Debug, Error)]
@@ -137,6 +142,7 @@ pub const PARENTS_RANGE_FULL: Range<u32> = 0..u32::MAX;
 pub enum RevsetCommitRef {
     WorkingCopy(WorkspaceNameBuf),
     WorkingCopies,
+    EphemeralWorkingCopies,
     Symbol(String),
     RemoteSymbol(RemoteRefSymbolBuf),
     ChangeId(HexPrefix),
@@ -150,6 +156,21 @@ pub enum RevsetCommitRef {
     Tags(StringExpression),
     GitRefs,
     GitHead,
+    /// Commits computed by an extension.
+    Extension(Arc<dyn RevsetCommitsExtension>),
+}
+
+/// A custom set of commits, defined by an extension. Unlike
+/// [`RevsetFilterExtension`], the set is computed once when the expression is
+/// resolved against a repo, so it can be backed by an external data source.
+///
+/// The set is computed again each time an expression containing it is
+/// resolved. Implementations querying a slow data source should cache the
+/// result.
+pub trait RevsetCommitsExtension: std::fmt::Debug + Send + Sync {
+    /// Returns the commits in the set. Commits which aren't in the `repo`
+    /// should be left out.
+    fn resolve(&self, repo: &dyn Repo) -> Result<Vec<CommitId>, RevsetResolutionError>;
 }
 
 /// A custom revset filter expression, defined by an extension.
@@ -383,6 +404,10 @@ impl<St: ExpressionState<CommitRef = RevsetCommitRef>> RevsetExpression<St> {
         Arc::new(Self::CommitRef(RevsetCommitRef::WorkingCopies))
     }
 
+    pub fn ephemeral_working_copies() -> Arc<Self> {
+        Arc::new(Self::CommitRef(RevsetCommitRef::EphemeralWorkingCopies))
+    }
+
     pub fn symbol(value: String) -> Arc<Self> {
         Arc::new(Self::CommitRef(RevsetCommitRef::Symbol(value)))
     }
@@ -429,6 +454,10 @@ impl<St: ExpressionState<CommitRef = RevsetCommitRef>> RevsetExpression<St> {
     pub fn git_head() -> Arc<Self> {
         Arc::new(Self::CommitRef(RevsetCommitRef::GitHead))
     }
+
+    pub fn extension_commits(commits: Arc<dyn RevsetCommitsExtension>) -> Arc<Self> {
+        Arc::new(Self::CommitRef(RevsetCommitRef::Extension(commits)))
+    }
 }
 
 // Compound expression
@@ -760,6 +789,18 @@ pub type RevsetFunction = fn(
     &LoweringContext,
 ) -> Result<Arc<UserRevsetExpression>, RevsetParseError>;
 
+/// Revset function which may capture state, e.g. a handle to the data source
+/// it looks up commits in.
+pub type BoxedRevsetFunction = Box<
+    dyn Fn(
+            &mut RevsetDiagnostics,
+            &FunctionCallNode,
+            &LoweringContext,
+        ) -> Result<Arc<UserRevsetExpression>, RevsetParseError>
+        + Send
+        + Sync,
+>;
+
 static BUILTIN_FUNCTION_MAP: LazyLock<HashMap<&str, RevsetFunction>> = LazyLock::new(|| {
     // Not using maplit::hashmap!{} or custom declarative macro here because
     // code completion inside macro is quite restricted.
@@ -850,6 +891,13 @@ static BUILTIN_FUNCTION_MAP: LazyLock<HashMap<&str, RevsetFunction>> = LazyLock:
         function.expect_no_arguments()?;
         Ok(RevsetExpression::working_copies())
     });
+    map.insert(
+        "ephemeral_working_copies",
+        |_diagnostics, function, _context| {
+            function.expect_no_arguments()?;
+            Ok(RevsetExpression::ephemeral_working_copies())
+        },
+    );
     map.insert("heads", |diagnostics, function, context| {
         let [arg] = function.expect_exact_arguments()?;
         let candidates = lower_expression(diagnostics, arg, context)?;
@@ -1071,7 +1119,12 @@ static BUILTIN_FUNCTION_MAP: LazyLock<HashMap<&str, RevsetFunction>> = LazyLock:
             )
         })?;
         let [arg] = function.expect_exact_arguments()?;
-        let expr = expect_fileset_expression(diagnostics, arg, ctx.path_converter)?;
+        let expr = expect_fileset_expression(
+            diagnostics,
+            arg,
+            ctx.path_converter,
+            context.extensions.fileset(),
+        )?;
         Ok(RevsetExpression::filter(RevsetFilterPredicate::File(expr)))
     });
     map.insert("diff_lines", |diagnostics, function, context| {
@@ -1091,7 +1144,12 @@ static BUILTIN_FUNCTION_MAP: LazyLock<HashMap<&str, RevsetFunction>> = LazyLock:
                     files_arg.span,
                 )
             })?;
-            expect_fileset_expression(diagnostics, files_arg, ctx.path_converter)?
+            expect_fileset_expression(
+                diagnostics,
+                files_arg,
+                ctx.path_converter,
+                context.extensions.fileset(),
+            )?
         } else {
             // TODO: defaults to CLI path arguments?
             // https://github.com/jj-vcs/jj/issues/2933#issuecomment-1925870731
@@ -1143,16 +1201,22 @@ pub fn expect_fileset_expression(
     diagnostics: &mut RevsetDiagnostics,
     node: &ExpressionNode,
     path_converter: &RepoPathUiConverter,
+    extensions: &FilesetExtensions,
 ) -> Result<FilesetExpression, RevsetParseError> {
     // Alias handling is a bit tricky. The outermost expression `alias` is
     // substituted, but inner expressions `x & alias` aren't. If this seemed
     // weird, we can either transform AST or turn off revset aliases completely.
     revset_parser::catch_aliases(diagnostics, node, |diagnostics, node| {
         let mut inner_diagnostics = FilesetDiagnostics::new();
-        let expression = fileset::parse(&mut inner_diagnostics, node.span.as_str(), path_converter)
-            .map_err(|err| {
-                RevsetParseError::expression("In fileset expression", node.span).with_source(err)
-            })?;
+        let expression = fileset::parse(
+            &mut inner_diagnostics,
+            node.span.as_str(),
+            path_converter,
+            extensions,
+        )
+        .map_err(|err| {
+            RevsetParseError::expression("In fileset expression", node.span).with_source(err)
+        })?;
         diagnostics.extend_with(inner_diagnostics, |diag| {
             RevsetParseError::expression("In fileset expression", node.span).with_source(diag)
         });
@@ -2891,6 +2955,16 @@ fn resolve_commit_ref(
             let wc_commits = repo.view().wc_commit_ids().values().cloned().collect_vec();
             Ok(wc_commits)
         }
+        RevsetCommitRef::EphemeralWorkingCopies => {
+            let view = repo.view();
+            let wc_commits = view
+                .ephemeral_workspaces()
+                .iter()
+                .filter_map(|name| view.get_wc_commit_id(name))
+                .cloned()
+                .collect_vec();
+            Ok(wc_commits)
+        }
         RevsetCommitRef::ChangeId(prefix) => {
             let resolver = &symbol_resolver.change_id_resolver;
             Ok(resolver
@@ -2946,6 +3020,7 @@ fn resolve_commit_ref(
             Ok(commit_ids)
         }
         RevsetCommitRef::GitHead => Ok(repo.view().git_head().added_ids().cloned().collect()),
+        RevsetCommitRef::Extension(commits) => commits.resolve(repo),
     }
 }
 
@@ -3052,15 +3127,39 @@ fn resolve_visibility(
     let context = VisibilityResolutionContext {
         referenced_commits: &[],
         visible_heads: &repo.view().heads().iter().cloned().collect_vec(),
+        hidden_heads: &ephemeral_wc_heads(repo.view()),
         root: repo.store().root_commit_id(),
     };
     context.resolve(expression)
 }
 
+/// Returns the working-copy commits of ephemeral workspaces which are heads.
+///
+/// They're left out of the visible set, so they aren't advertised as heads of
+/// the repo, but they can still be referenced explicitly, e.g. as `@`.
+fn ephemeral_wc_heads(view: &View) -> Vec<CommitId> {
+    let ephemeral_workspaces = view.ephemeral_workspaces();
+    let shared_wc_commit_ids: HashSet<_> = view
+        .wc_commit_ids()
+        .iter()
+        .filter(|(name, _)| !ephemeral_workspaces.contains(*name))
+        .map(|(_, id)| id)
+        .collect();
+    ephemeral_workspaces
+        .iter()
+        .filter_map(|name| view.get_wc_commit_id(name))
+        .filter(|id| view.heads().contains(*id) && !shared_wc_commit_ids.contains(id))
+        .cloned()
+        .collect()
+}
+
 #[derive(Clone, Debug)]
 struct VisibilityResolutionContext<'a> {
     referenced_commits: &'a [CommitId],
     visible_heads: &'a [CommitId],
+    /// Visible heads which are excluded from the visible set unless they're
+    /// referenced. Their parents become visible heads instead.
+    hidden_heads: &'a [CommitId],
     root: &'a CommitId,
 }
 
@@ -3167,6 +3266,7 @@ impl VisibilityResolutionContext<'_> {
                 let context = VisibilityResolutionContext {
                     referenced_commits: commits,
                     visible_heads: self.visible_heads,
+                    hidden_heads: self.hidden_heads,
                     root: self.root,
                 };
                 context.resolve(candidates)
@@ -3175,9 +3275,12 @@ impl VisibilityResolutionContext<'_> {
                 candidates,
                 visible_heads,
             } => {
+                // Ephemeral working-copy commits are included in the
+                // historical views.
                 let context = VisibilityResolutionContext {
                     referenced_commits: self.referenced_commits,
                     visible_heads,
+                    hidden_heads: &[],
                     root: self.root,
                 };
                 context.resolve(candidates)
@@ -3228,7 +3331,11 @@ impl VisibilityResolutionContext<'_> {
     }
 
     fn resolve_visible_heads(&self) -> ResolvedExpression {
-        ResolvedExpression::Commits(self.visible_heads.to_owned())
+        if self.hidden_heads.is_empty() {
+            ResolvedExpression::Commits(self.visible_heads.to_owned())
+        } else {
+            ResolvedExpression::Heads(self.resolve_visible_heads_with_parents(&[]).into())
+        }
     }
 
     fn resolve_visible_heads_or_referenced(&self) -> ResolvedExpression {
@@ -3236,10 +3343,37 @@ impl VisibilityResolutionContext<'_> {
         // `all()`, some of the logical transformation rules might subtly change
         // the evaluated set. For example, `all() & x` wouldn't be `x` if `x`
         // were hidden and if not included in `all()`.
-        let commits = itertools::chain(self.referenced_commits, self.visible_heads)
-            .cloned()
-            .collect();
-        ResolvedExpression::Commits(commits)
+        if self.hidden_heads.is_empty() {
+            let commits = itertools::chain(self.referenced_commits, self.visible_heads)
+                .cloned()
+                .collect();
+            ResolvedExpression::Commits(commits)
+        } else {
+            self.resolve_visible_heads_with_parents(self.referenced_commits)
+        }
+    }
+
+    /// Resolves the visible heads other than the hidden ones, the parents of
+    /// the hidden heads, and the `extra_commits`. The result may contain
+    /// non-head commits.
+    fn resolve_visible_heads_with_parents(&self, extra_commits: &[CommitId]) -> ResolvedExpression {
+        let commits = itertools::chain(
+            extra_commits,
+            self.visible_heads
+                .iter()
+                .filter(|id| !self.hidden_heads.contains(id)),
+        )
+        .cloned()
+        .collect();
+        let hidden_parents = ResolvedExpression::Ancestors {
+            heads: ResolvedExpression::Commits(self.hidden_heads.to_owned()).into(),
+            generation: 1..2,
+            parents_range: PARENTS_RANGE_FULL,
+        };
+        ResolvedExpression::Union(
+            ResolvedExpression::Commits(commits).into(),
+            hidden_parents.into(),
+        )
     }
 
     fn resolve_root(&self) -> ResolvedExpression {
@@ -3391,10 +3525,18 @@ impl<I: Iterator<Item = Result<CommitId, RevsetEvaluationError>>> Iterator
     }
 }
 
+/// Error registering a revset function under a name which is already taken.
+#[derive(Debug, Error)]
+#[error("Revset function `{name}` is already defined")]
+pub struct RevsetFunctionConflictError {
+    pub name: String,
+}
+
 /// A set of extensions for revset evaluation.
 pub struct RevsetExtensions {
     symbol_resolvers: Vec<Box<dyn SymbolResolverExtension>>,
-    function_map: HashMap<&'static str, RevsetFunction>,
+    function_map: HashMap<String, BoxedRevsetFunction>,
+    fileset: FilesetExtensions,
 }
 
 impl Default for RevsetExtensions {
@@ -3407,7 +3549,11 @@ impl RevsetExtensions {
     pub fn new() -> Self {
         Self {
             symbol_resolvers: vec![],
-            function_map: BUILTIN_FUNCTION_MAP.clone(),
+            function_map: BUILTIN_FUNCTION_MAP
+                .iter()
+                .map(|(&name, &func)| (name.to_owned(), Box::new(func) as BoxedRevsetFunction))
+                .collect(),
+            fileset: FilesetExtensions::new(),
         }
     }
 
@@ -3420,12 +3566,49 @@ impl RevsetExtensions {
     }
 
     pub fn add_custom_function(&mut self, name: &'static str, func: RevsetFunction) {
-        match self.function_map.entry(name) {
-            hash_map::Entry::Occupied(_) => {
-                panic!("Conflict registering revset function '{name}'")
+        if let Err(err) = self.add_boxed_function(name, Box::new(func)) {
+            panic!("{err}");
+        }
+    }
+
+    /// Registers a function which may capture state. Unlike
+    /// [`Self::add_custom_function()`], the name can be determined at
+    /// runtime, e.g. from config, so a conflicting name is reported as an
+    /// error.
+    pub fn add_boxed_function(
+        &mut self,
+        name: impl Into<String>,
+        func: BoxedRevsetFunction,
+    ) -> Result<(), RevsetFunctionConflictError> {
+        match self.function_map.entry(name.into()) {
+            hash_map::Entry::Occupied(entry) => Err(RevsetFunctionConflictError {
+                name: entry.key().clone(),
+            }),
+            hash_map::Entry::Vacant(entry) => {
+                entry.insert(func);
+                Ok(())
             }
-            hash_map::Entry::Vacant(v) => v.insert(func),
-        };
+        }
+    }
+
+    /// Returns true if a function of the given name is registered.
+    pub fn has_function(&self, name: &str) -> bool {
+        self.function_map.contains_key(name)
+    }
+
+    /// Extensions for the fileset expressions embedded in revsets, e.g. in
+    /// `files()`.
+    pub fn fileset(&self) -> &FilesetExtensions {
+        &self.fileset
+    }
+
+    /// Registers a fileset function, which may capture state.
+    pub fn add_fileset_function(
+        &mut self,
+        name: impl Into<String>,
+        func: BoxedFilesetFunction,
+    ) -> Result<(), FilesetFunctionConflictError> {
+        self.fileset.add_function(name, func)
     }
 }
 
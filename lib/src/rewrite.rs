@@ -49,6 +49,9 @@ use crate::merged_tree_builder::MergedTreeBuilder;
 use crate::repo::MutableRepo;
 use crate::repo::Repo;
 use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::rerere;
+use crate::rerere::RerereError;
 use crate::revset::RevsetExpression;
 use crate::revset::RevsetIteratorExt as _;
 use crate::store::Store;
@@ -266,13 +269,14 @@ impl<'repo> CommitRewriter<'repo> {
             .map(|parent| parent.tree_ids().clone())
             .collect_vec();
 
-        let (was_empty, new_tree) = if new_parent_trees == old_parent_trees {
+        let (was_empty, new_tree, auto_resolved_paths) = if new_parent_trees == old_parent_trees {
             (
                 // Optimization: was_empty is only used for newly empty, but when the
                 // parents haven't changed it can't be newly empty.
                 true,
                 // Optimization: Skip merging.
                 self.old_commit.tree(),
+                vec![],
             )
         } else {
             // We wouldn't need to resolve merge conflicts here if the
@@ -282,30 +286,30 @@ impl<'repo> CommitRewriter<'repo> {
             let new_base_tree_fut = merge_commit_trees(self.mut_repo, &new_parents);
             let old_tree = self.old_commit.tree();
             let (old_base_tree, new_base_tree) = try_join!(old_base_tree_fut, new_base_tree_fut)?;
-            (
-                old_base_tree.tree_ids() == self.old_commit.tree_ids(),
-                MergedTree::merge(Merge::from_vec(vec![
-                    (
-                        new_base_tree,
-                        format!(
-                            "{} (rebase destination)",
-                            conflict_label_for_commits(&new_parents)
-                        ),
-                    ),
-                    (
-                        old_base_tree,
-                        format!(
-                            "{} (parents of rebased revision)",
-                            conflict_label_for_commits(&old_parents)
-                        ),
+            let was_empty = old_base_tree.tree_ids() == self.old_commit.tree_ids();
+            let new_tree = MergedTree::merge(Merge::from_vec(vec![
+                (
+                    new_base_tree,
+                    format!(
+                        "{} (rebase destination)",
+                        conflict_label_for_commits(&new_parents)
                     ),
-                    (
-                        old_tree,
-                        format!("{} (rebased revision)", self.old_commit.conflict_label()),
+                ),
+                (
+                    old_base_tree,
+                    format!(
+                        "{} (parents of rebased revision)",
+                        conflict_label_for_commits(&old_parents)
                     ),
-                ]))
-                .await?,
-            )
+                ),
+                (
+                    old_tree,
+                    format!("{} (rebased revision)", self.old_commit.conflict_label()),
+                ),
+            ]))
+            .await?;
+            let (new_tree, auto_resolved_paths) = self.apply_recorded_resolutions(new_tree).await?;
+            (was_empty, new_tree, auto_resolved_paths)
         };
         // Ensure we don't abandon commits with multiple parents (merge commits), even
         // if they're empty.
@@ -327,10 +331,32 @@ impl<'repo> CommitRewriter<'repo> {
             .mut_repo
             .rewrite_commit(&self.old_commit)
             .set_parents(self.new_parents)
-            .set_tree(new_tree);
+            .set_tree(new_tree)
+            .set_auto_resolved_paths(auto_resolved_paths);
         Ok(Some(builder))
     }
 
+    /// Resolves conflicts in the rebased tree using recorded resolutions, if
+    /// enabled. Returns the new tree and the resolved paths.
+    async fn apply_recorded_resolutions(
+        &self,
+        tree: MergedTree,
+    ) -> BackendResult<(MergedTree, Vec<RepoPathBuf>)> {
+        let base_repo = self.mut_repo.base_repo();
+        if !tree.has_conflict() || !base_repo.settings().rerere_enabled() {
+            return Ok((tree, vec![]));
+        }
+        let rerere_store = base_repo.loader().rerere_store();
+        match rerere::apply_resolutions(rerere_store, self.mut_repo.store(), tree.clone()).await {
+            Ok(result) => Ok(result),
+            Err(RerereError::Backend(err)) => Err(err),
+            Err(err) => {
+                tracing::warn!(?err, "Failed to apply recorded conflict resolutions");
+                Ok((tree, vec![]))
+            }
+        }
+    }
+
     /// Rebase the old commit onto the new parents. Returns a `CommitBuilder`
     /// for the new commit.
     pub async fn rebase(self) -> BackendResult<CommitBuilder<'repo>> {
@@ -182,6 +182,14 @@ impl Backend for SecretBackend {
         self.inner.write_tree(path, contents).await
     }
 
+    fn compute_tree_id(&self, path: &RepoPath, contents: &Tree) -> Option<TreeId> {
+        self.inner.compute_tree_id(path, contents)
+    }
+
+    fn compute_file_id(&self, path: &RepoPath, contents: &[u8]) -> Option<FileId> {
+        self.inner.compute_file_id(path, contents)
+    }
+
     async fn read_commit(&self, id: &CommitId) -> BackendResult<Commit> {
         self.inner.read_commit(id).await
     }
@@ -56,6 +56,7 @@ struct UserSettingsData {
     operation_username: String,
     signing_behavior: SignBehavior,
     signing_key: Option<String>,
+    rerere_enabled: bool,
 }
 
 pub type RemoteSettingsMap = HashMap<RemoteNameBuf, RemoteSettings>;
@@ -144,6 +145,7 @@ impl UserSettings {
         let operation_username = config.get("operation.username")?;
         let signing_behavior = config.get("signing.behavior")?;
         let signing_key = config.get("signing.key").optional()?;
+        let rerere_enabled = config.get("rerere.enabled")?;
         let data = UserSettingsData {
             user_name,
             user_email,
@@ -153,6 +155,7 @@ impl UserSettings {
             operation_username,
             signing_behavior,
             signing_key,
+            rerere_enabled,
         };
         Ok(Self {
             config: Arc::new(config),
@@ -231,6 +234,11 @@ impl UserSettings {
         Ok((backend != "none").then_some(backend))
     }
 
+    /// Whether conflict resolutions should be recorded and reused.
+    pub fn rerere_enabled(&self) -> bool {
+        self.data.rerere_enabled
+    }
+
     pub fn sign_settings(&self) -> SignSettings {
         SignSettings {
             behavior: self.data.signing_behavior,
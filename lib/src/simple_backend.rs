@@ -14,15 +14,22 @@
 
 #![expect(missing_docs)]
 
+use std::collections::BTreeMap;
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::fs;
 use std::fs::File;
+use std::io;
 use std::io::Cursor;
-use std::io::Read as _;
 use std::io::Write as _;
 use std::path::Path;
 use std::path::PathBuf;
 use std::pin::Pin;
+use std::process::Command;
+use std::process::ExitStatus;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::Mutex;
 use std::time::SystemTime;
 
 use async_trait::async_trait;
@@ -30,14 +37,18 @@ use blake2::Blake2b512;
 use blake2::Digest as _;
 use futures::stream;
 use futures::stream::BoxStream;
+use itertools::Itertools as _;
 use pollster::FutureExt as _;
 use prost::Message as _;
 use tempfile::NamedTempFile;
+use thiserror::Error;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt as _;
 
 use crate::backend::Backend;
 use crate::backend::BackendError;
+use crate::backend::BackendInitError;
+use crate::backend::BackendLoadError;
 use crate::backend::BackendResult;
 use crate::backend::ChangeId;
 use crate::backend::Commit;
@@ -56,18 +67,36 @@ use crate::backend::Tree;
 use crate::backend::TreeId;
 use crate::backend::TreeValue;
 use crate::backend::make_root_commit;
+use crate::config::ConfigGetError;
+use crate::config::ConfigGetResultExt as _;
 use crate::conflict_labels::ConflictLabels;
+use crate::content_hash::ContentHash;
 use crate::content_hash::blake2b_hash;
+use crate::file_util::BlockingAsyncReader;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
 use crate::file_util::persist_content_addressed_temp_file;
+use crate::hex_util;
 use crate::index::Index;
 use crate::merge::MergeBuilder;
 use crate::object_id::ObjectId;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathComponentBuf;
-
-const COMMIT_ID_LENGTH: usize = 64;
-const CHANGE_ID_LENGTH: usize = 16;
+use crate::settings::UserSettings;
+use crate::simple_encryption::ObjectCipher;
+use crate::simple_pack::MAX_WRITTEN_DELTA_CHAIN_LENGTH;
+use crate::simple_pack::ObjectKind;
+use crate::simple_pack::PackSet;
+use crate::simple_pack::PackWriter;
+
+pub(crate) const COMMIT_ID_LENGTH: usize = 64;
+pub(crate) const CHANGE_ID_LENGTH: usize = 16;
+pub(crate) const EMPTY_TREE_ID_HEX: &str = "482ae5a29fbe856c7272f2071b8b0f0359ee2d89ff392b8a900643fbd0836eccd067b8bf41909e206c90d45d6e7d8b6686b93ecaee5fe1a9060d87b672101310";
+/// File in the store directory which marks the repo as encrypted. It contains
+/// the salt for deriving the keys and the key check value, as hex lines.
+const ENCRYPTION_FILE: &str = "encryption";
+const ENCRYPTION_KEY_COMMAND_CONFIG: &str = "simple-backend.encryption-key-command";
 
 fn map_not_found_err(err: std::io::Error, id: &impl ObjectId) -> BackendError {
     if err.kind() == std::io::ErrorKind::NotFound {
@@ -85,16 +114,148 @@ fn map_not_found_err(err: std::io::Error, id: &impl ObjectId) -> BackendError {
     }
 }
 
+fn map_read_err(err: io::Error, id: &impl ObjectId) -> BackendError {
+    BackendError::ReadObject {
+        object_type: id.object_type(),
+        hash: id.hex(),
+        source: Box::new(err),
+    }
+}
+
 fn to_other_err(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> BackendError {
     BackendError::Other(err.into())
 }
 
+/// Error setting up the encryption of a [`SimpleBackend`].
+#[derive(Debug, Error)]
+pub enum SimpleBackendEncryptionError {
+    #[error("`{ENCRYPTION_KEY_COMMAND_CONFIG}` must be set to use an encrypted repo")]
+    KeyCommandNotSet,
+    #[error(transparent)]
+    Config(#[from] ConfigGetError),
+    #[error("Failed to run the encryption key command")]
+    RunKeyCommand(#[source] io::Error),
+    #[error("The encryption key command failed with {0}")]
+    KeyCommandFailed(ExitStatus),
+    #[error("The encryption key command printed an empty key")]
+    EmptyKey,
+    #[error("The encryption key doesn't match the key the repo was encrypted with")]
+    WrongKey,
+    #[error("Failed to derive the encryption key: {0}")]
+    KeyDerivation(String),
+    #[error("Invalid encryption file")]
+    InvalidEncryptionFile,
+    #[error(transparent)]
+    Path(#[from] PathError),
+}
+
+impl From<SimpleBackendEncryptionError> for BackendInitError {
+    fn from(err: SimpleBackendEncryptionError) -> Self {
+        Self(err.into())
+    }
+}
+
+impl From<SimpleBackendEncryptionError> for BackendLoadError {
+    fn from(err: SimpleBackendEncryptionError) -> Self {
+        Self(err.into())
+    }
+}
+
+/// Runs the configured key command and derives the cipher from its output
+/// and the `salt`.
+fn cipher_from_settings(
+    settings: &UserSettings,
+    salt: &[u8],
+) -> Result<ObjectCipher, SimpleBackendEncryptionError> {
+    let command: Vec<String> = settings
+        .get(ENCRYPTION_KEY_COMMAND_CONFIG)
+        .optional()?
+        .unwrap_or_default();
+    let Some((program, args)) = command.split_first() else {
+        return Err(SimpleBackendEncryptionError::KeyCommandNotSet);
+    };
+    let mut cmd = Command::new(program);
+    cmd.args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit());
+    tracing::info!(?cmd, "running encryption key command");
+    let output = cmd
+        .output()
+        .map_err(SimpleBackendEncryptionError::RunKeyCommand)?;
+    if !output.status.success() {
+        return Err(SimpleBackendEncryptionError::KeyCommandFailed(
+            output.status,
+        ));
+    }
+    let secret = output.stdout.strip_suffix(b"\n").unwrap_or(&output.stdout);
+    let secret = secret.strip_suffix(b"\r").unwrap_or(secret);
+    if secret.is_empty() {
+        return Err(SimpleBackendEncryptionError::EmptyKey);
+    }
+    ObjectCipher::from_secret(secret, salt)
+        .map_err(|err| SimpleBackendEncryptionError::KeyDerivation(err.to_string()))
+}
+
+fn remove_file_if_exists(path: &Path) -> BackendResult<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => {
+            Err(err).context(path).map_err(to_other_err)
+        }
+        _ => Ok(()),
+    }
+}
+
 #[derive(Debug)]
 pub struct SimpleBackend {
     path: PathBuf,
     root_commit_id: CommitId,
     root_change_id: ChangeId,
     empty_tree_id: TreeId,
+    packs: Mutex<Option<Arc<PackSet>>>,
+    cipher: Option<ObjectCipher>,
+}
+
+/// Objects reachable from the index heads, as found by
+/// [`SimpleBackend::find_reachable_objects()`].
+#[derive(Debug, Default)]
+struct ReachableObjects {
+    commits: Vec<CommitId>,
+    trees: Vec<TreeId>,
+    /// Files with the path they were first found at, newer versions first.
+    files: Vec<(RepoPathBuf, FileId)>,
+    symlinks: Vec<SymlinkId>,
+}
+
+impl ReachableObjects {
+    fn ids(&self) -> HashSet<(ObjectKind, &[u8])> {
+        let commits = self
+            .commits
+            .iter()
+            .map(|id| (ObjectKind::Commit, id.as_bytes()));
+        let trees = self
+            .trees
+            .iter()
+            .map(|id| (ObjectKind::Tree, id.as_bytes()));
+        let files = self
+            .files
+            .iter()
+            .map(|(_, id)| (ObjectKind::File, id.as_bytes()));
+        let symlinks = self
+            .symlinks
+            .iter()
+            .map(|id| (ObjectKind::Symlink, id.as_bytes()));
+        commits.chain(trees).chain(files).chain(symlinks).collect()
+    }
+}
+
+/// Result of [`SimpleBackend::compact()`].
+#[derive(Clone, Debug, Default)]
+pub struct CompactStats {
+    /// Number of objects written to the new pack.
+    pub num_objects: usize,
+    /// Number of objects stored as deltas against other objects.
+    pub num_deltas: usize,
 }
 
 impl SimpleBackend {
@@ -103,12 +264,38 @@ impl SimpleBackend {
     }
 
     pub fn init(store_path: &Path) -> Self {
+        Self::init_with_cipher(store_path, None)
+    }
+
+    /// Initializes a backend which encrypts all objects it stores. The key is
+    /// read from the output of the `simple-backend.encryption-key-command`,
+    /// which has to be configured whenever the repo is loaded.
+    pub fn init_encrypted(
+        settings: &UserSettings,
+        store_path: &Path,
+    ) -> Result<Self, SimpleBackendEncryptionError> {
+        let salt = ObjectCipher::generate_salt();
+        let cipher = cipher_from_settings(settings, &salt)?;
+        let encryption_path = store_path.join(ENCRYPTION_FILE);
+        fs::write(
+            &encryption_path,
+            format!(
+                "{}\n{}\n",
+                hex_util::encode_hex(&salt),
+                hex_util::encode_hex(&cipher.key_check_value())
+            ),
+        )
+        .context(&encryption_path)?;
+        Ok(Self::init_with_cipher(store_path, Some(cipher)))
+    }
+
+    fn init_with_cipher(store_path: &Path, cipher: Option<ObjectCipher>) -> Self {
         fs::create_dir(store_path.join("commits")).unwrap();
         fs::create_dir(store_path.join("trees")).unwrap();
         fs::create_dir(store_path.join("files")).unwrap();
         fs::create_dir(store_path.join("symlinks")).unwrap();
         fs::create_dir(store_path.join("conflicts")).unwrap();
-        let backend = Self::load(store_path);
+        let backend = Self::new(store_path, cipher);
         let empty_tree_id = backend
             .write_tree(RepoPath::root(), &Tree::default())
             .block_on()
@@ -117,17 +304,73 @@ impl SimpleBackend {
         backend
     }
 
-    pub fn load(store_path: &Path) -> Self {
+    /// Loads the backend. If the repo is encrypted, the key is obtained by
+    /// running the `simple-backend.encryption-key-command`.
+    pub fn load(
+        settings: &UserSettings,
+        store_path: &Path,
+    ) -> Result<Self, SimpleBackendEncryptionError> {
+        let encryption_path = store_path.join(ENCRYPTION_FILE);
+        let cipher = match fs::read_to_string(&encryption_path) {
+            Ok(content) => {
+                let Some((salt, key_check_value)) = content
+                    .lines()
+                    .collect_tuple::<(_, _)>()
+                    .and_then(|(salt, check)| Some((hex_util::decode_hex(salt)?, check)))
+                else {
+                    return Err(SimpleBackendEncryptionError::InvalidEncryptionFile);
+                };
+                let cipher = cipher_from_settings(settings, &salt)?;
+                if hex_util::encode_hex(&cipher.key_check_value()) != key_check_value {
+                    return Err(SimpleBackendEncryptionError::WrongKey);
+                }
+                Some(cipher)
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err).context(&encryption_path)?,
+        };
+        Ok(Self::new(store_path, cipher))
+    }
+
+    fn new(store_path: &Path, cipher: Option<ObjectCipher>) -> Self {
         let root_commit_id = CommitId::from_bytes(&[0; COMMIT_ID_LENGTH]);
         let root_change_id = ChangeId::from_bytes(&[0; CHANGE_ID_LENGTH]);
-        let empty_tree_id = TreeId::from_hex(
-            "482ae5a29fbe856c7272f2071b8b0f0359ee2d89ff392b8a900643fbd0836eccd067b8bf41909e206c90d45d6e7d8b6686b93ecaee5fe1a9060d87b672101310",
-        );
+        let empty_tree_id = match &cipher {
+            Some(cipher) => TreeId::new(keyed_content_hash(cipher, &Tree::default())),
+            None => TreeId::from_hex(EMPTY_TREE_ID_HEX),
+        };
         Self {
             path: store_path.to_path_buf(),
             root_commit_id,
             root_change_id,
             empty_tree_id,
+            packs: Mutex::new(None),
+            cipher,
+        }
+    }
+
+    /// Returns true if the objects are encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// Computes the id of a file or symlink from its raw contents.
+    fn file_content_hash(&self, data: &[u8]) -> Vec<u8> {
+        match &self.cipher {
+            Some(cipher) => {
+                let mut hasher = cipher.id_hasher();
+                digest::Update::update(&mut hasher, data);
+                digest::Mac::finalize(hasher).into_bytes().to_vec()
+            }
+            None => Blake2b512::digest(data).to_vec(),
+        }
+    }
+
+    /// Computes the id of an object. Ids are keyed hashes in encrypted repos.
+    fn content_hash(&self, x: &impl ContentHash) -> Vec<u8> {
+        match &self.cipher {
+            Some(cipher) => keyed_content_hash(cipher, x),
+            None => blake2b_hash(x).to_vec(),
         }
     }
 
@@ -146,6 +389,347 @@ impl SimpleBackend {
     fn commit_path(&self, id: &CommitId) -> PathBuf {
         self.path.join("commits").join(id.hex())
     }
+
+    fn packs_dir(&self) -> PathBuf {
+        self.path.join("packs")
+    }
+
+    fn load_packs(&self, reload: bool) -> BackendResult<Arc<PackSet>> {
+        let mut packs = self.packs.lock().unwrap();
+        if reload || packs.is_none() {
+            let pack_set =
+                PackSet::load(&self.packs_dir(), self.cipher.clone()).map_err(to_other_err)?;
+            *packs = Some(Arc::new(pack_set));
+        }
+        Ok(packs.clone().unwrap())
+    }
+
+    /// Reads an object which isn't stored as a loose file from the packs.
+    fn read_packed_object(
+        &self,
+        kind: ObjectKind,
+        id: &impl ObjectId,
+    ) -> BackendResult<Option<Vec<u8>>> {
+        let packs = self.load_packs(false)?;
+        match self.read_from_packs(&packs, kind, id.as_bytes()) {
+            Ok(Some(data)) => return Ok(Some(data)),
+            Ok(None) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(map_read_err(err, id)),
+        }
+        // The object might have been packed, or the packs rewritten, by
+        // another process since the packs were loaded.
+        let packs = self.load_packs(true)?;
+        self.read_from_packs(&packs, kind, id.as_bytes())
+            .map_err(|err| map_read_err(err, id))
+    }
+
+    /// Reads an object from the `packs`, and checks that its contents match
+    /// its id. This catches corrupt packs, and deltas which were applied to
+    /// the wrong base.
+    fn read_from_packs(
+        &self,
+        packs: &PackSet,
+        kind: ObjectKind,
+        id: &[u8],
+    ) -> io::Result<Option<Vec<u8>>> {
+        let Some(data) = packs.read(kind, id)? else {
+            return Ok(None);
+        };
+        let actual_id = match kind {
+            ObjectKind::Commit => {
+                let proto = crate::protos::simple_store::Commit::decode(&*data)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                self.content_hash(&commit_from_proto(proto))
+            }
+            ObjectKind::Tree => {
+                let proto = crate::protos::simple_store::Tree::decode(&*data)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                self.content_hash(&tree_from_proto(proto))
+            }
+            ObjectKind::File | ObjectKind::Symlink => self.file_content_hash(&data),
+        };
+        if actual_id != id {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Packed object doesn't match its id",
+            ));
+        }
+        Ok(Some(data))
+    }
+
+    /// Encrypts the data of an object to be written as a loose file, if the
+    /// repo is encrypted.
+    fn encrypt_object(&self, kind: ObjectKind, id: &[u8], data: Vec<u8>) -> Vec<u8> {
+        match &self.cipher {
+            Some(cipher) => cipher.encrypt(&kind.associated_data(id), &data),
+            None => data,
+        }
+    }
+
+    fn decrypt_object(
+        &self,
+        kind: ObjectKind,
+        data: Vec<u8>,
+        id: &impl ObjectId,
+    ) -> BackendResult<Vec<u8>> {
+        match &self.cipher {
+            Some(cipher) => cipher
+                .decrypt(&kind.associated_data(id.as_bytes()), &data)
+                .map_err(|err| BackendError::ReadObject {
+                    object_type: id.object_type(),
+                    hash: id.hex(),
+                    source: Box::new(err),
+                }),
+            None => Ok(data),
+        }
+    }
+
+    /// Reads an object from its loose file, or from the packs.
+    fn read_object(
+        &self,
+        kind: ObjectKind,
+        disk_path: &Path,
+        id: &impl ObjectId,
+    ) -> BackendResult<Vec<u8>> {
+        match fs::read(disk_path) {
+            Ok(data) => self.decrypt_object(kind, data, id),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => self
+                .read_packed_object(kind, id)?
+                .ok_or_else(|| map_not_found_err(err, id)),
+            Err(err) => Err(map_read_err(err, id)),
+        }
+    }
+
+    /// Finds the objects reachable from the heads of the `index`.
+    fn find_reachable_objects(&self, index: &dyn Index) -> BackendResult<ReachableObjects> {
+        let mut reachable = ReachableObjects::default();
+        let mut visited_commits = HashSet::new();
+        let mut visited_trees = HashSet::new();
+        let mut visited_files = HashSet::new();
+        let mut visited_symlinks = HashSet::new();
+
+        let mut commit_ids: Vec<CommitId> =
+            index.all_heads_for_gc().map_err(to_other_err)?.collect();
+        let mut tree_ids: Vec<(RepoPathBuf, TreeId)> = vec![];
+        while let Some(id) = commit_ids.pop() {
+            if id == self.root_commit_id || !visited_commits.insert(id.clone()) {
+                continue;
+            }
+            let commit = self.read_commit(&id).block_on()?;
+            commit_ids.extend(commit.parents);
+            tree_ids.extend(
+                commit
+                    .root_tree
+                    .into_iter()
+                    .map(|id| (RepoPathBuf::root(), id)),
+            );
+            reachable.commits.push(id);
+            // Walk the trees of each commit before moving to its parents, so
+            // that newer versions of a file are found first.
+            while let Some((dir, id)) = tree_ids.pop() {
+                if !visited_trees.insert(id.clone()) {
+                    continue;
+                }
+                let tree = self.read_tree(&dir, &id).block_on()?;
+                for entry in tree.entries() {
+                    let path = dir.join(entry.name());
+                    match entry.value() {
+                        TreeValue::File { id, .. } => {
+                            if visited_files.insert(id.clone()) {
+                                reachable.files.push((path, id.clone()));
+                            }
+                        }
+                        TreeValue::Symlink(id) => {
+                            if visited_symlinks.insert(id.clone()) {
+                                reachable.symlinks.push(id.clone());
+                            }
+                        }
+                        TreeValue::Tree(id) => tree_ids.push((path, id.clone())),
+                        TreeValue::GitSubmodule(_) => {}
+                    }
+                }
+                reachable.trees.push(id);
+            }
+        }
+        if visited_trees.insert(self.empty_tree_id.clone()) {
+            reachable.trees.push(self.empty_tree_id.clone());
+        }
+        tracing::info!(
+            commits = reachable.commits.len(),
+            trees = reachable.trees.len(),
+            files = reachable.files.len(),
+            symlinks = reachable.symlinks.len(),
+            "reachable objects"
+        );
+        Ok(reachable)
+    }
+
+    /// Packs the objects reachable from the `index` into a single pack file.
+    ///
+    /// Versions of a file found at the same path are stored as deltas against
+    /// each other where that saves space. The previous packs and the loose
+    /// files of the packed objects are removed afterwards. Unreachable objects
+    /// of the previous packs are dropped unless the pack was modified after
+    /// `keep_newer`, in which case they're written out as loose files, which
+    /// keep the pack's modification time. Unreachable loose objects are left
+    /// for [`Backend::gc()`] to remove.
+    pub fn compact(
+        &self,
+        index: &dyn Index,
+        keep_newer: SystemTime,
+    ) -> BackendResult<CompactStats> {
+        let reachable = self.find_reachable_objects(index)?;
+        self.repack(&reachable, keep_newer)
+    }
+
+    fn repack(
+        &self,
+        reachable: &ReachableObjects,
+        keep_newer: SystemTime,
+    ) -> BackendResult<CompactStats> {
+        let old_packs = self.load_packs(true)?;
+        let mut writer =
+            PackWriter::new(&self.packs_dir(), self.cipher.clone()).map_err(to_other_err)?;
+        let mut stats = CompactStats::default();
+        let mut add = |kind: ObjectKind,
+                       id: &dyn ObjectId,
+                       base: Option<(&[u8], &[u8])>,
+                       contents: &[u8]|
+         -> BackendResult<bool> {
+            let is_delta = writer
+                .add(kind, id.as_bytes(), base, contents)
+                .map_err(to_other_err)?;
+            stats.num_objects += 1;
+            stats.num_deltas += usize::from(is_delta);
+            Ok(is_delta)
+        };
+
+        for id in &reachable.commits {
+            let data = self.read_object(ObjectKind::Commit, &self.commit_path(id), id)?;
+            add(ObjectKind::Commit, id, None, &data)?;
+        }
+        for id in &reachable.trees {
+            let data = self.read_object(ObjectKind::Tree, &self.tree_path(id), id)?;
+            add(ObjectKind::Tree, id, None, &data)?;
+        }
+        for id in &reachable.symlinks {
+            let data = self.read_object(ObjectKind::Symlink, &self.symlink_path(id), id)?;
+            add(ObjectKind::Symlink, id, None, &data)?;
+        }
+        let mut file_versions: BTreeMap<&RepoPath, Vec<&FileId>> = BTreeMap::new();
+        for (path, id) in &reachable.files {
+            file_versions.entry(path.as_ref()).or_default().push(id);
+        }
+        for ids in file_versions.values() {
+            let mut base: Option<(&FileId, Vec<u8>)> = None;
+            let mut chain_length = 0;
+            for &id in ids {
+                let data = self.read_object(ObjectKind::File, &self.file_path(id), id)?;
+                let base_ref = base
+                    .as_ref()
+                    .filter(|_| chain_length < MAX_WRITTEN_DELTA_CHAIN_LENGTH)
+                    .map(|(base_id, base_data)| (base_id.as_bytes(), base_data.as_slice()));
+                if add(ObjectKind::File, id, base_ref, &data)? {
+                    chain_length += 1;
+                } else {
+                    chain_length = 0;
+                }
+                base = Some((id, data));
+            }
+        }
+        let new_pack_path = writer.finish().map_err(to_other_err)?;
+        self.load_packs(true)?;
+
+        let reachable_ids = reachable.ids();
+        let recent_packs = recent_pack_paths(&old_packs, keep_newer)?;
+        for (kind, id, pack_path) in old_packs.objects() {
+            if reachable_ids.contains(&(kind, id)) || !recent_packs.contains(pack_path) {
+                continue;
+            }
+            let path = self
+                .path
+                .join(object_dir(kind))
+                .join(hex_util::encode_hex(id));
+            if path.exists() {
+                continue;
+            }
+            let data = self
+                .read_from_packs(&old_packs, kind, id)
+                .context(pack_path)
+                .map_err(to_other_err)?
+                .expect("packed object should exist");
+            let mtime = fs::metadata(pack_path)
+                .and_then(|metadata| metadata.modified())
+                .context(pack_path)
+                .map_err(to_other_err)?;
+            let temp_file = NamedTempFile::new_in(&self.path).map_err(to_other_err)?;
+            temp_file
+                .as_file()
+                .write_all(&self.encrypt_object(kind, id, data))
+                .and_then(|()| temp_file.as_file().set_modified(mtime))
+                .map_err(to_other_err)?;
+            persist_content_addressed_temp_file(temp_file, &path).map_err(to_other_err)?;
+        }
+
+        for path in old_packs.paths() {
+            if *path != new_pack_path {
+                remove_file_if_exists(path)?;
+            }
+        }
+        for id in &reachable.commits {
+            remove_file_if_exists(&self.commit_path(id))?;
+        }
+        for id in &reachable.trees {
+            remove_file_if_exists(&self.tree_path(id))?;
+        }
+        for id in &reachable.symlinks {
+            remove_file_if_exists(&self.symlink_path(id))?;
+        }
+        for (_, id) in &reachable.files {
+            remove_file_if_exists(&self.file_path(id))?;
+        }
+        Ok(stats)
+    }
+
+    /// Removes the files in `dir` whose names aren't in `reachable`, unless
+    /// they were modified after `keep_newer`.
+    fn remove_unreachable_objects(
+        &self,
+        dir: &str,
+        reachable: &HashSet<String>,
+        keep_newer: SystemTime,
+    ) -> BackendResult<()> {
+        let dir_path = self.path.join(dir);
+        for entry in dir_path
+            .read_dir()
+            .context(&dir_path)
+            .map_err(to_other_err)?
+        {
+            let entry = entry.context(&dir_path).map_err(to_other_err)?;
+            let path = entry.path();
+            if entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| reachable.contains(name))
+            {
+                continue;
+            }
+            // Check timestamp, but there's still TOCTOU problem if an existing
+            // file is renewed.
+            let metadata = entry.metadata().context(&path).map_err(to_other_err)?;
+            let mtime = metadata.modified().expect("unsupported platform?");
+            if mtime > keep_newer {
+                tracing::trace!(?path, "not removing");
+            } else {
+                tracing::trace!(?path, "removing");
+                fs::remove_file(&path)
+                    .context(&path)
+                    .map_err(to_other_err)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -180,19 +764,26 @@ impl Backend for SimpleBackend {
 
     async fn read_file(
         &self,
-        path: &RepoPath,
+        _path: &RepoPath,
         id: &FileId,
     ) -> BackendResult<Pin<Box<dyn AsyncRead + Send>>> {
         let disk_path = self.file_path(id);
-        let mut file = File::open(disk_path).map_err(|err| map_not_found_err(err, id))?;
-        let mut buf = vec![];
-        file.read_to_end(&mut buf)
-            .map_err(|err| BackendError::ReadFile {
-                path: path.to_owned(),
-                id: id.clone(),
-                source: err.into(),
-            })?;
-        Ok(Box::pin(Cursor::new(buf)))
+        if self.cipher.is_some() {
+            // The whole object has to be authenticated before any of it can
+            // be returned.
+            let data = self.read_object(ObjectKind::File, &disk_path, id)?;
+            return Ok(Box::pin(Cursor::new(data)));
+        }
+        match File::open(disk_path) {
+            Ok(file) => Ok(Box::pin(BlockingAsyncReader::new(file))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                let data = self
+                    .read_packed_object(ObjectKind::File, id)?
+                    .ok_or_else(|| map_not_found_err(err, id))?;
+                Ok(Box::pin(Cursor::new(data)))
+            }
+            Err(err) => Err(map_read_err(err, id)),
+        }
     }
 
     async fn write_file(
@@ -203,6 +794,22 @@ impl Backend for SimpleBackend {
         // TODO: Write temporary file in the destination directory (#5712)
         let temp_file = NamedTempFile::new_in(&self.path).map_err(to_other_err)?;
         let mut file = temp_file.as_file();
+        if let Some(cipher) = &self.cipher {
+            // The whole object is authenticated at once.
+            let mut data = vec![];
+            contents
+                .read_to_end(&mut data)
+                .await
+                .map_err(to_other_err)?;
+            let id = FileId::new(self.file_content_hash(&data));
+            let associated_data = ObjectKind::File.associated_data(id.as_bytes());
+            file.write_all(&cipher.encrypt(&associated_data, &data))
+                .map_err(to_other_err)?;
+            file.flush().map_err(to_other_err)?;
+            persist_content_addressed_temp_file(temp_file, self.file_path(&id))
+                .map_err(to_other_err)?;
+            return Ok(id);
+        }
         let mut hasher = Blake2b512::new();
         let mut buff: Vec<u8> = vec![0; 1 << 14];
         loop {
@@ -211,8 +818,8 @@ impl Backend for SimpleBackend {
                 break;
             }
             let bytes = &buff[..bytes_read];
-            file.write_all(bytes).map_err(to_other_err)?;
             hasher.update(bytes);
+            file.write_all(bytes).map_err(to_other_err)?;
         }
         file.flush().map_err(to_other_err)?;
         let id = FileId::new(hasher.finalize().to_vec());
@@ -223,20 +830,25 @@ impl Backend for SimpleBackend {
     }
 
     async fn read_symlink(&self, _path: &RepoPath, id: &SymlinkId) -> BackendResult<String> {
-        let path = self.symlink_path(id);
-        let target = fs::read_to_string(path).map_err(|err| map_not_found_err(err, id))?;
-        Ok(target)
+        let data = self.read_object(ObjectKind::Symlink, &self.symlink_path(id), id)?;
+        String::from_utf8(data).map_err(|err| BackendError::InvalidUtf8 {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: err.utf8_error(),
+        })
     }
 
     async fn write_symlink(&self, _path: &RepoPath, target: &str) -> BackendResult<SymlinkId> {
         // TODO: Write temporary file in the destination directory (#5712)
         let mut temp_file = NamedTempFile::new_in(&self.path).map_err(to_other_err)?;
+        let id = SymlinkId::new(self.file_content_hash(target.as_bytes()));
         temp_file
-            .write_all(target.as_bytes())
+            .write_all(&self.encrypt_object(
+                ObjectKind::Symlink,
+                id.as_bytes(),
+                target.as_bytes().to_vec(),
+            ))
             .map_err(to_other_err)?;
-        let mut hasher = Blake2b512::new();
-        hasher.update(target.as_bytes());
-        let id = SymlinkId::new(hasher.finalize().to_vec());
 
         persist_content_addressed_temp_file(temp_file, self.symlink_path(&id))
             .map_err(to_other_err)?;
@@ -262,8 +874,7 @@ impl Backend for SimpleBackend {
     }
 
     async fn read_tree(&self, _path: &RepoPath, id: &TreeId) -> BackendResult<Tree> {
-        let path = self.tree_path(id);
-        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
+        let buf = self.read_object(ObjectKind::Tree, &self.tree_path(id), id)?;
 
         let proto = crate::protos::simple_store::Tree::decode(&*buf).map_err(to_other_err)?;
         Ok(tree_from_proto(proto))
@@ -273,19 +884,26 @@ impl Backend for SimpleBackend {
         // TODO: Write temporary file in the destination directory (#5712)
         let temp_file = NamedTempFile::new_in(&self.path).map_err(to_other_err)?;
 
+        let id = TreeId::new(self.content_hash(tree));
         let proto = tree_to_proto(tree);
         temp_file
             .as_file()
-            .write_all(&proto.encode_to_vec())
+            .write_all(&self.encrypt_object(ObjectKind::Tree, id.as_bytes(), proto.encode_to_vec()))
             .map_err(to_other_err)?;
 
-        let id = TreeId::new(blake2b_hash(tree).to_vec());
-
         persist_content_addressed_temp_file(temp_file, self.tree_path(&id))
             .map_err(to_other_err)?;
         Ok(id)
     }
 
+    fn compute_tree_id(&self, _path: &RepoPath, tree: &Tree) -> Option<TreeId> {
+        Some(TreeId::new(self.content_hash(tree)))
+    }
+
+    fn compute_file_id(&self, _path: &RepoPath, contents: &[u8]) -> Option<FileId> {
+        Some(FileId::new(self.file_content_hash(contents)))
+    }
+
     async fn read_commit(&self, id: &CommitId) -> BackendResult<Commit> {
         if *id == self.root_commit_id {
             return Ok(make_root_commit(
@@ -294,8 +912,7 @@ impl Backend for SimpleBackend {
             ));
         }
 
-        let path = self.commit_path(id);
-        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
+        let buf = self.read_object(ObjectKind::Commit, &self.commit_path(id), id)?;
 
         let proto = crate::protos::simple_store::Commit::decode(&*buf).map_err(to_other_err)?;
         Ok(commit_from_proto(proto))
@@ -324,13 +941,16 @@ impl Backend for SimpleBackend {
             commit.secure_sig = Some(SecureSig { data, sig });
         }
 
+        let id = CommitId::new(self.content_hash(&commit));
         temp_file
             .as_file()
-            .write_all(&proto.encode_to_vec())
+            .write_all(&self.encrypt_object(
+                ObjectKind::Commit,
+                id.as_bytes(),
+                proto.encode_to_vec(),
+            ))
             .map_err(to_other_err)?;
 
-        let id = CommitId::new(blake2b_hash(&commit).to_vec());
-
         persist_content_addressed_temp_file(temp_file, self.commit_path(&id))
             .map_err(to_other_err)?;
         Ok((id, commit))
@@ -345,11 +965,59 @@ impl Backend for SimpleBackend {
         Ok(Box::pin(stream::empty()))
     }
 
-    fn gc(&self, _index: &dyn Index, _keep_newer: SystemTime) -> BackendResult<()> {
+    fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+        let reachable = self.find_reachable_objects(index)?;
+        // Packed objects are removed by rewriting the packs, which is only
+        // worth it if some of them have expired.
+        let packs = self.load_packs(true)?;
+        let reachable_ids = reachable.ids();
+        let recent_packs = recent_pack_paths(&packs, keep_newer)?;
+        let has_expired_objects = packs.objects().any(|(kind, id, pack_path)| {
+            !reachable_ids.contains(&(kind, id)) && !recent_packs.contains(pack_path)
+        });
+        if has_expired_objects {
+            self.repack(&reachable, keep_newer)?;
+        }
+        let reachable_commits = reachable.commits.iter().map(|id| id.hex()).collect();
+        let reachable_trees = reachable.trees.iter().map(|id| id.hex()).collect();
+        let reachable_files = reachable.files.iter().map(|(_, id)| id.hex()).collect();
+        let reachable_symlinks = reachable.symlinks.iter().map(|id| id.hex()).collect();
+        self.remove_unreachable_objects("commits", &reachable_commits, keep_newer)?;
+        self.remove_unreachable_objects("trees", &reachable_trees, keep_newer)?;
+        self.remove_unreachable_objects("files", &reachable_files, keep_newer)?;
+        self.remove_unreachable_objects("symlinks", &reachable_symlinks, keep_newer)?;
         Ok(())
     }
 }
 
+fn keyed_content_hash(cipher: &ObjectCipher, x: &impl ContentHash) -> Vec<u8> {
+    let mut hasher = cipher.id_hasher();
+    x.hash(&mut hasher);
+    digest::Mac::finalize(hasher).into_bytes().to_vec()
+}
+
+fn object_dir(kind: ObjectKind) -> &'static str {
+    match kind {
+        ObjectKind::Commit => "commits",
+        ObjectKind::Tree => "trees",
+        ObjectKind::File => "files",
+        ObjectKind::Symlink => "symlinks",
+    }
+}
+
+/// Returns the paths to the packs which were modified after `keep_newer`.
+fn recent_pack_paths(packs: &PackSet, keep_newer: SystemTime) -> BackendResult<HashSet<&Path>> {
+    let mut paths = HashSet::new();
+    for path in packs.paths() {
+        let metadata = fs::metadata(path).context(path).map_err(to_other_err)?;
+        let mtime = metadata.modified().expect("unsupported platform?");
+        if mtime > keep_newer {
+            paths.insert(path.as_path());
+        }
+    }
+    Ok(paths)
+}
+
 #[expect(clippy::assigning_clones)]
 pub fn commit_to_proto(commit: &Commit) -> crate::protos::simple_store::Commit {
     let mut proto = crate::protos::simple_store::Commit::default();
@@ -370,7 +1038,7 @@ pub fn commit_to_proto(commit: &Commit) -> crate::protos::simple_store::Commit {
     proto
 }
 
-fn commit_from_proto(mut proto: crate::protos::simple_store::Commit) -> Commit {
+pub(crate) fn commit_from_proto(mut proto: crate::protos::simple_store::Commit) -> Commit {
     // Note how .take() sets the secure_sig field to None before we encode the data.
     // Needs to be done first since proto is partially moved a bunch below
     let secure_sig = proto.secure_sig.take().map(|sig| SecureSig {
@@ -397,7 +1065,7 @@ fn commit_from_proto(mut proto: crate::protos::simple_store::Commit) -> Commit {
     }
 }
 
-fn tree_to_proto(tree: &Tree) -> crate::protos::simple_store::Tree {
+pub(crate) fn tree_to_proto(tree: &Tree) -> crate::protos::simple_store::Tree {
     let mut proto = crate::protos::simple_store::Tree::default();
     for entry in tree.entries() {
         proto
@@ -410,7 +1078,7 @@ fn tree_to_proto(tree: &Tree) -> crate::protos::simple_store::Tree {
     proto
 }
 
-fn tree_from_proto(proto: crate::protos::simple_store::Tree) -> Tree {
+pub(crate) fn tree_from_proto(proto: crate::protos::simple_store::Tree) -> Tree {
     // Serialized data should be sorted
     let entries = proto
         .entries
@@ -0,0 +1,197 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encryption of the objects stored by the simple backend.
+//!
+//! Objects are encrypted with XChaCha20-Poly1305 under a random nonce. An
+//! encrypted object consists of the nonce, the ciphertext, and the tag. The
+//! kind and id of the object are authenticated as associated data, so an
+//! object can't be passed off as another one by renaming its file.
+//!
+//! The keys are derived from a secret supplied by the user, stretched with
+//! Argon2id using a random salt stored in the repo. Object ids are keyed
+//! BLAKE2b hashes, so that the ids don't reveal whether the store contains
+//! some known contents.
+
+use argon2::Argon2;
+use blake2::Blake2bMac512;
+use blake2::digest::Mac;
+use chacha20poly1305::KeyInit as _;
+use chacha20poly1305::XChaCha20Poly1305;
+use chacha20poly1305::XNonce;
+use chacha20poly1305::aead::Aead as _;
+use chacha20poly1305::aead::Payload;
+use rand::RngCore as _;
+use thiserror::Error;
+
+const NONCE_LENGTH: usize = 24;
+const TAG_LENGTH: usize = 16;
+/// Length added to each object by the encryption.
+const OVERHEAD: usize = NONCE_LENGTH + TAG_LENGTH;
+/// Length of the salt for deriving the keys from the secret.
+pub(crate) const SALT_LENGTH: usize = 16;
+
+/// Error decrypting an object.
+#[derive(Debug, Error)]
+#[error("Failed to decrypt object: it's corrupt or was encrypted with another key")]
+pub(crate) struct DecryptError;
+
+/// Error deriving the keys from the secret.
+#[derive(Debug, Error)]
+#[error("{0}")]
+pub(crate) struct KeyDerivationError(argon2::Error);
+
+/// Keys for encrypting and decrypting objects, and for computing their ids.
+#[derive(Clone)]
+pub(crate) struct ObjectCipher {
+    aead: XChaCha20Poly1305,
+    id_key: [u8; 64],
+    check_key: [u8; 64],
+}
+
+impl std::fmt::Debug for ObjectCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ObjectCipher").finish_non_exhaustive()
+    }
+}
+
+impl ObjectCipher {
+    /// Generates a random salt for [`Self::from_secret()`].
+    pub fn generate_salt() -> [u8; SALT_LENGTH] {
+        let mut salt = [0; SALT_LENGTH];
+        rand::rng().fill_bytes(&mut salt);
+        salt
+    }
+
+    /// Derives the keys from the user-supplied `secret` and the repo's `salt`.
+    pub fn from_secret(secret: &[u8], salt: &[u8]) -> Result<Self, KeyDerivationError> {
+        let mut master_key = [0; 64];
+        Argon2::default()
+            .hash_password_into(secret, salt, &mut master_key)
+            .map_err(KeyDerivationError)?;
+        let derive = |purpose: &[u8]| -> [u8; 64] { keyed_hash(&master_key, purpose).into() };
+        let encryption_key = derive(b"encryption");
+        Ok(Self {
+            aead: XChaCha20Poly1305::new_from_slice(&encryption_key[..32]).unwrap(),
+            id_key: derive(b"object id"),
+            check_key: derive(b"key check"),
+        })
+    }
+
+    /// Value which identifies the key without revealing it. It's stored in
+    /// the repo so that a wrong key can be detected up front.
+    pub fn key_check_value(&self) -> Vec<u8> {
+        self.check_key[..16].to_vec()
+    }
+
+    /// Starts computing the id of an object.
+    pub fn id_hasher(&self) -> Blake2bMac512 {
+        Mac::new_from_slice(&self.id_key).unwrap()
+    }
+
+    /// Encrypts a whole object. The object can only be decrypted with the
+    /// same `associated_data`.
+    pub fn encrypt(&self, associated_data: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let mut nonce = XNonce::default();
+        rand::rng().fill_bytes(&mut nonce);
+        let payload = Payload {
+            msg: plaintext,
+            aad: associated_data,
+        };
+        let ciphertext = self
+            .aead
+            .encrypt(&nonce, payload)
+            .expect("object should be small enough to encrypt");
+        let mut data = Vec::with_capacity(plaintext.len() + OVERHEAD);
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&ciphertext);
+        data
+    }
+
+    /// Verifies and decrypts an object which was encrypted with the given
+    /// `associated_data`.
+    pub fn decrypt(&self, associated_data: &[u8], data: &[u8]) -> Result<Vec<u8>, DecryptError> {
+        if data.len() < OVERHEAD {
+            return Err(DecryptError);
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LENGTH);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: associated_data,
+        };
+        self.aead
+            .decrypt(XNonce::from_slice(nonce), payload)
+            .map_err(|_| DecryptError)
+    }
+}
+
+fn keyed_hash(key: &[u8], data: &[u8]) -> blake2::digest::Output<Blake2bMac512> {
+    let mut mac: Blake2bMac512 = Mac::new_from_slice(key).unwrap();
+    mac.update(data);
+    mac.finalize().into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SALT: &[u8; SALT_LENGTH] = b"0123456789abcdef";
+
+    #[test]
+    fn test_roundtrip() {
+        let cipher = ObjectCipher::from_secret(b"secret", SALT).unwrap();
+        let plaintext = b"some contents\n".repeat(20);
+        let encrypted = cipher.encrypt(b"id", &plaintext);
+        assert_eq!(encrypted.len(), plaintext.len() + OVERHEAD);
+        assert!(!encrypted.windows(13).any(|w| w == b"some contents"));
+        assert_eq!(cipher.decrypt(b"id", &encrypted).unwrap(), plaintext);
+
+        // Nonces are random
+        assert_ne!(cipher.encrypt(b"id", &plaintext), encrypted);
+    }
+
+    #[test]
+    fn test_tampering_and_wrong_key() {
+        let cipher = ObjectCipher::from_secret(b"secret", SALT).unwrap();
+        let mut encrypted = cipher.encrypt(b"id", b"contents");
+        let other_cipher = ObjectCipher::from_secret(b"other secret", SALT).unwrap();
+        assert!(other_cipher.decrypt(b"id", &encrypted).is_err());
+        assert_ne!(cipher.key_check_value(), other_cipher.key_check_value());
+
+        // The salt changes the keys
+        let salted_cipher = ObjectCipher::from_secret(b"secret", b"fedcba9876543210").unwrap();
+        assert!(salted_cipher.decrypt(b"id", &encrypted).is_err());
+
+        // The associated data must match
+        assert!(cipher.decrypt(b"other id", &encrypted).is_err());
+        assert_ne!(cipher.key_check_value(), salted_cipher.key_check_value());
+
+        encrypted[NONCE_LENGTH] ^= 1;
+        assert!(cipher.decrypt(b"id", &encrypted).is_err());
+        assert!(cipher.decrypt(b"id", b"short").is_err());
+    }
+
+    #[test]
+    fn test_keyed_ids() {
+        let cipher = ObjectCipher::from_secret(b"secret", SALT).unwrap();
+        let other_cipher = ObjectCipher::from_secret(b"other secret", SALT).unwrap();
+        let id = |cipher: &ObjectCipher| {
+            let mut hasher = cipher.id_hasher();
+            hasher.update(b"contents");
+            hasher.finalize().into_bytes()
+        };
+        assert_eq!(id(&cipher), id(&cipher));
+        assert_ne!(id(&cipher), id(&other_cipher));
+    }
+}
@@ -391,6 +391,33 @@ fn io_to_write_error(err: PathError, object_type: &'static str) -> OpStoreError
     }
 }
 
+/// Serializes `operation` in the format operations are stored in.
+///
+/// Operations are transferred in this format through other repositories, e.g.
+/// Git remotes.
+pub(crate) fn encode_operation(operation: &Operation) -> Vec<u8> {
+    operation_to_proto(operation).encode_to_vec()
+}
+
+/// Deserializes an operation serialized by [`encode_operation()`].
+pub(crate) fn decode_operation(
+    buf: &[u8],
+) -> Result<Operation, Box<dyn std::error::Error + Send + Sync>> {
+    let proto = crate::protos::simple_op_store::Operation::decode(buf)?;
+    Ok(operation_from_proto(proto)?)
+}
+
+/// Serializes `view` in the format views are stored in.
+pub(crate) fn encode_view(view: &View) -> Vec<u8> {
+    view_to_proto(view).encode_to_vec()
+}
+
+/// Deserializes a view serialized by [`encode_view()`].
+pub(crate) fn decode_view(buf: &[u8]) -> Result<View, Box<dyn std::error::Error + Send + Sync>> {
+    let proto = crate::protos::simple_op_store::View::decode(buf)?;
+    Ok(view_from_proto(proto)?)
+}
+
 #[derive(Debug, Error)]
 enum PostDecodeError {
     #[error("Invalid hash length (expected {expected} bytes, got {actual} bytes)")]
@@ -570,6 +597,23 @@ fn view_to_proto(view: &View) -> crate::protos::simple_op_store::View {
 
     let git_head = ref_target_to_proto(&view.git_head);
 
+    let deferred_rewrites = view
+        .deferred_rewrites
+        .iter()
+        .map(
+            |(old_id, new_id)| crate::protos::simple_op_store::DeferredRewrite {
+                old_commit_id: old_id.to_bytes(),
+                new_commit_id: new_id.to_bytes(),
+            },
+        )
+        .collect();
+
+    let ephemeral_workspaces = view
+        .ephemeral_workspaces
+        .iter()
+        .map(|name| name.into())
+        .collect();
+
     #[expect(deprecated)]
     crate::protos::simple_op_store::View {
         head_ids,
@@ -583,6 +627,8 @@ fn view_to_proto(view: &View) -> crate::protos::simple_op_store::View {
         git_head,
         // New/loaded view should have been migrated to the latest format
         has_git_refs_migrated_to_remote_tags: true,
+        deferred_rewrites,
+        ephemeral_workspaces,
     }
 }
 
@@ -669,6 +715,23 @@ fn view_from_proto(proto: crate::protos::simple_op_store::View) -> Result<View,
         RefTarget::absent()
     };
 
+    let deferred_rewrites = proto
+        .deferred_rewrites
+        .into_iter()
+        .map(|rewrite| {
+            (
+                CommitId::new(rewrite.old_commit_id),
+                CommitId::new(rewrite.new_commit_id),
+            )
+        })
+        .collect();
+
+    let ephemeral_workspaces = proto
+        .ephemeral_workspaces
+        .into_iter()
+        .map(WorkspaceNameBuf::from)
+        .collect();
+
     Ok(View {
         head_ids,
         local_bookmarks,
@@ -677,6 +740,8 @@ fn view_from_proto(proto: crate::protos::simple_op_store::View) -> Result<View,
         git_refs,
         git_head,
         wc_commit_ids,
+        deferred_rewrites,
+        ephemeral_workspaces,
     })
 }
 
@@ -937,9 +1002,12 @@ fn remote_ref_state_from_proto(proto_value: i32) -> Result<RemoteRefState, PostD
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+
     use insta::assert_snapshot;
     use itertools::Itertools as _;
     use maplit::btreemap;
+    use maplit::btreeset;
     use maplit::hashmap;
     use maplit::hashset;
 
@@ -1000,6 +1068,8 @@ mod tests {
                 WorkspaceName::DEFAULT.to_owned() => default_wc_commit_id,
                 "test".into() => test_wc_commit_id,
             },
+            deferred_rewrites: BTreeMap::new(),
+            ephemeral_workspaces: BTreeSet::new(),
         }
     }
 
@@ -1054,6 +1124,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_hash_view_with_deferred_rewrites() {
+        let mut view = create_view();
+        view.deferred_rewrites = btreemap! {
+            CommitId::from_hex("abc333") => CommitId::from_hex("abc444"),
+        };
+        // Test exact output so we detect regressions in compatibility
+        assert_snapshot!(
+            ViewId::new(blake2b_hash(&view).to_vec()).hex(),
+            @"e43c54fffe3211e7c317b10ed002ceeb668e209d8d5169c21a093e667493483b9fa250e6b956aec582905559f8204f1a1a36e1791b1bfc1d1c55319693d1c9bc"
+        );
+    }
+
+    #[test]
+    fn test_hash_view_with_ephemeral_workspaces() {
+        let mut view = create_view();
+        view.ephemeral_workspaces = btreeset! {"test".into()};
+        // Test exact output so we detect regressions in compatibility
+        assert_snapshot!(
+            ViewId::new(blake2b_hash(&view).to_vec()).hex(),
+            @"0269e98f7efbab2b45065edbe3d0b78783cf5d21597d1941fd7f80db765a619bcc43b8513e0ba1957bf6a70be270281cdd6d887049423ffb4e3f6851a841ae87"
+        );
+    }
+
     #[test]
     fn test_hash_operation() {
         // Test exact output so we detect regressions in compatibility
@@ -1074,6 +1168,20 @@ mod tests {
         let view_id = store.write_view(&view).block_on().unwrap();
         let read_view = store.read_view(&view_id).block_on().unwrap();
         assert_eq!(read_view, view);
+
+        let mut view = create_view();
+        view.deferred_rewrites = btreemap! {
+            CommitId::from_hex("abc333") => CommitId::from_hex("abc444"),
+        };
+        let view_id = store.write_view(&view).block_on().unwrap();
+        let read_view = store.read_view(&view_id).block_on().unwrap();
+        assert_eq!(read_view, view);
+
+        let mut view = create_view();
+        view.ephemeral_workspaces = btreeset! {"test".into()};
+        let view_id = store.write_view(&view).block_on().unwrap();
+        let read_view = store.read_view(&view_id).block_on().unwrap();
+        assert_eq!(read_view, view);
     }
 
     #[test]
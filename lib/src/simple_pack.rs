@@ -0,0 +1,546 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pack files for the simple backend.
+//!
+//! A pack stores many objects in a single file. An object can be stored as a
+//! delta against another object of the same kind in the same pack, which
+//! saves space for similar versions of a file.
+//!
+//! The file starts with the object data. It is followed by a table of
+//! entries, and a footer consisting of the table's offset (u64 LE) and
+//! [`PACK_MAGIC`]. Each entry of the table consists of:
+//!
+//! * kind: u8
+//! * id length: u8, followed by the id
+//! * base id length: u8 (0 if the object is stored in full), followed by the
+//!   base id
+//! * data offset and length: u64 LE each
+//!
+//! In encrypted repos, the data of each object (or delta) is encrypted
+//! separately, bound to the object's kind and id. The table isn't encrypted
+//! since it only contains ids.
+
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Read as _;
+use std::io::Seek as _;
+use std::io::SeekFrom;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use blake2::Blake2b512;
+use blake2::Digest as _;
+use tempfile::NamedTempFile;
+
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::file_util::persist_content_addressed_temp_file;
+use crate::hex_util;
+use crate::simple_encryption::ObjectCipher;
+
+const PACK_MAGIC: &[u8; 8] = b"jjpack01";
+const PACK_EXTENSION: &str = "pack";
+const FOOTER_LENGTH: u64 = 16;
+/// Maximum number of deltas a written object may have to be applied to when
+/// it's read back. Each link costs a read, so chains are kept short.
+pub(crate) const MAX_WRITTEN_DELTA_CHAIN_LENGTH: usize = 10;
+/// Maximum length of delta chains accepted when reading. It's more lenient
+/// than [`MAX_WRITTEN_DELTA_CHAIN_LENGTH`] and only guards against cycles in
+/// corrupt packs.
+const MAX_READ_DELTA_CHAIN_LENGTH: usize = 50;
+/// Size of the blocks of the base object which are matched when computing a
+/// delta.
+const DELTA_BLOCK_SIZE: usize = 16;
+
+const DELTA_INSERT: u8 = 0;
+const DELTA_COPY: u8 = 1;
+
+/// Kind of a packed object.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum ObjectKind {
+    Commit,
+    Tree,
+    File,
+    Symlink,
+}
+
+impl ObjectKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Commit => 0,
+            Self::Tree => 1,
+            Self::File => 2,
+            Self::Symlink => 3,
+        }
+    }
+
+    /// Data which binds an encrypted object to its kind and id.
+    pub fn associated_data(self, id: &[u8]) -> Vec<u8> {
+        [&[self.to_byte()], id].concat()
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Commit),
+            1 => Some(Self::Tree),
+            2 => Some(Self::File),
+            3 => Some(Self::Symlink),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PackEntry {
+    pack_index: usize,
+    base_id: Option<Vec<u8>>,
+    offset: u64,
+    len: u64,
+}
+
+/// The objects stored in all packs of a directory.
+#[derive(Debug, Default)]
+pub(crate) struct PackSet {
+    paths: Vec<PathBuf>,
+    entries: HashMap<(ObjectKind, Vec<u8>), PackEntry>,
+    cipher: Option<ObjectCipher>,
+}
+
+impl PackSet {
+    /// Loads the tables of the packs in `dir`. The directory doesn't have to
+    /// exist. Object data is decrypted with the `cipher` if given.
+    pub fn load(dir: &Path, cipher: Option<ObjectCipher>) -> Result<Self, PathError> {
+        let mut pack_set = Self {
+            cipher,
+            ..Self::default()
+        };
+        let dir_entries = match dir.read_dir() {
+            Ok(dir_entries) => dir_entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(pack_set),
+            Err(err) => return Err(err).context(dir),
+        };
+        let mut paths = vec![];
+        for entry in dir_entries {
+            let path = entry.context(dir)?.path();
+            if path.extension().is_some_and(|ext| ext == PACK_EXTENSION) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        for path in paths {
+            let pack_index = pack_set.paths.len();
+            pack_set.load_table(&path, pack_index).context(&path)?;
+            pack_set.paths.push(path);
+        }
+        Ok(pack_set)
+    }
+
+    fn load_table(&mut self, path: &Path, pack_index: usize) -> io::Result<()> {
+        let mut file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        if file_len < FOOTER_LENGTH {
+            return Err(invalid_data("Pack file is truncated"));
+        }
+        let mut footer = [0; FOOTER_LENGTH as usize];
+        file.seek(SeekFrom::Start(file_len - FOOTER_LENGTH))?;
+        file.read_exact(&mut footer)?;
+        let (table_offset, magic) = footer.split_at(8);
+        if magic != PACK_MAGIC {
+            return Err(invalid_data("Not a pack file"));
+        }
+        let table_offset = u64::from_le_bytes(table_offset.try_into().unwrap());
+        let table_len = (file_len - FOOTER_LENGTH)
+            .checked_sub(table_offset)
+            .ok_or_else(|| invalid_data("Invalid pack table offset"))?;
+        let mut table = vec![0; table_len as usize];
+        file.seek(SeekFrom::Start(table_offset))?;
+        file.read_exact(&mut table)?;
+
+        let mut rest = table.as_slice();
+        while !rest.is_empty() {
+            let kind = ObjectKind::from_byte(take_u8(&mut rest)?)
+                .ok_or_else(|| invalid_data("Invalid object kind"))?;
+            let id = take_id(&mut rest)?;
+            let base_id = take_id(&mut rest)?;
+            let offset = take_u64(&mut rest)?;
+            let len = take_u64(&mut rest)?;
+            if offset.checked_add(len).is_none_or(|end| end > table_offset) {
+                return Err(invalid_data("Invalid object offset"));
+            }
+            let entry = PackEntry {
+                pack_index,
+                base_id: (!base_id.is_empty()).then_some(base_id),
+                offset,
+                len,
+            };
+            self.entries.insert((kind, id), entry);
+        }
+        Ok(())
+    }
+
+    /// Paths to the pack files.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Iterates over the kinds and ids of the packed objects, along with the
+    /// path to the pack containing each.
+    pub fn objects(&self) -> impl Iterator<Item = (ObjectKind, &[u8], &Path)> {
+        self.entries.iter().map(|((kind, id), entry)| {
+            (*kind, id.as_slice(), self.paths[entry.pack_index].as_path())
+        })
+    }
+
+    /// Reads the contents of an object. Returns `None` if the object isn't
+    /// packed.
+    pub fn read(&self, kind: ObjectKind, id: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        let Some(entry) = self.entries.get(&(kind, id.to_vec())) else {
+            return Ok(None);
+        };
+        self.read_entry(kind, id, entry, 0).map(Some)
+    }
+
+    fn read_entry(
+        &self,
+        kind: ObjectKind,
+        id: &[u8],
+        entry: &PackEntry,
+        depth: usize,
+    ) -> io::Result<Vec<u8>> {
+        let mut file = File::open(&self.paths[entry.pack_index])?;
+        file.seek(SeekFrom::Start(entry.offset))?;
+        let mut data = vec![0; entry.len as usize];
+        file.read_exact(&mut data)?;
+        if let Some(cipher) = &self.cipher {
+            data = cipher
+                .decrypt(&kind.associated_data(id), &data)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        }
+        let Some(base_id) = &entry.base_id else {
+            return Ok(data);
+        };
+        if depth >= MAX_READ_DELTA_CHAIN_LENGTH {
+            return Err(invalid_data("Delta chain is too long"));
+        }
+        let base_entry = self
+            .entries
+            .get(&(kind, base_id.clone()))
+            .ok_or_else(|| invalid_data("Delta base is missing"))?;
+        let base = self.read_entry(kind, base_id, base_entry, depth + 1)?;
+        apply_delta(&base, &data)
+    }
+}
+
+/// Writes a new pack file.
+pub(crate) struct PackWriter {
+    dir: PathBuf,
+    writer: BufWriter<NamedTempFile>,
+    hasher: Blake2b512,
+    table: Vec<u8>,
+    offset: u64,
+    cipher: Option<ObjectCipher>,
+}
+
+impl PackWriter {
+    /// Starts writing a pack in `dir`, which is created if needed. Object data
+    /// is encrypted with the `cipher` if given.
+    pub fn new(dir: &Path, cipher: Option<ObjectCipher>) -> Result<Self, PathError> {
+        fs::create_dir_all(dir).context(dir)?;
+        let temp_file = NamedTempFile::new_in(dir).context(dir)?;
+        Ok(Self {
+            dir: dir.to_owned(),
+            writer: BufWriter::new(temp_file),
+            hasher: Blake2b512::new(),
+            table: vec![],
+            offset: 0,
+            cipher,
+        })
+    }
+
+    /// Adds an object to the pack. If a `base` object is given, the contents
+    /// are stored as a delta against it if that's smaller. The base object
+    /// must be added to the same pack.
+    ///
+    /// Returns true if the object was stored as a delta.
+    pub fn add(
+        &mut self,
+        kind: ObjectKind,
+        id: &[u8],
+        base: Option<(&[u8], &[u8])>,
+        contents: &[u8],
+    ) -> io::Result<bool> {
+        let delta = base.and_then(|(base_id, base_contents)| {
+            let delta = compute_delta(base_contents, contents);
+            (delta.len() < contents.len() / 2).then_some((base_id, delta))
+        });
+        let (base_id, data) = match &delta {
+            Some((base_id, delta)) => (*base_id, delta.as_slice()),
+            None => (&[][..], contents),
+        };
+        let encrypted;
+        let data = if let Some(cipher) = &self.cipher {
+            encrypted = cipher.encrypt(&kind.associated_data(id), data);
+            encrypted.as_slice()
+        } else {
+            data
+        };
+        self.write_data(data)?;
+        self.table.push(kind.to_byte());
+        push_id(&mut self.table, id)?;
+        push_id(&mut self.table, base_id)?;
+        self.table.extend_from_slice(&self.offset.to_le_bytes());
+        self.table
+            .extend_from_slice(&(data.len() as u64).to_le_bytes());
+        self.offset += data.len() as u64;
+        Ok(delta.is_some())
+    }
+
+    /// Writes the table and moves the pack into place. Returns the path to
+    /// the pack file, which is named after the hash of its contents.
+    pub fn finish(mut self) -> Result<PathBuf, PathError> {
+        let table = std::mem::take(&mut self.table);
+        let offset = self.offset;
+        let write_footer = |this: &mut Self| {
+            this.write_data(&table)?;
+            this.write_data(&offset.to_le_bytes())?;
+            this.write_data(PACK_MAGIC)
+        };
+        write_footer(&mut self).context(&self.dir)?;
+        let name = hex_util::encode_hex(&self.hasher.finalize()[..32]);
+        let path = self.dir.join(format!("{name}.{PACK_EXTENSION}"));
+        let temp_file = self
+            .writer
+            .into_inner()
+            .map_err(|err| err.into_error())
+            .context(&path)?;
+        persist_content_addressed_temp_file(temp_file, &path).context(&path)?;
+        Ok(path)
+    }
+
+    fn write_data(&mut self, data: &[u8]) -> io::Result<()> {
+        self.hasher.update(data);
+        self.writer.write_all(data)
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn take_u8(rest: &mut &[u8]) -> io::Result<u8> {
+    let (&byte, tail) = rest
+        .split_first()
+        .ok_or_else(|| invalid_data("Unexpected end of data"))?;
+    *rest = tail;
+    Ok(byte)
+}
+
+fn take_bytes<'a>(rest: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if rest.len() < len {
+        return Err(invalid_data("Unexpected end of data"));
+    }
+    let (bytes, tail) = rest.split_at(len);
+    *rest = tail;
+    Ok(bytes)
+}
+
+fn take_id(rest: &mut &[u8]) -> io::Result<Vec<u8>> {
+    let len = take_u8(rest)?;
+    Ok(take_bytes(rest, len.into())?.to_vec())
+}
+
+fn take_u64(rest: &mut &[u8]) -> io::Result<u64> {
+    Ok(u64::from_le_bytes(take_bytes(rest, 8)?.try_into().unwrap()))
+}
+
+fn take_varint(rest: &mut &[u8]) -> io::Result<usize> {
+    let mut value: usize = 0;
+    for shift in (0..usize::BITS).step_by(7) {
+        let byte = take_u8(rest)?;
+        value |= usize::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("Invalid varint"))
+}
+
+fn push_id(buf: &mut Vec<u8>, id: &[u8]) -> io::Result<()> {
+    let len = u8::try_from(id.len()).map_err(|_| invalid_data("Object id is too long"))?;
+    buf.push(len);
+    buf.extend_from_slice(id);
+    Ok(())
+}
+
+fn push_varint(buf: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        buf.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn push_insert(delta: &mut Vec<u8>, bytes: &[u8]) {
+    if !bytes.is_empty() {
+        delta.push(DELTA_INSERT);
+        push_varint(delta, bytes.len());
+        delta.extend_from_slice(bytes);
+    }
+}
+
+/// Computes instructions to build `target` by copying ranges of `base` and
+/// inserting the remaining bytes.
+fn compute_delta(base: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut blocks: HashMap<&[u8], usize> = HashMap::new();
+    for (i, block) in base.chunks_exact(DELTA_BLOCK_SIZE).enumerate() {
+        blocks.entry(block).or_insert(i * DELTA_BLOCK_SIZE);
+    }
+
+    let mut delta = vec![];
+    let mut literal_start = 0;
+    let mut pos = 0;
+    while pos + DELTA_BLOCK_SIZE <= target.len() {
+        let Some(&block_start) = blocks.get(&target[pos..pos + DELTA_BLOCK_SIZE]) else {
+            pos += 1;
+            continue;
+        };
+        // Extend the match in both directions.
+        let (mut start, mut base_start) = (pos, block_start);
+        while start > literal_start && base_start > 0 && target[start - 1] == base[base_start - 1] {
+            start -= 1;
+            base_start -= 1;
+        }
+        let (mut end, mut base_end) = (pos + DELTA_BLOCK_SIZE, block_start + DELTA_BLOCK_SIZE);
+        while end < target.len() && base_end < base.len() && target[end] == base[base_end] {
+            end += 1;
+            base_end += 1;
+        }
+        push_insert(&mut delta, &target[literal_start..start]);
+        delta.push(DELTA_COPY);
+        push_varint(&mut delta, base_start);
+        push_varint(&mut delta, base_end - base_start);
+        pos = end;
+        literal_start = end;
+    }
+    push_insert(&mut delta, &target[literal_start..]);
+    delta
+}
+
+fn apply_delta(base: &[u8], mut delta: &[u8]) -> io::Result<Vec<u8>> {
+    let mut output = vec![];
+    while !delta.is_empty() {
+        match take_u8(&mut delta)? {
+            DELTA_INSERT => {
+                let len = take_varint(&mut delta)?;
+                output.extend_from_slice(take_bytes(&mut delta, len)?);
+            }
+            DELTA_COPY => {
+                let start = take_varint(&mut delta)?;
+                let len = take_varint(&mut delta)?;
+                let range = base
+                    .get(start..start.saturating_add(len))
+                    .ok_or_else(|| invalid_data("Delta copies out of range"))?;
+                output.extend_from_slice(range);
+            }
+            _ => return Err(invalid_data("Invalid delta instruction")),
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delta_roundtrip() {
+        let base = b"The quick brown fox jumps over the lazy dog.\n".repeat(10);
+        let mut target = base.clone();
+        target.splice(100..110, b"inserted text".iter().copied());
+        target.extend_from_slice(b"trailer");
+        let delta = compute_delta(&base, &target);
+        assert!(delta.len() < target.len() / 4);
+        assert_eq!(apply_delta(&base, &delta).unwrap(), target);
+
+        // Nothing in common
+        let delta = compute_delta(b"", b"abc");
+        assert_eq!(apply_delta(b"", &delta).unwrap(), b"abc");
+        let delta = compute_delta(b"abc", b"");
+        assert_eq!(apply_delta(b"abc", &delta).unwrap(), b"");
+
+        assert!(apply_delta(b"abc", &[DELTA_COPY, 2, 5]).is_err());
+    }
+
+    #[test]
+    fn test_pack_roundtrip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path().join("packs");
+        assert!(PackSet::load(&dir, None).unwrap().paths().is_empty());
+
+        let v1 = b"line\n".repeat(100);
+        let mut v2 = v1.clone();
+        v2.extend_from_slice(b"more\n");
+        let mut writer = PackWriter::new(&dir, None).unwrap();
+        assert!(!writer.add(ObjectKind::File, b"v1", None, &v1).unwrap());
+        assert!(
+            writer
+                .add(ObjectKind::File, b"v2", Some((b"v1", &v1)), &v2)
+                .unwrap()
+        );
+        assert!(!writer.add(ObjectKind::Tree, b"t", None, b"tree").unwrap());
+        let path = writer.finish().unwrap();
+
+        let pack_set = PackSet::load(&dir, None).unwrap();
+        assert_eq!(pack_set.paths(), [path]);
+        let read = |kind, id: &[u8]| pack_set.read(kind, id).unwrap();
+        assert_eq!(read(ObjectKind::File, b"v1"), Some(v1));
+        assert_eq!(read(ObjectKind::File, b"v2"), Some(v2));
+        assert_eq!(read(ObjectKind::Tree, b"t"), Some(b"tree".to_vec()));
+        assert_eq!(read(ObjectKind::Commit, b"t"), None);
+    }
+
+    #[test]
+    fn test_encrypted_pack() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path().join("packs");
+        let cipher = ObjectCipher::from_secret(b"secret", b"0123456789abcdef").unwrap();
+
+        let v1 = b"line\n".repeat(100);
+        let mut v2 = v1.clone();
+        v2.extend_from_slice(b"more\n");
+        let mut writer = PackWriter::new(&dir, Some(cipher.clone())).unwrap();
+        assert!(!writer.add(ObjectKind::File, b"v1", None, &v1).unwrap());
+        assert!(
+            writer
+                .add(ObjectKind::File, b"v2", Some((b"v1", &v1)), &v2)
+                .unwrap()
+        );
+        let path = writer.finish().unwrap();
+        let contents = fs::read(path).unwrap();
+        assert!(!contents.windows(5).any(|w| w == b"line\n"));
+
+        let pack_set = PackSet::load(&dir, Some(cipher)).unwrap();
+        assert_eq!(pack_set.read(ObjectKind::File, b"v1").unwrap(), Some(v1));
+        assert_eq!(pack_set.read(ObjectKind::File, b"v2").unwrap(), Some(v2));
+
+        let other_cipher = ObjectCipher::from_secret(b"other secret", b"0123456789abcdef").unwrap();
+        let pack_set = PackSet::load(&dir, Some(other_cipher)).unwrap();
+        assert!(pack_set.read(ObjectKind::File, b"v1").is_err());
+    }
+}
@@ -14,8 +14,11 @@
 
 #![expect(missing_docs)]
 
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::fmt::Formatter;
+use std::mem;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -36,6 +39,7 @@ use crate::backend::FileId;
 use crate::backend::SigningFn;
 use crate::backend::SymlinkId;
 use crate::backend::TreeId;
+use crate::backend::TreeValue;
 use crate::commit::Commit;
 use crate::index::Index;
 use crate::merge::Merge;
@@ -58,9 +62,38 @@ pub struct Store {
     signer: Signer,
     commit_cache: Mutex<CLruCache<CommitId, Arc<backend::Commit>>>,
     tree_cache: Mutex<CLruCache<(RepoPathBuf, TreeId), Arc<backend::Tree>>>,
+    /// Trees that have been computed in memory but not written to the
+    /// backend yet. They are written when a commit or another written tree
+    /// refers to them.
+    pending_trees: Mutex<PendingTrees>,
     merge_options: MergeOptions,
 }
 
+#[derive(Default)]
+struct PendingTrees {
+    num_scopes: usize,
+    trees: HashMap<(RepoPathBuf, TreeId), Arc<backend::Tree>>,
+}
+
+/// Keeps trees written by [`Store::write_tree_deferred()`] in memory while
+/// alive. See [`Store::pending_trees_scope()`].
+#[must_use]
+pub struct PendingTreesScope {
+    store: Arc<Store>,
+}
+
+impl Debug for PendingTreesScope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        f.debug_struct("PendingTreesScope").finish_non_exhaustive()
+    }
+}
+
+impl Drop for PendingTreesScope {
+    fn drop(&mut self) {
+        self.store.end_pending_trees_scope();
+    }
+}
+
 impl Debug for Store {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
         f.debug_struct("Store")
@@ -80,6 +113,7 @@ impl Store {
             signer,
             commit_cache: Mutex::new(CLruCache::new(COMMIT_CACHE_CAPACITY.try_into().unwrap())),
             tree_cache: Mutex::new(CLruCache::new(TREE_CACHE_CAPACITY.try_into().unwrap())),
+            pending_trees: Mutex::new(PendingTrees::default()),
             merge_options,
         })
     }
@@ -178,6 +212,9 @@ impl Store {
     ) -> BackendResult<Commit> {
         assert!(!commit.parents.is_empty());
 
+        for tree_id in &commit.root_tree {
+            self.flush_pending_trees(RepoPath::root(), tree_id).await?;
+        }
         let (commit_id, commit) = self.backend.write_commit(commit, sign_with).await?;
         let data = Arc::new(commit);
         {
@@ -213,6 +250,9 @@ impl Store {
                 return Ok(data);
             }
         }
+        if let Some(data) = self.pending_trees.lock().unwrap().trees.get(&key).cloned() {
+            return Ok(data);
+        }
         let data = self.backend.read_tree(dir, id).await?;
         let data = Arc::new(data);
         let mut locked_cache = self.tree_cache.lock().unwrap();
@@ -225,6 +265,7 @@ impl Store {
         path: &RepoPath,
         tree: backend::Tree,
     ) -> BackendResult<Tree> {
+        self.flush_pending_subtrees(path, &tree).await?;
         let tree_id = self.backend.write_tree(path, &tree).await?;
         let data = Arc::new(tree);
         {
@@ -235,6 +276,125 @@ impl Store {
         Ok(Tree::new(self.clone(), path.to_owned(), tree_id, data))
     }
 
+    /// Like [`Store::write_tree()`], but keeps the tree in memory until a
+    /// commit refers to it.
+    ///
+    /// This is meant for trees computed by merges and rewrites, many of which
+    /// are intermediate results that are never committed. Trees are only kept
+    /// in memory while a [`PendingTreesScope`] is alive, and are discarded
+    /// when the last scope ends. Falls back to writing the tree immediately if
+    /// there's no scope, or if the backend can't compute tree IDs.
+    pub async fn write_tree_deferred(
+        self: &Arc<Self>,
+        path: &RepoPath,
+        tree: backend::Tree,
+    ) -> BackendResult<Tree> {
+        let in_scope = self.pending_trees.lock().unwrap().num_scopes > 0;
+        let tree_id = in_scope
+            .then(|| self.backend.compute_tree_id(path, &tree))
+            .flatten();
+        let Some(tree_id) = tree_id else {
+            return self.write_tree(path, tree).await;
+        };
+        let key = (path.to_owned(), tree_id.clone());
+        let data = Arc::new(tree);
+        let inserted = {
+            let mut pending_trees = self.pending_trees.lock().unwrap();
+            // The last scope may have ended in the meantime.
+            (pending_trees.num_scopes > 0)
+                .then(|| pending_trees.trees.insert(key.clone(), data.clone()))
+                .is_some()
+        };
+        if !inserted {
+            return self.write_tree(path, Arc::unwrap_or_clone(data)).await;
+        }
+        self.tree_cache.lock().unwrap().put(key, data.clone());
+        Ok(Tree::new(self.clone(), path.to_owned(), tree_id, data))
+    }
+
+    /// Starts a scope in which [`Store::write_tree_deferred()`] keeps trees
+    /// in memory. Pending trees that haven't been written when the last scope
+    /// ends are discarded.
+    pub fn pending_trees_scope(self: &Arc<Self>) -> PendingTreesScope {
+        self.pending_trees.lock().unwrap().num_scopes += 1;
+        PendingTreesScope {
+            store: self.clone(),
+        }
+    }
+
+    fn end_pending_trees_scope(&self) {
+        let discarded_trees = {
+            let mut pending_trees = self.pending_trees.lock().unwrap();
+            pending_trees.num_scopes -= 1;
+            if pending_trees.num_scopes > 0 {
+                return;
+            }
+            mem::take(&mut pending_trees.trees)
+        };
+        // Unwritten trees mustn't be found in the cache either.
+        let mut locked_cache = self.tree_cache.lock().unwrap();
+        for key in discarded_trees.keys() {
+            locked_cache.pop(key);
+        }
+    }
+
+    /// Writes the pending tree `id` at `dir` and all pending trees it refers
+    /// to. Children are written before their parents.
+    async fn flush_pending_trees(&self, dir: &RepoPath, id: &TreeId) -> BackendResult<()> {
+        let trees_to_write = {
+            let pending_trees = &self.pending_trees.lock().unwrap().trees;
+            if pending_trees.is_empty() {
+                return Ok(());
+            }
+            let mut trees_to_write = vec![];
+            let mut visited = HashSet::new();
+            let mut stack = vec![(dir.to_owned(), id.clone())];
+            while let Some(key) = stack.pop() {
+                let Some(data) = pending_trees.get(&key) else {
+                    continue;
+                };
+                if !visited.insert(key.clone()) {
+                    continue;
+                }
+                for entry in data.entries() {
+                    if let TreeValue::Tree(sub_id) = entry.value() {
+                        stack.push((key.0.join(entry.name()), sub_id.clone()));
+                    }
+                }
+                trees_to_write.push((key, data.clone()));
+            }
+            trees_to_write
+        };
+        for (key, data) in trees_to_write.into_iter().rev() {
+            let (dir, id) = &key;
+            let written_id = self.backend.write_tree(dir, &data).await?;
+            assert_eq!(
+                &written_id, id,
+                "backend computed a different tree ID than it wrote"
+            );
+            self.pending_trees.lock().unwrap().trees.remove(&key);
+        }
+        Ok(())
+    }
+
+    /// Writes pending trees that `tree` at `dir` refers to.
+    async fn flush_pending_subtrees(
+        &self,
+        dir: &RepoPath,
+        tree: &backend::Tree,
+    ) -> BackendResult<()> {
+        if self.pending_trees.lock().unwrap().trees.is_empty() {
+            return Ok(());
+        }
+        for entry in tree.entries() {
+            if let TreeValue::Tree(sub_id) = entry.value() {
+                self.flush_pending_trees(&dir.join(entry.name()), sub_id)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
     pub async fn read_file(
         &self,
         path: &RepoPath,
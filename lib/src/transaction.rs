@@ -14,12 +14,15 @@
 
 #![expect(missing_docs)]
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use itertools::Itertools as _;
 use pollster::FutureExt as _;
 use thiserror::Error;
 
+use crate::backend::BackendError;
+use crate::backend::CommitId;
 use crate::backend::Timestamp;
 use crate::dag_walk;
 use crate::index::IndexStoreError;
@@ -31,21 +34,28 @@ use crate::op_store::OpStoreError;
 use crate::op_store::OperationMetadata;
 use crate::op_store::TimestampRange;
 use crate::operation::Operation;
+use crate::protected_bookmarks::ProtectedBookmarkError;
+use crate::protected_bookmarks::ProtectedBookmarks;
 use crate::repo::MutableRepo;
 use crate::repo::ReadonlyRepo;
 use crate::repo::Repo as _;
 use crate::repo::RepoLoader;
 use crate::repo::RepoLoaderError;
+use crate::rerere;
+use crate::rerere::RerereError;
 use crate::settings::UserSettings;
+use crate::store::PendingTreesScope;
 use crate::view::View;
 
 /// Error from attempts to write and publish transaction.
 #[derive(Debug, Error)]
 #[error("Failed to commit new operation")]
 pub enum TransactionCommitError {
+    Backend(#[from] BackendError),
     IndexStore(#[from] IndexStoreError),
     OpHeadsStore(#[from] OpHeadsStoreError),
     OpStore(#[from] OpStoreError),
+    ProtectedBookmark(#[from] ProtectedBookmarkError),
 }
 
 /// An in-memory representation of a repo and any changes being made to it.
@@ -64,6 +74,10 @@ pub struct Transaction {
     parent_ops: Vec<Operation>,
     op_metadata: OperationMetadata,
     end_time: Option<Timestamp>,
+    check_protected_bookmarks: bool,
+    /// Keeps the trees computed by the transaction in memory until they're
+    /// committed.
+    _pending_trees_scope: PendingTreesScope,
 }
 
 impl Transaction {
@@ -71,11 +85,14 @@ impl Transaction {
         let parent_ops = vec![mut_repo.base_repo().operation().clone()];
         let op_metadata = create_op_metadata(user_settings, "".to_string(), false);
         let end_time = user_settings.operation_timestamp();
+        let pending_trees_scope = mut_repo.store().pending_trees_scope();
         Self {
             mut_repo,
             parent_ops,
             op_metadata,
             end_time,
+            check_protected_bookmarks: true,
+            _pending_trees_scope: pending_trees_scope,
         }
     }
 
@@ -107,6 +124,9 @@ impl Transaction {
         let base_repo = repo_loader.load_at(&ancestor_op)?;
         let other_repo = repo_loader.load_at(&other_op)?;
         self.parent_ops.push(other_op);
+        // The bookmark changes of the merged operations were checked when they
+        // were committed.
+        self.check_protected_bookmarks = false;
         let merged_repo = self.repo_mut();
         merged_repo.merge(&base_repo, &other_repo)?;
         Ok(())
@@ -116,6 +136,25 @@ impl Transaction {
         self.op_metadata.is_snapshot = is_snapshot;
     }
 
+    /// Allows the transaction to move bookmarks matching the
+    /// `bookmarks.protect` setting backwards or sideways, or to delete them.
+    /// Otherwise, writing such a transaction fails.
+    pub fn allow_protected_bookmarks(&mut self) {
+        self.check_protected_bookmarks = false;
+    }
+
+    /// Checks that the bookmarks matching the `bookmarks.protect` setting are
+    /// only moved forward, unless that's allowed. This is also checked when
+    /// the transaction is written.
+    pub fn check_protected_bookmarks(&self) -> Result<(), ProtectedBookmarkError> {
+        if !self.check_protected_bookmarks {
+            return Ok(());
+        }
+        let base_repo = self.base_repo();
+        let protected_bookmarks = ProtectedBookmarks::from_settings(base_repo.settings())?;
+        protected_bookmarks.check_local_bookmark_changes(&self.mut_repo, base_repo.view())
+    }
+
     /// Writes the transaction to the operation store and publishes it.
     pub fn commit(
         self,
@@ -131,6 +170,7 @@ impl Transaction {
         mut self,
         description: impl Into<String>,
     ) -> Result<UnpublishedOperation, TransactionCommitError> {
+        self.check_protected_bookmarks()?;
         let mut_repo = self.mut_repo;
         // TODO: Should we instead just do the rebasing here if necessary?
         assert!(
@@ -139,6 +179,9 @@ impl Transaction {
         );
         let base_repo = mut_repo.base_repo().clone();
         let (mut_index, view, predecessors) = mut_repo.consume();
+        if base_repo.settings().rerere_enabled() {
+            record_resolutions(&base_repo, &predecessors)?;
+        }
 
         let operation = {
             let view_id = base_repo
@@ -167,6 +210,28 @@ impl Transaction {
     }
 }
 
+/// Records how the commits created in a transaction resolved the conflicts
+/// of their predecessors.
+fn record_resolutions(
+    base_repo: &ReadonlyRepo,
+    predecessors: &BTreeMap<CommitId, Vec<CommitId>>,
+) -> Result<(), BackendError> {
+    let store = base_repo.store();
+    let rerere_store = base_repo.loader().rerere_store();
+    for (new_id, old_ids) in predecessors {
+        let new_commit = store.get_commit(new_id)?;
+        for old_id in old_ids {
+            let old_commit = store.get_commit(old_id)?;
+            match rerere::record_resolutions(rerere_store, &old_commit, &new_commit).block_on() {
+                Ok(_) => {}
+                Err(RerereError::Backend(err)) => return Err(err),
+                Err(err) => tracing::warn!(?err, "Failed to record conflict resolutions"),
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn create_op_metadata(
     user_settings: &UserSettings,
     description: String,
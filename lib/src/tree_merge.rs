@@ -27,7 +27,6 @@ use futures::future::BoxFuture;
 use futures::future::try_join_all;
 use futures::stream::FuturesUnordered;
 use itertools::Itertools as _;
-use tokio::io::AsyncReadExt as _;
 
 use crate::backend;
 use crate::backend::BackendError;
@@ -35,9 +34,13 @@ use crate::backend::BackendResult;
 use crate::backend::TreeId;
 use crate::backend::TreeValue;
 use crate::config::ConfigGetError;
+use crate::diff::DiffAlgorithm;
+use crate::file_util;
 use crate::files;
 use crate::files::FileMergeHunkLevel;
+use crate::files::MAX_CONTENT_SIZE;
 use crate::merge::Merge;
+use crate::merge::MergeBuilder;
 use crate::merge::MergedTreeVal;
 use crate::merge::MergedTreeValue;
 use crate::merge::SameChange;
@@ -57,6 +60,8 @@ pub struct MergeOptions {
     pub hunk_level: FileMergeHunkLevel,
     /// Whether to resolve conflict that makes the same change at all sides.
     pub same_change: SameChange,
+    /// Algorithm to find the unchanged regions between the sides.
+    pub diff_algorithm: DiffAlgorithm,
 }
 
 impl MergeOptions {
@@ -67,6 +72,7 @@ impl MergeOptions {
             // needed. It wouldn't be translated to FileMergeHunkLevel.
             hunk_level: settings.get("merge.hunk-level")?,
             same_change: settings.get("merge.same-change")?,
+            diff_algorithm: settings.get("diff.algorithm")?,
         })
     }
 }
@@ -350,7 +356,7 @@ async fn write_trees(
     let trees = try_join_all(
         backend_trees
             .into_iter()
-            .map(|backend_tree| store.write_tree(&dir, backend_tree)),
+            .map(|backend_tree| store.write_tree_deferred(&dir, backend_tree)),
     )
     .await?;
     Ok(Merge::from_vec(trees))
@@ -476,19 +482,25 @@ async fn try_resolve_file_conflict(
 
     let contents = file_id_conflict
         .try_map_async(async |file_id| {
-            let mut content = vec![];
-            let mut reader = store.read_file(filename, file_id).await?;
-            reader
-                .read_to_end(&mut content)
+            let reader = store.read_file(filename, file_id).await?;
+            file_util::read_to_end_with_limit(reader, MAX_CONTENT_SIZE)
                 .await
                 .map_err(|err| BackendError::ReadObject {
                     object_type: file_id.object_type(),
                     hash: file_id.hex(),
                     source: err.into(),
-                })?;
-            BackendResult::Ok(content)
+                })
         })
         .await?;
+    // Huge files aren't loaded into memory, so their conflicts are left
+    // unresolved.
+    let Some(contents) = contents
+        .into_iter()
+        .collect::<Option<MergeBuilder<_>>>()
+        .map(MergeBuilder::build)
+    else {
+        return Ok(None);
+    };
     if let Some(merged_content) = files::try_merge(&contents, options) {
         let id = store
             .write_file(filename, &mut merged_content.as_slice())
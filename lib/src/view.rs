@@ -15,6 +15,7 @@
 #![expect(missing_docs)]
 
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashSet;
 
 use itertools::Itertools as _;
@@ -73,10 +74,26 @@ impl View {
         self.data.wc_commit_ids.values().contains(commit_id)
     }
 
+    /// Returns the names of the workspaces whose working-copy commits are
+    /// throwaway.
+    pub fn ephemeral_workspaces(&self) -> &BTreeSet<WorkspaceNameBuf> {
+        &self.data.ephemeral_workspaces
+    }
+
+    pub fn is_ephemeral_workspace(&self, name: &WorkspaceName) -> bool {
+        self.data.ephemeral_workspaces.contains(name)
+    }
+
     pub fn heads(&self) -> &HashSet<CommitId> {
         &self.data.head_ids
     }
 
+    /// Returns the commits whose descendants haven't been rebased onto their
+    /// new versions yet, mapped to the new versions.
+    pub fn deferred_rewrites(&self) -> &BTreeMap<CommitId, CommitId> {
+        &self.data.deferred_rewrites
+    }
+
     /// Iterates pair of local and remote bookmarks by bookmark name.
     pub fn bookmarks(&self) -> impl Iterator<Item = (&RefName, LocalRemoteRefTarget<'_>)> {
         op_store::merge_join_ref_views(
@@ -107,6 +124,26 @@ impl View {
 
     pub fn remove_wc_commit(&mut self, name: &WorkspaceName) {
         self.data.wc_commit_ids.remove(name);
+        self.data.ephemeral_workspaces.remove(name);
+    }
+
+    /// Marks the workspace as ephemeral or not.
+    pub fn set_ephemeral_workspace(&mut self, name: WorkspaceNameBuf, ephemeral: bool) {
+        if ephemeral {
+            self.data.ephemeral_workspaces.insert(name);
+        } else {
+            self.data.ephemeral_workspaces.remove(&name);
+        }
+    }
+
+    /// Records that the descendants of `old_id` should be rebased onto
+    /// `new_id` later.
+    pub fn set_deferred_rewrite(&mut self, old_id: CommitId, new_id: CommitId) {
+        self.data.deferred_rewrites.insert(old_id, new_id);
+    }
+
+    pub fn remove_deferred_rewrite(&mut self, old_id: &CommitId) {
+        self.data.deferred_rewrites.remove(old_id);
     }
 
     pub fn rename_workspace(
@@ -124,6 +161,9 @@ impl View {
                 name: old_name.to_owned(),
             }
         })?;
+        if self.data.ephemeral_workspaces.remove(old_name) {
+            self.data.ephemeral_workspaces.insert(new_name.clone());
+        }
         self.data.wc_commit_ids.insert(new_name, wc_commit_id);
         Ok(())
     }
@@ -537,6 +577,8 @@ impl View {
             git_refs,
             git_head,
             wc_commit_ids,
+            deferred_rewrites,
+            ephemeral_workspaces: _,
         } = &self.data;
         itertools::chain!(
             head_ids,
@@ -549,7 +591,10 @@ impl View {
             }),
             git_refs.values().flat_map(ref_target_ids),
             ref_target_ids(git_head),
-            wc_commit_ids.values()
+            wc_commit_ids.values(),
+            deferred_rewrites
+                .iter()
+                .flat_map(|(old_id, new_id)| [old_id, new_id])
         )
     }
 
@@ -32,18 +32,22 @@ use crate::commit::Commit;
 use crate::dag_walk;
 use crate::gitignore::GitIgnoreError;
 use crate::gitignore::GitIgnoreFile;
+use crate::matchers::ConeMatcher;
+use crate::matchers::DifferenceMatcher;
 use crate::matchers::Matcher;
+use crate::matchers::NothingMatcher;
+use crate::matchers::PrefixMatcher;
 use crate::merged_tree::MergedTree;
 use crate::op_store::OpStoreError;
 use crate::op_store::OperationId;
 use crate::operation::Operation;
+use crate::progress::Progress;
 use crate::ref_name::WorkspaceName;
 use crate::ref_name::WorkspaceNameBuf;
 use crate::repo::ReadonlyRepo;
 use crate::repo::Repo as _;
 use crate::repo::RewriteRootCommit;
 use crate::repo_path::InvalidRepoPathError;
-use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::settings::UserSettings;
 use crate::store::Store;
@@ -65,10 +69,12 @@ pub trait WorkingCopy: Any + Send {
     fn tree(&self) -> Result<&MergedTree, WorkingCopyStateError>;
 
     /// Patterns that decide which paths from the current tree should be checked
-    /// out in the working copy. An empty list means that no paths should be
-    /// checked out in the working copy. A single `RepoPath::root()` entry means
-    /// that all files should be checked out.
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError>;
+    /// out in the working copy.
+    fn sparse_patterns(&self) -> Result<&SparsePatterns, WorkingCopyStateError>;
+
+    /// Paths which are tracked even if they're ignored or too large. They're
+    /// tracked again when they're re-created after being deleted.
+    fn force_tracked_paths(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError>;
 
     /// Locks the working copy and returns an instance with methods for updating
     /// the working copy files and state.
@@ -121,7 +127,11 @@ pub trait LockedWorkingCopy: Any + Send {
     ) -> Result<(MergedTree, SnapshotStats), SnapshotError>;
 
     /// Check out the specified commit in the working copy.
-    async fn check_out(&mut self, commit: &Commit) -> Result<CheckoutStats, CheckoutError>;
+    async fn check_out(
+        &mut self,
+        commit: &Commit,
+        options: &CheckoutOptions,
+    ) -> Result<CheckoutStats, CheckoutError>;
 
     /// Update the workspace name.
     fn rename_workspace(&mut self, new_workspace_name: WorkspaceNameBuf);
@@ -134,7 +144,7 @@ pub trait LockedWorkingCopy: Any + Send {
     async fn recover(&mut self, commit: &Commit) -> Result<(), ResetError>;
 
     /// See `WorkingCopy::sparse_patterns()`
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError>;
+    fn sparse_patterns(&self) -> Result<&SparsePatterns, WorkingCopyStateError>;
 
     /// Updates the patterns that decide which paths from the current tree
     /// should be checked out in the working copy.
@@ -144,9 +154,19 @@ pub trait LockedWorkingCopy: Any + Send {
     // to use sparse).
     async fn set_sparse_patterns(
         &mut self,
-        new_sparse_patterns: Vec<RepoPathBuf>,
+        new_sparse_patterns: SparsePatterns,
     ) -> Result<CheckoutStats, CheckoutError>;
 
+    /// See `WorkingCopy::force_tracked_paths()`
+    fn force_tracked_paths(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError>;
+
+    /// Updates the paths which are tracked even if they're ignored or too
+    /// large.
+    fn set_force_tracked_paths(
+        &mut self,
+        paths: Vec<RepoPathBuf>,
+    ) -> Result<(), WorkingCopyStateError>;
+
     /// Finish the modifications to the working copy by writing the updated
     /// states to disk. Returns the new (unlocked) working copy.
     async fn finish(
@@ -167,6 +187,55 @@ impl dyn LockedWorkingCopy {
     }
 }
 
+/// Patterns that decide which paths from the current tree should be checked
+/// out in the working copy.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SparsePatterns {
+    /// Path prefixes to check out. An empty list means that no paths should be
+    /// checked out. A single `RepoPath::root()` entry means that all files
+    /// should be checked out.
+    pub included: Vec<RepoPathBuf>,
+    /// Path prefixes not to check out even if they're under an included
+    /// prefix.
+    pub excluded: Vec<RepoPathBuf>,
+    /// Whether to also check out the files directly in the ancestor
+    /// directories of the included prefixes, like Git's cone mode.
+    pub cone: bool,
+}
+
+impl SparsePatterns {
+    /// Creates patterns that check out the given path prefixes.
+    pub fn new(included: Vec<RepoPathBuf>) -> Self {
+        Self {
+            included,
+            excluded: vec![],
+            cone: false,
+        }
+    }
+
+    /// Creates patterns that check out all files.
+    pub fn full() -> Self {
+        Self::new(vec![RepoPathBuf::root()])
+    }
+
+    /// Creates matcher for the paths to be checked out.
+    pub fn to_matcher(&self) -> Box<dyn Matcher> {
+        let included: Box<dyn Matcher> = if self.included.is_empty() {
+            Box::new(NothingMatcher)
+        } else if self.cone {
+            Box::new(ConeMatcher::new(&self.included))
+        } else {
+            Box::new(PrefixMatcher::new(&self.included))
+        };
+        if self.excluded.is_empty() {
+            included
+        } else {
+            let excluded = PrefixMatcher::new(&self.excluded);
+            Box::new(DifferenceMatcher::new(included, excluded))
+        }
+    }
+}
+
 /// An error while snapshotting the working copy.
 #[derive(Debug, Error)]
 pub enum SnapshotError {
@@ -216,8 +285,8 @@ pub struct SnapshotOptions<'a> {
     // because the TreeState may be long-lived if the library is used in a
     // long-lived process.
     pub base_ignores: Arc<GitIgnoreFile>,
-    /// A callback for the UI to display progress.
-    pub progress: Option<&'a SnapshotProgress<'a>>,
+    /// Receiver of progress updates, e.g. for the UI to display progress.
+    pub progress: Option<&'a dyn Progress>,
     /// For new files that are not already tracked, start tracking them if they
     /// match this.
     pub start_tracking_matcher: &'a dyn Matcher,
@@ -232,8 +301,13 @@ pub struct SnapshotOptions<'a> {
     pub max_new_file_size: u64,
 }
 
-/// A callback for getting progress updates.
-pub type SnapshotProgress<'a> = dyn Fn(&RepoPath) + 'a + Sync;
+/// Options used when checking out a commit in the working copy. Some of them
+/// may be ignored by some `WorkingCopy` implementations.
+#[derive(Clone, Default)]
+pub struct CheckoutOptions<'a> {
+    /// Receiver of progress updates, e.g. for the UI to display progress.
+    pub progress: Option<&'a dyn Progress>,
+}
 
 /// Stats about a snapshot operation on a working copy.
 #[derive(Clone, Debug, Default)]
@@ -254,6 +328,12 @@ pub enum UntrackedReason {
     },
     /// File does not match the fileset specified in snapshot.auto-track.
     FileNotAutoTracked,
+    /// File differs only in case from another path in the working-copy
+    /// commit, which is rejected by the configured case collision policy.
+    CaseCollision {
+        /// The file or directory path that the file collides with.
+        other_path: RepoPathBuf,
+    },
 }
 
 /// Stats about a checkout operation on a working copy. All "files" mentioned
@@ -271,6 +351,23 @@ pub struct CheckoutStats {
     /// working copy but were skipped because there was an untracked (probably
     /// ignored) file in its place.
     pub skipped_files: u32,
+    /// Paths that differ only in case from other paths, and which were newly
+    /// skipped or checked out under another name. Skipped paths stay
+    /// unchanged when the working copy is snapshotted.
+    pub case_collisions: Vec<CaseCollision>,
+}
+
+/// A path that can't be checked out alongside another path which differs only
+/// in case, such as `Foo.rs` and `foo.rs`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CaseCollision {
+    /// The path that collides.
+    pub path: RepoPathBuf,
+    /// The file or directory path that `path` collides with.
+    pub other_path: RepoPathBuf,
+    /// The path that `path` is checked out as instead, or `None` if it's not
+    /// checked out.
+    pub checked_out_as: Option<RepoPathBuf>,
 }
 
 /// The working-copy checkout failed.
@@ -298,6 +395,10 @@ pub enum CheckoutError {
         /// The reserved path component.
         name: &'static str,
     },
+    /// Paths differ only in case from other paths, which is rejected by the
+    /// configured case collision policy.
+    #[error("Some paths differ only in case from other paths")]
+    CaseCollision(Vec<CaseCollision>),
     /// Reading or writing from the commit backend failed.
     #[error("Internal backend error")]
     InternalBackendError(#[from] BackendError),
@@ -35,6 +35,7 @@ use crate::local_working_copy::LocalWorkingCopyFactory;
 use crate::merged_tree::MergedTree;
 use crate::op_heads_store::OpHeadsStoreError;
 use crate::op_store::OperationId;
+use crate::plugin_backend::PluginBackend;
 use crate::ref_name::WorkspaceName;
 use crate::ref_name::WorkspaceNameBuf;
 use crate::repo::BackendInitializer;
@@ -56,6 +57,7 @@ use crate::signing::Signer;
 use crate::simple_backend::SimpleBackend;
 use crate::transaction::TransactionCommitError;
 use crate::working_copy::CheckoutError;
+use crate::working_copy::CheckoutOptions;
 use crate::working_copy::CheckoutStats;
 use crate::working_copy::LockedWorkingCopy;
 use crate::working_copy::WorkingCopy;
@@ -71,6 +73,8 @@ pub enum WorkspaceInitError {
     #[error(transparent)]
     CheckOutCommit(#[from] CheckOutCommitError),
     #[error(transparent)]
+    CheckOut(#[from] CheckoutError),
+    #[error(transparent)]
     WorkingCopyState(#[from] WorkingCopyStateError),
     #[error(transparent)]
     Path(#[from] PathError),
@@ -126,19 +130,25 @@ fn create_jj_dir(workspace_root: &Path) -> Result<PathBuf, WorkspaceInitError> {
     }
 }
 
+#[expect(clippy::type_complexity)]
 fn init_working_copy(
     repo: &Arc<ReadonlyRepo>,
     workspace_root: &Path,
     jj_dir: &Path,
     working_copy_factory: &dyn WorkingCopyFactory,
     workspace_name: WorkspaceNameBuf,
-) -> Result<(Box<dyn WorkingCopy>, Arc<ReadonlyRepo>), WorkspaceInitError> {
+    parent: &Commit,
+    ephemeral: bool,
+) -> Result<(Box<dyn WorkingCopy>, Arc<ReadonlyRepo>, Commit), WorkspaceInitError> {
     let working_copy_state_path = jj_dir.join("working_copy");
     std::fs::create_dir(&working_copy_state_path).context(&working_copy_state_path)?;
 
     let mut tx = repo.start_transaction();
-    tx.repo_mut()
-        .check_out(workspace_name.clone(), &repo.store().root_commit())?;
+    let wc_commit = tx.repo_mut().check_out(workspace_name.clone(), parent)?;
+    if ephemeral {
+        tx.repo_mut()
+            .set_ephemeral_workspace(workspace_name.clone(), true);
+    }
     let repo = tx.commit(format!("add workspace '{}'", workspace_name.as_symbol()))?;
 
     let working_copy = working_copy_factory.init_working_copy(
@@ -151,7 +161,7 @@ fn init_working_copy(
     )?;
     let working_copy_type_path = working_copy_state_path.join("type");
     fs::write(&working_copy_type_path, working_copy.name()).context(&working_copy_type_path)?;
-    Ok((working_copy, repo))
+    Ok((working_copy, repo, wc_commit))
 }
 
 impl Workspace {
@@ -194,6 +204,39 @@ impl Workspace {
         Self::init_with_backend(user_settings, workspace_root, backend_initializer, signer)
     }
 
+    /// Initializes a workspace with a new simple backend which encrypts the
+    /// objects it stores.
+    pub fn init_simple_encrypted(
+        user_settings: &UserSettings,
+        workspace_root: &Path,
+    ) -> Result<(Self, Arc<ReadonlyRepo>), WorkspaceInitError> {
+        let backend_initializer: &BackendInitializer = &|settings, store_path| {
+            Ok(Box::new(SimpleBackend::init_encrypted(
+                settings, store_path,
+            )?))
+        };
+        let signer = Signer::from_settings(user_settings)?;
+        Self::init_with_backend(user_settings, workspace_root, backend_initializer, signer)
+    }
+
+    /// Initializes a workspace with a new backend which stores objects through
+    /// the backend plugin configured in `backend-plugins.<plugin_name>`.
+    pub fn init_with_backend_plugin(
+        user_settings: &UserSettings,
+        workspace_root: &Path,
+        plugin_name: &str,
+    ) -> Result<(Self, Arc<ReadonlyRepo>), WorkspaceInitError> {
+        let backend_initializer: &BackendInitializer = &|settings, store_path| {
+            Ok(Box::new(PluginBackend::init(
+                settings,
+                store_path,
+                plugin_name,
+            )?))
+        };
+        let signer = Signer::from_settings(user_settings)?;
+        Self::init_with_backend(user_settings, workspace_root, backend_initializer, signer)
+    }
+
     /// Initializes a workspace with a new Git backend and bare Git repo in
     /// `.jj/repo/store/git`.
     #[cfg(feature = "git")]
@@ -312,12 +355,14 @@ impl Workspace {
                 RepoInitError::OpHeadsStore(err) => WorkspaceInitError::OpHeadsStore(err),
                 RepoInitError::Path(err) => WorkspaceInitError::Path(err),
             })?;
-            let (working_copy, repo) = init_working_copy(
+            let (working_copy, repo, _) = init_working_copy(
                 &repo,
                 workspace_root,
                 &jj_dir,
                 working_copy_factory,
                 workspace_name,
+                &repo.store().root_commit(),
+                false,
             )?;
             let repo_loader = repo.loader().clone();
             let workspace = Self::new(workspace_root, repo_dir, working_copy, repo_loader)?;
@@ -355,6 +400,54 @@ impl Workspace {
         working_copy_factory: &dyn WorkingCopyFactory,
         workspace_name: WorkspaceNameBuf,
     ) -> Result<(Self, Arc<ReadonlyRepo>), WorkspaceInitError> {
+        Self::init_workspace_with_existing_repo_impl(
+            workspace_root,
+            repo_path,
+            repo,
+            working_copy_factory,
+            workspace_name,
+            &repo.store().root_commit(),
+            false,
+        )
+        .map(|(workspace, repo, _)| (workspace, repo))
+    }
+
+    /// Creates a throwaway workspace with a new working-copy commit on top of
+    /// `parent`, and updates the files on disk.
+    ///
+    /// The workspace is recorded as ephemeral in the view, so its working-copy
+    /// commit is abandoned when the workspace is removed, even if it has
+    /// changes.
+    pub fn init_ephemeral_workspace(
+        workspace_root: &Path,
+        repo_path: &Path,
+        repo: &Arc<ReadonlyRepo>,
+        working_copy_factory: &dyn WorkingCopyFactory,
+        workspace_name: WorkspaceNameBuf,
+        parent: &Commit,
+    ) -> Result<(Self, Arc<ReadonlyRepo>), WorkspaceInitError> {
+        let (mut workspace, repo, wc_commit) = Self::init_workspace_with_existing_repo_impl(
+            workspace_root,
+            repo_path,
+            repo,
+            working_copy_factory,
+            workspace_name,
+            parent,
+            true,
+        )?;
+        workspace.check_out(repo.op_id().clone(), None, &wc_commit)?;
+        Ok((workspace, repo))
+    }
+
+    fn init_workspace_with_existing_repo_impl(
+        workspace_root: &Path,
+        repo_path: &Path,
+        repo: &Arc<ReadonlyRepo>,
+        working_copy_factory: &dyn WorkingCopyFactory,
+        workspace_name: WorkspaceNameBuf,
+        parent: &Commit,
+        ephemeral: bool,
+    ) -> Result<(Self, Arc<ReadonlyRepo>, Commit), WorkspaceInitError> {
         let jj_dir = create_jj_dir(workspace_root)?;
 
         let repo_dir = dunce::canonicalize(repo_path).context(repo_path)?;
@@ -363,12 +456,14 @@ impl Workspace {
         let repo_file_path = jj_dir.join("repo");
         fs::write(&repo_file_path, repo_dir_bytes).context(&repo_file_path)?;
 
-        let (working_copy, repo) = init_working_copy(
+        let (working_copy, repo, wc_commit) = init_working_copy(
             repo,
             workspace_root,
             &jj_dir,
             working_copy_factory,
             workspace_name,
+            parent,
+            ephemeral,
         )?;
         let workspace = Self::new(
             workspace_root,
@@ -376,7 +471,7 @@ impl Workspace {
             working_copy,
             repo.loader().clone(),
         )?;
-        Ok((workspace, repo))
+        Ok((workspace, repo, wc_commit))
     }
 
     pub fn load(
@@ -430,6 +525,18 @@ impl Workspace {
         operation_id: OperationId,
         old_tree: Option<&MergedTree>,
         commit: &Commit,
+    ) -> Result<CheckoutStats, CheckoutError> {
+        self.check_out_with_options(operation_id, old_tree, commit, &CheckoutOptions::default())
+    }
+
+    /// Like `check_out()`, but with the given options such as the receiver of
+    /// progress updates.
+    pub fn check_out_with_options(
+        &mut self,
+        operation_id: OperationId,
+        old_tree: Option<&MergedTree>,
+        commit: &Commit,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         let mut locked_ws = self.start_working_copy_mutation()?;
         // Check if the current working-copy commit has changed on disk compared to what
@@ -442,7 +549,10 @@ impl Workspace {
         {
             return Err(CheckoutError::ConcurrentCheckout);
         }
-        let stats = locked_ws.locked_wc().check_out(commit).block_on()?;
+        let stats = locked_ws
+            .locked_wc()
+            .check_out(commit, options)
+            .block_on()?;
         locked_ws
             .finish(operation_id)
             .map_err(|err| CheckoutError::Other {
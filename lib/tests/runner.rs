@@ -18,6 +18,7 @@ mod test_evolution_predecessors;
 mod test_fix;
 mod test_git;
 mod test_git_backend;
+mod test_gitattributes;
 mod test_gpg;
 mod test_id_prefix;
 mod test_index;
@@ -31,13 +32,16 @@ mod test_merge_trees;
 mod test_merged_tree;
 mod test_mut_repo;
 mod test_operations;
+mod test_protected_bookmarks;
 mod test_refs;
+mod test_rerere;
 mod test_revset;
 mod test_revset_optimized;
 mod test_rewrite;
 mod test_rewrite_duplicate;
 mod test_rewrite_transform;
 mod test_signing;
+mod test_simple_backend;
 mod test_ssh_signing;
 mod test_view;
 mod test_workspace;
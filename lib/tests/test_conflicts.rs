@@ -21,9 +21,11 @@ use jj_lib::conflicts::ConflictMaterializeOptions;
 use jj_lib::conflicts::MIN_CONFLICT_MARKER_LEN;
 use jj_lib::conflicts::choose_materialized_conflict_marker_len;
 use jj_lib::conflicts::extract_as_single_hunk;
+use jj_lib::conflicts::extract_as_single_hunk_with_limit;
 use jj_lib::conflicts::materialize_merge_result_to_bytes;
 use jj_lib::conflicts::parse_conflict;
 use jj_lib::conflicts::update_from_content;
+use jj_lib::diff::DiffAlgorithm;
 use jj_lib::files::FileMergeHunkLevel;
 use jj_lib::merge::Merge;
 use jj_lib::merge::SameChange;
@@ -1729,6 +1731,33 @@ fn test_parse_conflict_mixed_header_styles() {
     );
 }
 
+#[test]
+fn test_extract_as_single_hunk_with_limit() {
+    let test_repo = TestRepo::init();
+    let store = test_repo.repo.store();
+
+    let path = repo_path("file");
+    let base_file_id = testutils::write_file(store, path, "base\n");
+    let left_file_id = testutils::write_file(store, path, "left\n");
+    let conflict =
+        Merge::from_removes_adds(vec![Some(base_file_id)], vec![Some(left_file_id), None]);
+    let extract = |max_size| {
+        extract_as_single_hunk_with_limit(&conflict, store, path, max_size)
+            .block_on()
+            .unwrap()
+    };
+    assert_eq!(
+        extract(5),
+        Some(Merge::from_vec(vec![
+            "left\n".into(),
+            "base\n".into(),
+            "".into()
+        ]))
+    );
+    // Nothing is returned if any of the files is larger than the limit
+    assert_eq!(extract(4), None);
+}
+
 #[test]
 fn test_update_conflict_from_content() {
     let test_repo = TestRepo::init();
@@ -2515,6 +2544,7 @@ fn materialize_conflict_string_with_labels(
         merge: MergeOptions {
             hunk_level: FileMergeHunkLevel::Line,
             same_change: SameChange::Accept,
+            diff_algorithm: DiffAlgorithm::Histogram,
         },
     };
     String::from_utf8(
@@ -16,10 +16,14 @@ use std::fs::File;
 use std::io::Write as _;
 
 use bstr::ByteSlice as _;
+use jj_lib::backend::TreeValue;
 use jj_lib::config::ConfigLayer;
 use jj_lib::config::ConfigSource;
+use jj_lib::local_working_copy::EolConversionRule;
+use jj_lib::merged_tree::MergedTree;
 use jj_lib::repo::Repo as _;
 use jj_lib::repo::StoreFactories;
+use jj_lib::repo_path::RepoPath;
 use jj_lib::rewrite::merge_commit_trees;
 use jj_lib::settings::UserSettings;
 use jj_lib::workspace::Workspace;
@@ -577,9 +581,137 @@ fn test_eol_conversion_checkout(
     // When we take a snapshot now, the tree may not be clean, because the EOL our
     // snapshot creates may not align with what is currently used in store. e.g.
     // with working-copy.eol-conversion = "input-output", the test-eol-file may have
-    // CRLF line endings in the store, but the snapshot will change the EOL to LF,
-    // hence the diff.
+    // mixed line endings in the store, but the checkout will change all EOLs to
+    // CRLF, hence the diff.
 
     assert!(std::fs::exists(&file_disk_path).unwrap());
     std::fs::read(&file_disk_path).unwrap()
 }
+
+fn read_stored_file(tree: &MergedTree, path: &RepoPath) -> Vec<u8> {
+    let value = tree.path_value(path).unwrap().into_resolved().unwrap();
+    let Some(TreeValue::File { id, .. }) = value else {
+        panic!("{path:?} should be a file");
+    };
+    testutils::read_file(tree.store(), path, &id)
+}
+
+#[test]
+fn test_eol_conversion_rules() {
+    let user_settings = base_user_settings_with_extra_configs(
+        r#"
+        working-copy.eol-conversion = "input"
+        [[working-copy.eol-conversion-rules]]
+        patterns = ["*.bat"]
+        eol-conversion = "input-output"
+        [[working-copy.eol-conversion-rules]]
+        patterns = ["data/**"]
+        eol-conversion = "none"
+        "#,
+    );
+    let mut test_workspace =
+        TestWorkspace::init_with_backend_and_settings(TestRepoBackend::Git, &user_settings);
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let bat_path = repo_path("sub/run.bat");
+    let text_path = repo_path("sub/file.txt");
+    let data_path = repo_path("data/file.txt");
+    for path in [bat_path, text_path, data_path] {
+        testutils::write_working_copy_file(&workspace_root, path, CRLF_FILE_CONTENT);
+    }
+
+    // Only the files matching the "none" rule are stored as is
+    let tree = test_workspace.snapshot().unwrap();
+    assert_eq!(read_stored_file(&tree, bat_path), LF_FILE_CONTENT);
+    assert_eq!(read_stored_file(&tree, text_path), LF_FILE_CONTENT);
+    assert_eq!(read_stored_file(&tree, data_path), CRLF_FILE_CONTENT);
+
+    // Only the files matching the "input-output" rule get CRLF line endings on
+    // checkout
+    let commit = commit_with_tree(test_workspace.repo.store(), tree.clone());
+    let root_commit = test_workspace.repo.store().root_commit();
+    let op_id = test_workspace.repo.op_id().clone();
+    test_workspace
+        .workspace
+        .check_out(op_id.clone(), None, &root_commit)
+        .unwrap();
+    test_workspace
+        .workspace
+        .check_out(op_id, None, &commit)
+        .unwrap();
+    let read_disk_file =
+        |path: &RepoPath| std::fs::read(path.to_fs_path_unchecked(&workspace_root));
+    assert_eq!(read_disk_file(bat_path).unwrap(), CRLF_FILE_CONTENT);
+    assert_eq!(read_disk_file(text_path).unwrap(), LF_FILE_CONTENT);
+    assert_eq!(read_disk_file(data_path).unwrap(), CRLF_FILE_CONTENT);
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_tree_eq!(new_tree, tree, "The working copy should be clean.");
+}
+
+#[test]
+fn test_eol_conversion_invalid_rule() {
+    let mut config = base_user_config();
+    config.add_layer(
+        ConfigLayer::parse(
+            ConfigSource::User,
+            r#"
+            [[working-copy.eol-conversion-rules]]
+            patterns = ["[invalid"]
+            eol-conversion = "input"
+            "#,
+        )
+        .unwrap(),
+    );
+    let user_settings = UserSettings::from_config(config).unwrap();
+    assert!(EolConversionRule::try_from_settings(&user_settings).is_err());
+}
+
+#[test]
+fn test_eol_conversion_keeps_stored_crlf() {
+    // Commit a file with CRLF line endings and one with LF line endings without
+    // EOL conversion, like a git user without core.autocrlf would.
+    let no_eol_conversion_settings =
+        base_user_settings_with_extra_configs("working-copy.eol-conversion = \"none\"\n");
+    let mut test_workspace = TestWorkspace::init_with_backend_and_settings(
+        TestRepoBackend::Git,
+        &no_eol_conversion_settings,
+    );
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let crlf_path = repo_path("crlf-file");
+    let lf_path = repo_path("lf-file");
+    testutils::write_working_copy_file(&workspace_root, crlf_path, CRLF_FILE_CONTENT);
+    testutils::write_working_copy_file(&workspace_root, lf_path, LF_FILE_CONTENT);
+    let tree = test_workspace.snapshot().unwrap();
+    let commit = commit_with_tree(test_workspace.repo.store(), tree);
+
+    let user_settings =
+        base_user_settings_with_extra_configs("working-copy.eol-conversion = \"input\"\n");
+    test_workspace.workspace = Workspace::load(
+        &user_settings,
+        &workspace_root,
+        &StoreFactories::default(),
+        &default_working_copy_factories(),
+    )
+    .expect("Failed to reload the workspace");
+    let commit = test_workspace
+        .workspace
+        .repo_loader()
+        .store()
+        .get_commit(commit.id())
+        .unwrap();
+    test_workspace
+        .workspace
+        .check_out(test_workspace.repo.op_id().clone(), None, &commit)
+        .unwrap();
+
+    // Editing the file stored with CRLF line endings keeps them. The other file
+    // is still normalized.
+    let appended_content = [CRLF_FILE_CONTENT, b"dddddd\r\n"].concat();
+    testutils::write_working_copy_file(&workspace_root, crlf_path, &appended_content);
+    testutils::write_working_copy_file(&workspace_root, lf_path, &appended_content);
+    let tree = test_workspace.snapshot().unwrap();
+    assert_eq!(read_stored_file(&tree, crlf_path), appended_content);
+    assert_eq!(
+        read_stored_file(&tree, lf_path),
+        appended_content.replace(b"\r\n", b"\n")
+    );
+}
@@ -14,17 +14,23 @@
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::time::Duration;
+use std::time::SystemTime;
 
 use jj_lib::backend::CommitId;
 use jj_lib::backend::FileId;
 use jj_lib::fix::FileFixer;
 use jj_lib::fix::FileToFix;
+use jj_lib::fix::FixCache;
+use jj_lib::fix::FixCacheKey;
 use jj_lib::fix::FixError;
+use jj_lib::fix::FixedFile;
 use jj_lib::fix::ParallelFileFixer;
 use jj_lib::fix::fix_files;
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::store::Store;
 use jj_lib::transaction::Transaction;
 use pollster::FutureExt as _;
@@ -52,11 +58,11 @@ impl FileFixer for TestFileFixer {
         &mut self,
         store: &Store,
         files_to_fix: &'a HashSet<FileToFix>,
-    ) -> Result<HashMap<&'a FileToFix, FileId>, FixError> {
+    ) -> Result<HashMap<&'a FileToFix, FixedFile>, FixError> {
         let mut changed_files = HashMap::new();
         for file_to_fix in files_to_fix {
             if let Some(new_file_id) = fix_file(store, file_to_fix)? {
-                changed_files.insert(file_to_fix, new_file_id);
+                changed_files.insert(file_to_fix, new_file_id.into());
             }
         }
         Ok(changed_files)
@@ -91,6 +97,43 @@ fn fix_file(store: &Store, file_to_fix: &FileToFix) -> Result<Option<FileId>, Fi
     }
 }
 
+// Reads the file from store. If the file starts with "move:", it's moved to a
+// path with a ".moved" suffix. If the file starts with "split:", the rest of
+// the content is moved to a new file with a ".split" suffix. Otherwise returns
+// None.
+fn move_or_split_file(
+    store: &Store,
+    file_to_fix: &FileToFix,
+) -> Result<Option<FixedFile>, FixError> {
+    let old_content = read_file(store, &file_to_fix.repo_path, &file_to_fix.file_id);
+    let with_suffix = |suffix: &str| {
+        RepoPathBuf::from_internal_string(format!(
+            "{}{suffix}",
+            file_to_fix.repo_path.as_internal_file_string()
+        ))
+        .unwrap()
+    };
+    let write_file = |path: &RepoPathBuf, content: &[u8]| {
+        store.write_file(path, &mut &*content).block_on().unwrap()
+    };
+    if old_content.starts_with(b"move:") {
+        Ok(Some(FixedFile {
+            new_path: Some(with_suffix(".moved")),
+            ..FixedFile::default()
+        }))
+    } else if let Some(rest) = old_content.strip_prefix(b"split:") {
+        let new_path = with_suffix(".split");
+        let new_file_id = write_file(&new_path, rest);
+        Ok(Some(FixedFile {
+            file_id: Some(write_file(&file_to_fix.repo_path, b"split")),
+            new_files: vec![(new_path, new_file_id)],
+            ..FixedFile::default()
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
 fn create_commit(tx: &mut Transaction, parents: Vec<CommitId>, tree: MergedTree) -> CommitId {
     tx.repo_mut()
         .new_commit(parents, tree)
@@ -538,3 +581,155 @@ fn test_fix_multiple_revisions() {
         .unwrap();
     assert_tree_eq!(new_commit_a.tree(), expected_tree_a);
 }
+
+#[test]
+fn test_fix_moves_file() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let path1 = repo_path("file1");
+    let path2 = repo_path("file2");
+    let moved_path1 = repo_path("file1.moved");
+    let tree1 = create_tree(repo, &[(path1, "move:content")]);
+    let commit_a = create_commit(&mut tx, vec![repo.store().root_commit_id().clone()], tree1);
+    let tree2 = create_tree(repo, &[(path1, "move:content"), (path2, "content")]);
+    let commit_b = create_commit(&mut tx, vec![commit_a.clone()], tree2);
+
+    let root_commits = vec![commit_a.clone()];
+    let mut parallel_fixer = ParallelFileFixer::new(move_or_split_file);
+
+    let summary = fix_files(
+        root_commits,
+        &EverythingMatcher,
+        false,
+        tx.repo_mut(),
+        &mut parallel_fixer,
+    )
+    .block_on()
+    .unwrap();
+
+    assert_eq!(summary.rewrites.len(), 2);
+    assert_eq!(summary.num_checked_commits, 2);
+    assert_eq!(summary.num_fixed_commits, 2);
+
+    // The file is also moved in the descendant
+    let new_commit_a = repo
+        .store()
+        .get_commit(summary.rewrites.get(&commit_a).unwrap())
+        .unwrap();
+    let expected_tree_a = create_tree(repo, &[(moved_path1, "move:content")]);
+    assert_tree_eq!(new_commit_a.tree(), expected_tree_a);
+    let new_commit_b = repo
+        .store()
+        .get_commit(summary.rewrites.get(&commit_b).unwrap())
+        .unwrap();
+    let expected_tree_b = create_tree(repo, &[(moved_path1, "move:content"), (path2, "content")]);
+    assert_tree_eq!(new_commit_b.tree(), expected_tree_b);
+}
+
+#[test]
+fn test_fix_creates_file() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let path1 = repo_path("file1");
+    let split_path1 = repo_path("file1.split");
+    let tree1 = create_tree(repo, &[(path1, "split:content")]);
+    let commit_a = create_commit(&mut tx, vec![repo.store().root_commit_id().clone()], tree1);
+
+    let root_commits = vec![commit_a.clone()];
+    let mut parallel_fixer = ParallelFileFixer::new(move_or_split_file);
+
+    let summary = fix_files(
+        root_commits,
+        &EverythingMatcher,
+        false,
+        tx.repo_mut(),
+        &mut parallel_fixer,
+    )
+    .block_on()
+    .unwrap();
+
+    assert_eq!(summary.num_fixed_commits, 1);
+    let new_commit_a = repo
+        .store()
+        .get_commit(summary.rewrites.get(&commit_a).unwrap())
+        .unwrap();
+    let expected_tree_a = create_tree(repo, &[(path1, "split"), (split_path1, "content")]);
+    assert_tree_eq!(new_commit_a.tree(), expected_tree_a);
+}
+
+#[test]
+fn test_fix_path_conflict() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let path1 = repo_path("file1");
+    let moved_path1 = repo_path("file1.moved");
+    let tree1 = create_tree(repo, &[(path1, "move:content"), (moved_path1, "existing")]);
+    let commit_a = create_commit(&mut tx, vec![repo.store().root_commit_id().clone()], tree1);
+
+    let root_commits = vec![commit_a.clone()];
+    let mut parallel_fixer = ParallelFileFixer::new(move_or_split_file);
+
+    let result = fix_files(
+        root_commits,
+        &EverythingMatcher,
+        false,
+        tx.repo_mut(),
+        &mut parallel_fixer,
+    )
+    .block_on();
+    let error = result.err().unwrap();
+    assert_eq!(
+        error.to_string(),
+        format!("Cannot write fixed file to file1.moved in commit {commit_a}: path already exists")
+    );
+}
+
+#[test]
+fn test_fix_cache() {
+    let temp_dir = testutils::new_temp_dir();
+    let cache = FixCache::new(temp_dir.path().join("fix_cache"));
+
+    let key1 = FixCacheKey::new([&b"tool"[..], b"content"]);
+    let key2 = FixCacheKey::new([&b"tool"[..], b"other content"]);
+    // Parts are delimited, so moving bytes between parts changes the key.
+    assert_ne!(key1, FixCacheKey::new([&b"toolc"[..], b"ontent"]));
+    assert_eq!(key1, FixCacheKey::new([&b"tool"[..], b"content"]));
+
+    assert_eq!(cache.get(&key1).unwrap(), None);
+    cache.insert(&key1, b"fixed content").unwrap();
+    assert_eq!(
+        cache.get(&key1).unwrap().as_deref(),
+        Some(&b"fixed content"[..])
+    );
+    assert_eq!(cache.get(&key2).unwrap(), None);
+
+    // Entries can be overwritten, for example by a concurrent `jj fix`.
+    cache.insert(&key1, b"fixed content").unwrap();
+    assert_eq!(
+        cache.get(&key1).unwrap().as_deref(),
+        Some(&b"fixed content"[..])
+    );
+
+    // Recently used entries are kept, and older ones are removed.
+    cache
+        .gc(SystemTime::now() - Duration::from_secs(3600))
+        .unwrap();
+    assert!(cache.get(&key1).unwrap().is_some());
+    cache
+        .gc(SystemTime::now() + Duration::from_secs(3600))
+        .unwrap();
+    assert_eq!(cache.get(&key1).unwrap(), None);
+}
+
+#[test]
+fn test_fix_cache_gc_missing_dir() {
+    let temp_dir = testutils::new_temp_dir();
+    let cache = FixCache::new(temp_dir.path().join("fix_cache"));
+    cache.gc(SystemTime::now()).unwrap();
+}
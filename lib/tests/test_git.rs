@@ -59,6 +59,7 @@ use jj_lib::git::IgnoredRefspecs;
 use jj_lib::git::expand_default_fetch_refspecs;
 use jj_lib::git::expand_fetch_refspecs;
 use jj_lib::git_backend::GitBackend;
+use jj_lib::git_notes::GitNotes;
 use jj_lib::hex_util;
 use jj_lib::index::ResolvedChangeTargets;
 use jj_lib::merge::Merge;
@@ -165,8 +166,9 @@ fn fetch_with(
         expand_fetch_refspecs(remote, bookmark_expr).expect("ref patterns should be valid");
     let callbacks = git::RemoteCallbacks::default();
     let depth = None;
+    let filter = None;
     let fetch_tags = None;
-    fetcher.fetch(remote, refspecs, callbacks, depth, fetch_tags)
+    fetcher.fetch(remote, refspecs, callbacks, depth, filter, fetch_tags)
 }
 
 fn push_status_rejected_references(push_stats: GitPushStats) -> Vec<GitRefNameBuf> {
@@ -1803,6 +1805,51 @@ fn test_export_refs_no_detach() {
     );
 }
 
+#[test]
+fn test_create_annotated_tag() {
+    let test_data = GitRepoData::create();
+    let git_repo = test_data.git_repo;
+    let mut tx = test_data.repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+    let commit = write_random_commit(mut_repo);
+    let tagger = Signature {
+        name: "Test User".to_owned(),
+        email: "test.user@example.com".to_owned(),
+        timestamp: Timestamp {
+            timestamp: MillisSinceEpoch(1_000_000),
+            tz_offset: 60,
+        },
+    };
+    git::create_annotated_tag(mut_repo, "v1".as_ref(), commit.id(), &tagger, "Release\n").unwrap();
+    let target = RefTarget::normal(commit.id().clone());
+    assert_eq!(mut_repo.get_local_tag("v1".as_ref()), target);
+    assert_eq!(mut_repo.get_git_ref("refs/tags/v1".as_ref()), target);
+
+    let tag = git_repo
+        .find_reference("refs/tags/v1")
+        .unwrap()
+        .id()
+        .object()
+        .unwrap()
+        .into_tag();
+    let tag = tag.decode().unwrap();
+    assert_eq!(tag.target(), git_id(&commit));
+    assert_eq!(tag.message, "Release\n");
+    assert_eq!(tag.tagger().unwrap().unwrap().name, "Test User");
+
+    // The annotated tag isn't overwritten by export
+    let stats = git::export_refs(mut_repo).unwrap();
+    assert!(stats.failed_tags.is_empty());
+    let git_ref = git_repo.find_reference("refs/tags/v1").unwrap();
+    assert_eq!(git_ref.id().object().unwrap().kind, gix::object::Kind::Tag);
+
+    // Existing tags can't be replaced
+    assert_matches!(
+        git::create_annotated_tag(mut_repo, "v1".as_ref(), commit.id(), &tagger, "Again\n"),
+        Err(git::GitCreateTagError::AlreadyExists(_))
+    );
+}
+
 #[test]
 fn test_export_refs_bookmark_changed() {
     // We can export a change to a bookmark
@@ -3878,8 +3925,9 @@ fn test_fetch_with_fetch_tags_override() {
             let refspecs = expand_fetch_refspecs(remote, StringExpression::all()).unwrap();
             let callbacks = git::RemoteCallbacks::default();
             let depth = None;
+            let filter = None;
             fetcher
-                .fetch(remote, refspecs, callbacks, depth, fetch_tags)
+                .fetch(remote, refspecs, callbacks, depth, filter, fetch_tags)
                 .unwrap();
             fetcher.import_refs().unwrap()
         };
@@ -5623,3 +5671,76 @@ fn test_set_remote_urls() {
         Some("git@example.com:repo/path3"),
     );
 }
+
+#[test]
+fn test_git_notes() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let git_repo = get_git_repo(repo);
+    let signature = repo.settings().signature();
+    let commit1 = empty_git_commit(&git_repo, "refs/heads/main", &[]);
+    let commit2 = empty_git_commit(&git_repo, "refs/heads/main", &[commit1]);
+    let commit_id1 = jj_id(commit1);
+    let commit_id2 = jj_id(commit2);
+
+    // A missing notes ref has no notes
+    let mut notes = GitNotes::load(repo.store(), "refs/notes/commits").unwrap();
+    assert_eq!(notes.get(&commit_id1).unwrap(), None);
+
+    assert!(
+        notes
+            .set(&commit_id1, Some(b"note 1\n"), &signature, "add")
+            .unwrap()
+    );
+    assert!(
+        notes
+            .set(&commit_id2, Some(b"note 2\n"), &signature, "add")
+            .unwrap()
+    );
+    // Setting the same note is a no-op
+    assert!(
+        !notes
+            .set(&commit_id2, Some(b"note 2\n"), &signature, "add")
+            .unwrap()
+    );
+    let notes = GitNotes::load(repo.store(), "refs/notes/commits").unwrap();
+    assert_eq!(notes.get(&commit_id1).unwrap(), Some(b"note 1\n".to_vec()));
+    assert_eq!(notes.get(&commit_id2).unwrap(), Some(b"note 2\n".to_vec()));
+    let notes_commit = git_repo
+        .find_reference("refs/notes/commits")
+        .unwrap()
+        .peel_to_commit()
+        .unwrap();
+    assert_eq!(notes_commit.parent_ids().count(), 1);
+
+    // Notes written by git with fanout can be read and replaced
+    let hex1 = commit_id1.hex();
+    testutils::git::add_commit(
+        &git_repo,
+        "refs/notes/ci",
+        &format!("{}/{}", &hex1[..2], &hex1[2..]),
+        b"passed\n",
+        "notes",
+        &[],
+    );
+    let mut notes = GitNotes::load(repo.store(), "refs/notes/ci").unwrap();
+    assert_eq!(notes.get(&commit_id1).unwrap(), Some(b"passed\n".to_vec()));
+    assert_eq!(notes.get(&commit_id2).unwrap(), None);
+    notes
+        .set(&commit_id1, Some(b"failed\n"), &signature, "edit")
+        .unwrap();
+    let notes_tree = git_repo
+        .find_reference("refs/notes/ci")
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .tree()
+        .unwrap();
+    assert!(notes_tree.find_entry(hex1.as_str()).is_some());
+    assert!(notes_tree.find_entry(&hex1[..2]).is_none());
+
+    // Removing notes
+    notes.set(&commit_id1, None, &signature, "remove").unwrap();
+    let notes = GitNotes::load(repo.store(), "refs/notes/ci").unwrap();
+    assert_eq!(notes.get(&commit_id1).unwrap(), None);
+}
@@ -14,6 +14,7 @@
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::iter;
 use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
@@ -23,6 +24,7 @@ use futures::executor::block_on_stream;
 use itertools::Itertools as _;
 use jj_lib::backend::CommitId;
 use jj_lib::backend::CopyRecord;
+use jj_lib::backend::TreeId;
 use jj_lib::commit::Commit;
 use jj_lib::conflict_labels::ConflictLabels;
 use jj_lib::git_backend::GitBackend;
@@ -38,8 +40,10 @@ use jj_lib::stacked_table::TableSegment as _;
 use jj_lib::stacked_table::TableStore;
 use jj_lib::store::Store;
 use jj_lib::transaction::Transaction;
+use jj_lib::tree_builder::TreeBuilder;
 use maplit::hashmap;
 use maplit::hashset;
+use pollster::FutureExt as _;
 use testutils::TestRepo;
 use testutils::TestRepoBackend;
 use testutils::assert_tree_eq;
@@ -47,11 +51,13 @@ use testutils::commit_with_tree;
 use testutils::create_random_commit;
 use testutils::create_single_tree;
 use testutils::create_tree;
+use testutils::git;
 use testutils::is_external_tool_installed;
 use testutils::repo_path;
 use testutils::repo_path_buf;
 use testutils::write_random_commit;
 use testutils::write_random_commit_with_parents;
+use tokio::io::AsyncReadExt as _;
 
 fn get_git_backend(repo: &Arc<ReadonlyRepo>) -> &GitBackend {
     repo.store().backend_impl().unwrap()
@@ -497,3 +503,202 @@ fn test_conflict_headers_roundtrip() {
         merged_tree
     );
 }
+
+#[test]
+fn test_merged_trees_written_on_commit() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let store = repo.store();
+    let git_repo = get_git_backend(repo).git_repo();
+    let has_tree = |tree: &MergedTree, dir: &RepoPath| {
+        let root_tree = tree.trees().block_on().unwrap().into_resolved().unwrap();
+        let sub_tree = root_tree.sub_tree_recursive(dir).unwrap().unwrap();
+        git_repo.has_object(gix::ObjectId::from_bytes_or_panic(sub_tree.id().as_bytes()))
+    };
+    let merge = |side1: &MergedTree, base: &MergedTree, side2: &MergedTree| {
+        MergedTree::merge(Merge::from_vec(vec![
+            (side1.clone(), "side 1".into()),
+            (base.clone(), "base".into()),
+            (side2.clone(), "side 2".into()),
+        ]))
+        .block_on()
+        .unwrap()
+    };
+
+    let dir_path = repo_path("dir");
+    let file1_path = repo_path("dir/file1");
+    let file2_path = repo_path("dir/file2");
+    let file3_path = repo_path("dir/file3");
+    let base = create_tree(repo, &[(file1_path, "1"), (file2_path, "2")]);
+    let side1 = create_tree(repo, &[(file1_path, "1 side 1"), (file2_path, "2")]);
+    let side2 = create_tree(repo, &[(file1_path, "1"), (file2_path, "2 side 2")]);
+    let side3 = create_tree(
+        repo,
+        &[(file1_path, "1"), (file2_path, "2"), (file3_path, "3")],
+    );
+    assert!(has_tree(&side1, RepoPath::root()));
+
+    // Merged trees are kept in memory, but can be read back
+    let scope = store.pending_trees_scope();
+    let merged = merge(&side1, &base, &side2);
+    assert!(!has_tree(&merged, RepoPath::root()));
+    assert!(!has_tree(&merged, dir_path));
+    store.clear_caches();
+    assert_tree_eq!(
+        merged,
+        create_tree(repo, &[(file1_path, "1 side 1"), (file2_path, "2 side 2")])
+    );
+
+    // An intermediate tree that isn't committed is never written
+    let intermediate = merge(&merged, &base, &side3);
+    let commit = commit_with_tree(store, merged.clone());
+    assert!(has_tree(&merged, RepoPath::root()));
+    assert!(has_tree(&merged, dir_path));
+    assert!(!has_tree(&intermediate, RepoPath::root()));
+    assert!(!has_tree(&intermediate, dir_path));
+    assert_tree_eq!(commit.tree(), merged);
+
+    // Writing a tree that refers to a pending tree writes the pending tree
+    let intermediate_tree = intermediate
+        .trees()
+        .block_on()
+        .unwrap()
+        .into_resolved()
+        .unwrap();
+    let file_value = side3
+        .path_value(file3_path)
+        .unwrap()
+        .into_resolved()
+        .unwrap();
+    let mut tree_builder = TreeBuilder::new(store.clone(), intermediate_tree.id().clone());
+    tree_builder.set(repo_path_buf("other/file"), file_value.unwrap());
+    let tree_id = tree_builder.write_tree().unwrap();
+    let tree = MergedTree::resolved(store.clone(), tree_id);
+    assert!(has_tree(&tree, RepoPath::root()));
+    assert!(has_tree(&tree, dir_path));
+    assert!(!has_tree(&intermediate, RepoPath::root()));
+
+    // Pending trees are discarded when the scope ends
+    let unused = merge(&side2, &base, &side3);
+    let unused_id = unused.tree_ids().as_resolved().unwrap().clone();
+    assert!(store.get_tree(RepoPathBuf::root(), &unused_id).is_ok());
+    drop(scope);
+    assert!(store.get_tree(RepoPathBuf::root(), &unused_id).is_err());
+
+    // Merged trees are written immediately outside of a scope
+    let merged = merge(&side2, &base, &side3);
+    assert!(has_tree(&merged, RepoPath::root()));
+    assert!(has_tree(&merged, dir_path));
+}
+
+#[test]
+fn test_merged_trees_in_transaction() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let git_repo = get_git_backend(repo).git_repo();
+    let has_tree = |tree_id: &TreeId| {
+        git_repo.has_object(gix::ObjectId::from_bytes_or_panic(tree_id.as_bytes()))
+    };
+
+    let file1_path = repo_path("file1");
+    let file2_path = repo_path("file2");
+    let base = create_tree(repo, &[(file1_path, "1"), (file2_path, "2")]);
+    let side1 = create_tree(repo, &[(file1_path, "1 side 1"), (file2_path, "2")]);
+    let side2 = create_tree(repo, &[(file1_path, "1"), (file2_path, "2 side 2")]);
+
+    // Trees computed in an abandoned transaction are never written
+    let tx = repo.start_transaction();
+    let merged = MergedTree::merge(Merge::from_vec(vec![
+        (side1.clone(), "side 1".into()),
+        (base.clone(), "base".into()),
+        (side2.clone(), "side 2".into()),
+    ]))
+    .block_on()
+    .unwrap();
+    let merged_id = merged.tree_ids().as_resolved().unwrap().clone();
+    assert!(!has_tree(&merged_id));
+    drop(tx);
+    assert!(!has_tree(&merged_id));
+    assert!(
+        repo.store()
+            .get_tree(RepoPathBuf::root(), &merged_id)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_write_large_file() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let store = repo.store();
+    let path = repo_path("file");
+
+    // Large enough to be spooled to disk before it's written to the object
+    // database
+    let contents = (0..300_000u32).flat_map(|i| i.to_le_bytes()).collect_vec();
+    let id = store
+        .write_file(path, &mut contents.as_slice())
+        .block_on()
+        .unwrap();
+    assert_eq!(
+        id.to_bytes(),
+        gix::objs::compute_hash(gix::hash::Kind::Sha1, gix::object::Kind::Blob, &contents)
+            .unwrap()
+            .as_bytes()
+    );
+
+    let mut read_contents = vec![];
+    store
+        .read_file(path, &id)
+        .block_on()
+        .unwrap()
+        .read_to_end(&mut read_contents)
+        .block_on()
+        .unwrap();
+    assert_eq!(read_contents, contents);
+}
+
+#[test]
+fn test_read_packed_large_file() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let store = repo.store();
+    let path = repo_path("file");
+
+    // Large enough to be streamed from the pack. The second file is likely
+    // stored as a delta of the first one, which has to be read into memory.
+    let contents1 = (0..300_000u32).flat_map(|i| i.to_le_bytes()).collect_vec();
+    let contents2 = [&contents1[..], b"more"].concat();
+    let ids = [&contents1, &contents2].map(|contents| {
+        store
+            .write_file(path, &mut contents.as_slice())
+            .block_on()
+            .unwrap()
+    });
+    let git_repo_path = get_git_backend(repo).git_repo_path();
+    git::pack_objects(
+        git_repo_path,
+        &ids.each_ref()
+            .map(|id| gix::ObjectId::from_bytes_or_panic(id.as_bytes())),
+    );
+    let hex = ids[0].hex();
+    assert!(
+        !git_repo_path
+            .join("objects")
+            .join(&hex[..2])
+            .join(&hex[2..])
+            .exists()
+    );
+
+    for (id, contents) in iter::zip(&ids, [contents1, contents2]) {
+        let mut read_contents = vec![];
+        store
+            .read_file(path, id)
+            .block_on()
+            .unwrap()
+            .read_to_end(&mut read_contents)
+            .block_on()
+            .unwrap();
+        assert_eq!(read_contents, contents);
+    }
+}
@@ -0,0 +1,340 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use jj_lib::backend::FileId;
+use jj_lib::backend::TreeValue;
+use jj_lib::commit::Commit;
+use jj_lib::config::ConfigLayer;
+use jj_lib::config::ConfigSource;
+use jj_lib::git_backend::GitBackend;
+use jj_lib::git_lfs::LfsObjectCache;
+use jj_lib::git_lfs::LfsPointer;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::settings::UserSettings;
+use testutils::TestRepoBackend;
+use testutils::TestWorkspace;
+use testutils::assert_tree_eq;
+use testutils::base_user_config;
+use testutils::commit_with_tree;
+use testutils::repo_path;
+
+fn user_settings_with(extra_settings: &str) -> UserSettings {
+    let mut config = base_user_config();
+    config.add_layer(ConfigLayer::parse(ConfigSource::User, extra_settings).unwrap());
+    UserSettings::from_config(config).unwrap()
+}
+
+fn stored_file_id(tree: &MergedTree, path: &RepoPath) -> FileId {
+    let value = tree.path_value(path).unwrap().into_resolved().unwrap();
+    let Some(TreeValue::File { id, .. }) = value else {
+        panic!("{path:?} should be a file");
+    };
+    id
+}
+
+fn read_stored_file(tree: &MergedTree, path: &RepoPath) -> Vec<u8> {
+    testutils::read_file(tree.store(), path, &stored_file_id(tree, path))
+}
+
+fn read_disk_file(workspace_root: &Path, path: &RepoPath) -> Vec<u8> {
+    std::fs::read(path.to_fs_path_unchecked(workspace_root)).unwrap()
+}
+
+/// Checks out the root commit and then `commit`, so that all files of `commit`
+/// are written again.
+fn check_out_again(test_workspace: &mut TestWorkspace, commit: &Commit) {
+    let root_commit = test_workspace.repo.store().root_commit();
+    let op_id = test_workspace.repo.op_id().clone();
+    test_workspace
+        .workspace
+        .check_out(op_id.clone(), None, &root_commit)
+        .unwrap();
+    test_workspace
+        .workspace
+        .check_out(op_id, None, commit)
+        .unwrap();
+}
+
+#[test]
+fn test_gitattributes_text_and_eol() {
+    let user_settings = user_settings_with(r#"working-copy.eol-conversion = "none""#);
+    let mut test_workspace =
+        TestWorkspace::init_with_backend_and_settings(TestRepoBackend::Git, &user_settings);
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let gitattributes_path = repo_path(".gitattributes");
+    let text_path = repo_path("file.txt");
+    let crlf_path = repo_path("sub/run.bat");
+    let other_path = repo_path("file.dat");
+    testutils::write_working_copy_file(
+        &workspace_root,
+        gitattributes_path,
+        "*.txt text\n*.bat eol=crlf\n",
+    );
+    for path in [text_path, crlf_path, other_path] {
+        testutils::write_working_copy_file(&workspace_root, path, "a\r\nb\r\n");
+    }
+
+    // Files with the text or eol attribute are normalized even though the EOL
+    // conversion is disabled.
+    let tree = test_workspace.snapshot().unwrap();
+    assert_eq!(read_stored_file(&tree, text_path), b"a\nb\n");
+    assert_eq!(read_stored_file(&tree, crlf_path), b"a\nb\n");
+    assert_eq!(read_stored_file(&tree, other_path), b"a\r\nb\r\n");
+
+    // Only the file with eol=crlf gets CRLF line endings on checkout
+    let commit = commit_with_tree(test_workspace.repo.store(), tree.clone());
+    check_out_again(&mut test_workspace, &commit);
+    assert_eq!(read_disk_file(&workspace_root, text_path), b"a\nb\n");
+    assert_eq!(read_disk_file(&workspace_root, crlf_path), b"a\r\nb\r\n");
+    assert_eq!(read_disk_file(&workspace_root, other_path), b"a\r\nb\r\n");
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_tree_eq!(new_tree, tree, "The working copy should be clean.");
+}
+
+#[test]
+fn test_gitattributes_unset_text() {
+    let user_settings = user_settings_with(r#"working-copy.eol-conversion = "input""#);
+    let mut test_workspace =
+        TestWorkspace::init_with_backend_and_settings(TestRepoBackend::Git, &user_settings);
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let gitattributes_path = repo_path("data/.gitattributes");
+    let data_path = repo_path("data/file");
+    let other_path = repo_path("file");
+    testutils::write_working_copy_file(&workspace_root, gitattributes_path, "* -text\n");
+    for path in [data_path, other_path] {
+        testutils::write_working_copy_file(&workspace_root, path, "a\r\nb\r\n");
+    }
+
+    let tree = test_workspace.snapshot().unwrap();
+    assert_eq!(read_stored_file(&tree, data_path), b"a\r\nb\r\n");
+    assert_eq!(read_stored_file(&tree, other_path), b"a\nb\n");
+}
+
+#[test]
+fn test_gitattributes_disabled() {
+    let user_settings = user_settings_with("working-copy.use-gitattributes = false");
+    let mut test_workspace =
+        TestWorkspace::init_with_backend_and_settings(TestRepoBackend::Git, &user_settings);
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let text_path = repo_path("file.txt");
+    testutils::write_working_copy_file(&workspace_root, repo_path(".gitattributes"), "* text\n");
+    testutils::write_working_copy_file(&workspace_root, text_path, "a\r\nb\r\n");
+
+    let tree = test_workspace.snapshot().unwrap();
+    assert_eq!(read_stored_file(&tree, text_path), b"a\r\nb\r\n");
+}
+
+#[test]
+fn test_gitattributes_ident() {
+    let mut test_workspace = TestWorkspace::init_with_backend(TestRepoBackend::Git);
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let ident_path = repo_path("file.c");
+    let other_path = repo_path("file.h");
+    testutils::write_working_copy_file(&workspace_root, repo_path(".gitattributes"), "*.c ident\n");
+    for path in [ident_path, other_path] {
+        testutils::write_working_copy_file(&workspace_root, path, "// $Id: old $\n");
+    }
+
+    // The keyword is collapsed when snapshotting
+    let tree = test_workspace.snapshot().unwrap();
+    assert_eq!(read_stored_file(&tree, ident_path), b"// $Id$\n");
+    assert_eq!(read_stored_file(&tree, other_path), b"// $Id: old $\n");
+
+    // ...and expanded to the file id on checkout
+    let commit = commit_with_tree(test_workspace.repo.store(), tree.clone());
+    check_out_again(&mut test_workspace, &commit);
+    let id = stored_file_id(&tree, ident_path);
+    assert_eq!(
+        read_disk_file(&workspace_root, ident_path),
+        format!("// $Id: {} $\n", id.hex()).as_bytes()
+    );
+    assert_eq!(
+        read_disk_file(&workspace_root, other_path),
+        b"// $Id: old $\n"
+    );
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_tree_eq!(new_tree, tree, "The working copy should be clean.");
+}
+
+#[test]
+fn test_gitattributes_change_of_touched_file() {
+    // A recorded content hash shouldn't hide that the stored contents change
+    // because of new attributes.
+    let user_settings = user_settings_with(r#"working-copy.touched-file-check = "full""#);
+    let mut test_workspace =
+        TestWorkspace::init_with_backend_and_settings(TestRepoBackend::Git, &user_settings);
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let ident_path = repo_path("file.c");
+    testutils::write_working_copy_file(&workspace_root, ident_path, "// $Id: old $\n");
+    let tree = test_workspace.snapshot().unwrap();
+    assert_eq!(read_stored_file(&tree, ident_path), b"// $Id: old $\n");
+
+    // Touch the file without changing it, and add the attribute
+    testutils::write_working_copy_file(&workspace_root, repo_path(".gitattributes"), "*.c ident\n");
+    testutils::write_working_copy_file(&workspace_root, ident_path, "// $Id: old $\n");
+    let tree = test_workspace.snapshot().unwrap();
+    assert_eq!(read_stored_file(&tree, ident_path), b"// $Id$\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_gitattributes_filter_commands() {
+    let user_settings = user_settings_with(
+        r#"
+        [working-copy.filters.upper]
+        clean = ["tr", "a-z", "A-Z"]
+        smudge = ["tr", "A-Z", "a-z"]
+        [working-copy.filters.path]
+        clean = ["echo", "$path"]
+        "#,
+    );
+    let mut test_workspace =
+        TestWorkspace::init_with_backend_and_settings(TestRepoBackend::Git, &user_settings);
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let upper_path = repo_path("sub/file.upper");
+    let path_path = repo_path("sub/file.path");
+    let unknown_path = repo_path("file.unknown");
+    testutils::write_working_copy_file(
+        &workspace_root,
+        repo_path(".gitattributes"),
+        "*.upper filter=upper\n*.path filter=path\n*.unknown filter=unknown\n",
+    );
+    for path in [upper_path, path_path, unknown_path] {
+        testutils::write_working_copy_file(&workspace_root, path, "foo\n");
+    }
+
+    // The clean filter runs when snapshotting. Unknown filters are ignored.
+    let tree = test_workspace.snapshot().unwrap();
+    assert_eq!(read_stored_file(&tree, upper_path), b"FOO\n");
+    assert_eq!(read_stored_file(&tree, path_path), b"sub/file.path\n");
+    assert_eq!(read_stored_file(&tree, unknown_path), b"foo\n");
+
+    // The smudge filter runs on checkout. Without a smudge command, the stored
+    // contents are written as is.
+    let commit = commit_with_tree(test_workspace.repo.store(), tree.clone());
+    check_out_again(&mut test_workspace, &commit);
+    assert_eq!(read_disk_file(&workspace_root, upper_path), b"foo\n");
+    assert_eq!(
+        read_disk_file(&workspace_root, path_path),
+        b"sub/file.path\n"
+    );
+    assert_eq!(read_disk_file(&workspace_root, unknown_path), b"foo\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_gitattributes_failing_filter() {
+    let user_settings = user_settings_with(
+        r#"
+        [working-copy.filters.optional]
+        clean = ["false"]
+        [working-copy.filters.required]
+        clean = ["false"]
+        required = true
+        "#,
+    );
+    let mut test_workspace =
+        TestWorkspace::init_with_backend_and_settings(TestRepoBackend::Git, &user_settings);
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let file_path = repo_path("file");
+    testutils::write_working_copy_file(
+        &workspace_root,
+        repo_path(".gitattributes"),
+        "* filter=optional\n",
+    );
+    testutils::write_working_copy_file(&workspace_root, file_path, "foo\n");
+
+    // A failing filter leaves the contents unchanged
+    let tree = test_workspace.snapshot().unwrap();
+    assert_eq!(read_stored_file(&tree, file_path), b"foo\n");
+
+    // ...unless it's required
+    testutils::write_working_copy_file(
+        &workspace_root,
+        repo_path(".gitattributes"),
+        "* filter=required\n",
+    );
+    testutils::write_working_copy_file(&workspace_root, file_path, "bar\n");
+    assert!(test_workspace.snapshot().is_err());
+}
+
+#[test]
+fn test_gitattributes_lfs() {
+    let user_settings = user_settings_with("git.lfs = true");
+    let mut test_workspace =
+        TestWorkspace::init_with_backend_and_settings(TestRepoBackend::Git, &user_settings);
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let git_repo_path = test_workspace
+        .repo
+        .store()
+        .backend_impl::<GitBackend>()
+        .unwrap()
+        .git_repo_path()
+        .to_owned();
+    let lfs_objects = LfsObjectCache::for_git_dir(&git_repo_path);
+    let large_path = repo_path("large.bin");
+    let other_path = repo_path("other.txt");
+    testutils::write_working_copy_file(
+        &workspace_root,
+        repo_path(".gitattributes"),
+        "*.bin filter=lfs diff=lfs merge=lfs -text\n",
+    );
+    testutils::write_working_copy_file(&workspace_root, large_path, "large\n");
+    testutils::write_working_copy_file(&workspace_root, other_path, "other\n");
+
+    // The file is stored as a pointer, and its contents are added to the cache
+    let tree = test_workspace.snapshot().unwrap();
+    let pointer = LfsPointer::for_contents(b"large\n");
+    assert_eq!(read_stored_file(&tree, large_path), pointer.to_bytes());
+    assert_eq!(read_stored_file(&tree, other_path), b"other\n");
+    assert!(lfs_objects.object_path(&pointer.oid).is_file());
+
+    // The contents are restored on checkout
+    let commit = commit_with_tree(test_workspace.repo.store(), tree.clone());
+    check_out_again(&mut test_workspace, &commit);
+    assert_eq!(read_disk_file(&workspace_root, large_path), b"large\n");
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_tree_eq!(new_tree, tree, "The working copy should be clean.");
+
+    // Without the object, the pointer is checked out, and stored as is again
+    std::fs::remove_file(lfs_objects.object_path(&pointer.oid)).unwrap();
+    check_out_again(&mut test_workspace, &commit);
+    assert_eq!(
+        read_disk_file(&workspace_root, large_path),
+        pointer.to_bytes()
+    );
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_tree_eq!(new_tree, tree, "The working copy should be clean.");
+}
+
+#[test]
+fn test_gitattributes_lfs_disabled() {
+    let mut test_workspace = TestWorkspace::init_with_backend(TestRepoBackend::Git);
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let large_path = repo_path("large.bin");
+    testutils::write_working_copy_file(
+        &workspace_root,
+        repo_path(".gitattributes"),
+        "*.bin filter=lfs\n",
+    );
+    testutils::write_working_copy_file(&workspace_root, large_path, "large\n");
+
+    let tree = test_workspace.snapshot().unwrap();
+    assert_eq!(read_stored_file(&tree, large_path), b"large\n");
+}
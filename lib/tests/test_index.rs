@@ -68,7 +68,7 @@ where
 fn enable_changed_path_index(repo: &ReadonlyRepo) -> Arc<ReadonlyRepo> {
     let default_index_store: &DefaultIndexStore = repo.index_store().downcast_ref().unwrap();
     default_index_store
-        .build_changed_path_index_at_operation(repo.op_id(), repo.store(), 0)
+        .build_changed_path_index_at_operation(repo.op_id(), repo.store(), 0, None)
         .block_on()
         .unwrap();
     repo.reload_at(repo.operation()).unwrap()
@@ -651,6 +651,28 @@ fn test_index_commits_incremental_squashed() {
     assert_eq!(commits_by_level(&repo), vec![71, 20]);
 }
 
+#[test]
+fn test_compact_index() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let repo = create_n_commits(repo, 30);
+    let repo = create_n_commits(&repo, 15);
+    let repo = create_n_commits(&repo, 7);
+    let repo = enable_changed_path_index(&repo);
+    let repo = create_n_commits(&repo, 3);
+    assert_eq!(commits_by_level(&repo), vec![31, 15, 7, 3]);
+
+    // The stack is already compact
+    let default_index_store: &DefaultIndexStore = repo.index_store().downcast_ref().unwrap();
+    let index = default_index_store
+        .compact_index_at_operation(repo.op_id(), repo.store())
+        .unwrap();
+    assert_eq!(index.stats().commit_levels.len(), 4);
+    let repo = repo.reload_at(repo.operation()).unwrap();
+    assert_eq!(commits_by_level(&repo), vec![31, 15, 7, 3]);
+    assert_eq!(as_readonly_index(&repo).num_commits(), 56);
+}
+
 #[test]
 fn test_reindex_no_segments_dir() {
     let settings = testutils::user_settings();
@@ -778,7 +800,7 @@ fn test_reindex_missing_commit() {
     let default_index_store: &DefaultIndexStore = repo.index_store().downcast_ref().unwrap();
     default_index_store.reinit().unwrap();
     let err = default_index_store
-        .build_index_at_operation(repo.operation(), repo.store())
+        .build_index_at_operation(repo.operation(), repo.store(), None)
         .block_on()
         .unwrap_err();
     assert_matches!(err, DefaultIndexStoreError::IndexCommits { op_id, .. } if op_id == *bad_op_id);
@@ -912,7 +934,7 @@ fn test_build_changed_path_segments() {
 
     // Index the last 4 commits
     default_index_store
-        .build_changed_path_index_at_operation(repo.op_id(), repo.store(), 4)
+        .build_changed_path_index_at_operation(repo.op_id(), repo.store(), 4, None)
         .block_on()
         .unwrap();
     let repo = repo.reload_at(repo.operation()).unwrap();
@@ -925,7 +947,7 @@ fn test_build_changed_path_segments() {
 
     // Index remainders
     default_index_store
-        .build_changed_path_index_at_operation(repo.op_id(), repo.store(), u32::MAX)
+        .build_changed_path_index_at_operation(repo.op_id(), repo.store(), u32::MAX, None)
         .block_on()
         .unwrap();
     let repo = repo.reload_at(repo.operation()).unwrap();
@@ -986,7 +1008,7 @@ fn test_build_changed_path_segments_partially_enabled() {
 
     // Index later commits from the mid point
     default_index_store
-        .build_changed_path_index_at_operation(repo.op_id(), repo.store(), 2)
+        .build_changed_path_index_at_operation(repo.op_id(), repo.store(), 2, None)
         .block_on()
         .unwrap();
     let repo = repo.reload_at(repo.operation()).unwrap();
@@ -999,7 +1021,7 @@ fn test_build_changed_path_segments_partially_enabled() {
 
     // Index later and earlier commits from the mid point
     default_index_store
-        .build_changed_path_index_at_operation(repo.op_id(), repo.store(), 3)
+        .build_changed_path_index_at_operation(repo.op_id(), repo.store(), 3, None)
         .block_on()
         .unwrap();
     let repo = repo.reload_at(repo.operation()).unwrap();
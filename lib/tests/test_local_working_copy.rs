@@ -31,8 +31,11 @@ use itertools::Itertools as _;
 use jj_lib::backend::CopyId;
 use jj_lib::backend::TreeId;
 use jj_lib::backend::TreeValue;
+use jj_lib::config::ConfigLayer;
+use jj_lib::config::ConfigSource;
 use jj_lib::conflict_labels::ConflictLabels;
 use jj_lib::conflicts::ConflictMaterializeOptions;
+use jj_lib::diff::DiffAlgorithm;
 use jj_lib::file_util;
 use jj_lib::file_util::check_symlink_support;
 use jj_lib::file_util::symlink_dir;
@@ -40,7 +43,11 @@ use jj_lib::file_util::symlink_file;
 use jj_lib::files::FileMergeHunkLevel;
 use jj_lib::fsmonitor::FsmonitorSettings;
 use jj_lib::gitignore::GitIgnoreFile;
+use jj_lib::local_working_copy::CaseCollisionSetting;
+use jj_lib::local_working_copy::ChangeDetection;
 use jj_lib::local_working_copy::LocalWorkingCopy;
+use jj_lib::local_working_copy::PathNormalizationSetting;
+use jj_lib::local_working_copy::TouchedFileCheck;
 use jj_lib::local_working_copy::TreeState;
 use jj_lib::local_working_copy::TreeStateSettings;
 use jj_lib::matchers::FilesMatcher;
@@ -56,11 +63,15 @@ use jj_lib::repo_path::RepoPath;
 use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::rewrite::merge_commit_trees;
 use jj_lib::secret_backend::SecretBackend;
+use jj_lib::settings::UserSettings;
 use jj_lib::tree_builder::TreeBuilder;
 use jj_lib::tree_merge::MergeOptions;
+use jj_lib::working_copy::CaseCollision;
 use jj_lib::working_copy::CheckoutError;
+use jj_lib::working_copy::CheckoutOptions;
 use jj_lib::working_copy::CheckoutStats;
 use jj_lib::working_copy::SnapshotOptions;
+use jj_lib::working_copy::SparsePatterns;
 use jj_lib::working_copy::UntrackedReason;
 use jj_lib::working_copy::WorkingCopy as _;
 use jj_lib::workspace::Workspace;
@@ -125,7 +136,7 @@ fn test_root() {
     let mut test_workspace = TestWorkspace::init();
 
     let wc = test_workspace.workspace.working_copy();
-    assert_eq!(wc.sparse_patterns().unwrap(), vec![RepoPathBuf::root()]);
+    assert_eq!(*wc.sparse_patterns().unwrap(), SparsePatterns::full());
     let new_tree = test_workspace.snapshot().unwrap();
     let repo = &test_workspace.repo;
     let wc_commit_id = repo
@@ -664,7 +675,8 @@ fn test_conflicting_changes_on_disk() {
             updated_files: 0,
             added_files: 3,
             removed_files: 0,
-            skipped_files: 3
+            skipped_files: 3,
+            case_collisions: vec![],
         }
     );
 
@@ -779,7 +791,7 @@ fn test_checkout_discard() {
     let mut locked_ws = ws.start_working_copy_mutation().unwrap();
     locked_ws
         .locked_wc()
-        .check_out(&commit2)
+        .check_out(&commit2, &CheckoutOptions::default())
         .block_on()
         .unwrap();
     // The change should be reflected in the working copy but not saved
@@ -889,7 +901,8 @@ fn test_materialize_snapshot_conflicted_files() {
             updated_files: 0,
             added_files: 2,
             removed_files: 0,
-            skipped_files: 0
+            skipped_files: 0,
+            case_collisions: vec![],
         }
     );
 
@@ -1352,6 +1365,7 @@ fn test_snapshot_modified_materialized_conflict(
                 merge: MergeOptions {
                     hunk_level: FileMergeHunkLevel::Line,
                     same_change: SameChange::Accept,
+                    diff_algorithm: DiffAlgorithm::Histogram,
                 },
             },
         )
@@ -1417,6 +1431,284 @@ fn test_snapshot_racy_timestamps() {
     }
 }
 
+#[test_case(false; "disabled")]
+#[test_case(true; "enabled")]
+fn test_snapshot_untracked_cache(untracked_cache: bool) {
+    // Tests that a directory isn't read again if its mtime hasn't changed
+    // since the last snapshot, unless the untracked cache is disabled.
+    let mut config = testutils::base_user_config();
+    config.add_layer(
+        ConfigLayer::parse(
+            ConfigSource::User,
+            &format!("working-copy.untracked-cache = {untracked_cache}"),
+        )
+        .unwrap(),
+    );
+    let settings = UserSettings::from_config(config).unwrap();
+    let mut test_workspace = TestWorkspace::init_with_settings(&settings);
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let file1_path = repo_path("dir/file1");
+    let file2_path = repo_path("dir/file2");
+    let dir_disk_path = workspace_root.join("dir");
+    std::fs::create_dir(&dir_disk_path).unwrap();
+    std::fs::write(file1_path.to_fs_path_unchecked(&workspace_root), "1").unwrap();
+    let set_dir_mtime = |time: SystemTime| {
+        File::open(&dir_disk_path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    };
+    // The listing is only reused if the directory was modified before the
+    // working copy state was written.
+    let old_time = SystemTime::now() - Duration::from_secs(60);
+    set_dir_mtime(old_time);
+    let tree = test_workspace.snapshot().unwrap();
+    assert_eq!(
+        tree.entries().map(|(path, _value)| path).collect_vec(),
+        to_owned_path_vec(&[file1_path])
+    );
+
+    // A new file is only missed if the directory mtime is reset and the cache
+    // is enabled
+    std::fs::write(file2_path.to_fs_path_unchecked(&workspace_root), "2").unwrap();
+    set_dir_mtime(old_time);
+    let tree = test_workspace.snapshot().unwrap();
+    let expected_paths = if untracked_cache {
+        to_owned_path_vec(&[file1_path])
+    } else {
+        to_owned_path_vec(&[file1_path, file2_path])
+    };
+    assert_eq!(
+        tree.entries().map(|(path, _value)| path).collect_vec(),
+        expected_paths
+    );
+
+    // Once the mtime changes, the directory is read again
+    set_dir_mtime(old_time + Duration::from_secs(1));
+    let tree = test_workspace.snapshot().unwrap();
+    assert_eq!(
+        tree.entries().map(|(path, _value)| path).collect_vec(),
+        to_owned_path_vec(&[file1_path, file2_path])
+    );
+}
+
+#[test_case(TouchedFileCheck::None; "none")]
+#[test_case(TouchedFileCheck::Sampled; "sampled")]
+#[test_case(TouchedFileCheck::Full; "full")]
+fn test_snapshot_touched_file_check(touched_file_check: TouchedFileCheck) {
+    // Tests that files whose mtime changed but whose contents didn't aren't
+    // treated as modified, and that changes are detected as far as the
+    // configured check can tell.
+    let test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let state_path = test_workspace.env.root().join("state");
+    std::fs::create_dir(&state_path).unwrap();
+    let tree_state_settings = TreeStateSettings {
+        touched_file_check,
+        ..TreeStateSettings::try_from_user_settings(repo.settings()).unwrap()
+    };
+    TreeState::init(
+        repo.store().clone(),
+        workspace_root.clone(),
+        state_path.clone(),
+        &tree_state_settings,
+    )
+    .unwrap();
+    let snapshot = || {
+        let mut tree_state = TreeState::load(
+            repo.store().clone(),
+            workspace_root.clone(),
+            state_path.clone(),
+            &tree_state_settings,
+        )
+        .unwrap();
+        tree_state
+            .snapshot(&empty_snapshot_options())
+            .block_on()
+            .unwrap();
+        tree_state.save().unwrap();
+        tree_state
+    };
+
+    let file_path = repo_path("file");
+    let file_disk_path = file_path.to_fs_path_unchecked(&workspace_root);
+    let mut contents = vec![b'a'; 100 << 10];
+    let write_file = |contents: &[u8], mtime: SystemTime| {
+        std::fs::write(&file_disk_path, contents).unwrap();
+        File::options()
+            .write(true)
+            .open(&file_disk_path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+    };
+    let old_time = SystemTime::now() - Duration::from_secs(60);
+    write_file(&contents, old_time);
+    let tree_state = snapshot();
+    let tree = tree_state.current_tree().clone();
+    let quick_hash = tree_state.file_states().get(file_path).unwrap().quick_hash;
+    assert_eq!(
+        quick_hash.is_some(),
+        touched_file_check != TouchedFileCheck::None
+    );
+    // The hash is kept if the file isn't touched
+    let tree_state = snapshot();
+    assert_eq!(
+        tree_state.file_states().get(file_path).unwrap().quick_hash,
+        quick_hash
+    );
+
+    // Touching the file doesn't change the tree
+    write_file(&contents, old_time + Duration::from_secs(1));
+    let tree_state = snapshot();
+    assert_tree_eq!(*tree_state.current_tree(), tree);
+
+    // Modifying a byte which isn't sampled goes unnoticed by the sampled check
+    contents[20 << 10] = b'b';
+    write_file(&contents, old_time + Duration::from_secs(2));
+    let tree_state = snapshot();
+    if touched_file_check == TouchedFileCheck::Sampled {
+        assert_tree_eq!(*tree_state.current_tree(), tree);
+    } else {
+        assert_ne!(tree_state.current_tree().tree_ids(), tree.tree_ids());
+    }
+
+    // Modifying the beginning of the file is always noticed
+    contents[0] = b'b';
+    write_file(&contents, old_time + Duration::from_secs(3));
+    let tree_state = snapshot();
+    assert_ne!(tree_state.current_tree().tree_ids(), tree.tree_ids());
+}
+
+#[test_case(ChangeDetection::Mtime; "mtime")]
+#[test_case(ChangeDetection::Checksum; "checksum")]
+fn test_snapshot_change_detection(change_detection: ChangeDetection) {
+    // Tests that modifications which restore the mtime are only noticed when
+    // hashing files.
+    let test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let state_path = test_workspace.env.root().join("state");
+    std::fs::create_dir(&state_path).unwrap();
+    let tree_state_settings = TreeStateSettings {
+        change_detection,
+        ..TreeStateSettings::try_from_user_settings(repo.settings()).unwrap()
+    };
+    TreeState::init(
+        repo.store().clone(),
+        workspace_root.clone(),
+        state_path.clone(),
+        &tree_state_settings,
+    )
+    .unwrap();
+    let snapshot = || {
+        let mut tree_state = TreeState::load(
+            repo.store().clone(),
+            workspace_root.clone(),
+            state_path.clone(),
+            &tree_state_settings,
+        )
+        .unwrap();
+        tree_state
+            .snapshot(&empty_snapshot_options())
+            .block_on()
+            .unwrap();
+        tree_state.save().unwrap();
+        tree_state
+    };
+
+    let file_path = repo_path("file");
+    let file_disk_path = file_path.to_fs_path_unchecked(&workspace_root);
+    let write_file = |contents: &str, mtime: SystemTime| {
+        std::fs::write(&file_disk_path, contents).unwrap();
+        File::options()
+            .write(true)
+            .open(&file_disk_path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+    };
+    let old_time = SystemTime::now() - Duration::from_secs(60);
+    write_file("contents 1", old_time);
+    let tree_state = snapshot();
+    let tree = tree_state.current_tree().clone();
+    assert_eq!(
+        tree_state
+            .file_states()
+            .get(file_path)
+            .unwrap()
+            .quick_hash
+            .is_some(),
+        change_detection == ChangeDetection::Checksum
+    );
+
+    // Touching the file doesn't change the tree
+    write_file("contents 1", old_time + Duration::from_secs(1));
+    let tree_state = snapshot();
+    assert_tree_eq!(*tree_state.current_tree(), tree);
+
+    // Modifying the file without changing its size and mtime
+    write_file("contents 2", old_time + Duration::from_secs(1));
+    let tree_state = snapshot();
+    if change_detection == ChangeDetection::Checksum {
+        assert_ne!(tree_state.current_tree().tree_ids(), tree.tree_ids());
+    } else {
+        assert_tree_eq!(*tree_state.current_tree(), tree);
+    }
+}
+
+#[test]
+fn test_snapshot_force_tracked_paths() {
+    // Tests that force-tracked paths are persisted and snapshotted even if
+    // they're ignored.
+    let test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let state_path = test_workspace.env.root().join("state");
+    std::fs::create_dir(&state_path).unwrap();
+    let tree_state_settings = TreeStateSettings::try_from_user_settings(repo.settings()).unwrap();
+    let mut tree_state = TreeState::init(
+        repo.store().clone(),
+        workspace_root.clone(),
+        state_path.clone(),
+        &tree_state_settings,
+    )
+    .unwrap();
+
+    let gitignore_path = repo_path(".gitignore");
+    let forced_path = repo_path("forced.ignored");
+    let ignored_path = repo_path("other.ignored");
+    for (path, contents) in [
+        (gitignore_path, "*.ignored\n"),
+        (forced_path, "forced"),
+        (ignored_path, "ignored"),
+    ] {
+        std::fs::write(path.to_fs_path_unchecked(&workspace_root), contents).unwrap();
+    }
+    tree_state.set_force_tracked_paths(vec![forced_path.to_owned()]);
+    tree_state.save().unwrap();
+
+    let mut tree_state = TreeState::load(
+        repo.store().clone(),
+        workspace_root.clone(),
+        state_path,
+        &tree_state_settings,
+    )
+    .unwrap();
+    assert_eq!(tree_state.force_tracked_paths(), [forced_path.to_owned()]);
+    tree_state
+        .snapshot(&empty_snapshot_options())
+        .block_on()
+        .unwrap();
+    let expected_tree = create_tree(
+        repo,
+        &[(gitignore_path, "*.ignored\n"), (forced_path, "forced")],
+    );
+    assert_tree_eq!(*tree_state.current_tree(), expected_tree);
+}
+
 #[cfg(unix)]
 #[test]
 fn test_snapshot_special_file() {
@@ -1831,6 +2123,357 @@ fn test_git_submodule(gitignore_content: &str) {
     assert_eq!(stats.skipped_files, 1);
 }
 
+#[test]
+fn test_check_out_case_collisions() {
+    let test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let init_tree_state = |name: &str, case_collision_setting| {
+        let state_path = test_workspace.env.root().join(name);
+        std::fs::create_dir(&state_path).unwrap();
+        let tree_state_settings = TreeStateSettings {
+            case_collision_setting,
+            ..TreeStateSettings::try_from_user_settings(repo.settings()).unwrap()
+        };
+        TreeState::init(
+            repo.store().clone(),
+            workspace_root.clone(),
+            state_path,
+            &tree_state_settings,
+        )
+        .unwrap()
+    };
+
+    let upper_dir_file_path = repo_path("Dir/b");
+    let upper_file_path = repo_path("Foo.rs");
+    let lower_dir_file_path = repo_path("dir/a");
+    let lower_file_path = repo_path("foo.rs");
+    let other_file_path = repo_path("other");
+    let tree1 = create_tree(
+        repo,
+        &[
+            (upper_dir_file_path, "b"),
+            (upper_file_path, "upper"),
+            (lower_dir_file_path, "a"),
+            (lower_file_path, "lower"),
+            (other_file_path, "other"),
+        ],
+    );
+    let tree2 = create_tree(
+        repo,
+        &[
+            (upper_dir_file_path, "b"),
+            (lower_dir_file_path, "a"),
+            (lower_file_path, "lower 2"),
+            (other_file_path, "other"),
+        ],
+    );
+
+    // Checking out fails if configured to reject colliding paths
+    let mut tree_state = init_tree_state("error", CaseCollisionSetting::Error);
+    let result = tree_state.check_out(&tree1, &CheckoutOptions::default());
+    assert_matches!(result, Err(CheckoutError::CaseCollision(collisions)) if collisions.len() == 2);
+
+    // Only the first path of colliding paths is checked out
+    let mut tree_state = init_tree_state("skip", CaseCollisionSetting::Skip);
+    let stats = tree_state
+        .check_out(&tree1, &CheckoutOptions::default())
+        .unwrap();
+    assert_eq!(stats.added_files, 3);
+    assert_eq!(
+        stats.case_collisions,
+        [
+            CaseCollision {
+                path: lower_dir_file_path.to_owned(),
+                other_path: repo_path("Dir").to_owned(),
+                checked_out_as: None,
+            },
+            CaseCollision {
+                path: lower_file_path.to_owned(),
+                other_path: upper_file_path.to_owned(),
+                checked_out_as: None,
+            },
+        ]
+    );
+    let file_exists = |path: &RepoPath| path.to_fs_path_unchecked(&workspace_root).exists();
+    assert!(file_exists(upper_dir_file_path));
+    assert!(file_exists(upper_file_path));
+    assert!(file_exists(other_file_path));
+    if !file_exists(repo_path("DIR")) {
+        assert!(!file_exists(lower_dir_file_path));
+        assert!(!file_exists(lower_file_path));
+    }
+
+    // The skipped paths stay unchanged when snapshotting
+    tree_state
+        .snapshot(&empty_snapshot_options())
+        .block_on()
+        .unwrap();
+    assert_tree_eq!(*tree_state.current_tree(), tree1);
+
+    // The skipped file is checked out once the other path is removed
+    let stats = tree_state
+        .check_out(&tree2, &CheckoutOptions::default())
+        .unwrap();
+    assert_eq!(stats.removed_files, 1);
+    assert_eq!(stats.added_files, 1);
+    assert_eq!(stats.case_collisions, []);
+    assert!(!file_exists(upper_file_path) || file_exists(repo_path("FOO.RS")));
+    assert_eq!(
+        std::fs::read_to_string(lower_file_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        "lower 2"
+    );
+    tree_state
+        .snapshot(&empty_snapshot_options())
+        .block_on()
+        .unwrap();
+    assert_tree_eq!(*tree_state.current_tree(), tree2);
+}
+
+#[test]
+fn test_snapshot_path_normalization() {
+    let test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let state_path = test_workspace.env.root().join("state");
+    std::fs::create_dir(&state_path).unwrap();
+    let tree_state_settings = TreeStateSettings {
+        path_normalization_setting: PathNormalizationSetting::Nfc,
+        ..TreeStateSettings::try_from_user_settings(repo.settings()).unwrap()
+    };
+    let mut tree_state = TreeState::init(
+        repo.store().clone(),
+        workspace_root.clone(),
+        state_path,
+        &tree_state_settings,
+    )
+    .unwrap();
+
+    let dir_file_path = repo_path("caf\u{e9}/file");
+    let file_path = repo_path("\u{e9}t\u{e9}");
+    let tree1 = create_tree(repo, &[(dir_file_path, "a"), (file_path, "b")]);
+    tree_state
+        .check_out(&tree1, &CheckoutOptions::default())
+        .unwrap();
+
+    // Tracked paths renamed to the decomposed form are matched with the
+    // original paths
+    std::fs::rename(
+        workspace_root.join("caf\u{e9}"),
+        workspace_root.join("cafe\u{301}"),
+    )
+    .unwrap();
+    std::fs::rename(
+        workspace_root.join("\u{e9}t\u{e9}"),
+        workspace_root.join("e\u{301}te\u{301}"),
+    )
+    .unwrap();
+    tree_state
+        .snapshot(&empty_snapshot_options())
+        .block_on()
+        .unwrap();
+    assert_tree_eq!(*tree_state.current_tree(), tree1);
+
+    // New paths are converted to the composed form
+    std::fs::write(workspace_root.join("nai\u{308}ve"), "c").unwrap();
+    tree_state
+        .snapshot(&empty_snapshot_options())
+        .block_on()
+        .unwrap();
+    let tree2 = create_tree(
+        repo,
+        &[
+            (dir_file_path, "a"),
+            (file_path, "b"),
+            (repo_path("na\u{ef}ve"), "c"),
+        ],
+    );
+    assert_tree_eq!(*tree_state.current_tree(), tree2);
+}
+
+#[test]
+fn test_check_out_case_collisions_with_suffix() {
+    let test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let state_path = test_workspace.env.root().join("suffix");
+    std::fs::create_dir(&state_path).unwrap();
+    let tree_state_settings = TreeStateSettings {
+        case_collision_setting: CaseCollisionSetting::Suffix,
+        ..TreeStateSettings::try_from_user_settings(repo.settings()).unwrap()
+    };
+    let mut tree_state = TreeState::init(
+        repo.store().clone(),
+        workspace_root.clone(),
+        state_path,
+        &tree_state_settings,
+    )
+    .unwrap();
+
+    let upper_dir_file_path = repo_path("Dir/b");
+    let upper_file_path = repo_path("Foo.rs");
+    let lower_dir_file_path = repo_path("dir/a");
+    let lower_file_path = repo_path("foo.rs");
+    let suffixed_dir_file_path = repo_path("dir.case-collision-1/a");
+    let suffixed_file_path = repo_path("foo.rs.case-collision-1");
+    let tree1 = create_tree(
+        repo,
+        &[
+            (upper_dir_file_path, "b"),
+            (upper_file_path, "upper"),
+            (lower_dir_file_path, "a"),
+            (lower_file_path, "lower"),
+        ],
+    );
+    let read_file = |path: &RepoPath| {
+        std::fs::read_to_string(path.to_fs_path_unchecked(&workspace_root)).unwrap()
+    };
+    let file_exists = |path: &RepoPath| path.to_fs_path_unchecked(&workspace_root).exists();
+
+    // The colliding paths are checked out under suffixed names
+    let stats = tree_state
+        .check_out(&tree1, &CheckoutOptions::default())
+        .unwrap();
+    assert_eq!(stats.added_files, 4);
+    assert_eq!(
+        stats.case_collisions,
+        [
+            CaseCollision {
+                path: lower_dir_file_path.to_owned(),
+                other_path: repo_path("Dir").to_owned(),
+                checked_out_as: Some(suffixed_dir_file_path.to_owned()),
+            },
+            CaseCollision {
+                path: lower_file_path.to_owned(),
+                other_path: upper_file_path.to_owned(),
+                checked_out_as: Some(suffixed_file_path.to_owned()),
+            },
+        ]
+    );
+    assert_eq!(read_file(upper_file_path), "upper");
+    assert_eq!(read_file(suffixed_dir_file_path), "a");
+    assert_eq!(read_file(suffixed_file_path), "lower");
+    tree_state
+        .snapshot(&empty_snapshot_options())
+        .block_on()
+        .unwrap();
+    assert_tree_eq!(*tree_state.current_tree(), tree1);
+
+    // Changes to the renamed file are recorded at its own path
+    std::fs::write(
+        suffixed_file_path.to_fs_path_unchecked(&workspace_root),
+        "lower modified",
+    )
+    .unwrap();
+    tree_state
+        .snapshot(&empty_snapshot_options())
+        .block_on()
+        .unwrap();
+    let tree2 = create_tree(
+        repo,
+        &[
+            (upper_dir_file_path, "b"),
+            (upper_file_path, "upper"),
+            (lower_dir_file_path, "a"),
+            (lower_file_path, "lower modified"),
+        ],
+    );
+    assert_tree_eq!(*tree_state.current_tree(), tree2);
+
+    // The renamed file is moved to its own path once the other path is removed
+    let tree3 = create_tree(
+        repo,
+        &[
+            (upper_dir_file_path, "b"),
+            (lower_dir_file_path, "a 3"),
+            (lower_file_path, "lower 3"),
+        ],
+    );
+    let stats = tree_state
+        .check_out(&tree3, &CheckoutOptions::default())
+        .unwrap();
+    assert_eq!(stats.case_collisions, []);
+    assert!(!file_exists(suffixed_file_path));
+    assert_eq!(read_file(lower_file_path), "lower 3");
+    assert_eq!(read_file(suffixed_dir_file_path), "a 3");
+    tree_state
+        .snapshot(&empty_snapshot_options())
+        .block_on()
+        .unwrap();
+    assert_tree_eq!(*tree_state.current_tree(), tree3);
+
+    // Deleting the renamed file deletes its own path
+    std::fs::remove_file(suffixed_dir_file_path.to_fs_path_unchecked(&workspace_root)).unwrap();
+    tree_state
+        .snapshot(&empty_snapshot_options())
+        .block_on()
+        .unwrap();
+    let tree4 = create_tree(
+        repo,
+        &[(upper_dir_file_path, "b"), (lower_file_path, "lower 3")],
+    );
+    assert_tree_eq!(*tree_state.current_tree(), tree4);
+}
+
+#[test]
+fn test_snapshot_case_collisions() {
+    let test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let state_path = test_workspace.env.root().join("skip");
+    std::fs::create_dir(&state_path).unwrap();
+    let tree_state_settings = TreeStateSettings {
+        case_collision_setting: CaseCollisionSetting::Skip,
+        ..TreeStateSettings::try_from_user_settings(repo.settings()).unwrap()
+    };
+    let mut tree_state = TreeState::init(
+        repo.store().clone(),
+        workspace_root.clone(),
+        state_path,
+        &tree_state_settings,
+    )
+    .unwrap();
+
+    let upper_file_path = repo_path("Foo.rs");
+    let lower_file_path = repo_path("foo.rs");
+    let tree1 = create_tree(repo, &[(upper_file_path, "upper")]);
+    tree_state
+        .check_out(&tree1, &CheckoutOptions::default())
+        .unwrap();
+    testutils::write_working_copy_file(&workspace_root, lower_file_path, "lower");
+    if std::fs::read_to_string(upper_file_path.to_fs_path_unchecked(&workspace_root)).unwrap()
+        == "lower"
+    {
+        // The filesystem is case-insensitive, so the file was overwritten
+        return;
+    }
+
+    // A new file which collides with a tracked file isn't tracked
+    let (_, stats) = tree_state
+        .snapshot(&empty_snapshot_options())
+        .block_on()
+        .unwrap();
+    assert_tree_eq!(*tree_state.current_tree(), tree1);
+    assert_eq!(stats.untracked_paths.len(), 1);
+    assert_matches!(
+        stats.untracked_paths.get(lower_file_path),
+        Some(UntrackedReason::CaseCollision { other_path })
+            if other_path.as_ref() == upper_file_path
+    );
+
+    // Renaming a file to a name which differs only in case isn't a collision
+    std::fs::remove_file(upper_file_path.to_fs_path_unchecked(&workspace_root)).unwrap();
+    let (_, stats) = tree_state
+        .snapshot(&empty_snapshot_options())
+        .block_on()
+        .unwrap();
+    assert_tree_eq!(
+        *tree_state.current_tree(),
+        create_tree(repo, &[(lower_file_path, "lower")])
+    );
+    assert!(stats.untracked_paths.is_empty());
+}
+
 #[test]
 fn test_check_out_existing_file_cannot_be_removed() {
     let mut test_workspace = TestWorkspace::init();
@@ -2523,6 +3166,157 @@ fn test_fsmonitor() {
     tree_state.save().unwrap();
 }
 
+#[cfg(feature = "builtin-fsmonitor")]
+#[test]
+fn test_builtin_fsmonitor() {
+    use jj_lib::fsmonitor::BuiltinFsmonitorConfig;
+    use jj_lib::fsmonitor::builtin;
+
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let workspace_root = test_repo.env.root().join("workspace");
+    let state_path = test_repo.env.root().join("state");
+    std::fs::create_dir(&workspace_root).unwrap();
+    std::fs::create_dir(&state_path).unwrap();
+    let tree_state_settings = TreeStateSettings {
+        fsmonitor_settings: FsmonitorSettings::Builtin(BuiltinFsmonitorConfig {
+            auto_start: false,
+            daemon_command: None,
+        }),
+        ..TreeStateSettings::try_from_user_settings(repo.settings()).unwrap()
+    };
+    TreeState::init(
+        repo.store().clone(),
+        workspace_root.clone(),
+        state_path.clone(),
+        &tree_state_settings,
+    )
+    .unwrap();
+
+    let foo_path = repo_path("foo");
+    let nested_path = repo_path("dir/nested");
+    testutils::write_working_copy_file(&workspace_root, foo_path, "foo\n");
+    testutils::write_working_copy_file(&workspace_root, nested_path, "nested\n");
+
+    let fsmonitor_state_path = state_path.join("fsmonitor");
+    let fsmonitor = builtin::Fsmonitor::new(fsmonitor_state_path.clone());
+    assert!(!fsmonitor.is_daemon_running().unwrap());
+    assert_matches!(
+        fsmonitor.query_changed_files(None),
+        Err(builtin::Error::DaemonNotRunning)
+    );
+    let daemon = std::thread::spawn({
+        let workspace_root = workspace_root.clone();
+        move || builtin::run_daemon(&workspace_root, &fsmonitor_state_path)
+    });
+    while !fsmonitor.is_daemon_running().unwrap() {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let snapshot = || {
+        let mut tree_state = TreeState::load(
+            repo.store().clone(),
+            workspace_root.clone(),
+            state_path.clone(),
+            &tree_state_settings,
+        )
+        .unwrap();
+        tree_state
+            .snapshot(&empty_snapshot_options())
+            .block_on()
+            .unwrap();
+        tree_state.save().unwrap();
+        tree_state
+    };
+
+    // Without a previous clock, the whole working copy has to be crawled.
+    let (clock, changed_files) = fsmonitor.query_changed_files(None).unwrap();
+    assert_eq!(changed_files, None);
+    let tree_state = snapshot();
+    insta::assert_snapshot!(testutils::dump_tree(tree_state.current_tree()), @r#"
+    merged tree (sides: 1)
+      tree cbbe32599146b9672f67
+        file "dir/nested" (6209060941cd770c8d46): "nested\n"
+        file "foo" (e99c2057c15160add351): "foo\n"
+    "#);
+
+    testutils::write_working_copy_file(&workspace_root, nested_path, "updated nested\n");
+    let (clock, changed_files) = fsmonitor.query_changed_files(Some(clock)).unwrap();
+    assert_eq!(
+        changed_files,
+        Some(vec![nested_path.to_fs_path_unchecked(Path::new(""))])
+    );
+    let (_clock, changed_files) = fsmonitor.query_changed_files(Some(clock)).unwrap();
+    assert_eq!(changed_files, Some(vec![]));
+
+    // The tree state picks up changes reported by the daemon.
+    let tree_state = snapshot();
+    insta::assert_snapshot!(testutils::dump_tree(tree_state.current_tree()), @r#"
+    merged tree (sides: 1)
+      tree 25c61938ec3cf07f9c9f
+        file "dir/nested" (fe8814975c40ad94c62d): "updated nested\n"
+        file "foo" (e99c2057c15160add351): "foo\n"
+    "#);
+
+    // Files in new directories are reported too.
+    let new_path = repo_path("new/file");
+    testutils::write_working_copy_file(&workspace_root, new_path, "new\n");
+    std::fs::remove_file(foo_path.to_fs_path_unchecked(&workspace_root)).unwrap();
+    let tree_state = snapshot();
+    insta::assert_snapshot!(testutils::dump_tree(tree_state.current_tree()), @r#"
+    merged tree (sides: 1)
+      tree 9ff7e82d6800a9fb4ac3
+        file "dir/nested" (fe8814975c40ad94c62d): "updated nested\n"
+        file "new/file" (c2bcc6eb158b9fe4f027): "new\n"
+    "#);
+
+    assert!(fsmonitor.stop_daemon().unwrap());
+    daemon.join().unwrap().unwrap();
+    assert!(!fsmonitor.is_daemon_running().unwrap());
+    assert!(!fsmonitor.stop_daemon().unwrap());
+}
+
+#[cfg(all(feature = "builtin-fsmonitor", unix))]
+#[test]
+fn test_builtin_fsmonitor_spawn_daemon() {
+    use jj_lib::fsmonitor::builtin;
+
+    let test_repo = TestRepo::init();
+    let workspace_root = test_repo.env.root().join("workspace");
+    let fsmonitor_state_path = test_repo.env.root().join("state");
+    std::fs::create_dir(&workspace_root).unwrap();
+    let fsmonitor = builtin::Fsmonitor::new(fsmonitor_state_path.clone());
+
+    // The working copy and state directory arguments are appended to the
+    // configured command.
+    let daemon_command = ["sh", "-c", r#"echo "$@" > spawned"#, "sh"].map(str::to_owned);
+    fsmonitor
+        .spawn_daemon(&daemon_command, &workspace_root)
+        .unwrap();
+    let spawned_path = workspace_root.join("spawned");
+    let args = loop {
+        if let Ok(args) = std::fs::read_to_string(&spawned_path)
+            && args.ends_with('\n')
+        {
+            break args;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    assert_eq!(
+        args.trim_end(),
+        format!(
+            "--working-copy {} --state-dir {}",
+            workspace_root.display(),
+            fsmonitor_state_path.display()
+        )
+    );
+
+    assert_matches!(
+        fsmonitor.spawn_daemon(&[], &workspace_root),
+        Err(builtin::Error::SpawnDaemon(_))
+    );
+}
+
 #[test]
 fn track_ignored_with_flag_and_fsmonitor() {
     let test_repo = TestRepo::init();
@@ -2811,7 +3605,7 @@ fn test_snapshot_and_update_valid_symlink(get_link_target: impl FnOnce(&Path, &P
     let root_commit = test_workspace.repo.store().root_commit();
     locked_ws
         .locked_wc()
-        .check_out(&root_commit)
+        .check_out(&root_commit, &CheckoutOptions::default())
         .block_on()
         .unwrap();
     locked_ws
@@ -2826,7 +3620,11 @@ fn test_snapshot_and_update_valid_symlink(get_link_target: impl FnOnce(&Path, &P
         .workspace
         .start_working_copy_mutation()
         .unwrap();
-    locked_ws.locked_wc().check_out(&commit).block_on().unwrap();
+    locked_ws
+        .locked_wc()
+        .check_out(&commit, &CheckoutOptions::default())
+        .block_on()
+        .unwrap();
     locked_ws
         .finish(test_workspace.repo.op_id().clone())
         .unwrap();
@@ -20,6 +20,7 @@ use jj_lib::repo::Repo as _;
 use jj_lib::repo_path::RepoPath;
 use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::working_copy::CheckoutStats;
+use jj_lib::working_copy::SparsePatterns;
 use jj_lib::working_copy::WorkingCopy as _;
 use pollster::FutureExt as _;
 use testutils::TestWorkspace;
@@ -68,7 +69,7 @@ fn test_sparse_checkout() {
 
     // Set sparse patterns to only dir1/
     let mut locked_ws = ws.start_working_copy_mutation().unwrap();
-    let sparse_patterns = to_owned_path_vec(&[dir1_path]);
+    let sparse_patterns = SparsePatterns::new(to_owned_path_vec(&[dir1_path]));
     let stats = locked_ws
         .locked_wc()
         .set_sparse_patterns(sparse_patterns.clone())
@@ -81,10 +82,11 @@ fn test_sparse_checkout() {
             added_files: 0,
             removed_files: 3,
             skipped_files: 0,
+            case_collisions: vec![],
         }
     );
     assert_eq!(
-        locked_ws.locked_wc().sparse_patterns().unwrap(),
+        *locked_ws.locked_wc().sparse_patterns().unwrap(),
         sparse_patterns
     );
     assert!(
@@ -125,7 +127,7 @@ fn test_sparse_checkout() {
         wc.file_states().unwrap().paths().collect_vec(),
         vec![dir1_file1_path, dir1_file2_path, dir1_subdir1_file1_path]
     );
-    assert_eq!(wc.sparse_patterns().unwrap(), sparse_patterns);
+    assert_eq!(*wc.sparse_patterns().unwrap(), sparse_patterns);
 
     // Reload the state to check that it was persisted
     let wc = LocalWorkingCopy::load(
@@ -139,11 +141,15 @@ fn test_sparse_checkout() {
         wc.file_states().unwrap().paths().collect_vec(),
         vec![dir1_file1_path, dir1_file2_path, dir1_subdir1_file1_path]
     );
-    assert_eq!(wc.sparse_patterns().unwrap(), sparse_patterns);
+    assert_eq!(*wc.sparse_patterns().unwrap(), sparse_patterns);
 
     // Set sparse patterns to file2, dir1/subdir1/ and dir2/
     let mut locked_wc = wc.start_mutation().unwrap();
-    let sparse_patterns = to_owned_path_vec(&[root_file1_path, dir1_subdir1_path, dir2_path]);
+    let sparse_patterns = SparsePatterns::new(to_owned_path_vec(&[
+        root_file1_path,
+        dir1_subdir1_path,
+        dir2_path,
+    ]));
     let stats = locked_wc
         .set_sparse_patterns(sparse_patterns.clone())
         .block_on()
@@ -155,9 +161,10 @@ fn test_sparse_checkout() {
             added_files: 2,
             removed_files: 2,
             skipped_files: 0,
+            case_collisions: vec![],
         }
     );
-    assert_eq!(locked_wc.sparse_patterns().unwrap(), sparse_patterns);
+    assert_eq!(*locked_wc.sparse_patterns().unwrap(), sparse_patterns);
     assert!(
         root_file1_path
             .to_fs_path_unchecked(&working_copy_path)
@@ -197,6 +204,91 @@ fn test_sparse_checkout() {
 }
 
 /// Test that sparse patterns are respected on commit
+#[test]
+fn test_sparse_checkout_excluded_and_cone() {
+    let mut test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let working_copy_path = test_workspace.workspace.workspace_root().to_owned();
+
+    let root_file1_path = repo_path("file1");
+    let dir1_path = repo_path("dir1");
+    let dir1_file1_path = repo_path("dir1/file1");
+    let dir1_subdir1_path = repo_path("dir1/subdir1");
+    let dir1_subdir1_file1_path = repo_path("dir1/subdir1/file1");
+    let dir1_subdir2_file1_path = repo_path("dir1/subdir2/file1");
+    let dir2_file1_path = repo_path("dir2/file1");
+
+    let tree = create_tree(
+        repo,
+        &[
+            (root_file1_path, "contents"),
+            (dir1_file1_path, "contents"),
+            (dir1_subdir1_file1_path, "contents"),
+            (dir1_subdir2_file1_path, "contents"),
+            (dir2_file1_path, "contents"),
+        ],
+    );
+    let commit = commit_with_tree(repo.store(), tree);
+
+    test_workspace
+        .workspace
+        .check_out(repo.op_id().clone(), None, &commit)
+        .unwrap();
+    let ws = &mut test_workspace.workspace;
+    let exists = |path: &RepoPath| path.to_fs_path_unchecked(&working_copy_path).exists();
+
+    // Include dir1/ except dir1/subdir1/
+    let mut locked_ws = ws.start_working_copy_mutation().unwrap();
+    let sparse_patterns = SparsePatterns {
+        included: to_owned_path_vec(&[dir1_path]),
+        excluded: to_owned_path_vec(&[dir1_subdir1_path]),
+        cone: false,
+    };
+    let stats = locked_ws
+        .locked_wc()
+        .set_sparse_patterns(sparse_patterns)
+        .block_on()
+        .unwrap();
+    assert_eq!(stats.removed_files, 3);
+    assert!(!exists(root_file1_path));
+    assert!(exists(dir1_file1_path));
+    assert!(!exists(dir1_subdir1_file1_path));
+    assert!(exists(dir1_subdir2_file1_path));
+    assert!(!exists(dir2_file1_path));
+
+    // In cone mode, files in the parent directories of dir1/subdir2/ are
+    // included too
+    let sparse_patterns = SparsePatterns {
+        included: to_owned_path_vec(&[repo_path("dir1/subdir2")]),
+        excluded: vec![],
+        cone: true,
+    };
+    let stats = locked_ws
+        .locked_wc()
+        .set_sparse_patterns(sparse_patterns.clone())
+        .block_on()
+        .unwrap();
+    assert_eq!(stats.added_files, 1);
+    assert_eq!(stats.removed_files, 0);
+    assert!(exists(root_file1_path));
+    assert!(exists(dir1_file1_path));
+    assert!(!exists(dir1_subdir1_file1_path));
+    assert!(exists(dir1_subdir2_file1_path));
+    assert!(!exists(dir2_file1_path));
+
+    // The patterns are persisted
+    locked_ws.finish(repo.op_id().clone()).unwrap();
+    let wc: &LocalWorkingCopy = ws.working_copy().downcast_ref().unwrap();
+    let wc = LocalWorkingCopy::load(
+        repo.store().clone(),
+        ws.workspace_root().to_path_buf(),
+        wc.state_path().to_path_buf(),
+        repo.settings(),
+    )
+    .unwrap();
+    assert_eq!(*wc.sparse_patterns().unwrap(), sparse_patterns);
+}
+
 #[test]
 fn test_sparse_commit() {
     let mut test_workspace = TestWorkspace::init();
@@ -230,7 +322,7 @@ fn test_sparse_commit() {
         .workspace
         .start_working_copy_mutation()
         .unwrap();
-    let sparse_patterns = to_owned_path_vec(&[dir1_path]);
+    let sparse_patterns = SparsePatterns::new(to_owned_path_vec(&[dir1_path]));
     locked_ws
         .locked_wc()
         .set_sparse_patterns(sparse_patterns)
@@ -272,7 +364,7 @@ fn test_sparse_commit() {
         .workspace
         .start_working_copy_mutation()
         .unwrap();
-    let sparse_patterns = to_owned_path_vec(&[dir1_path, dir2_path]);
+    let sparse_patterns = SparsePatterns::new(to_owned_path_vec(&[dir1_path, dir2_path]));
     locked_ws
         .locked_wc()
         .set_sparse_patterns(sparse_patterns)
@@ -308,7 +400,7 @@ fn test_sparse_commit_gitignore() {
         .workspace
         .start_working_copy_mutation()
         .unwrap();
-    let sparse_patterns = to_owned_path_vec(&[dir1_path]);
+    let sparse_patterns = SparsePatterns::new(to_owned_path_vec(&[dir1_path]));
     locked_ws
         .locked_wc()
         .set_sparse_patterns(sparse_patterns)
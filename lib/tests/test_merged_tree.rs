@@ -23,6 +23,9 @@ use jj_lib::copies::CopiesTreeDiffEntryPath;
 use jj_lib::copies::CopyOperation;
 use jj_lib::copies::CopyRecords;
 use jj_lib::files;
+use jj_lib::fileset;
+use jj_lib::fileset::FilesetDiagnostics;
+use jj_lib::fileset::FilesetExtensions;
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::matchers::FilesMatcher;
 use jj_lib::matchers::Matcher;
@@ -38,12 +41,14 @@ use jj_lib::merged_tree_builder::MergedTreeBuilder;
 use jj_lib::repo::Repo as _;
 use jj_lib::repo_path::RepoPath;
 use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::repo_path::RepoPathUiConverter;
 use pollster::FutureExt as _;
 use pretty_assertions::assert_eq;
 use testutils::TestRepo;
 use testutils::assert_tree_eq;
 use testutils::create_single_tree;
 use testutils::create_tree;
+use testutils::create_tree_with;
 use testutils::repo_path;
 use testutils::repo_path_buf;
 use testutils::repo_path_component;
@@ -1712,3 +1717,74 @@ fn test_merge_simplify_file_conflict_with_absent() {
     .unwrap();
     assert_tree_eq!(merged, expected_merged);
 }
+
+#[test]
+fn test_resolve_fileset_predicates() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let small_path = repo_path("small");
+    let large_path = repo_path("dir/large");
+    let exec_path = repo_path("dir/exec");
+    let link_path = repo_path("link");
+    let conflict_path = repo_path("conflict");
+    let large_content = "x".repeat(2000);
+    let base = create_tree_with(repo, |builder| {
+        builder.file(small_path, "small");
+        builder.file(large_path, &large_content);
+        builder.file(exec_path, "exec").executable(true);
+        builder.symlink(link_path, "target");
+        builder.file(conflict_path, "base");
+    });
+    let side1 = create_tree_with(repo, |builder| {
+        builder.file(small_path, "small");
+        builder.file(large_path, &large_content);
+        builder.file(exec_path, "exec").executable(true);
+        builder.symlink(link_path, "target");
+        builder.file(conflict_path, "side 1");
+    });
+    let side2 = create_tree_with(repo, |builder| {
+        builder.file(small_path, "small");
+        builder.file(large_path, &large_content);
+        builder.file(exec_path, "exec").executable(true);
+        builder.symlink(link_path, "target");
+        builder.file(conflict_path, &large_content);
+    });
+    let tree = MergedTree::merge(Merge::from_vec(vec![
+        (side1, "side 1".into()),
+        (base, "base".into()),
+        (side2, "side 2".into()),
+    ]))
+    .block_on()
+    .unwrap();
+
+    let resolve = |text: &str| -> Vec<String> {
+        let expression = fileset::parse(
+            &mut FilesetDiagnostics::new(),
+            text,
+            &RepoPathUiConverter::Fs {
+                cwd: "".into(),
+                base: "".into(),
+            },
+            &FilesetExtensions::new(),
+        )
+        .unwrap();
+        let matcher = expression
+            .resolve_predicates(&[&tree])
+            .block_on()
+            .unwrap()
+            .to_matcher();
+        tree.entries_matching(matcher.as_ref())
+            .map(|(path, _)| path.as_internal_file_string().to_owned())
+            .collect()
+    };
+    assert_eq!(resolve("size(\">1KiB\")"), ["conflict", "dir/large"]);
+    assert_eq!(resolve("dir & size(\">1KiB\")"), ["dir/large"]);
+    assert_eq!(resolve("executable()"), ["dir/exec"]);
+    assert_eq!(resolve("symlink()"), ["link"]);
+    assert_eq!(resolve("conflict()"), ["conflict"]);
+    assert_eq!(
+        resolve("~(symlink() | conflict() | size(..3))"),
+        ["dir/exec", "dir/large", "small"]
+    );
+}
@@ -26,10 +26,14 @@ use jj_lib::evolution::walk_predecessors;
 use jj_lib::index::Index;
 use jj_lib::object_id::ObjectId as _;
 use jj_lib::op_store::OperationId;
+use jj_lib::op_store::RefTarget;
 use jj_lib::op_walk;
+use jj_lib::op_walk::BookmarkChangeIndex;
 use jj_lib::op_walk::OpsetEvaluationError;
 use jj_lib::op_walk::OpsetResolutionError;
 use jj_lib::operation::Operation;
+use jj_lib::ref_name::RefName;
+use jj_lib::ref_name::RefNameBuf;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
 use jj_lib::settings::UserSettings;
@@ -977,6 +981,109 @@ fn test_walk_ancestors() {
     );
 }
 
+#[test]
+fn test_walk_bookmark_changes() {
+    let test_repo = TestRepo::init();
+    let repo_0 = test_repo.repo.clone();
+    let name: &RefName = "main".as_ref();
+    let index_dir = test_repo.repo_path().join("bookmark_change_index");
+    let index = BookmarkChangeIndex::new(index_dir.clone());
+
+    let collect_changes =
+        |head_op: &Operation, name: &RefName| -> Vec<(OperationId, RefTarget, RefTarget)> {
+            op_walk::walk_bookmark_changes(&index, slice::from_ref(head_op), name)
+                .map_ok(|change| {
+                    (
+                        change.operation.id().clone(),
+                        change.old_target,
+                        change.new_target,
+                    )
+                })
+                .try_collect()
+                .unwrap()
+        };
+
+    // Set up operation graph:
+    // E (merge: main=2)
+    // |\
+    // | D (main=2)
+    // C | (unrelated)
+    // |/
+    // B (main=1)
+    // A (create commits)
+    // 0 (initial)
+    let mut tx = repo_0.start_transaction();
+    let commit1 = write_random_commit(tx.repo_mut());
+    let commit2 = write_random_commit(tx.repo_mut());
+    let repo_a = tx.commit("op A").unwrap();
+    let mut tx = repo_a.start_transaction();
+    tx.repo_mut()
+        .set_local_bookmark_target(name, RefTarget::normal(commit1.id().clone()));
+    let repo_b = tx.commit("op B").unwrap();
+    let mut tx_c = repo_b.start_transaction();
+    tx_c.repo_mut()
+        .set_local_bookmark_target("other".as_ref(), RefTarget::normal(commit2.id().clone()));
+    let mut tx_d = repo_b.start_transaction();
+    tx_d.repo_mut()
+        .set_local_bookmark_target(name, RefTarget::normal(commit2.id().clone()));
+    let repo_e = testutils::commit_transactions(vec![tx_c, tx_d]);
+    let [_op_c, op_d]: [Operation; 2] = repo_e
+        .operation()
+        .parents()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let mut tx = repo_e.start_transaction();
+    tx.repo_mut()
+        .set_local_bookmark_target(name, RefTarget::absent());
+    let repo_f = tx.commit("op F").unwrap();
+
+    // The merge operation E doesn't change the bookmark since the target is the
+    // same as the parent D.
+    assert_eq!(
+        collect_changes(repo_f.operation(), name),
+        [
+            (
+                repo_f.operation().id().clone(),
+                RefTarget::normal(commit2.id().clone()),
+                RefTarget::absent(),
+            ),
+            (
+                op_d.id().clone(),
+                RefTarget::normal(commit1.id().clone()),
+                RefTarget::normal(commit2.id().clone()),
+            ),
+            (
+                repo_b.operation().id().clone(),
+                RefTarget::absent(),
+                RefTarget::normal(commit1.id().clone()),
+            ),
+        ]
+    );
+
+    // Unknown bookmark
+    assert_eq!(collect_changes(repo_f.operation(), "unknown".as_ref()), []);
+
+    // All operations are indexed, including the merge operation
+    assert_eq!(
+        index.changed_bookmarks(repo_f.operation()).unwrap(),
+        [RefNameBuf::from("main")]
+    );
+    assert!(
+        index
+            .changed_bookmarks(repo_e.operation())
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(index_dir.read_dir().unwrap().count(), 7);
+
+    // Entries of unreachable operations are removed by gc
+    index.gc(slice::from_ref(repo_b.operation())).unwrap();
+    assert_eq!(index_dir.read_dir().unwrap().count(), 3);
+    assert_eq!(collect_changes(repo_f.operation(), name).len(), 3);
+}
+
 #[test]
 fn test_gc() {
     let settings = stable_op_id_settings();
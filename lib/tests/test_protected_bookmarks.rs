@@ -0,0 +1,214 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use assert_matches::assert_matches;
+use jj_lib::backend::CommitId;
+use jj_lib::config::ConfigLayer;
+use jj_lib::config::ConfigSource;
+use jj_lib::op_store::RefTarget;
+use jj_lib::protected_bookmarks::ProtectedBookmarkError;
+use jj_lib::protected_bookmarks::ProtectedBookmarks;
+use jj_lib::ref_name::RefName;
+use jj_lib::refs::BookmarkPushUpdate;
+use jj_lib::repo::Repo as _;
+use jj_lib::settings::UserSettings;
+use jj_lib::transaction::TransactionCommitError;
+use testutils::TestRepo;
+use testutils::base_user_config;
+use testutils::write_random_commit;
+use testutils::write_random_commit_with_parents;
+
+fn settings_with_protected_bookmarks(patterns: &str) -> UserSettings {
+    let mut config = base_user_config();
+    config.add_layer(
+        ConfigLayer::parse(
+            ConfigSource::User,
+            &format!("bookmarks.protect = {patterns}"),
+        )
+        .unwrap(),
+    );
+    UserSettings::from_config(config).unwrap()
+}
+
+fn protected_bookmarks(patterns: &str) -> ProtectedBookmarks {
+    let settings = settings_with_protected_bookmarks(patterns);
+    ProtectedBookmarks::from_settings(&settings).unwrap()
+}
+
+#[test]
+fn test_protected_bookmarks_patterns() {
+    let protected = protected_bookmarks(r#"["main", "release/*"]"#);
+    assert!(protected.is_protected(RefName::new("main")));
+    assert!(protected.is_protected(RefName::new("release/1.0")));
+    assert!(!protected.is_protected(RefName::new("main2")));
+    assert!(!protected.is_protected(RefName::new("feature")));
+
+    let protected = protected_bookmarks("[]");
+    assert!(!protected.is_protected(RefName::new("main")));
+}
+
+#[test]
+fn test_protected_bookmarks_local_changes() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let protected = protected_bookmarks(r#"["main"]"#);
+
+    let mut tx = repo.start_transaction();
+    let commit1 = write_random_commit(tx.repo_mut());
+    let commit2 = write_random_commit_with_parents(tx.repo_mut(), &[&commit1]);
+    let commit3 = write_random_commit(tx.repo_mut());
+    for name in ["main", "feature"] {
+        tx.repo_mut()
+            .set_local_bookmark_target(RefName::new(name), RefTarget::normal(commit1.id().clone()));
+    }
+    let repo = tx.commit("test").unwrap();
+    let main = RefName::new("main");
+    let feature = RefName::new("feature");
+
+    // Moving forward is allowed
+    let mut tx = repo.start_transaction();
+    tx.repo_mut()
+        .set_local_bookmark_target(main, RefTarget::normal(commit2.id().clone()));
+    assert_matches!(
+        protected.check_local_bookmark_changes(tx.repo(), repo.view()),
+        Ok(())
+    );
+
+    // Moving sideways isn't allowed
+    tx.repo_mut()
+        .set_local_bookmark_target(main, RefTarget::normal(commit3.id().clone()));
+    assert_matches!(
+        protected.check_local_bookmark_changes(tx.repo(), repo.view()),
+        Err(ProtectedBookmarkError::NotFastForward { name }) if name == *main
+    );
+
+    // Deleting isn't allowed
+    tx.repo_mut()
+        .set_local_bookmark_target(main, RefTarget::absent());
+    assert_matches!(
+        protected.check_local_bookmark_changes(tx.repo(), repo.view()),
+        Err(ProtectedBookmarkError::Deleted { name }) if name == *main
+    );
+
+    // Unprotected bookmarks can be moved freely
+    let mut tx = repo.start_transaction();
+    tx.repo_mut()
+        .set_local_bookmark_target(feature, RefTarget::normal(commit3.id().clone()));
+    tx.repo_mut()
+        .set_local_bookmark_target(RefName::new("new"), RefTarget::normal(commit3.id().clone()));
+    assert_matches!(
+        protected.check_local_bookmark_changes(tx.repo(), repo.view()),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_protected_bookmarks_push_update() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let protected = protected_bookmarks(r#"["main"]"#);
+
+    let mut tx = repo.start_transaction();
+    let commit1 = write_random_commit(tx.repo_mut());
+    let commit2 = write_random_commit_with_parents(tx.repo_mut(), &[&commit1]);
+    let repo = tx.commit("test").unwrap();
+    let main = RefName::new("main");
+    let update = |old: Option<&CommitId>, new: Option<&CommitId>| BookmarkPushUpdate {
+        old_target: old.cloned(),
+        new_target: new.cloned(),
+    };
+
+    let check = |update| protected.check_push_update(repo.as_ref(), main, &update);
+    assert_matches!(check(update(None, Some(commit1.id()))), Ok(()));
+    assert_matches!(
+        check(update(Some(commit1.id()), Some(commit2.id()))),
+        Ok(())
+    );
+    assert_matches!(
+        check(update(Some(commit2.id()), Some(commit1.id()))),
+        Err(ProtectedBookmarkError::NotFastForward { .. })
+    );
+    assert_matches!(
+        check(update(Some(commit1.id()), None)),
+        Err(ProtectedBookmarkError::Deleted { .. })
+    );
+}
+
+#[test]
+fn test_protected_bookmarks_transaction() {
+    let settings = settings_with_protected_bookmarks(r#"["main"]"#);
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let repo = &test_repo.repo;
+    let main = RefName::new("main");
+
+    let mut tx = repo.start_transaction();
+    let commit1 = write_random_commit(tx.repo_mut());
+    let commit2 = write_random_commit_with_parents(tx.repo_mut(), &[&commit1]);
+    let commit3 = write_random_commit(tx.repo_mut());
+    tx.repo_mut()
+        .set_local_bookmark_target(main, RefTarget::normal(commit2.id().clone()));
+    let repo = tx.commit("test").unwrap();
+
+    // Abandoning the target moves the bookmark backwards
+    let mut tx = repo.start_transaction();
+    tx.repo_mut().record_abandoned_commit(&commit2);
+    tx.repo_mut().rebase_descendants().unwrap();
+    assert_eq!(
+        tx.repo().view().get_local_bookmark(main),
+        &RefTarget::normal(commit1.id().clone())
+    );
+    assert_matches!(
+        tx.commit("abandon"),
+        Err(TransactionCommitError::ProtectedBookmark(
+            ProtectedBookmarkError::NotFastForward { name }
+        )) if name == *main
+    );
+
+    // Forgetting the bookmark deletes it
+    let mut tx = repo.start_transaction();
+    tx.repo_mut()
+        .set_local_bookmark_target(main, RefTarget::absent());
+    assert_matches!(
+        tx.commit("forget"),
+        Err(TransactionCommitError::ProtectedBookmark(
+            ProtectedBookmarkError::Deleted { name }
+        )) if name == *main
+    );
+
+    // Unless it's explicitly allowed
+    let mut tx = repo.start_transaction();
+    tx.repo_mut()
+        .set_local_bookmark_target(main, RefTarget::normal(commit3.id().clone()));
+    tx.allow_protected_bookmarks();
+    let repo = tx.commit("move sideways").unwrap();
+    assert_eq!(
+        repo.view().get_local_bookmark(main),
+        &RefTarget::normal(commit3.id().clone())
+    );
+
+    // Concurrent operations can be merged even if the bookmark becomes
+    // conflicted
+    let mut tx1 = repo.start_transaction();
+    let commit4 = write_random_commit_with_parents(tx1.repo_mut(), &[&commit3]);
+    tx1.repo_mut()
+        .set_local_bookmark_target(main, RefTarget::normal(commit4.id().clone()));
+    tx1.commit("move forward 1").unwrap();
+    let mut tx2 = repo.start_transaction();
+    let commit5 = write_random_commit_with_parents(tx2.repo_mut(), &[&commit3]);
+    tx2.repo_mut()
+        .set_local_bookmark_target(main, RefTarget::normal(commit5.id().clone()));
+    tx2.commit("move forward 2").unwrap();
+    let repo = repo.reload_at_head().unwrap();
+    assert!(repo.view().get_local_bookmark(main).has_conflict());
+}
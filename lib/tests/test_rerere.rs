@@ -0,0 +1,178 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::commit::Commit;
+use jj_lib::config::ConfigLayer;
+use jj_lib::config::ConfigSource;
+use jj_lib::repo::MutableRepo;
+use jj_lib::repo::Repo as _;
+use jj_lib::rewrite::CommitRewriter;
+use jj_lib::rewrite::RebaseOptions;
+use jj_lib::rewrite::RebasedCommit;
+use jj_lib::rewrite::rebase_commit_with_options;
+use jj_lib::settings::UserSettings;
+use testutils::TestRepo;
+use testutils::assert_tree_eq;
+use testutils::base_user_config;
+use testutils::create_tree;
+use testutils::repo_path;
+
+fn rerere_settings() -> UserSettings {
+    let mut config = base_user_config();
+    config.add_layer(ConfigLayer::parse(ConfigSource::User, "rerere.enabled = true").unwrap());
+    UserSettings::from_config(config).unwrap()
+}
+
+fn rebase_onto(mut_repo: &mut MutableRepo, commit: Commit, parent: &Commit) -> Commit {
+    let rewriter = CommitRewriter::new(mut_repo, commit, vec![parent.id().clone()]);
+    match rebase_commit_with_options(rewriter, &RebaseOptions::default()).unwrap() {
+        RebasedCommit::Rewritten(commit) => commit,
+        RebasedCommit::Abandoned { .. } => panic!("commit should not be abandoned"),
+    }
+}
+
+#[test]
+fn test_rerere_record_and_replay() {
+    let settings = rerere_settings();
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let repo = &test_repo.repo;
+    let rerere_store = repo.loader().rerere_store().clone();
+    let path = repo_path("file");
+
+    let base_tree = create_tree(repo, &[(path, "a\n")]);
+    let side1_tree = create_tree(repo, &[(path, "b\n")]);
+    let side2_tree = create_tree(repo, &[(path, "c\n")]);
+    let resolved_tree = create_tree(repo, &[(path, "b\nc\n")]);
+
+    let mut tx = repo.start_transaction();
+    let root_id = repo.store().root_commit_id().clone();
+    let base = tx
+        .repo_mut()
+        .new_commit(vec![root_id], base_tree)
+        .write()
+        .unwrap();
+    let side1 = tx
+        .repo_mut()
+        .new_commit(vec![base.id().clone()], side1_tree)
+        .write()
+        .unwrap();
+    let side2 = tx
+        .repo_mut()
+        .new_commit(vec![base.id().clone()], side2_tree.clone())
+        .write()
+        .unwrap();
+    let side3 = tx
+        .repo_mut()
+        .new_commit(vec![base.id().clone()], side2_tree)
+        .set_description("same change as side2")
+        .write()
+        .unwrap();
+
+    let repo = tx.commit("test").unwrap();
+
+    // Nothing is recorded yet, so the conflict is left as is
+    let mut tx = repo.start_transaction();
+    let rebased2 = rebase_onto(tx.repo_mut(), side2.clone(), &side1);
+    assert!(rebased2.has_conflict());
+    assert!(tx.repo().auto_resolved_paths().is_empty());
+    assert!(rerere_store.list().unwrap().is_empty());
+
+    // Resolutions aren't recorded until the transaction is committed
+    tx.repo_mut()
+        .rewrite_commit(&rebased2)
+        .set_tree(resolved_tree.clone())
+        .write()
+        .unwrap();
+    drop(tx);
+    assert!(rerere_store.list().unwrap().is_empty());
+
+    // Resolving the conflict without changing the parents records the
+    // resolution
+    let mut tx = repo.start_transaction();
+    let rebased2 = rebase_onto(tx.repo_mut(), side2, &side1);
+    let resolved2 = tx
+        .repo_mut()
+        .rewrite_commit(&rebased2)
+        .set_tree(resolved_tree.clone())
+        .write()
+        .unwrap();
+    assert!(!resolved2.has_conflict());
+    tx.repo_mut().rebase_descendants().unwrap();
+    assert!(rerere_store.list().unwrap().is_empty());
+    let repo = tx.commit("test").unwrap();
+    let recorded = rerere_store.list().unwrap();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(recorded[0].path, path.to_owned());
+
+    // The same conflict is resolved automatically
+    let mut tx = repo.start_transaction();
+    let rebased3 = rebase_onto(tx.repo_mut(), side3.clone(), &side1);
+    assert!(!rebased3.has_conflict());
+    assert_tree_eq!(rebased3.tree(), resolved_tree);
+    assert_eq!(
+        tx.repo().auto_resolved_paths().get(rebased3.id()),
+        Some(&vec![path.to_owned()])
+    );
+    drop(tx);
+
+    // Forgotten resolutions are no longer applied
+    assert!(rerere_store.forget(&recorded[0].fingerprint).unwrap());
+    assert!(!rerere_store.forget(&recorded[0].fingerprint).unwrap());
+    assert!(rerere_store.list().unwrap().is_empty());
+    let mut tx = repo.start_transaction();
+    let rebased3 = rebase_onto(tx.repo_mut(), side3, &side1);
+    assert!(rebased3.has_conflict());
+    assert!(tx.repo().auto_resolved_paths().is_empty());
+}
+
+#[test]
+fn test_rerere_disabled() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let rerere_store = repo.loader().rerere_store().clone();
+    let path = repo_path("file");
+
+    let base_tree = create_tree(repo, &[(path, "a\n")]);
+    let side1_tree = create_tree(repo, &[(path, "b\n")]);
+    let side2_tree = create_tree(repo, &[(path, "c\n")]);
+    let resolved_tree = create_tree(repo, &[(path, "b\nc\n")]);
+
+    let mut tx = repo.start_transaction();
+    let root_id = repo.store().root_commit_id().clone();
+    let base = tx
+        .repo_mut()
+        .new_commit(vec![root_id], base_tree)
+        .write()
+        .unwrap();
+    let side1 = tx
+        .repo_mut()
+        .new_commit(vec![base.id().clone()], side1_tree)
+        .write()
+        .unwrap();
+    let side2 = tx
+        .repo_mut()
+        .new_commit(vec![base.id().clone()], side2_tree)
+        .write()
+        .unwrap();
+    let rebased2 = rebase_onto(tx.repo_mut(), side2, &side1);
+    assert!(rebased2.has_conflict());
+    tx.repo_mut()
+        .rewrite_commit(&rebased2)
+        .set_tree(resolved_tree)
+        .write()
+        .unwrap();
+    tx.repo_mut().rebase_descendants().unwrap();
+    tx.commit("test").unwrap();
+    assert!(rerere_store.list().unwrap().is_empty());
+}
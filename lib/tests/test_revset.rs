@@ -47,14 +47,17 @@ use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
 use jj_lib::repo_path::RepoPathUiConverter;
+use jj_lib::revset;
 use jj_lib::revset::ResolvedRevsetExpression;
 use jj_lib::revset::Revset;
 use jj_lib::revset::RevsetAliasesMap;
+use jj_lib::revset::RevsetCommitsExtension;
 use jj_lib::revset::RevsetDiagnostics;
 use jj_lib::revset::RevsetEvaluationError;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetExtensions;
 use jj_lib::revset::RevsetFilterPredicate;
+use jj_lib::revset::RevsetFunctionConflictError;
 use jj_lib::revset::RevsetParseContext;
 use jj_lib::revset::RevsetResolutionError;
 use jj_lib::revset::RevsetWorkspaceContext;
@@ -130,7 +133,7 @@ fn revset_for_commits<'index>(
 fn build_changed_path_index(repo: &ReadonlyRepo) -> Arc<ReadonlyRepo> {
     let default_index_store: &DefaultIndexStore = repo.index_store().downcast_ref().unwrap();
     default_index_store
-        .build_changed_path_index_at_operation(repo.op_id(), repo.store(), u32::MAX)
+        .build_changed_path_index_at_operation(repo.op_id(), repo.store(), u32::MAX, None)
         .block_on()
         .unwrap();
     repo.reload_at(repo.operation()).unwrap()
@@ -2434,6 +2437,52 @@ fn test_evaluate_expression_visible_heads() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_visible_heads_ephemeral_working_copy() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+    let commit1 = write_random_commit(mut_repo);
+    let commit2 = write_random_commit_with_parents(mut_repo, &[&commit1]);
+    let commit3 = write_random_commit(mut_repo);
+    let ws_name = WorkspaceNameBuf::from("ws");
+    mut_repo
+        .set_wc_commit(ws_name.clone(), commit2.id().clone())
+        .unwrap();
+    mut_repo.set_ephemeral_workspace(ws_name, true);
+
+    // The working-copy commit of an ephemeral workspace isn't a visible head,
+    // but its parent is.
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "visible_heads()"),
+        vec![commit3.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "all()"),
+        vec![
+            commit3.id().clone(),
+            commit1.id().clone(),
+            mut_repo.store().root_commit_id().clone(),
+        ]
+    );
+    // It can still be referenced explicitly.
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "ws@"),
+        vec![commit2.id().clone()]
+    );
+    // Like other hidden commits, it isn't a descendant unless referenced.
+    assert_eq!(
+        resolve_commit_ids(mut_repo, &format!("{}::", commit1.id().hex())),
+        vec![commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, &format!("{}:: | ws@", commit1.id().hex())),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+}
+
 #[test]
 fn test_evaluate_expression_git_refs() {
     let test_repo = TestRepo::init();
@@ -4814,6 +4863,111 @@ fn test_evaluate_expression_divergent() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_extension_commits() {
+    #[derive(Debug)]
+    struct LabeledCommits {
+        labels: Arc<HashMap<String, Vec<CommitId>>>,
+        label: String,
+    }
+
+    impl RevsetCommitsExtension for LabeledCommits {
+        fn resolve(&self, repo: &dyn Repo) -> Result<Vec<CommitId>, RevsetResolutionError> {
+            let ids = self.labels.get(&self.label).ok_or_else(|| {
+                RevsetResolutionError::Other(format!("No label {}", self.label).into())
+            })?;
+            Ok(ids
+                .iter()
+                .filter(|id| repo.index().has_id(id).unwrap())
+                .cloned()
+                .collect())
+        }
+    }
+
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+    let commit1 = write_random_commit(mut_repo);
+    let commit2 = write_random_commit_with_parents(mut_repo, &[&commit1]);
+    let commit3 = write_random_commit(mut_repo);
+    let missing_id = CommitId::from_hex("0123456789abcdef0123456789abcdef01234567");
+
+    let labels = Arc::new(HashMap::from([
+        ("a".to_owned(), vec![commit1.id().clone(), missing_id]),
+        ("b".to_owned(), vec![commit3.id().clone()]),
+    ]));
+    let mut extensions = RevsetExtensions::new();
+    assert!(!extensions.has_function("labeled"));
+    extensions
+        .add_boxed_function(
+            "labeled",
+            Box::new(move |_diagnostics, function, _context| {
+                let [arg] = function.expect_exact_arguments()?;
+                let label = revset::expect_literal("string", arg)?;
+                Ok(RevsetExpression::extension_commits(Arc::new(
+                    LabeledCommits {
+                        labels: labels.clone(),
+                        label,
+                    },
+                )))
+            }),
+        )
+        .unwrap();
+    assert!(extensions.has_function("labeled"));
+    // Conflicting names are rejected
+    assert_matches!(
+        extensions.add_boxed_function("labeled", Box::new(|_, _, _| Ok(RevsetExpression::all()))),
+        Err(RevsetFunctionConflictError { name }) if name == "labeled"
+    );
+    assert_matches!(
+        extensions.add_boxed_function("all", Box::new(|_, _, _| Ok(RevsetExpression::none()))),
+        Err(RevsetFunctionConflictError { name }) if name == "all"
+    );
+
+    let settings = testutils::user_settings();
+    let context = RevsetParseContext {
+        aliases_map: &RevsetAliasesMap::default(),
+        local_variables: HashMap::new(),
+        user_email: settings.user_email(),
+        date_pattern_context: chrono::Utc::now().fixed_offset().into(),
+        default_ignored_remote: Some(git::REMOTE_NAME_FOR_LOCAL_GIT_REPO),
+        use_glob_by_default: true,
+        extensions: &extensions,
+        workspace: None,
+    };
+    let symbol_resolver = default_symbol_resolver(mut_repo);
+    let resolve = |revset_str: &str| {
+        let expression = parse(&mut RevsetDiagnostics::new(), revset_str, &context).unwrap();
+        let expression = expression.resolve_user_expression(mut_repo, &symbol_resolver)?;
+        Ok::<_, RevsetResolutionError>(
+            expression
+                .evaluate(mut_repo)
+                .unwrap()
+                .iter()
+                .map(Result::unwrap)
+                .collect_vec(),
+        )
+    };
+
+    // Commits which aren't in the repo are left out
+    assert_eq!(resolve("labeled(a)").unwrap(), vec![commit1.id().clone()]);
+    // Can be combined with other expressions
+    assert_eq!(
+        resolve("labeled(a):: | labeled(b)").unwrap(),
+        vec![
+            commit3.id().clone(),
+            commit2.id().clone(),
+            commit1.id().clone()
+        ]
+    );
+    assert_matches!(
+        resolve("labeled(c)"),
+        Err(RevsetResolutionError::Other(err)) if err.to_string() == "No label c"
+    );
+}
+
 #[test]
 fn test_reverse_graph() {
     let test_repo = TestRepo::init();
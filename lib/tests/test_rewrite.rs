@@ -40,6 +40,7 @@ use jj_lib::rewrite::find_recursive_merge_commits;
 use jj_lib::rewrite::merge_commit_trees;
 use jj_lib::rewrite::rebase_commit_with_options;
 use jj_lib::rewrite::restore_tree;
+use maplit::btreemap;
 use maplit::hashmap;
 use maplit::hashset;
 use pollster::FutureExt as _;
@@ -851,6 +852,144 @@ fn test_rebase_descendants_multiple_no_descendants() {
     assert_matches!(result, Err(err) if err.to_string().contains("Cycle"));
 }
 
+#[test]
+fn test_defer_rebase_descendants() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // Commit B was rewritten. Rebasing commits C and D is deferred until B is
+    // rewritten once more, and the deferred rewrites are applied.
+    //
+    // D
+    // C
+    // B B2
+    // |/
+    // A
+    let mut tx = repo.start_transaction();
+    let commit_a = write_random_commit(tx.repo_mut());
+    let commit_b = write_random_commit_with_parents(tx.repo_mut(), &[&commit_a]);
+    let commit_c = write_random_commit_with_parents(tx.repo_mut(), &[&commit_b]);
+    let commit_d = write_random_commit_with_parents(tx.repo_mut(), &[&commit_c]);
+    tx.repo_mut()
+        .set_local_bookmark_target("main".as_ref(), RefTarget::normal(commit_b.id().clone()));
+    let repo = tx.commit("test").unwrap();
+
+    let mut tx = repo.start_transaction();
+    let commit_b2 = tx
+        .repo_mut()
+        .rewrite_commit(&commit_b)
+        .set_description("b2")
+        .write()
+        .unwrap();
+    let (num_rebased, deferred_ids) = tx.repo_mut().defer_rebase_descendants().unwrap();
+    assert_eq!(num_rebased, 0);
+    assert_eq!(deferred_ids, vec![commit_b.id().clone()]);
+    assert_eq!(
+        *tx.repo().view().deferred_rewrites(),
+        btreemap! { commit_b.id().clone() => commit_b2.id().clone() }
+    );
+    assert_eq!(
+        *tx.repo().view().heads(),
+        hashset! { commit_b2.id().clone(), commit_d.id().clone() }
+    );
+    // References are updated as usual
+    assert_eq!(
+        tx.repo().view().get_local_bookmark("main".as_ref()),
+        &RefTarget::normal(commit_b2.id().clone())
+    );
+    let repo = tx.commit("test").unwrap();
+
+    // Rewriting the new commit again updates the deferred rewrite
+    let mut tx = repo.start_transaction();
+    let commit_b3 = tx
+        .repo_mut()
+        .rewrite_commit(&commit_b2)
+        .set_description("b3")
+        .write()
+        .unwrap();
+    let (num_rebased, deferred_ids) = tx.repo_mut().defer_rebase_descendants().unwrap();
+    assert_eq!(num_rebased, 0);
+    assert_eq!(deferred_ids, vec![]);
+    assert_eq!(
+        *tx.repo().view().deferred_rewrites(),
+        btreemap! { commit_b.id().clone() => commit_b3.id().clone() }
+    );
+    let repo = tx.commit("test").unwrap();
+
+    let mut tx = repo.start_transaction();
+    assert_eq!(tx.repo_mut().rebase_deferred_descendants().unwrap(), 2);
+    assert!(tx.repo().view().deferred_rewrites().is_empty());
+    let heads = tx.repo().view().heads().iter().cloned().collect_vec();
+    let [new_commit_d] = &*heads else {
+        panic!("unexpected heads: {heads:?}");
+    };
+    let new_commit_d = repo.store().get_commit(new_commit_d).unwrap();
+    assert_eq!(new_commit_d.change_id(), commit_d.change_id());
+    let new_commit_c = new_commit_d.parents().next().unwrap().unwrap();
+    assert_eq!(new_commit_c.change_id(), commit_c.change_id());
+    assert_eq!(new_commit_c.parent_ids(), [commit_b3.id().clone()]);
+}
+
+#[test]
+fn test_defer_rebase_descendants_abandoned() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // Commit B was rewritten and commit E was abandoned. Commit F is rebased
+    // right away, but rebasing commit C is deferred.
+    //
+    // C   F
+    // B   E
+    // |   |
+    // | B2|
+    // |/  |
+    // A---+
+    let mut tx = repo.start_transaction();
+    let commit_a = write_random_commit(tx.repo_mut());
+    let commit_b = write_random_commit_with_parents(tx.repo_mut(), &[&commit_a]);
+    let commit_c = write_random_commit_with_parents(tx.repo_mut(), &[&commit_b]);
+    let commit_e = write_random_commit_with_parents(tx.repo_mut(), &[&commit_a]);
+    let commit_f = write_random_commit_with_parents(tx.repo_mut(), &[&commit_e]);
+    let repo = tx.commit("test").unwrap();
+
+    let mut tx = repo.start_transaction();
+    let commit_b2 = tx
+        .repo_mut()
+        .rewrite_commit(&commit_b)
+        .set_description("b2")
+        .write()
+        .unwrap();
+    tx.repo_mut().record_abandoned_commit(&commit_e);
+    let (num_rebased, deferred_ids) = tx.repo_mut().defer_rebase_descendants().unwrap();
+    assert_eq!(num_rebased, 1);
+    assert_eq!(deferred_ids, vec![commit_b.id().clone()]);
+    let heads = tx.repo().view().heads().clone();
+    assert_eq!(heads.len(), 3);
+    assert!(heads.contains(commit_b2.id()));
+    assert!(heads.contains(commit_c.id()));
+    assert!(!heads.contains(commit_f.id()));
+    let repo = tx.commit("test").unwrap();
+
+    // Abandoning the new commit rebases the descendants onto its parent
+    let mut tx = repo.start_transaction();
+    tx.repo_mut().record_abandoned_commit(&commit_b2);
+    tx.repo_mut().defer_rebase_descendants().unwrap();
+    assert_eq!(
+        *tx.repo().view().deferred_rewrites(),
+        btreemap! { commit_b.id().clone() => commit_a.id().clone() }
+    );
+    assert_eq!(tx.repo_mut().rebase_deferred_descendants().unwrap(), 1);
+    let new_commit_c = tx
+        .repo()
+        .view()
+        .heads()
+        .iter()
+        .map(|id| repo.store().get_commit(id).unwrap())
+        .find(|commit| commit.change_id() == commit_c.change_id())
+        .unwrap();
+    assert_eq!(new_commit_c.parent_ids(), [commit_a.id().clone()]);
+}
+
 #[test]
 fn test_rebase_descendants_divergent_rewrite() {
     let test_repo = TestRepo::init();
@@ -0,0 +1,389 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use assert_matches::assert_matches;
+use itertools::Itertools as _;
+use jj_lib::backend::Backend as _;
+use jj_lib::backend::BackendError;
+use jj_lib::backend::TreeValue;
+use jj_lib::config::ConfigLayer;
+use jj_lib::config::ConfigSource;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::ReadonlyRepo;
+use jj_lib::repo::Repo as _;
+use jj_lib::settings::UserSettings;
+use jj_lib::simple_backend::SimpleBackend;
+use jj_lib::simple_backend::SimpleBackendEncryptionError;
+use pollster::FutureExt as _;
+use testutils::TestRepo;
+use testutils::TestRepoBackend;
+use testutils::base_user_config;
+use testutils::create_tree;
+use testutils::read_file;
+use testutils::repo_path;
+use tokio::io::AsyncReadExt as _;
+
+fn list_dir(dir: &Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_str().unwrap().to_owned())
+        .sorted()
+        .collect()
+}
+
+#[test]
+fn test_gc() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Simple);
+    let store_path = test_repo.repo_path().join("store");
+    let repo = &test_repo.repo;
+    let base_index = repo.readonly_index();
+
+    // Commit B replaces A, and only shares the unmodified file with it
+    let tree_a = create_tree(
+        repo,
+        &[(repo_path("dir/file"), "a"), (repo_path("same"), "same")],
+    );
+    let tree_b = create_tree(
+        repo,
+        &[(repo_path("dir/file"), "b"), (repo_path("same"), "same")],
+    );
+    let mut tx = repo.start_transaction();
+    let root_commit_id = repo.store().root_commit_id();
+    let commit_a = tx
+        .repo_mut()
+        .new_commit(vec![root_commit_id.clone()], tree_a)
+        .write()
+        .unwrap();
+    let commit_b = tx
+        .repo_mut()
+        .new_commit(vec![root_commit_id.clone()], tree_b)
+        .write()
+        .unwrap();
+    let repo = tx.commit("test").unwrap();
+    let list_objects =
+        || ["commits", "trees", "files"].map(|dir| list_dir(&store_path.join(dir)).len());
+    // The empty tree was written on init
+    assert_eq!(list_objects(), [2, 5, 3]);
+
+    // All kept by file modification time
+    let mut mut_index = base_index.start_modification();
+    mut_index.add_commit(&commit_b).unwrap();
+    repo.store()
+        .gc(mut_index.as_index(), SystemTime::UNIX_EPOCH)
+        .unwrap();
+    assert_eq!(list_objects(), [2, 5, 3]);
+
+    // Don't rely on the exact system time because file modification time might
+    // have lower precision for example.
+    let now = SystemTime::now() + Duration::from_secs(1);
+
+    // All reachable
+    repo.store().gc(repo.index(), now).unwrap();
+    assert_eq!(list_objects(), [2, 5, 3]);
+
+    // A is no longer reachable
+    repo.store().gc(mut_index.as_index(), now).unwrap();
+    assert_eq!(list_objects(), [1, 3, 2]);
+
+    // Reload repo to invalidate cache in Store
+    let repo = test_repo
+        .env
+        .load_repo_at_head(repo.settings(), test_repo.repo_path());
+    assert!(repo.store().get_commit(commit_a.id()).is_err());
+    let commit_b = repo.store().get_commit(commit_b.id()).unwrap();
+    let value = commit_b.tree().path_value(repo_path("same")).unwrap();
+    let Some(Some(TreeValue::File { id, .. })) = value.as_resolved() else {
+        panic!("unexpected value: {value:?}");
+    };
+    assert_eq!(read_file(repo.store(), repo_path("same"), id), b"same");
+}
+
+#[test]
+fn test_compact() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Simple);
+    let store_path = test_repo.repo_path().join("store");
+    let repo = &test_repo.repo;
+    let base_index = repo.readonly_index();
+
+    // A and B contain similar versions of a large file
+    let content_a = (0..1000).map(|i| format!("line {i}\n")).join("");
+    let content_b = content_a.replace("line 500\n", "modified line\n");
+    let tree_a = create_tree(
+        repo,
+        &[(repo_path("file"), &content_a), (repo_path("same"), "same")],
+    );
+    let tree_b = create_tree(
+        repo,
+        &[(repo_path("file"), &content_b), (repo_path("same"), "same")],
+    );
+    let mut tx = repo.start_transaction();
+    let root_commit_id = repo.store().root_commit_id();
+    let commit_a = tx
+        .repo_mut()
+        .new_commit(vec![root_commit_id.clone()], tree_a)
+        .write()
+        .unwrap();
+    let commit_b = tx
+        .repo_mut()
+        .new_commit(vec![root_commit_id.clone()], tree_b)
+        .write()
+        .unwrap();
+    let repo = tx.commit("test").unwrap();
+    let list_objects = || {
+        ["commits", "trees", "files", "packs"].map(|dir| {
+            let dir = store_path.join(dir);
+            if dir.exists() {
+                list_dir(&dir).len()
+            } else {
+                0
+            }
+        })
+    };
+    assert_eq!(list_objects(), [2, 3, 3, 0]);
+
+    let backend = repo.store().backend_impl::<SimpleBackend>().unwrap();
+    let stats = backend.compact(repo.index(), SystemTime::now()).unwrap();
+    assert_eq!(stats.num_objects, 8);
+    assert_eq!(stats.num_deltas, 1);
+    assert_eq!(list_objects(), [0, 0, 0, 1]);
+
+    let read_file_at = |repo: &Arc<ReadonlyRepo>, commit_id, path| {
+        let commit = repo.store().get_commit(commit_id).unwrap();
+        let value = commit.tree().path_value(path).unwrap();
+        let Some(Some(TreeValue::File { id, .. })) = value.as_resolved() else {
+            panic!("unexpected value: {value:?}");
+        };
+        read_file(repo.store(), path, id)
+    };
+    let repo = test_repo
+        .env
+        .load_repo_at_head(repo.settings(), test_repo.repo_path());
+    assert_eq!(
+        read_file_at(&repo, commit_a.id(), repo_path("file")),
+        content_a.as_bytes()
+    );
+    assert_eq!(
+        read_file_at(&repo, commit_b.id(), repo_path("file")),
+        content_b.as_bytes()
+    );
+
+    // Packed objects which are no longer reachable are kept until they expire
+    let mut mut_index = base_index.start_modification();
+    mut_index.add_commit(&commit_b).unwrap();
+    let load_repo = || {
+        test_repo
+            .env
+            .load_repo_at_head(repo.settings(), test_repo.repo_path())
+    };
+    repo.store()
+        .gc(mut_index.as_index(), SystemTime::UNIX_EPOCH)
+        .unwrap();
+    assert_eq!(list_objects(), [0, 0, 0, 1]);
+    let stats = backend
+        .compact(mut_index.as_index(), SystemTime::UNIX_EPOCH)
+        .unwrap();
+    assert_eq!(stats.num_objects, 5);
+    assert_eq!(stats.num_deltas, 0);
+    // They're written out as loose files when repacking
+    assert_eq!(list_objects(), [1, 1, 1, 1]);
+    assert_eq!(
+        read_file_at(&load_repo(), commit_a.id(), repo_path("file")),
+        content_a.as_bytes()
+    );
+
+    // Expired packed objects are dropped by gc
+    backend.compact(repo.index(), SystemTime::now()).unwrap();
+    assert_eq!(list_objects(), [0, 0, 0, 1]);
+    repo.store()
+        .gc(mut_index.as_index(), SystemTime::now())
+        .unwrap();
+    assert_eq!(list_objects(), [0, 0, 0, 1]);
+    let repo = load_repo();
+    assert!(repo.store().get_commit(commit_a.id()).is_err());
+    assert_eq!(
+        read_file_at(&repo, commit_b.id(), repo_path("file")),
+        content_b.as_bytes()
+    );
+    assert_eq!(
+        read_file_at(&repo, commit_b.id(), repo_path("same")),
+        b"same"
+    );
+}
+
+#[test]
+fn test_compact_detects_corrupt_pack() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Simple);
+    let store_path = test_repo.repo_path().join("store");
+    let repo = &test_repo.repo;
+
+    let tree = create_tree(repo, &[(repo_path("file"), "packed contents\n")]);
+    let mut tx = repo.start_transaction();
+    let commit = tx
+        .repo_mut()
+        .new_commit(vec![repo.store().root_commit_id().clone()], tree)
+        .write()
+        .unwrap();
+    let repo = tx.commit("test").unwrap();
+    let backend = repo.store().backend_impl::<SimpleBackend>().unwrap();
+    backend.compact(repo.index(), SystemTime::now()).unwrap();
+    let value = commit.tree().path_value(repo_path("file")).unwrap();
+    let Some(Some(TreeValue::File { id: file_id, .. })) = value.as_resolved() else {
+        panic!("unexpected value: {value:?}");
+    };
+
+    // Modified contents don't match the object id
+    let [pack_path] = list_dir(&store_path.join("packs")).try_into().unwrap();
+    let pack_path = store_path.join("packs").join(pack_path);
+    let mut pack = std::fs::read(&pack_path).unwrap();
+    let pos = pack
+        .windows(15)
+        .position(|w| w == b"packed contents")
+        .unwrap();
+    pack[pos] = b'P';
+    std::fs::write(&pack_path, &pack).unwrap();
+    let backend = SimpleBackend::load(repo.settings(), &store_path).unwrap();
+    assert_matches!(
+        backend
+            .read_file(repo_path("file"), file_id)
+            .block_on()
+            .map(|_| ()),
+        Err(BackendError::ReadObject { .. })
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_encryption() {
+    let settings_with_key = |key_command: &str| {
+        let mut config = base_user_config();
+        let text = format!("simple-backend.encryption-key-command = {key_command}");
+        config.add_layer(ConfigLayer::parse(ConfigSource::User, &text).unwrap());
+        UserSettings::from_config(config).unwrap()
+    };
+    let settings = settings_with_key(r#"["echo", "secret"]"#);
+    let temp_dir = testutils::new_temp_dir();
+    let store_path = temp_dir.path();
+    let backend = SimpleBackend::init_encrypted(&settings, store_path).unwrap();
+    assert!(backend.is_encrypted());
+
+    let contents = b"sensitive contents\n".repeat(10);
+    let file_id = backend
+        .write_file(repo_path("file"), &mut contents.as_slice())
+        .block_on()
+        .unwrap();
+    let symlink_id = backend
+        .write_symlink(repo_path("link"), "sensitive target")
+        .block_on()
+        .unwrap();
+    let stored_file = std::fs::read(store_path.join("files").join(file_id.hex())).unwrap();
+    assert!(!stored_file.windows(9).any(|w| w == b"sensitive"));
+    let stored_symlink = std::fs::read(store_path.join("symlinks").join(symlink_id.hex())).unwrap();
+    assert!(!stored_symlink.windows(9).any(|w| w == b"sensitive"));
+
+    // The contents are decrypted when read back
+    let backend = SimpleBackend::load(&settings, store_path).unwrap();
+    let mut reader = backend
+        .read_file(repo_path("file"), &file_id)
+        .block_on()
+        .unwrap();
+    let mut read_contents = vec![];
+    reader.read_to_end(&mut read_contents).block_on().unwrap();
+    assert_eq!(read_contents, contents);
+    assert_eq!(
+        backend
+            .read_symlink(repo_path("link"), &symlink_id)
+            .block_on()
+            .unwrap(),
+        "sensitive target"
+    );
+
+    // The repo can't be loaded with another key, or without one
+    assert_matches!(
+        SimpleBackend::load(&settings_with_key(r#"["echo", "other"]"#), store_path),
+        Err(SimpleBackendEncryptionError::WrongKey)
+    );
+    assert_matches!(
+        SimpleBackend::load(&testutils::user_settings(), store_path),
+        Err(SimpleBackendEncryptionError::KeyCommandNotSet)
+    );
+    assert_matches!(
+        SimpleBackend::load(&settings_with_key(r#"["false"]"#), store_path),
+        Err(SimpleBackendEncryptionError::KeyCommandFailed(_))
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_encryption_rejects_swapped_objects() {
+    let mut config = base_user_config();
+    config.add_layer(
+        ConfigLayer::parse(
+            ConfigSource::User,
+            r#"simple-backend.encryption-key-command = ["echo", "secret"]"#,
+        )
+        .unwrap(),
+    );
+    let settings = UserSettings::from_config(config).unwrap();
+    let temp_dir = testutils::new_temp_dir();
+    let store_path = temp_dir.path();
+    let backend = SimpleBackend::init_encrypted(&settings, store_path).unwrap();
+
+    let file_id1 = backend
+        .write_file(repo_path("file1"), &mut &b"contents 1"[..])
+        .block_on()
+        .unwrap();
+    let file_id2 = backend
+        .write_file(repo_path("file2"), &mut &b"contents 2"[..])
+        .block_on()
+        .unwrap();
+    let symlink_id = backend
+        .write_symlink(repo_path("link"), "target")
+        .block_on()
+        .unwrap();
+
+    // An object can't be passed off as another one by swapping the files
+    let file_path1 = store_path.join("files").join(file_id1.hex());
+    let file_path2 = store_path.join("files").join(file_id2.hex());
+    let symlink_path = store_path.join("symlinks").join(symlink_id.hex());
+    let stored_file1 = std::fs::read(&file_path1).unwrap();
+    let stored_file2 = std::fs::read(&file_path2).unwrap();
+    std::fs::write(&file_path1, &stored_file2).unwrap();
+    std::fs::write(&file_path2, &stored_file1).unwrap();
+    std::fs::write(&symlink_path, &stored_file1).unwrap();
+    assert_matches!(
+        backend
+            .read_file(repo_path("file1"), &file_id1)
+            .block_on()
+            .map(|_| ()),
+        Err(BackendError::ReadObject { .. })
+    );
+    assert_matches!(
+        backend
+            .read_file(repo_path("file2"), &file_id2)
+            .block_on()
+            .map(|_| ()),
+        Err(BackendError::ReadObject { .. })
+    );
+    assert_matches!(
+        backend
+            .read_symlink(repo_path("link"), &symlink_id)
+            .block_on(),
+        Err(BackendError::ReadObject { .. })
+    );
+}
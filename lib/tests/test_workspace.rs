@@ -23,6 +23,8 @@ use jj_lib::workspace::default_working_copy_factories;
 use jj_lib::workspace::default_working_copy_factory;
 use testutils::TestEnvironment;
 use testutils::TestWorkspace;
+use testutils::create_tree;
+use testutils::repo_path;
 
 #[test]
 fn test_load_bad_path() {
@@ -92,6 +94,64 @@ fn test_init_additional_workspace() {
     assert_eq!(same_workspace.workspace_root(), ws2.workspace_root());
 }
 
+#[test]
+fn test_init_ephemeral_workspace() {
+    let settings = testutils::user_settings();
+    let test_workspace = TestWorkspace::init_with_settings(&settings);
+    let repo = &test_workspace.repo;
+
+    let file_path = repo_path("file");
+    let mut tx = repo.start_transaction();
+    let tree = create_tree(repo, &[(file_path, "contents")]);
+    let parent = tx
+        .repo_mut()
+        .new_commit(vec![repo.store().root_commit_id().clone()], tree)
+        .write()
+        .unwrap();
+    let repo = tx.commit("test").unwrap();
+
+    let ws2_name = WorkspaceNameBuf::from("ws2");
+    let ws2_root = test_workspace.root_dir().join("ws2_root");
+    std::fs::create_dir(&ws2_root).unwrap();
+    let (_ws2, repo) = Workspace::init_ephemeral_workspace(
+        &ws2_root,
+        test_workspace.repo_path(),
+        &repo,
+        &*default_working_copy_factory(),
+        ws2_name.clone(),
+        &parent,
+    )
+    .unwrap();
+    assert!(repo.view().is_ephemeral_workspace(&ws2_name));
+    let wc_commit_id = repo.view().get_wc_commit_id(&ws2_name).unwrap();
+    let wc_commit = repo.store().get_commit(wc_commit_id).unwrap();
+    assert_eq!(wc_commit.parent_ids(), [parent.id().clone()]);
+    assert_eq!(
+        std::fs::read_to_string(ws2_root.join("file")).unwrap(),
+        "contents"
+    );
+
+    // The working-copy commit is abandoned when the workspace is forgotten
+    // even if it has changes.
+    let mut tx = repo.start_transaction();
+    let tree = create_tree(&repo, &[(file_path, "modified")]);
+    let wc_commit = tx
+        .repo_mut()
+        .rewrite_commit(&wc_commit)
+        .set_tree(tree)
+        .write()
+        .unwrap();
+    tx.repo_mut().rebase_descendants().unwrap();
+    let repo = tx.commit("test").unwrap();
+    let mut tx = repo.start_transaction();
+    tx.repo_mut().remove_wc_commit(&ws2_name).unwrap();
+    tx.repo_mut().rebase_descendants().unwrap();
+    let repo = tx.commit("test").unwrap();
+    assert!(!repo.view().heads().contains(wc_commit.id()));
+    assert!(repo.view().heads().contains(parent.id()));
+    assert!(repo.view().ephemeral_workspaces().is_empty());
+}
+
 #[cfg(unix)]
 #[test]
 fn test_init_additional_workspace_non_utf8_path() {
@@ -380,6 +380,36 @@ pub fn rename_remote(repo_dir: impl AsRef<Path>, original: &str, new: &str) {
     );
 }
 
+/// Moves the objects `ids` from loose object files to a new pack.
+pub fn pack_objects(git_dir: impl AsRef<Path>, ids: &[gix::ObjectId]) {
+    let git_dir = git_dir.as_ref();
+    let mut child = std::process::Command::new("git")
+        .arg("--git-dir")
+        .arg(git_dir)
+        .args(["pack-objects", "--quiet"])
+        .arg(git_dir.join("objects").join("pack").join("pack"))
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let stdin = ids.iter().map(|id| format!("{id}\n")).collect::<String>();
+    std::io::Write::write_all(&mut child.stdin.take().unwrap(), stdin.as_bytes()).unwrap();
+    assert!(child.wait().unwrap().success(), "git pack-objects failed");
+    let output = std::process::Command::new("git")
+        .arg("--git-dir")
+        .arg(git_dir)
+        .arg("prune-packed")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git prune-packed failed with {}:\n{}\n----- stderr -----\n{}",
+        output.status,
+        bstr::BString::from(output.stdout),
+        bstr::BString::from(output.stderr),
+    );
+}
+
 pub fn fetch(repo_dir: impl AsRef<Path>, remote: &str) {
     let output = std::process::Command::new("git")
         .current_dir(repo_dir)
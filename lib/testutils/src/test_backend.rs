@@ -373,6 +373,10 @@ impl Backend for TestBackend {
         .await
     }
 
+    fn compute_tree_id(&self, _path: &RepoPath, contents: &Tree) -> Option<TreeId> {
+        Some(TreeId::new(get_hash(contents)))
+    }
+
     async fn read_commit(&self, id: &CommitId) -> BackendResult<Commit> {
         if id == &self.root_commit_id {
             return Ok(make_root_commit(